@@ -0,0 +1,269 @@
+//! # Accessibility Linting
+//!
+//! Finds common accessibility problems in generated HTML, for
+//! `nucleusflow check --a11y`:
+//!
+//! - images without an `alt` attribute (an empty `alt` marks an image as
+//!   decorative and is accepted),
+//! - headings more than one level below the heading before them, such as
+//!   an `h4` straight after an `h2`,
+//! - links and buttons with no text, image `alt` text, `aria-label`,
+//!   `aria-labelledby` or `title` to name them, and
+//! - pages whose `<html>` element has no `lang` attribute.
+//!
+//! How each rule is reported is set in the `[a11y]` table of the
+//! configuration file (see [`crate::core::config::A11yConfig`]).
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::a11y::{lint, A11yRule};
+//!
+//! let findings = lint("<html lang=\"en\"><h1>Hi</h1><img src=\"a.png\">");
+//! assert_eq!(findings.len(), 1);
+//! assert_eq!(findings[0].rule, A11yRule::MissingAlt);
+//! ```
+
+use std::fmt;
+
+use html5ever::Attribute;
+
+use crate::core::config::{A11yConfig, A11ySeverity};
+use crate::generators::dom::{Document, NodeData, NodeId};
+
+/// An accessibility rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum A11yRule {
+    /// An image has no `alt` attribute
+    MissingAlt,
+    /// A heading skips a level
+    SkippedHeading,
+    /// A link has no accessible name
+    EmptyLink,
+    /// A button has no accessible name
+    EmptyButton,
+    /// The page does not declare its language
+    MissingLang,
+}
+
+impl A11yRule {
+    /// Returns how a configuration reports this rule.
+    pub fn severity(&self, config: &A11yConfig) -> A11ySeverity {
+        match self {
+            A11yRule::MissingAlt => config.missing_alt,
+            A11yRule::SkippedHeading => config.skipped_heading,
+            A11yRule::EmptyLink => config.empty_link,
+            A11yRule::EmptyButton => config.empty_button,
+            A11yRule::MissingLang => config.missing_lang,
+        }
+    }
+}
+
+impl fmt::Display for A11yRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            A11yRule::MissingAlt => "image without alt text",
+            A11yRule::SkippedHeading => "skipped heading level",
+            A11yRule::EmptyLink => "empty link",
+            A11yRule::EmptyButton => "empty button",
+            A11yRule::MissingLang => "missing lang attribute",
+        })
+    }
+}
+
+/// An accessibility problem found in a page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct A11yFinding {
+    /// The rule the page breaks
+    pub rule: A11yRule,
+    /// The element breaking it
+    pub details: String,
+}
+
+impl fmt::Display for A11yFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.rule, self.details)
+    }
+}
+
+/// Finds the accessibility problems in an HTML page, in document
+/// order.
+pub fn lint(html: &str) -> Vec<A11yFinding> {
+    let document = Document::parse(html);
+    let mut findings = Vec::new();
+    let mut previous_level: Option<u8> = None;
+    let mut stack: Vec<NodeId> = vec![document.root()];
+    while let Some(id) = stack.pop() {
+        let node = document.node(id);
+        stack.extend(node.children.iter().rev());
+        let (name, attrs) = match &node.data {
+            NodeData::Element { name, attrs, .. } => {
+                (&*name.local, attrs)
+            }
+            _ => continue,
+        };
+        let mut find = |rule, details: String| {
+            findings.push(A11yFinding { rule, details })
+        };
+
+        match name {
+            "html"
+                if attr(attrs, "lang").map_or(true, str::is_empty) =>
+            {
+                find(A11yRule::MissingLang, "<html>".to_string())
+            }
+            "img" if attr(attrs, "alt").is_none() => find(
+                A11yRule::MissingAlt,
+                format!(
+                    "<img src=\"{}\">",
+                    attr(attrs, "src").unwrap_or("")
+                ),
+            ),
+            "a" if attr(attrs, "href").is_some()
+                && !has_name(&document, id, attrs) =>
+            {
+                find(
+                    A11yRule::EmptyLink,
+                    format!(
+                        "<a href=\"{}\">",
+                        attr(attrs, "href").unwrap_or("")
+                    ),
+                )
+            }
+            "button" if !has_name(&document, id, attrs) => {
+                find(A11yRule::EmptyButton, "<button>".to_string())
+            }
+            _ => {}
+        }
+
+        if let Some(level) = heading_level(name) {
+            if let Some(previous) = previous_level {
+                if level > previous + 1 {
+                    find(
+                        A11yRule::SkippedHeading,
+                        format!("<h{}> after <h{}>", level, previous),
+                    );
+                }
+            }
+            previous_level = Some(level);
+        }
+    }
+    findings
+}
+
+/// Returns the value of an attribute.
+fn attr<'a>(attrs: &'a [Attribute], name: &str) -> Option<&'a str> {
+    attrs
+        .iter()
+        .find(|a| &*a.name.local == name)
+        .map(|a| &*a.value)
+}
+
+/// Returns the level of a heading element name.
+fn heading_level(name: &str) -> Option<u8> {
+    match name {
+        "h1" => Some(1),
+        "h2" => Some(2),
+        "h3" => Some(3),
+        "h4" => Some(4),
+        "h5" => Some(5),
+        "h6" => Some(6),
+        _ => None,
+    }
+}
+
+/// Returns `true` if an element has an accessible name: a label
+/// attribute, or text or image `alt` text within it.
+fn has_name(
+    document: &Document,
+    id: NodeId,
+    attrs: &[Attribute],
+) -> bool {
+    ["aria-label", "aria-labelledby", "title"]
+        .iter()
+        .any(|key| {
+            attr(attrs, key).map_or(false, |v| !v.trim().is_empty())
+        })
+        || has_content(document, id)
+}
+
+/// Returns `true` if a node holds text or image `alt` text.
+fn has_content(document: &Document, id: NodeId) -> bool {
+    let node = document.node(id);
+    let own = match &node.data {
+        NodeData::Text(text) => !text.trim().is_empty(),
+        NodeData::Element { name, attrs, .. } => {
+            &*name.local == "img"
+                && attr(attrs, "alt")
+                    .map_or(false, |alt| !alt.trim().is_empty())
+        }
+        _ => false,
+    };
+    own || node
+        .children
+        .iter()
+        .any(|&child| has_content(document, child))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(html: &str) -> Vec<A11yRule> {
+        lint(html).into_iter().map(|finding| finding.rule).collect()
+    }
+
+    #[test]
+    fn test_lint_accessible_page() {
+        let html = r#"<!DOCTYPE html><html lang="en"><body>
+            <h1>Title</h1><h2>Part</h2><h3>Section</h3><h2>Next</h2>
+            <img src="rule.png" alt="">
+            <a href="/"><img src="logo.png" alt="Home"></a>
+            <a href="/search" aria-label="Search"></a>
+            <button title="Close"></button><button>Send</button>
+            </body></html>"#;
+        assert_eq!(lint(html), []);
+    }
+
+    #[test]
+    fn test_lint_findings() {
+        let html = r#"<html><body>
+            <h1>Title</h1><h3>Skipped</h3>
+            <img src="photo.jpg">
+            <a href="/next"> </a><a name="anchor"></a>
+            <button><span></span></button>
+            </body></html>"#;
+        assert_eq!(
+            rules(html),
+            [
+                A11yRule::MissingLang,
+                A11yRule::SkippedHeading,
+                A11yRule::MissingAlt,
+                A11yRule::EmptyLink,
+                A11yRule::EmptyButton,
+            ]
+        );
+        let findings = lint(html);
+        assert_eq!(
+            findings[1].to_string(),
+            "skipped heading level: <h3> after <h1>"
+        );
+        assert_eq!(findings[3].details, "<a href=\"/next\">");
+    }
+
+    #[test]
+    fn test_rule_severity() {
+        let config = A11yConfig {
+            missing_lang: A11ySeverity::Off,
+            ..A11yConfig::default()
+        };
+        assert_eq!(
+            A11yRule::MissingLang.severity(&config),
+            A11ySeverity::Off
+        );
+        assert_eq!(
+            A11yRule::MissingAlt.severity(&config),
+            A11ySeverity::Error
+        );
+    }
+}
@@ -0,0 +1,268 @@
+//! # CI Annotations
+//!
+//! Formats the problems found by `nucleusflow check` for CI systems, so
+//! they appear inline on the content files of a pull or merge request:
+//!
+//! - `github` prints GitHub Actions workflow commands, one per line:
+//!   `::error file=content/post.md,line=3,title=broken link::'/gone'`.
+//!   Problems are errors and warnings are warnings.
+//! - `gitlab` prints a GitLab Code Quality report, a JSON array to save
+//!   as the job's `codequality` artifact. Problems are `major` and
+//!   warnings are `minor`.
+//!
+//! An annotation names the line of its content file that mentions the
+//! link or image at fault, when there is one, and the file as a whole
+//! otherwise.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::annotations::AnnotationFormat;
+//! use nucleusflow::check::{CheckReport, IssueKind};
+//!
+//! let mut report = CheckReport::new(1);
+//! report.push(IssueKind::MissingFrontmatter, "post.md", "`title` is not set");
+//! assert_eq!(
+//!     AnnotationFormat::Github.render(&report),
+//!     "::error file=post.md,title=missing frontmatter::`title` is not set\n"
+//! );
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::check::{CheckIssue, CheckReport};
+use crate::generators::fingerprint::content_hash;
+
+/// The CI systems annotations can be written for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnotationFormat {
+    /// GitHub Actions workflow commands
+    Github,
+    /// A GitLab Code Quality report
+    Gitlab,
+}
+
+impl AnnotationFormat {
+    /// Formats the problems and warnings of a check.
+    ///
+    /// # Arguments
+    /// * `report` - The report of the check.
+    ///
+    /// # Returns
+    /// * `String` - The annotations, ready to print.
+    pub fn render(&self, report: &CheckReport) -> String {
+        let mut lines = SourceLines::default();
+        let entries =
+            report.issues.iter().map(|issue| (issue, false)).chain(
+                report.warnings.iter().map(|issue| (issue, true)),
+            );
+        match self {
+            AnnotationFormat::Github => entries
+                .map(|(issue, warning)| {
+                    github_command(issue, warning, lines.find(issue))
+                })
+                .collect(),
+            AnnotationFormat::Gitlab => {
+                let issues: Vec<_> = entries
+                    .map(|(issue, warning)| {
+                        gitlab_issue(issue, warning, lines.find(issue))
+                    })
+                    .collect();
+                let mut json = serde_json::to_string_pretty(&issues)
+                    .unwrap_or_default();
+                json.push('\n');
+                json
+            }
+        }
+    }
+}
+
+impl fmt::Display for AnnotationFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            AnnotationFormat::Github => "github",
+            AnnotationFormat::Gitlab => "gitlab",
+        })
+    }
+}
+
+impl FromStr for AnnotationFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "github" => Ok(AnnotationFormat::Github),
+            "gitlab" => Ok(AnnotationFormat::Gitlab),
+            _ => Err(format!(
+                "unknown annotation format '{}', expected github or gitlab",
+                s
+            )),
+        }
+    }
+}
+
+/// Returns a GitHub Actions workflow command for a problem.
+fn github_command(
+    issue: &CheckIssue,
+    warning: bool,
+    line: Option<usize>,
+) -> String {
+    let mut properties =
+        format!("file={}", github_property(&issue.page.display()));
+    if let Some(line) = line {
+        properties.push_str(&format!(",line={}", line));
+    }
+    format!(
+        "::{} {},title={}::{}\n",
+        if warning { "warning" } else { "error" },
+        properties,
+        github_property(&issue.kind),
+        github_data(&issue.details)
+    )
+}
+
+/// Escapes the message of a workflow command.
+fn github_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escapes a property value of a workflow command.
+fn github_property(value: &dyn fmt::Display) -> String {
+    github_data(&value.to_string())
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
+
+/// Returns a GitLab Code Quality issue for a problem.
+fn gitlab_issue(
+    issue: &CheckIssue,
+    warning: bool,
+    line: Option<usize>,
+) -> serde_json::Value {
+    let path = issue.page.display().to_string();
+    let fingerprint = content_hash(
+        format!("{}\0{}\0{}", issue.kind, path, issue.details)
+            .as_bytes(),
+    );
+    serde_json::json!({
+        "description": format!("{}: {}", issue.kind, issue.details),
+        "check_name": issue.kind.to_string().replace(' ', "-"),
+        "fingerprint": fingerprint,
+        "severity": if warning { "minor" } else { "major" },
+        "location": {
+            "path": path,
+            "lines": { "begin": line.unwrap_or(1) },
+        },
+    })
+}
+
+/// The lines of content files, read as problems are located in them.
+#[derive(Debug, Default)]
+struct SourceLines {
+    files: BTreeMap<PathBuf, Option<String>>,
+}
+
+impl SourceLines {
+    /// Returns the first line of a problem's content file that contains
+    /// the quoted link or image the problem names, counting from 1.
+    fn find(&mut self, issue: &CheckIssue) -> Option<usize> {
+        let subject = quoted(&issue.details)?;
+        let content = self
+            .files
+            .entry(issue.page.clone())
+            .or_insert_with(|| fs::read_to_string(&issue.page).ok())
+            .as_deref()?;
+        content
+            .lines()
+            .position(|line| line.contains(subject))
+            .map(|index| index + 1)
+    }
+}
+
+/// Returns the first single-quoted part of a problem's details.
+fn quoted(details: &str) -> Option<&str> {
+    let start = details.find('\'')? + 1;
+    let end = start + details[start..].find('\'')?;
+    Some(&details[start..end]).filter(|subject| !subject.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::check::IssueKind;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    fn report(page: &Path) -> CheckReport {
+        let mut report = CheckReport::new(1);
+        report.push(IssueKind::BrokenLink, page, "'/gone.html'");
+        report.warnings.push(CheckIssue {
+            kind: IssueKind::Accessibility,
+            page: page.to_path_buf(),
+            details: "image without alt text: <img src=\"a,b.png\">"
+                .to_string(),
+        });
+        report
+    }
+
+    #[test]
+    fn test_github_annotations() {
+        let temp_dir = TempDir::new().unwrap();
+        let page = temp_dir.path().join("post.md");
+        fs::write(&page, "# Post\n\nSee [gone](/gone.html).\n")
+            .unwrap();
+
+        let output = AnnotationFormat::Github.render(&report(&page));
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(
+            lines[0],
+            format!(
+                "::error file={},line=3,title=broken link::'/gone.html'",
+                github_property(&page.display())
+            )
+        );
+        assert!(lines[1].starts_with("::warning file="));
+        assert!(!lines[1].contains("line="));
+        assert!(lines[1].ends_with(
+            "title=accessibility::image without alt text: <img src=\"a,b.png\">"
+        ));
+        assert_eq!(github_property(&"a:b,c%\n"), "a%3Ab%2Cc%25%0A");
+    }
+
+    #[test]
+    fn test_gitlab_annotations() {
+        let output = AnnotationFormat::Gitlab
+            .render(&report(Path::new("missing/post.md")));
+        let issues: serde_json::Value =
+            serde_json::from_str(&output).unwrap();
+        assert_eq!(issues[0]["check_name"], "broken-link");
+        assert_eq!(issues[0]["severity"], "major");
+        assert_eq!(issues[0]["location"]["path"], "missing/post.md");
+        assert_eq!(issues[0]["location"]["lines"]["begin"], 1);
+        assert_eq!(issues[1]["severity"], "minor");
+        assert_eq!(
+            issues[0]["fingerprint"].as_str().unwrap().len(),
+            64
+        );
+        assert_ne!(issues[0]["fingerprint"], issues[1]["fingerprint"]);
+
+        let empty =
+            AnnotationFormat::Gitlab.render(&CheckReport::new(0));
+        assert_eq!(empty, "[]\n");
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!("github".parse(), Ok(AnnotationFormat::Github));
+        assert_eq!("gitlab".parse(), Ok(AnnotationFormat::Gitlab));
+        assert!("jenkins".parse::<AnnotationFormat>().is_err());
+        assert_eq!(AnnotationFormat::Gitlab.to_string(), "gitlab");
+    }
+}
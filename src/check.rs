@@ -0,0 +1,776 @@
+//! # Site Health Checks
+//!
+//! Finds the problems that make a site look broken once it is published,
+//! without writing anything to the output directory. A check builds the
+//! whole site into a scratch directory, skipping pages that fail rather
+//! than stopping, and then reports:
+//!
+//! - links to pages or files the build does not produce,
+//! - fragment links (`#section`) to an element the target page does not
+//!   have,
+//! - images that are not produced,
+//! - pages that failed to render, such as those using a missing template
+//!   or partial,
+//! - content files that would be written to the same output path, and
+//! - pages missing frontmatter keys the site requires, and
+//! - when enabled, accessibility problems (see [`crate::a11y`]), as
+//!   problems or as warnings that do not fail the check.
+//!
+//! External links are collected as the pages are checked, and verified
+//! when the pipeline has an external link checker (see
+//! [`crate::linkcheck`]); their dead links are reported last.
+//!
+//! Only internal links are followed. A link is resolved against the URL
+//! of the page it appears on and must name a generated page, static file
+//! or shortlink; a link to a directory needs the directory's
+//! `index.html`. A fragment must match the `id` of an element in the
+//! target page, the `name` of one of its anchors, or the ID the table
+//! of contents gives one of its headings.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! # use nucleusflow::core::error::Result;
+//! # fn example(flow: nucleusflow::NucleusFlow) -> Result<()> {
+//! let report = flow.check()?;
+//! print!("{}", report);
+//! assert!(report.is_healthy());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::a11y::lint;
+use crate::core::config::{A11yConfig, A11ySeverity};
+use crate::core::error::BuildStage;
+use crate::generators::dom::{Document, NodeData, NodeId};
+use crate::linkcheck::LinkStatus;
+use crate::processors::markdown::heading_id;
+use crate::site::{Page, Site};
+use crate::ProcessingError;
+
+/// The kind of problem a check found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum IssueKind {
+    /// A link to a page or file the build does not produce
+    BrokenLink,
+    /// An image the build does not produce
+    MissingImage,
+    /// A page failed to render, usually because of a missing template
+    /// or partial
+    TemplateError,
+    /// Several content files produce the same output path
+    DuplicateOutput,
+    /// A page lacks a required frontmatter key
+    MissingFrontmatter,
+    /// A page failed at a stage other than rendering
+    PageFailed,
+    /// An external link that does not answer
+    DeadLink,
+    /// A link to a fragment the target page does not have
+    BrokenAnchor,
+    /// An accessibility problem in a rendered page
+    Accessibility,
+}
+
+impl fmt::Display for IssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            IssueKind::BrokenLink => "broken link",
+            IssueKind::MissingImage => "missing image",
+            IssueKind::TemplateError => "template error",
+            IssueKind::DuplicateOutput => "duplicate output",
+            IssueKind::MissingFrontmatter => "missing frontmatter",
+            IssueKind::PageFailed => "page failed",
+            IssueKind::DeadLink => "dead link",
+            IssueKind::BrokenAnchor => "broken anchor",
+            IssueKind::Accessibility => "accessibility",
+        })
+    }
+}
+
+/// A problem found by a check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckIssue {
+    /// What kind of problem it is
+    pub kind: IssueKind,
+    /// The content file the problem was found in
+    pub page: PathBuf,
+    /// A description of the problem
+    pub details: String,
+}
+
+/// The problems found by a check of a site.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CheckReport {
+    /// The number of pages that were checked
+    pub pages: usize,
+    /// Every problem found, in the order it was found
+    pub issues: Vec<CheckIssue>,
+    /// Problems reported without failing the check
+    pub warnings: Vec<CheckIssue>,
+    /// The `http` and `https` links found, with the content files
+    /// linking to them
+    pub external_links: BTreeMap<String, BTreeSet<PathBuf>>,
+}
+
+impl CheckReport {
+    /// Creates an empty report for a site with the given number of
+    /// pages.
+    pub fn new(pages: usize) -> Self {
+        Self {
+            pages,
+            ..Self::default()
+        }
+    }
+
+    /// Returns `true` if no problem was found.
+    pub fn is_healthy(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Returns the number of problems of a kind.
+    pub fn count(&self, kind: IssueKind) -> usize {
+        self.issues
+            .iter()
+            .filter(|issue| issue.kind == kind)
+            .count()
+    }
+
+    /// Records a problem.
+    pub fn push<P: AsRef<Path>>(
+        &mut self,
+        kind: IssueKind,
+        page: P,
+        details: impl Into<String>,
+    ) {
+        self.issues.push(CheckIssue {
+            kind,
+            page: page.as_ref().to_path_buf(),
+            details: details.into(),
+        });
+    }
+
+    /// Records a page that failed to build, as a template error if it
+    /// failed to render.
+    pub fn push_failure(
+        &mut self,
+        page: &Path,
+        stage: BuildStage,
+        error: &ProcessingError,
+    ) {
+        let kind = match stage {
+            BuildStage::Render => IssueKind::TemplateError,
+            _ => IssueKind::PageFailed,
+        };
+        self.push(
+            kind,
+            page,
+            format!("failed to {}: {}", stage, error),
+        );
+    }
+
+    /// Records the content files of a site that share an output path.
+    pub fn check_duplicates(&mut self, site: &Site) {
+        let mut outputs: BTreeMap<&Path, Vec<&Path>> = BTreeMap::new();
+        for page in &site.pages {
+            outputs.entry(&page.path).or_default().push(&page.source);
+        }
+        for (output, sources) in outputs {
+            if sources.len() < 2 {
+                continue;
+            }
+            for source in &sources {
+                let others: Vec<_> = sources
+                    .iter()
+                    .filter(|other| *other != source)
+                    .map(|other| other.display().to_string())
+                    .collect();
+                self.push(
+                    IssueKind::DuplicateOutput,
+                    source,
+                    format!(
+                        "'{}' is also generated from {}",
+                        output.display(),
+                        others.join(", ")
+                    ),
+                );
+            }
+        }
+    }
+
+    /// Records the pages of a site that lack required frontmatter keys.
+    ///
+    /// # Arguments
+    /// * `site` - The site to check.
+    /// * `required` - The keys every page must set.
+    pub fn check_frontmatter(
+        &mut self,
+        site: &Site,
+        required: &[String],
+    ) {
+        for page in &site.pages {
+            for key in required {
+                if !page.frontmatter.contains_key(key) {
+                    self.push(
+                        IssueKind::MissingFrontmatter,
+                        &page.source,
+                        format!("`{}` is not set", key),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Records the internal links and images of a rendered page that
+    /// do not resolve to a file in the output directory, or name a
+    /// fragment the file does not have, and collects its external
+    /// links.
+    ///
+    /// # Arguments
+    /// * `page` - The page the HTML was rendered from.
+    /// * `html` - The rendered page.
+    /// * `anchors` - The anchors of the output directory the site was
+    ///   built into.
+    pub fn check_links(
+        &mut self,
+        page: &Page,
+        html: &str,
+        anchors: &mut OutputAnchors,
+    ) {
+        let own_file = anchors.output_dir.join(&page.path);
+        anchors.add_page(&own_file, html);
+
+        for (kind, target) in page_links(html) {
+            if is_external(&target) {
+                _ = self
+                    .external_links
+                    .entry(target)
+                    .or_default()
+                    .insert(page.source.clone());
+                continue;
+            }
+            let fragment = target
+                .split_once('#')
+                .map(|(_, fragment)| percent_decode(fragment));
+            let file = if target.starts_with('#') {
+                own_file.clone()
+            } else if is_internal(&target) {
+                let file =
+                    resolve_link(&page.url, &target).map(|path| {
+                        output_file(&anchors.output_dir, &path)
+                    });
+                match file {
+                    Some(file) if file.is_file() => file,
+                    _ => {
+                        self.push(
+                            kind,
+                            &page.source,
+                            format!("'{}'", target),
+                        );
+                        continue;
+                    }
+                }
+            } else {
+                continue;
+            };
+            if let Some(fragment) = fragment {
+                if !anchors.contains(&file, &fragment) {
+                    self.push(
+                        IssueKind::BrokenAnchor,
+                        &page.source,
+                        format!("'{}'", target),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Records the accessibility problems of a rendered page, as
+    /// problems or warnings by the severity of the rule each breaks.
+    ///
+    /// # Arguments
+    /// * `page` - The page the HTML was rendered from.
+    /// * `html` - The rendered page.
+    /// * `config` - The severity of each rule.
+    pub fn check_accessibility(
+        &mut self,
+        page: &Page,
+        html: &str,
+        config: &A11yConfig,
+    ) {
+        for finding in lint(html) {
+            let issue = CheckIssue {
+                kind: IssueKind::Accessibility,
+                page: page.source.clone(),
+                details: finding.to_string(),
+            };
+            match finding.rule.severity(config) {
+                A11ySeverity::Error => self.issues.push(issue),
+                A11ySeverity::Warning => self.warnings.push(issue),
+                A11ySeverity::Off => {}
+            }
+        }
+    }
+
+    /// Records the external links that are dead, once for each page
+    /// using them.
+    pub fn push_dead_links(
+        &mut self,
+        dead: &BTreeMap<String, LinkStatus>,
+    ) {
+        for (url, status) in dead {
+            let pages = match self.external_links.get(url) {
+                Some(pages) => pages.clone(),
+                None => continue,
+            };
+            for page in pages {
+                self.push(
+                    IssueKind::DeadLink,
+                    page,
+                    format!("'{}' {}", url, status),
+                );
+            }
+        }
+    }
+}
+
+impl fmt::Display for CheckReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for issue in &self.issues {
+            writeln!(
+                f,
+                "{:<20} {}: {}",
+                issue.kind,
+                issue.page.display(),
+                issue.details
+            )?;
+        }
+        for warning in &self.warnings {
+            writeln!(
+                f,
+                "{:<20} {}: {}",
+                format!("{} warning", warning.kind),
+                warning.page.display(),
+                warning.details
+            )?;
+        }
+        write!(
+            f,
+            "{} pages checked, {} problems found",
+            self.pages,
+            self.issues.len()
+        )?;
+        if !self.warnings.is_empty() {
+            write!(f, ", {} warnings", self.warnings.len())?;
+        }
+        writeln!(f)
+    }
+}
+
+/// The fragments each HTML file of a build's output can be linked to,
+/// read as links to the file are checked.
+#[derive(Debug, Clone, Default)]
+pub struct OutputAnchors {
+    output_dir: PathBuf,
+    files: BTreeMap<PathBuf, BTreeSet<String>>,
+}
+
+impl OutputAnchors {
+    /// Creates an empty index of the output directory a site was built
+    /// into.
+    pub fn new<P: AsRef<Path>>(output_dir: P) -> Self {
+        Self {
+            output_dir: output_dir.as_ref().to_path_buf(),
+            files: BTreeMap::new(),
+        }
+    }
+
+    /// Returns `true` if a fragment names an element of a file, or the
+    /// file is not HTML. The empty fragment and `top` always match.
+    ///
+    /// # Arguments
+    /// * `file` - The output file the link resolves to.
+    /// * `fragment` - The decoded fragment, without the `#`.
+    pub fn contains(&mut self, file: &Path, fragment: &str) -> bool {
+        let is_html = file.extension().map_or(false, |ext| {
+            ext.eq_ignore_ascii_case("html")
+                || ext.eq_ignore_ascii_case("htm")
+        });
+        if !is_html
+            || fragment.is_empty()
+            || fragment.eq_ignore_ascii_case("top")
+        {
+            return true;
+        }
+        self.files
+            .entry(file.to_path_buf())
+            .or_insert_with(|| {
+                fs::read_to_string(file)
+                    .map(|html| page_anchors(&html))
+                    .unwrap_or_default()
+            })
+            .contains(fragment)
+    }
+
+    /// Indexes a file from HTML already in memory.
+    fn add_page(&mut self, file: &Path, html: &str) {
+        _ = self
+            .files
+            .entry(file.to_path_buf())
+            .or_insert_with(|| page_anchors(html));
+    }
+}
+
+/// Returns the fragments an HTML page can be linked to: the `id` of
+/// every element, the `name` of every anchor, and the table of contents
+/// ID of every heading without an `id`.
+fn page_anchors(html: &str) -> BTreeSet<String> {
+    let document = Document::parse(html);
+    let mut anchors = BTreeSet::new();
+    let mut stack: Vec<NodeId> = vec![document.root()];
+    while let Some(id) = stack.pop() {
+        let node = document.node(id);
+        if let NodeData::Element { name, attrs, .. } = &node.data {
+            let attr = |key: &str| {
+                attrs
+                    .iter()
+                    .find(|a| &*a.name.local == key)
+                    .map(|a| a.value.to_string())
+            };
+            let is_heading = matches!(
+                &*name.local,
+                "h1" | "h2" | "h3" | "h4" | "h5" | "h6"
+            );
+            match attr("id") {
+                Some(value) => _ = anchors.insert(value),
+                None if is_heading => {
+                    let mut text = String::new();
+                    push_text(&document, id, &mut text);
+                    _ = anchors.insert(heading_id(&text));
+                }
+                None => {}
+            }
+            if &*name.local == "a" {
+                anchors.extend(attr("name"));
+            }
+        }
+        stack.extend(node.children.iter().rev());
+    }
+    anchors
+}
+
+/// Appends the text within a node to `text`.
+fn push_text(document: &Document, id: NodeId, text: &mut String) {
+    let node = document.node(id);
+    if let NodeData::Text(content) = &node.data {
+        text.push_str(content);
+    }
+    for &child in &node.children {
+        push_text(document, child, text);
+    }
+}
+
+/// Returns the link and image targets of an HTML page, in document
+/// order.
+pub(crate) fn page_links(html: &str) -> Vec<(IssueKind, String)> {
+    let document = Document::parse(html);
+    let mut links = Vec::new();
+    let mut stack: Vec<NodeId> = vec![document.root()];
+    while let Some(id) = stack.pop() {
+        let node = document.node(id);
+        if let NodeData::Element { name, attrs, .. } = &node.data {
+            let (kind, attr) = match &*name.local {
+                "a" | "area" | "link" => {
+                    (IssueKind::BrokenLink, "href")
+                }
+                "img" => (IssueKind::MissingImage, "src"),
+                "audio" | "iframe" | "script" | "source" | "video" => {
+                    (IssueKind::BrokenLink, "src")
+                }
+                _ => (IssueKind::BrokenLink, ""),
+            };
+            links.extend(
+                attrs
+                    .iter()
+                    .filter(|a| {
+                        !attr.is_empty() && &*a.name.local == attr
+                    })
+                    .map(|a| (kind, a.value.trim().to_string())),
+            );
+        }
+        stack.extend(node.children.iter().rev());
+    }
+    links
+}
+
+/// Returns `true` if a link target is an `http` or `https` URL.
+fn is_external(target: &str) -> bool {
+    let scheme = target.split_once("://").map(|(scheme, _)| scheme);
+    scheme.map_or(false, |scheme| {
+        scheme.eq_ignore_ascii_case("http")
+            || scheme.eq_ignore_ascii_case("https")
+    })
+}
+
+/// Returns `true` if a link target points into the site: it has no
+/// scheme or host and names more than a fragment or query.
+pub(crate) fn is_internal(target: &str) -> bool {
+    if target.is_empty()
+        || target.starts_with('#')
+        || target.starts_with('?')
+        || target.starts_with("//")
+    {
+        return false;
+    }
+    let end = target.find(['/', '?', '#']).unwrap_or(target.len());
+    match target[..end].split_once(':') {
+        Some((scheme, _)) => {
+            !scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        }
+        None => true,
+    }
+}
+
+/// Resolves an internal link against the URL of the page it is on.
+///
+/// # Returns
+/// * `Option<String>` - The output-relative path the link names, ending
+///   in `/` for a directory, or `None` if it climbs above the site root.
+pub(crate) fn resolve_link(
+    page_url: &str,
+    target: &str,
+) -> Option<String> {
+    let end = target.find(['?', '#']).unwrap_or(target.len());
+    let path = percent_decode(&target[..end]);
+    let joined = match path.strip_prefix('/') {
+        Some(absolute) => absolute.to_string(),
+        None => {
+            let base = page_url
+                .rsplit_once('/')
+                .map_or("", |(dir, _)| dir)
+                .trim_start_matches('/');
+            format!("{}/{}", base, path)
+        }
+    };
+
+    let mut segments: Vec<&str> = Vec::new();
+    let parts: Vec<&str> = joined.split('/').collect();
+    for (i, part) in parts.iter().enumerate() {
+        let last = i + 1 == parts.len();
+        match *part {
+            ".." => {
+                _ = segments.pop()?;
+                if last {
+                    segments.push("");
+                }
+            }
+            "." | "" if last => segments.push(""),
+            "." | "" => {}
+            part => segments.push(part),
+        }
+    }
+    Some(segments.join("/"))
+}
+
+/// Returns the file a resolved link is served from: the path itself, or
+/// `index.html` within it for a directory.
+fn output_file(output_dir: &Path, path: &str) -> PathBuf {
+    let file = output_dir.join(path);
+    if path.is_empty() || path.ends_with('/') || file.is_dir() {
+        file.join("index.html")
+    } else {
+        file
+    }
+}
+
+/// Decodes `%XX` escapes in a URL path, leaving invalid ones as they
+/// are.
+pub(crate) fn percent_decode(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escape = (bytes[i] == b'%')
+            .then(|| path.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escape {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Map};
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_is_internal() {
+        for internal in ["/a.html", "a.html", "../a", "./"] {
+            assert!(is_internal(internal), "{}", internal);
+        }
+        for external in [
+            "",
+            "#top",
+            "?page=2",
+            "//cdn.example.com/a.js",
+            "https://example.com",
+            "mailto:me@example.com",
+            "data:image/png;base64,AA",
+        ] {
+            assert!(!is_internal(external), "{}", external);
+        }
+    }
+
+    #[test]
+    fn test_resolve_link() {
+        let resolve = |url, target| resolve_link(url, target);
+        assert_eq!(
+            resolve("/", "about.html"),
+            Some("about.html".into())
+        );
+        assert_eq!(
+            resolve("/blog/post.html", "../img/a%20b.png#x"),
+            Some("img/a b.png".into())
+        );
+        assert_eq!(resolve("/blog/", "./"), Some("blog/".into()));
+        assert_eq!(resolve("/blog/post.html", "/"), Some("".into()));
+        assert_eq!(resolve("/a.html", "../b.html"), None);
+    }
+
+    #[test]
+    fn test_check_links() {
+        let temp_dir = TempDir::new().unwrap();
+        let output = temp_dir.path();
+        fs::create_dir_all(output.join("docs")).unwrap();
+        fs::write(output.join("docs/index.html"), "").unwrap();
+        fs::write(output.join("logo.png"), "").unwrap();
+
+        let html = r##"<a href="/docs">Docs</a>
+            <a href="missing.html#x">Gone</a>
+            <a href="#top">Top</a>
+            <a href="https://example.com">Out</a>
+            <img src="logo.png"><img src="/photo.jpg">"##;
+        let mut report = CheckReport::new(1);
+        report.check_links(
+            &Page::new("content/index.md", "index.html", Map::new()),
+            html,
+            &mut OutputAnchors::new(output),
+        );
+        assert_eq!(report.count(IssueKind::BrokenLink), 1);
+        assert_eq!(report.count(IssueKind::MissingImage), 1);
+        assert_eq!(report.issues[0].details, "'missing.html#x'");
+        assert_eq!(report.issues[1].details, "'/photo.jpg'");
+        assert_eq!(
+            report.external_links.keys().collect::<Vec<_>>(),
+            ["https://example.com"]
+        );
+
+        let dead = BTreeMap::from([(
+            "https://example.com".to_string(),
+            LinkStatus::Dead(404),
+        )]);
+        report.push_dead_links(&dead);
+        assert_eq!(report.count(IssueKind::DeadLink), 1);
+        assert_eq!(
+            report.issues[2].details,
+            "'https://example.com' answered with status 404"
+        );
+    }
+
+    #[test]
+    fn test_check_anchors() {
+        let temp_dir = TempDir::new().unwrap();
+        let output = temp_dir.path();
+        fs::write(
+            output.join("guide.html"),
+            r#"<h2>Getting Started!</h2><h3 id="api">API</h3>
+            <a name="legacy"></a>"#,
+        )
+        .unwrap();
+        fs::write(output.join("manual.pdf"), "").unwrap();
+
+        let html = r##"<p id="intro">Intro</p>
+            <a href="#intro">Intro</a>
+            <a href="#top">Top</a>
+            <a href="#outro">Outro</a>
+            <a href="guide.html#getting-started">Start</a>
+            <a href="guide.html#api">API</a>
+            <a href="guide.html#legacy">Legacy</a>
+            <a href="guide.html#gone">Gone</a>
+            <a href="manual.pdf#page=2">Manual</a>"##;
+        let mut report = CheckReport::new(1);
+        report.check_links(
+            &Page::new("content/index.md", "index.html", Map::new()),
+            html,
+            &mut OutputAnchors::new(output),
+        );
+        assert_eq!(report.count(IssueKind::BrokenAnchor), 2);
+        assert_eq!(report.issues[0].details, "'#outro'");
+        assert_eq!(report.issues[1].details, "'guide.html#gone'");
+        assert_eq!(report.count(IssueKind::BrokenLink), 0);
+    }
+
+    #[test]
+    fn test_check_accessibility() {
+        let html =
+            r#"<html><h1>A</h1><h3>B</h3><img src="a.png"></html>"#;
+        let config = A11yConfig {
+            missing_lang: A11ySeverity::Off,
+            ..A11yConfig::default()
+        };
+        let mut report = CheckReport::new(1);
+        report.check_accessibility(
+            &Page::new("content/index.md", "index.html", Map::new()),
+            html,
+            &config,
+        );
+        assert_eq!(report.count(IssueKind::Accessibility), 1);
+        assert_eq!(
+            report.issues[0].details,
+            "image without alt text: <img src=\"a.png\">"
+        );
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.to_string().ends_with(
+            "1 pages checked, 1 problems found, 1 warnings\n"
+        ));
+    }
+
+    #[test]
+    fn test_check_duplicates_and_frontmatter() {
+        let mut titled =
+            Page::new("content/a.md", "a.html", Map::new());
+        _ = titled.frontmatter.insert("title".into(), json!("A"));
+        let site = Site::new(vec![
+            titled,
+            Page::new("content/a.html", "a.html", Map::new()),
+            Page::new("content/b.md", "b.html", Map::new()),
+        ]);
+
+        let mut report = CheckReport::new(site.pages.len());
+        report.check_duplicates(&site);
+        report.check_frontmatter(&site, &["title".to_string()]);
+        assert_eq!(report.count(IssueKind::DuplicateOutput), 2);
+        assert_eq!(report.count(IssueKind::MissingFrontmatter), 2);
+        assert!(!report.is_healthy());
+        assert!(report
+            .to_string()
+            .ends_with("3 pages checked, 4 problems found\n"));
+    }
+}
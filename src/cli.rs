@@ -28,9 +28,9 @@
 
 use crate::core::error::{ProcessingError, Result};
 use clap::{value_parser, Arg, ArgAction, Command};
-use log::{debug, info};
 use std::fs;
 use std::path::PathBuf;
+use tracing::{debug, info};
 
 /// The current version of NucleusFlow, as defined in `Cargo.toml`.
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
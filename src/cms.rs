@@ -0,0 +1,479 @@
+//! # Headless CMS
+//!
+//! Fetches the entries of headless CMS endpoints before a build and
+//! writes each as a content file, so pages edited in a CMS go through
+//! the same pipeline as those written by hand. Each endpoint is a
+//! [`CmsSource`], read from the `[[cms]]` tables of the configuration.
+//!
+//! A REST endpoint is fetched with a `GET` request; a GraphQL endpoint
+//! is sent its `query`, and fails if it answers with `errors`. The
+//! entries are the list found at the source's `items` JSON pointer, and
+//! each is written to the content directory as:
+//!
+//! - a file named after the slug of the value at its `slug` pointer,
+//!   with the `.md` or `.html` extension of the source's format,
+//! - frontmatter holding the value at each of the source's `fields`
+//!   pointers, keyed by frontmatter key, and the source's name as
+//!   `cms`, and
+//! - a body holding the value at its `body` pointer.
+//!
+//! Each fetch replaces the files the source wrote before, which are
+//! known by their `cms` frontmatter, so an entry removed from the CMS
+//! is removed from the site. A file written by hand, or by another
+//! source, is never overwritten: writing over it fails instead.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::cms::HeadlessCms;
+//! use nucleusflow::core::config::{CmsFormat, CmsSource};
+//! use nucleusflow::core::error::Result;
+//! use nucleusflow::import::Fetch;
+//! use std::sync::Arc;
+//!
+//! #[derive(Debug)]
+//! struct Api;
+//!
+//! impl Fetch for Api {
+//!     fn fetch(&self, _url: &str) -> Result<Vec<u8>> {
+//!         let json = r#"[{"slug": "hello", "title": "Hello",
+//!                         "body": "Hi!"}]"#;
+//!         Ok(json.as_bytes().to_vec())
+//!     }
+//! }
+//!
+//! let source = CmsSource {
+//!     name: "blog".to_string(),
+//!     url: "https://cms.example.com/posts".to_string(),
+//!     query: None,
+//!     headers: Default::default(),
+//!     items: String::new(),
+//!     slug: "/slug".to_string(),
+//!     body: "/body".to_string(),
+//!     format: CmsFormat::Markdown,
+//!     fields: [("title".to_string(), "/title".to_string())]
+//!         .into_iter()
+//!         .collect(),
+//! };
+//! let dir = tempfile::tempdir().unwrap();
+//! let written = HeadlessCms::new(vec![source], Arc::new(Api))
+//!     .materialise(dir.path())
+//!     .unwrap();
+//! assert_eq!(
+//!     std::fs::read_to_string(&written[0]).unwrap(),
+//!     "---\ncms: blog\ntitle: Hello\n---\nHi!\n"
+//! );
+//! ```
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde_json::{json, Map, Value as JsonValue};
+
+use crate::core::config::{CmsFormat, CmsSource};
+use crate::import::{slugify, Fetch};
+use crate::processors::markdown::MarkdownProcessor;
+use crate::{ProcessingError, Result};
+
+/// The frontmatter key naming the source that wrote a content file.
+pub const CMS_KEY: &str = "cms";
+
+/// A CMS entry, ready to be written as a content file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CmsPage {
+    /// The file's name, without its extension
+    pub slug: String,
+
+    /// The file's frontmatter
+    pub frontmatter: Map<String, JsonValue>,
+
+    /// The file's Markdown or HTML content
+    pub body: String,
+}
+
+impl CmsPage {
+    /// Returns the content file of the entry: its frontmatter and body.
+    ///
+    /// # Returns
+    /// * `Result<String>` - The file, or a serialization error if the
+    ///   frontmatter cannot be written as YAML.
+    pub fn file(&self) -> Result<String> {
+        let yaml =
+            serde_yml::to_string(&self.frontmatter).map_err(|e| {
+                ProcessingError::serialization(
+                    "Failed to write CMS frontmatter",
+                    Some(Box::new(e)),
+                )
+            })?;
+        Ok(format!("---\n{}---\n{}\n", yaml, self.body.trim()))
+    }
+}
+
+/// Reads the entries of a CMS response.
+///
+/// # Arguments
+/// * `source` - Where entries and their values are in the response.
+/// * `response` - The endpoint's JSON response.
+///
+/// # Returns
+/// * `Result<Vec<CmsPage>>` - The entries, in response order, or a
+///   content processing error if a GraphQL query failed or there is no
+///   list of entries where the source says.
+pub fn cms_pages(
+    source: &CmsSource,
+    response: &JsonValue,
+) -> Result<Vec<CmsPage>> {
+    let failed = |details: String| {
+        ProcessingError::content_processing(
+            format!("CMS source '{}' {}", source.name, details),
+            None,
+        )
+    };
+    if let Some(errors) = source
+        .query
+        .as_ref()
+        .and_then(|_| response.get("errors"))
+        .and_then(JsonValue::as_array)
+        .filter(|errors| !errors.is_empty())
+    {
+        let messages: Vec<String> = errors
+            .iter()
+            .map(|error| match error.get("message") {
+                Some(JsonValue::String(message)) => message.clone(),
+                _ => error.to_string(),
+            })
+            .collect();
+        return Err(failed(format!(
+            "answered with errors: {}",
+            messages.join("; ")
+        )));
+    }
+    let items = response
+        .pointer(&source.items)
+        .and_then(JsonValue::as_array)
+        .ok_or_else(|| {
+            failed(format!(
+                "has no list of entries at '{}'",
+                source.items
+            ))
+        })?;
+
+    let mut slugs = BTreeSet::new();
+    let mut pages = Vec::new();
+    for (index, item) in items.iter().enumerate() {
+        let mut slug = match item.pointer(&source.slug) {
+            Some(JsonValue::String(slug)) => slugify(slug),
+            Some(JsonValue::Number(slug)) => slug.to_string(),
+            _ => String::new(),
+        };
+        if slug.is_empty() {
+            slug = format!("{}-{}", slugify(&source.name), index + 1);
+        }
+        let base = slug.clone();
+        let mut n = 1;
+        while !slugs.insert(slug.clone()) {
+            n += 1;
+            slug = format!("{}-{}", base, n);
+        }
+
+        let mut frontmatter = Map::new();
+        for (key, pointer) in &source.fields {
+            if let Some(value) =
+                item.pointer(pointer).filter(|value| !value.is_null())
+            {
+                _ = frontmatter.insert(key.clone(), value.clone());
+            }
+        }
+        _ = frontmatter
+            .insert(CMS_KEY.to_string(), source.name.clone().into());
+        let body = item
+            .pointer(&source.body)
+            .and_then(JsonValue::as_str)
+            .unwrap_or_default()
+            .to_string();
+        pages.push(CmsPage {
+            slug,
+            frontmatter,
+            body,
+        });
+    }
+    Ok(pages)
+}
+
+/// The headless CMS endpoints a site's content is fetched from.
+#[derive(Debug, Clone)]
+pub struct HeadlessCms {
+    sources: Vec<CmsSource>,
+    fetcher: Arc<dyn Fetch>,
+}
+
+impl HeadlessCms {
+    /// Creates the endpoints of a site, fetched through `fetcher`.
+    pub fn new(
+        sources: Vec<CmsSource>,
+        fetcher: Arc<dyn Fetch>,
+    ) -> Self {
+        Self { sources, fetcher }
+    }
+
+    /// Fetches the entries of a source.
+    ///
+    /// # Returns
+    /// * `Result<Vec<CmsPage>>` - The entries, or an error if the
+    ///   endpoint cannot be fetched or does not answer with entries.
+    pub fn fetch(&self, source: &CmsSource) -> Result<Vec<CmsPage>> {
+        let query = source
+            .query
+            .as_ref()
+            .map(|query| json!({ "query": query }));
+        let bytes = self.fetcher.request(
+            &source.url,
+            &source.headers,
+            query.as_ref(),
+        )?;
+        let response: JsonValue = serde_json::from_slice(&bytes)
+            .map_err(|e| {
+                ProcessingError::serialization(
+                    format!(
+                        "CMS source '{}' did not answer with JSON",
+                        source.name
+                    ),
+                    Some(Box::new(e)),
+                )
+            })?;
+        cms_pages(source, &response)
+    }
+
+    /// Fetches every source and writes its entries to a content
+    /// directory, replacing the files it wrote before.
+    ///
+    /// # Arguments
+    /// * `content_dir` - The content directory.
+    ///
+    /// # Returns
+    /// * `Result<Vec<PathBuf>>` - The files written, or an error if a
+    ///   source cannot be fetched, an entry would overwrite a file the
+    ///   source did not write, or a file cannot be written.
+    pub fn materialise(
+        &self,
+        content_dir: &Path,
+    ) -> Result<Vec<PathBuf>> {
+        fs::create_dir_all(content_dir).map_err(|e| {
+            ProcessingError::io_error(content_dir.to_path_buf(), e)
+        })?;
+        let mut written = Vec::new();
+        for source in &self.sources {
+            let pages = self.fetch(source)?;
+            let previous = source_files(content_dir, &source.name)?;
+            let extension = match source.format {
+                CmsFormat::Markdown => "md",
+                CmsFormat::Html => "html",
+            };
+
+            let mut current = BTreeSet::new();
+            for page in &pages {
+                let path = content_dir
+                    .join(format!("{}.{}", page.slug, extension));
+                if path.exists() && !previous.contains(&path) {
+                    return Err(ProcessingError::validation(
+                        format!(
+                            "CMS source '{}' would overwrite {}, which \
+                             it did not write",
+                            source.name,
+                            path.display()
+                        ),
+                        None::<String>,
+                    ));
+                }
+                fs::write(&path, page.file()?).map_err(|e| {
+                    ProcessingError::io_error(path.clone(), e)
+                })?;
+                _ = current.insert(path.clone());
+                written.push(path);
+            }
+            for stale in previous.difference(&current) {
+                fs::remove_file(stale).map_err(|e| {
+                    ProcessingError::io_error(stale.clone(), e)
+                })?;
+            }
+        }
+        Ok(written)
+    }
+}
+
+/// Returns the content files a source wrote, by their `cms`
+/// frontmatter.
+fn source_files(
+    content_dir: &Path,
+    name: &str,
+) -> Result<BTreeSet<PathBuf>> {
+    let mut files = BTreeSet::new();
+    let entries = fs::read_dir(content_dir).map_err(|e| {
+        ProcessingError::io_error(content_dir.to_path_buf(), e)
+    })?;
+    for entry in entries {
+        let path = entry
+            .map_err(|e| {
+                ProcessingError::io_error(content_dir.to_path_buf(), e)
+            })?
+            .path();
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        if MarkdownProcessor::parse_frontmatter(&content)
+            .get(CMS_KEY)
+            .and_then(JsonValue::as_str)
+            == Some(name)
+        {
+            _ = files.insert(path);
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use tempfile::TempDir;
+
+    #[derive(Debug)]
+    struct Canned(&'static str);
+
+    impl Fetch for Canned {
+        fn fetch(&self, _url: &str) -> Result<Vec<u8>> {
+            Ok(self.0.as_bytes().to_vec())
+        }
+    }
+
+    fn source(name: &str) -> CmsSource {
+        CmsSource {
+            name: name.to_string(),
+            url: "https://cms.example.com/api".to_string(),
+            query: None,
+            headers: BTreeMap::new(),
+            items: "/posts".to_string(),
+            slug: "/slug".to_string(),
+            body: "/content/html".to_string(),
+            format: CmsFormat::Html,
+            fields: [
+                ("title".to_string(), "/title".to_string()),
+                ("date".to_string(), "/meta/published".to_string()),
+            ]
+            .into_iter()
+            .collect(),
+        }
+    }
+
+    #[test]
+    fn test_cms_pages() -> Result<()> {
+        let response = json!({ "posts": [
+            {
+                "slug": "Hello World",
+                "title": "Hello",
+                "meta": { "published": "2024-05-01" },
+                "content": { "html": "<p>Hi</p>" },
+            },
+            { "slug": "hello-world", "title": null },
+            { "slug": 7 },
+            { "title": "No slug" },
+        ]});
+        let pages = cms_pages(&source("blog"), &response)?;
+        let slugs: Vec<&str> =
+            pages.iter().map(|page| page.slug.as_str()).collect();
+        assert_eq!(
+            slugs,
+            ["hello-world", "hello-world-2", "7", "blog-4"]
+        );
+        assert_eq!(
+            JsonValue::Object(pages[0].frontmatter.clone()),
+            json!({
+                "title": "Hello",
+                "date": "2024-05-01",
+                "cms": "blog",
+            })
+        );
+        assert_eq!(pages[0].body, "<p>Hi</p>");
+        assert_eq!(
+            JsonValue::Object(pages[1].frontmatter.clone()),
+            json!({ "cms": "blog" })
+        );
+
+        assert!(cms_pages(&source("blog"), &json!({ "data": [] }))
+            .unwrap_err()
+            .to_string()
+            .contains("has no list of entries at '/posts'"));
+        let graphql = CmsSource {
+            query: Some("{ posts { slug } }".to_string()),
+            ..source("blog")
+        };
+        let errors =
+            json!({ "errors": [{ "message": "Unauthorized" }] });
+        assert!(cms_pages(&graphql, &errors)
+            .unwrap_err()
+            .to_string()
+            .contains("answered with errors: Unauthorized"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_materialise() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_dir = temp_dir.path();
+        let cms = |response: &'static str| {
+            HeadlessCms::new(
+                vec![source("blog")],
+                Arc::new(Canned(response)),
+            )
+        };
+
+        let response = r#"{"posts": [{"slug": "a", "title": "A"},
+                                     {"slug": "b"}]}"#;
+        let written = cms(response).materialise(content_dir)?;
+        assert_eq!(
+            written,
+            [content_dir.join("a.html"), content_dir.join("b.html")]
+        );
+        assert_eq!(
+            fs::read_to_string(content_dir.join("a.html"))?,
+            "---\ncms: blog\ntitle: A\n---\n\n"
+        );
+
+        fs::write(content_dir.join("about.md"), "About")?;
+        _ = cms(r#"{"posts": [{"slug": "a", "title": "New"}]}"#)
+            .materialise(content_dir)?;
+        assert!(fs::read_to_string(content_dir.join("a.html"))?
+            .contains("title: New"));
+        assert!(!content_dir.join("b.html").exists());
+        assert!(content_dir.join("about.md").exists());
+
+        fs::write(content_dir.join("c.html"), "By hand")?;
+        assert!(cms(r#"{"posts": [{"slug": "c"}]}"#)
+            .materialise(content_dir)
+            .unwrap_err()
+            .to_string()
+            .contains("which it did not write"));
+        assert_eq!(
+            fs::read_to_string(content_dir.join("c.html"))?,
+            "By hand"
+        );
+
+        assert!(cms("<html>").materialise(content_dir).is_err());
+        let graphql = CmsSource {
+            query: Some("{ posts { slug } }".to_string()),
+            ..source("blog")
+        };
+        assert!(HeadlessCms::new(
+            vec![graphql],
+            Arc::new(Canned("{}"))
+        )
+        .materialise(content_dir)
+        .unwrap_err()
+        .to_string()
+        .contains("not supported"));
+        Ok(())
+    }
+}
@@ -0,0 +1,361 @@
+//! # Build Comparison
+//!
+//! Compares two builds of a site, such as the output of the main branch
+//! and of a pull request, to review the impact of a large content or
+//! template refactor before it is deployed. A comparison reports:
+//!
+//! - the pages added, removed and changed, with their size deltas,
+//! - the other output files added, removed and changed, and
+//! - for every page in both builds, the internal links it gained and
+//!   lost, so changes to the site's link graph stand out.
+//!
+//! Two output directories give the full comparison. Two page manifests
+//! (`pages-manifest.json`) only list pages, so comparing them reports
+//! the pages added and removed.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use nucleusflow::compare::BuildComparison;
+//! use std::path::Path;
+//!
+//! let comparison =
+//!     BuildComparison::compare(Path::new("main"), Path::new("public"))
+//!         .unwrap();
+//! print!("{}", comparison);
+//! ```
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::check::{is_internal, page_links, resolve_link};
+use crate::generators::preview::{
+    BuildPreview, ChangeKind, FileChange,
+};
+use crate::generators::tombstones::{manifest_path, PageManifest};
+use crate::{ProcessingError, Result};
+
+/// The internal links that changed on a page in both builds.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkChange {
+    /// The page's path relative to the output directory
+    pub page: PathBuf,
+    /// Output paths the page links to only in the second build
+    pub added: BTreeSet<String>,
+    /// Output paths the page links to only in the first build
+    pub removed: BTreeSet<String>,
+}
+
+/// The differences between two builds of a site.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuildComparison {
+    /// The output files that differ, ordered by path
+    pub files: BuildPreview,
+    /// The link changes of pages in both builds, ordered by page
+    pub links: Vec<LinkChange>,
+}
+
+impl BuildComparison {
+    /// Compares two output directories, or two page manifests.
+    ///
+    /// # Arguments
+    /// * `old` - The first build's output directory or manifest.
+    /// * `new` - The second build's output directory or manifest.
+    ///
+    /// # Returns
+    /// * `Result<Self>` - The differences, or an error if the builds
+    ///   cannot be read or are not both directories or both manifests.
+    pub fn compare(old: &Path, new: &Path) -> Result<Self> {
+        if old.is_dir() && new.is_dir() {
+            Self::compare_dirs(old, new)
+        } else if old.is_file() && new.is_file() {
+            Ok(Self::compare_manifests(
+                &PageManifest::read_file(old)?,
+                &PageManifest::read_file(new)?,
+            ))
+        } else {
+            Err(ProcessingError::content_processing(
+                format!(
+                    "Cannot compare '{}' with '{}': expected two output \
+                     directories or two page manifests",
+                    old.display(),
+                    new.display()
+                ),
+                None,
+            ))
+        }
+    }
+
+    /// Compares two output directories.
+    ///
+    /// # Arguments
+    /// * `old` - The first build's output directory.
+    /// * `new` - The second build's output directory.
+    ///
+    /// # Returns
+    /// * `Result<Self>` - The differences, or an error if either
+    ///   directory cannot be read.
+    pub fn compare_dirs(old: &Path, new: &Path) -> Result<Self> {
+        let files = BuildPreview::compare(old, new)?;
+        let mut links = Vec::new();
+        for change in &files.changes {
+            if change.kind != ChangeKind::Updated
+                || !is_page(&change.path)
+            {
+                continue;
+            }
+            let old_links = internal_links(old, &change.path)?;
+            let new_links = internal_links(new, &change.path)?;
+            let link_change = LinkChange {
+                page: change.path.clone(),
+                added: new_links
+                    .difference(&old_links)
+                    .cloned()
+                    .collect(),
+                removed: old_links
+                    .difference(&new_links)
+                    .cloned()
+                    .collect(),
+            };
+            if !link_change.added.is_empty()
+                || !link_change.removed.is_empty()
+            {
+                links.push(link_change);
+            }
+        }
+        Ok(Self { files, links })
+    }
+
+    /// Compares the pages listed by two page manifests.
+    ///
+    /// # Arguments
+    /// * `old` - The first build's manifest.
+    /// * `new` - The second build's manifest.
+    pub fn compare_manifests(
+        old: &PageManifest,
+        new: &PageManifest,
+    ) -> Self {
+        let change = |path: &String, kind| FileChange {
+            path: PathBuf::from(path),
+            kind,
+            old_size: None,
+            new_size: None,
+        };
+        let mut changes: Vec<FileChange> = new
+            .pages
+            .difference(&old.pages)
+            .map(|path| change(path, ChangeKind::Created))
+            .chain(
+                old.pages
+                    .difference(&new.pages)
+                    .map(|path| change(path, ChangeKind::Deleted)),
+            )
+            .collect();
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+        Self {
+            files: BuildPreview { changes },
+            links: Vec::new(),
+        }
+    }
+
+    /// Returns the changed pages, ordered by path.
+    pub fn pages(&self) -> impl Iterator<Item = &FileChange> {
+        self.files.changes.iter().filter(|c| is_page(&c.path))
+    }
+
+    /// Returns the changed files that are not pages, ordered by path.
+    pub fn other_files(&self) -> impl Iterator<Item = &FileChange> {
+        self.files.changes.iter().filter(|c| !is_page(&c.path))
+    }
+
+    /// Returns `true` if the builds are identical.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+impl fmt::Display for BuildComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (title, changes) in [
+            ("Pages", self.pages().collect::<Vec<_>>()),
+            ("Other files", self.other_files().collect()),
+        ] {
+            let count = |kind| {
+                changes.iter().filter(|c| c.kind == kind).count()
+            };
+            writeln!(
+                f,
+                "{}: {} added, {} removed, {} changed ({:+} bytes)",
+                title,
+                count(ChangeKind::Created),
+                count(ChangeKind::Deleted),
+                count(ChangeKind::Updated),
+                changes.iter().map(|c| c.size_delta()).sum::<i64>()
+            )?;
+            for change in changes {
+                let marker = match change.kind {
+                    ChangeKind::Created => '+',
+                    ChangeKind::Deleted => '-',
+                    ChangeKind::Updated => '~',
+                };
+                writeln!(
+                    f,
+                    "  {} {} ({:+} bytes)",
+                    marker,
+                    change.path.display(),
+                    change.size_delta()
+                )?;
+            }
+        }
+
+        let (added, removed) =
+            self.links.iter().fold((0, 0), |(added, removed), c| {
+                (added + c.added.len(), removed + c.removed.len())
+            });
+        writeln!(f, "Links: {} added, {} removed", added, removed)?;
+        for change in &self.links {
+            let page = change.page.display();
+            for link in &change.added {
+                writeln!(f, "  {}: + {}", page, link)?;
+            }
+            for link in &change.removed {
+                writeln!(f, "  {}: - {}", page, link)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns `true` if an output file is a page.
+fn is_page(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map_or(false, |ext| {
+            ext.eq_ignore_ascii_case("html")
+                || ext.eq_ignore_ascii_case("htm")
+        })
+}
+
+/// Returns the output paths a page links to within the site.
+fn internal_links(
+    output_dir: &Path,
+    page: &Path,
+) -> Result<BTreeSet<String>> {
+    let path = output_dir.join(page);
+    let html = fs::read_to_string(&path)
+        .map_err(|e| ProcessingError::io_error(path, e))?;
+    let url = format!("/{}", manifest_path(page));
+    Ok(page_links(&html)
+        .into_iter()
+        .map(|(_, target)| target)
+        .filter(|target| is_internal(target))
+        .filter_map(|target| resolve_link(&url, &target))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_compare_dirs() -> Result<()> {
+        let old = TempDir::new().unwrap();
+        let new = TempDir::new().unwrap();
+        fs::create_dir(new.path().join("blog"))?;
+        fs::write(
+            old.path().join("index.html"),
+            "<a href=\"old.html\">Old</a><a href=\"/about.html\">A</a>",
+        )?;
+        fs::write(
+            new.path().join("index.html"),
+            "<a href=\"blog/post.html#top\">New</a>\
+             <a href=\"about.html\">A</a><a href=\"https://x.org\">X</a>",
+        )?;
+        fs::write(old.path().join("old.html"), "old")?;
+        fs::write(new.path().join("blog/post.html"), "post")?;
+        fs::write(old.path().join("about.html"), "about")?;
+        fs::write(new.path().join("about.html"), "about")?;
+        fs::write(old.path().join("site.css"), "a{}")?;
+        fs::write(new.path().join("site.css"), "a{b:c}")?;
+
+        let comparison =
+            BuildComparison::compare(old.path(), new.path())?;
+        let pages: Vec<_> = comparison
+            .pages()
+            .map(|c| (c.path.to_str().unwrap(), c.kind))
+            .collect();
+        assert_eq!(
+            pages,
+            [
+                ("blog/post.html", ChangeKind::Created),
+                ("index.html", ChangeKind::Updated),
+                ("old.html", ChangeKind::Deleted),
+            ]
+        );
+        assert_eq!(comparison.other_files().count(), 1);
+        assert_eq!(
+            comparison.links,
+            [LinkChange {
+                page: "index.html".into(),
+                added: ["blog/post.html".to_string()]
+                    .into_iter()
+                    .collect(),
+                removed: ["old.html".to_string()].into_iter().collect(),
+            }]
+        );
+
+        let report = comparison.to_string();
+        assert!(report.contains("Pages: 1 added, 1 removed, 1 changed"));
+        assert!(report.contains("  + blog/post.html (+4 bytes)\n"));
+        assert!(report.contains(
+            "Other files: 0 added, 0 removed, 1 changed (+3 bytes)"
+        ));
+        assert!(report.contains("Links: 1 added, 1 removed\n"));
+        assert!(report.contains("  index.html: - old.html\n"));
+
+        assert!(BuildComparison::compare(old.path(), old.path())?
+            .is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_compare_manifests() -> Result<()> {
+        let dir = TempDir::new().unwrap();
+        let manifest = |pages: &[&str]| PageManifest {
+            pages: pages.iter().map(|p| p.to_string()).collect(),
+            ..PageManifest::default()
+        };
+        let old = dir.path().join("old.json");
+        let new = dir.path().join("new.json");
+        fs::write(
+            &old,
+            serde_json::to_string(&manifest(&["a.html", "b.html"]))
+                .unwrap(),
+        )?;
+        fs::write(
+            &new,
+            serde_json::to_string(&manifest(&["b.html", "c.html"]))
+                .unwrap(),
+        )?;
+
+        let comparison = BuildComparison::compare(&old, &new)?;
+        let pages: Vec<_> = comparison
+            .pages()
+            .map(|c| (c.path.to_str().unwrap(), c.kind))
+            .collect();
+        assert_eq!(
+            pages,
+            [
+                ("a.html", ChangeKind::Deleted),
+                ("c.html", ChangeKind::Created)
+            ]
+        );
+        assert!(comparison.links.is_empty());
+
+        assert!(BuildComparison::compare(&old, dir.path()).is_err());
+        Ok(())
+    }
+}
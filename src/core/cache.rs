@@ -0,0 +1,281 @@
+//! # Cache Metrics
+//!
+//! Counts hits and misses of the caches a build relies on, so users can
+//! see whether repeated and incremental builds reuse earlier work:
+//!
+//! - the content cache, which keeps each page's processed content until
+//!   its source changes,
+//! - the template cache of renderers such as `HandlebarsRenderer`,
+//! - the asset cache of generators such as `HtmlGenerator`.
+//!
+//! Each cache owns a [`CacheCounter`]; snapshots of the counters are
+//! [`CacheStats`], gathered for a build as [`CacheMetrics`].
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::core::cache::CacheCounter;
+//!
+//! let counter = CacheCounter::default();
+//! counter.record(false);
+//! counter.record(true);
+//! let stats = counter.stats();
+//! assert_eq!((stats.hits, stats.misses), (1, 1));
+//! assert_eq!(stats.to_string(), "1 hits, 1 misses (50% hit rate)");
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use parking_lot::RwLock;
+use serde::Serialize;
+
+use crate::core::error::Result;
+use crate::generators::fingerprint::content_hash;
+
+/// A snapshot of a cache's hit and miss counts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CacheStats {
+    /// Lookups answered from the cache
+    pub hits: u64,
+
+    /// Lookups that had to do the work
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// Returns the number of lookups.
+    pub fn lookups(&self) -> u64 {
+        self.hits + self.misses
+    }
+
+    /// Returns the fraction of lookups that hit, or `None` if there
+    /// were none.
+    pub fn hit_rate(&self) -> Option<f64> {
+        match self.lookups() {
+            0 => None,
+            lookups => Some(self.hits as f64 / lookups as f64),
+        }
+    }
+
+    /// Returns the lookups made since an earlier snapshot.
+    pub fn since(&self, earlier: &CacheStats) -> CacheStats {
+        CacheStats {
+            hits: self.hits.saturating_sub(earlier.hits),
+            misses: self.misses.saturating_sub(earlier.misses),
+        }
+    }
+}
+
+impl fmt::Display for CacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} hits, {} misses", self.hits, self.misses)?;
+        if let Some(rate) = self.hit_rate() {
+            write!(f, " ({:.0}% hit rate)", rate * 100.0)?;
+        }
+        Ok(())
+    }
+}
+
+/// Thread-safe hit and miss counters for a cache.
+#[derive(Debug, Default)]
+pub struct CacheCounter {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl CacheCounter {
+    /// Counts a lookup as a hit or a miss.
+    pub fn record(&self, hit: bool) {
+        let counter = if hit { &self.hits } else { &self.misses };
+        _ = counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the counts so far.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// The cache counts of a build.
+///
+/// Template and asset counts are `None` when the configured renderer or
+/// generator has no cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CacheMetrics {
+    /// Processed page content
+    pub content: CacheStats,
+
+    /// Loaded templates
+    pub template: Option<CacheStats>,
+
+    /// Copied assets
+    pub asset: Option<CacheStats>,
+}
+
+impl CacheMetrics {
+    /// Returns the lookups made since an earlier snapshot.
+    pub fn since(&self, earlier: &CacheMetrics) -> CacheMetrics {
+        let since = |now: Option<CacheStats>,
+                     then: Option<CacheStats>| {
+            now.map(|now| now.since(&then.unwrap_or_default()))
+        };
+        CacheMetrics {
+            content: self.content.since(&earlier.content),
+            template: since(self.template, earlier.template),
+            asset: since(self.asset, earlier.asset),
+        }
+    }
+}
+
+impl fmt::Display for CacheMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "content cache: {}", self.content)?;
+        for (name, stats) in
+            [("template", self.template), ("asset", self.asset)]
+        {
+            match stats {
+                Some(stats) => {
+                    writeln!(f, "{} cache: {}", name, stats)?
+                }
+                None => writeln!(f, "{} cache: not used", name)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Processed page content, kept until the page's source changes.
+///
+/// Entries are keyed by source path and hold a hash of the content they
+/// were processed from, and of the footnote number it starts from when
+/// footnotes are numbered across pages, so an edited page, or one whose
+/// footnotes are renumbered, misses and is processed again.
+#[derive(Debug, Default)]
+pub struct ContentCache {
+    entries: RwLock<HashMap<PathBuf, (String, String)>>,
+    counter: CacheCounter,
+}
+
+impl ContentCache {
+    /// Returns the cached output for `content` at `path`, or runs
+    /// `process` and caches its output.
+    ///
+    /// # Arguments
+    /// * `path` - The content file's path.
+    /// * `content` - The content to process.
+    /// * `footnote_start` - The footnotes numbered before the content,
+    ///   if footnotes are numbered across pages.
+    /// * `process` - Processes `content` on a miss.
+    ///
+    /// # Returns
+    /// * `Result<String>` - The processed content, or the error from
+    ///   `process`, which is not cached.
+    pub fn get_or_process<F>(
+        &self,
+        path: &Path,
+        content: &str,
+        footnote_start: Option<usize>,
+        process: F,
+    ) -> Result<String>
+    where
+        F: FnOnce() -> Result<String>,
+    {
+        let hash = match footnote_start {
+            Some(start) => content_hash(
+                format!("{}\n{}", start, content).as_bytes(),
+            ),
+            None => content_hash(content.as_bytes()),
+        };
+        if let Some((cached_hash, output)) =
+            self.entries.read().get(path)
+        {
+            if *cached_hash == hash {
+                self.counter.record(true);
+                return Ok(output.clone());
+            }
+        }
+
+        self.counter.record(false);
+        let output = process()?;
+        _ = self
+            .entries
+            .write()
+            .insert(path.to_path_buf(), (hash, output.clone()));
+        Ok(output)
+    }
+
+    /// Returns the cache's counts so far.
+    pub fn stats(&self) -> CacheStats {
+        self.counter.stats()
+    }
+
+    /// Drops all cached content, keeping the counts.
+    pub fn clear(&self) {
+        self.entries.write().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_cache() -> Result<()> {
+        let cache = ContentCache::default();
+        let path = Path::new("post.md");
+        let upper =
+            |text: &str| -> Result<String> { Ok(text.to_uppercase()) };
+
+        assert_eq!(
+            cache.get_or_process(path, "a", None, || upper("a"))?,
+            "A"
+        );
+        assert_eq!(
+            cache
+                .get_or_process(path, "a", None, || panic!("cached"))?,
+            "A"
+        );
+        assert_eq!(
+            cache.get_or_process(path, "b", None, || upper("b"))?,
+            "B"
+        );
+        assert_eq!(
+            cache.get_or_process(path, "b", Some(2), || upper("b"))?,
+            "B"
+        );
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 3 });
+        Ok(())
+    }
+
+    #[test]
+    fn test_cache_metrics_since() {
+        let before = CacheMetrics {
+            content: CacheStats { hits: 1, misses: 2 },
+            template: None,
+            asset: Some(CacheStats { hits: 0, misses: 3 }),
+        };
+        let after = CacheMetrics {
+            content: CacheStats { hits: 4, misses: 2 },
+            template: None,
+            asset: Some(CacheStats { hits: 3, misses: 3 }),
+        };
+        let build = after.since(&before);
+        assert_eq!(build.content, CacheStats { hits: 3, misses: 0 });
+        assert_eq!(
+            build.asset,
+            Some(CacheStats { hits: 3, misses: 0 })
+        );
+        assert_eq!(
+            build.to_string(),
+            "content cache: 3 hits, 0 misses (100% hit rate)\n\
+             template cache: not used\n\
+             asset cache: 3 hits, 0 misses (100% hit rate)\n"
+        );
+    }
+}
@@ -41,6 +41,52 @@
 //! assert_eq!(config_read.profile, Profile::Production);
 //! ```
 //!
+//! ## Layered Configuration
+//!
+//! A base file can be combined with overlay files, which are deep-merged
+//! over it in order: tables are merged key by key, and any other value
+//! in a later file replaces the earlier one. The overlay for the active
+//! profile is picked up automatically from next to the base file, so
+//! `config.toml` is followed by `config.production.toml` when building
+//! with `Profile::Production`:
+//!
+//! ```rust,no_run
+//! use nucleusflow::core::config::{ConfigBuilder, Profile};
+//!
+//! // Merges config.toml, config.production.toml (if present) and
+//! // then local.toml.
+//! let config = ConfigBuilder::new()
+//!     .with_file("config.toml")
+//!     .with_overlay("local.toml")
+//!     .with_profile(Profile::Production)
+//!     .build()
+//!     .unwrap();
+//! ```
+//!
+//! ## Environment Variables in Values
+//!
+//! String values in configuration files may refer to environment
+//! variables, resolved when the file is loaded, so the same file works
+//! across CI environments:
+//!
+//! ```toml
+//! output_dir = "${OUTPUT_DIR:-public}"
+//!
+//! [custom]
+//! base_url = "${SITE_URL}"
+//! ```
+//!
+//! An unset variable without a `:-` default fails the load, as does a
+//! blocked variable such as `PATH` or `HOME`. Write `$${` for a literal
+//! `${`.
+//!
+//! ## JSON Schema
+//!
+//! [`Config::json_schema`] describes the configuration file format as a
+//! JSON Schema, generated from the configuration types, so editors can
+//! complete `nucleusflow.toml` and CI can validate it. The CLI prints it
+//! with `nucleusflow config schema`.
+//!
 //! ## Security Considerations
 //!
 //! - All paths are sanitized to prevent directory traversal attacks
@@ -49,17 +95,23 @@
 //! - Sensitive values are masked in debug output
 //! - File operations use secure default permissions
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
 use std::sync::Arc;
+use std::thread::{self, JoinHandle};
 use std::time::{Duration, SystemTime};
 
 use parking_lot::RwLock;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use toml::Value as TomlValue;
 
+use crate::core::time::SiteTimezone;
+use crate::generators::redirects::RedirectMap;
+use crate::generators::structured_data::ARTICLE_TYPES;
 use crate::ProcessingError;
 use crate::Result;
 
@@ -72,6 +124,17 @@
 /// List of environment variables that should never be used in configuration
 const BLOCKED_ENV_VARS: &[&str] = &["PATH", "HOME", "USER", "SHELL"];
 
+/// Name fragments marking environment variables as secrets, which are
+/// never exposed to templates
+const SENSITIVE_ENV_MARKERS: &[&str] =
+    &["TOKEN", "SECRET", "PASSWORD", "PASSWD", "CREDENTIAL", "KEY"];
+
+/// Replaces sensitive values when a configuration is displayed
+const MASKED_VALUE: &str = "********";
+
+/// Largest width or height of a social card image, in pixels
+const MAX_SOCIAL_CARD_SIZE: u32 = 4096;
+
 /// Specifies operational profiles for configuration.
 ///
 /// Each profile determines distinct settings suitable for specific environments
@@ -85,7 +148,16 @@
 /// - Staging: Moderate security for testing
 /// - Production: Maximum security with all protections enabled
 /// - Custom: User-defined security settings (requires careful configuration)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
 #[serde(rename_all = "lowercase")]
 pub enum Profile {
     /// Development profile with settings optimized for debugging.
@@ -111,6 +183,19 @@ fn default() -> Self {
     }
 }
 
+impl Profile {
+    /// Returns the profile's name as used in configuration files, such
+    /// as `production`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Profile::Development => "development",
+            Profile::Staging => "staging",
+            Profile::Production => "production",
+            Profile::Custom => "custom",
+        }
+    }
+}
+
 /// Represents the main configuration structure encompassing all application settings.
 ///
 /// This structure consolidates settings for content processing, templating,
@@ -122,7 +207,7 @@ fn default() -> Self {
 /// - File size limits are enforced
 /// - Configuration reloading is protected against race conditions
 /// - Sensitive values are masked in debug output
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct Config {
     /// Directory for content files (sanitized path)
     #[serde(default = "default_content_dir")]
@@ -136,10 +221,19 @@ pub struct Config {
     #[serde(default = "default_template_dir")]
     pub template_dir: PathBuf,
 
+    /// The URL the site is published at, used for canonical links
+    #[serde(default)]
+    pub base_url: Option<String>,
+
     /// Current operational profile
     #[serde(default)]
     pub profile: Profile,
 
+    /// Site timezone for naive dates and build timestamps (IANA name)
+    #[serde(default)]
+    #[schemars(with = "String")]
+    pub timezone: SiteTimezone,
+
     /// Content processing configuration
     #[serde(default)]
     pub content: ContentConfig,
@@ -152,8 +246,129 @@ pub struct Config {
     #[serde(default)]
     pub output: OutputConfig,
 
+    /// Static file passthrough configuration
+    #[serde(default, rename = "static")]
+    pub static_files: StaticConfig,
+
+    /// Memory limits enforced while building
+    #[serde(default)]
+    pub memory: MemoryConfig,
+
+    /// External link checking settings for `nucleusflow check`
+    #[serde(default)]
+    pub link_check: LinkCheckConfig,
+
+    /// Accessibility lint severities for `nucleusflow check --a11y`
+    #[serde(default)]
+    pub a11y: A11yConfig,
+
+    /// Site-wide values for OpenGraph and Twitter card tags
+    #[serde(default)]
+    pub seo: SeoConfig,
+
+    /// Paths content may not claim, and how clashes with generated
+    /// pages are resolved
+    #[serde(default)]
+    pub routes: RoutesConfig,
+
+    /// Synonyms merged into canonical taxonomy terms
+    #[serde(default)]
+    pub taxonomies: TaxonomyConfig,
+
+    /// Where rebuilds push their changed files for a shared preview
+    #[serde(default)]
+    pub deploy: DeployConfig,
+
+    /// The languages of a multilingual site
+    #[serde(default)]
+    pub i18n: I18nConfig,
+
+    /// Pages captured as screenshots after each build, for visual
+    /// regression checks
+    #[serde(default)]
+    pub screenshots: ScreenshotConfig,
+
+    /// Social card images rendered for pages' `og:image`
+    #[serde(default)]
+    pub social_cards: SocialCardConfig,
+
+    /// Archive pages listing dated pages by year and month, generated
+    /// when set
+    #[serde(default)]
+    pub archives: Option<ArchiveConfig>,
+
+    /// Author pages listing each author's pages, generated when set
+    #[serde(default)]
+    pub authors: Option<AuthorConfig>,
+
+    /// Series index pages listing the posts of each article series,
+    /// generated when set
+    #[serde(default)]
+    pub series: Option<SeriesConfig>,
+
+    /// Collections of records in data files, each record rendered as a
+    /// page
+    #[serde(default)]
+    pub data_pages: Vec<DataPagesConfig>,
+
+    /// Citations of a BibTeX bibliography, rendered with a references
+    /// section, when set
+    #[serde(default)]
+    pub citations: Option<CitationConfig>,
+
+    /// Dates and contributors read from the git history of content
+    /// files, when set
+    #[serde(default)]
+    pub git: Option<GitConfig>,
+
+    /// Rhai scripts run on each page, in order, with the `scripting`
+    /// feature
+    #[serde(default)]
+    pub scripts: Vec<PathBuf>,
+
+    /// Navigation menus exposed to templates as `site.menus`, keyed by
+    /// name, such as `main` or `footer`
+    #[serde(default)]
+    pub menus: BTreeMap<String, Vec<MenuItem>>,
+
+    /// How symlinks in the content and static directories are handled
+    #[serde(default)]
+    pub symlinks: SymlinkPolicy,
+
+    /// Frontmatter values applied to content files by path
+    #[serde(default)]
+    pub frontmatter_defaults: Vec<FrontmatterDefault>,
+
+    /// Headless CMS endpoints whose entries are written as content
+    /// files before each build
+    #[serde(default)]
+    pub cms: Vec<CmsSource>,
+
+    /// Target URLs of short vanity paths, such as `/go/discord`
+    #[serde(default)]
+    pub shortlinks: BTreeMap<String, String>,
+
+    /// The new path of each section that moved, keyed by its old path,
+    /// such as `"/blog/" = "/posts/"`
+    #[serde(default)]
+    pub moved_sections: BTreeMap<String, String>,
+
+    /// HTTP status codes, besides 404, whose error page is rendered
+    /// from the template of the same name, such as `410.hbs`
+    #[serde(default)]
+    pub error_pages: Vec<u16>,
+
+    /// Environment variables exposed to templates as `build.env`
+    #[serde(default)]
+    pub build_env: Vec<String>,
+
+    /// Per-plugin settings, keyed by plugin name
+    #[serde(default)]
+    pub plugins: HashMap<String, PluginConfig>,
+
     /// Custom configuration values
     #[serde(default)]
+    #[schemars(with = "HashMap<String, serde_json::Value>")]
     pub custom: HashMap<String, TomlValue>,
 
     /// Tracks when the configuration was last modified
@@ -167,6 +382,17 @@ pub struct Config {
     /// Interval for reload checks
     #[serde(skip)]
     reload_interval: Duration,
+
+    /// How the configuration was loaded, so it can be reloaded
+    #[serde(skip)]
+    source: Option<Box<ConfigSource>>,
+}
+
+/// The builder a configuration was loaded with, and the files it reads.
+#[derive(Debug, Clone)]
+struct ConfigSource {
+    builder: ConfigBuilder,
+    files: Vec<PathBuf>,
 }
 
 /// Configuration settings specific to content processing.
@@ -180,7 +406,7 @@ pub struct Config {
 /// - File extension validation
 /// - Content sanitization
 /// - Metadata validation
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ContentConfig {
     /// Enables validation of content before processing
     #[serde(default = "default_true")]
@@ -200,6 +426,7 @@ pub struct ContentConfig {
 
     /// Additional content-specific options
     #[serde(default)]
+    #[schemars(with = "HashMap<String, serde_json::Value>")]
     pub options: HashMap<String, TomlValue>,
 
     /// Maximum content size in bytes (10MB default)
@@ -213,6 +440,188 @@ pub struct ContentConfig {
     /// List of allowed HTML tags if sanitization is enabled
     #[serde(default = "default_allowed_html_tags")]
     pub allowed_html_tags: Vec<String>,
+
+    /// List of attributes kept on allowed tags if sanitization is enabled
+    #[serde(default = "default_allowed_html_attributes")]
+    pub allowed_html_attributes: Vec<String>,
+
+    /// List of URL schemes permitted in links and sources if
+    /// sanitization is enabled (relative URLs are always allowed)
+    #[serde(default = "default_allowed_protocols")]
+    pub allowed_protocols: Vec<String>,
+
+    /// Markdown syntax extensions
+    #[serde(default)]
+    pub markdown: MarkdownExtensions,
+
+    /// How footnotes are numbered and rendered
+    #[serde(default)]
+    pub footnotes: FootnoteConfig,
+
+    /// How page summaries are cut from pages without a `<!--more-->`
+    /// marker
+    #[serde(default)]
+    pub summary: SummaryConfig,
+
+    /// Reading speed, in words per minute, that page reading times are
+    /// estimated with
+    #[serde(default = "default_words_per_minute")]
+    pub words_per_minute: usize,
+
+    /// Frontmatter values merged beneath every page's own, such as
+    /// `author`; `[[frontmatter_defaults]]` rules scope values to paths
+    #[serde(default)]
+    #[schemars(with = "HashMap<String, serde_json::Value>")]
+    pub frontmatter_defaults: toml::Table,
+}
+
+/// Markdown syntax extensions enabled for every page.
+///
+/// An extension left unset keeps the processor's own setting, and any
+/// of them can still be toggled per page with the `markdown`
+/// frontmatter key.
+///
+/// ```toml
+/// [content.markdown]
+/// tasklists = true
+/// definition_lists = true
+/// ```
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct MarkdownExtensions {
+    /// Enables or disables tables
+    pub tables: Option<bool>,
+
+    /// Enables or disables strikethrough
+    pub strikethrough: Option<bool>,
+
+    /// Enables or disables footnotes
+    pub footnotes: Option<bool>,
+
+    /// Enables or disables `- [x]` task lists
+    pub tasklists: Option<bool>,
+
+    /// Enables or disables `{#id .class}` heading attributes
+    pub heading_attributes: Option<bool>,
+
+    /// Enables or disables `term` / `: definition` lists
+    pub definition_lists: Option<bool>,
+
+    /// Enables or disables curly quotes, dashes and ellipses
+    pub smart_punctuation: Option<bool>,
+}
+
+/// How page summaries are cut from pages that set no `summary`
+/// frontmatter and have no `<!--more-->` marker.
+///
+/// ```toml
+/// [content.summary]
+/// paragraphs = 1
+/// ```
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct SummaryConfig {
+    /// The number of words a summary is cut to
+    #[serde(default = "default_summary_words")]
+    pub words: usize,
+
+    /// The number of paragraphs a summary is cut to instead, if set
+    #[serde(default)]
+    pub paragraphs: Option<usize>,
+}
+
+impl Default for SummaryConfig {
+    fn default() -> Self {
+        Self {
+            words: default_summary_words(),
+            paragraphs: None,
+        }
+    }
+}
+
+/// How footnotes are numbered and rendered.
+///
+/// Footnotes are listed at the end of the page, in a
+/// `<section class="footnotes">`, in the order they are first cited.
+///
+/// ```toml
+/// [content.footnotes]
+/// numbering = "continuous"
+/// heading = "Notes"
+/// ```
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct FootnoteConfig {
+    /// Ends each footnote with a `↩` link back to where it is cited
+    #[serde(default = "default_true")]
+    pub backlinks: bool,
+
+    /// Whether numbering restarts on every page
+    #[serde(default)]
+    pub numbering: FootnoteNumbering,
+
+    /// The heading of the footnotes section, such as "Notes"; the
+    /// section has no heading if empty
+    #[serde(default)]
+    pub heading: String,
+}
+
+impl Default for FootnoteConfig {
+    fn default() -> Self {
+        Self {
+            backlinks: true,
+            numbering: FootnoteNumbering::default(),
+            heading: String::new(),
+        }
+    }
+}
+
+/// How footnotes are numbered.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum FootnoteNumbering {
+    /// Every page's footnotes are numbered from 1
+    Page,
+    /// Footnotes are numbered on from the pages before, in site
+    /// order, so pages read in sequence, such as a book's chapters,
+    /// never reuse a number
+    Continuous,
+}
+
+impl Default for FootnoteNumbering {
+    fn default() -> Self {
+        FootnoteNumbering::Page
+    }
 }
 
 impl Default for ContentConfig {
@@ -226,6 +635,13 @@ fn default() -> Self {
             max_content_size: default_max_content_size(),
             max_metadata_size: default_max_metadata_size(),
             allowed_html_tags: default_allowed_html_tags(),
+            allowed_html_attributes: default_allowed_html_attributes(),
+            allowed_protocols: default_allowed_protocols(),
+            markdown: MarkdownExtensions::default(),
+            footnotes: FootnoteConfig::default(),
+            summary: SummaryConfig::default(),
+            words_per_minute: default_words_per_minute(),
+            frontmatter_defaults: toml::Table::new(),
         }
     }
 }
@@ -241,18 +657,24 @@ fn default() -> Self {
 /// - Cache size limits
 /// - Template validation
 /// - Strict mode for enhanced security
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TemplateConfig {
     /// Enables strict syntax checking
     #[serde(default)]
     pub strict_mode: bool,
 
+    /// Renders an inline error page instead of failing the build when a
+    /// template fails to render (development only)
+    #[serde(default)]
+    pub error_recovery: bool,
+
     /// Enables template caching
     #[serde(default = "default_true")]
     pub cache_templates: bool,
 
     /// Additional template-specific options
     #[serde(default)]
+    #[schemars(with = "HashMap<String, serde_json::Value>")]
     pub options: HashMap<String, TomlValue>,
 
     /// Maximum template size in bytes (1MB default)
@@ -276,6 +698,7 @@ impl Default for TemplateConfig {
     fn default() -> Self {
         Self {
             strict_mode: false,
+            error_recovery: false,
             cache_templates: true,
             options: HashMap::new(),
             max_template_size: default_max_template_size(),
@@ -297,7 +720,7 @@ fn default() -> Self {
 /// - File permission controls
 /// - Path sanitization
 /// - Resource limits
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct OutputConfig {
     /// Enables output minification
     #[serde(default)]
@@ -313,6 +736,7 @@ pub struct OutputConfig {
 
     /// Additional output-specific options
     #[serde(default)]
+    #[schemars(with = "HashMap<String, serde_json::Value>")]
     pub options: HashMap<String, TomlValue>,
 
     /// Maximum output file size in bytes (100MB default)
@@ -331,6 +755,27 @@ pub struct OutputConfig {
     /// Output rate limiting in bytes per second (0 = unlimited)
     #[serde(default)]
     pub rate_limit: u64,
+
+    /// Fine-grained minification settings, used when `minify` is enabled
+    #[serde(default)]
+    pub minify_options: MinifyOptions,
+
+    /// Pre-compressed `.gz`/`.br` twins of generated text assets
+    #[serde(default)]
+    pub precompress: PrecompressConfig,
+
+    /// Builds into a staging directory swapped into place on success
+    #[serde(default)]
+    pub atomic_swap: Option<SwapStrategy>,
+
+    /// Writes `build-report.json` listing every generated file
+    #[serde(default)]
+    pub build_report: bool,
+
+    /// Keeps source maps and unminified `.debug` variants of assets;
+    /// defaults to keeping them in the development profile only
+    #[serde(default)]
+    pub debug_artifacts: Option<bool>,
 }
 
 impl Default for OutputConfig {
@@ -345,118 +790,1560 @@ fn default() -> Self {
             file_permissions: default_file_permissions(),
             max_concurrent_ops: default_max_concurrent_ops(),
             rate_limit: 0,
+            minify_options: MinifyOptions::default(),
+            precompress: PrecompressConfig::default(),
+            atomic_swap: None,
+            build_report: false,
+            debug_artifacts: None,
         }
     }
 }
 
-/// Builder for constructing Config instances securely.
-///
-/// Provides a fluent interface for creating configuration instances
-/// with proper validation and security measures.
-///
-/// # Security Notes
+/// How a build replaces the previous output when atomic output is
+/// enabled.
 ///
-/// - All paths are sanitized
-/// - Environment variables are validated
-/// - Overrides are checked for safety
-/// - Size limits are enforced
-#[derive(Debug)]
-pub struct ConfigBuilder {
-    config_file: Option<PathBuf>,
-    env_prefix: Option<String>,
-    profile: Option<Profile>,
-    overrides: HashMap<String, TomlValue>,
-    auto_reload: bool,
-    reload_interval: Duration,
-    max_file_size: usize,
+/// ```toml
+/// [output]
+/// atomic_swap = "symlink"
+/// ```
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum SwapStrategy {
+    /// Rename the staging directory over the output directory
+    Rename,
+    /// Point the output path at the staging directory via a symlink
+    Symlink,
 }
 
-// Default value functions
-fn default_max_content_size() -> usize {
-    10 * 1024 * 1024 // 10MB
+impl Default for SwapStrategy {
+    fn default() -> Self {
+        Self::Rename
+    }
 }
 
-fn default_max_metadata_size() -> usize {
-    64 * 1024 // 64KB
+/// How symlinks met while scanning content and copying static files are
+/// handled.
+///
+/// Following symlinks lets content be shared from other repositories; a
+/// symlinked directory that points back at one of its ancestors is
+/// reported as a cycle rather than walked forever.
+///
+/// ```toml
+/// symlinks = "error"
+/// ```
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum SymlinkPolicy {
+    /// Treat symlinks as the files and directories they point to
+    Follow,
+    /// Skip symlinks as if they were not there
+    Ignore,
+    /// Fail the build on the first symlink
+    Error,
 }
 
-fn default_max_template_size() -> usize {
-    1024 * 1024 // 1MB
+impl Default for SymlinkPolicy {
+    fn default() -> Self {
+        Self::Follow
+    }
 }
 
-fn default_max_cache_size() -> usize {
-    100 * 1024 * 1024 // 100MB
+impl SymlinkPolicy {
+    /// Decides whether a symlink found at `link` is used.
+    ///
+    /// # Returns
+    /// * `Result<bool>` - `true` to follow the link, `false` to skip it,
+    ///   or an error naming the link under `SymlinkPolicy::Error`.
+    pub fn allows(&self, link: &Path) -> Result<bool> {
+        match self {
+            SymlinkPolicy::Follow => Ok(true),
+            SymlinkPolicy::Ignore => Ok(false),
+            SymlinkPolicy::Error => {
+                Err(ProcessingError::file_operation(
+                    link,
+                    "Symlinks are not allowed by the symlink policy",
+                    None,
+                ))
+            }
+        }
+    }
 }
 
-fn default_max_output_size() -> usize {
-    100 * 1024 * 1024 // 100MB
-}
+/// Settings for emitting pre-compressed twins of output files.
+///
+/// Servers such as nginx (`gzip_static`, `brotli_static`) and Caddy
+/// (`precompressed`) can serve `index.html.gz` or `index.html.br` directly
+/// instead of compressing every response on the fly.
+///
+/// ```toml
+/// [output.precompress]
+/// gzip = true
+/// brotli = true
+/// ```
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct PrecompressConfig {
+    /// Emits `.gz` twins
+    #[serde(default)]
+    pub gzip: bool,
 
-fn default_cache_ttl() -> u64 {
-    3600 // 1 hour
-}
+    /// Emits `.br` twins
+    #[serde(default)]
+    pub brotli: bool,
 
-fn default_max_concurrent_ops() -> usize {
-    10
+    /// File extensions to compress
+    #[serde(default = "default_precompress_extensions")]
+    pub extensions: Vec<String>,
+
+    /// Files smaller than this many bytes are not compressed
+    #[serde(default = "default_precompress_min_size")]
+    pub min_size: u64,
 }
 
-#[cfg(unix)]
-fn default_file_permissions() -> u32 {
-    0o644
+impl PrecompressConfig {
+    /// Returns `true` if at least one compression format is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.gzip || self.brotli
+    }
 }
 
-fn default_true() -> bool {
-    true
+impl Default for PrecompressConfig {
+    fn default() -> Self {
+        Self {
+            gzip: false,
+            brotli: false,
+            extensions: default_precompress_extensions(),
+            min_size: default_precompress_min_size(),
+        }
+    }
 }
 
-fn default_allowed_html_tags() -> Vec<String> {
-    vec![
-        "p",
-        "br",
-        "h1",
-        "h2",
-        "h3",
-        "h4",
-        "h5",
-        "h6",
-        "strong",
-        "em",
-        "del",
-        "ul",
-        "ol",
-        "li",
-        "code",
-        "pre",
-        "blockquote",
-        "hr",
-        "table",
-        "thead",
-        "tbody",
-        "tr",
-        "th",
-        "td",
-        "img",
-        "a",
-    ]
-    .into_iter()
-    .map(String::from)
-    .collect()
+/// Fine-grained HTML minification settings.
+///
+/// Each field maps to the minifier option of the same name. The defaults
+/// minify embedded CSS and JavaScript and otherwise favour the smallest
+/// output; enable the `keep_*` and spec-compliance toggles for stricter
+/// markup.
+///
+/// ```toml
+/// [output.minify_options]
+/// keep_closing_tags = true
+/// keep_comments = true
+/// ```
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+#[serde(default, deny_unknown_fields)]
+pub struct MinifyOptions {
+    /// Minifies CSS in `<style>` tags and `style` attributes
+    pub minify_css: bool,
+
+    /// Minifies JavaScript in `<script>` tags
+    pub minify_js: bool,
+
+    /// Keeps all comments
+    pub keep_comments: bool,
+
+    /// Keeps closing tags that could be omitted
+    pub keep_closing_tags: bool,
+
+    /// Keeps `<html>` and `<head>` opening tags without attributes
+    pub keep_html_and_head_opening_tags: bool,
+
+    /// Keeps spaces between attributes
+    pub keep_spaces_between_attributes: bool,
+
+    /// Keeps `type=text` on `<input>` elements
+    pub keep_input_type_text_attr: bool,
+
+    /// Keeps server-side include comments
+    pub keep_ssi_comments: bool,
+
+    /// Leaves DOCTYPE declarations untouched
+    pub do_not_minify_doctype: bool,
+
+    /// Quotes attribute values containing characters the specification
+    /// prohibits in unquoted values
+    pub ensure_spec_compliant_unquoted_attribute_values: bool,
+
+    /// Passes `{{ }}`, `{% %}` and `{# #}` template syntax through
+    /// untouched
+    pub preserve_brace_template_syntax: bool,
+
+    /// Passes `<% %>` template syntax through untouched
+    pub preserve_chevron_percent_template_syntax: bool,
+
+    /// Removes all bangs
+    pub remove_bangs: bool,
+
+    /// Removes all processing instructions
+    pub remove_processing_instructions: bool,
 }
 
-fn default_allowed_functions() -> Vec<String> {
-    vec![
-        "upper", "lower", "trim", "date", "length", "join", "split",
-        "slice", "first", "last",
-    ]
-    .into_iter()
-    .map(String::from)
-    .collect()
+impl MinifyOptions {
+    /// Returns settings that only produce specification-compliant HTML.
+    pub fn spec_compliant() -> Self {
+        Self {
+            do_not_minify_doctype: true,
+            ensure_spec_compliant_unquoted_attribute_values: true,
+            keep_spaces_between_attributes: true,
+            ..Self::default()
+        }
+    }
 }
 
-fn default_extensions() -> Vec<String> {
+impl Default for MinifyOptions {
+    fn default() -> Self {
+        Self {
+            minify_css: true,
+            minify_js: true,
+            keep_comments: false,
+            keep_closing_tags: false,
+            keep_html_and_head_opening_tags: false,
+            keep_spaces_between_attributes: false,
+            keep_input_type_text_attr: false,
+            keep_ssi_comments: false,
+            do_not_minify_doctype: false,
+            ensure_spec_compliant_unquoted_attribute_values: false,
+            preserve_brace_template_syntax: false,
+            preserve_chevron_percent_template_syntax: false,
+            remove_bangs: false,
+            remove_processing_instructions: false,
+        }
+    }
+}
+
+/// Configuration settings for static file passthrough.
+///
+/// Files in the static directory are copied verbatim to the output
+/// directory, preserving their relative paths. Glob patterns are matched
+/// against paths relative to the static directory using `/` separators,
+/// where `*` matches within a single path segment and `**` matches across
+/// segments.
+///
+/// # Security Features
+///
+/// - Dotfiles (e.g. `.env`, `.git/`) are skipped unless explicitly enabled
+/// - Paths are sanitized to prevent directory traversal
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema,
+)]
+pub struct StaticConfig {
+    /// Directory containing static files (sanitized path)
+    #[serde(default = "default_static_dir")]
+    pub dir: PathBuf,
+
+    /// Glob patterns selecting files to copy
+    #[serde(default = "default_static_include")]
+    pub include: Vec<String>,
+
+    /// Glob patterns excluding files from copying, applied after `include`
+    #[serde(default)]
+    pub exclude: Vec<String>,
+
+    /// Copies files and directories whose names start with a dot
+    #[serde(default)]
+    pub include_dotfiles: bool,
+}
+
+impl Default for StaticConfig {
+    fn default() -> Self {
+        Self {
+            dir: default_static_dir(),
+            include: default_static_include(),
+            exclude: Vec::new(),
+            include_dotfiles: false,
+        }
+    }
+}
+
+/// Frontmatter values given to every content file matching a glob.
+///
+/// The pattern is matched against paths relative to the content
+/// directory. A file's own frontmatter takes precedence over its
+/// defaults, and later rules take precedence over earlier ones.
+///
+/// ```toml
+/// [[frontmatter_defaults]]
+/// path = "docs/**"
+/// values = { template = "doc", toc = true }
+/// ```
+#[derive(
+    Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct FrontmatterDefault {
+    /// Glob pattern selecting content files
+    pub path: String,
+
+    /// Frontmatter values merged beneath each matching file's own
+    #[schemars(with = "HashMap<String, serde_json::Value>")]
+    pub values: toml::Table,
+}
+
+/// Memory limits enforced while building, in mebibytes of resident
+/// memory.
+///
+/// Past the soft limit, cached content and assets are dropped to free
+/// memory; past the hard limit, the build stops with an error naming the
+/// page it was on, rather than being killed by the system. Limits are
+/// only enforced where resident memory can be read (currently Linux).
+///
+/// ```toml
+/// [memory]
+/// soft_limit_mb = 1536
+/// hard_limit_mb = 2048
+/// ```
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct MemoryConfig {
+    /// Usage at which caches are dropped
+    #[serde(default)]
+    pub soft_limit_mb: Option<u64>,
+
+    /// Usage at which the build stops
+    #[serde(default)]
+    pub hard_limit_mb: Option<u64>,
+}
+
+/// Settings for checking the external links of a site, which `check`
+/// does when built with the `link-check` feature and run with
+/// `--external`.
+///
+/// Links are checked concurrently, but requests to the same host are
+/// spaced out so a site linking to one host many times is not rate
+/// limited. Links that answered recently are remembered in a cache file
+/// and not checked again until it expires.
+///
+/// ```toml
+/// [link_check]
+/// concurrency = 8
+/// host_interval_ms = 500
+/// skip_domains = ["linkedin.com", "localhost"]
+/// ```
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct LinkCheckConfig {
+    /// The number of links checked at once
+    #[serde(default = "default_link_check_concurrency")]
+    pub concurrency: usize,
+
+    /// The minimum time between requests to the same host, in
+    /// milliseconds
+    #[serde(default = "default_link_check_host_interval_ms")]
+    pub host_interval_ms: u64,
+
+    /// How long to wait for a response, in seconds
+    #[serde(default = "default_link_check_timeout_secs")]
+    pub timeout_secs: u64,
+
+    /// How long a link that answered is trusted without checking it
+    /// again, in hours
+    #[serde(default = "default_link_check_cache_hours")]
+    pub cache_hours: u64,
+
+    /// Domains whose links are never checked, including their
+    /// subdomains
+    #[serde(default)]
+    pub skip_domains: Vec<String>,
+}
+
+impl Default for LinkCheckConfig {
+    fn default() -> Self {
+        Self {
+            concurrency: default_link_check_concurrency(),
+            host_interval_ms: default_link_check_host_interval_ms(),
+            timeout_secs: default_link_check_timeout_secs(),
+            cache_hours: default_link_check_cache_hours(),
+            skip_domains: Vec::new(),
+        }
+    }
+}
+
+/// How an accessibility problem found by `check --a11y` is reported.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum A11ySeverity {
+    /// Reported as a problem that fails the check
+    Error,
+    /// Reported without failing the check
+    Warning,
+    /// Not reported
+    Off,
+}
+
+/// The severity of each accessibility rule checked by `check --a11y`.
+///
+/// ```toml
+/// [a11y]
+/// skipped_heading = "error"
+/// missing_lang = "off"
+/// ```
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct A11yConfig {
+    /// Images without an `alt` attribute
+    #[serde(default = "default_a11y_error")]
+    pub missing_alt: A11ySeverity,
+
+    /// Headings more than one level below the heading before them
+    #[serde(default = "default_a11y_warning")]
+    pub skipped_heading: A11ySeverity,
+
+    /// Links without text or an accessible label
+    #[serde(default = "default_a11y_error")]
+    pub empty_link: A11ySeverity,
+
+    /// Buttons without text or an accessible label
+    #[serde(default = "default_a11y_error")]
+    pub empty_button: A11ySeverity,
+
+    /// Pages whose `<html>` element has no `lang` attribute
+    #[serde(default = "default_a11y_warning")]
+    pub missing_lang: A11ySeverity,
+}
+
+impl Default for A11yConfig {
+    fn default() -> Self {
+        Self {
+            missing_alt: default_a11y_error(),
+            skipped_heading: default_a11y_warning(),
+            empty_link: default_a11y_error(),
+            empty_button: default_a11y_error(),
+            missing_lang: default_a11y_warning(),
+        }
+    }
+}
+
+/// Site-wide values for the OpenGraph and Twitter card tags added to
+/// each page by [`crate::generators::seo::SocialMetaTags`], and the
+/// JSON-LD added by
+/// [`crate::generators::structured_data::StructuredData`].
+///
+/// The tags themselves come from each page's frontmatter; these settings
+/// make their URLs absolute and fill in what pages do not set.
+///
+/// ```toml
+/// [seo]
+/// base_url = "https://example.com"
+/// site_name = "Example"
+/// default_image = "/images/card.png"
+/// twitter_site = "@example"
+/// article_type = "Article"
+/// ```
+#[derive(
+    Debug,
+    Clone,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct SeoConfig {
+    /// The URL the site is published at, used to make page and image
+    /// URLs absolute
+    #[serde(default)]
+    pub base_url: Option<String>,
+
+    /// The site's name, as `og:site_name`
+    #[serde(default)]
+    pub site_name: Option<String>,
+
+    /// The image shared for pages that do not set an `image`
+    #[serde(default)]
+    pub default_image: Option<String>,
+
+    /// The site's Twitter handle, as `twitter:site`
+    #[serde(default)]
+    pub twitter_site: Option<String>,
+
+    /// The card type for pages with an image; pages without one use a
+    /// `summary` card. Defaults to `summary_large_image`.
+    #[serde(default)]
+    pub twitter_card: Option<String>,
+
+    /// The schema.org type of the JSON-LD for dated pages, which a
+    /// page's `schema_type` overrides. Defaults to `BlogPosting`.
+    #[serde(default)]
+    pub article_type: Option<String>,
+
+    /// Adds a `BreadcrumbList` to the JSON-LD of pages below the root.
+    /// Defaults to `true`.
+    #[serde(default)]
+    pub breadcrumbs: Option<bool>,
+}
+
+/// What happens to a content page whose output path is also generated
+/// by the build, such as a shortlink stub.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum CollisionPolicy {
+    /// Fail the page
+    Error,
+    /// Move the page to the first free path with a `-1`, `-2`, ...
+    /// suffix
+    Suffix,
+    /// Leave the page out of the build with a warning
+    Skip,
+}
+
+impl Default for CollisionPolicy {
+    fn default() -> Self {
+        Self::Error
+    }
+}
+
+/// Paths reserved for things other than content, and how content that
+/// clashes with generated pages is handled.
+///
+/// A reserved path ending in `/` reserves everything under it; any
+/// other reserves that one page, with or without `.html`. Content under
+/// a reserved path always fails.
+///
+/// ```toml
+/// [routes]
+/// reserved = ["/api/", "/admin/", "/status"]
+/// on_collision = "suffix"
+/// ```
+#[derive(
+    Debug,
+    Clone,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct RoutesConfig {
+    /// URL paths content may not be published at
+    #[serde(default)]
+    pub reserved: Vec<String>,
+
+    /// What happens when a content page would be written where the
+    /// build generates a page
+    #[serde(default)]
+    pub on_collision: CollisionPolicy,
+}
+
+impl RoutesConfig {
+    /// Returns the reserved path a page URL falls under, if any.
+    ///
+    /// # Arguments
+    /// * `url` - The page's URL path, such as `/api/users.html`.
+    pub fn reserves(&self, url: &str) -> Option<&str> {
+        let url = url.trim_start_matches('/');
+        self.reserved
+            .iter()
+            .find(|reserved| {
+                let path = reserved.trim_matches('/');
+                if reserved.ends_with('/') {
+                    url == path
+                        || url.starts_with(&format!("{}/", path))
+                } else {
+                    url == path
+                        || url == format!("{}.html", path)
+                        || url == format!("{}/", path)
+                }
+            })
+            .map(String::as_str)
+    }
+}
+
+/// Synonyms of taxonomy terms, merged into one canonical term when
+/// pages are collected.
+///
+/// The `tags` and `categories` of every page are rewritten to their
+/// canonical terms, so listings, feeds and templates see historically
+/// inconsistent tagging merged without old posts being edited. Synonyms
+/// match regardless of case, and a term listed twice after merging is
+/// kept once.
+///
+/// ```toml
+/// [taxonomies.synonyms]
+/// rustlang = "rust"
+/// "rust-lang" = "rust"
+/// ```
+#[derive(
+    Debug,
+    Clone,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct TaxonomyConfig {
+    /// The canonical term of each synonym
+    #[serde(default)]
+    pub synonyms: BTreeMap<String, String>,
+}
+
+impl TaxonomyConfig {
+    /// Frontmatter keys holding taxonomy terms.
+    pub const KEYS: [&'static str; 2] = ["tags", "categories"];
+
+    /// Returns the canonical term of a term, which is the term itself
+    /// unless it is a synonym.
+    pub fn canonical<'a>(&'a self, term: &'a str) -> &'a str {
+        self.synonyms
+            .iter()
+            .find(|(synonym, _)| synonym.eq_ignore_ascii_case(term))
+            .map_or(term, |(_, canonical)| canonical.as_str())
+    }
+
+    /// Rewrites the taxonomy terms of a page's frontmatter to their
+    /// canonical terms, dropping the duplicates this creates.
+    ///
+    /// # Arguments
+    /// * `frontmatter` - The page's frontmatter; `tags` and
+    ///   `categories` may be a list of terms or a single term.
+    pub fn apply(
+        &self,
+        frontmatter: &mut serde_json::Map<String, serde_json::Value>,
+    ) {
+        if self.synonyms.is_empty() {
+            return;
+        }
+        for key in Self::KEYS {
+            match frontmatter.get_mut(key) {
+                Some(serde_json::Value::String(term)) => {
+                    *term = self.canonical(term).to_string();
+                }
+                Some(serde_json::Value::Array(terms)) => {
+                    let mut merged: Vec<serde_json::Value> =
+                        Vec::with_capacity(terms.len());
+                    for term in terms.drain(..) {
+                        let term = match term.as_str() {
+                            Some(name) => serde_json::Value::from(
+                                self.canonical(name),
+                            ),
+                            None => term,
+                        };
+                        if !merged.contains(&term) {
+                            merged.push(term);
+                        }
+                    }
+                    *terms = merged;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The languages of a multilingual site.
+///
+/// Each language has its own tree of pages: the default language at the
+/// root, and every other language under a directory named by its code,
+/// such as `/fr/about.html`. Pages at the same path in different trees,
+/// or sharing a `translation_key`, are translations of each other and
+/// are linked with `hreflang` alternates (see
+/// [`crate::generators::hreflang::HreflangLinks`]).
+///
+/// ```toml
+/// [i18n]
+/// default_language = "en"
+/// languages = ["en", "fr", "de"]
+/// ```
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct I18nConfig {
+    /// The language of the pages at the root of the site
+    #[serde(default = "default_language")]
+    pub default_language: String,
+
+    /// Every language of the site, including the default; the site is
+    /// not multilingual if empty
+    #[serde(default)]
+    pub languages: Vec<String>,
+}
+
+impl Default for I18nConfig {
+    fn default() -> Self {
+        Self {
+            default_language: default_language(),
+            languages: Vec::new(),
+        }
+    }
+}
+
+impl I18nConfig {
+    /// Returns `true` if the site has languages configured.
+    pub fn is_enabled(&self) -> bool {
+        !self.languages.is_empty()
+    }
+}
+
+/// Pages captured as PNG screenshots by a headless browser after each
+/// successful build, with
+/// [`crate::generators::screenshots::Screenshots`].
+///
+/// A page's screenshot from the previous build is kept beside the new
+/// one and the two are compared pixel by pixel, so visual changes can
+/// be reviewed between builds.
+///
+/// ```toml
+/// [screenshots]
+/// pages = ["/", "/blog/"]
+/// browser = "chromium"
+/// width = 1280
+/// height = 800
+/// ```
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct ScreenshotConfig {
+    /// The site-relative URLs of the pages to capture; nothing is
+    /// captured if empty
+    #[serde(default)]
+    pub pages: Vec<String>,
+
+    /// The Chrome or Chromium executable run in headless mode
+    #[serde(default = "default_screenshot_browser")]
+    pub browser: String,
+
+    /// The width of the browser window, in pixels
+    #[serde(default = "default_screenshot_width")]
+    pub width: u32,
+
+    /// The height of the browser window, in pixels
+    #[serde(default = "default_screenshot_height")]
+    pub height: u32,
+
+    /// The directory screenshots are written to
+    #[serde(default = "default_screenshot_dir")]
+    pub dir: PathBuf,
+}
+
+impl Default for ScreenshotConfig {
+    fn default() -> Self {
+        Self {
+            pages: Vec::new(),
+            browser: default_screenshot_browser(),
+            width: default_screenshot_width(),
+            height: default_screenshot_height(),
+            dir: default_screenshot_dir(),
+        }
+    }
+}
+
+impl ScreenshotConfig {
+    /// Returns `true` if there are pages to capture.
+    pub fn is_enabled(&self) -> bool {
+        !self.pages.is_empty()
+    }
+}
+
+/// Archive pages listing the dated pages of each year and month, such
+/// as `/archive/2024/` and `/archive/2024/05/`.
+///
+/// Paths are relative to the output directory: `{year}` is replaced
+/// with the year and `{month}` with the month's two digits, and a path
+/// ending with `/` is written as the directory's `index.html`.
+///
+/// ```toml
+/// [archives]
+/// year_path = "posts/{year}/"
+/// month_path = ""
+/// year_template = "archive"
+/// ```
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct ArchiveConfig {
+    /// Where each year's page is written
+    #[serde(default = "default_archive_year_path")]
+    pub year_path: String,
+
+    /// Where each month's page is written; no month pages are
+    /// generated if empty
+    #[serde(default = "default_archive_month_path")]
+    pub month_path: String,
+
+    /// The template year pages are rendered with
+    #[serde(default = "default_archive_template")]
+    pub year_template: String,
+
+    /// The template month pages are rendered with
+    #[serde(default = "default_archive_template")]
+    pub month_template: String,
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self {
+            year_path: default_archive_year_path(),
+            month_path: default_archive_month_path(),
+            year_template: default_archive_template(),
+            month_template: default_archive_template(),
+        }
+    }
+}
+
+/// Author pages listing the pages naming each author in their `author`
+/// frontmatter, such as `/authors/ada/`.
+///
+/// Authors' display names, bios and avatars are read from a TOML data
+/// file keyed by the ids pages name them by. The path is relative to
+/// the output directory, with `{author}` replaced by the author's id
+/// as a slug, and a path ending with `/` is written as the directory's
+/// `index.html`.
+///
+/// ```toml
+/// [authors]
+/// data = "data/authors.toml"
+/// path = "people/{author}.html"
+/// template = "author"
+/// ```
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct AuthorConfig {
+    /// The data file describing authors; authors are named by their
+    /// ids if it does not exist
+    #[serde(default = "default_author_data")]
+    pub data: PathBuf,
+
+    /// Where each author's page is written
+    #[serde(default = "default_author_path")]
+    pub path: String,
+
+    /// The template author pages are rendered with
+    #[serde(default = "default_author_template")]
+    pub template: String,
+}
+
+impl Default for AuthorConfig {
+    fn default() -> Self {
+        Self {
+            data: default_author_data(),
+            path: default_author_path(),
+            template: default_author_template(),
+        }
+    }
+}
+
+/// A headless CMS endpoint whose entries are written as content files
+/// before each build.
+///
+/// Entries are fetched with a `GET` request, or, when a GraphQL `query`
+/// is set, by posting it. The `items`, `slug`, `body` and `fields`
+/// values are JSON pointers, such as `/data/posts`, into the response
+/// and each of its entries; `fields` maps frontmatter keys to entry
+/// values.
+///
+/// ```toml
+/// [[cms]]
+/// name = "blog"
+/// url = "https://cms.example.com/graphql"
+/// query = "{ posts { slug title publishedAt body } }"
+/// items = "/data/posts"
+/// headers = { Authorization = "Bearer ${CMS_TOKEN}" }
+///
+/// [cms.fields]
+/// title = "/title"
+/// date = "/publishedAt"
+/// ```
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct CmsSource {
+    /// Names the source in the `cms` frontmatter of its files
+    pub name: String,
+
+    /// The REST or GraphQL endpoint
+    pub url: String,
+
+    /// A GraphQL query posted to the endpoint
+    #[serde(default)]
+    pub query: Option<String>,
+
+    /// Request headers, such as `Authorization`
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+
+    /// Where the list of entries is in the response; the response
+    /// itself by default
+    #[serde(default)]
+    pub items: String,
+
+    /// Where an entry's file name is, as a slug
+    #[serde(default = "default_cms_slug")]
+    pub slug: String,
+
+    /// Where an entry's content is
+    #[serde(default = "default_cms_body")]
+    pub body: String,
+
+    /// Whether an entry's content is Markdown or HTML
+    #[serde(default)]
+    pub format: CmsFormat,
+
+    /// Frontmatter keys and where their values are in an entry
+    #[serde(default)]
+    pub fields: BTreeMap<String, String>,
+}
+
+/// The format of the content a CMS entry holds, which names the
+/// extension of its file.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum CmsFormat {
+    /// Markdown, written to a `.md` file
+    Markdown,
+    /// HTML, written to a `.html` file
+    Html,
+}
+
+impl Default for CmsFormat {
+    fn default() -> Self {
+        Self::Markdown
+    }
+}
+
+/// Citations of the entries of a BibTeX bibliography, written as
+/// `[@key]` in content, and the references section listing them.
+///
+/// ```toml
+/// [citations]
+/// bibliography = "data/references.bib"
+/// style = "numeric"
+/// title = "Bibliography"
+/// ```
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct CitationConfig {
+    /// The BibTeX file of the entries cited
+    #[serde(default = "default_bibliography")]
+    pub bibliography: PathBuf,
+
+    /// How citations and references are written
+    #[serde(default)]
+    pub style: CitationStyle,
+
+    /// The heading of the references section
+    #[serde(default = "default_references_title")]
+    pub title: String,
+}
+
+impl Default for CitationConfig {
+    fn default() -> Self {
+        Self {
+            bibliography: default_bibliography(),
+            style: CitationStyle::default(),
+            title: default_references_title(),
+        }
+    }
+}
+
+/// How citations and references are written.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum CitationStyle {
+    /// `(Knuth, 1984)`, with references ordered by author
+    AuthorYear,
+    /// `[1]`, with references numbered in the order they are cited
+    Numeric,
+}
+
+impl Default for CitationStyle {
+    fn default() -> Self {
+        Self::AuthorYear
+    }
+}
+
+/// Metadata read from the git history of each content file: when it
+/// was created and last changed, and who contributed to it.
+///
+/// Each value is set in a page's frontmatter unless the page sets it
+/// itself.
+///
+/// ```toml
+/// [git]
+/// lastmod = true
+/// created = true
+/// contributors = false
+/// ```
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct GitConfig {
+    /// Sets `lastmod` to the date of the file's latest commit
+    #[serde(default = "default_true")]
+    pub lastmod: bool,
+
+    /// Sets `created` to the date of the file's first commit
+    #[serde(default = "default_true")]
+    pub created: bool,
+
+    /// Sets `contributors` to the authors of the file's commits
+    #[serde(default = "default_true")]
+    pub contributors: bool,
+}
+
+impl Default for GitConfig {
+    fn default() -> Self {
+        Self {
+            lastmod: true,
+            created: true,
+            contributors: true,
+        }
+    }
+}
+
+/// Series index pages listing the posts sharing a `series` frontmatter
+/// value, such as `/series/rust-in-production/`.
+///
+/// The path is relative to the output directory, with `{series}`
+/// replaced by the series' name as a slug, and a path ending with `/`
+/// is written as the directory's `index.html`.
+///
+/// ```toml
+/// [series]
+/// path = "series/{series}/"
+/// template = "series"
+/// ```
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct SeriesConfig {
+    /// Where each series' index page is written
+    #[serde(default = "default_series_path")]
+    pub path: String,
+
+    /// The template series index pages are rendered with
+    #[serde(default = "default_series_template")]
+    pub template: String,
+}
+
+impl Default for SeriesConfig {
+    fn default() -> Self {
+        Self {
+            path: default_series_path(),
+            template: default_series_template(),
+        }
+    }
+}
+
+/// A collection of records in a JSON, YAML or TOML data file, each
+/// rendered as a page with a template, such as a page per team member.
+///
+/// The records are the list, or the table of records keyed by id, at
+/// the `items` JSON pointer, the whole file by default. The path is
+/// relative to the output directory, with each `{field}` replaced by
+/// the record's value for it as a slug; a record keyed by id has its
+/// key as `id` unless it sets one. A path ending with `/` is written as
+/// the directory's `index.html`.
+///
+/// ```toml
+/// [[data_pages]]
+/// data = "data/team.yaml"
+/// path = "team/{id}/"
+/// template = "member"
+/// ```
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct DataPagesConfig {
+    /// The data file holding the records
+    pub data: PathBuf,
+
+    /// Where the records are in the data file
+    #[serde(default)]
+    pub items: String,
+
+    /// Where each record's page is written
+    pub path: String,
+
+    /// The template record pages are rendered with
+    pub template: String,
+}
+
+/// An entry of a navigation menu, with any entries nested beneath it.
+///
+/// Entries are listed by `weight`, lightest first, keeping their
+/// configured order otherwise.
+///
+/// ```toml
+/// [[menus.main]]
+/// title = "Docs"
+/// url = "/docs/"
+/// weight = 1
+///
+/// [[menus.main.children]]
+/// title = "Install"
+/// url = "/docs/install/"
+/// ```
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct MenuItem {
+    /// The entry's text
+    pub title: String,
+
+    /// The URL the entry links to
+    pub url: String,
+
+    /// Where the entry is listed among its siblings
+    #[serde(default)]
+    pub weight: i64,
+
+    /// The entries nested beneath this one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<MenuItem>,
+}
+
+/// Social card images rendered for each page's `og:image`, with
+/// `SocialCards` from the `social-cards` feature.
+///
+/// Each card shows the page's title, its author and the site's name over
+/// a background image, or a solid colour without one. Pages that set
+/// their own `image` get no card.
+///
+/// ```toml
+/// [social_cards]
+/// font = "assets/fonts/Inter-Bold.ttf"
+/// background = "assets/card-background.png"
+/// site_name = "Example"
+/// text_color = "#ffffff"
+/// ```
+#[derive(
+    Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct SocialCardConfig {
+    /// The TrueType or OpenType font the text is set in; no cards are
+    /// rendered without one
+    #[serde(default)]
+    pub font: Option<PathBuf>,
+
+    /// The image the text is drawn over, cropped to the card's size
+    #[serde(default)]
+    pub background: Option<PathBuf>,
+
+    /// The background colour used without a background image, as
+    /// `#rrggbb`
+    #[serde(default = "default_social_card_background_color")]
+    pub background_color: String,
+
+    /// The colour of the text, as `#rrggbb`
+    #[serde(default = "default_social_card_text_color")]
+    pub text_color: String,
+
+    /// The site name shown at the bottom of each card
+    #[serde(default)]
+    pub site_name: Option<String>,
+
+    /// The width of the cards, in pixels
+    #[serde(default = "default_social_card_width")]
+    pub width: u32,
+
+    /// The height of the cards, in pixels
+    #[serde(default = "default_social_card_height")]
+    pub height: u32,
+}
+
+impl Default for SocialCardConfig {
+    fn default() -> Self {
+        Self {
+            font: None,
+            background: None,
+            background_color: default_social_card_background_color(),
+            text_color: default_social_card_text_color(),
+            site_name: None,
+            width: default_social_card_width(),
+            height: default_social_card_height(),
+        }
+    }
+}
+
+impl SocialCardConfig {
+    /// Returns `true` if a font is set to render cards with.
+    pub fn is_enabled(&self) -> bool {
+        self.font.is_some()
+    }
+}
+
+/// Parses a `#rrggbb` colour into its red, green and blue channels.
+pub fn parse_hex_color(color: &str) -> Option<[u8; 3]> {
+    let hex = color.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |index: usize| {
+        u8::from_str_radix(&hex[index..index + 2], 16).ok()
+    };
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// Where successful rebuilds push their changed files for a shared
+/// preview, with [`crate::generators::deploy::PreviewDeploy`].
+///
+/// ```toml
+/// [deploy]
+/// preview_dir = "/mnt/preview/my-site"
+/// ```
+#[derive(
+    Debug,
+    Clone,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct DeployConfig {
+    /// The preview deploy target, a directory such as a mounted share;
+    /// nothing is pushed if unset
+    #[serde(default)]
+    pub preview_dir: Option<PathBuf>,
+}
+
+/// Per-plugin settings, read from a `[plugins.<name>]` table.
+///
+/// Plugins are enabled unless their table sets `enabled = false`. Any
+/// other keys are the plugin's own options, checked against its schema
+/// when it is registered.
+///
+/// ```toml
+/// [plugins.gallery]
+/// enabled = true
+/// album = "cats"
+/// columns = 4
+/// ```
+#[derive(
+    Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema,
+)]
+pub struct PluginConfig {
+    /// Whether the plugin's helpers and shortcodes are registered
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Plugin-specific options
+    #[serde(flatten)]
+    #[schemars(with = "HashMap<String, serde_json::Value>")]
+    pub options: HashMap<String, TomlValue>,
+}
+
+impl Default for PluginConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            options: HashMap::new(),
+        }
+    }
+}
+
+/// Builder for constructing Config instances securely.
+///
+/// Provides a fluent interface for creating configuration instances
+/// with proper validation and security measures.
+///
+/// # Security Notes
+///
+/// - All paths are sanitized
+/// - Environment variables are validated
+/// - Overrides are checked for safety
+/// - Size limits are enforced
+#[derive(Debug, Clone)]
+pub struct ConfigBuilder {
+    config_file: Option<PathBuf>,
+    overlay_files: Vec<PathBuf>,
+    env_prefix: Option<String>,
+    profile: Option<Profile>,
+    overrides: HashMap<String, TomlValue>,
+    auto_reload: bool,
+    reload_interval: Duration,
+    max_file_size: usize,
+}
+
+// Default value functions
+fn default_max_content_size() -> usize {
+    10 * 1024 * 1024 // 10MB
+}
+
+fn default_max_metadata_size() -> usize {
+    64 * 1024 // 64KB
+}
+
+fn default_archive_year_path() -> String {
+    "archive/{year}/".to_string()
+}
+
+fn default_archive_month_path() -> String {
+    "archive/{year}/{month}/".to_string()
+}
+
+fn default_archive_template() -> String {
+    "archive".to_string()
+}
+
+fn default_author_data() -> PathBuf {
+    PathBuf::from("data/authors.toml")
+}
+
+fn default_author_path() -> String {
+    "authors/{author}/".to_string()
+}
+
+fn default_author_template() -> String {
+    "author".to_string()
+}
+
+fn default_series_path() -> String {
+    "series/{series}/".to_string()
+}
+
+fn default_series_template() -> String {
+    "series".to_string()
+}
+
+fn default_words_per_minute() -> usize {
+    crate::processors::markdown::DEFAULT_WORDS_PER_MINUTE
+}
+
+fn default_summary_words() -> usize {
+    70
+}
+
+fn default_max_template_size() -> usize {
+    1024 * 1024 // 1MB
+}
+
+fn default_max_cache_size() -> usize {
+    100 * 1024 * 1024 // 100MB
+}
+
+fn default_max_output_size() -> usize {
+    100 * 1024 * 1024 // 100MB
+}
+
+fn default_cache_ttl() -> u64 {
+    3600 // 1 hour
+}
+
+fn default_max_concurrent_ops() -> usize {
+    10
+}
+
+#[cfg(unix)]
+fn default_file_permissions() -> u32 {
+    0o644
+}
+
+fn default_bibliography() -> PathBuf {
+    PathBuf::from("data/references.bib")
+}
+
+fn default_references_title() -> String {
+    "References".to_string()
+}
+
+fn default_cms_slug() -> String {
+    "/slug".to_string()
+}
+
+fn default_cms_body() -> String {
+    "/body".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_allowed_html_tags() -> Vec<String> {
+    vec![
+        "p",
+        "br",
+        "h1",
+        "h2",
+        "h3",
+        "h4",
+        "h5",
+        "h6",
+        "strong",
+        "em",
+        "del",
+        "ul",
+        "ol",
+        "li",
+        "code",
+        "pre",
+        "blockquote",
+        "hr",
+        "table",
+        "thead",
+        "tbody",
+        "tr",
+        "th",
+        "td",
+        "dl",
+        "dt",
+        "dd",
+        "img",
+        "a",
+        "nav",
+        "sup",
+        "section",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+fn default_allowed_html_attributes() -> Vec<String> {
+    vec![
+        "alt",
+        "aria-label",
+        "class",
+        "href",
+        "id",
+        "src",
+        "start",
+        "title",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+fn default_allowed_protocols() -> Vec<String> {
+    vec!["http", "https", "mailto"]
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+fn default_allowed_functions() -> Vec<String> {
+    vec![
+        "upper", "lower", "trim", "date", "length", "join", "split",
+        "slice", "first", "last",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+fn default_extensions() -> Vec<String> {
     vec!["md".to_string(), "markdown".to_string()]
 }
 
+fn default_static_include() -> Vec<String> {
+    vec!["**".to_string()]
+}
+
 impl ConfigBuilder {
     /// Creates a new ConfigBuilder with default settings.
     ///
@@ -470,6 +2357,7 @@ impl ConfigBuilder {
     pub fn new() -> Self {
         Self {
             config_file: None,
+            overlay_files: Vec::new(),
             env_prefix: None,
             profile: None,
             overrides: HashMap::new(),
@@ -505,6 +2393,21 @@ pub fn with_file<P: AsRef<Path>>(mut self, path: P) -> Self {
         self
     }
 
+    /// Adds a configuration file merged over the base file.
+    ///
+    /// Overlays are deep-merged in the order they are added, after the
+    /// active profile's overlay: tables are merged key by key, and any
+    /// other value replaces the one from earlier files. Each overlay is
+    /// subject to the same checks as the base file and must exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the TOML overlay file
+    pub fn with_overlay<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.overlay_files.push(sanitize_path(path.as_ref()));
+        self
+    }
+
     /// Adds a prefix for environment variables.
     ///
     /// # Security
@@ -529,6 +2432,11 @@ pub fn with_env_prefix<S: Into<String>>(
 
     /// Sets the configuration profile.
     ///
+    /// The profile also selects the overlay merged over the base file:
+    /// `config.production.toml` for a base `config.toml` under
+    /// `Profile::Production`. Without a profile set here, the `profile`
+    /// key of the base file is used.
+    ///
     /// # Arguments
     ///
     /// * `profile` - The operational profile to use
@@ -615,8 +2523,29 @@ pub fn with_max_file_size(mut self, size: usize) -> Self {
     ///
     /// * `Result<Arc<RwLock<Config>>>` - Thread-safe configuration or error
     pub fn build(self) -> Result<Arc<RwLock<Config>>> {
-        let mut config = if let Some(path) = self.config_file {
-            load_from_file(&path, self.max_file_size)?
+        Ok(Arc::new(RwLock::new(self.load()?)))
+    }
+
+    /// Loads the configuration with every layer, environment variable
+    /// and override applied, without validating it.
+    ///
+    /// Use [`Config::problems`] on the result to report every problem
+    /// at once, rather than the first as [`ConfigBuilder::build`] does.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Config>` - The resolved configuration, or an error if a
+    ///   file cannot be read or parsed, or an override is invalid
+    pub fn resolve(&self) -> Result<Config> {
+        let mut config = if let Some(path) = &self.config_file {
+            load_layered(
+                path,
+                &self.overlay_files,
+                self.profile,
+                self.max_file_size,
+            )?
+        } else if !self.overlay_files.is_empty() {
+            merge_layers(&self.overlay_files, self.max_file_size)?
         } else {
             Config::default()
         };
@@ -628,14 +2557,144 @@ pub fn build(self) -> Result<Arc<RwLock<Config>>> {
             config.profile = profile;
         }
 
-        if let Some(prefix) = self.env_prefix {
-            apply_env_overrides(&mut config, &prefix)?;
+        if let Some(prefix) = &self.env_prefix {
+            apply_env_overrides(&mut config, prefix)?;
         }
 
         apply_overrides(&mut config, &self.overrides)?;
+
+        let mut files: Vec<PathBuf> = self
+            .config_file
+            .iter()
+            .flat_map(|base| {
+                [
+                    base.clone(),
+                    profile_overlay_path(base, config.profile),
+                ]
+            })
+            .collect();
+        files.extend(self.overlay_files.iter().cloned());
+        if !files.is_empty() {
+            config.source = Some(Box::new(ConfigSource {
+                builder: self.clone(),
+                files,
+            }));
+        }
+        Ok(config)
+    }
+
+    /// Loads and validates the configuration, remembering its source
+    /// files so it can be reloaded when they change.
+    fn load(&self) -> Result<Config> {
+        let config = self.resolve()?;
         validate_config(&config)?;
+        Ok(config)
+    }
+}
+
+/// Reloads a shared configuration in the background when its files
+/// change.
+///
+/// Every reload interval, the watcher checks the configuration's files,
+/// including the active profile's overlay. Changed files are loaded and
+/// validated outside the lock, then swapped in with a single write, so
+/// readers see either the old or the new configuration. A configuration
+/// that fails to load or validate is logged and the previous one kept
+/// until the files change again.
+///
+/// The watcher stops when dropped.
+///
+/// ```rust,no_run
+/// use nucleusflow::core::config::{ConfigBuilder, ConfigWatcher};
+/// use std::sync::Arc;
+///
+/// let config = ConfigBuilder::new()
+///     .with_file("config.toml")
+///     .with_auto_reload(true)
+///     .build()
+///     .unwrap();
+/// let _watcher = ConfigWatcher::spawn(Arc::clone(&config)).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct ConfigWatcher {
+    stop: Option<Sender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl ConfigWatcher {
+    /// Starts watching a configuration built by [`ConfigBuilder`].
+    ///
+    /// Configurations without auto-reload enabled, or not loaded from
+    /// files, are never reloaded.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self>` - The running watcher, or an error if its thread
+    ///   cannot be started
+    pub fn spawn(config: Arc<RwLock<Config>>) -> Result<Self> {
+        let (stop, stopped) = mpsc::channel();
+        let handle = thread::Builder::new()
+            .name("config-watcher".to_string())
+            .spawn(move || watch_config(&config, &stopped))
+            .map_err(|e| {
+                ProcessingError::internal(
+                    "Failed to start the configuration watcher",
+                    Some(Box::new(e)),
+                )
+            })?;
+        Ok(Self {
+            stop: Some(stop),
+            handle: Some(handle),
+        })
+    }
+}
 
-        Ok(Arc::new(RwLock::new(config)))
+impl Drop for ConfigWatcher {
+    fn drop(&mut self) {
+        self.stop = None;
+        if let Some(handle) = self.handle.take() {
+            _ = handle.join();
+        }
+    }
+}
+
+/// Reloads `config` whenever its files change, until `stopped` is
+/// disconnected.
+fn watch_config(config: &RwLock<Config>, stopped: &Receiver<()>) {
+    let mut failed = None;
+    loop {
+        let interval =
+            config.read().reload_interval.max(Duration::from_secs(1));
+        match stopped.recv_timeout(interval) {
+            Err(RecvTimeoutError::Timeout) => {}
+            _ => return,
+        }
+
+        let (source, modified) = {
+            let config = config.read();
+            if !config.needs_reload() {
+                continue;
+            }
+            (config.source.clone(), config.source_modified())
+        };
+        let source = match source {
+            Some(source) if modified != failed => source,
+            _ => continue,
+        };
+        match source.builder.load() {
+            Ok(reloaded) => {
+                *config.write() = reloaded;
+                failed = None;
+                tracing::info!("Reloaded configuration");
+            }
+            Err(e) => {
+                failed = modified;
+                tracing::warn!(
+                    error = %e,
+                    "Keeping the previous configuration"
+                );
+            }
+        }
     }
 }
 
@@ -646,6 +2705,34 @@ fn default() -> Self {
 }
 
 impl Config {
+    /// Returns a JSON Schema describing the configuration file format.
+    ///
+    /// Field descriptions come from the configuration types'
+    /// documentation, and defaults from their serde defaults.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<serde_json::Value>` - The schema document
+    pub fn json_schema() -> Result<serde_json::Value> {
+        serde_json::to_value(schemars::schema_for!(Config)).map_err(
+            |e| {
+                ProcessingError::serialization(
+                    "Failed to serialise configuration schema",
+                    Some(Box::new(e)),
+                )
+            },
+        )
+    }
+
+    /// Returns `true` if builds keep source maps and debug variants of
+    /// assets: as set by `output.debug_artifacts`, or only in the
+    /// development profile when unset.
+    pub fn debug_artifacts(&self) -> bool {
+        self.output
+            .debug_artifacts
+            .unwrap_or(self.profile == Profile::Development)
+    }
+
     /// Validates all configuration settings.
     ///
     /// # Security
@@ -659,6 +2746,51 @@ pub fn validate(&self) -> Result<()> {
         validate_config(self)
     }
 
+    /// Runs every validation check and returns all the problems found,
+    /// rather than stopping at the first as [`Config::validate`] does.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<ProcessingError>` - The problems, empty if the
+    ///   configuration is valid
+    pub fn problems(&self) -> Vec<ProcessingError> {
+        config_problems(self)
+    }
+
+    /// Renders the configuration as TOML, with every default filled in
+    /// and sensitive values masked.
+    ///
+    /// A value is masked when a word of its key, split on `_`, `-` and
+    /// `.`, names a secret, as in `api_key` or `deploy-token`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String>` - The TOML document
+    pub fn to_masked_toml(&self) -> Result<String> {
+        let failed = |e: Box<dyn std::error::Error + Send + Sync>| {
+            ProcessingError::serialization(
+                "Failed to serialise configuration",
+                Some(e),
+            )
+        };
+        let mut value = TomlValue::try_from(self)
+            .map_err(|e| failed(Box::new(e)))?;
+        mask_sensitive_values(&mut value);
+        toml::to_string_pretty(&value).map_err(|e| failed(Box::new(e)))
+    }
+
+    /// Reads the whitelisted `build_env` variables from the environment.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<BTreeMap<String, String>>` - The variables that are set,
+    ///   or an error if a name is blocked
+    pub fn build_env_snapshot(
+        &self,
+    ) -> Result<BTreeMap<String, String>> {
+        snapshot_build_env(&self.build_env)
+    }
+
     /// Retrieves a custom configuration value.
     ///
     /// # Security
@@ -746,24 +2878,33 @@ pub fn set_custom<T: Serialize>(
 
     /// Checks if configuration needs reloading.
     ///
+    /// A configuration built from files needs reloading once any of
+    /// them, including the active profile's overlay, has changed since
+    /// it was loaded.
+    ///
     /// # Security
     ///
     /// - File access is controlled
     /// - Changes are validated
     /// - Race conditions are prevented
     pub fn needs_reload(&self) -> bool {
-        if !self.auto_reload {
-            return false;
-        }
+        self.auto_reload && self.source_modified() > self.last_modified
+    }
 
-        if let Some(last_modified) = self.last_modified {
-            if let Ok(metadata) = fs::metadata("config.toml") {
-                if let Ok(modified) = metadata.modified() {
-                    return modified > last_modified;
-                }
-            }
-        }
-        false
+    /// Returns the interval between reload checks.
+    pub fn reload_interval(&self) -> Duration {
+        self.reload_interval
+    }
+
+    /// Returns when the newest of the configuration's source files was
+    /// modified, or `None` if it was not loaded from files.
+    fn source_modified(&self) -> Option<SystemTime> {
+        self.source
+            .as_ref()?
+            .files
+            .iter()
+            .filter_map(|path| fs::metadata(path).ok()?.modified().ok())
+            .max()
     }
 
     /// Reloads configuration if needed.
@@ -778,15 +2919,12 @@ pub fn needs_reload(&self) -> bool {
     ///
     /// * `Result<bool>` - Whether the configuration was reloaded
     pub fn reload_if_needed(&mut self) -> Result<bool> {
-        if self.needs_reload() {
-            let new_config = load_from_file(
-                Path::new("config.toml"),
-                MAX_CONFIG_SIZE,
-            )?;
-            *self = new_config;
-            Ok(true)
-        } else {
-            Ok(false)
+        match &self.source {
+            Some(source) if self.needs_reload() => {
+                *self = source.builder.load()?;
+                Ok(true)
+            }
+            _ => Ok(false),
         }
     }
 }
@@ -888,8 +3026,199 @@ fn is_safe_env_prefix(prefix: &str) -> bool {
         && prefix.ends_with('_')
 }
 
-/// Loads configuration from file with security checks.
-fn load_from_file(path: &Path, max_size: usize) -> Result<Config> {
+/// Validates a variable name requested for `build.env`.
+///
+/// Names must be valid environment variable names, must not be one of
+/// the blocked variables and must not look like a secret.
+fn validate_build_env_name(name: &str) -> Result<()> {
+    let invalid = |reason: &str| {
+        ProcessingError::Configuration {
+        details: format!(
+            "Environment variable '{}' cannot be exposed to templates: {}",
+            name, reason
+        ),
+        path: None,
+        source: None,
+    }
+    };
+
+    let valid_chars = |c: char| {
+        c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_'
+    };
+    if name.is_empty()
+        || name.len() > 64
+        || name.starts_with(|c: char| c.is_ascii_digit())
+        || !name.chars().all(valid_chars)
+    {
+        return Err(invalid("invalid name"));
+    }
+    if BLOCKED_ENV_VARS.contains(&name) {
+        return Err(invalid("blocked variable"));
+    }
+    if SENSITIVE_ENV_MARKERS
+        .iter()
+        .any(|marker| name.contains(marker))
+    {
+        return Err(invalid("looks like a secret"));
+    }
+    Ok(())
+}
+
+/// Replaces the values of sensitive keys in a TOML value with a mask.
+fn mask_sensitive_values(value: &mut TomlValue) {
+    match value {
+        TomlValue::Table(table) => {
+            for (key, value) in table.iter_mut() {
+                if is_sensitive_key(key) {
+                    *value =
+                        TomlValue::String(MASKED_VALUE.to_string());
+                } else {
+                    mask_sensitive_values(value);
+                }
+            }
+        }
+        TomlValue::Array(values) => {
+            values.iter_mut().for_each(mask_sensitive_values)
+        }
+        _ => {}
+    }
+}
+
+/// Returns `true` if a word of a configuration key names a secret.
+fn is_sensitive_key(key: &str) -> bool {
+    key.split(|c| matches!(c, '_' | '-' | '.')).any(|word| {
+        let word = word.to_ascii_uppercase();
+        SENSITIVE_ENV_MARKERS.iter().any(|marker| {
+            word.strip_suffix('S').unwrap_or(&word) == *marker
+        })
+    })
+}
+
+/// Reads a whitelisted set of environment variables for templates.
+///
+/// Every name is validated first, so a blocked or secret-looking name
+/// fails the build rather than being silently skipped. Variables that
+/// are not set, or are not valid Unicode, are left out.
+///
+/// # Arguments
+///
+/// * `names` - The variable names to read
+///
+/// # Returns
+///
+/// * `Result<BTreeMap<String, String>>` - The variables that are set
+pub fn snapshot_build_env(
+    names: &[String],
+) -> Result<BTreeMap<String, String>> {
+    let mut snapshot = BTreeMap::new();
+    for name in names {
+        validate_build_env_name(name)?;
+        if let Ok(value) = env::var(name) {
+            _ = snapshot.insert(name.clone(), value);
+        }
+    }
+    Ok(snapshot)
+}
+
+/// Loads a base configuration file with its overlays.
+///
+/// The overlay for the active profile, named after the base file as
+/// `<stem>.<profile>.toml`, is merged first if it exists, followed by
+/// `overlays` in order.
+fn load_layered(
+    base: &Path,
+    overlays: &[PathBuf],
+    profile: Option<Profile>,
+    max_size: usize,
+) -> Result<Config> {
+    let profile = match profile {
+        Some(profile) => profile,
+        None => {
+            let (table, _) = read_config_table(base, max_size)?;
+            table
+                .get("profile")
+                .cloned()
+                .map(TomlValue::try_into)
+                .transpose()
+                .map_err(|e| ProcessingError::Configuration {
+                    details: format!("Invalid profile: {}", e),
+                    path: Some(base.to_path_buf()),
+                    source: None,
+                })?
+                .unwrap_or_default()
+        }
+    };
+
+    let mut layers = vec![base.to_path_buf()];
+    let profile_overlay = profile_overlay_path(base, profile);
+    if profile_overlay.is_file() {
+        layers.push(profile_overlay);
+    }
+    layers.extend(overlays.iter().cloned());
+    merge_layers(&layers, max_size)
+}
+
+/// Returns the path of a base file's overlay for `profile`, such as
+/// `config.production.toml` for `config.toml`.
+fn profile_overlay_path(base: &Path, profile: Profile) -> PathBuf {
+    let stem = base
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = base
+        .extension()
+        .map(|extension| extension.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "toml".to_string());
+    base.with_file_name(format!(
+        "{}.{}.{}",
+        stem,
+        profile.name(),
+        extension
+    ))
+}
+
+/// Deep-merges configuration files in order and parses the result.
+fn merge_layers(paths: &[PathBuf], max_size: usize) -> Result<Config> {
+    let mut merged = toml::Table::new();
+    let mut last_modified = None;
+    for path in paths {
+        let (table, modified) = read_config_table(path, max_size)?;
+        merge_tables(&mut merged, table);
+        last_modified = last_modified.max(Some(modified));
+    }
+
+    let mut config: Config = TomlValue::Table(merged)
+        .try_into()
+        .map_err(|e| ProcessingError::Configuration {
+            details: format!("Failed to parse config file: {}", e),
+            path: paths.last().cloned(),
+            source: None,
+        })?;
+    config.last_modified = last_modified;
+    Ok(config)
+}
+
+/// Merges `overlay` into `base`, recursing into tables present in both.
+fn merge_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (
+                Some(TomlValue::Table(base_table)),
+                TomlValue::Table(overlay_table),
+            ) => merge_tables(base_table, overlay_table),
+            (_, value) => {
+                _ = base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Reads a configuration file as a TOML table with security checks,
+/// returning it with the file's modification time.
+fn read_config_table(
+    path: &Path,
+    max_size: usize,
+) -> Result<(toml::Table, SystemTime)> {
     // Verify file size
     let metadata = fs::metadata(path).map_err(|e| {
         ProcessingError::Configuration {
@@ -935,18 +3264,106 @@ fn load_from_file(path: &Path, max_size: usize) -> Result<Config> {
         }
     })?;
 
-    let mut config: Config = toml::from_str(&content).map_err(|e| {
+    let mut table = content.parse::<toml::Table>().map_err(|e| {
         ProcessingError::Configuration {
             details: format!("Failed to parse config file: {}", e),
             path: Some(path.to_path_buf()),
             source: None,
         }
     })?;
+    for (_, value) in table.iter_mut() {
+        interpolate_env(value, path)?;
+    }
 
-    config.last_modified =
-        Some(metadata.modified().unwrap_or_else(|_| SystemTime::now()));
+    let modified =
+        metadata.modified().unwrap_or_else(|_| SystemTime::now());
+    Ok((table, modified))
+}
 
-    Ok(config)
+/// Replaces `${NAME}` placeholders in every string of a config value
+/// with the environment variable's value.
+///
+/// `${NAME:-default}` falls back to `default` when the variable is not
+/// set, and `$${` writes a literal `${`. Blocked variables, invalid names
+/// and unset variables without a default are errors.
+fn interpolate_env(value: &mut TomlValue, path: &Path) -> Result<()> {
+    match value {
+        TomlValue::String(text) if text.contains("${") => {
+            *text = interpolate_str(text, path)?;
+        }
+        TomlValue::Array(values) => {
+            for value in values {
+                interpolate_env(value, path)?;
+            }
+        }
+        TomlValue::Table(table) => {
+            for (_, value) in table.iter_mut() {
+                interpolate_env(value, path)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Expands the `${NAME}` placeholders of a single string.
+fn interpolate_str(text: &str, path: &Path) -> Result<String> {
+    let invalid = |details: String| ProcessingError::Configuration {
+        details,
+        path: Some(path.to_path_buf()),
+        source: None,
+    };
+
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('$') {
+            expanded.push_str(&rest[..start - 1]);
+            expanded.push_str("${");
+            rest = &rest[start + 2..];
+            continue;
+        }
+        expanded.push_str(&rest[..start]);
+        let end = rest[start..].find('}').ok_or_else(|| {
+            invalid(format!("Unclosed placeholder in '{}'", text))
+        })?;
+        let placeholder = &rest[start + 2..start + end];
+        let (name, default) = match placeholder.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (placeholder, None),
+        };
+
+        let valid_name = !name.is_empty()
+            && !name.starts_with(|c: char| c.is_ascii_digit())
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid_name {
+            return Err(invalid(format!(
+                "Invalid environment variable name '{}'",
+                name
+            )));
+        }
+        if BLOCKED_ENV_VARS.contains(&name) {
+            return Err(invalid(format!(
+                "Environment variable '{}' cannot be used in configuration",
+                name
+            )));
+        }
+        match (env::var(name), default) {
+            (Ok(value), _) => expanded.push_str(&value),
+            (Err(_), Some(default)) => expanded.push_str(default),
+            (Err(_), None) => {
+                return Err(invalid(format!(
+                    "Environment variable '{}' is not set",
+                    name
+                )))
+            }
+        }
+        rest = &rest[start + end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
 }
 
 /// Applies environment variable overrides with validation.
@@ -1028,6 +3445,16 @@ fn apply_config_value<T: ToString>(
             config.template_dir =
                 sanitize_path(&PathBuf::from(value_str));
         }
+        "timezone" => {
+            config.timezone = SiteTimezone::parse(&value_str)?;
+        }
+        "build_env" => {
+            let names = split_list(&value_str);
+            for name in &names {
+                validate_build_env_name(name)?;
+            }
+            config.build_env = names;
+        }
         "profile" => {
             config.profile = match value_str.to_lowercase().as_str() {
                 "development" => Profile::Development,
@@ -1054,6 +3481,16 @@ fn apply_config_value<T: ToString>(
                         key,
                         &value_str,
                     )?,
+                    "static" => apply_static_value(
+                        &mut config.static_files,
+                        key,
+                        &value_str,
+                    )?,
+                    "plugins" => apply_plugin_value(
+                        &mut config.plugins,
+                        key,
+                        &value_str,
+                    )?,
                     "custom" => {
                         if is_safe_config_key(key) {
                             let toml_value =
@@ -1111,13 +3548,13 @@ fn enforce_production_security(config: &mut Config) -> Result<()> {
     // Force enable critical security settings
     config.content.sanitize = true;
     config.template.strict_mode = true;
+    config.template.error_recovery = false;
 
     // Ensure secure file permissions
-#[cfg(unix)]
-{
-    config.output.file_permissions &= 0o644;
-}
-
+    #[cfg(unix)]
+    {
+        config.output.file_permissions &= 0o644;
+    }
 
     // Enforce minimum rate limiting
     if config.output.rate_limit == 0 {
@@ -1139,35 +3576,409 @@ fn enforce_production_security(config: &mut Config) -> Result<()> {
 
 /// Validates all configuration settings.
 fn validate_config(config: &Config) -> Result<()> {
+    match config_problems(config).into_iter().next() {
+        Some(problem) => Err(problem),
+        None => Ok(()),
+    }
+}
+
+/// Runs every configuration check, returning all the problems found in
+/// the order the checks run.
+fn config_problems(config: &Config) -> Vec<ProcessingError> {
+    let mut problems = Vec::new();
+    let mut check = |result: Result<()>| {
+        if let Err(problem) = result {
+            problems.push(problem);
+        }
+    };
+
     // Validate paths
-    validate_path(&config.content_dir, "content", true)?;
-    validate_path(&config.template_dir, "template", true)?;
+    check(validate_path(&config.content_dir, "content", true));
+    check(validate_path(&config.template_dir, "template", true));
 
     if let Some(asset_dir) = &config.output.asset_dir {
-        validate_path(asset_dir, "asset", true)?;
+        check(validate_path(asset_dir, "asset", true));
     }
 
     // Validate extensions
     if config.content.extensions.is_empty() {
-        return Err(ProcessingError::Configuration {
+        check(Err(ProcessingError::Configuration {
             details: "No content extensions specified".to_string(),
             path: None,
             source: None,
-        });
+        }));
+    }
+
+    // Validate static file patterns
+    for pattern in config
+        .static_files
+        .include
+        .iter()
+        .chain(&config.static_files.exclude)
+    {
+        if let Err(e) = globset::Glob::new(pattern) {
+            check(Err(ProcessingError::Configuration {
+                details: format!(
+                    "Invalid static file pattern '{}': {}",
+                    pattern, e
+                ),
+                path: None,
+                source: Some(Box::new(e)),
+            }));
+        }
+    }
+
+    // Validate frontmatter default patterns
+    for default in &config.frontmatter_defaults {
+        if let Err(e) = globset::Glob::new(&default.path) {
+            check(Err(ProcessingError::Configuration {
+                details: format!(
+                    "Invalid frontmatter default pattern '{}': {}",
+                    default.path, e
+                ),
+                path: None,
+                source: Some(Box::new(e)),
+            }));
+        }
+    }
+
+    // Validate CMS sources
+    for source in &config.cms {
+        let pointers = [&source.items, &source.slug, &source.body];
+        if let Some(pointer) = pointers
+            .into_iter()
+            .chain(source.fields.values())
+            .find(|p| !p.is_empty() && !p.starts_with('/'))
+        {
+            check(Err(ProcessingError::Configuration {
+                details: format!(
+                    "Invalid JSON pointer '{}' in CMS source '{}'",
+                    pointer, source.name
+                ),
+                path: None,
+                source: None,
+            }));
+        }
+        if source.name.trim().is_empty() || source.url.is_empty() {
+            check(Err(ProcessingError::Configuration {
+                details: "CMS sources need a name and a url"
+                    .to_string(),
+                path: None,
+                source: None,
+            }));
+        }
+    }
+
+    // Validate moved sections
+    if let Err(e) = RedirectMap::from_moves(&config.moved_sections, &[])
+    {
+        check(Err(ProcessingError::Configuration {
+            details: format!("Invalid moved section: {}", e),
+            path: None,
+            source: Some(Box::new(e)),
+        }));
+    }
+
+    // Validate error pages
+    for code in &config.error_pages {
+        if !(400..=599).contains(code) {
+            check(Err(ProcessingError::Configuration {
+                details: format!(
+                    "Error page {} is not an HTTP error status (400-599)",
+                    code
+                ),
+                path: None,
+                source: None,
+            }));
+        }
+    }
+
+    // Validate shortlinks
+    for (from, to) in &config.shortlinks {
+        let link = BTreeMap::from([(from.clone(), to.clone())]);
+        if let Err(e) = RedirectMap::from_shortlinks(&link) {
+            check(Err(ProcessingError::Configuration {
+                details: format!("Invalid shortlink: {}", e),
+                path: None,
+                source: Some(Box::new(e)),
+            }));
+        }
+    }
+
+    // Validate sanitization policy
+    check(validate_sanitization_policy(&config.content));
+
+    // Validate template environment variables
+    for name in &config.build_env {
+        check(validate_build_env_name(name));
+    }
+
+    // Validate memory limits
+    check(validate_memory_limits(&config.memory));
+
+    // Validate the published site URLs
+    for (name, base_url) in [
+        ("Base URL", &config.base_url),
+        ("SEO base URL", &config.seo.base_url),
+    ] {
+        if let Some(base_url) = base_url {
+            if !base_url.starts_with("https://")
+                && !base_url.starts_with("http://")
+            {
+                check(Err(ProcessingError::Configuration {
+                    details: format!(
+                        "{} must start with http:// or https://: {}",
+                        name, base_url
+                    ),
+                    path: None,
+                    source: None,
+                }));
+            }
+        }
+    }
+
+    if let Some(article_type) = &config.seo.article_type {
+        if !ARTICLE_TYPES.contains(&article_type.as_str()) {
+            check(Err(ProcessingError::Configuration {
+                details: format!(
+                    "Unknown SEO article type '{}', expected one of: {}",
+                    article_type,
+                    ARTICLE_TYPES.join(", ")
+                ),
+                path: None,
+                source: None,
+            }));
+        }
+    }
+
+    // Validate the site's languages
+    let i18n = &config.i18n;
+    if i18n.is_enabled()
+        && !i18n.languages.contains(&i18n.default_language)
+    {
+        check(Err(ProcessingError::Configuration {
+            details: format!(
+                "Default language '{}' is not one of the site's languages: {}",
+                i18n.default_language,
+                i18n.languages.join(", ")
+            ),
+            path: None,
+            source: None,
+        }));
+    }
+    for language in &i18n.languages {
+        let valid = !language.is_empty()
+            && language
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-');
+        if !valid {
+            check(Err(ProcessingError::Configuration {
+                details: format!(
+                    "Language '{}' must be a language tag, such as 'en' or 'pt-BR'",
+                    language
+                ),
+                path: None,
+                source: None,
+            }));
+        }
+    }
+
+    // Validate social cards
+    let cards = &config.social_cards;
+    if cards.width == 0
+        || cards.height == 0
+        || cards.width > MAX_SOCIAL_CARD_SIZE
+        || cards.height > MAX_SOCIAL_CARD_SIZE
+    {
+        check(Err(ProcessingError::Configuration {
+            details: format!(
+                "Social card size {}x{} must be between 1 and {} pixels a side",
+                cards.width, cards.height, MAX_SOCIAL_CARD_SIZE
+            ),
+            path: None,
+            source: None,
+        }));
+    }
+    for color in [&cards.background_color, &cards.text_color] {
+        if parse_hex_color(color).is_none() {
+            check(Err(ProcessingError::Configuration {
+                details: format!(
+                    "Social card colour '{}' must be of the form #rrggbb",
+                    color
+                ),
+                path: None,
+                source: None,
+            }));
+        }
+    }
+
+    // Validate screenshots
+    let screenshots = &config.screenshots;
+    if screenshots.width == 0 || screenshots.height == 0 {
+        check(Err(ProcessingError::Configuration {
+            details: format!(
+                "Screenshot size {}x{} must not be empty",
+                screenshots.width, screenshots.height
+            ),
+            path: None,
+            source: None,
+        }));
+    }
+    for page in &screenshots.pages {
+        if !page.starts_with('/') {
+            check(Err(ProcessingError::Configuration {
+                details: format!(
+                    "Screenshot page '{}' must be a site-relative URL starting with '/'",
+                    page
+                ),
+                path: None,
+                source: None,
+            }));
+        }
+    }
+
+    // Validate reserved paths
+    for reserved in &config.routes.reserved {
+        if reserved.trim_matches('/').is_empty() {
+            check(Err(ProcessingError::Configuration {
+                details: format!(
+                    "Reserved path '{}' would reserve the whole site",
+                    reserved
+                ),
+                path: None,
+                source: None,
+            }));
+        }
+    }
+
+    // Validate taxonomy synonyms
+    for (synonym, canonical) in &config.taxonomies.synonyms {
+        if canonical.trim().is_empty() {
+            check(Err(ProcessingError::Configuration {
+                details: format!(
+                    "Taxonomy synonym '{}' has an empty canonical term",
+                    synonym
+                ),
+                path: None,
+                source: None,
+            }));
+        } else if config.taxonomies.canonical(canonical) != canonical {
+            check(Err(ProcessingError::Configuration {
+                details: format!(
+                    "Taxonomy synonym '{}' maps to '{}', which is itself a synonym",
+                    synonym, canonical
+                ),
+                path: None,
+                source: None,
+            }));
+        }
+    }
+
+    // Validate link checking
+    if config.link_check.concurrency == 0 {
+        check(Err(ProcessingError::Configuration {
+            details: "Link check concurrency must be at least 1"
+                .to_string(),
+            path: None,
+            source: None,
+        }));
     }
 
     // Validate sizes
     if config.content.max_content_size > 100 * 1024 * 1024 {
-        return Err(ProcessingError::Configuration {
+        check(Err(ProcessingError::Configuration {
             details: "Content size limit too large".to_string(),
             path: None,
             source: None,
-        });
+        }));
+    }
+
+    problems
+}
+
+/// Validates the HTML sanitization allowlists.
+///
+/// Tag and attribute names must be plain HTML identifiers, event handler
+/// attributes (`on*`) and `style` are never permitted, and protocols must
+/// be valid URL schemes.
+fn validate_sanitization_policy(content: &ContentConfig) -> Result<()> {
+    let invalid = |details: String| ProcessingError::Configuration {
+        details,
+        path: None,
+        source: None,
+    };
+    let is_name = |name: &str| {
+        !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-')
+    };
+
+    for tag in &content.allowed_html_tags {
+        if !is_name(tag) {
+            return Err(invalid(format!("Invalid HTML tag '{}'", tag)));
+        }
+    }
+    for attribute in &content.allowed_html_attributes {
+        let lower = attribute.to_ascii_lowercase();
+        if !is_name(attribute) {
+            return Err(invalid(format!(
+                "Invalid HTML attribute '{}'",
+                attribute
+            )));
+        }
+        if lower.starts_with("on") || lower == "style" {
+            return Err(invalid(format!(
+                "HTML attribute '{}' cannot be allowed",
+                attribute
+            )));
+        }
+    }
+    for protocol in &content.allowed_protocols {
+        let valid = protocol
+            .chars()
+            .next()
+            .map_or(false, |c| c.is_ascii_alphabetic())
+            && protocol.chars().all(|c| {
+                c.is_ascii_alphanumeric()
+                    || matches!(c, '+' | '-' | '.')
+            });
+        if !valid {
+            return Err(invalid(format!(
+                "Invalid URL protocol '{}'",
+                protocol
+            )));
+        }
+        if protocol.eq_ignore_ascii_case("javascript") {
+            return Err(invalid(
+                "URL protocol 'javascript' cannot be allowed"
+                    .to_string(),
+            ));
+        }
     }
 
     Ok(())
 }
 
+/// Validates that the memory soft limit is not above the hard limit.
+fn validate_memory_limits(memory: &MemoryConfig) -> Result<()> {
+    if let (Some(soft), Some(hard)) =
+        (memory.soft_limit_mb, memory.hard_limit_mb)
+    {
+        if soft > hard {
+            return Err(ProcessingError::Configuration {
+                details: format!(
+                    "Memory soft limit ({} MB) is above the hard limit ({} MB)",
+                    soft, hard
+                ),
+                path: None,
+                source: None,
+            });
+        }
+    }
+    Ok(())
+}
+
 /// Validates a path for security and accessibility.
 fn validate_path(
     path: &Path,
@@ -1258,6 +4069,15 @@ fn apply_content_value(
                 }
             })?;
         }
+        "allowed_html_tags" => {
+            config.allowed_html_tags = split_list(value);
+        }
+        "allowed_html_attributes" => {
+            config.allowed_html_attributes = split_list(value);
+        }
+        "allowed_protocols" => {
+            config.allowed_protocols = split_list(value);
+        }
         _ => {
             let toml_value = TomlValue::String(value.to_string());
             if is_safe_config_value(&toml_value) {
@@ -1268,6 +4088,20 @@ fn apply_content_value(
     Ok(())
 }
 
+/// Splits a comma-separated or TOML array override value into trimmed
+/// entries.
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .split(',')
+        .map(|entry| entry.trim().trim_matches('"').trim())
+        .filter(|entry| !entry.is_empty())
+        .map(String::from)
+        .collect()
+}
+
 /// Applies template-specific configuration values.
 fn apply_template_value(
     config: &mut TemplateConfig,
@@ -1287,6 +4121,18 @@ fn apply_template_value(
                 }
             })?;
         }
+        "error_recovery" => {
+            config.error_recovery = value.parse().map_err(|e| {
+                ProcessingError::Configuration {
+                    details: format!(
+                        "Invalid error_recovery value '{}': {}",
+                        value, e
+                    ),
+                    path: None,
+                    source: None,
+                }
+            })?;
+        }
         "cache_templates" => {
             config.cache_templates = value.parse().map_err(|e| {
                 ProcessingError::Configuration {
@@ -1328,23 +4174,173 @@ fn apply_output_value(
     value: &str,
 ) -> Result<()> {
     match key {
-        "minify" => {
-            config.minify = value.parse().map_err(|e| {
-                ProcessingError::Configuration {
-                    details: format!(
-                        "Invalid minify value '{}': {}",
-                        value, e
-                    ),
-                    path: None,
-                    source: None,
-                }
-            })?;
+        "minify" => {
+            config.minify = value.parse().map_err(|e| {
+                ProcessingError::Configuration {
+                    details: format!(
+                        "Invalid minify value '{}': {}",
+                        value, e
+                    ),
+                    path: None,
+                    source: None,
+                }
+            })?;
+        }
+        "pretty_print" => {
+            config.pretty_print = value.parse().map_err(|e| {
+                ProcessingError::Configuration {
+                    details: format!(
+                        "Invalid pretty_print value '{}': {}",
+                        value, e
+                    ),
+                    path: None,
+                    source: None,
+                }
+            })?;
+        }
+        "asset_dir" => {
+            config.asset_dir =
+                Some(sanitize_path(&PathBuf::from(value)));
+        }
+        "max_output_size" => {
+            config.max_output_size = value.parse().map_err(|e| {
+                ProcessingError::Configuration {
+                    details: format!(
+                        "Invalid max_output_size value '{}': {}",
+                        value, e
+                    ),
+                    path: None,
+                    source: None,
+                }
+            })?;
+        }
+        "precompress.gzip" | "precompress.brotli" => {
+            let enabled = value.parse().map_err(|e| {
+                ProcessingError::Configuration {
+                    details: format!(
+                        "Invalid {} value '{}': {}",
+                        key, value, e
+                    ),
+                    path: None,
+                    source: None,
+                }
+            })?;
+            if key == "precompress.gzip" {
+                config.precompress.gzip = enabled;
+            } else {
+                config.precompress.brotli = enabled;
+            }
+        }
+        "build_report" => {
+            config.build_report = value.parse().map_err(|e| {
+                ProcessingError::Configuration {
+                    details: format!(
+                        "Invalid build_report value '{}': {}",
+                        value, e
+                    ),
+                    path: None,
+                    source: None,
+                }
+            })?;
+        }
+        "debug_artifacts" => {
+            config.debug_artifacts =
+                Some(value.parse().map_err(|e| {
+                    ProcessingError::Configuration {
+                        details: format!(
+                            "Invalid debug_artifacts value '{}': {}",
+                            value, e
+                        ),
+                        path: None,
+                        source: None,
+                    }
+                })?);
+        }
+        "atomic_swap" => {
+            config.atomic_swap = match value {
+                "rename" => Some(SwapStrategy::Rename),
+                "symlink" => Some(SwapStrategy::Symlink),
+                "none" | "false" => None,
+                _ => {
+                    return Err(ProcessingError::Configuration {
+                        details: format!(
+                            "Invalid atomic_swap value '{}': expected rename, symlink or none",
+                            value
+                        ),
+                        path: None,
+                        source: None,
+                    })
+                }
+            };
+        }
+        _ => {
+            if let Some(option) = key.strip_prefix("minify_options.") {
+                return apply_minify_value(
+                    &mut config.minify_options,
+                    option,
+                    value,
+                );
+            }
+            let toml_value = TomlValue::String(value.to_string());
+            if is_safe_config_value(&toml_value) {
+                _ = config.options.insert(key.to_string(), toml_value);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Applies a single minification toggle, rejecting unknown options.
+fn apply_minify_value(
+    options: &mut MinifyOptions,
+    key: &str,
+    value: &str,
+) -> Result<()> {
+    let invalid = |details: String| ProcessingError::Configuration {
+        details,
+        path: None,
+        source: None,
+    };
+    let enabled: bool = value.parse().map_err(|e| {
+        invalid(format!(
+            "Invalid minify_options.{} value '{}': {}",
+            key, value, e
+        ))
+    })?;
+
+    let mut table = match TomlValue::try_from(*options) {
+        Ok(TomlValue::Table(table)) => table,
+        _ => {
+            return Err(invalid(
+                "Failed to serialize minify options".to_string(),
+            ))
+        }
+    };
+    if !table.contains_key(key) {
+        return Err(invalid(format!("Unknown minify option: {}", key)));
+    }
+    _ = table.insert(key.to_string(), TomlValue::Boolean(enabled));
+    *options = TomlValue::Table(table).try_into().map_err(|e| {
+        invalid(format!("Invalid minify options: {}", e))
+    })?;
+    Ok(())
+}
+
+/// Applies static file configuration values.
+fn apply_static_value(
+    config: &mut StaticConfig,
+    key: &str,
+    value: &str,
+) -> Result<()> {
+    match key {
+        "dir" => {
+            config.dir = sanitize_path(&PathBuf::from(value));
         }
-        "pretty_print" => {
-            config.pretty_print = value.parse().map_err(|e| {
+        "include_dotfiles" => {
+            config.include_dotfiles = value.parse().map_err(|e| {
                 ProcessingError::Configuration {
                     details: format!(
-                        "Invalid pretty_print value '{}': {}",
+                        "Invalid include_dotfiles value '{}': {}",
                         value, e
                     ),
                     path: None,
@@ -1352,30 +4348,51 @@ fn apply_output_value(
                 }
             })?;
         }
-        "asset_dir" => {
-            config.asset_dir =
-                Some(sanitize_path(&PathBuf::from(value)));
+        _ => {
+            return Err(ProcessingError::Configuration {
+                details: format!(
+                    "Unknown static configuration key: {}",
+                    key
+                ),
+                path: None,
+                source: None,
+            });
         }
-        "max_output_size" => {
-            config.max_output_size = value.parse().map_err(|e| {
+    }
+    Ok(())
+}
+
+/// Applies plugin-specific configuration values (`<name>.enabled`).
+fn apply_plugin_value(
+    plugins: &mut HashMap<String, PluginConfig>,
+    key: &str,
+    value: &str,
+) -> Result<()> {
+    match key.split_once('.') {
+        Some((name, "enabled")) => {
+            let enabled = value.parse().map_err(|e| {
                 ProcessingError::Configuration {
                     details: format!(
-                        "Invalid max_output_size value '{}': {}",
-                        value, e
+                        "Invalid plugins.{} value '{}': {}",
+                        key, value, e
                     ),
                     path: None,
                     source: None,
                 }
             })?;
+            plugins.entry(name.to_string()).or_default().enabled =
+                enabled;
+            Ok(())
         }
-        _ => {
-            let toml_value = TomlValue::String(value.to_string());
-            if is_safe_config_value(&toml_value) {
-                _ = config.options.insert(key.to_string(), toml_value);
-            }
-        }
+        _ => Err(ProcessingError::Configuration {
+            details: format!(
+                "Unknown plugin configuration key: {}",
+                key
+            ),
+            path: None,
+            source: None,
+        }),
     }
-    Ok(())
 }
 
 /// Returns the default content directory path.
@@ -1393,11 +4410,159 @@ fn default_template_dir() -> PathBuf {
     PathBuf::from("templates")
 }
 
+/// Returns the default static files directory path.
+fn default_precompress_extensions() -> Vec<String> {
+    vec!["html", "css", "js", "mjs", "svg", "xml", "json"]
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+fn default_precompress_min_size() -> u64 {
+    1024 // 1KB
+}
+
+fn default_a11y_error() -> A11ySeverity {
+    A11ySeverity::Error
+}
+
+fn default_a11y_warning() -> A11ySeverity {
+    A11ySeverity::Warning
+}
+
+fn default_language() -> String {
+    "en".to_string()
+}
+
+fn default_screenshot_browser() -> String {
+    "chromium".to_string()
+}
+
+fn default_screenshot_width() -> u32 {
+    1280
+}
+
+fn default_screenshot_height() -> u32 {
+    800
+}
+
+fn default_screenshot_dir() -> PathBuf {
+    PathBuf::from("screenshots")
+}
+
+fn default_social_card_background_color() -> String {
+    "#1e293b".to_string()
+}
+
+fn default_social_card_text_color() -> String {
+    "#ffffff".to_string()
+}
+
+fn default_social_card_width() -> u32 {
+    1200
+}
+
+fn default_social_card_height() -> u32 {
+    630
+}
+
+fn default_link_check_concurrency() -> usize {
+    8
+}
+
+fn default_link_check_host_interval_ms() -> u64 {
+    250
+}
+
+fn default_link_check_timeout_secs() -> u64 {
+    10
+}
+
+fn default_link_check_cache_hours() -> u64 {
+    24
+}
+
+fn default_static_dir() -> PathBuf {
+    PathBuf::from("static")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use tempfile::TempDir;
 
+    #[test]
+    fn test_config_auto_reload() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.toml");
+        // Each version is stamped a second after the one before, rather
+        // than written after a pause, so coarse filesystem timestamps
+        // cannot hide an edit.
+        let started = SystemTime::now();
+        let version = std::cell::Cell::new(0);
+        let write = |content_dir: &str, theme: &str| {
+            fs::write(
+                &path,
+                format!(
+                    "content_dir = \"{}\"\ntemplate_dir = \"src\"\n\
+                     [custom]\ntheme = \"{}\"\n",
+                    content_dir, theme
+                ),
+            )
+            .unwrap();
+            version.set(version.get() + 1);
+            fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(
+                    started + Duration::from_secs(version.get()),
+                )
+                .unwrap();
+        };
+        write("src", "light");
+
+        // `with_file` only takes paths under the working directory, so
+        // the temporary file is set directly.
+        let mut builder = ConfigBuilder::new()
+            .with_auto_reload(true)
+            .with_reload_interval(Duration::from_secs(1));
+        builder.config_file = Some(path.clone());
+        let config = builder.build().unwrap();
+        let theme = || {
+            config
+                .read()
+                .get_custom::<String>("theme")
+                .unwrap()
+                .unwrap()
+        };
+        let mut copy = config.read().clone();
+        assert!(!copy.needs_reload());
+        write("src", "sepia");
+        assert!(copy.reload_if_needed().unwrap());
+        assert_eq!(
+            copy.get_custom::<String>("theme").unwrap().unwrap(),
+            "sepia"
+        );
+        assert!(!copy.reload_if_needed().unwrap());
+
+        let watcher =
+            ConfigWatcher::spawn(Arc::clone(&config)).unwrap();
+        write("src", "dark");
+        for _ in 0..50 {
+            if theme() == "dark" {
+                break;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        assert_eq!(theme(), "dark");
+
+        write("missing", "broken");
+        thread::sleep(Duration::from_millis(1500));
+        assert_eq!(theme(), "dark");
+        drop(watcher);
+    }
+
     #[test]
     fn test_path_sanitization() {
         let path = Path::new("../../../etc/passwd");
@@ -1433,6 +4598,181 @@ fn test_safe_config_value() {
         assert!(!is_safe_config_value(&TomlValue::Table(large_table)));
     }
 
+    #[test]
+    fn test_layered_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path().join("config.toml");
+        let local = temp_dir.path().join("local.toml");
+        fs::write(
+            &base,
+            "content_dir = \"content\"\n\
+             [memory]\nsoft_limit_mb = 512\nhard_limit_mb = 1024\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("config.production.toml"),
+            "output_dir = \"dist\"\n[memory]\nhard_limit_mb = 2048\n",
+        )
+        .unwrap();
+        fs::write(&local, "content_dir = \"posts\"\n").unwrap();
+
+        let config = load_layered(
+            &base,
+            &[local],
+            Some(Profile::Production),
+            MAX_CONFIG_SIZE,
+        )
+        .unwrap();
+        assert_eq!(config.content_dir, PathBuf::from("posts"));
+        assert_eq!(config.output_dir, PathBuf::from("dist"));
+        assert_eq!(config.memory.soft_limit_mb, Some(512));
+        assert_eq!(config.memory.hard_limit_mb, Some(2048));
+
+        let config =
+            load_layered(&base, &[], None, MAX_CONFIG_SIZE).unwrap();
+        assert_eq!(config.output_dir, default_output_dir());
+
+        let mut content = fs::read_to_string(&base).unwrap();
+        content.insert_str(0, "profile = \"production\"\n");
+        fs::write(&base, content).unwrap();
+        let config =
+            load_layered(&base, &[], None, MAX_CONFIG_SIZE).unwrap();
+        assert_eq!(config.profile, Profile::Production);
+        assert_eq!(config.output_dir, PathBuf::from("dist"));
+
+        let missing = temp_dir.path().join("missing.toml");
+        assert!(load_layered(&base, &[missing], None, MAX_CONFIG_SIZE)
+            .is_err());
+    }
+
+    #[test]
+    fn test_config_env_interpolation() {
+        let path = Path::new("config.toml");
+        env::set_var("NUCLEUS_TEST_SITE_URL", "https://example.com");
+        let mut value: TomlValue = toml::from_str(
+            r#"
+            base_url = "${NUCLEUS_TEST_SITE_URL}/blog"
+            output_dir = "${NUCLEUS_TEST_UNSET:-public}"
+            literal = "$${NUCLEUS_TEST_SITE_URL}"
+            tags = ["${NUCLEUS_TEST_SITE_URL}"]
+            "#,
+        )
+        .unwrap();
+        interpolate_env(&mut value, path).unwrap();
+        assert_eq!(
+            value["base_url"].as_str(),
+            Some("https://example.com/blog")
+        );
+        assert_eq!(value["output_dir"].as_str(), Some("public"));
+        assert_eq!(
+            value["literal"].as_str(),
+            Some("${NUCLEUS_TEST_SITE_URL}")
+        );
+        assert_eq!(
+            value["tags"][0].as_str(),
+            Some("https://example.com")
+        );
+
+        for text in [
+            "${NUCLEUS_TEST_UNSET}",
+            "${HOME}",
+            "${NOT-A-NAME}",
+            "${NUCLEUS_TEST_SITE_URL",
+        ] {
+            assert!(interpolate_str(text, path).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_config_json_schema() {
+        let schema = Config::json_schema().unwrap();
+        assert_eq!(schema["title"], "Config");
+
+        let properties = &schema["properties"];
+        for key in
+            ["content", "template", "output", "static", "plugins"]
+        {
+            assert!(properties[key].is_object(), "{}", key);
+        }
+        assert!(properties.get("auto_reload").is_none());
+        assert_eq!(properties["timezone"]["type"], "string");
+
+        let output = &schema["definitions"]["OutputConfig"];
+        assert_eq!(
+            output["properties"]["pretty_print"]["default"],
+            true
+        );
+        let strategies: Vec<_> = schema["definitions"]["SwapStrategy"]
+            ["oneOf"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|variant| variant["enum"][0].clone())
+            .collect();
+        assert_eq!(strategies, ["rename", "symlink"]);
+    }
+
+    #[test]
+    fn test_config_problems() {
+        let mut config = Config {
+            content_dir: PathBuf::from("src"),
+            template_dir: PathBuf::from("src"),
+            ..Config::default()
+        };
+        config.content.extensions.clear();
+        config.static_files.exclude = vec!["[invalid".to_string()];
+        config.build_env = vec!["HOME".to_string()];
+        config.memory.soft_limit_mb = Some(200);
+        config.memory.hard_limit_mb = Some(100);
+
+        let problems = config.problems();
+        assert_eq!(problems.len(), 4, "{:?}", problems);
+        assert!(problems[0].to_string().contains("extensions"));
+        assert_eq!(
+            config.validate().unwrap_err().to_string(),
+            problems[0].to_string()
+        );
+
+        config.content.extensions = default_extensions();
+        config.static_files.exclude.clear();
+        config.build_env.clear();
+        config.memory = MemoryConfig::default();
+        assert!(config.problems().is_empty());
+    }
+
+    #[test]
+    fn test_config_to_masked_toml() {
+        let config: Config = toml::from_str(
+            r#"
+            [custom]
+            api_key = "abc123"
+            keywords = ["rust"]
+
+            [plugins.deploy]
+            auth-tokens = ["t1"]
+            "#,
+        )
+        .unwrap();
+        let shown: TomlValue =
+            toml::from_str(&config.to_masked_toml().unwrap()).unwrap();
+        assert_eq!(
+            shown["custom"]["api_key"].as_str(),
+            Some(MASKED_VALUE)
+        );
+        assert_eq!(
+            shown["custom"]["keywords"][0].as_str(),
+            Some("rust")
+        );
+        assert_eq!(
+            shown["plugins"]["deploy"]["auth-tokens"].as_str(),
+            Some(MASKED_VALUE)
+        );
+        assert_eq!(
+            shown["output"]["pretty_print"].as_bool(),
+            Some(true)
+        );
+    }
+
     #[test]
     fn test_config_size_limit() {
         let temp_dir = TempDir::new().unwrap();
@@ -1440,7 +4780,9 @@ fn test_config_size_limit() {
         let large_content = "x".repeat(MAX_CONFIG_SIZE + 1);
         fs::write(&config_file, large_content).unwrap();
 
-        assert!(load_from_file(&config_file, MAX_CONFIG_SIZE).is_err());
+        assert!(
+            read_config_table(&config_file, MAX_CONFIG_SIZE).is_err()
+        );
     }
 
     #[test]
@@ -1576,4 +4918,683 @@ fn test_config_set_custom_with_invalid_key() {
         // Ensure it returns an error due to the invalid key
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_static_config_from_toml() {
+        let config: Config = toml::from_str(
+            r#"
+            [static]
+            include = ["**/*.css", "robots.txt"]
+            exclude = ["drafts/**"]
+            include_dotfiles = true
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.static_files.dir, PathBuf::from("static"));
+        assert_eq!(config.static_files.include.len(), 2);
+        assert_eq!(config.static_files.exclude, vec!["drafts/**"]);
+        assert!(config.static_files.include_dotfiles);
+    }
+
+    #[test]
+    fn test_invalid_static_pattern() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = Config {
+            content_dir: temp_dir.path().to_path_buf(),
+            template_dir: temp_dir.path().to_path_buf(),
+            ..Config::default()
+        };
+        config.content.extensions = default_extensions();
+        config.static_files.exclude = vec!["[invalid".to_string()];
+
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_cms_from_toml() {
+        let mut config: Config = toml::from_str(
+            r#"
+            content_dir = "src"
+            template_dir = "src"
+
+            [[cms]]
+            name = "blog"
+            url = "https://cms.example.com/graphql"
+            query = "{ posts { slug title body } }"
+            items = "/data/posts"
+            format = "html"
+            fields = { title = "/title" }
+            "#,
+        )
+        .unwrap();
+        let source = &config.cms[0];
+        assert_eq!(source.slug, "/slug");
+        assert_eq!(source.body, "/body");
+        assert_eq!(source.format, CmsFormat::Html);
+        assert_eq!(source.fields["title"], "/title");
+        assert!(config.validate().is_ok());
+
+        config.cms[0].items = "data.posts".to_string();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_citations_from_toml() {
+        let config: Config = toml::from_str(
+            r#"
+            content_dir = "src"
+            template_dir = "src"
+
+            [citations]
+            style = "numeric"
+            "#,
+        )
+        .unwrap();
+        let citations = config.citations.unwrap();
+        assert_eq!(citations.style, CitationStyle::Numeric);
+        assert_eq!(
+            citations.bibliography,
+            PathBuf::from("data/references.bib")
+        );
+        assert_eq!(citations.title, "References");
+        assert!(toml::from_str::<CitationConfig>(r#"style = "apa""#)
+            .is_err());
+    }
+
+    #[test]
+    fn test_shortlinks_from_toml() {
+        let mut config: Config = toml::from_str(
+            r#"
+            content_dir = "src"
+            template_dir = "src"
+
+            [shortlinks]
+            "/go/discord" = "https://discord.gg/example"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.shortlinks["/go/discord"],
+            "https://discord.gg/example"
+        );
+        assert!(config.validate().is_ok());
+
+        _ = config
+            .shortlinks
+            .insert("go/relative".to_string(), "/x/".to_string());
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_production_disables_error_recovery() {
+        let mut config = Config::default();
+        config.template.error_recovery = true;
+
+        enforce_production_security(&mut config).unwrap();
+        assert!(!config.template.error_recovery);
+    }
+
+    #[test]
+    fn test_sanitization_policy() {
+        let config: Config = toml::from_str(
+            r#"
+            [content]
+            allowed_html_tags = ["p", "details", "summary", "video"]
+            allowed_protocols = ["https"]
+            "#,
+        )
+        .unwrap();
+        assert!(validate_sanitization_policy(&config.content).is_ok());
+        assert_eq!(config.content.allowed_protocols, vec!["https"]);
+        assert!(config
+            .content
+            .allowed_html_attributes
+            .contains(&"href".to_string()));
+
+        let mut content = ContentConfig::default();
+        content.allowed_html_attributes.push("onclick".to_string());
+        assert!(validate_sanitization_policy(&content).is_err());
+
+        let mut content = ContentConfig::default();
+        content.allowed_protocols.push("javascript".to_string());
+        assert!(validate_sanitization_policy(&content).is_err());
+
+        let mut content = ContentConfig::default();
+        content.allowed_html_tags.push("<script>".to_string());
+        assert!(validate_sanitization_policy(&content).is_err());
+
+        apply_content_value(
+            &mut content,
+            "allowed_html_tags",
+            "p, video",
+        )
+        .unwrap();
+        assert_eq!(content.allowed_html_tags, vec!["p", "video"]);
+        apply_content_value(
+            &mut content,
+            "allowed_protocols",
+            r#"["https", "tel"]"#,
+        )
+        .unwrap();
+        assert_eq!(content.allowed_protocols, vec!["https", "tel"]);
+    }
+
+    #[test]
+    fn test_minify_options() {
+        let config: Config = toml::from_str(
+            r#"
+            [output.minify_options]
+            keep_comments = true
+            minify_js = false
+            "#,
+        )
+        .unwrap();
+        let options = config.output.minify_options;
+        assert!(options.keep_comments);
+        assert!(!options.minify_js);
+        assert!(options.minify_css);
+
+        let mut output = OutputConfig::default();
+        apply_output_value(
+            &mut output,
+            "minify_options.keep_closing_tags",
+            "true",
+        )
+        .unwrap();
+        assert!(output.minify_options.keep_closing_tags);
+        assert!(apply_output_value(
+            &mut output,
+            "minify_options.unknown",
+            "true"
+        )
+        .is_err());
+        assert!(apply_output_value(
+            &mut output,
+            "minify_options.keep_comments",
+            "yes"
+        )
+        .is_err());
+
+        assert!(
+            toml::from_str::<MinifyOptions>("keep_all = true").is_err()
+        );
+    }
+
+    #[test]
+    fn test_timezone() {
+        let config: Config =
+            toml::from_str(r#"timezone = "Australia/Sydney""#).unwrap();
+        assert_eq!(config.timezone.name(), "Australia/Sydney");
+        assert_eq!(Config::default().timezone.name(), "UTC");
+        assert!(toml::from_str::<Config>(r#"timezone = "Nowhere""#)
+            .is_err());
+
+        let mut config = Config::default();
+        apply_config_value(&mut config, "timezone", &"Asia/Kolkata")
+            .unwrap();
+        assert_eq!(config.timezone.name(), "Asia/Kolkata");
+        assert!(apply_config_value(
+            &mut config,
+            "timezone",
+            &"Nowhere"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_precompress_config() {
+        let config: Config = toml::from_str(
+            r#"
+            [output.precompress]
+            brotli = true
+            "#,
+        )
+        .unwrap();
+        let precompress = &config.output.precompress;
+        assert!(precompress.brotli && !precompress.gzip);
+        assert!(precompress.extensions.contains(&"css".to_string()));
+        assert!(!OutputConfig::default().precompress.is_enabled());
+
+        let mut output = OutputConfig::default();
+        apply_output_value(&mut output, "precompress.gzip", "true")
+            .unwrap();
+        assert!(output.precompress.gzip);
+    }
+
+    #[test]
+    fn test_atomic_swap_config() {
+        let config: Config = toml::from_str(
+            r#"
+            [output]
+            atomic_swap = "symlink"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.output.atomic_swap,
+            Some(SwapStrategy::Symlink)
+        );
+        assert_eq!(OutputConfig::default().atomic_swap, None);
+
+        let mut output = OutputConfig::default();
+        apply_output_value(&mut output, "atomic_swap", "rename")
+            .unwrap();
+        assert_eq!(output.atomic_swap, Some(SwapStrategy::Rename));
+        apply_output_value(&mut output, "atomic_swap", "none").unwrap();
+        assert_eq!(output.atomic_swap, None);
+        assert!(apply_output_value(&mut output, "atomic_swap", "copy")
+            .is_err());
+    }
+
+    #[test]
+    fn test_build_report_config() {
+        let config: Config = toml::from_str(
+            r#"
+            [output]
+            build_report = true
+            "#,
+        )
+        .unwrap();
+        assert!(config.output.build_report);
+        assert!(!OutputConfig::default().build_report);
+
+        let mut output = OutputConfig::default();
+        apply_output_value(&mut output, "build_report", "true")
+            .unwrap();
+        assert!(output.build_report);
+        assert!(apply_output_value(&mut output, "build_report", "yes")
+            .is_err());
+    }
+
+    #[test]
+    fn test_debug_artifacts_config() {
+        let mut config = Config::default();
+        assert!(config.debug_artifacts());
+        config.profile = Profile::Production;
+        assert!(!config.debug_artifacts());
+
+        let config: Config = toml::from_str(
+            r#"
+            profile = "production"
+
+            [output]
+            debug_artifacts = true
+            "#,
+        )
+        .unwrap();
+        assert!(config.debug_artifacts());
+
+        let mut output = OutputConfig::default();
+        apply_output_value(&mut output, "debug_artifacts", "false")
+            .unwrap();
+        assert_eq!(output.debug_artifacts, Some(false));
+        assert!(apply_output_value(
+            &mut output,
+            "debug_artifacts",
+            "no"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_memory_config() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [memory]
+            soft_limit_mb = 1536
+            hard_limit_mb = 2048
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.memory.soft_limit_mb, Some(1536));
+        assert_eq!(config.memory.hard_limit_mb, Some(2048));
+        assert_eq!(Config::default().memory, MemoryConfig::default());
+        assert!(toml::from_str::<MemoryConfig>("limit = 1").is_err());
+
+        assert!(validate_memory_limits(&config.memory).is_ok());
+        config.memory.soft_limit_mb = Some(4096);
+        let error = validate_memory_limits(&config.memory).unwrap_err();
+        assert!(error.to_string().contains("above the hard limit"));
+    }
+
+    #[test]
+    fn test_link_check_config() {
+        let config: Config = toml::from_str(
+            r#"
+            [link_check]
+            concurrency = 2
+            skip_domains = ["example.com"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.link_check.concurrency, 2);
+        assert_eq!(config.link_check.skip_domains, ["example.com"]);
+        assert_eq!(
+            config.link_check.timeout_secs,
+            LinkCheckConfig::default().timeout_secs
+        );
+        assert!(
+            toml::from_str::<LinkCheckConfig>("retries = 1").is_err()
+        );
+    }
+
+    #[test]
+    fn test_seo_config() {
+        let mut config: Config = toml::from_str(
+            r#"
+            content_dir = "src"
+            template_dir = "src"
+
+            [seo]
+            base_url = "https://example.com"
+            twitter_site = "@example"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.seo.base_url.as_deref(),
+            Some("https://example.com")
+        );
+        assert_eq!(config.seo.twitter_card, None);
+        assert!(config.problems().is_empty());
+
+        config.seo.base_url = Some("example.com".to_string());
+        assert_eq!(config.problems().len(), 1);
+
+        config.seo.base_url = None;
+        config.base_url = Some("ftp://example.com".to_string());
+        assert_eq!(config.problems().len(), 1);
+
+        config.base_url = Some("https://example.com".to_string());
+        config.seo.article_type = Some("Recipe".to_string());
+        assert_eq!(config.problems().len(), 1);
+        config.seo.article_type = Some("NewsArticle".to_string());
+        assert!(config.problems().is_empty());
+    }
+
+    #[test]
+    fn test_routes_config() {
+        let config: Config = toml::from_str(
+            r#"
+            [routes]
+            reserved = ["/api/", "/status"]
+            on_collision = "suffix"
+            "#,
+        )
+        .unwrap();
+        let routes = &config.routes;
+        assert_eq!(routes.on_collision, CollisionPolicy::Suffix);
+        assert_eq!(routes.reserves("/api/users.html"), Some("/api/"));
+        assert_eq!(routes.reserves("/api/"), Some("/api/"));
+        assert_eq!(routes.reserves("/status.html"), Some("/status"));
+        assert_eq!(routes.reserves("/status/"), Some("/status"));
+        assert_eq!(routes.reserves("/apis.html"), None);
+        assert_eq!(routes.reserves("/status/ok.html"), None);
+        assert_eq!(
+            RoutesConfig::default().on_collision,
+            CollisionPolicy::Error
+        );
+    }
+
+    #[test]
+    fn test_taxonomy_config() {
+        let mut config: Config = toml::from_str(
+            r#"
+            content_dir = "src"
+            template_dir = "src"
+
+            [taxonomies.synonyms]
+            rustlang = "rust"
+            "Rust-Lang" = "rust"
+            "#,
+        )
+        .unwrap();
+        let taxonomies = &config.taxonomies;
+        assert_eq!(taxonomies.canonical("RustLang"), "rust");
+        assert_eq!(taxonomies.canonical("rust-lang"), "rust");
+        assert_eq!(taxonomies.canonical("go"), "go");
+
+        let mut frontmatter = serde_json::json!({
+            "tags": ["rustlang", "cli", "rust", "Rust-Lang"],
+            "categories": "RUSTLANG",
+            "keywords": ["rustlang"],
+        });
+        taxonomies.apply(frontmatter.as_object_mut().unwrap());
+        assert_eq!(
+            frontmatter,
+            serde_json::json!({
+                "tags": ["rust", "cli"],
+                "categories": "rust",
+                "keywords": ["rustlang"],
+            })
+        );
+        assert!(config.problems().is_empty());
+
+        _ = config
+            .taxonomies
+            .synonyms
+            .insert("rs".to_string(), "rustlang".to_string());
+        _ = config
+            .taxonomies
+            .synonyms
+            .insert("golang".to_string(), " ".to_string());
+        assert_eq!(config.problems().len(), 2);
+    }
+
+    #[test]
+    fn test_deploy_config() {
+        let config: Config = toml::from_str(
+            r#"
+            [deploy]
+            preview_dir = "/mnt/preview"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.deploy.preview_dir.as_deref(),
+            Some(Path::new("/mnt/preview"))
+        );
+        assert_eq!(Config::default().deploy.preview_dir, None);
+        assert!(
+            toml::from_str::<DeployConfig>("target = \"x\"").is_err()
+        );
+    }
+
+    #[test]
+    fn test_i18n_config() {
+        let mut config: Config = toml::from_str(
+            r#"
+            content_dir = "src"
+            template_dir = "src"
+
+            [i18n]
+            languages = ["en", "pt-BR"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.i18n.default_language, "en");
+        assert!(config.i18n.is_enabled());
+        assert!(!I18nConfig::default().is_enabled());
+        assert!(config.problems().is_empty());
+
+        config.i18n.default_language = "fr".to_string();
+        assert_eq!(config.problems().len(), 1);
+        config.i18n.languages.push("fr/../x".to_string());
+        assert_eq!(config.problems().len(), 2);
+        config.i18n.languages.push("fr".to_string());
+        assert_eq!(config.problems().len(), 1);
+    }
+
+    #[test]
+    fn test_screenshot_config() {
+        let mut config: Config = toml::from_str(
+            r#"
+            content_dir = "src"
+            template_dir = "src"
+
+            [screenshots]
+            pages = ["/", "/about.html"]
+            width = 390
+            "#,
+        )
+        .unwrap();
+        assert!(config.screenshots.is_enabled());
+        assert_eq!(config.screenshots.browser, "chromium");
+        assert_eq!(config.screenshots.height, 800);
+        assert!(!ScreenshotConfig::default().is_enabled());
+        assert!(config.problems().is_empty());
+
+        config.screenshots.pages.push("about.html".to_string());
+        config.screenshots.height = 0;
+        assert_eq!(config.problems().len(), 2);
+    }
+
+    #[test]
+    fn test_social_card_config() {
+        let mut config: Config = toml::from_str(
+            r##"
+            content_dir = "src"
+            template_dir = "src"
+
+            [social_cards]
+            font = "fonts/card.ttf"
+            text_color = "#FFEEDD"
+            "##,
+        )
+        .unwrap();
+        assert!(config.social_cards.is_enabled());
+        assert_eq!(config.social_cards.width, 1200);
+        assert!(!SocialCardConfig::default().is_enabled());
+        assert!(config.problems().is_empty());
+        assert_eq!(parse_hex_color("#FFEEDD"), Some([255, 238, 221]));
+        assert_eq!(parse_hex_color("#fff"), None);
+        assert_eq!(parse_hex_color("1e293b"), None);
+
+        config.social_cards.background_color = "navy".to_string();
+        config.social_cards.height = 0;
+        assert_eq!(config.problems().len(), 2);
+    }
+
+    #[test]
+    fn test_moved_sections_config() {
+        let mut config: Config = toml::from_str(
+            r#"
+            content_dir = "src"
+            template_dir = "src"
+
+            [moved_sections]
+            "/blog/" = "/posts/"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.moved_sections["/blog/"], "/posts/");
+        assert!(config.problems().is_empty());
+
+        _ = config
+            .moved_sections
+            .insert("/news".to_string(), "/posts/".to_string());
+        assert_eq!(config.problems().len(), 1);
+    }
+
+    #[test]
+    fn test_error_pages_config() {
+        let mut config: Config = toml::from_str(
+            r#"
+            content_dir = "src"
+            template_dir = "src"
+            error_pages = [410, 500]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.error_pages, [410, 500]);
+        assert!(config.problems().is_empty());
+
+        config.error_pages.push(302);
+        assert_eq!(config.problems().len(), 1);
+    }
+
+    #[test]
+    fn test_a11y_config() {
+        let config: Config = toml::from_str(
+            r#"
+            [a11y]
+            skipped_heading = "error"
+            missing_lang = "off"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.a11y.skipped_heading, A11ySeverity::Error);
+        assert_eq!(config.a11y.missing_lang, A11ySeverity::Off);
+        assert_eq!(config.a11y.missing_alt, A11ySeverity::Error);
+        assert!(toml::from_str::<A11yConfig>(
+            r#"missing_alt = "fatal""#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_build_env() {
+        let config: Config =
+            toml::from_str(r#"build_env = ["CI", "GIT_COMMIT_SHA"]"#)
+                .unwrap();
+        assert_eq!(config.build_env, ["CI", "GIT_COMMIT_SHA"]);
+
+        for name in
+            ["PATH", "GITHUB_TOKEN", "AWS_SECRET_ACCESS_KEY", "ci"]
+        {
+            assert!(snapshot_build_env(&[name.to_string()]).is_err());
+        }
+
+        env::set_var("NUCLEUSFLOW_TEST_BUILD_ID", "42");
+        let snapshot = snapshot_build_env(&[
+            "NUCLEUSFLOW_TEST_BUILD_ID".to_string(),
+            "NUCLEUSFLOW_TEST_UNSET".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot["NUCLEUSFLOW_TEST_BUILD_ID"], "42");
+
+        let mut config = Config::default();
+        apply_config_value(&mut config, "build_env", &"[CI, BUILD_ID]")
+            .unwrap();
+        assert_eq!(config.build_env, ["CI", "BUILD_ID"]);
+        assert!(apply_config_value(&mut config, "build_env", &"HOME")
+            .is_err());
+    }
+
+    #[test]
+    fn test_plugin_config() {
+        let config: Config = toml::from_str(
+            r#"
+            [plugins.gallery]
+            enabled = false
+            columns = 3
+
+            [plugins.search]
+            "#,
+        )
+        .unwrap();
+        assert!(!config.plugins["gallery"].enabled);
+        assert_eq!(
+            config.plugins["gallery"].options["columns"],
+            TomlValue::Integer(3)
+        );
+        assert!(config.plugins["search"].enabled);
+
+        let mut config = Config::default();
+        apply_config_value(
+            &mut config,
+            "plugins.search.enabled",
+            &false,
+        )
+        .unwrap();
+        assert!(!config.plugins["search"].enabled);
+        assert!(apply_config_value(
+            &mut config,
+            "plugins.search.colour",
+            &"red"
+        )
+        .is_err());
+    }
 }
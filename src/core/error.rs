@@ -3,7 +3,55 @@
 //! This module defines custom error types for various components of the
 //! NucleusFlow static site generator. The `thiserror` crate is used to
 //! simplify error creation and ensure consistent handling across the library.
-
+//!
+//! Errors can point at the source they came from, such as a frontmatter
+//! line or a template expression, with [`ProcessingError::with_snippet`].
+//! `ProcessingError` implements [`miette::Diagnostic`], so such errors
+//! render with the offending span underlined:
+//!
+//! ```text
+//!   × Validation failed: Invalid date 'never'
+//!    ╭─[content/post.md:3:1]
+//!  2 │ title: Post
+//!  3 │ date: never
+//!    · ─────┬─────
+//!    ·      ╰── invalid `date`
+//!  4 │ ---
+//!    ╰────
+//! ```
+//!
+//! # Error Codes
+//!
+//! Every error has a stable code, returned by [`ProcessingError::code`]
+//! and printed by the CLI, so scripts and bug reports can match errors
+//! without relying on their wording. Codes are never reused or
+//! renumbered.
+//!
+//! | Code     | Variant                     | Meaning                             |
+//! |----------|-----------------------------|-------------------------------------|
+//! | `NF1001` | `ContentProcessing`         | Content could not be processed      |
+//! | `NF1002` | `FileOperation`             | A file operation failed             |
+//! | `NF1003` | `FileNotFound`              | A required file does not exist      |
+//! | `NF1004` | `TemplateProcessing`        | A template failed to load or render |
+//! | `NF1005` | `Configuration`             | The configuration is invalid        |
+//! | `NF1006` | `Validation`                | Content or input failed validation  |
+//! | `NF1007` | `OutputGeneration`          | Output could not be generated       |
+//! | `NF1008` | `Serialization`             | Data could not be (de)serialized    |
+//! | `NF1009` | `Plugin`                    | A plugin failed                     |
+//! | `NF1010` | `Internal`, `InternalError` | An unexpected internal failure      |
+//! | `NF1011` | `IOError`                   | Reading or writing a path failed    |
+//! | `NF1012` | `BuildFailed`               | Pages were skipped by a build       |
+//! | `NF1013` | `MemoryLimitExceeded`       | The build passed its memory limit   |
+//!
+//! An error pointing at a source span keeps the code of the error it
+//! wraps.
+
+use miette::{
+    Diagnostic, GraphicalReportHandler, LabeledSpan, NamedSource,
+    SourceCode, SourceSpan,
+};
+use std::fmt;
+use std::ops::Range;
 use std::path::PathBuf;
 use thiserror::Error;
 
@@ -145,6 +193,202 @@ pub enum ProcessingError {
     /// do not fall under any specific category.
     #[error("Internal error: {0}")]
     InternalError(String),
+
+    /// Represents pages skipped by a build that continued past errors.
+    #[error("{} page(s) failed to build", .failures.len())]
+    BuildFailed {
+        /// The pages that failed, in the order they failed
+        failures: Vec<PageFailure>,
+    },
+
+    /// Represents a build stopped for using more memory than allowed.
+    #[error(
+        "Memory use of {used_mb} MB exceeded the {limit_mb} MB hard limit while building '{page}'"
+    )]
+    MemoryLimitExceeded {
+        /// The content file being built when the limit was crossed
+        page: PathBuf,
+        /// Resident memory in use, in mebibytes
+        used_mb: u64,
+        /// The configured hard limit, in mebibytes
+        limit_mb: u64,
+    },
+
+    /// An error pointing at the span of a source file it came from.
+    #[error("{error}")]
+    Located {
+        /// The underlying error
+        error: Box<ProcessingError>,
+        /// The span of the source the error points at
+        snippet: Box<SourceSnippet>,
+    },
+}
+
+/// A span of a source file that an error points at, such as a
+/// frontmatter line or a template expression.
+#[derive(Debug, Clone)]
+pub struct SourceSnippet {
+    source: NamedSource<String>,
+    span: Range<usize>,
+    label: String,
+}
+
+impl SourceSnippet {
+    /// Creates a snippet underlining a byte range of `source`.
+    ///
+    /// # Arguments
+    /// * `name` - The name shown for the source, usually its path.
+    /// * `source` - The full source text.
+    /// * `span` - The byte range to underline.
+    /// * `label` - A short note shown under the span.
+    pub fn new<N, S, L>(
+        name: N,
+        source: S,
+        span: Range<usize>,
+        label: L,
+    ) -> Self
+    where
+        N: AsRef<str>,
+        S: Into<String>,
+        L: Into<String>,
+    {
+        Self {
+            source: NamedSource::new(name, source.into()),
+            span,
+            label: label.into(),
+        }
+    }
+
+    /// Creates a snippet underlining a line of `source`, without its
+    /// indentation.
+    ///
+    /// # Returns
+    /// * `Option<Self>` - The snippet, or `None` if `source` has no such
+    ///   line. Lines are numbered from 1.
+    pub fn line<N, S, L>(
+        name: N,
+        source: S,
+        line: usize,
+        label: L,
+    ) -> Option<Self>
+    where
+        N: AsRef<str>,
+        S: Into<String>,
+        L: Into<String>,
+    {
+        let source = source.into();
+        let span = line_span(&source, line)?;
+        Some(Self::new(name, source, span, label))
+    }
+
+    /// Returns the name shown for the source.
+    pub fn name(&self) -> &str {
+        self.source.name()
+    }
+
+    /// Returns the underlined byte range.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// Returns the underlined text.
+    pub fn text(&self) -> &str {
+        self.source.inner().get(self.span()).unwrap_or_default()
+    }
+
+    /// Returns the note shown under the span.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+}
+
+/// Returns the byte range of a line of `source`, numbered from 1,
+/// without its indentation or line ending.
+pub(crate) fn line_span(
+    source: &str,
+    line: usize,
+) -> Option<Range<usize>> {
+    let mut start = 0;
+    for (index, text) in source.split_inclusive('\n').enumerate() {
+        if index + 1 == line {
+            let content = text.trim_end();
+            let indent = content.len() - content.trim_start().len();
+            return Some(start + indent..start + content.len());
+        }
+        start += text.len();
+    }
+    None
+}
+
+/// The stage of the build a page was in when it failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildStage {
+    /// Reading the content file
+    Read,
+    /// Running content validators
+    Validate,
+    /// Expanding shortcodes and processing content
+    Process,
+    /// Rendering the page template
+    Render,
+    /// Running HTML post-processors over the rendered page
+    PostProcess,
+    /// Writing the page to the output directory
+    Generate,
+}
+
+impl fmt::Display for BuildStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            BuildStage::Read => "read",
+            BuildStage::Validate => "validate",
+            BuildStage::Process => "process",
+            BuildStage::Render => "render",
+            BuildStage::PostProcess => "post-process",
+            BuildStage::Generate => "generate",
+        })
+    }
+}
+
+/// A page skipped by a build that continued past errors.
+#[derive(Debug)]
+pub struct PageFailure {
+    /// The content file of the failed page
+    pub path: PathBuf,
+    /// The stage the page failed in
+    pub stage: BuildStage,
+    /// Why the page failed
+    pub error: ProcessingError,
+}
+
+impl fmt::Display for PageFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} [{}]: {}",
+            self.path.display(),
+            self.stage,
+            self.error
+        )
+    }
+}
+
+impl std::error::Error for PageFailure {}
+
+impl Diagnostic for PageFailure {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Some(Box::new(self.error.code()))
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        self.error.source_code()
+    }
+
+    fn labels(
+        &self,
+    ) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        self.error.labels()
+    }
 }
 
 impl ProcessingError {
@@ -266,6 +510,119 @@ pub fn io_error(path: PathBuf, source: std::io::Error) -> Self {
     pub fn internal_error<S: Into<String>>(message: S) -> Self {
         ProcessingError::InternalError(message.into())
     }
+
+    /// Creates a `BuildFailed` error from the pages a build skipped.
+    pub fn build_failed(failures: Vec<PageFailure>) -> Self {
+        ProcessingError::BuildFailed { failures }
+    }
+
+    /// Creates a `MemoryLimitExceeded` error for the page being built.
+    pub fn memory_limit_exceeded<P: Into<PathBuf>>(
+        page: P,
+        used_mb: u64,
+        limit_mb: u64,
+    ) -> Self {
+        ProcessingError::MemoryLimitExceeded {
+            page: page.into(),
+            used_mb,
+            limit_mb,
+        }
+    }
+
+    /// Returns the error's stable code, such as `NF1005` for a
+    /// configuration error.
+    ///
+    /// See the [module documentation](self#error-codes) for the full
+    /// list.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ProcessingError::ContentProcessing { .. } => "NF1001",
+            ProcessingError::FileOperation { .. } => "NF1002",
+            ProcessingError::FileNotFound { .. } => "NF1003",
+            ProcessingError::TemplateProcessing { .. } => "NF1004",
+            ProcessingError::Configuration { .. } => "NF1005",
+            ProcessingError::Validation { .. } => "NF1006",
+            ProcessingError::OutputGeneration { .. } => "NF1007",
+            ProcessingError::Serialization { .. } => "NF1008",
+            ProcessingError::Plugin { .. } => "NF1009",
+            ProcessingError::Internal { .. }
+            | ProcessingError::InternalError(_) => "NF1010",
+            ProcessingError::IOError { .. } => "NF1011",
+            ProcessingError::BuildFailed { .. } => "NF1012",
+            ProcessingError::MemoryLimitExceeded { .. } => "NF1013",
+            ProcessingError::Located { error, .. } => error.code(),
+        }
+    }
+
+    /// Points the error at a span of the source it came from.
+    ///
+    /// An error that already points at a span is moved to the new one.
+    pub fn with_snippet(self, snippet: SourceSnippet) -> Self {
+        let error = match self {
+            ProcessingError::Located { error, .. } => error,
+            error => Box::new(error),
+        };
+        ProcessingError::Located {
+            error,
+            snippet: Box::new(snippet),
+        }
+    }
+
+    /// Returns the span of source the error points at, if any.
+    pub fn snippet(&self) -> Option<&SourceSnippet> {
+        match self {
+            ProcessingError::Located { snippet, .. } => Some(snippet),
+            _ => None,
+        }
+    }
+
+    /// Renders the error as a diagnostic report, with the span it points
+    /// at underlined and the pages of a failed build listed below it.
+    ///
+    /// Colours are only used when writing to a terminal.
+    pub fn render_diagnostic(&self) -> String {
+        let mut report = String::new();
+        _ = GraphicalReportHandler::new()
+            .render_report(&mut report, self);
+        report
+    }
+}
+
+impl Diagnostic for ProcessingError {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Some(Box::new(ProcessingError::code(self)))
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        let snippet = self.snippet()?;
+        Some(&snippet.source)
+    }
+
+    fn labels(
+        &self,
+    ) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        let snippet = self.snippet()?;
+        let label = LabeledSpan::new_with_span(
+            Some(snippet.label.clone()),
+            SourceSpan::from(snippet.span()),
+        );
+        Some(Box::new(std::iter::once(label)))
+    }
+
+    fn related<'a>(
+        &'a self,
+    ) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        match self {
+            ProcessingError::BuildFailed { failures } => {
+                Some(Box::new(
+                    failures
+                        .iter()
+                        .map(|failure| -> &dyn Diagnostic { failure }),
+                ))
+            }
+            _ => None,
+        }
+    }
 }
 
 impl From<std::io::Error> for ProcessingError {
@@ -363,4 +720,111 @@ fn test_validation_error() {
         assert!(matches!(error, ProcessingError::Validation { .. }));
         assert!(error.to_string().contains("Invalid input"));
     }
+
+    #[test]
+    fn test_build_failed_error() {
+        let error = ProcessingError::build_failed(vec![PageFailure {
+            path: PathBuf::from("content/bad.md"),
+            stage: BuildStage::Render,
+            error: ProcessingError::template_processing(
+                "default",
+                "missing partial",
+                None,
+            ),
+        }]);
+        assert_eq!(error.to_string(), "1 page(s) failed to build");
+        if let ProcessingError::BuildFailed { failures } = &error {
+            assert_eq!(
+                failures[0].to_string(),
+                "content/bad.md [render]: Template error in 'default': missing partial"
+            );
+        }
+    }
+
+    #[test]
+    fn test_memory_limit_exceeded_error() {
+        let error = ProcessingError::memory_limit_exceeded(
+            "content/big.md",
+            2100,
+            2048,
+        );
+        assert_eq!(
+            error.to_string(),
+            "Memory use of 2100 MB exceeded the 2048 MB hard limit while building 'content/big.md'"
+        );
+    }
+
+    #[test]
+    fn test_located_error() {
+        let source = "---\ntitle: Post\n  date: never\n---\n";
+        let snippet = SourceSnippet::line(
+            "content/post.md",
+            source,
+            3,
+            "invalid `date`",
+        )
+        .unwrap();
+        assert_eq!(snippet.text(), "date: never");
+        assert!(SourceSnippet::line("post.md", source, 9, "").is_none());
+
+        let error = ProcessingError::validation(
+            "Invalid date 'never'",
+            None::<String>,
+        )
+        .with_snippet(snippet);
+        assert_eq!(
+            error.to_string(),
+            "Validation failed: Invalid date 'never'"
+        );
+        assert_eq!(error.snippet().unwrap().name(), "content/post.md");
+
+        let failed = ProcessingError::build_failed(vec![PageFailure {
+            path: PathBuf::from("content/post.md"),
+            stage: BuildStage::Render,
+            error,
+        }]);
+        let report = failed.render_diagnostic();
+        assert!(report.contains("1 page(s) failed to build"));
+        assert!(report.contains("content/post.md [render]"));
+        assert!(report.contains("[content/post.md:3:3]"));
+        assert!(report.contains("invalid `date`"));
+        assert!(report.contains("NF1012"));
+        assert!(report.contains("NF1006"));
+    }
+
+    #[test]
+    fn test_error_codes() {
+        let errors = [
+            ProcessingError::content_processing("", None),
+            ProcessingError::file_operation("", "", None),
+            ProcessingError::FileNotFound {
+                path: PathBuf::new(),
+                details: String::new(),
+            },
+            ProcessingError::template_processing("", "", None),
+            ProcessingError::configuration("", None, None),
+            ProcessingError::validation("", None::<String>),
+            ProcessingError::output_generation("", "", None),
+            ProcessingError::serialization("", None),
+            ProcessingError::plugin("", "", None),
+            ProcessingError::internal("", None),
+            ProcessingError::io_error(
+                PathBuf::new(),
+                Error::new(ErrorKind::Other, ""),
+            ),
+            ProcessingError::build_failed(Vec::new()),
+            ProcessingError::memory_limit_exceeded("", 2, 1),
+        ];
+        for (number, error) in (1001..).zip(&errors) {
+            assert_eq!(error.code(), format!("NF{}", number));
+        }
+        assert_eq!(
+            ProcessingError::internal_error("").code(),
+            "NF1010"
+        );
+
+        let located = ProcessingError::validation("", None::<String>)
+            .with_snippet(SourceSnippet::new("post.md", "x", 0..1, ""));
+        assert_eq!(located.code(), "NF1006");
+    }
 }
@@ -0,0 +1,202 @@
+//! # Ignore Rules
+//!
+//! Support for a project-level `.nucleusignore` file. The file uses
+//! gitignore syntax and lets authors exclude editor swap files, drafts
+//! directories and other clutter from the stages that scan the project
+//! tree: content discovery, static and asset copying, and the file
+//! watcher of `nucleusflow serve --watch`.
+//!
+//! ```text
+//! # .nucleusignore
+//! *.swp
+//! *~
+//! .DS_Store
+//! _drafts/
+//! ```
+//!
+//! Patterns are matched against paths relative to the project root. A
+//! pattern that matches a directory also excludes everything beneath it.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+use crate::core::error::{ProcessingError, Result};
+
+/// Name of the ignore file looked up in the project root.
+pub const IGNORE_FILE_NAME: &str = ".nucleusignore";
+
+/// A compiled set of gitignore-style rules rooted at a project directory.
+#[derive(Debug, Clone)]
+pub struct IgnoreRules {
+    root: PathBuf,
+    matcher: Gitignore,
+}
+
+impl IgnoreRules {
+    /// Creates an empty rule set that ignores nothing.
+    pub fn empty<P: AsRef<Path>>(root: P) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+            matcher: Gitignore::empty(),
+        }
+    }
+
+    /// Loads the `.nucleusignore` file from a project root.
+    ///
+    /// A missing file is not an error and yields an empty rule set.
+    ///
+    /// # Arguments
+    /// * `root` - The project root directory.
+    ///
+    /// # Returns
+    /// * `Result<Self>` - The compiled rules, or an error if the file
+    ///   cannot be read or contains an invalid pattern.
+    pub fn load<P: AsRef<Path>>(root: P) -> Result<Self> {
+        let root = root.as_ref();
+        let path = root.join(IGNORE_FILE_NAME);
+        if !path.is_file() {
+            return Ok(Self::empty(root));
+        }
+        let content = fs::read_to_string(&path)
+            .map_err(|e| ProcessingError::io_error(path.clone(), e))?;
+        Self::build(root, content.lines(), Some(path))
+    }
+
+    /// Creates a rule set from a list of patterns.
+    ///
+    /// # Arguments
+    /// * `root` - The directory patterns are relative to.
+    /// * `patterns` - Gitignore-style patterns, one per entry.
+    pub fn from_patterns<P: AsRef<Path>>(
+        root: P,
+        patterns: &[&str],
+    ) -> Result<Self> {
+        Self::build(root.as_ref(), patterns.iter().copied(), None)
+    }
+
+    /// Returns the root directory the rules are relative to.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns `true` if the rule set contains no patterns.
+    pub fn is_empty(&self) -> bool {
+        self.matcher.is_empty()
+    }
+
+    /// Returns `true` if the path, or any of its parent directories, is
+    /// ignored.
+    ///
+    /// Paths may be absolute (under the root) or relative to the root.
+    /// Absolute paths outside the root are never ignored.
+    ///
+    /// # Arguments
+    /// * `path` - The path to check.
+    /// * `is_dir` - Whether the path refers to a directory.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        if self.matcher.is_empty() {
+            return false;
+        }
+        let relative = match path.strip_prefix(&self.root) {
+            Ok(relative) => relative,
+            Err(_) if path.is_relative() => path,
+            Err(_) => return false,
+        };
+        if relative.as_os_str().is_empty() {
+            return false;
+        }
+        self.matcher
+            .matched_path_or_any_parents(relative, is_dir)
+            .is_ignore()
+    }
+
+    /// Compiles patterns into a matcher, reporting the offending line on
+    /// error.
+    fn build<'a, I>(
+        root: &Path,
+        lines: I,
+        source: Option<PathBuf>,
+    ) -> Result<Self>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let mut builder = GitignoreBuilder::new(root);
+        for (index, line) in lines.into_iter().enumerate() {
+            _ = builder.add_line(source.clone(), line).map_err(
+                |e| {
+                    ProcessingError::configuration(
+                        format!(
+                            "Invalid ignore pattern on line {}: {}",
+                            index + 1,
+                            e
+                        ),
+                        source.clone(),
+                        None,
+                    )
+                },
+            )?;
+        }
+        let matcher = builder.build().map_err(|e| {
+            ProcessingError::configuration(
+                format!("Failed to compile ignore rules: {}", e),
+                source,
+                None,
+            )
+        })?;
+        Ok(Self {
+            root: root.to_path_buf(),
+            matcher,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_patterns_and_negation() {
+        let rules = IgnoreRules::from_patterns(
+            "/site",
+            &["*.swp", "!keep.swp", "_drafts/", "# comment"],
+        )
+        .unwrap();
+
+        assert!(
+            rules.is_ignored(Path::new("content/.post.md.swp"), false)
+        );
+        assert!(
+            rules.is_ignored(Path::new("/site/content/a.swp"), false)
+        );
+        assert!(!rules.is_ignored(Path::new("keep.swp"), false));
+        assert!(rules.is_ignored(Path::new("_drafts"), true));
+        assert!(rules
+            .is_ignored(Path::new("content/_drafts/wip.md"), false));
+        assert!(!rules.is_ignored(Path::new("content/post.md"), false));
+        assert!(!rules.is_ignored(Path::new("/elsewhere/a.swp"), false));
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let rules = IgnoreRules::load(temp_dir.path()).unwrap();
+        assert!(rules.is_empty());
+        assert!(!rules.is_ignored(Path::new("anything"), false));
+    }
+
+    #[test]
+    fn test_load_file() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join(IGNORE_FILE_NAME), "*~\n")
+            .unwrap();
+
+        let rules = IgnoreRules::load(temp_dir.path()).unwrap();
+        assert!(rules.is_ignored(
+            &temp_dir.path().join("content/index.md~"),
+            false
+        ));
+    }
+}
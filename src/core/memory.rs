@@ -0,0 +1,190 @@
+//! # Build Memory Guard
+//!
+//! Watches the resident memory of the build process against the limits
+//! in [`MemoryConfig`], so a build on a constrained CI runner fails with
+//! an error naming the page it was on instead of being killed without
+//! explanation.
+//!
+//! The guard is checked before each page is processed and rendered.
+//! Crossing the soft limit asks the pipeline to drop its caches; crossing
+//! the hard limit stops the build. Resident memory is read from
+//! `/proc/self/status`, so on other platforms the guard does nothing.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::core::config::MemoryConfig;
+//! use nucleusflow::core::memory::{MemoryGuard, MemoryStatus};
+//!
+//! let guard = MemoryGuard::new(MemoryConfig::default());
+//! assert_eq!(
+//!     guard.check("content/index.md").unwrap(),
+//!     MemoryStatus::Normal
+//! );
+//! ```
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::core::config::MemoryConfig;
+use crate::core::error::{ProcessingError, Result};
+
+/// Bytes in a mebibyte, the unit memory limits are configured in.
+const MB: u64 = 1024 * 1024;
+
+/// What the pipeline should do after a memory check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryStatus {
+    /// Usage is within the limits, or is not being watched
+    Normal,
+    /// Usage has just crossed the soft limit; caches should be dropped
+    ShedCaches {
+        /// Resident memory in use, in mebibytes
+        used_mb: u64,
+    },
+}
+
+/// Checks resident memory against configured soft and hard limits.
+#[derive(Debug, Default)]
+pub struct MemoryGuard {
+    limits: MemoryConfig,
+    over_soft_limit: AtomicBool,
+}
+
+impl MemoryGuard {
+    /// Creates a guard enforcing the given limits.
+    pub fn new(limits: MemoryConfig) -> Self {
+        Self {
+            limits,
+            over_soft_limit: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns `true` if either limit is set.
+    pub fn is_enabled(&self) -> bool {
+        self.limits.soft_limit_mb.is_some()
+            || self.limits.hard_limit_mb.is_some()
+    }
+
+    /// Checks current memory use before working on a page.
+    ///
+    /// `ShedCaches` is returned once each time usage crosses the soft
+    /// limit, rather than for every page while it stays above it.
+    ///
+    /// # Arguments
+    /// * `page` - The content file about to be worked on.
+    ///
+    /// # Returns
+    /// * `Result<MemoryStatus>` - What to do next, or a
+    ///   `MemoryLimitExceeded` error naming `page` past the hard limit.
+    pub fn check<P: AsRef<Path>>(
+        &self,
+        page: P,
+    ) -> Result<MemoryStatus> {
+        if !self.is_enabled() {
+            return Ok(MemoryStatus::Normal);
+        }
+        self.check_usage(page.as_ref(), resident_memory())
+    }
+
+    /// Checks a measured memory use, in bytes, against the limits.
+    fn check_usage(
+        &self,
+        page: &Path,
+        used: Option<u64>,
+    ) -> Result<MemoryStatus> {
+        let used_mb = match used {
+            Some(used) => used / MB,
+            None => return Ok(MemoryStatus::Normal),
+        };
+        if let Some(limit_mb) = self.limits.hard_limit_mb {
+            if used_mb >= limit_mb {
+                return Err(ProcessingError::memory_limit_exceeded(
+                    page, used_mb, limit_mb,
+                ));
+            }
+        }
+
+        let over = self
+            .limits
+            .soft_limit_mb
+            .map_or(false, |limit_mb| used_mb >= limit_mb);
+        let was_over =
+            self.over_soft_limit.swap(over, Ordering::Relaxed);
+        if over && !was_over {
+            Ok(MemoryStatus::ShedCaches { used_mb })
+        } else {
+            Ok(MemoryStatus::Normal)
+        }
+    }
+}
+
+/// Returns the resident memory of the current process in bytes, where
+/// the platform exposes it.
+pub fn resident_memory() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))
+        .and_then(|value| value.trim().strip_suffix("kB"))
+        .and_then(|kb| kb.trim().parse::<u64>().ok())
+        .map(|kb| kb * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guard(soft: Option<u64>, hard: Option<u64>) -> MemoryGuard {
+        MemoryGuard::new(MemoryConfig {
+            soft_limit_mb: soft,
+            hard_limit_mb: hard,
+        })
+    }
+
+    #[test]
+    fn test_soft_limit_sheds_once_per_crossing() {
+        let guard = guard(Some(100), None);
+        let page = Path::new("post.md");
+        let check = |mb: u64| guard.check_usage(page, Some(mb * MB));
+
+        assert_eq!(check(50).unwrap(), MemoryStatus::Normal);
+        assert_eq!(
+            check(120).unwrap(),
+            MemoryStatus::ShedCaches { used_mb: 120 }
+        );
+        assert_eq!(check(130).unwrap(), MemoryStatus::Normal);
+        assert_eq!(check(90).unwrap(), MemoryStatus::Normal);
+        assert_eq!(
+            check(110).unwrap(),
+            MemoryStatus::ShedCaches { used_mb: 110 }
+        );
+        assert_eq!(
+            guard.check_usage(page, None).unwrap(),
+            MemoryStatus::Normal
+        );
+    }
+
+    #[test]
+    fn test_hard_limit_names_page() {
+        let guard = guard(Some(100), Some(200));
+        let error = guard
+            .check_usage(Path::new("content/big.md"), Some(250 * MB))
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            ProcessingError::MemoryLimitExceeded {
+                used_mb: 250,
+                limit_mb: 200,
+                ..
+            }
+        ));
+        assert!(error.to_string().contains("content/big.md"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_resident_memory() {
+        assert!(resident_memory().unwrap() > 0);
+    }
+}
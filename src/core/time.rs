@@ -0,0 +1,256 @@
+//! # Site Timezone
+//!
+//! Dates in frontmatter are usually written without an offset
+//! (`date: 2024-03-10` or `date: 2024-03-10 09:30`). Interpreting them in
+//! the build machine's local time makes output depend on where the site is
+//! built, so every date calculation goes through a [`SiteTimezone`]
+//! instead: naive dates are read as local time in the site's timezone,
+//! "today" is the current date there, and timestamps stamped into output
+//! carry its offset.
+//!
+//! ```toml
+//! timezone = "Europe/London"
+//! ```
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::core::time::SiteTimezone;
+//!
+//! let timezone = SiteTimezone::parse("America/New_York").unwrap();
+//! let date = timezone.parse_date("2024-07-04 09:00").unwrap();
+//! assert_eq!(date.to_rfc3339(), "2024-07-04T09:00:00-04:00");
+//! ```
+
+use std::fmt;
+
+use chrono::{
+    DateTime, LocalResult, NaiveDate, NaiveDateTime, NaiveTime,
+    TimeZone, Utc,
+};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::core::error::{ProcessingError, Result};
+
+/// Naive date-time formats accepted in frontmatter, tried in order.
+const NAIVE_DATE_TIME_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+];
+
+/// The timezone a site's dates are interpreted and reported in.
+///
+/// Defaults to UTC. Serialized as an IANA timezone name such as
+/// `Europe/Paris`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct SiteTimezone {
+    tz: Tz,
+}
+
+impl SiteTimezone {
+    /// Returns the UTC timezone.
+    pub fn utc() -> Self {
+        Self { tz: Tz::UTC }
+    }
+
+    /// Parses an IANA timezone name.
+    ///
+    /// # Arguments
+    /// * `name` - The timezone name, e.g. `Asia/Tokyo` or `UTC`.
+    ///
+    /// # Returns
+    /// * `Result<Self>` - The timezone, or a configuration error if the
+    ///   name is unknown.
+    pub fn parse(name: &str) -> Result<Self> {
+        name.trim()
+            .parse::<Tz>()
+            .map(|tz| Self { tz })
+            .map_err(|e| {
+                ProcessingError::configuration(
+                    format!("Unknown timezone '{}': {}", name, e),
+                    None,
+                    None,
+                )
+            })
+    }
+
+    /// Returns the IANA name of the timezone.
+    pub fn name(&self) -> &'static str {
+        self.tz.name()
+    }
+
+    /// Returns the current time in the site timezone.
+    pub fn now(&self) -> DateTime<Tz> {
+        Utc::now().with_timezone(&self.tz)
+    }
+
+    /// Returns the current date in the site timezone.
+    pub fn today(&self) -> NaiveDate {
+        self.now().date_naive()
+    }
+
+    /// Parses a frontmatter date.
+    ///
+    /// Dates with an explicit offset (RFC 3339) keep their instant and
+    /// are converted to the site timezone. Naive date-times are read as
+    /// local time in the site timezone, and bare dates as local midnight.
+    /// Local times repeated by a daylight saving transition resolve to
+    /// the earlier instant.
+    ///
+    /// # Arguments
+    /// * `value` - The date as written in frontmatter.
+    ///
+    /// # Returns
+    /// * `Result<DateTime<Tz>>` - The date in the site timezone, or a
+    ///   validation error if it cannot be parsed or does not exist
+    ///   locally.
+    pub fn parse_date(&self, value: &str) -> Result<DateTime<Tz>> {
+        let value = value.trim();
+        if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+            return Ok(date.with_timezone(&self.tz));
+        }
+
+        let naive = NAIVE_DATE_TIME_FORMATS
+            .iter()
+            .find_map(|format| {
+                NaiveDateTime::parse_from_str(value, format).ok()
+            })
+            .or_else(|| {
+                NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                    .ok()
+                    .map(|date| date.and_time(NaiveTime::MIN))
+            });
+        let naive = match naive {
+            Some(naive) => naive,
+            None => {
+                return Err(ProcessingError::validation(
+                    format!("Invalid date '{}'", value),
+                    Some("expected YYYY-MM-DD, YYYY-MM-DD HH:MM[:SS] or RFC 3339"),
+                ))
+            }
+        };
+
+        match self.tz.from_local_datetime(&naive) {
+            LocalResult::Single(date) => Ok(date),
+            LocalResult::Ambiguous(earliest, _) => Ok(earliest),
+            LocalResult::None => Err(ProcessingError::validation(
+                format!(
+                    "Date '{}' does not exist in timezone {}",
+                    value,
+                    self.name()
+                ),
+                None::<String>,
+            )),
+        }
+    }
+
+    /// Returns `true` if a date falls on the current day in the site
+    /// timezone.
+    pub fn is_today(&self, date: &DateTime<Tz>) -> bool {
+        date.with_timezone(&self.tz).date_naive() == self.today()
+    }
+
+    /// Returns `true` if a date is not in the future.
+    pub fn is_published(&self, date: &DateTime<Tz>) -> bool {
+        *date <= self.now()
+    }
+}
+
+impl Default for SiteTimezone {
+    fn default() -> Self {
+        Self::utc()
+    }
+}
+
+impl fmt::Display for SiteTimezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl TryFrom<String> for SiteTimezone {
+    type Error = ProcessingError;
+
+    fn try_from(name: String) -> Result<Self> {
+        Self::parse(&name)
+    }
+}
+
+impl From<SiteTimezone> for String {
+    fn from(timezone: SiteTimezone) -> Self {
+        timezone.name().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timezone() {
+        assert_eq!(SiteTimezone::default().name(), "UTC");
+        assert_eq!(
+            SiteTimezone::parse("Europe/Paris").unwrap().to_string(),
+            "Europe/Paris"
+        );
+        assert!(SiteTimezone::parse("Mars/Olympus_Mons").is_err());
+    }
+
+    #[test]
+    fn test_parse_naive_dates_in_site_timezone() {
+        let tokyo = SiteTimezone::parse("Asia/Tokyo").unwrap();
+
+        let date = tokyo.parse_date("2024-01-01").unwrap();
+        assert_eq!(date.to_rfc3339(), "2024-01-01T00:00:00+09:00");
+
+        let date = tokyo.parse_date("2024-01-01 18:30").unwrap();
+        assert_eq!(date.to_rfc3339(), "2024-01-01T18:30:00+09:00");
+
+        // Explicit offsets keep their instant
+        let date = tokyo.parse_date("2024-01-01T00:00:00Z").unwrap();
+        assert_eq!(date.to_rfc3339(), "2024-01-01T09:00:00+09:00");
+
+        assert!(tokyo.parse_date("January 1st").is_err());
+    }
+
+    #[test]
+    fn test_daylight_saving_transitions() {
+        let london = SiteTimezone::parse("Europe/London").unwrap();
+
+        // Clocks go forward at 01:00 on 2024-03-31
+        assert!(london.parse_date("2024-03-31 01:30").is_err());
+
+        // Clocks go back at 02:00 on 2024-10-27; 01:30 happens twice
+        let date = london.parse_date("2024-10-27 01:30").unwrap();
+        assert_eq!(date.to_rfc3339(), "2024-10-27T01:30:00+01:00");
+    }
+
+    #[test]
+    fn test_today_and_published() {
+        let timezone =
+            SiteTimezone::parse("Pacific/Kiritimati").unwrap();
+        let now = timezone.now();
+        assert!(timezone.is_today(&now));
+        assert!(timezone.is_published(&now));
+
+        let tomorrow = now + chrono::Duration::days(1);
+        assert!(!timezone.is_today(&tomorrow));
+        assert!(!timezone.is_published(&tomorrow));
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let timezone: SiteTimezone =
+            serde_json::from_str("\"America/Denver\"").unwrap();
+        assert_eq!(
+            serde_json::to_string(&timezone).unwrap(),
+            "\"America/Denver\""
+        );
+        assert!(serde_json::from_str::<SiteTimezone>("\"Nowhere\"")
+            .is_err());
+    }
+}
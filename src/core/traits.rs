@@ -8,6 +8,7 @@
 //! - [`Transform`]: Trait for content transformation operations
 //! - [`Generator`]: Trait for output generation
 //! - [`Validator`]: Trait for content validation
+//! - [`PostProcessor`]: Trait for rewriting rendered HTML before it is written
 //! - [`ProcessingContext`]: Trait for converting types into a processing context
 //! - [`Shareable`]: Trait for wrapping types in a shareable, thread-safe container
 //!
@@ -26,7 +27,9 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 
+use crate::core::cache::CacheStats;
 use crate::core::error::Result;
+use crate::site::Page;
 
 /// Core trait for implementing content processors.
 ///
@@ -123,6 +126,41 @@ fn validate(
         path: &Path,
         options: Option<&JsonValue>,
     ) -> Result<()>;
+
+    /// Returns the hit and miss counts of the generator's asset cache.
+    ///
+    /// Generators without a cache return `None`, the default.
+    fn cache_stats(&self) -> Option<CacheStats> {
+        None
+    }
+
+    /// Drops cached data to free memory when the build nears its soft
+    /// memory limit.
+    ///
+    /// Generators without a cache do nothing, the default.
+    fn shed_cache(&self) {}
+}
+
+/// Trait for rewriting a page's rendered HTML before it is written.
+///
+/// Post-processors are chained: each receives the output of the one
+/// registered before it, so features such as link rewriting or snippet
+/// injection compose without re-reading files from disk.
+pub trait PostProcessor: Send + Sync + Debug {
+    /// Returns a short name identifying the post-processor.
+    fn name(&self) -> &str;
+
+    /// Rewrites the rendered HTML of a page.
+    ///
+    /// # Arguments
+    ///
+    /// * `html` - The page's HTML as rendered so far
+    /// * `page` - The page being written
+    ///
+    /// # Returns
+    ///
+    /// Returns the rewritten HTML, or an error if it cannot be processed.
+    fn process(&self, html: &str, page: &Page) -> Result<String>;
 }
 
 /// Trait for implementing content validation.
@@ -0,0 +1,281 @@
+//! # Content Validation
+//!
+//! Validators implement [`Validator`] over a [`SourceFile`] and run on
+//! every content file before it is processed. Each one is registered on
+//! `NucleusFlow` with a [`Severity`]: a failing `Error` validator stops
+//! the build once every file has been checked, while a failing `Warning`
+//! validator is only reported. Failures are gathered as [`Diagnostics`],
+//! so a single run lists every problem rather than the first one.
+//!
+//! Two validators are built in: [`MaxSize`] limits the size of a file and
+//! [`RequiredFields`] checks that its frontmatter sets the given keys.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::core::traits::Validator;
+//! use nucleusflow::core::validation::{RequiredFields, SourceFile};
+//!
+//! let file = SourceFile::new("post.md", "---\ntitle: Hello\n---\nBody");
+//! assert!(RequiredFields::new(["title"]).validate(&file).is_ok());
+//! assert!(RequiredFields::new(["date"]).validate(&file).is_err());
+//! ```
+
+use std::fmt;
+use std::path::PathBuf;
+
+use serde_json::Value as JsonValue;
+
+use crate::core::error::{ProcessingError, Result};
+use crate::core::traits::Validator;
+use crate::processors::markdown::MarkdownProcessor;
+
+/// A content file as read from disk, before any processing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceFile {
+    /// The file's path
+    pub path: PathBuf,
+
+    /// The file's raw contents, including frontmatter
+    pub content: String,
+}
+
+impl SourceFile {
+    /// Creates a source file from a path and its contents.
+    pub fn new<P, S>(path: P, content: S) -> Self
+    where
+        P: Into<PathBuf>,
+        S: Into<String>,
+    {
+        Self {
+            path: path.into(),
+            content: content.into(),
+        }
+    }
+
+    /// Returns the value of a frontmatter key, if it is set.
+    pub fn frontmatter(&self, key: &str) -> Option<JsonValue> {
+        MarkdownProcessor::parse_frontmatter(&self.content).remove(key)
+    }
+
+    /// Returns the line a top-level frontmatter key is set on, numbered
+    /// from 1, if it is set.
+    pub fn frontmatter_line(&self, key: &str) -> Option<usize> {
+        let mut lines = self.content.lines().enumerate();
+        if lines.next()?.1 != "---" {
+            return None;
+        }
+        lines
+            .take_while(|(_, line)| *line != "---")
+            .find(|(_, line)| {
+                line.strip_prefix(key).map_or(false, |rest| {
+                    rest.trim_start().starts_with(':')
+                })
+            })
+            .map(|(index, _)| index + 1)
+    }
+}
+
+/// How a failing validator affects the build.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The failure is reported and the build continues
+    Warning,
+    /// The failure is reported and the build stops
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// A single validation failure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The file that failed validation
+    pub path: PathBuf,
+
+    /// Whether the failure stops the build
+    pub severity: Severity,
+
+    /// What was wrong
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}: {}",
+            self.severity,
+            self.path.display(),
+            self.message
+        )
+    }
+}
+
+/// The validation failures gathered over a build.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diagnostics {
+    /// The failures, in the order they were found
+    pub entries: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    /// Records a failure.
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        self.entries.push(diagnostic);
+    }
+
+    /// Returns the number of failures of the given severity.
+    pub fn count(&self, severity: Severity) -> usize {
+        self.entries
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == severity)
+            .count()
+    }
+
+    /// Returns `true` if any failure stops the build.
+    pub fn has_errors(&self) -> bool {
+        self.count(Severity::Error) > 0
+    }
+
+    /// Returns `true` if nothing failed.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for diagnostic in &self.entries {
+            writeln!(f, "{}", diagnostic)?;
+        }
+        Ok(())
+    }
+}
+
+/// Rejects files larger than a number of bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaxSize(pub usize);
+
+impl Validator for MaxSize {
+    type Input = SourceFile;
+
+    fn validate(&self, input: &Self::Input) -> Result<()> {
+        if input.content.len() > self.0 {
+            return Err(ProcessingError::validation(
+                format!(
+                    "File is {} bytes, larger than the {} byte limit",
+                    input.content.len(),
+                    self.0
+                ),
+                None::<String>,
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Rejects files whose frontmatter does not set every given key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequiredFields {
+    fields: Vec<String>,
+}
+
+impl RequiredFields {
+    /// Creates a validator requiring the given frontmatter keys.
+    pub fn new<I, S>(fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            fields: fields.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl Validator for RequiredFields {
+    type Input = SourceFile;
+
+    fn validate(&self, input: &Self::Input) -> Result<()> {
+        let frontmatter =
+            MarkdownProcessor::parse_frontmatter(&input.content);
+        let missing: Vec<_> = self
+            .fields
+            .iter()
+            .filter(|field| {
+                frontmatter
+                    .get(field.as_str())
+                    .map_or(true, |value| value.is_null())
+            })
+            .map(String::as_str)
+            .collect();
+        if !missing.is_empty() {
+            return Err(ProcessingError::validation(
+                format!(
+                    "Missing frontmatter fields: {}",
+                    missing.join(", ")
+                ),
+                None::<String>,
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_validators() {
+        let file = SourceFile::new(
+            "post.md",
+            "---\ntitle: Hello\ndate: ~\n---\nBody",
+        );
+        assert!(MaxSize(100).validate(&file).is_ok());
+        assert!(MaxSize(10).validate(&file).is_err());
+
+        let error = RequiredFields::new(["title", "date", "tags"])
+            .validate(&file)
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("Missing frontmatter fields: date, tags"));
+        assert_eq!(
+            file.frontmatter("title"),
+            Some(JsonValue::String("Hello".to_string()))
+        );
+        assert_eq!(file.frontmatter_line("date"), Some(3));
+        assert_eq!(file.frontmatter_line("tags"), None);
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let mut diagnostics = Diagnostics::default();
+        assert!(diagnostics.is_empty());
+        diagnostics.push(Diagnostic {
+            path: PathBuf::from("a.md"),
+            severity: Severity::Warning,
+            message: "short".to_string(),
+        });
+        assert!(!diagnostics.has_errors());
+        diagnostics.push(Diagnostic {
+            path: PathBuf::from("b.md"),
+            severity: Severity::Error,
+            message: "empty".to_string(),
+        });
+        assert!(diagnostics.has_errors());
+        assert_eq!(
+            diagnostics.to_string(),
+            "warning: a.md: short\nerror: b.md: empty\n"
+        );
+    }
+}
@@ -0,0 +1,249 @@
+//! # Text Extraction
+//!
+//! Extracts the plain text of every page, with its metadata, for
+//! feeding search services, embeddings pipelines or translation
+//! workflows. The text is taken from each page's processed content, so
+//! it is what the content processor kept after sanitization, without
+//! the surrounding template.
+//!
+//! Tags are removed, the contents of `<script>`, `<style>` and
+//! `<template>` elements are dropped, the common character references
+//! are decoded and whitespace is collapsed. Drafts and utility pages,
+//! such as `404.html`, are left out, as they are from search indexes.
+//!
+//! Pages are written in one of the [`ExtractFormat`]s:
+//!
+//! - `jsonl` prints one JSON object per line, one line per page.
+//! - `json` prints a single JSON array of the pages.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::extract::{plain_text, ExtractFormat, ExtractedPage};
+//!
+//! let page = ExtractedPage {
+//!     url: "/about.html".to_string(),
+//!     title: Some("About".to_string()),
+//!     text: plain_text("<h1>About</h1><p>Fish &amp; chips</p>"),
+//!     ..ExtractedPage::default()
+//! };
+//! assert_eq!(page.text, "About Fish & chips");
+//! assert!(ExtractFormat::Jsonl
+//!     .render(&[page])
+//!     .unwrap()
+//!     .contains("\"text\":\"About Fish & chips\""));
+//! ```
+
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use serde::Serialize;
+use serde_json::{Map, Value as JsonValue};
+
+use crate::site::Page;
+use crate::{ProcessingError, Result};
+
+/// Elements whose contents are not text.
+const SKIPPED_ELEMENTS: [&str; 3] = ["script", "style", "template"];
+
+/// The plain text and metadata of a page.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ExtractedPage {
+    /// The content file the page was read from
+    pub source: PathBuf,
+    /// The page's path relative to the output directory
+    pub path: PathBuf,
+    /// The page's site-relative URL
+    pub url: String,
+    /// The page's title, if its frontmatter sets one
+    pub title: Option<String>,
+    /// The page's frontmatter
+    pub frontmatter: Map<String, JsonValue>,
+    /// The plain text of the page's content
+    pub text: String,
+}
+
+impl ExtractedPage {
+    /// Extracts the plain text and metadata of a page.
+    pub fn new(page: &Page) -> Self {
+        Self {
+            source: page.source.clone(),
+            path: page.path.clone(),
+            url: page.url.clone(),
+            title: page.title().map(str::to_string),
+            frontmatter: page.frontmatter.clone(),
+            text: plain_text(&page.content),
+        }
+    }
+}
+
+/// The formats extracted pages can be written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtractFormat {
+    /// One JSON object per line
+    Jsonl,
+    /// A single JSON array
+    Json,
+}
+
+impl ExtractFormat {
+    /// Formats extracted pages.
+    ///
+    /// # Arguments
+    /// * `pages` - The pages to write.
+    ///
+    /// # Returns
+    /// * `Result<String>` - The pages, ready to print, or an error if
+    ///   they cannot be serialized.
+    pub fn render(&self, pages: &[ExtractedPage]) -> Result<String> {
+        let serialization_error = |e: serde_json::Error| {
+            ProcessingError::serialization(
+                "Failed to write extracted pages",
+                Some(Box::new(e)),
+            )
+        };
+        match self {
+            ExtractFormat::Jsonl => {
+                let mut lines = String::new();
+                for page in pages {
+                    lines.push_str(
+                        &serde_json::to_string(page)
+                            .map_err(serialization_error)?,
+                    );
+                    lines.push('\n');
+                }
+                Ok(lines)
+            }
+            ExtractFormat::Json => {
+                let mut json = serde_json::to_string_pretty(pages)
+                    .map_err(serialization_error)?;
+                json.push('\n');
+                Ok(json)
+            }
+        }
+    }
+}
+
+impl fmt::Display for ExtractFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            ExtractFormat::Jsonl => "jsonl",
+            ExtractFormat::Json => "json",
+        })
+    }
+}
+
+impl FromStr for ExtractFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "jsonl" => Ok(ExtractFormat::Jsonl),
+            "json" => Ok(ExtractFormat::Json),
+            _ => Err(format!(
+                "unknown extract format '{}', expected jsonl or json",
+                s
+            )),
+        }
+    }
+}
+
+/// Returns the text of some HTML, without tags, scripts or styles and
+/// with whitespace collapsed.
+pub fn plain_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        text.push(' ');
+        let tag = &rest[start + 1..];
+        let end = match tag.find('>') {
+            Some(end) => end,
+            None => {
+                rest = "";
+                break;
+            }
+        };
+        rest = &tag[end + 1..];
+        let name: String = tag[..end]
+            .chars()
+            .take_while(char::is_ascii_alphanumeric)
+            .collect::<String>()
+            .to_ascii_lowercase();
+        if SKIPPED_ELEMENTS.contains(&name.as_str()) {
+            let close = format!("</{}", name);
+            rest = match rest.to_ascii_lowercase().find(&close) {
+                Some(i) => rest[i..]
+                    .find('>')
+                    .map_or("", |e| &rest[i + e + 1..]),
+                None => "",
+            };
+        }
+    }
+    text.push_str(rest);
+
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_text() {
+        assert_eq!(
+            plain_text("<p>A&nbsp;<b>bold</b>\n claim</p>"),
+            "A bold claim"
+        );
+        assert_eq!(
+            plain_text(
+                "<h1>Title</h1><SCRIPT>let a = 1 < 2;</SCRIPT>\
+                 <style>p{}</style><p>Body &lt;tag&gt;</p><br"
+            ),
+            "Title Body <tag>"
+        );
+        assert_eq!(plain_text("<template>x"), "");
+    }
+
+    #[test]
+    fn test_extract_format() {
+        let pages = [
+            ExtractedPage {
+                url: "/a.html".to_string(),
+                text: "A".to_string(),
+                ..ExtractedPage::default()
+            },
+            ExtractedPage {
+                url: "/b.html".to_string(),
+                ..ExtractedPage::default()
+            },
+        ];
+        let jsonl = ExtractFormat::Jsonl.render(&pages).unwrap();
+        let lines: Vec<JsonValue> = jsonl
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["url"], "/a.html");
+        assert_eq!(lines[0]["text"], "A");
+        assert_eq!(lines[1]["title"], JsonValue::Null);
+
+        let json: JsonValue = serde_json::from_str(
+            &ExtractFormat::Json.render(&pages).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(json[1]["url"], "/b.html");
+
+        assert_eq!("json".parse(), Ok(ExtractFormat::Json));
+        assert!("csv".parse::<ExtractFormat>().is_err());
+        assert_eq!(ExtractFormat::Jsonl.to_string(), "jsonl");
+    }
+}
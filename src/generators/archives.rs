@@ -0,0 +1,284 @@
+//! # Archives
+//!
+//! Generates archive pages listing a site's dated pages by the year and
+//! month of their `date`, such as `/archive/2024/` and
+//! `/archive/2024/05/`. Where archives are written and the templates
+//! they are rendered with are set by [`ArchiveConfig`].
+//!
+//! Each archive is rendered like a content page, with its listing in
+//! its frontmatter, which templates see as `page.frontmatter`:
+//!
+//! - `title` is the year, such as `2024`, or the month, such as
+//!   `May 2024`,
+//! - `archive` holds the `year` and, on month pages, the `month`, and
+//! - `pages` lists the `url`, `title` and `date` of each page, newest
+//!   first.
+//!
+//! Drafts, utility pages and pages whose date cannot be parsed are left
+//! out. An archive is not generated where a content page is written.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::core::config::ArchiveConfig;
+//! use nucleusflow::core::time::SiteTimezone;
+//! use nucleusflow::generators::archives::archive_pages;
+//! use nucleusflow::site::Page;
+//!
+//! let post = Page::new(
+//!     "content/post.md",
+//!     "post.html",
+//!     serde_json::json!({ "date": "2024-05-03" })
+//!         .as_object()
+//!         .cloned()
+//!         .unwrap(),
+//! );
+//! let archives = archive_pages(
+//!     &ArchiveConfig::default(),
+//!     &SiteTimezone::utc(),
+//!     &[post],
+//! )
+//! .unwrap();
+//! let paths: Vec<_> = archives.iter().map(|a| a.path.clone()).collect();
+//! assert_eq!(
+//!     paths,
+//!     [
+//!         std::path::PathBuf::from("archive/2024/index.html"),
+//!         std::path::PathBuf::from("archive/2024/05/index.html"),
+//!     ]
+//! );
+//! assert_eq!(archives[1].frontmatter["title"], "May 2024");
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Datelike, NaiveDate};
+use chrono_tz::Tz;
+use serde_json::{json, Map, Value as JsonValue};
+
+use crate::core::config::ArchiveConfig;
+use crate::core::time::SiteTimezone;
+use crate::site::{generated_path, Page};
+use crate::Result;
+
+/// An archive page, ready to be rendered.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchivePage {
+    /// The archive's path relative to the output directory
+    pub path: PathBuf,
+
+    /// The archive's title, template, period and pages
+    pub frontmatter: Map<String, JsonValue>,
+}
+
+/// Lists dated pages by year and, unless month pages are disabled, by
+/// month.
+///
+/// # Arguments
+/// * `config` - Where archives are written and their templates.
+/// * `timezone` - The timezone naive dates are read in.
+/// * `pages` - The site's pages.
+///
+/// # Returns
+/// * `Result<Vec<ArchivePage>>` - The archives, newest year first, each
+///   year followed by its months, or an error if a configured path is
+///   outside the output directory.
+pub fn archive_pages(
+    config: &ArchiveConfig,
+    timezone: &SiteTimezone,
+    pages: &[Page],
+) -> Result<Vec<ArchivePage>> {
+    let mut years: BTreeMap<i32, Vec<(DateTime<Tz>, &Page)>> =
+        BTreeMap::new();
+    for page in
+        pages.iter().filter(|p| !p.is_draft() && !p.is_utility())
+    {
+        if let Some(date) =
+            page.date().and_then(|d| timezone.parse_date(d).ok())
+        {
+            years.entry(date.year()).or_default().push((date, page));
+        }
+    }
+
+    let mut archives = Vec::new();
+    for (year, mut dated) in years.into_iter().rev() {
+        dated.sort_by(|a, b| {
+            b.0.cmp(&a.0).then_with(|| a.1.url.cmp(&b.1.url))
+        });
+        archives.push(ArchivePage {
+            path: archive_path(&config.year_path, year, 1)?,
+            frontmatter: frontmatter(
+                year.to_string(),
+                &config.year_template,
+                json!({ "year": year }),
+                &dated,
+            ),
+        });
+        if config.month_path.is_empty() {
+            continue;
+        }
+
+        let mut months: BTreeMap<u32, Vec<(DateTime<Tz>, &Page)>> =
+            BTreeMap::new();
+        for (date, page) in dated {
+            months.entry(date.month()).or_default().push((date, page));
+        }
+        for (month, dated) in months.into_iter().rev() {
+            let title = NaiveDate::from_ymd_opt(year, month, 1)
+                .map_or_else(
+                    || format!("{}-{:02}", year, month),
+                    |date| date.format("%B %Y").to_string(),
+                );
+            archives.push(ArchivePage {
+                path: archive_path(&config.month_path, year, month)?,
+                frontmatter: frontmatter(
+                    title,
+                    &config.month_template,
+                    json!({ "year": year, "month": month }),
+                    &dated,
+                ),
+            });
+        }
+    }
+    Ok(archives)
+}
+
+/// Returns the output path of an archive from its pattern.
+fn archive_path(
+    pattern: &str,
+    year: i32,
+    month: u32,
+) -> Result<PathBuf> {
+    let path = pattern
+        .replace("{year}", &year.to_string())
+        .replace("{month}", &format!("{:02}", month));
+    generated_path("Archive", pattern, &path)
+}
+
+/// Returns the frontmatter of an archive listing dated pages.
+fn frontmatter(
+    title: String,
+    template: &str,
+    archive: JsonValue,
+    dated: &[(DateTime<Tz>, &Page)],
+) -> Map<String, JsonValue> {
+    let pages: Vec<JsonValue> = dated
+        .iter()
+        .map(|(_, page)| {
+            json!({
+                "url": page.url,
+                "title": page.title(),
+                "date": page.date(),
+            })
+        })
+        .collect();
+    let mut frontmatter = Map::new();
+    _ = frontmatter.insert("title".to_string(), title.into());
+    _ = frontmatter.insert("template".to_string(), template.into());
+    _ = frontmatter.insert("archive".to_string(), archive);
+    _ = frontmatter.insert("pages".to_string(), pages.into());
+    frontmatter
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::site::tests::page;
+
+    #[test]
+    fn test_archive_pages() -> Result<()> {
+        let pages = [
+            page(
+                "a.html",
+                json!({ "date": "2023-12-31", "title": "A" }),
+            ),
+            page("b.html", json!({ "date": "2024-05-01T10:00:00Z" })),
+            page("c.html", json!({ "date": "2024-05-20" })),
+            page("d.html", json!({ "date": "2024-01-02" })),
+            page(
+                "e.html",
+                json!({ "date": "2024-02-01", "draft": true }),
+            ),
+            page("f.html", json!({ "date": "someday" })),
+            page("g.html", json!({})),
+        ];
+        let archives = archive_pages(
+            &ArchiveConfig::default(),
+            &SiteTimezone::utc(),
+            &pages,
+        )?;
+        let listed: Vec<(String, Vec<&str>)> = archives
+            .iter()
+            .map(|archive| {
+                (
+                    archive.path.to_string_lossy().replace('\\', "/"),
+                    archive.frontmatter["pages"]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .map(|page| page["url"].as_str().unwrap())
+                        .collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            listed,
+            [
+                (
+                    "archive/2024/index.html".to_string(),
+                    vec!["/c.html", "/b.html", "/d.html"]
+                ),
+                (
+                    "archive/2024/05/index.html".to_string(),
+                    vec!["/c.html", "/b.html"]
+                ),
+                (
+                    "archive/2024/01/index.html".to_string(),
+                    vec!["/d.html"]
+                ),
+                (
+                    "archive/2023/index.html".to_string(),
+                    vec!["/a.html"]
+                ),
+                (
+                    "archive/2023/12/index.html".to_string(),
+                    vec!["/a.html"]
+                ),
+            ]
+        );
+        let december = &archives[4].frontmatter;
+        assert_eq!(december["title"], "December 2023");
+        assert_eq!(december["template"], "archive");
+        assert_eq!(
+            december["archive"],
+            json!({ "year": 2023, "month": 12 })
+        );
+        assert_eq!(december["pages"][0]["title"], "A");
+        Ok(())
+    }
+
+    #[test]
+    fn test_archive_paths() {
+        let pages = [page("a.html", json!({ "date": "2024-05-01" }))];
+        let config = ArchiveConfig {
+            year_path: "/posts/{year}.html".to_string(),
+            month_path: String::new(),
+            ..ArchiveConfig::default()
+        };
+        let archives =
+            archive_pages(&config, &SiteTimezone::utc(), &pages)
+                .unwrap();
+        assert_eq!(archives.len(), 1);
+        assert_eq!(archives[0].path, PathBuf::from("posts/2024.html"));
+
+        let config = ArchiveConfig {
+            year_path: "../{year}/".to_string(),
+            ..ArchiveConfig::default()
+        };
+        assert!(archive_pages(&config, &SiteTimezone::utc(), &pages)
+            .unwrap_err()
+            .to_string()
+            .contains("outside the output directory"));
+    }
+}
@@ -0,0 +1,409 @@
+//! # Authors
+//!
+//! Gives templates the details of each page's authors, and generates a
+//! page per author listing the pages they wrote, such as
+//! `/authors/ada/`. Where author pages are written, the template they
+//! are rendered with and the data file describing authors are set by
+//! [`AuthorConfig`].
+//!
+//! The data file is a TOML table of authors, keyed by the ids pages
+//! name them by in their `author` frontmatter, which is an id or a list
+//! of ids:
+//!
+//! ```toml
+//! [ada]
+//! name = "Ada Lovelace"
+//! bio = "Writes about analytical engines."
+//! avatar = "/images/ada.png"
+//! ```
+//!
+//! Templates see a page's authors as `page.authors`, each with its
+//! `id`, display `name`, `bio`, `avatar` and the `url` of its author
+//! page, along with any other keys its entry sets. An author missing
+//! from the data file is named by its id.
+//!
+//! Each author page is rendered like a content page, with its listing
+//! in its frontmatter, which templates see as `page.frontmatter`:
+//!
+//! - `title` is the author's display name,
+//! - `profile` holds the author's details, and
+//! - `pages` lists the `url`, `title` and `date` of each page, newest
+//!   first.
+//!
+//! Drafts and utility pages are left out. An author page is not
+//! generated where a content page is written.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::core::config::AuthorConfig;
+//! use nucleusflow::generators::authors::{Author, Authors};
+//! use nucleusflow::site::Page;
+//!
+//! let post = Page::new(
+//!     "content/post.md",
+//!     "post.html",
+//!     serde_json::json!({ "author": "ada" })
+//!         .as_object()
+//!         .cloned()
+//!         .unwrap(),
+//! );
+//! let ada = Author {
+//!     name: Some("Ada Lovelace".to_string()),
+//!     ..Author::default()
+//! };
+//! let authors = Authors::new(
+//!     AuthorConfig::default(),
+//!     [("ada".to_string(), ada)].into_iter().collect(),
+//! );
+//! let details = authors.page_authors(&post).unwrap();
+//! assert_eq!(details[0]["name"], "Ada Lovelace");
+//! assert_eq!(details[0]["url"], "/authors/ada/");
+//!
+//! let pages = authors.pages(&[post]).unwrap();
+//! assert_eq!(
+//!     pages[0].path,
+//!     std::path::PathBuf::from("authors/ada/index.html")
+//! );
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value as JsonValue};
+
+use crate::core::config::AuthorConfig;
+use crate::import::slugify;
+use crate::site::{generated_path, page_url, Page};
+use crate::{ProcessingError, Result};
+
+/// An author's entry in the data file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Author {
+    /// The author's display name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// A short biography
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bio: Option<String>,
+
+    /// The URL of the author's picture
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avatar: Option<String>,
+
+    /// Any other keys the entry sets, such as social profiles
+    #[serde(flatten)]
+    pub extra: Map<String, JsonValue>,
+}
+
+/// An author page, ready to be rendered.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthorPage {
+    /// The author page's path relative to the output directory
+    pub path: PathBuf,
+
+    /// The author page's title, template, profile and pages
+    pub frontmatter: Map<String, JsonValue>,
+}
+
+/// The site's authors, and where their pages are written.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Authors {
+    config: AuthorConfig,
+    authors: BTreeMap<String, Author>,
+}
+
+impl Authors {
+    /// Creates the authors of a site from their entries.
+    pub fn new(
+        config: AuthorConfig,
+        authors: BTreeMap<String, Author>,
+    ) -> Self {
+        Self { config, authors }
+    }
+
+    /// Reads the authors from the configured data file.
+    ///
+    /// # Arguments
+    /// * `config` - The data file and where author pages are written.
+    ///
+    /// # Returns
+    /// * `Result<Self>` - The authors, with none described if the data
+    ///   file does not exist, or an error if it cannot be read or
+    ///   parsed.
+    pub fn read(config: AuthorConfig) -> Result<Self> {
+        let data = match fs::read_to_string(&config.data) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Ok(Self::new(config, BTreeMap::new()));
+            }
+            Err(e) => {
+                return Err(ProcessingError::io_error(
+                    config.data.clone(),
+                    e,
+                ))
+            }
+        };
+        let authors = toml::from_str(&data).map_err(|e| {
+            ProcessingError::serialization(
+                format!(
+                    "Failed to parse authors from '{}'",
+                    config.data.display()
+                ),
+                Some(Box::new(e)),
+            )
+        })?;
+        Ok(Self::new(config, authors))
+    }
+
+    /// Returns an author's details for templates.
+    ///
+    /// # Arguments
+    /// * `id` - The id a page names the author by.
+    ///
+    /// # Returns
+    /// * `Result<JsonValue>` - The author's entry with its `id`, `name`
+    ///   and `url`, or an error if the configured path is outside the
+    ///   output directory.
+    pub fn details(&self, id: &str) -> Result<JsonValue> {
+        let author = self.authors.get(id).cloned().unwrap_or_default();
+        let mut details = author.extra;
+        _ = details.insert("id".to_string(), id.into());
+        _ = details.insert(
+            "name".to_string(),
+            author.name.as_deref().unwrap_or(id).into(),
+        );
+        if let Some(bio) = author.bio {
+            _ = details.insert("bio".to_string(), bio.into());
+        }
+        if let Some(avatar) = author.avatar {
+            _ = details.insert("avatar".to_string(), avatar.into());
+        }
+        _ = details.insert(
+            "url".to_string(),
+            page_url(&self.path(id)?).into(),
+        );
+        Ok(JsonValue::Object(details))
+    }
+
+    /// Returns the details of the authors a page names, in the order it
+    /// names them.
+    ///
+    /// # Returns
+    /// * `Result<Vec<JsonValue>>` - Each author's details, or an error
+    ///   if the configured path is outside the output directory.
+    pub fn page_authors(&self, page: &Page) -> Result<Vec<JsonValue>> {
+        author_ids(page).iter().map(|id| self.details(id)).collect()
+    }
+
+    /// Lists the pages of each author named by a page.
+    ///
+    /// # Arguments
+    /// * `pages` - The site's pages.
+    ///
+    /// # Returns
+    /// * `Result<Vec<AuthorPage>>` - The author pages, ordered by id,
+    ///   or an error if the configured path is outside the output
+    ///   directory.
+    pub fn pages(&self, pages: &[Page]) -> Result<Vec<AuthorPage>> {
+        let mut written: BTreeMap<String, Vec<&Page>> = BTreeMap::new();
+        for page in
+            pages.iter().filter(|p| !p.is_draft() && !p.is_utility())
+        {
+            for id in author_ids(page) {
+                let listed = written.entry(id).or_default();
+                if listed.iter().all(|p| p.url != page.url) {
+                    listed.push(page);
+                }
+            }
+        }
+
+        let mut author_pages = Vec::with_capacity(written.len());
+        for (id, mut listed) in written {
+            listed.sort_by(|a, b| {
+                let key = |page: &Page| {
+                    (
+                        page.date().is_some(),
+                        page.date().map(str::to_string),
+                    )
+                };
+                key(b).cmp(&key(a)).then_with(|| a.url.cmp(&b.url))
+            });
+            let profile = self.details(&id)?;
+            let pages: Vec<JsonValue> = listed
+                .iter()
+                .map(|page| {
+                    json!({
+                        "url": page.url,
+                        "title": page.title(),
+                        "date": page.date(),
+                    })
+                })
+                .collect();
+            let mut frontmatter = Map::new();
+            _ = frontmatter
+                .insert("title".to_string(), profile["name"].clone());
+            _ = frontmatter.insert(
+                "template".to_string(),
+                self.config.template.clone().into(),
+            );
+            _ = frontmatter.insert("profile".to_string(), profile);
+            _ = frontmatter.insert("pages".to_string(), pages.into());
+            author_pages.push(AuthorPage {
+                path: self.path(&id)?,
+                frontmatter,
+            });
+        }
+        Ok(author_pages)
+    }
+
+    /// Returns the output path of an author's page.
+    fn path(&self, id: &str) -> Result<PathBuf> {
+        let pattern = &self.config.path;
+        let path = pattern.replace("{author}", &slugify(id));
+        generated_path("Author", pattern, &path)
+    }
+}
+
+/// Returns the author ids a page's `author` frontmatter names.
+fn author_ids(page: &Page) -> Vec<String> {
+    let ids = match page.frontmatter.get("author") {
+        Some(JsonValue::String(id)) => vec![id.as_str()],
+        Some(JsonValue::Array(ids)) => {
+            ids.iter().filter_map(JsonValue::as_str).collect()
+        }
+        _ => Vec::new(),
+    };
+    ids.into_iter()
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::site::tests::page;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_read_authors() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let data = temp_dir.path().join("authors.toml");
+        fs::write(
+            &data,
+            "[ada]\nname = \"Ada Lovelace\"\nbio = \"Engines.\"\n\
+             avatar = \"/ada.png\"\nmastodon = \"@ada\"\n",
+        )?;
+        let config = AuthorConfig {
+            data: data.clone(),
+            ..AuthorConfig::default()
+        };
+        let authors = Authors::read(config.clone())?;
+        assert_eq!(
+            authors.details("ada")?,
+            json!({
+                "id": "ada",
+                "name": "Ada Lovelace",
+                "bio": "Engines.",
+                "avatar": "/ada.png",
+                "mastodon": "@ada",
+                "url": "/authors/ada/",
+            })
+        );
+        assert_eq!(
+            authors.details("Grace Hopper")?,
+            json!({
+                "id": "Grace Hopper",
+                "name": "Grace Hopper",
+                "url": "/authors/grace-hopper/",
+            })
+        );
+
+        fs::write(&data, "ada = 1")?;
+        assert!(Authors::read(config.clone()).is_err());
+        fs::remove_file(&data)?;
+        assert_eq!(
+            Authors::read(config)?.details("ada")?["name"],
+            "ada"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_author_pages() -> Result<()> {
+        let pages = [
+            page(
+                "a.html",
+                json!({ "author": "ada", "date": "2023-01-01" }),
+            ),
+            page(
+                "b.html",
+                json!({
+                    "author": ["grace", "ada"],
+                    "date": "2024-01-01",
+                    "title": "B",
+                }),
+            ),
+            page("c.html", json!({ "author": ["ada", "ada"] })),
+            page("d.html", json!({ "author": "ada", "draft": true })),
+            page("e.html", json!({})),
+        ];
+        let authors = Authors::default();
+        assert_eq!(
+            authors.page_authors(&pages[1])?,
+            [authors.details("grace")?, authors.details("ada")?]
+        );
+
+        let author_pages = authors.pages(&pages)?;
+        let listed: Vec<(&str, Vec<&str>)> = author_pages
+            .iter()
+            .map(|author| {
+                (
+                    author.frontmatter["title"].as_str().unwrap(),
+                    author.frontmatter["pages"]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .map(|page| page["url"].as_str().unwrap())
+                        .collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            listed,
+            [
+                ("ada", vec!["/b.html", "/a.html", "/c.html"]),
+                ("grace", vec!["/b.html"]),
+            ]
+        );
+        assert_eq!(
+            author_pages[1].path,
+            PathBuf::from("authors/grace/index.html")
+        );
+        assert_eq!(author_pages[1].frontmatter["template"], "author");
+        assert_eq!(
+            author_pages[1].frontmatter["pages"][0]["title"],
+            "B"
+        );
+
+        let authors = Authors::new(
+            AuthorConfig {
+                path: "../{author}/".to_string(),
+                ..AuthorConfig::default()
+            },
+            BTreeMap::new(),
+        );
+        assert!(authors
+            .pages(&pages)
+            .unwrap_err()
+            .to_string()
+            .contains("outside the output directory"));
+        Ok(())
+    }
+}
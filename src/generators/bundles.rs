@@ -0,0 +1,300 @@
+//! # Per-Page Asset Bundles
+//!
+//! Pages can declare extra stylesheets and scripts in their frontmatter.
+//! The bundles are resolved against the asset directory, written to the
+//! output with a content fingerprint in their file name, and injected into
+//! that page only, keeping the site-wide bundles small.
+//!
+//! ```yaml
+//! ---
+//! title: Gallery
+//! bundles:
+//!   css: [css/lightbox.css]
+//!   js: [js/lightbox.js]
+//! ---
+//! ```
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::generators::bundles::{inject_bundles, ResolvedBundles};
+//!
+//! let resolved = ResolvedBundles {
+//!     stylesheets: vec!["css/lightbox.3f2a1b9c.css".to_string()],
+//!     scripts: vec![],
+//! };
+//! let html = inject_bundles("<head></head><body></body>", &resolved);
+//! assert!(html.contains(r#"<link rel="stylesheet" href="css/lightbox.3f2a1b9c.css">"#));
+//! ```
+
+use std::fs;
+use std::path::{Component, Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::generators::fingerprint::fingerprinted_path;
+use crate::{ProcessingError, Result};
+
+/// Bundles declared by a page, as paths relative to the asset directory.
+#[derive(
+    Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(deny_unknown_fields)]
+pub struct PageBundles {
+    /// Stylesheets to link in the page `<head>`
+    #[serde(default)]
+    pub css: Vec<String>,
+
+    /// Scripts to load at the end of the page `<body>`
+    #[serde(default)]
+    pub js: Vec<String>,
+}
+
+impl PageBundles {
+    /// Returns `true` if the page declares no bundles.
+    pub fn is_empty(&self) -> bool {
+        self.css.is_empty() && self.js.is_empty()
+    }
+}
+
+/// Fingerprinted URLs for a page's bundles, relative to the page.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResolvedBundles {
+    /// Stylesheet URLs, in declaration order
+    pub stylesheets: Vec<String>,
+
+    /// Script URLs, in declaration order
+    pub scripts: Vec<String>,
+}
+
+/// Resolves, fingerprints and writes a page's bundles.
+///
+/// # Arguments
+/// * `bundles` - The bundles declared by the page.
+/// * `asset_dir` - The directory bundle paths are relative to.
+/// * `output_dir` - The directory fingerprinted files are written to.
+///
+/// # Returns
+/// * `Result<ResolvedBundles>` - The fingerprinted URLs, or an error if a
+///   bundle path is unsafe, has the wrong extension or does not exist.
+pub fn resolve_bundles(
+    bundles: &PageBundles,
+    asset_dir: &Path,
+    output_dir: &Path,
+) -> Result<ResolvedBundles> {
+    let mut resolved = ResolvedBundles::default();
+    for bundle in &bundles.css {
+        resolved.stylesheets.push(write_bundle(
+            bundle,
+            &["css"],
+            asset_dir,
+            output_dir,
+        )?);
+    }
+    for bundle in &bundles.js {
+        resolved.scripts.push(write_bundle(
+            bundle,
+            &["js", "mjs"],
+            asset_dir,
+            output_dir,
+        )?);
+    }
+    Ok(resolved)
+}
+
+/// Injects stylesheet links before `</head>` and scripts before `</body>`.
+///
+/// If either closing tag is missing, the tags are prepended or appended
+/// to the document respectively.
+pub fn inject_bundles(
+    html: &str,
+    resolved: &ResolvedBundles,
+) -> String {
+    let links: String = resolved
+        .stylesheets
+        .iter()
+        .map(|href| {
+            format!(
+                "<link rel=\"stylesheet\" href=\"{}\">",
+                escape_attribute(href)
+            )
+        })
+        .collect();
+    let scripts: String = resolved
+        .scripts
+        .iter()
+        .map(|src| {
+            format!(
+                "<script src=\"{}\" defer></script>",
+                escape_attribute(src)
+            )
+        })
+        .collect();
+
+    let mut output =
+        String::with_capacity(html.len() + links.len() + scripts.len());
+    match html.find("</head>") {
+        Some(pos) => {
+            output.push_str(&html[..pos]);
+            output.push_str(&links);
+            output.push_str(&html[pos..]);
+        }
+        None => {
+            output.push_str(&links);
+            output.push_str(html);
+        }
+    }
+    match output.rfind("</body>") {
+        Some(pos) => output.insert_str(pos, &scripts),
+        None => output.push_str(&scripts),
+    }
+    output
+}
+
+/// Copies a single bundle to its fingerprinted location.
+fn write_bundle(
+    bundle: &str,
+    extensions: &[&str],
+    asset_dir: &Path,
+    output_dir: &Path,
+) -> Result<String> {
+    let relative = Path::new(bundle);
+    if relative
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_)))
+    {
+        return Err(ProcessingError::validation(
+            format!(
+                "Bundle '{}' must be a relative path inside the asset directory",
+                bundle
+            ),
+            None::<String>,
+        ));
+    }
+    let extension = relative
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("");
+    if !extensions.contains(&extension) {
+        return Err(ProcessingError::validation(
+            format!(
+                "Bundle '{}' must have one of the extensions: {}",
+                bundle,
+                extensions.join(", ")
+            ),
+            None::<String>,
+        ));
+    }
+
+    let source = asset_dir.join(relative);
+    let content = fs::read(&source)
+        .map_err(|e| ProcessingError::io_error(source.clone(), e))?;
+    let fingerprinted = fingerprinted_path(relative, &content);
+    let target = output_dir.join(&fingerprinted);
+    if !target.exists() {
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| {
+                ProcessingError::io_error(parent.to_path_buf(), e)
+            })?;
+        }
+        fs::write(&target, &content).map_err(|e| {
+            ProcessingError::io_error(target.clone(), e)
+        })?;
+    }
+
+    Ok(fingerprinted
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/"))
+}
+
+/// Escapes a value for use inside a double-quoted attribute.
+fn escape_attribute(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_resolve_bundles() {
+        let assets = TempDir::new().unwrap();
+        let output = TempDir::new().unwrap();
+        fs::create_dir(assets.path().join("css")).unwrap();
+        fs::write(assets.path().join("css/gallery.css"), "a{}")
+            .unwrap();
+        fs::write(assets.path().join("gallery.js"), "run()").unwrap();
+
+        let bundles = PageBundles {
+            css: vec!["css/gallery.css".to_string()],
+            js: vec!["gallery.js".to_string()],
+        };
+        let resolved =
+            resolve_bundles(&bundles, assets.path(), output.path())
+                .unwrap();
+
+        assert_eq!(resolved.stylesheets.len(), 1);
+        let href = &resolved.stylesheets[0];
+        assert!(
+            href.starts_with("css/gallery.") && href.ends_with(".css")
+        );
+        assert_eq!(
+            fs::read_to_string(output.path().join(href)).unwrap(),
+            "a{}"
+        );
+        assert!(output.path().join(&resolved.scripts[0]).exists());
+    }
+
+    #[test]
+    fn test_rejects_unsafe_bundles() {
+        let assets = TempDir::new().unwrap();
+        let output = TempDir::new().unwrap();
+
+        for (css, js) in [
+            (vec!["../secret.css"], vec![]),
+            (vec!["/etc/passwd.css"], vec![]),
+            (vec!["style.js"], vec![]),
+            (vec![], vec!["missing.js"]),
+        ] {
+            let bundles = PageBundles {
+                css: css.into_iter().map(String::from).collect(),
+                js: js.into_iter().map(String::from).collect(),
+            };
+            assert!(resolve_bundles(
+                &bundles,
+                assets.path(),
+                output.path()
+            )
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn test_inject_bundles() {
+        let resolved = ResolvedBundles {
+            stylesheets: vec!["a.css".to_string()],
+            scripts: vec!["b.js".to_string()],
+        };
+
+        let html = inject_bundles(
+            "<html><head><title>T</title></head><body><p>x</p></body></html>",
+            &resolved,
+        );
+        assert_eq!(
+            html,
+            "<html><head><title>T</title><link rel=\"stylesheet\" href=\"a.css\"></head>\
+             <body><p>x</p><script src=\"b.js\" defer></script></body></html>"
+        );
+
+        // Fragments without head or body
+        let html = inject_bundles("<p>x</p>", &resolved);
+        assert!(html.starts_with("<link"));
+        assert!(html.ends_with("</script>"));
+    }
+}
@@ -0,0 +1,279 @@
+//! # Pre-Compressed Output
+//!
+//! Emits `.gz` and `.br` twins next to generated text assets, so servers
+//! that support pre-compressed files can send them directly instead of
+//! compressing each response on the fly. Both formats use their highest
+//! compression level, since the work is done once at build time.
+//!
+//! A twin is only written when it is smaller than the original, and
+//! existing twins are overwritten so they never go stale.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use nucleusflow::core::config::PrecompressConfig;
+//! use nucleusflow::generators::compress::precompress_dir;
+//! use std::path::Path;
+//!
+//! let config = PrecompressConfig {
+//!     gzip: true,
+//!     brotli: true,
+//!     ..PrecompressConfig::default()
+//! };
+//! let report = precompress_dir(Path::new("public"), &config).unwrap();
+//! println!("Compressed {} files", report.files);
+//! ```
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use brotli::enc::BrotliEncoderParams;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use walkdir::WalkDir;
+
+use crate::core::config::PrecompressConfig;
+use crate::{ProcessingError, Result};
+
+/// Brotli quality level (0-11).
+const BROTLI_QUALITY: i32 = 11;
+
+/// Brotli window size as a power of two.
+const BROTLI_WINDOW: i32 = 22;
+
+/// The outcome of a pre-compression pass.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrecompressReport {
+    /// Number of files that received at least one twin
+    pub files: usize,
+
+    /// Paths of the twins written
+    pub written: Vec<PathBuf>,
+
+    /// Combined size of the compressed originals in bytes
+    pub original_bytes: u64,
+
+    /// Combined size of the twins written in bytes
+    pub compressed_bytes: u64,
+}
+
+/// Writes compressed twins for every matching file under `output_dir`.
+///
+/// # Arguments
+/// * `output_dir` - The generated site's output directory.
+/// * `config` - The formats, extensions and size threshold to use.
+///
+/// # Returns
+/// * `Result<PrecompressReport>` - The twins written and the sizes
+///   involved.
+pub fn precompress_dir(
+    output_dir: &Path,
+    config: &PrecompressConfig,
+) -> Result<PrecompressReport> {
+    let mut report = PrecompressReport::default();
+    if !config.is_enabled() || !output_dir.is_dir() {
+        return Ok(report);
+    }
+
+    for entry in WalkDir::new(output_dir).sort_by_file_name() {
+        let entry = entry.map_err(|e| {
+            ProcessingError::content_processing(
+                "Failed to read output directory",
+                Some(Box::new(e)),
+            )
+        })?;
+        if !entry.file_type().is_file()
+            || !is_candidate(entry.path(), config)
+        {
+            continue;
+        }
+
+        let original = fs::read(entry.path()).map_err(|e| {
+            ProcessingError::io_error(entry.path().to_path_buf(), e)
+        })?;
+        if (original.len() as u64) < config.min_size {
+            continue;
+        }
+
+        let written =
+            precompress_bytes(entry.path(), &original, config)?;
+        if !written.is_empty() {
+            report.files += 1;
+            report.original_bytes += original.len() as u64;
+            for (path, size) in written {
+                report.compressed_bytes += size;
+                report.written.push(path);
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Returns `true` if a file's extension is configured for compression.
+fn is_candidate(path: &Path, config: &PrecompressConfig) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map_or(false, |ext| {
+            config
+                .extensions
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(ext))
+        })
+}
+
+/// Writes the enabled twins of a single file, returning their paths and
+/// sizes.
+fn precompress_bytes(
+    path: &Path,
+    original: &[u8],
+    config: &PrecompressConfig,
+) -> Result<Vec<(PathBuf, u64)>> {
+    let mut written = Vec::new();
+    if config.gzip {
+        let compressed = gzip_bytes(original).map_err(|e| {
+            ProcessingError::io_error(path.to_path_buf(), e)
+        })?;
+        if let Some(twin) =
+            write_twin(path, "gz", original, &compressed)?
+        {
+            written.push(twin);
+        }
+    }
+    if config.brotli {
+        let compressed = brotli_bytes(original).map_err(|e| {
+            ProcessingError::io_error(path.to_path_buf(), e)
+        })?;
+        if let Some(twin) =
+            write_twin(path, "br", original, &compressed)?
+        {
+            written.push(twin);
+        }
+    }
+    Ok(written)
+}
+
+/// Writes a twin if it is smaller than the original, removing any stale
+/// twin otherwise.
+fn write_twin(
+    path: &Path,
+    suffix: &str,
+    original: &[u8],
+    compressed: &[u8],
+) -> Result<Option<(PathBuf, u64)>> {
+    let mut twin = path.as_os_str().to_owned();
+    twin.push(".");
+    twin.push(suffix);
+    let twin = PathBuf::from(twin);
+
+    if compressed.len() >= original.len() {
+        if twin.exists() {
+            fs::remove_file(&twin).map_err(|e| {
+                ProcessingError::io_error(twin.clone(), e)
+            })?;
+        }
+        return Ok(None);
+    }
+    fs::write(&twin, compressed)
+        .map_err(|e| ProcessingError::io_error(twin.clone(), e))?;
+    Ok(Some((twin, compressed.len() as u64)))
+}
+
+/// Compresses bytes with gzip at its highest level.
+pub(crate) fn gzip_bytes(bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(bytes)?;
+    encoder.finish()
+}
+
+/// Compresses bytes with brotli at its highest quality.
+pub(crate) fn brotli_bytes(bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+    let params = BrotliEncoderParams {
+        quality: BROTLI_QUALITY,
+        lgwin: BROTLI_WINDOW,
+        ..BrotliEncoderParams::default()
+    };
+    let mut output = Vec::new();
+    _ = brotli::BrotliCompress(&mut &bytes[..], &mut output, &params)?;
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+    use tempfile::TempDir;
+
+    fn config() -> PrecompressConfig {
+        PrecompressConfig {
+            gzip: true,
+            brotli: true,
+            ..PrecompressConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_precompress_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let page = "<p>Hello, world!</p>\n".repeat(200);
+        fs::write(root.join("index.html"), &page).unwrap();
+        fs::create_dir(root.join("css")).unwrap();
+        fs::write(root.join("css/site.css"), "a{}").unwrap();
+        fs::write(root.join("logo.png"), page.as_bytes()).unwrap();
+
+        let report = precompress_dir(root, &config()).unwrap();
+
+        assert_eq!(report.files, 1);
+        assert_eq!(report.written.len(), 2);
+        assert!(report.compressed_bytes < report.original_bytes);
+        // Below the size threshold, or not a configured extension
+        assert!(!root.join("css/site.css.gz").exists());
+        assert!(!root.join("logo.png.gz").exists());
+
+        let mut decoded = String::new();
+        _ = GzDecoder::new(
+            fs::File::open(root.join("index.html.gz")).unwrap(),
+        )
+        .read_to_string(&mut decoded)
+        .unwrap();
+        assert_eq!(decoded, page);
+
+        let mut decoded = Vec::new();
+        brotli::BrotliDecompress(
+            &mut fs::File::open(root.join("index.html.br")).unwrap(),
+            &mut decoded,
+        )
+        .unwrap();
+        assert_eq!(decoded, page.as_bytes());
+    }
+
+    #[test]
+    fn test_disabled() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("index.html"), "x".repeat(4096))
+            .unwrap();
+
+        let report = precompress_dir(
+            temp_dir.path(),
+            &PrecompressConfig::default(),
+        )
+        .unwrap();
+        assert_eq!(report, PrecompressReport::default());
+    }
+
+    #[test]
+    fn test_incompressible_removes_stale_twin() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("data.json");
+        fs::write(temp_dir.path().join("data.json.gz"), "stale")
+            .unwrap();
+
+        assert!(write_twin(&path, "gz", b"ab", b"abc")
+            .unwrap()
+            .is_none());
+        assert!(!temp_dir.path().join("data.json.gz").exists());
+    }
+}
@@ -0,0 +1,352 @@
+//! # Data Pages
+//!
+//! Generates a page per record of a collection in a JSON, YAML or TOML
+//! data file, such as a page per team member or product, rendered with
+//! the collection's template. Where the records are, where their pages
+//! are written and their template are set by [`DataPagesConfig`].
+//!
+//! A collection is a list of records, or a table of records keyed by
+//! id, which gives each record its key as `id` unless it sets one:
+//!
+//! ```yaml
+//! ada:
+//!   name: Ada Lovelace
+//!   role: Engineer
+//! grace:
+//!   name: Grace Hopper
+//!   role: Admiral
+//! ```
+//!
+//! Each page's path is its collection's pattern, such as `team/{id}/`,
+//! with every `{field}` replaced by the record's value for it as a
+//! slug. A record without a value for a field its path names fails the
+//! build, as do two records written to the same path.
+//!
+//! Each page is rendered like a content page, with its record as its
+//! frontmatter, which templates see as `page.frontmatter`, such as
+//! `page.frontmatter.role`. A page is not generated where a content
+//! page is written.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::core::config::DataPagesConfig;
+//! use nucleusflow::generators::data_pages::record_pages;
+//! use serde_json::json;
+//!
+//! let config = DataPagesConfig {
+//!     data: "data/team.yaml".into(),
+//!     items: String::new(),
+//!     path: "team/{name}/".to_string(),
+//!     template: "member".to_string(),
+//! };
+//! let records =
+//!     json!([{ "name": "Ada Lovelace", "role": "Engineer" }]);
+//!
+//! let pages = record_pages(&config, &records).unwrap();
+//! assert_eq!(
+//!     pages[0].path,
+//!     std::path::PathBuf::from("team/ada-lovelace/index.html")
+//! );
+//! assert_eq!(pages[0].frontmatter["role"], "Engineer");
+//! assert_eq!(pages[0].frontmatter["template"], "member");
+//! ```
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+
+use serde_json::{Map, Value as JsonValue};
+
+use crate::core::config::DataPagesConfig;
+use crate::import::slugify;
+use crate::site::generated_path;
+use crate::{ProcessingError, Result};
+
+/// A page generated from a data record, ready to be rendered.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataPage {
+    /// The page's path relative to the output directory
+    pub path: PathBuf,
+
+    /// The page's record and template
+    pub frontmatter: Map<String, JsonValue>,
+}
+
+/// Reads a collection's data file and generates a page per record.
+///
+/// # Arguments
+/// * `config` - The data file, where its records are, where their
+///   pages are written and their template.
+///
+/// # Returns
+/// * `Result<Vec<DataPage>>` - The pages, in record order, or an error
+///   if the data file cannot be read, holds no records where the
+///   configuration says, or a record's path is invalid.
+pub fn data_pages(config: &DataPagesConfig) -> Result<Vec<DataPage>> {
+    let text = fs::read_to_string(&config.data).map_err(|e| {
+        ProcessingError::io_error(config.data.clone(), e)
+    })?;
+    let invalid = |e: Box<dyn std::error::Error + Send + Sync>| {
+        ProcessingError::serialization(
+            format!("Invalid data file {}", config.data.display()),
+            Some(e),
+        )
+    };
+    let extension = config
+        .data
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase());
+    let data: JsonValue = match extension.as_deref() {
+        Some("json") => serde_json::from_str(&text)
+            .map_err(|e| invalid(e.into()))?,
+        Some("yaml" | "yml") => {
+            serde_yml::from_str(&text).map_err(|e| invalid(e.into()))?
+        }
+        Some("toml") => {
+            let table: toml::Table =
+                toml::from_str(&text).map_err(|e| invalid(e.into()))?;
+            serde_json::to_value(table)
+                .map_err(|e| invalid(e.into()))?
+        }
+        _ => {
+            return Err(ProcessingError::configuration(
+                format!(
+                    "Data file {} is not JSON, YAML or TOML",
+                    config.data.display()
+                ),
+                Some(config.data.clone()),
+                None,
+            ))
+        }
+    };
+    record_pages(config, &data)
+}
+
+/// Generates a page per record of a collection's data.
+///
+/// # Arguments
+/// * `config` - Where the records are, where their pages are written
+///   and their template.
+/// * `data` - The data file's contents.
+///
+/// # Returns
+/// * `Result<Vec<DataPage>>` - The pages, in record order, or an error
+///   if there are no records where the configuration says, or a
+///   record's path is invalid.
+pub fn record_pages(
+    config: &DataPagesConfig,
+    data: &JsonValue,
+) -> Result<Vec<DataPage>> {
+    let records = records(config, data)?;
+    let mut paths = HashSet::new();
+    let mut pages = Vec::new();
+    for (index, mut record) in records.into_iter().enumerate() {
+        let path = record_path(config, &record, index)?;
+        if !paths.insert(path.clone()) {
+            return Err(ProcessingError::validation(
+                format!(
+                    "Several records of {} are written to {}",
+                    config.data.display(),
+                    path.display()
+                ),
+                None::<String>,
+            ));
+        }
+        _ = record.insert(
+            "template".to_string(),
+            config.template.clone().into(),
+        );
+        pages.push(DataPage {
+            path,
+            frontmatter: record,
+        });
+    }
+    Ok(pages)
+}
+
+/// Returns the records of a collection's data, giving the records of a
+/// table their keys as `id`.
+fn records(
+    config: &DataPagesConfig,
+    data: &JsonValue,
+) -> Result<Vec<Map<String, JsonValue>>> {
+    let invalid = || {
+        ProcessingError::validation(
+            format!(
+                "Data file {} has no list or table of records at '{}'",
+                config.data.display(),
+                config.items
+            ),
+            None::<String>,
+        )
+    };
+    match data.pointer(&config.items).ok_or_else(invalid)? {
+        JsonValue::Array(records) => records
+            .iter()
+            .map(|record| {
+                record.as_object().cloned().ok_or_else(invalid)
+            })
+            .collect(),
+        JsonValue::Object(records) => records
+            .iter()
+            .map(|(id, record)| {
+                let mut record =
+                    record.as_object().cloned().ok_or_else(invalid)?;
+                _ = record
+                    .entry("id")
+                    .or_insert_with(|| id.clone().into());
+                Ok(record)
+            })
+            .collect(),
+        _ => Err(invalid()),
+    }
+}
+
+/// Returns the output path of a record's page, with each `{field}` of
+/// the configured path replaced by the record's value as a slug.
+fn record_path(
+    config: &DataPagesConfig,
+    record: &Map<String, JsonValue>,
+    index: usize,
+) -> Result<PathBuf> {
+    let mut path = String::with_capacity(config.path.len());
+    let mut rest = config.path.as_str();
+    while let Some(start) = rest.find('{') {
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => break,
+        };
+        let field = &rest[start + 1..end];
+        let value = match record.get(field) {
+            Some(JsonValue::String(value)) => slugify(value),
+            Some(JsonValue::Number(value)) => value.to_string(),
+            Some(JsonValue::Bool(value)) => value.to_string(),
+            _ => String::new(),
+        };
+        if value.is_empty() {
+            return Err(ProcessingError::validation(
+                format!(
+                    "Record {} of {} has no '{}' for the path '{}'",
+                    index + 1,
+                    config.data.display(),
+                    field,
+                    config.path
+                ),
+                None::<String>,
+            ));
+        }
+        path.push_str(&rest[..start]);
+        path.push_str(&value);
+        rest = &rest[end + 1..];
+    }
+    path.push_str(rest);
+    generated_path("Data page", &config.path, &path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn collection(data: PathBuf, path: &str) -> DataPagesConfig {
+        DataPagesConfig {
+            data,
+            items: String::new(),
+            path: path.to_string(),
+            template: "product".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_record_pages() -> Result<()> {
+        let config =
+            collection("products.json".into(), "/shop/{sku}.html");
+        let pages = record_pages(
+            &config,
+            &json!([
+                { "sku": 42, "name": "Kettle" },
+                { "sku": "Tea Cup", "template": "ignored" },
+            ]),
+        )?;
+        let paths: Vec<&PathBuf> =
+            pages.iter().map(|page| &page.path).collect();
+        assert_eq!(
+            paths,
+            [
+                &PathBuf::from("shop/42.html"),
+                &PathBuf::from("shop/tea-cup.html")
+            ]
+        );
+        assert_eq!(
+            JsonValue::Object(pages[0].frontmatter.clone()),
+            json!({
+                "sku": 42,
+                "name": "Kettle",
+                "template": "product",
+            })
+        );
+        assert_eq!(pages[1].frontmatter["template"], "product");
+
+        let error = |data: JsonValue| {
+            record_pages(&config, &data).unwrap_err().to_string()
+        };
+        assert!(error(json!([{ "name": "Kettle" }]))
+            .contains("Record 1 of products.json has no 'sku'"));
+        assert!(error(json!([{ "sku": 1 }, { "sku": 1 }]))
+            .contains("Several records"));
+        assert!(error(json!([1])).contains("no list or table"));
+        assert!(error(json!("records")).contains("no list or table"));
+
+        let config = DataPagesConfig {
+            path: "../{sku}/".to_string(),
+            ..config
+        };
+        assert!(record_pages(&config, &json!([{ "sku": 1 }]))
+            .unwrap_err()
+            .to_string()
+            .contains("outside the output directory"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_data_pages() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let yaml = temp_dir.path().join("team.yaml");
+        fs::write(
+            &yaml,
+            "ada:\n  name: Ada\ngrace:\n  id: admiral\n  name: Grace\n",
+        )?;
+        let pages = data_pages(&collection(yaml, "team/{id}/"))?;
+        let paths: Vec<&PathBuf> =
+            pages.iter().map(|page| &page.path).collect();
+        assert_eq!(
+            paths,
+            [
+                &PathBuf::from("team/ada/index.html"),
+                &PathBuf::from("team/admiral/index.html")
+            ]
+        );
+
+        let toml = temp_dir.path().join("products.toml");
+        fs::write(
+            &toml,
+            "[[product]]\nsku = \"kettle\"\n\n\
+             [[product]]\nsku = \"cup\"\n",
+        )?;
+        let config = DataPagesConfig {
+            items: "/product".to_string(),
+            ..collection(toml, "{sku}.html")
+        };
+        assert_eq!(data_pages(&config)?[1].frontmatter["sku"], "cup");
+
+        let csv = temp_dir.path().join("team.csv");
+        fs::write(&csv, "id\nada\n")?;
+        assert!(data_pages(&collection(csv, "{id}.html"))
+            .unwrap_err()
+            .to_string()
+            .contains("is not JSON, YAML or TOML"));
+        Ok(())
+    }
+}
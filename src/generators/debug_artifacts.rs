@@ -0,0 +1,225 @@
+//! # Debug Artifacts
+//!
+//! Asset toolchains emit debugging aids next to compiled stylesheets and
+//! scripts: source maps (`app.js.map`), unminified variants
+//! (`app.debug.js`) and the `sourceMappingURL` comments linking the two.
+//! Development builds keep them so browser tools show the original
+//! sources, while production builds strip them so they are never
+//! deployed.
+//!
+//! Stripping removes the artifact files and the `sourceMappingURL`
+//! comments from `.css`, `.js` and `.mjs` files, and nothing else, so a
+//! development and a production build of the same site differ only in
+//! their debug artifacts and can be compared for parity.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use nucleusflow::generators::debug_artifacts::strip_debug_artifacts;
+//! use std::path::Path;
+//!
+//! let report = strip_debug_artifacts(Path::new("public")).unwrap();
+//! println!("Removed {} debug artifacts", report.removed.len());
+//! ```
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use crate::{ProcessingError, Result};
+
+/// Extensions of the assets whose debug artifacts are stripped.
+const ASSET_EXTENSIONS: &[&str] = &["css", "js", "mjs"];
+
+/// The outcome of stripping an output directory's debug artifacts.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StripReport {
+    /// Source maps and debug variants deleted
+    pub removed: Vec<PathBuf>,
+
+    /// Assets whose `sourceMappingURL` comments were removed
+    pub unlinked: Vec<PathBuf>,
+}
+
+/// Returns `true` if a file is a source map, such as `app.js.map`, or
+/// an unminified debug variant of an asset, such as `app.debug.js`.
+pub fn is_debug_artifact(path: &Path) -> bool {
+    let extension = path.extension().and_then(|ext| ext.to_str());
+    match extension {
+        Some(ext) if ext.eq_ignore_ascii_case("map") => true,
+        Some(ext) if is_asset_extension(ext) => path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .map_or(false, |stem| {
+                stem.to_ascii_lowercase().ends_with(".debug")
+            }),
+        _ => false,
+    }
+}
+
+/// Removes the debug artifacts under `output_dir`, and the
+/// `sourceMappingURL` comments pointing at source maps.
+///
+/// # Arguments
+/// * `output_dir` - The generated site's output directory.
+///
+/// # Returns
+/// * `Result<StripReport>` - The files removed and changed, ordered by
+///   path, or an error if one cannot be read, written or deleted.
+pub fn strip_debug_artifacts(output_dir: &Path) -> Result<StripReport> {
+    let mut report = StripReport::default();
+    if !output_dir.is_dir() {
+        return Ok(report);
+    }
+
+    for entry in WalkDir::new(output_dir).sort_by_file_name() {
+        let entry = entry.map_err(|e| {
+            ProcessingError::content_processing(
+                "Failed to read output directory",
+                Some(Box::new(e)),
+            )
+        })?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let path = entry.path();
+        if is_debug_artifact(path) {
+            fs::remove_file(path).map_err(|e| {
+                ProcessingError::io_error(path.to_path_buf(), e)
+            })?;
+            report.removed.push(path.to_path_buf());
+            continue;
+        }
+        let is_asset = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map_or(false, is_asset_extension);
+        if !is_asset {
+            continue;
+        }
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::InvalidData => {
+                continue
+            }
+            Err(e) => {
+                return Err(ProcessingError::io_error(
+                    path.to_path_buf(),
+                    e,
+                ))
+            }
+        };
+        if let Some(stripped) = strip_source_mapping_urls(&content) {
+            fs::write(path, stripped).map_err(|e| {
+                ProcessingError::io_error(path.to_path_buf(), e)
+            })?;
+            report.unlinked.push(path.to_path_buf());
+        }
+    }
+
+    Ok(report)
+}
+
+fn is_asset_extension(extension: &str) -> bool {
+    ASSET_EXTENSIONS
+        .iter()
+        .any(|asset| asset.eq_ignore_ascii_case(extension))
+}
+
+/// Removes `//# sourceMappingURL=...` and `/*# sourceMappingURL=... */`
+/// lines, returning `None` if there are none.
+fn strip_source_mapping_urls(content: &str) -> Option<String> {
+    let is_link = |line: &str| {
+        let line = line.trim();
+        ["//# ", "//@ ", "/*# ", "/*@ "].iter().any(|prefix| {
+            line.strip_prefix(prefix).map_or(false, |rest| {
+                rest.starts_with("sourceMappingURL=")
+            })
+        })
+    };
+    if !content.lines().any(is_link) {
+        return None;
+    }
+    let mut stripped = String::with_capacity(content.len());
+    for line in content.split_inclusive('\n') {
+        if !is_link(line) {
+            stripped.push_str(line);
+        }
+    }
+    Some(stripped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_is_debug_artifact() {
+        assert!(is_debug_artifact(Path::new("js/app.js.map")));
+        assert!(is_debug_artifact(Path::new("css/site.css.MAP")));
+        assert!(is_debug_artifact(Path::new("js/app.debug.js")));
+        assert!(is_debug_artifact(Path::new("css/site.Debug.css")));
+        assert!(!is_debug_artifact(Path::new("js/app.js")));
+        assert!(!is_debug_artifact(Path::new("notes.debug.txt")));
+        assert!(!is_debug_artifact(Path::new("debug.js")));
+    }
+
+    #[test]
+    fn test_strip_debug_artifacts() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let output = temp_dir.path();
+        fs::create_dir_all(output.join("js"))?;
+        fs::write(
+            output.join("js/app.js"),
+            "run();\n//# sourceMappingURL=app.js.map\n",
+        )?;
+        fs::write(output.join("js/app.js.map"), "{}")?;
+        fs::write(output.join("js/app.debug.js"), "run();\n")?;
+        fs::write(
+            output.join("site.css"),
+            "a{}\n/*# sourceMappingURL=site.css.map */",
+        )?;
+        fs::write(output.join("plain.js"), "go();\n")?;
+        fs::write(
+            output.join("index.html"),
+            "//# sourceMappingURL=kept.map",
+        )?;
+
+        let report = strip_debug_artifacts(output)?;
+        assert_eq!(
+            report.removed,
+            [
+                output.join("js/app.debug.js"),
+                output.join("js/app.js.map")
+            ]
+        );
+        assert_eq!(
+            report.unlinked,
+            [output.join("js/app.js"), output.join("site.css")]
+        );
+        assert_eq!(
+            fs::read_to_string(output.join("js/app.js"))?,
+            "run();\n"
+        );
+        assert_eq!(
+            fs::read_to_string(output.join("site.css"))?,
+            "a{}\n"
+        );
+        assert_eq!(
+            fs::read_to_string(output.join("plain.js"))?,
+            "go();\n"
+        );
+        assert_eq!(
+            fs::read_to_string(output.join("index.html"))?,
+            "//# sourceMappingURL=kept.map"
+        );
+
+        assert_eq!(
+            strip_debug_artifacts(output)?,
+            StripReport::default()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,369 @@
+//! # Image Deduplication
+//!
+//! Sites often end up with the same image in several places: a logo
+//! copied into each section's asset folder, or a screenshot committed next
+//! to every page that uses it. This post-build pass finds images in the
+//! output directory with identical content, keeps a single copy, deletes
+//! the rest and rewrites references to them in HTML, CSS, XML,
+//! JavaScript, JSON and web manifest files.
+//!
+//! References are rewritten in site-absolute (`/img/logo.png`),
+//! document-relative (`../img/logo.png`) and, given the site's base
+//! URL, absolute (`https://example.com/img/logo.png`) form, such as an
+//! `og:image` tag's, wherever they appear as a quoted value, a CSS
+//! `url()` or a `srcset` candidate.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use nucleusflow::generators::dedup::deduplicate_images;
+//! use std::path::Path;
+//!
+//! let report = deduplicate_images(
+//!     Path::new("public"),
+//!     Some("https://example.com"),
+//! )
+//! .unwrap();
+//! println!(
+//!     "Removed {} duplicate images, saving {} bytes",
+//!     report.duplicates.len(),
+//!     report.bytes_saved
+//! );
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use crate::generators::fingerprint::content_hash;
+use crate::generators::seo::absolute_url;
+use crate::{ProcessingError, Result};
+
+/// File extensions treated as images.
+pub const IMAGE_EXTENSIONS: &[&str] = &[
+    "avif", "bmp", "gif", "ico", "jpeg", "jpg", "png", "svg", "webp",
+];
+
+/// File extensions whose references to images are rewritten.
+const REFERENCING_EXTENSIONS: &[&str] = &[
+    "css",
+    "htm",
+    "html",
+    "js",
+    "json",
+    "mjs",
+    "webmanifest",
+    "xml",
+];
+
+/// The outcome of a deduplication pass.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DedupReport {
+    /// Removed duplicates mapped to the copy that replaced them, as paths
+    /// relative to the output directory
+    pub duplicates: BTreeMap<PathBuf, PathBuf>,
+
+    /// Total size of the removed files in bytes
+    pub bytes_saved: u64,
+
+    /// Files whose references were rewritten
+    pub rewritten_files: Vec<PathBuf>,
+}
+
+/// Removes duplicate images from an output directory and rewrites
+/// references to point at the remaining copy.
+///
+/// Of each set of identical images, the copy with the lexicographically
+/// smallest path is kept, so repeated builds make the same choice.
+///
+/// # Arguments
+/// * `output_dir` - The generated site's output directory.
+/// * `base_url` - The URL the site is published at, if references may
+///   be absolute URLs under it.
+///
+/// # Returns
+/// * `Result<DedupReport>` - What was removed and how much space it
+///   saved.
+pub fn deduplicate_images(
+    output_dir: &Path,
+    base_url: Option<&str>,
+) -> Result<DedupReport> {
+    let mut report = DedupReport::default();
+    let (images, documents) = collect_files(output_dir)?;
+
+    let mut by_hash: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+    for image in images {
+        let bytes = fs::read(output_dir.join(&image)).map_err(|e| {
+            ProcessingError::io_error(output_dir.join(&image), e)
+        })?;
+        by_hash.entry(content_hash(&bytes)).or_default().push(image);
+    }
+
+    for mut paths in by_hash.into_values() {
+        if paths.len() < 2 {
+            continue;
+        }
+        paths.sort();
+        let canonical = paths.remove(0);
+        for duplicate in paths {
+            let full_path = output_dir.join(&duplicate);
+            let size = fs::metadata(&full_path)
+                .map_err(|e| {
+                    ProcessingError::io_error(full_path.clone(), e)
+                })?
+                .len();
+            fs::remove_file(&full_path)
+                .map_err(|e| ProcessingError::io_error(full_path, e))?;
+            report.bytes_saved += size;
+            _ = report.duplicates.insert(duplicate, canonical.clone());
+        }
+    }
+
+    if report.duplicates.is_empty() {
+        return Ok(report);
+    }
+
+    for document in documents {
+        let full_path = output_dir.join(&document);
+        let content = fs::read_to_string(&full_path).map_err(|e| {
+            ProcessingError::io_error(full_path.clone(), e)
+        })?;
+        let base = document.parent().unwrap_or(Path::new(""));
+
+        let mut rewritten = content.clone();
+        for (duplicate, canonical) in &report.duplicates {
+            let mut forms = vec![
+                (site_url(duplicate), site_url(canonical)),
+                (
+                    relative_url(base, duplicate),
+                    relative_url(base, canonical),
+                ),
+            ];
+            if base_url.is_some() {
+                forms.push((
+                    absolute_url(base_url, &site_url(duplicate)),
+                    absolute_url(base_url, &site_url(canonical)),
+                ));
+            }
+            for (from, to) in forms {
+                rewritten = replace_reference(&rewritten, &from, &to);
+            }
+        }
+
+        if rewritten != content {
+            fs::write(&full_path, rewritten)
+                .map_err(|e| ProcessingError::io_error(full_path, e))?;
+            report.rewritten_files.push(document);
+        }
+    }
+
+    Ok(report)
+}
+
+/// Walks the output directory, returning image and referencing files as
+/// paths relative to it.
+fn collect_files(
+    output_dir: &Path,
+) -> Result<(Vec<PathBuf>, Vec<PathBuf>)> {
+    let mut images = Vec::new();
+    let mut documents = Vec::new();
+    if !output_dir.is_dir() {
+        return Ok((images, documents));
+    }
+
+    for entry in WalkDir::new(output_dir).sort_by_file_name() {
+        let entry = entry.map_err(|e| {
+            ProcessingError::content_processing(
+                "Failed to read output directory",
+                Some(Box::new(e)),
+            )
+        })?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let relative = match entry.path().strip_prefix(output_dir) {
+            Ok(relative) => relative.to_path_buf(),
+            Err(_) => continue,
+        };
+        let extension = relative
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase)
+            .unwrap_or_default();
+        if IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+            images.push(relative);
+        } else if REFERENCING_EXTENSIONS.contains(&extension.as_str()) {
+            documents.push(relative);
+        }
+    }
+
+    Ok((images, documents))
+}
+
+/// Returns the site-absolute URL of an output-relative path.
+fn site_url(path: &Path) -> String {
+    format!("/{}", url_segments(path).join("/"))
+}
+
+/// Returns the URL of `target` relative to the directory `base`, both
+/// relative to the output directory.
+fn relative_url(base: &Path, target: &Path) -> String {
+    let base: Vec<_> = base.components().collect();
+    let target: Vec<_> = target.components().collect();
+    let common =
+        base.iter().zip(&target).take_while(|(a, b)| a == b).count();
+
+    let mut segments = vec!["..".to_string(); base.len() - common];
+    let rest: PathBuf = target[common..].iter().collect();
+    segments.extend(url_segments(&rest));
+    segments.join("/")
+}
+
+fn url_segments(path: &Path) -> Vec<String> {
+    path.components()
+        .map(|component| {
+            component.as_os_str().to_string_lossy().into_owned()
+        })
+        .collect()
+}
+
+/// Replaces `from` with `to` where it appears as a complete URL: inside
+/// quotes, a CSS `url()` or a comma-separated `srcset`.
+fn replace_reference(content: &str, from: &str, to: &str) -> String {
+    let is_start =
+        |c: char| matches!(c, '"' | '\'' | '`' | '(' | ',' | ' ');
+    let is_end =
+        |c: char| matches!(c, '"' | '\'' | '`' | ')' | ',' | ' ');
+
+    let mut output = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(index) = rest.find(from) {
+        let before = rest[..index]
+            .chars()
+            .next_back()
+            .or_else(|| output.chars().next_back());
+        let after = rest[index + from.len()..].chars().next();
+        output.push_str(&rest[..index]);
+        if before.map_or(false, is_start) && after.map_or(false, is_end)
+        {
+            output.push_str(to);
+        } else {
+            output.push_str(from);
+        }
+        rest = &rest[index + from.len()..];
+    }
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_deduplicate_images() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write(root, "assets/logo.png", "LOGO");
+        write(root, "blog/logo.png", "LOGO");
+        write(root, "blog/other.png", "OTHER");
+        write(
+            root,
+            "blog/post.html",
+            r#"<img src="logo.png"><img src="/blog/logo.png" srcset="logo.png 1x, other.png 2x"><p>blog/logo.png</p>"#,
+        );
+        write(
+            root,
+            "css/site.css",
+            "a{background:url(../blog/logo.png)}",
+        );
+
+        let report = deduplicate_images(root, None).unwrap();
+
+        assert_eq!(report.bytes_saved, 4);
+        assert_eq!(
+            report.duplicates.get(Path::new("blog/logo.png")),
+            Some(&PathBuf::from("assets/logo.png"))
+        );
+        assert!(!root.join("blog/logo.png").exists());
+        assert!(root.join("blog/other.png").exists());
+        assert_eq!(report.rewritten_files.len(), 2);
+
+        assert_eq!(
+            fs::read_to_string(root.join("blog/post.html")).unwrap(),
+            r#"<img src="../assets/logo.png"><img src="/assets/logo.png" srcset="../assets/logo.png 1x, other.png 2x"><p>blog/logo.png</p>"#
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("css/site.css")).unwrap(),
+            "a{background:url(../assets/logo.png)}"
+        );
+    }
+
+    #[test]
+    fn test_rewrites_absolute_urls_and_scripts() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        write(root, "img/card.png", "CARD");
+        write(root, "social/card.png", "CARD");
+        write(
+            root,
+            "index.html",
+            r#"<meta property="og:image" content="https://example.com/social/card.png">"#,
+        );
+        write(
+            root,
+            "site.webmanifest",
+            r#"{"icons":[{"src":"/social/card.png"}]}"#,
+        );
+        write(root, "js/app.js", "load(`/social/card.png`)");
+
+        let report =
+            deduplicate_images(root, Some("https://example.com/"))
+                .unwrap();
+
+        assert_eq!(report.rewritten_files.len(), 3);
+        assert_eq!(
+            fs::read_to_string(root.join("index.html")).unwrap(),
+            r#"<meta property="og:image" content="https://example.com/img/card.png">"#
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("site.webmanifest")).unwrap(),
+            r#"{"icons":[{"src":"/img/card.png"}]}"#
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("js/app.js")).unwrap(),
+            "load(`/img/card.png`)"
+        );
+    }
+
+    #[test]
+    fn test_no_duplicates() {
+        let temp_dir = TempDir::new().unwrap();
+        write(temp_dir.path(), "a.png", "A");
+        write(temp_dir.path(), "b.png", "B");
+
+        let report = deduplicate_images(temp_dir.path(), None).unwrap();
+        assert_eq!(report, DedupReport::default());
+    }
+
+    #[test]
+    fn test_replace_reference_boundaries() {
+        assert_eq!(
+            replace_reference(
+                r#"src="a.png" src="ba.png""#,
+                "a.png",
+                "c.png"
+            ),
+            r#"src="c.png" src="ba.png""#
+        );
+    }
+}
@@ -0,0 +1,216 @@
+//! # Preview Deploys
+//!
+//! Pushes a build's output to a preview deploy target, copying only the
+//! files that changed since the last push, so rebuilds in watch mode
+//! reach a shared preview in moments rather than re-uploading the whole
+//! site.
+//!
+//! The target is a directory, such as a mounted network share or a
+//! folder synced to a preview host. Each push records the hash of every
+//! file it deployed in a manifest in the target, and the next push
+//! compares the output with that manifest: new and changed files are
+//! copied, and files the build no longer produces are deleted. A target
+//! without a manifest gets every file.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use nucleusflow::generators::deploy::PreviewDeploy;
+//! use std::path::Path;
+//!
+//! let deploy = PreviewDeploy::new("/mnt/preview");
+//! let pushed = deploy.push(Path::new("public")).unwrap();
+//! println!("pushed {} changed files", pushed.len());
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use crate::generators::fingerprint::content_hash;
+use crate::generators::preview::ChangeKind;
+use crate::{ProcessingError, Result};
+
+/// The name of the manifest written to the deploy target.
+pub const DEPLOY_MANIFEST_FILE_NAME: &str = ".nucleusflow-deploy.json";
+
+/// A file a push copied to or deleted from the target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeployedFile {
+    /// The file's path relative to the output directory
+    pub path: PathBuf,
+    /// `Created` or `Updated` if it was copied, `Deleted` if it was
+    /// removed
+    pub kind: ChangeKind,
+}
+
+/// A preview deploy target receiving only changed files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreviewDeploy {
+    target: PathBuf,
+}
+
+impl PreviewDeploy {
+    /// Creates a deploy to a target directory.
+    pub fn new<P: AsRef<Path>>(target: P) -> Self {
+        Self {
+            target: target.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Returns the target directory.
+    pub fn target(&self) -> &Path {
+        &self.target
+    }
+
+    /// Copies the files of an output directory that changed since the
+    /// last push to the target, and deletes the ones that are gone.
+    ///
+    /// # Arguments
+    /// * `output_dir` - The directory the build was written to.
+    ///
+    /// # Returns
+    /// * `Result<Vec<DeployedFile>>` - The files pushed, ordered by
+    ///   path, or an error if a file cannot be read, copied or deleted.
+    pub fn push(&self, output_dir: &Path) -> Result<Vec<DeployedFile>> {
+        let manifest_path = self.target.join(DEPLOY_MANIFEST_FILE_NAME);
+        let previous: BTreeMap<PathBuf, String> =
+            match fs::read(&manifest_path) {
+                Ok(json) => serde_json::from_slice(&json)?,
+                Err(_) => BTreeMap::new(),
+            };
+        let current = file_hashes(output_dir)?;
+
+        let mut pushed = Vec::new();
+        for (path, hash) in &current {
+            let kind = match previous.get(path) {
+                Some(previous) if previous == hash => continue,
+                Some(_) => ChangeKind::Updated,
+                None => ChangeKind::Created,
+            };
+            let destination = self.target.join(path);
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent).map_err(|e| {
+                    ProcessingError::io_error(parent.to_path_buf(), e)
+                })?;
+            }
+            _ = fs::copy(output_dir.join(path), &destination).map_err(
+                |e| ProcessingError::io_error(destination, e),
+            )?;
+            pushed.push(DeployedFile {
+                path: path.clone(),
+                kind,
+            });
+        }
+        for path in previous.keys() {
+            if current.contains_key(path) {
+                continue;
+            }
+            let destination = self.target.join(path);
+            match fs::remove_file(&destination) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => {
+                    return Err(ProcessingError::io_error(
+                        destination,
+                        e,
+                    ))
+                }
+            }
+            pushed.push(DeployedFile {
+                path: path.clone(),
+                kind: ChangeKind::Deleted,
+            });
+        }
+        pushed.sort_by(|a, b| a.path.cmp(&b.path));
+
+        fs::create_dir_all(&self.target).map_err(|e| {
+            ProcessingError::io_error(self.target.clone(), e)
+        })?;
+        let json = serde_json::to_vec_pretty(&current)?;
+        fs::write(&manifest_path, json)
+            .map_err(|e| ProcessingError::io_error(manifest_path, e))?;
+        Ok(pushed)
+    }
+}
+
+/// Returns the content hash of every file in a directory, keyed by
+/// relative path.
+fn file_hashes(dir: &Path) -> Result<BTreeMap<PathBuf, String>> {
+    let mut hashes = BTreeMap::new();
+    for entry in WalkDir::new(dir) {
+        let entry = entry.map_err(|e| {
+            ProcessingError::content_processing(
+                "Failed to read output directory",
+                Some(Box::new(e)),
+            )
+        })?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let relative = match entry.path().strip_prefix(dir) {
+            Ok(relative) => relative.to_path_buf(),
+            Err(_) => continue,
+        };
+        let bytes = fs::read(entry.path()).map_err(|e| {
+            ProcessingError::io_error(entry.path().to_path_buf(), e)
+        })?;
+        _ = hashes.insert(relative, content_hash(&bytes));
+    }
+    Ok(hashes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn deployed(path: &str, kind: ChangeKind) -> DeployedFile {
+        DeployedFile {
+            path: PathBuf::from(path),
+            kind,
+        }
+    }
+
+    #[test]
+    fn test_push_changed_files() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let output = temp_dir.path().join("public");
+        let target = temp_dir.path().join("preview");
+        fs::create_dir_all(output.join("blog"))?;
+        fs::write(output.join("index.html"), "home")?;
+        fs::write(output.join("blog/post.html"), "post")?;
+        let deploy = PreviewDeploy::new(&target);
+
+        assert_eq!(
+            deploy.push(&output)?,
+            [
+                deployed("blog/post.html", ChangeKind::Created),
+                deployed("index.html", ChangeKind::Created),
+            ]
+        );
+        assert_eq!(
+            fs::read_to_string(target.join("blog/post.html"))?,
+            "post"
+        );
+        assert_eq!(deploy.push(&output)?, []);
+
+        fs::write(output.join("index.html"), "new home")?;
+        fs::remove_file(output.join("blog/post.html"))?;
+        assert_eq!(
+            deploy.push(&output)?,
+            [
+                deployed("blog/post.html", ChangeKind::Deleted),
+                deployed("index.html", ChangeKind::Updated),
+            ]
+        );
+        assert_eq!(
+            fs::read_to_string(target.join("index.html"))?,
+            "new home"
+        );
+        assert!(!target.join("blog/post.html").exists());
+        Ok(())
+    }
+}
@@ -0,0 +1,560 @@
+//! # HTML Document Tree
+//!
+//! A lightweight, arena-backed document tree built with the `html5ever`
+//! parser. Parsing follows the HTML5 specification exactly, including
+//! implied and optional tags, and every parse error is recorded together
+//! with the line it occurred on.
+//!
+//! The tree is used by the HTML generator for structural validation and is
+//! intended as a shared foundation for DOM-based output transformations.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::generators::dom::Document;
+//!
+//! let document = Document::parse("<!DOCTYPE html><p>Hello</div>");
+//! let error = &document.errors()[0];
+//! assert_eq!(error.line, 1);
+//! ```
+
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::fmt;
+
+use html5ever::interface::{
+    ElemName, ElementFlags, NodeOrText, QuirksMode, TreeSink,
+};
+use html5ever::tendril::{StrTendril, TendrilSink};
+use html5ever::tree_builder::TreeBuilderOpts;
+use html5ever::{
+    parse_document, parse_fragment, Attribute, LocalName, Namespace,
+    ParseOpts, QualName,
+};
+
+use crate::{ProcessingError, Result};
+
+/// Index of a node within a [`Document`].
+pub type NodeId = usize;
+
+/// The kind of content held by a node.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeData {
+    /// The document root
+    Document,
+
+    /// A `<!DOCTYPE>` declaration
+    Doctype {
+        /// The doctype name, usually `html`
+        name: String,
+    },
+
+    /// A text node
+    Text(String),
+
+    /// A comment
+    Comment(String),
+
+    /// An element
+    Element {
+        /// The qualified element name
+        name: QualName,
+        /// The element's attributes, in source order
+        attrs: Vec<Attribute>,
+        /// The content fragment of a `<template>` element
+        template_contents: Option<NodeId>,
+    },
+
+    /// A processing instruction (only produced in foreign content)
+    ProcessingInstruction {
+        /// The instruction target
+        target: String,
+        /// The instruction data
+        data: String,
+    },
+}
+
+/// A single node in the document tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    /// The parent node, if the node is attached to the tree
+    pub parent: Option<NodeId>,
+    /// The node's children, in document order
+    pub children: Vec<NodeId>,
+    /// The node's content
+    pub data: NodeData,
+}
+
+impl Node {
+    fn new(data: NodeData) -> Self {
+        Self {
+            parent: None,
+            children: Vec::new(),
+            data,
+        }
+    }
+
+    /// Returns the local name if the node is an element.
+    pub fn element_name(&self) -> Option<&str> {
+        match &self.data {
+            NodeData::Element { name, .. } => Some(&name.local),
+            _ => None,
+        }
+    }
+}
+
+/// A parse error reported by the HTML5 parser.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The 1-based line the error was reported on
+    pub line: u64,
+    /// A description of the error
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A parsed HTML document.
+#[derive(Debug, Clone)]
+pub struct Document {
+    nodes: Vec<Node>,
+    errors: Vec<ParseError>,
+    quirks_mode: QuirksMode,
+}
+
+impl Document {
+    /// Parses a complete HTML document.
+    ///
+    /// Parsing never fails; malformed input is recovered from as
+    /// specified by HTML5 and the problems are available from
+    /// [`Document::errors`].
+    pub fn parse(html: &str) -> Self {
+        let opts = ParseOpts {
+            tree_builder: TreeBuilderOpts {
+                exact_errors: true,
+                ..TreeBuilderOpts::default()
+            },
+            ..ParseOpts::default()
+        };
+        parse_document(Sink::default(), opts).one(html)
+    }
+
+    /// Parses an HTML fragment in the context of a `<body>` element.
+    ///
+    /// No `<html>`, `<head>` or `<body>` elements are implied. The parsed
+    /// nodes are the children of a single `<html>` element beneath the
+    /// root, as specified by the HTML5 fragment parsing algorithm.
+    pub fn parse_fragment(html: &str) -> Self {
+        let context = QualName::new(
+            None,
+            Namespace::from("http://www.w3.org/1999/xhtml"),
+            LocalName::from("body"),
+        );
+        parse_fragment(
+            Sink::default(),
+            ParseOpts::default(),
+            context,
+            Vec::new(),
+        )
+        .one(html)
+    }
+
+    /// Returns the id of the document root.
+    pub fn root(&self) -> NodeId {
+        0
+    }
+
+    /// Returns the node with the given id.
+    ///
+    /// # Panics
+    /// Panics if the id does not belong to this document.
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id]
+    }
+
+    /// Returns the parse errors encountered, in document order.
+    pub fn errors(&self) -> &[ParseError] {
+        &self.errors
+    }
+
+    /// Returns `true` if the document was parsed in quirks mode, which
+    /// happens when the DOCTYPE is missing or obsolete.
+    pub fn is_quirks_mode(&self) -> bool {
+        self.quirks_mode == QuirksMode::Quirks
+    }
+}
+
+/// Validates the structure of an HTML document.
+///
+/// A missing DOCTYPE is tolerated, since pages are frequently rendered as
+/// fragments and completed later in the pipeline. Every other parse error
+/// is reported.
+///
+/// # Arguments
+/// * `html` - The HTML to validate.
+///
+/// # Returns
+/// * `Result<()>` - A validation error describing the first problem and
+///   the line it occurred on.
+pub fn validate_html(html: &str) -> Result<()> {
+    let document = Document::parse(html);
+    let error = document
+        .errors()
+        .iter()
+        .find(|error| !is_missing_doctype(error));
+    match error {
+        Some(error) => Err(ProcessingError::validation(
+            format!("Invalid HTML at {}", error),
+            Some(format!(
+                "{} parse error(s) reported",
+                document.errors().len()
+            )),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Returns `true` for the error raised when content precedes a DOCTYPE.
+fn is_missing_doctype(error: &ParseError) -> bool {
+    error.message.ends_with("in insertion mode Initial")
+}
+
+/// An element name borrowed out of the arena.
+#[derive(Debug)]
+struct OwnedElemName(QualName);
+
+impl ElemName for OwnedElemName {
+    fn ns(&self) -> &Namespace {
+        &self.0.ns
+    }
+
+    fn local_name(&self) -> &LocalName {
+        &self.0.local
+    }
+}
+
+/// Tree builder sink that records nodes in an arena.
+struct Sink {
+    nodes: RefCell<Vec<Node>>,
+    errors: RefCell<Vec<ParseError>>,
+    quirks_mode: Cell<QuirksMode>,
+    line: Cell<u64>,
+}
+
+impl Default for Sink {
+    fn default() -> Self {
+        Self {
+            nodes: RefCell::new(vec![Node::new(NodeData::Document)]),
+            errors: RefCell::new(Vec::new()),
+            quirks_mode: Cell::new(QuirksMode::NoQuirks),
+            line: Cell::new(1),
+        }
+    }
+}
+
+impl Sink {
+    fn new_node(&self, data: NodeData) -> NodeId {
+        let mut nodes = self.nodes.borrow_mut();
+        nodes.push(Node::new(data));
+        nodes.len() - 1
+    }
+
+    fn detach(&self, id: NodeId) {
+        let mut nodes = self.nodes.borrow_mut();
+        if let Some(parent) = nodes[id].parent.take() {
+            nodes[parent].children.retain(|&child| child != id);
+        }
+    }
+
+    /// Detaches a node that is about to be inserted elsewhere, so that
+    /// insertion indices are computed against the final sibling list.
+    fn prepare(&self, child: &NodeOrText<NodeId>) {
+        if let NodeOrText::AppendNode(id) = child {
+            self.detach(*id);
+        }
+    }
+
+    /// Inserts a child at `index`, merging adjacent text nodes.
+    fn insert(
+        &self,
+        parent: NodeId,
+        index: usize,
+        child: NodeOrText<NodeId>,
+    ) {
+        let id = match child {
+            NodeOrText::AppendNode(id) => id,
+            NodeOrText::AppendText(text) => {
+                let mut nodes = self.nodes.borrow_mut();
+                let previous = index
+                    .checked_sub(1)
+                    .map(|i| nodes[parent].children[i]);
+                if let Some(previous) = previous {
+                    if let NodeData::Text(existing) =
+                        &mut nodes[previous].data
+                    {
+                        existing.push_str(&text);
+                        return;
+                    }
+                }
+                drop(nodes);
+                self.new_node(NodeData::Text(text.to_string()))
+            }
+        };
+        let mut nodes = self.nodes.borrow_mut();
+        nodes[id].parent = Some(parent);
+        nodes[parent].children.insert(index, id);
+    }
+}
+
+impl TreeSink for Sink {
+    type Handle = NodeId;
+    type Output = Document;
+    type ElemName<'a> = OwnedElemName;
+
+    fn finish(self) -> Document {
+        Document {
+            nodes: self.nodes.into_inner(),
+            errors: self.errors.into_inner(),
+            quirks_mode: self.quirks_mode.get(),
+        }
+    }
+
+    fn parse_error(&self, msg: Cow<'static, str>) {
+        self.errors.borrow_mut().push(ParseError {
+            line: self.line.get(),
+            message: msg.into_owned(),
+        });
+    }
+
+    fn get_document(&self) -> NodeId {
+        0
+    }
+
+    fn elem_name<'a>(&'a self, target: &'a NodeId) -> OwnedElemName {
+        match &self.nodes.borrow()[*target].data {
+            NodeData::Element { name, .. } => {
+                OwnedElemName(name.clone())
+            }
+            _ => OwnedElemName(QualName::new(
+                None,
+                Namespace::from(""),
+                LocalName::from(""),
+            )),
+        }
+    }
+
+    fn create_element(
+        &self,
+        name: QualName,
+        attrs: Vec<Attribute>,
+        flags: ElementFlags,
+    ) -> NodeId {
+        let template_contents = if flags.template {
+            Some(self.new_node(NodeData::Document))
+        } else {
+            None
+        };
+        self.new_node(NodeData::Element {
+            name,
+            attrs,
+            template_contents,
+        })
+    }
+
+    fn create_comment(&self, text: StrTendril) -> NodeId {
+        self.new_node(NodeData::Comment(text.to_string()))
+    }
+
+    fn create_pi(
+        &self,
+        target: StrTendril,
+        data: StrTendril,
+    ) -> NodeId {
+        self.new_node(NodeData::ProcessingInstruction {
+            target: target.to_string(),
+            data: data.to_string(),
+        })
+    }
+
+    fn append(&self, parent: &NodeId, child: NodeOrText<NodeId>) {
+        self.prepare(&child);
+        let index = self.nodes.borrow()[*parent].children.len();
+        self.insert(*parent, index, child);
+    }
+
+    fn append_based_on_parent_node(
+        &self,
+        element: &NodeId,
+        prev_element: &NodeId,
+        child: NodeOrText<NodeId>,
+    ) {
+        if self.nodes.borrow()[*element].parent.is_some() {
+            self.append_before_sibling(element, child);
+        } else {
+            self.append(prev_element, child);
+        }
+    }
+
+    fn append_doctype_to_document(
+        &self,
+        name: StrTendril,
+        _public_id: StrTendril,
+        _system_id: StrTendril,
+    ) {
+        let doctype = self.new_node(NodeData::Doctype {
+            name: name.to_string(),
+        });
+        self.append(&0, NodeOrText::AppendNode(doctype));
+    }
+
+    fn get_template_contents(&self, target: &NodeId) -> NodeId {
+        match &self.nodes.borrow()[*target].data {
+            NodeData::Element {
+                template_contents: Some(contents),
+                ..
+            } => *contents,
+            _ => *target,
+        }
+    }
+
+    fn same_node(&self, x: &NodeId, y: &NodeId) -> bool {
+        x == y
+    }
+
+    fn set_quirks_mode(&self, mode: QuirksMode) {
+        self.quirks_mode.set(mode);
+    }
+
+    fn append_before_sibling(
+        &self,
+        sibling: &NodeId,
+        new_node: NodeOrText<NodeId>,
+    ) {
+        self.prepare(&new_node);
+        let position = {
+            let nodes = self.nodes.borrow();
+            nodes[*sibling].parent.and_then(|parent| {
+                nodes[parent]
+                    .children
+                    .iter()
+                    .position(|child| child == sibling)
+                    .map(|index| (parent, index))
+            })
+        };
+        if let Some((parent, index)) = position {
+            self.insert(parent, index, new_node);
+        }
+    }
+
+    fn add_attrs_if_missing(
+        &self,
+        target: &NodeId,
+        attrs: Vec<Attribute>,
+    ) {
+        let mut nodes = self.nodes.borrow_mut();
+        if let NodeData::Element {
+            attrs: existing, ..
+        } = &mut nodes[*target].data
+        {
+            for attr in attrs {
+                if !existing.iter().any(|a| a.name == attr.name) {
+                    existing.push(attr);
+                }
+            }
+        }
+    }
+
+    fn remove_from_parent(&self, target: &NodeId) {
+        self.detach(*target);
+    }
+
+    fn reparent_children(&self, node: &NodeId, new_parent: &NodeId) {
+        let mut nodes = self.nodes.borrow_mut();
+        let children = std::mem::take(&mut nodes[*node].children);
+        for &child in &children {
+            nodes[child].parent = Some(*new_parent);
+        }
+        nodes[*new_parent].children.extend(children);
+    }
+
+    fn set_current_line(&self, line_number: u64) {
+        self.line.set(line_number);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tree() {
+        let document =
+            Document::parse("<!DOCTYPE html><p>One<p>Two <b>bold</b>");
+        assert!(document.errors().is_empty());
+        assert!(!document.is_quirks_mode());
+
+        let root = document.node(document.root());
+        let html = document.node(root.children[1]);
+        assert_eq!(html.element_name(), Some("html"));
+        let body = document.node(html.children[1]);
+        // Optional `</p>` tags are implied
+        assert_eq!(body.children.len(), 2);
+    }
+
+    #[test]
+    fn test_recovers_misnested_markup() {
+        // Adoption agency and foster parenting both move nodes around
+        let document = Document::parse(
+            "<!DOCTYPE html><b>1<p>2</b>3</p><table>4<tr><td>5</table>",
+        );
+        assert!(!document.errors().is_empty());
+
+        let text: String = (0..document.nodes.len())
+            .filter_map(|id| match &document.node(id).data {
+                NodeData::Text(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(text, "12345");
+    }
+
+    #[test]
+    fn test_attribute_containing_angle_bracket() {
+        assert!(validate_html(r#"<a title="a > b">link</a>"#).is_ok());
+    }
+
+    #[test]
+    fn test_error_positions() {
+        let error =
+            validate_html("<div>\n  <span>ok</span>\n</p></div>")
+                .unwrap_err();
+        assert!(error.to_string().contains("line 3"), "{}", error);
+    }
+
+    #[test]
+    fn test_parse_fragment() {
+        let document = Document::parse_fragment("<li>One</li>Two");
+        let root = document.node(document.root());
+        assert_eq!(root.children.len(), 1);
+
+        let html = document.node(root.children[0]);
+        assert_eq!(html.element_name(), Some("html"));
+        assert_eq!(html.children.len(), 2);
+        assert_eq!(
+            document.node(html.children[0]).element_name(),
+            Some("li")
+        );
+    }
+
+    #[test]
+    fn test_missing_doctype_tolerated() {
+        assert!(validate_html("<h1>Title</h1>").is_ok());
+        assert!(Document::parse("<h1>Title</h1>").is_quirks_mode());
+    }
+}
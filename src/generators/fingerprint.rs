@@ -0,0 +1,72 @@
+//! # Content Fingerprinting
+//!
+//! Helpers for content-addressed asset names. A fingerprinted file embeds a
+//! short hash of its contents in the file name (`app.css` becomes
+//! `app.3f2a1b9c.css`), so it can be served with long-lived cache headers
+//! and is busted automatically whenever its contents change.
+
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+/// Number of hex characters of the content hash used in file names.
+pub const FINGERPRINT_LENGTH: usize = 8;
+
+/// Returns the hex-encoded SHA-256 hash of `bytes`.
+pub fn content_hash(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Returns `path` with a content fingerprint inserted before the extension.
+///
+/// # Arguments
+/// * `path` - The original file path.
+/// * `bytes` - The file contents.
+///
+/// # Returns
+/// * `PathBuf` - The fingerprinted path, e.g. `css/app.3f2a1b9c.css`.
+pub fn fingerprinted_path(path: &Path, bytes: &[u8]) -> PathBuf {
+    let hash = content_hash(bytes);
+    let fingerprint = &hash[..FINGERPRINT_LENGTH];
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let file_name = match path.extension() {
+        Some(ext) => {
+            format!(
+                "{}.{}.{}",
+                stem,
+                fingerprint,
+                ext.to_string_lossy()
+            )
+        }
+        None => format!("{}.{}", stem, fingerprint),
+    };
+    path.with_file_name(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_hash() {
+        assert_eq!(
+            content_hash(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_fingerprinted_path() {
+        let path = fingerprinted_path(Path::new("css/app.css"), b"abc");
+        assert_eq!(path, PathBuf::from("css/app.ba7816bf.css"));
+
+        let path = fingerprinted_path(Path::new("LICENSE"), b"abc");
+        assert_eq!(path, PathBuf::from("LICENSE.ba7816bf"));
+    }
+}
@@ -0,0 +1,316 @@
+//! # Hreflang Alternates
+//!
+//! Links the translations of each page of a multilingual site with
+//! `<link rel="alternate" hreflang="...">` tags, so search engines show
+//! readers the page in their own language.
+//!
+//! A page's language is its `lang` frontmatter, or the language whose
+//! tree it is in: the first directory of its path when that names one
+//! of the site's languages, and the default language otherwise (see
+//! [`I18nConfig`]). Pages are translations of each other when they share
+//! a `translation_key`, or, without one, the same path within their
+//! language trees, as `/about.html` and `/fr/about.html` do.
+//!
+//! Every page with at least one translation gets an alternate for each
+//! language it is available in, itself included, and an `x-default`
+//! pointing at the default language's version. Root-relative URLs are
+//! made absolute with the site's base URL when it is set. Pages whose
+//! template already has `hreflang` links, and pages without a `</head>`,
+//! are not changed.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::core::config::I18nConfig;
+//! use nucleusflow::generators::hreflang::HreflangLinks;
+//! use nucleusflow::site::{Page, Site};
+//!
+//! let page = |path: &str| {
+//!     Page::new(format!("content/{}", path), path, Default::default())
+//! };
+//! let site = Site::new(vec![page("about.html"), page("fr/about.html")]);
+//! let config = I18nConfig {
+//!     default_language: "en".to_string(),
+//!     languages: vec!["en".to_string(), "fr".to_string()],
+//! };
+//! let links = HreflangLinks::new(&config, &site);
+//! assert_eq!(
+//!     links.alternates(&site.pages[0]),
+//!     [
+//!         ("en".to_string(), "/about.html".to_string()),
+//!         ("fr".to_string(), "/fr/about.html".to_string()),
+//!         ("x-default".to_string(), "/about.html".to_string()),
+//!     ]
+//! );
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde_json::Value as JsonValue;
+
+use crate::core::config::I18nConfig;
+use crate::core::error::Result;
+use crate::core::traits::PostProcessor;
+use crate::generators::seo::absolute_url;
+use crate::site::{Page, Site};
+
+/// The `hreflang` of the version shown to readers of other languages.
+const X_DEFAULT: &str = "x-default";
+
+/// Adds `hreflang` alternates to every page with translations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HreflangLinks {
+    config: I18nConfig,
+    base_url: Option<String>,
+    /// The URL of each language's version of a page, by translation key
+    translations: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl HreflangLinks {
+    /// Groups the pages of a site into translations.
+    ///
+    /// # Arguments
+    /// * `config` - The site's languages.
+    /// * `site` - Every page of the site.
+    pub fn new(config: &I18nConfig, site: &Site) -> Self {
+        let mut links = Self {
+            config: config.clone(),
+            base_url: None,
+            translations: BTreeMap::new(),
+        };
+        for page in &site.pages {
+            let key = links.translation_key(page);
+            let language = links.language(page).to_string();
+            _ = links
+                .translations
+                .entry(key)
+                .or_default()
+                .entry(language)
+                .or_insert_with(|| page.url.clone());
+        }
+        links
+    }
+
+    /// Makes the alternates' URLs absolute with the site's base URL.
+    pub fn with_base_url<S: Into<String>>(
+        mut self,
+        base_url: S,
+    ) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Returns the language of a page.
+    pub fn language<'a>(&'a self, page: &'a Page) -> &'a str {
+        if let Some(JsonValue::String(language)) =
+            page.frontmatter.get("lang")
+        {
+            return language;
+        }
+        self.tree(page)
+            .map(|(language, _)| language)
+            .unwrap_or(&self.config.default_language)
+    }
+
+    /// Returns the alternates of a page's translations.
+    ///
+    /// # Arguments
+    /// * `page` - The page to link from.
+    ///
+    /// # Returns
+    /// * `Vec<(String, String)>` - `(hreflang, URL)` pairs ordered by
+    ///   language, with `x-default` last; empty if the page has no
+    ///   translations.
+    pub fn alternates(&self, page: &Page) -> Vec<(String, String)> {
+        let versions =
+            match self.translations.get(&self.translation_key(page)) {
+                Some(versions) if versions.len() > 1 => versions,
+                _ => return Vec::new(),
+            };
+        let href =
+            |url: &str| absolute_url(self.base_url.as_deref(), url);
+        let mut alternates: Vec<(String, String)> = versions
+            .iter()
+            .map(|(language, url)| (language.clone(), href(url)))
+            .collect();
+        if let Some(url) = versions.get(&self.config.default_language) {
+            alternates.push((X_DEFAULT.to_string(), href(url)));
+        }
+        alternates
+    }
+
+    /// Returns the key shared by a page's translations: its
+    /// `translation_key`, or its path within its language tree.
+    fn translation_key(&self, page: &Page) -> String {
+        if let Some(JsonValue::String(key)) =
+            page.frontmatter.get("translation_key")
+        {
+            return format!("key:{}", key);
+        }
+        let url = page.url.trim_start_matches('/');
+        let path = match self.tree(page) {
+            Some((_, path)) => path,
+            None => url,
+        };
+        format!("path:{}", path)
+    }
+
+    /// Returns the language tree a page's URL is in, other than the
+    /// default language's, and the page's path within it.
+    fn tree<'a>(
+        &'a self,
+        page: &'a Page,
+    ) -> Option<(&'a str, &'a str)> {
+        let url = page.url.trim_start_matches('/');
+        let (first, rest) = match url.split_once('/') {
+            Some(split) => split,
+            None => (url, ""),
+        };
+        self.config
+            .languages
+            .iter()
+            .find(|language| {
+                *language != &self.config.default_language
+                    && *language == first
+            })
+            .map(|language| (language.as_str(), rest))
+    }
+}
+
+impl PostProcessor for HreflangLinks {
+    fn name(&self) -> &str {
+        "hreflang"
+    }
+
+    fn process(&self, html: &str, page: &Page) -> Result<String> {
+        let lower = html.to_ascii_lowercase();
+        let index = match lower.rfind("</head>") {
+            Some(index) => index,
+            None => return Ok(html.to_string()),
+        };
+        if lower[..index].contains("hreflang=") {
+            return Ok(html.to_string());
+        }
+
+        let links: String = self
+            .alternates(page)
+            .into_iter()
+            .map(|(language, href)| {
+                format!(
+                    r#"<link rel="alternate" hreflang="{}" href="{}">"#,
+                    handlebars::html_escape(&language),
+                    handlebars::html_escape(&href)
+                )
+            })
+            .collect();
+        let mut output =
+            String::with_capacity(html.len() + links.len());
+        output.push_str(&html[..index]);
+        output.push_str(&links);
+        output.push_str(&html[index..]);
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::site::tests::page;
+    use serde_json::json;
+
+    fn config() -> I18nConfig {
+        I18nConfig {
+            default_language: "en".to_string(),
+            languages: vec![
+                "en".to_string(),
+                "fr".to_string(),
+                "de".to_string(),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_alternates() {
+        let site = Site::new(vec![
+            page("blog/post.html", json!({})),
+            page("fr/blog/post.html", json!({})),
+            page(
+                "de/blog/beitrag.html",
+                json!({ "translation_key": "p" }),
+            ),
+            page("blog/other.html", json!({ "translation_key": "p" })),
+            page("fr/only.html", json!({})),
+            page("french.html", json!({ "lang": "fr" })),
+        ]);
+        let links = HreflangLinks::new(&config(), &site)
+            .with_base_url("https://example.com");
+        let alternates =
+            |url: &str| links.alternates(site.page(url).unwrap());
+
+        assert_eq!(
+            alternates("/fr/blog/post.html"),
+            [
+                (
+                    "en".to_string(),
+                    "https://example.com/blog/post.html".to_string()
+                ),
+                (
+                    "fr".to_string(),
+                    "https://example.com/fr/blog/post.html".to_string()
+                ),
+                (
+                    "x-default".to_string(),
+                    "https://example.com/blog/post.html".to_string()
+                ),
+            ]
+        );
+        assert_eq!(
+            alternates("/de/blog/beitrag.html")
+                .into_iter()
+                .map(|(language, _)| language)
+                .collect::<Vec<_>>(),
+            ["de", "en", "x-default"]
+        );
+        assert!(alternates("/fr/only.html").is_empty());
+        assert_eq!(
+            links.language(site.page("/french.html").unwrap()),
+            "fr"
+        );
+        assert_eq!(
+            links.language(site.page("/fr/only.html").unwrap()),
+            "fr"
+        );
+        assert_eq!(
+            links.language(site.page("/blog/post.html").unwrap()),
+            "en"
+        );
+    }
+
+    #[test]
+    fn test_process() {
+        let site = Site::new(vec![
+            page("index.html", json!({})),
+            page("de/index.html", json!({})),
+        ]);
+        let links = HreflangLinks::new(&config(), &site);
+        let german = site.page("/de/").unwrap();
+        let html = links.process("<head></head>", german).unwrap();
+        assert_eq!(
+            html,
+            concat!(
+                "<head>",
+                r#"<link rel="alternate" hreflang="de" href="/de/">"#,
+                r#"<link rel="alternate" hreflang="en" href="/">"#,
+                r#"<link rel="alternate" hreflang="x-default" href="/">"#,
+                "</head>"
+            )
+        );
+
+        let existing = r#"<head><link rel="alternate" hreflang="en" href="/"></head>"#;
+        assert_eq!(links.process(existing, german).unwrap(), existing);
+        assert_eq!(
+            links.process("<p>x</p>", german).unwrap(),
+            "<p>x</p>"
+        );
+    }
+}
@@ -9,6 +9,8 @@
 //! - Configurable minification and formatting
 //! - Thread-safe metadata management
 //! - Secure asset handling with path validation
+//! - Low-quality image placeholders for progressive loading
+//! - SVG optimisation for standalone vector assets
 //! - Memory-efficient string processing
 //!
 //! # Examples
@@ -34,33 +36,34 @@
 //! ).unwrap();
 //! ```
 
+use crate::core::cache::{CacheCounter, CacheStats};
+use crate::core::config::MinifyOptions;
+use crate::core::ignore::IgnoreRules;
 use crate::core::traits::Generator;
+use crate::generators::bundles::{
+    inject_bundles, resolve_bundles, PageBundles,
+};
+use crate::generators::dom::validate_html;
+use crate::generators::images::{
+    generate_placeholder, is_placeholder_candidate, ImagePlaceholder,
+    PlaceholderConfig,
+};
+use crate::generators::pretty::PrettyPrinter;
+use crate::generators::svg::{is_svg, optimize_svg};
 use std::collections::HashMap;
 use std::fs::{self, File};
 use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-use log;
 use minify_html::{minify, Cfg};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
+use walkdir::WalkDir;
 
 use crate::{ProcessingError, Result};
 
-/// List of HTML5 void elements that don't need closing tags
-const VOID_ELEMENTS: &[&str] = &[
-    "area", "base", "br", "col", "embed", "hr", "img", "input", "link",
-    "meta", "param", "source", "track", "wbr",
-];
-
-/// List of optional tags in HTML5
-const OPTIONAL_TAGS: &[&str] = &[
-    "html", "head", "body", "tbody", "thead", "tfoot", "tr", "th",
-    "td", "li", "dt", "dd",
-];
-
 /// Configuration options for HTML output generation.
 /// Provides thread-safe, comprehensive control over HTML processing and generation.
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
@@ -77,6 +80,12 @@ pub struct OutputConfig {
     /// Optional directory for static assets
     pub asset_dir: Option<PathBuf>,
 
+    /// Optional image placeholder generation settings
+    pub placeholders: Option<PlaceholderConfig>,
+
+    /// Strips comments, metadata and editor cruft from SVG assets
+    pub optimize_svg: bool,
+
     /// Additional configuration options
     pub options: HashMap<String, JsonValue>,
 }
@@ -94,6 +103,15 @@ pub struct HtmlGenerator {
 
     /// Thread-safe asset cache
     asset_cache: Arc<RwLock<HashMap<PathBuf, Vec<u8>>>>,
+
+    /// Asset cache hits and misses
+    cache_counter: Arc<CacheCounter>,
+
+    /// Thread-safe image placeholders, keyed by asset-relative path
+    placeholders: Arc<RwLock<HashMap<PathBuf, ImagePlaceholder>>>,
+
+    /// Rules excluding assets from copying, e.g. from `.nucleusignore`
+    ignore_rules: Option<IgnoreRules>,
 }
 
 impl HtmlGenerator {
@@ -102,6 +120,9 @@ pub fn new() -> Self {
         Self {
             config: Arc::new(RwLock::new(OutputConfig::default())),
             asset_cache: Arc::new(RwLock::new(HashMap::new())),
+            cache_counter: Arc::new(CacheCounter::default()),
+            placeholders: Arc::new(RwLock::new(HashMap::new())),
+            ignore_rules: None,
         }
     }
 
@@ -111,6 +132,19 @@ pub fn with_minification(self, enable: bool) -> Self {
         self
     }
 
+    /// Sets the fine-grained minification settings used when
+    /// minification is enabled.
+    pub fn with_minify_options(self, options: MinifyOptions) -> Self {
+        if let Ok(value) = serde_json::to_value(options) {
+            _ = self
+                .config
+                .write()
+                .options
+                .insert("minify_options".to_string(), value);
+        }
+        self
+    }
+
     /// Enables or disables pretty printing of output HTML.
     pub fn with_pretty_print(self, enable: bool) -> Self {
         self.config.write().pretty_print = enable;
@@ -148,6 +182,33 @@ pub fn with_asset_dir<P: AsRef<Path>>(
         Ok(self)
     }
 
+    /// Enables or disables optimisation of SVG assets.
+    pub fn with_svg_optimization(self, enable: bool) -> Self {
+        self.config.write().optimize_svg = enable;
+        self
+    }
+
+    /// Enables generation of low-quality image placeholders for raster
+    /// images found in the asset directory.
+    pub fn with_placeholders(self, config: PlaceholderConfig) -> Self {
+        self.config.write().placeholders = Some(config);
+        self
+    }
+
+    /// Excludes assets matched by project ignore rules from copying and
+    /// placeholder generation.
+    pub fn with_ignore_rules(mut self, rules: IgnoreRules) -> Self {
+        self.ignore_rules = Some(rules);
+        self
+    }
+
+    /// Returns `true` if an asset path is excluded by the ignore rules.
+    fn is_ignored(&self, path: &Path) -> bool {
+        self.ignore_rules
+            .as_ref()
+            .map_or(false, |rules| rules.is_ignored(path, false))
+    }
+
     /// Processes and optimizes HTML content based on configuration.
     ///
     /// This function handles:
@@ -159,12 +220,14 @@ fn process_html(&self, content: &str) -> Result<String> {
         let config = self.config.read();
 
         // Step 1: Validate HTML structure before any processing
-        if !self.is_valid_html(content) {
+        if let Err(e) = validate_html(content) {
             return Err(ProcessingError::FileOperation {
-                details: "Initial HTML structure validation failed"
-                    .to_string(),
+                details: format!(
+                    "Initial HTML structure validation failed: {}",
+                    e
+                ),
                 path: PathBuf::new(),
-                source: None,
+                source: Some(Box::new(e)),
             });
         }
 
@@ -193,162 +256,30 @@ fn process_html(&self, content: &str) -> Result<String> {
         // Step 4: Apply minification or pretty printing based on configuration
         let optimized_content =
             match (config.minify, config.pretty_print) {
-                (true, _) => self.minify_html(&processed)?,
-                (false, true) => self.pretty_print_html(&processed),
+                (true, _) => {
+                    self.minify_html(&processed, &config.options)?
+                }
+                (false, true) => {
+                    self.pretty_print_html(&processed, &config.options)
+                }
                 (false, false) => processed.clone(),
             };
 
         // Step 5: Final validation of processed HTML content
-        if !self.is_valid_html(&optimized_content) {
+        if let Err(e) = validate_html(&optimized_content) {
             return Err(ProcessingError::FileOperation {
-                details:
-                    "Processed HTML is invalid after transformation"
-                        .to_string(),
+                details: format!(
+                    "Processed HTML is invalid after transformation: {}",
+                    e
+                ),
                 path: PathBuf::new(),
-                source: None,
+                source: Some(Box::new(e)),
             });
         }
 
         Ok(optimized_content)
     }
 
-    /// Validates basic HTML structure and syntax with HTML5 support
-    fn is_valid_html(&self, content: &str) -> bool {
-        let mut tag_stack: Vec<String> = Vec::new();
-        let mut in_tag = false;
-        let mut in_comment = false;
-        let mut tag_start = 0;
-
-        let mut chars = content.chars().enumerate().peekable();
-        while let Some((i, c)) = chars.next() {
-            match c {
-                '<' => {
-                    if !in_tag && !in_comment {
-                        in_tag = true;
-                        tag_start = i;
-
-                        // Check for comment start
-                        if content[i..].starts_with("<!--") {
-                            in_comment = true;
-                            in_tag = false;
-                            // Skip the rest of comment opening
-                            for _ in 0..3 {
-                                let _ = chars.next();
-                            }
-                            continue;
-                        }
-                    }
-                }
-                '>' => {
-                    if in_comment {
-                        // Check for comment end
-                        if i >= 2 && &content[i - 2..=i] == "-->" {
-                            in_comment = false;
-                        }
-                    } else if in_tag {
-                        in_tag = false;
-                        let tag = &content[tag_start..=i];
-
-                        // Skip doctypes, XML declarations, etc.
-                        if tag.starts_with("<!")
-                            || tag.starts_with("<?")
-                        {
-                            continue;
-                        }
-
-                        // Extract tag name, handling attributes
-                        let tag_name = if let Some(stripped) =
-                            tag.strip_prefix("</")
-                        {
-                            // Closing tag
-                            stripped
-                                .split_whitespace()
-                                .next()
-                                .unwrap_or("")
-                                .trim_end_matches('>')
-                                .to_lowercase()
-                        } else {
-                            // Opening tag
-                            tag[1..]
-                                .split_whitespace()
-                                .next()
-                                .unwrap_or("")
-                                .trim_end_matches('>')
-                                .trim_end_matches('/')
-                                .to_lowercase()
-                        };
-
-                        // Skip empty or invalid tags
-                        if tag_name.is_empty() {
-                            continue;
-                        }
-
-                        if tag.starts_with("</") {
-                            // Handle closing tag
-                            if !VOID_ELEMENTS
-                                .contains(&tag_name.as_str())
-                            {
-                                match tag_stack.last() {
-                                    Some(last_tag)
-                                        if last_tag == &tag_name =>
-                                    {
-                                        _ = tag_stack.pop();
-                                    }
-                                    Some(last_tag)
-                                        if OPTIONAL_TAGS.contains(
-                                            &last_tag.as_str(),
-                                        ) =>
-                                    {
-                                        // Pop optional tags until we find a match
-                                        while let Some(top) =
-                                            tag_stack.last()
-                                        {
-                                            if top == &tag_name {
-                                                _ = tag_stack.pop();
-                                                break;
-                                            } else if OPTIONAL_TAGS
-                                                .contains(&top.as_str())
-                                            {
-                                                _ = tag_stack.pop();
-                                            } else {
-                                                return false; // Mismatched non-optional tag
-                                            }
-                                        }
-                                    }
-                                    Some(_) => return false, // Mismatched non-optional tag
-                                    None => {} // Ignore extra closing tags for optional elements
-                                }
-                            }
-                        } else if !tag.ends_with("/>")
-                            && !VOID_ELEMENTS
-                                .contains(&tag_name.as_str())
-                        {
-                            // Push opening tag
-                            tag_stack.push(tag_name);
-                        }
-                    }
-                }
-                '-' if in_comment => {
-                    // Check for premature comment end
-                    if i >= 1 && content[i - 1..=i] == *"--" {
-                        if let Some((_, '>')) = chars.peek() {
-                            in_comment = false;
-                            let _ = chars.next();
-                        }
-                    }
-                }
-                _ => continue,
-            }
-        }
-
-        // Handle any remaining tags - only optional tags can be unclosed
-        !in_tag
-            && !in_comment
-            && tag_stack
-                .iter()
-                .all(|tag| OPTIONAL_TAGS.contains(&tag.as_str()))
-    }
-
     /// Injects metadata into HTML head section with proper escaping and structure handling
     fn inject_metadata(
         &self,
@@ -438,12 +369,30 @@ fn generate_meta_tags(
     }
 
     /// Minifies HTML content using the `minify-html` crate.
-    fn minify_html(&self, content: &str) -> Result<String> {
-        let cfg = Cfg {
-            minify_css: true,
-            minify_js: true,
-            ..Cfg::default()
+    ///
+    /// The `minify_options` option, if set, controls the minifier; see
+    /// [`MinifyOptions`].
+    fn minify_html(
+        &self,
+        content: &str,
+        options: &HashMap<String, JsonValue>,
+    ) -> Result<String> {
+        let minify_options = match options.get("minify_options") {
+            Some(value) => {
+                parse_minify_options(value).map_err(|e| {
+                    ProcessingError::FileOperation {
+                        details: format!(
+                            "Invalid minify_options: {}",
+                            e
+                        ),
+                        path: PathBuf::new(),
+                        source: Some(Box::new(e)),
+                    }
+                })?
+            }
+            None => MinifyOptions::default(),
         };
+        let cfg = minify_cfg(&minify_options);
         String::from_utf8(minify(content.as_bytes(), &cfg)).map_err(
             |e| ProcessingError::FileOperation {
                 details: "HTML minification failed".to_string(),
@@ -454,40 +403,28 @@ fn minify_html(&self, content: &str) -> Result<String> {
     }
 
     /// Formats HTML with indentation and line breaks.
-    fn pretty_print_html(&self, content: &str) -> String {
-        let mut pretty = String::new();
-        let mut depth: i32 = 0;
-        let mut in_tag = false;
-        let mut is_closing_tag = false;
-
-        for c in content.chars() {
-            match c {
-                '<' => {
-                    if !in_tag {
-                        if is_closing_tag {
-                            depth = depth.saturating_sub(1);
-                        }
-                        pretty.push('\n');
-                        pretty.push_str(
-                            &"    ".repeat(depth.try_into().unwrap()),
-                        );
-                        if !is_closing_tag {
-                            depth += 1;
-                        }
-                    }
-                    in_tag = true;
-                    is_closing_tag = false;
-                    pretty.push('<');
-                }
-                '/' if in_tag => is_closing_tag = true,
-                '>' => {
-                    pretty.push('>');
-                    in_tag = false;
-                }
-                _ => pretty.push(c),
-            }
+    ///
+    /// The `indent_size` and `preserve` options control the indentation
+    /// width and the elements whose content is kept verbatim.
+    fn pretty_print_html(
+        &self,
+        content: &str,
+        options: &HashMap<String, JsonValue>,
+    ) -> String {
+        let mut printer = PrettyPrinter::new();
+        if let Some(size) =
+            options.get("indent_size").and_then(JsonValue::as_u64)
+        {
+            printer = printer.with_indent_size(size as usize);
+        }
+        if let Some(tags) =
+            options.get("preserve").and_then(JsonValue::as_array)
+        {
+            printer = printer.with_preserve(
+                tags.iter().filter_map(JsonValue::as_str),
+            );
         }
-        pretty
+        printer.print(content)
     }
 
     /// Copies static assets to the output directory with caching.
@@ -497,7 +434,7 @@ fn copy_assets(&self, output_dir: &Path) -> Result<()> {
             for entry in fs::read_dir(asset_dir)? {
                 let entry = entry?;
                 let path = entry.path();
-                if path.is_file() {
+                if path.is_file() && !self.is_ignored(&path) {
                     self.process_asset(
                         &path, asset_dir, output_dir, &mut cache,
                     )?;
@@ -515,6 +452,7 @@ fn process_asset(
         output_dir: &Path,
         cache: &mut HashMap<PathBuf, Vec<u8>>,
     ) -> Result<()> {
+        self.cache_counter.record(cache.contains_key(path));
         let cached_content = cache
             .entry(path.to_path_buf())
             .or_insert_with(|| fs::read(path).unwrap_or_default());
@@ -530,10 +468,141 @@ fn process_asset(
         if let Some(parent) = output_path.parent() {
             fs::create_dir_all(parent)?;
         }
-        fs::write(&output_path, cached_content)?;
+        if self.config.read().optimize_svg && is_svg(path) {
+            fs::write(
+                &output_path,
+                self.optimize_svg_asset(path, cached_content),
+            )?;
+        } else {
+            fs::write(&output_path, &cached_content)?;
+        }
+        self.process_placeholder(relative_path, cached_content);
         Ok(())
     }
 
+    /// Optimises an SVG asset, falling back to the original bytes if the
+    /// file cannot be parsed.
+    fn optimize_svg_asset(
+        &self,
+        path: &Path,
+        content: &[u8],
+    ) -> Vec<u8> {
+        let optimized = std::str::from_utf8(content)
+            .map_err(|e| {
+                ProcessingError::content_processing(
+                    "SVG is not valid UTF-8",
+                    Some(Box::new(e)),
+                )
+            })
+            .and_then(optimize_svg);
+        match optimized {
+            Ok(svg) => svg.into_bytes(),
+            Err(e) => {
+                tracing::warn!(
+                    "Copying {} unoptimised: {}",
+                    path.display(),
+                    e
+                );
+                content.to_vec()
+            }
+        }
+    }
+
+    /// Generates and stores a placeholder for an image asset, if enabled.
+    ///
+    /// Images that cannot be decoded are logged and skipped so that a
+    /// single corrupt file does not abort the build.
+    fn process_placeholder(
+        &self,
+        relative_path: &Path,
+        content: &[u8],
+    ) {
+        let config = match self.config.read().placeholders {
+            Some(config) => config,
+            None => return,
+        };
+        if !is_placeholder_candidate(relative_path)
+            || self.placeholders.read().contains_key(relative_path)
+        {
+            return;
+        }
+        match generate_placeholder(content, &config) {
+            Ok(placeholder) => {
+                _ = self
+                    .placeholders
+                    .write()
+                    .insert(relative_path.to_path_buf(), placeholder);
+            }
+            Err(e) => tracing::warn!(
+                "Skipping placeholder for {}: {}",
+                relative_path.display(),
+                e
+            ),
+        }
+    }
+
+    /// Generates placeholders for every image in the asset directory
+    /// and its subdirectories without copying any files.
+    ///
+    /// This allows placeholders to be exposed to templates before pages
+    /// are rendered and written. Each is keyed by its path relative to
+    /// the asset directory, such as `img/hero.jpg`.
+    pub fn generate_placeholders(&self) -> Result<()> {
+        let asset_dir = match self.config.read().asset_dir.clone() {
+            Some(asset_dir) => asset_dir,
+            None => return Ok(()),
+        };
+        let walker = WalkDir::new(&asset_dir)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|entry| {
+                entry.depth() == 0 || !self.is_ignored(entry.path())
+            });
+        for entry in walker {
+            let entry = entry.map_err(|e| {
+                let path = e.path().unwrap_or(&asset_dir).to_path_buf();
+                ProcessingError::io_error(path, e.into())
+            })?;
+            let path = entry.path();
+            if entry.file_type().is_file()
+                && is_placeholder_candidate(path)
+            {
+                let content = fs::read(path).map_err(|e| {
+                    ProcessingError::io_error(path.to_path_buf(), e)
+                })?;
+                if let Ok(relative_path) = path.strip_prefix(&asset_dir)
+                {
+                    self.process_placeholder(relative_path, &content);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Gets the placeholder for an asset-relative image path
+    pub fn get_placeholder(
+        &self,
+        path: &Path,
+    ) -> Option<ImagePlaceholder> {
+        self.placeholders.read().get(path).cloned()
+    }
+
+    /// Returns all generated placeholders as a JSON object keyed by
+    /// asset-relative path, suitable for a template context.
+    pub fn placeholders_context(&self) -> JsonValue {
+        let placeholders = self.placeholders.read();
+        let map = placeholders
+            .iter()
+            .filter_map(|(path, placeholder)| {
+                Some((
+                    path.to_str()?.replace('\\', "/"),
+                    serde_json::to_value(placeholder).ok()?,
+                ))
+            })
+            .collect();
+        JsonValue::Object(map)
+    }
+
     /// Adds a custom configuration option
     pub fn with_option(self, key: &str, value: JsonValue) -> Self {
         let _ =
@@ -548,14 +617,13 @@ pub fn get_config(&self) -> OutputConfig {
 
     /// Validates HTML content without processing it
     pub fn validate_content(&self, content: &str) -> Result<()> {
-        if !self.is_valid_html(content) {
-            return Err(ProcessingError::FileOperation {
-                details: "Invalid HTML structure".to_string(),
+        validate_html(content).map_err(|e| {
+            ProcessingError::FileOperation {
+                details: format!("Invalid HTML structure: {}", e),
                 path: PathBuf::new(),
-                source: None,
-            });
-        }
-        Ok(())
+                source: Some(Box::new(e)),
+            }
+        })
     }
 
     /// Clears the asset cache to free memory
@@ -642,10 +710,26 @@ fn generate(
         options: Option<&JsonValue>,
     ) -> Result<()> {
         self.validate(path, options)?;
-        let processed = self.process_html(content)?;
+        let mut processed = self.process_html(content)?;
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent)?;
         }
+        if let Some(bundles) = page_bundles(path, options)? {
+            let asset_dir =
+                self.config.read().asset_dir.clone().ok_or_else(
+                    || ProcessingError::FileOperation {
+                        details:
+                            "Page bundles require an asset directory"
+                                .to_string(),
+                        path: path.to_path_buf(),
+                        source: None,
+                    },
+                )?;
+            let output_dir = path.parent().unwrap_or(Path::new(""));
+            let resolved =
+                resolve_bundles(&bundles, &asset_dir, output_dir)?;
+            processed = inject_bundles(&processed, &resolved);
+        }
         let file = File::create(path)?;
         let mut writer = BufWriter::new(file);
         writer.write_all(processed.as_bytes())?;
@@ -701,13 +785,107 @@ fn validate(
                                 source: None,
                             });
                         }
-                        _ => log::warn!("Unknown option key: {}", key),
+                        "preserve"
+                            if !value.as_array().map_or(
+                                false,
+                                |tags| {
+                                    tags.iter()
+                                        .all(JsonValue::is_string)
+                                },
+                            ) =>
+                        {
+                            return Err(ProcessingError::FileOperation {
+                                details: "preserve option must be an array of strings".to_string(),
+                                path: path.to_path_buf(),
+                                source: None,
+                            });
+                        }
+                        "minify_options" => {
+                            if let Err(e) = parse_minify_options(value)
+                            {
+                                return Err(ProcessingError::FileOperation {
+                                    details: format!("Invalid minify_options: {}", e),
+                                    path: path.to_path_buf(),
+                                    source: None,
+                                });
+                            }
+                        }
+                        "bundles" => {
+                            _ = page_bundles(path, options)?;
+                        }
+                        _ => tracing::warn!(
+                            "Unknown option key: {}",
+                            key
+                        ),
                     }
                 }
             }
         }
         Ok(())
     }
+
+    fn cache_stats(&self) -> Option<CacheStats> {
+        Some(self.cache_counter.stats())
+    }
+
+    fn shed_cache(&self) {
+        self.asset_cache.write().clear();
+    }
+}
+
+/// Deserializes minification settings from a JSON option value.
+fn parse_minify_options(
+    value: &JsonValue,
+) -> std::result::Result<MinifyOptions, serde_json::Error> {
+    serde_json::from_value(value.clone())
+}
+
+/// Maps minification settings onto the minifier configuration.
+fn minify_cfg(options: &MinifyOptions) -> Cfg {
+    Cfg {
+        minify_css: options.minify_css,
+        minify_js: options.minify_js,
+        keep_comments: options.keep_comments,
+        keep_closing_tags: options.keep_closing_tags,
+        keep_html_and_head_opening_tags: options
+            .keep_html_and_head_opening_tags,
+        keep_spaces_between_attributes: options
+            .keep_spaces_between_attributes,
+        keep_input_type_text_attr: options.keep_input_type_text_attr,
+        keep_ssi_comments: options.keep_ssi_comments,
+        do_not_minify_doctype: options.do_not_minify_doctype,
+        ensure_spec_compliant_unquoted_attribute_values: options
+            .ensure_spec_compliant_unquoted_attribute_values,
+        preserve_brace_template_syntax: options
+            .preserve_brace_template_syntax,
+        preserve_chevron_percent_template_syntax: options
+            .preserve_chevron_percent_template_syntax,
+        remove_bangs: options.remove_bangs,
+        remove_processing_instructions: options
+            .remove_processing_instructions,
+    }
+}
+
+/// Reads the per-page bundles from the `bundles` generator option.
+fn page_bundles(
+    path: &Path,
+    options: Option<&JsonValue>,
+) -> Result<Option<PageBundles>> {
+    let value = match options.and_then(|opts| opts.get("bundles")) {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+    let bundles: PageBundles = serde_json::from_value(value.clone())
+        .map_err(|e| ProcessingError::FileOperation {
+            details: format!("Invalid bundles option: {}", e),
+            path: path.to_path_buf(),
+            source: None,
+        })?;
+    if bundles.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(bundles))
+    }
 }
 
 impl std::fmt::Debug for HtmlGenerator {
@@ -715,6 +893,8 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("HtmlGenerator")
             .field("config", &*self.config.read())
             .field("asset_cache_size", &self.asset_cache.read().len())
+            .field("placeholder_count", &self.placeholders.read().len())
+            .field("ignore_rules", &self.ignore_rules.is_some())
             .finish()
     }
 }
@@ -919,7 +1099,8 @@ fn test_memory_efficiency() -> Result<()> {
         let generator = HtmlGenerator::new();
 
         // Test processing with pre-allocated buffer
-        let content = "<div>".repeat(1000) + &"</div>".repeat(1000);
+        let content =
+            "<div>".repeat(1000) + "</div>".repeat(1000).as_str();
         generator.generate(&content, &output_path, None)?;
 
         Ok(())
@@ -1072,6 +1253,17 @@ fn test_validate_content() -> Result<()> {
         // Invalid HTML
         assert!(generator.validate_content("<div>Test</p>").is_err());
 
+        // Attributes containing `>` are valid
+        assert!(generator
+            .validate_content(r#"<img alt="a > b" src="x.png">"#)
+            .is_ok());
+
+        // Errors report the line they occurred on
+        let error = generator
+            .validate_content("<ul>\n<li>One</li>\n</ol>")
+            .unwrap_err();
+        assert!(error.to_string().contains("line 3"));
+
         Ok(())
     }
 
@@ -1128,4 +1320,195 @@ fn test_cache_operations() -> Result<()> {
 
         Ok(())
     }
+
+    #[test]
+    fn test_image_placeholders() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let asset_dir = temp_dir.path().join("assets");
+        let output_dir = temp_dir.path().join("output");
+        fs::create_dir(&asset_dir)?;
+
+        let image = image::RgbImage::from_pixel(
+            64,
+            32,
+            image::Rgb([200, 100, 50]),
+        );
+        image.save(asset_dir.join("hero.png")).unwrap();
+        fs::write(asset_dir.join("notes.txt"), "not an image")?;
+        fs::create_dir(asset_dir.join("img"))?;
+        image.save(asset_dir.join("img/banner.png")).unwrap();
+
+        let generator = HtmlGenerator::new()
+            .with_asset_dir(&asset_dir)?
+            .with_placeholders(PlaceholderConfig::default());
+        generator.generate(
+            "<h1>Test</h1>",
+            &output_dir.join("index.html"),
+            None,
+        )?;
+
+        let placeholder = generator
+            .get_placeholder(Path::new("hero.png"))
+            .expect("placeholder should be generated");
+        assert_eq!((placeholder.width, placeholder.height), (64, 32));
+        assert!(generator
+            .get_placeholder(Path::new("notes.txt"))
+            .is_none());
+
+        let context = generator.placeholders_context();
+        assert!(context["hero.png"]["data_uri"]
+            .as_str()
+            .unwrap()
+            .starts_with("data:image/png;base64,"));
+
+        // Nested images are found when placeholders are generated
+        // ahead of copying, under their asset-relative path.
+        let generator = HtmlGenerator::new()
+            .with_asset_dir(&asset_dir)?
+            .with_placeholders(PlaceholderConfig::default());
+        generator.generate_placeholders()?;
+        let context = generator.placeholders_context();
+        assert_eq!(context["img/banner.png"]["width"], 64);
+        assert!(context["hero.png"].is_object());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_svg_optimization() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let asset_dir = temp_dir.path().join("assets");
+        let output_dir = temp_dir.path().join("output");
+        fs::create_dir(&asset_dir)?;
+        fs::write(
+            asset_dir.join("icon.svg"),
+            "<!-- editor --><svg><metadata>x</metadata><rect/></svg>",
+        )?;
+        fs::write(asset_dir.join("broken.svg"), "<svg><g></svg>")?;
+
+        let generator = HtmlGenerator::new()
+            .with_asset_dir(&asset_dir)?
+            .with_svg_optimization(true);
+        generator.generate(
+            "<h1>Test</h1>",
+            &output_dir.join("index.html"),
+            None,
+        )?;
+
+        assert_eq!(
+            fs::read_to_string(output_dir.join("icon.svg"))?,
+            "<svg><rect/></svg>"
+        );
+        // Unparseable SVGs are copied verbatim
+        assert_eq!(
+            fs::read_to_string(output_dir.join("broken.svg"))?,
+            "<svg><g></svg>"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ignored_assets() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let asset_dir = temp_dir.path().join("assets");
+        let output_dir = temp_dir.path().join("output");
+        fs::create_dir(&asset_dir)?;
+        fs::write(asset_dir.join("app.js"), "console.log(1)")?;
+        fs::write(asset_dir.join(".app.js.swp"), "swap")?;
+
+        let rules =
+            IgnoreRules::from_patterns(temp_dir.path(), &["*.swp"])?;
+        let generator = HtmlGenerator::new()
+            .with_asset_dir(&asset_dir)?
+            .with_ignore_rules(rules);
+        generator.generate(
+            "<h1>Test</h1>",
+            &output_dir.join("index.html"),
+            None,
+        )?;
+
+        assert!(output_dir.join("app.js").exists());
+        assert!(!output_dir.join(".app.js.swp").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_page_bundles() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let asset_dir = temp_dir.path().join("assets");
+        let output_dir = temp_dir.path().join("output");
+        fs::create_dir(&asset_dir)?;
+        fs::write(asset_dir.join("gallery.css"), ".g{}")?;
+
+        let generator =
+            HtmlGenerator::new().with_asset_dir(&asset_dir)?;
+        let path = output_dir.join("gallery.html");
+        generator.generate(
+            "<html><head><title>G</title></head><body></body></html>",
+            &path,
+            Some(&json!({ "bundles": { "css": ["gallery.css"] } })),
+        )?;
+
+        let html = fs::read_to_string(&path)?;
+        let start = html.find("href=\"gallery.").unwrap() + 6;
+        let href =
+            &html[start..start + html[start..].find('"').unwrap()];
+        assert!(href.ends_with(".css"));
+        assert!(output_dir.join(href).exists());
+
+        // Unknown bundle keys are rejected
+        assert!(generator
+            .validate(
+                &path,
+                Some(&json!({ "bundles": { "fonts": [] } }))
+            )
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pretty_print() -> Result<()> {
+        let generator = HtmlGenerator::new()
+            .with_pretty_print(true)
+            .with_option("indent_size", json!(2));
+        let html = generator.process_html(
+            "<div><p>A <strong>b</strong></p><pre>  x\n y</pre></div>",
+        )?;
+        assert_eq!(
+            html,
+            "<div>\n  <p>A <strong>b</strong></p>\n  <pre>  x\n y</pre>\n</div>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_minify_options() -> Result<()> {
+        let input = "<html><head></head><body><!-- note --><p>Text</p></body></html>";
+
+        let default = HtmlGenerator::new().with_minification(true);
+        let html = default.process_html(input)?;
+        assert!(!html.contains("note"));
+        assert!(!html.contains("</p>"));
+
+        let strict = HtmlGenerator::new()
+            .with_minification(true)
+            .with_minify_options(MinifyOptions {
+                keep_comments: true,
+                keep_closing_tags: true,
+                ..MinifyOptions::default()
+            });
+        let html = strict.process_html(input)?;
+        assert!(html.contains("<!-- note -->"));
+        assert!(html.contains("</p>"));
+
+        let invalid = HtmlGenerator::new()
+            .with_minification(true)
+            .with_option("minify_options", json!({ "keep_all": true }));
+        assert!(invalid.process_html(input).is_err());
+
+        Ok(())
+    }
 }
@@ -0,0 +1,270 @@
+//! # Image Placeholder Generation
+//!
+//! This module generates low-quality image placeholders (LQIP) for raster
+//! images processed by the asset pipeline. Each placeholder contains a tiny,
+//! blurred copy of the image encoded as a base64 data URI, an optional
+//! [BlurHash](https://blurha.sh) string, and the original dimensions so themes
+//! can reserve layout space and implement progressive image loading.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use nucleusflow::generators::images::{
+//!     generate_placeholder, PlaceholderConfig,
+//! };
+//!
+//! let bytes = std::fs::read("assets/hero.jpg").unwrap();
+//! let placeholder =
+//!     generate_placeholder(&bytes, &PlaceholderConfig::default()).unwrap();
+//!
+//! println!("<img src=\"{}\">", placeholder.data_uri);
+//! ```
+
+use std::io::Cursor;
+use std::path::Path;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use image::{imageops::FilterType, DynamicImage, ImageFormat};
+use serde::{Deserialize, Serialize};
+
+use crate::{ProcessingError, Result};
+
+/// File extensions for which placeholders are generated.
+pub const PLACEHOLDER_EXTENSIONS: &[&str] =
+    &["png", "jpg", "jpeg", "gif", "webp"];
+
+/// Maximum width used when sampling an image for its BlurHash.
+const BLURHASH_SAMPLE_WIDTH: u32 = 32;
+
+/// Configuration options for placeholder generation.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PlaceholderConfig {
+    /// Width in pixels of the generated placeholder image
+    #[serde(default = "default_width")]
+    pub width: u32,
+
+    /// Gaussian blur sigma applied to the placeholder image
+    #[serde(default = "default_blur_sigma")]
+    pub blur_sigma: f32,
+
+    /// Enables computation of a BlurHash string
+    #[serde(default = "default_true")]
+    pub blurhash: bool,
+
+    /// Number of horizontal BlurHash components (1-9)
+    #[serde(default = "default_components_x")]
+    pub components_x: u32,
+
+    /// Number of vertical BlurHash components (1-9)
+    #[serde(default = "default_components_y")]
+    pub components_y: u32,
+}
+
+impl Default for PlaceholderConfig {
+    fn default() -> Self {
+        Self {
+            width: default_width(),
+            blur_sigma: default_blur_sigma(),
+            blurhash: true,
+            components_x: default_components_x(),
+            components_y: default_components_y(),
+        }
+    }
+}
+
+/// A generated placeholder for a single image.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImagePlaceholder {
+    /// Width of the original image in pixels
+    pub width: u32,
+
+    /// Height of the original image in pixels
+    pub height: u32,
+
+    /// Blurred thumbnail encoded as a `data:image/png;base64,...` URI
+    pub data_uri: String,
+
+    /// BlurHash representation of the image, if enabled
+    pub blurhash: Option<String>,
+}
+
+/// Returns `true` if a placeholder can be generated for the given path.
+///
+/// # Arguments
+/// * `path` - The path of the asset to check.
+pub fn is_placeholder_candidate(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| {
+            PLACEHOLDER_EXTENSIONS
+                .contains(&ext.to_lowercase().as_str())
+        })
+        .unwrap_or(false)
+}
+
+/// Generates a placeholder for the encoded image in `bytes`.
+///
+/// # Arguments
+/// * `bytes` - The raw contents of the image file.
+/// * `config` - Placeholder generation options.
+///
+/// # Returns
+/// * `Result<ImagePlaceholder>` - The generated placeholder, or an error if
+///   the image cannot be decoded or encoded.
+pub fn generate_placeholder(
+    bytes: &[u8],
+    config: &PlaceholderConfig,
+) -> Result<ImagePlaceholder> {
+    if config.width == 0 {
+        return Err(ProcessingError::validation(
+            "Placeholder width must be greater than zero",
+            None::<String>,
+        ));
+    }
+
+    let image = image::load_from_memory(bytes).map_err(|e| {
+        ProcessingError::content_processing(
+            "Failed to decode image for placeholder",
+            Some(Box::new(e)),
+        )
+    })?;
+    let (width, height) = (image.width(), image.height());
+
+    let thumbnail =
+        resize_to_width(&image, config.width).blur(config.blur_sigma);
+    let mut encoded = Cursor::new(Vec::new());
+    thumbnail.write_to(&mut encoded, ImageFormat::Png).map_err(
+        |e| {
+            ProcessingError::content_processing(
+                "Failed to encode placeholder image",
+                Some(Box::new(e)),
+            )
+        },
+    )?;
+    let data_uri = format!(
+        "data:image/png;base64,{}",
+        STANDARD.encode(encoded.into_inner())
+    );
+
+    let blurhash = if config.blurhash {
+        let sample =
+            resize_to_width(&image, BLURHASH_SAMPLE_WIDTH).to_rgba8();
+        let hash = blurhash::encode(
+            config.components_x,
+            config.components_y,
+            sample.width(),
+            sample.height(),
+            sample.as_raw(),
+        )
+        .map_err(|e| {
+            ProcessingError::content_processing(
+                "Failed to compute BlurHash",
+                Some(Box::new(e)),
+            )
+        })?;
+        Some(hash)
+    } else {
+        None
+    };
+
+    Ok(ImagePlaceholder {
+        width,
+        height,
+        data_uri,
+        blurhash,
+    })
+}
+
+/// Resizes an image to the given width, preserving its aspect ratio.
+fn resize_to_width(image: &DynamicImage, width: u32) -> DynamicImage {
+    let width = width.min(image.width()).max(1);
+    let height = ((u64::from(image.height()) * u64::from(width))
+        / u64::from(image.width().max(1)))
+    .max(1);
+    image.resize_exact(
+        width,
+        u32::try_from(height).unwrap_or(u32::MAX),
+        FilterType::Triangle,
+    )
+}
+
+// Helper functions for serde defaults
+fn default_width() -> u32 {
+    16
+}
+
+fn default_blur_sigma() -> f32 {
+    1.5
+}
+
+fn default_components_x() -> u32 {
+    4
+}
+
+fn default_components_y() -> u32 {
+    3
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    fn sample_png(width: u32, height: u32) -> Vec<u8> {
+        let image = RgbImage::from_fn(width, height, |x, y| {
+            Rgb([(x % 256) as u8, (y % 256) as u8, 128])
+        });
+        let mut bytes = Cursor::new(Vec::new());
+        DynamicImage::ImageRgb8(image)
+            .write_to(&mut bytes, ImageFormat::Png)
+            .unwrap();
+        bytes.into_inner()
+    }
+
+    #[test]
+    fn test_placeholder_candidate() {
+        assert!(is_placeholder_candidate(Path::new("hero.JPG")));
+        assert!(is_placeholder_candidate(Path::new("img/logo.png")));
+        assert!(!is_placeholder_candidate(Path::new("logo.svg")));
+        assert!(!is_placeholder_candidate(Path::new("README")));
+    }
+
+    #[test]
+    fn test_generate_placeholder() {
+        let placeholder = generate_placeholder(
+            &sample_png(200, 100),
+            &PlaceholderConfig::default(),
+        )
+        .unwrap();
+
+        assert_eq!((placeholder.width, placeholder.height), (200, 100));
+        assert!(placeholder
+            .data_uri
+            .starts_with("data:image/png;base64,"));
+        assert!(placeholder.blurhash.is_some());
+    }
+
+    #[test]
+    fn test_generate_placeholder_without_blurhash() {
+        let config = PlaceholderConfig {
+            blurhash: false,
+            ..PlaceholderConfig::default()
+        };
+        let placeholder =
+            generate_placeholder(&sample_png(8, 8), &config).unwrap();
+        assert!(placeholder.blurhash.is_none());
+    }
+
+    #[test]
+    fn test_invalid_image() {
+        let result = generate_placeholder(
+            b"not an image",
+            &PlaceholderConfig::default(),
+        );
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,159 @@
+//! # Menus
+//!
+//! Exposes the navigation menus of the `[menus]` config section to
+//! templates as `site.menus`, keyed by name, such as `site.menus.main`.
+//!
+//! Each menu is a list of entries, ordered by `weight`, and each entry
+//! carries its `title`, `url`, `weight` and nested `children`, along
+//! with two flags for the page being rendered:
+//!
+//! - `is_active` is `true` on the entry linking to the page, ignoring
+//!   any query or fragment, with `/docs/` and `/docs/index.html`
+//!   linking to the same page, and
+//! - `has_active_child` is `true` on the entries above it, so templates
+//!   can open or highlight the branch leading to the page.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::core::config::MenuItem;
+//! use nucleusflow::generators::menus::menus_context;
+//! use std::collections::BTreeMap;
+//!
+//! let docs = MenuItem {
+//!     title: "Docs".to_string(),
+//!     url: "/docs/".to_string(),
+//!     weight: 0,
+//!     children: vec![MenuItem {
+//!         title: "Install".to_string(),
+//!         url: "/docs/install.html".to_string(),
+//!         weight: 0,
+//!         children: Vec::new(),
+//!     }],
+//! };
+//! let menus: BTreeMap<_, _> =
+//!     [("main".to_string(), vec![docs])].into_iter().collect();
+//!
+//! let context = menus_context(&menus, "/docs/install.html");
+//! let entry = &context["main"][0];
+//! assert_eq!(entry["is_active"], false);
+//! assert_eq!(entry["has_active_child"], true);
+//! assert_eq!(entry["children"][0]["is_active"], true);
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde_json::{json, Map, Value as JsonValue};
+
+use crate::core::config::MenuItem;
+
+/// Returns the menus as templates see them while a page is rendered.
+///
+/// # Arguments
+/// * `menus` - The menus, keyed by name.
+/// * `url` - The site-relative URL of the page being rendered.
+///
+/// # Returns
+/// * `JsonValue` - The menus keyed by name, their entries ordered and
+///   flagged for the page.
+pub fn menus_context(
+    menus: &BTreeMap<String, Vec<MenuItem>>,
+    url: &str,
+) -> JsonValue {
+    let mut context = Map::new();
+    for (name, items) in menus {
+        _ = context.insert(name.clone(), entries(items, url).0.into());
+    }
+    JsonValue::Object(context)
+}
+
+/// Returns menu entries for templates, and whether one of them, or an
+/// entry beneath them, links to the page.
+fn entries(items: &[MenuItem], url: &str) -> (Vec<JsonValue>, bool) {
+    let mut items: Vec<&MenuItem> = items.iter().collect();
+    items.sort_by_key(|item| item.weight);
+    let mut any_active = false;
+    let entries = items
+        .into_iter()
+        .map(|item| {
+            let (children, has_active_child) =
+                entries(&item.children, url);
+            let is_active = same_page(&item.url, url);
+            any_active |= is_active || has_active_child;
+            json!({
+                "title": item.title,
+                "url": item.url,
+                "weight": item.weight,
+                "is_active": is_active,
+                "has_active_child": has_active_child,
+                "children": children,
+            })
+        })
+        .collect();
+    (entries, any_active)
+}
+
+/// Returns `true` if a menu entry's URL addresses a page's URL.
+fn same_page(link: &str, url: &str) -> bool {
+    let path = |url: &str| {
+        let end = url.find(['?', '#']).unwrap_or(url.len());
+        let path = &url[..end];
+        path.strip_suffix("index.html").unwrap_or(path).to_string()
+    };
+    !link.is_empty() && path(link) == path(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(title: &str, url: &str, weight: i64) -> MenuItem {
+        MenuItem {
+            title: title.to_string(),
+            url: url.to_string(),
+            weight,
+            children: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_menus_context() {
+        let mut docs = item("Docs", "/docs/index.html", 2);
+        docs.children = vec![
+            item("FAQ", "/docs/faq.html", 2),
+            item("Install", "/docs/install.html#linux", 1),
+        ];
+        let menus: BTreeMap<_, _> = [
+            (
+                "main".to_string(),
+                vec![docs, item("Home", "/", 1), item("Blog", "", 1)],
+            ),
+            ("footer".to_string(), vec![item("Docs", "/docs/", 0)]),
+        ]
+        .into_iter()
+        .collect();
+
+        let context = menus_context(&menus, "/docs/install.html");
+        let titles: Vec<&str> = context["main"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry["title"].as_str().unwrap())
+            .collect();
+        assert_eq!(titles, ["Home", "Blog", "Docs"]);
+        let docs = &context["main"][2];
+        assert_eq!(docs["is_active"], false);
+        assert_eq!(docs["has_active_child"], true);
+        assert_eq!(docs["children"][0]["title"], "Install");
+        assert_eq!(docs["children"][0]["is_active"], true);
+        assert_eq!(docs["children"][1]["is_active"], false);
+        assert_eq!(context["main"][0]["has_active_child"], false);
+        assert_eq!(context["footer"][0]["is_active"], false);
+
+        let context = menus_context(&menus, "/docs/");
+        assert_eq!(context["main"][2]["is_active"], true);
+        assert_eq!(context["main"][2]["has_active_child"], false);
+        assert_eq!(context["main"][1]["is_active"], false);
+        assert_eq!(context["footer"][0]["is_active"], true);
+    }
+}
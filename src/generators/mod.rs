@@ -1,2 +1,59 @@
+/// The `archives` module provides year and month archive pages
+pub mod archives;
+/// The `authors` module provides author details and author pages
+pub mod authors;
+/// The `bundles` module provides per-page CSS/JS bundle resolution
+pub mod bundles;
+/// The `compress` module provides pre-compressed gzip/brotli output
+pub mod compress;
+/// The `data_pages` module provides pages generated from data records
+pub mod data_pages;
+/// The `debug_artifacts` module strips source maps and debug variants
+pub mod debug_artifacts;
+/// The `dedup` module provides content hash-based image deduplication
+pub mod dedup;
+/// The `deploy` module provides incremental preview deploys
+pub mod deploy;
+/// The `dom` module provides an HTML5 document tree and validation
+pub mod dom;
+/// The `fingerprint` module provides content-hashed asset names
+pub mod fingerprint;
+/// The `hreflang` module provides alternate links between translations
+pub mod hreflang;
 /// The `html` module provides configuration handling
 pub mod html;
+/// The `images` module provides image placeholder generation
+pub mod images;
+/// The `menus` module provides navigation menus for templates
+pub mod menus;
+/// The `postprocess` module provides built-in HTML post-processors
+pub mod postprocess;
+/// The `pretty` module provides DOM-based HTML pretty printing
+pub mod pretty;
+/// The `preview` module provides dry-run build change previews
+pub mod preview;
+/// The `redirects` module provides redirect map collection and export
+pub mod redirects;
+/// The `report` module provides the machine-readable build report
+pub mod report;
+/// The `screenshots` module provides headless browser page screenshots
+pub mod screenshots;
+/// The `seo` module provides social meta tags and canonical links
+pub mod seo;
+/// The `series` module provides article series listings
+pub mod series;
+/// The `sitemap` module provides sharded sitemap and index generation
+pub mod sitemap;
+/// The `social_cards` module provides rendered social card images
+#[cfg(feature = "social-cards")]
+pub mod social_cards;
+/// The `staging` module provides atomic output directory swaps
+pub mod staging;
+/// The `static_files` module provides static file passthrough
+pub mod static_files;
+/// The `structured_data` module provides schema.org JSON-LD
+pub mod structured_data;
+/// The `svg` module provides SVG asset optimisation
+pub mod svg;
+/// The `tombstones` module provides 410 Gone stubs for removed pages
+pub mod tombstones;
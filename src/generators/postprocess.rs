@@ -0,0 +1,230 @@
+//! # HTML Post-Processors
+//!
+//! Built-in [`PostProcessor`]s, run on each page's rendered HTML before
+//! it is written:
+//!
+//! - [`SnippetInjector`] inserts a fixed snippet, such as an analytics
+//!   tag, before `</head>` or `</body>`.
+//! - [`LinkRewriter`] prefixes root-relative `href` and `src` URLs with a
+//!   base path, for sites served from a subdirectory.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::core::traits::PostProcessor;
+//! use nucleusflow::generators::postprocess::LinkRewriter;
+//! use nucleusflow::site::Page;
+//!
+//! let page = Page::new(
+//!     "content/index.md",
+//!     "index.html",
+//!     Default::default(),
+//! );
+//! let html = LinkRewriter::new("/docs")
+//!     .process(r#"<a href="/guide.html">Guide</a>"#, &page)
+//!     .unwrap();
+//! assert_eq!(html, r#"<a href="/docs/guide.html">Guide</a>"#);
+//! ```
+
+use crate::core::error::Result;
+use crate::core::traits::PostProcessor;
+use crate::site::Page;
+
+/// Attributes whose root-relative URLs [`LinkRewriter`] prefixes.
+const URL_ATTRIBUTES: [&str; 2] = ["href", "src"];
+
+/// Where [`SnippetInjector`] places its snippet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InjectPosition {
+    /// Just before `</head>`
+    HeadEnd,
+    /// Just before `</body>`
+    BodyEnd,
+}
+
+/// Inserts a fixed HTML snippet into every page.
+///
+/// Pages without the closing tag get the snippet appended at the end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnippetInjector {
+    snippet: String,
+    position: InjectPosition,
+}
+
+impl SnippetInjector {
+    /// Creates an injector placing `snippet` at `position`.
+    pub fn new<S: Into<String>>(
+        snippet: S,
+        position: InjectPosition,
+    ) -> Self {
+        Self {
+            snippet: snippet.into(),
+            position,
+        }
+    }
+}
+
+impl PostProcessor for SnippetInjector {
+    fn name(&self) -> &str {
+        "snippet"
+    }
+
+    fn process(&self, html: &str, _page: &Page) -> Result<String> {
+        let tag = match self.position {
+            InjectPosition::HeadEnd => "</head>",
+            InjectPosition::BodyEnd => "</body>",
+        };
+        let index =
+            html.to_ascii_lowercase().rfind(tag).unwrap_or(html.len());
+
+        let mut output =
+            String::with_capacity(html.len() + self.snippet.len());
+        output.push_str(&html[..index]);
+        output.push_str(&self.snippet);
+        output.push_str(&html[index..]);
+        Ok(output)
+    }
+}
+
+/// Prefixes root-relative `href` and `src` URLs with a base path.
+///
+/// Protocol-relative URLs (`//cdn.example.com/...`) and URLs already
+/// under the base path are left alone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkRewriter {
+    base_path: String,
+}
+
+impl LinkRewriter {
+    /// Creates a rewriter for a site served under `base_path`, such as
+    /// `/docs`.
+    pub fn new(base_path: &str) -> Self {
+        Self {
+            base_path: format!("/{}", base_path.trim_matches('/')),
+        }
+    }
+
+    /// Returns `true` if the URL at the start of `url` should be
+    /// prefixed. `url` runs to the end of the page, so it still holds
+    /// the closing quote.
+    fn needs_prefix(&self, url: &str) -> bool {
+        if !url.starts_with('/') || url.starts_with("//") {
+            return false;
+        }
+        if self.base_path == "/" {
+            return false;
+        }
+        !url.starts_with(&self.base_path)
+            || !matches!(
+                url[self.base_path.len()..].chars().next(),
+                Some('/' | '"' | '\'' | '?' | '#')
+            )
+    }
+}
+
+impl PostProcessor for LinkRewriter {
+    fn name(&self) -> &str {
+        "links"
+    }
+
+    fn process(&self, html: &str, _page: &Page) -> Result<String> {
+        let mut output = String::with_capacity(html.len());
+        let mut copied = 0;
+        for start in url_starts(html) {
+            if self.needs_prefix(&html[start..]) {
+                output.push_str(&html[copied..start]);
+                output.push_str(&self.base_path);
+                copied = start;
+            }
+        }
+        output.push_str(&html[copied..]);
+        Ok(output)
+    }
+}
+
+/// Returns where each quoted `href` or `src` value starts, in order.
+fn url_starts(html: &str) -> Vec<usize> {
+    let lower = html.to_ascii_lowercase();
+    let bytes = lower.as_bytes();
+    let mut starts: Vec<usize> = URL_ATTRIBUTES
+        .iter()
+        .flat_map(|attribute| {
+            let pattern = format!("{}=", attribute);
+            let length = pattern.len();
+            lower
+                .match_indices(&pattern)
+                .map(|(index, _)| (index, index + length))
+                .collect::<Vec<_>>()
+        })
+        .filter(|&(index, quote)| {
+            index > 0
+                && bytes[index - 1].is_ascii_whitespace()
+                && matches!(bytes.get(quote), Some(b'"' | b'\''))
+        })
+        .map(|(_, quote)| quote + 1)
+        .collect();
+    starts.sort_unstable();
+    starts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::site::tests::page;
+    use serde_json::json;
+
+    #[test]
+    fn test_snippet_injector() {
+        let head = SnippetInjector::new(
+            "<script></script>",
+            InjectPosition::HeadEnd,
+        );
+        assert_eq!(
+            head.process(
+                "<HEAD><title>x</title></HEAD><body></body>",
+                &page("index.html", json!({}))
+            )
+            .unwrap(),
+            "<HEAD><title>x</title><script></script></HEAD><body></body>"
+        );
+
+        let body = SnippetInjector::new(
+            "<!-- end -->",
+            InjectPosition::BodyEnd,
+        );
+        assert_eq!(
+            body.process(
+                "<p>no body</p>",
+                &page("index.html", json!({}))
+            )
+            .unwrap(),
+            "<p>no body</p><!-- end -->"
+        );
+    }
+
+    #[test]
+    fn test_link_rewriter() {
+        let rewriter = LinkRewriter::new("docs/");
+        let html = concat!(
+            r#"<a href="/guide.html">a</a>"#,
+            r#"<img src='/img/cat.png'>"#,
+            r#"<a href="//cdn.example.com/x.js">b</a>"#,
+            r#"<a href="/docs/api.html">c</a>"#,
+            r#"<a href="relative.html">d</a>"#,
+            r#"<a data-href="/x">e</a>"#,
+        );
+        assert_eq!(
+            rewriter
+                .process(html, &page("index.html", json!({})))
+                .unwrap(),
+            concat!(
+                r#"<a href="/docs/guide.html">a</a>"#,
+                r#"<img src='/docs/img/cat.png'>"#,
+                r#"<a href="//cdn.example.com/x.js">b</a>"#,
+                r#"<a href="/docs/api.html">c</a>"#,
+                r#"<a href="relative.html">d</a>"#,
+                r#"<a data-href="/x">e</a>"#,
+            )
+        );
+    }
+}
@@ -0,0 +1,506 @@
+//! # HTML Pretty Printing
+//!
+//! Formats HTML by walking a parsed document tree rather than the raw
+//! markup, so the output is always well-formed and respects how HTML is
+//! rendered:
+//!
+//! - Block-level elements start on their own line and are indented by
+//!   depth.
+//! - Runs of text and inline elements (`<strong>`, `<a>`, `<code>`, ...)
+//!   stay on a single line, so no whitespace is introduced inside them.
+//! - Elements in the preserve list (`pre`, `textarea`, `script` and `style`
+//!   by default) are emitted verbatim, since their whitespace is
+//!   significant.
+//!
+//! Input starting with a DOCTYPE or `<html>` is treated as a complete
+//! document; anything else is formatted as a fragment.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::generators::pretty::PrettyPrinter;
+//!
+//! let html = PrettyPrinter::new()
+//!     .print("<div><p>Some <strong>bold</strong> text</p></div>");
+//! assert_eq!(
+//!     html,
+//!     "<div>\n    <p>Some <strong>bold</strong> text</p>\n</div>\n"
+//! );
+//! ```
+
+use std::collections::HashSet;
+
+use crate::generators::dom::{Document, NodeData, NodeId};
+
+/// Elements whose content is preserved verbatim by default.
+pub const DEFAULT_PRESERVE: &[&str] =
+    &["pre", "textarea", "script", "style"];
+
+/// Default number of spaces per indentation level.
+pub const DEFAULT_INDENT_SIZE: usize = 4;
+
+/// Elements laid out inline, which never start a new line.
+const INLINE_ELEMENTS: &[&str] = &[
+    "a", "abbr", "b", "bdi", "bdo", "br", "button", "cite", "code",
+    "data", "del", "dfn", "em", "i", "img", "input", "ins", "kbd",
+    "label", "mark", "meter", "output", "picture", "progress", "q",
+    "s", "samp", "select", "small", "span", "strong", "sub", "sup",
+    "svg", "time", "u", "var", "wbr",
+];
+
+/// Elements that have no closing tag.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link",
+    "meta", "source", "track", "wbr",
+];
+
+/// Elements whose text content is not escaped.
+const RAW_TEXT_ELEMENTS: &[&str] = &[
+    "script",
+    "style",
+    "xmp",
+    "iframe",
+    "noembed",
+    "noframes",
+    "plaintext",
+];
+
+/// Formats HTML with indentation and line breaks.
+#[derive(Debug, Clone)]
+pub struct PrettyPrinter {
+    indent_size: usize,
+    preserve: HashSet<String>,
+}
+
+impl PrettyPrinter {
+    /// Creates a printer with four-space indentation and the default
+    /// preserve list.
+    pub fn new() -> Self {
+        Self {
+            indent_size: DEFAULT_INDENT_SIZE,
+            preserve: DEFAULT_PRESERVE
+                .iter()
+                .map(|tag| tag.to_string())
+                .collect(),
+        }
+    }
+
+    /// Sets the number of spaces per indentation level.
+    pub fn with_indent_size(mut self, indent_size: usize) -> Self {
+        self.indent_size = indent_size;
+        self
+    }
+
+    /// Replaces the elements whose content is preserved verbatim.
+    pub fn with_preserve<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.preserve = tags
+            .into_iter()
+            .map(|tag| tag.into().to_lowercase())
+            .collect();
+        self
+    }
+
+    /// Formats an HTML document or fragment.
+    ///
+    /// # Arguments
+    /// * `html` - The HTML to format.
+    ///
+    /// # Returns
+    /// * `String` - The formatted HTML, ending with a newline.
+    pub fn print(&self, html: &str) -> String {
+        let mut output = String::with_capacity(html.len() * 2);
+        if is_document(html) {
+            let document = Document::parse(html);
+            let root = document.root();
+            self.print_block_children(&document, root, 0, &mut output);
+        } else {
+            let document = Document::parse_fragment(html);
+            // Fragment nodes live under a synthetic `<html>` element
+            if let Some(&context) =
+                document.node(document.root()).children.first()
+            {
+                self.print_block_children(
+                    &document,
+                    context,
+                    0,
+                    &mut output,
+                );
+            }
+        }
+        output
+    }
+
+    /// Prints the children of a block container, one line per block
+    /// child or inline run.
+    fn print_block_children(
+        &self,
+        document: &Document,
+        parent: NodeId,
+        depth: usize,
+        output: &mut String,
+    ) {
+        let mut inline_run = String::new();
+        for &child in &document.node(parent).children {
+            if self.is_inline(document, child) {
+                self.print_inline(document, child, &mut inline_run);
+                continue;
+            }
+            self.flush_inline_run(&mut inline_run, depth, output);
+            self.print_block(document, child, depth, output);
+        }
+        self.flush_inline_run(&mut inline_run, depth, output);
+    }
+
+    /// Writes a pending run of inline content as a single line.
+    fn flush_inline_run(
+        &self,
+        run: &mut String,
+        depth: usize,
+        output: &mut String,
+    ) {
+        let line = run.trim();
+        if !line.is_empty() {
+            self.push_indent(depth, output);
+            output.push_str(line);
+            output.push('\n');
+        }
+        run.clear();
+    }
+
+    /// Prints a block-level node on its own line.
+    fn print_block(
+        &self,
+        document: &Document,
+        id: NodeId,
+        depth: usize,
+        output: &mut String,
+    ) {
+        let node = document.node(id);
+        self.push_indent(depth, output);
+        match &node.data {
+            NodeData::Doctype { name } => {
+                output.push_str("<!DOCTYPE ");
+                output.push_str(name);
+                output.push('>');
+            }
+            NodeData::Comment(text) => {
+                output.push_str("<!--");
+                output.push_str(text);
+                output.push_str("-->");
+            }
+            NodeData::ProcessingInstruction { target, data } => {
+                output.push_str("<?");
+                output.push_str(target);
+                output.push(' ');
+                output.push_str(data);
+                output.push('>');
+            }
+            NodeData::Element { .. } => {
+                let name = node.element_name().unwrap_or_default();
+                push_start_tag(document, id, output);
+                if VOID_ELEMENTS.contains(&name) {
+                    // No content or closing tag
+                } else if self.preserve.contains(name) {
+                    push_verbatim(document, id, output);
+                    push_end_tag(name, output);
+                } else {
+                    let children = content_children(document, id);
+                    if children
+                        .iter()
+                        .all(|&child| self.is_inline(document, child))
+                    {
+                        let mut line = String::new();
+                        for &child in children {
+                            self.print_inline(
+                                document, child, &mut line,
+                            );
+                        }
+                        output.push_str(line.trim());
+                    } else {
+                        output.push('\n');
+                        self.print_children_of(
+                            document,
+                            id,
+                            depth + 1,
+                            output,
+                        );
+                        self.push_indent(depth, output);
+                    }
+                    push_end_tag(name, output);
+                }
+            }
+            NodeData::Document | NodeData::Text(_) => {}
+        }
+        output.push('\n');
+    }
+
+    /// Prints the content of an element, following `<template>`
+    /// contents.
+    fn print_children_of(
+        &self,
+        document: &Document,
+        id: NodeId,
+        depth: usize,
+        output: &mut String,
+    ) {
+        let container = match &document.node(id).data {
+            NodeData::Element {
+                template_contents: Some(contents),
+                ..
+            } => *contents,
+            _ => id,
+        };
+        self.print_block_children(document, container, depth, output);
+    }
+
+    /// Prints an inline node compactly, collapsing whitespace in text.
+    fn print_inline(
+        &self,
+        document: &Document,
+        id: NodeId,
+        output: &mut String,
+    ) {
+        let node = document.node(id);
+        match &node.data {
+            NodeData::Text(text) => {
+                push_collapsed_text(text, output);
+            }
+            NodeData::Comment(text) => {
+                output.push_str("<!--");
+                output.push_str(text);
+                output.push_str("-->");
+            }
+            NodeData::Element { .. } => {
+                let name = node.element_name().unwrap_or_default();
+                push_start_tag(document, id, output);
+                if VOID_ELEMENTS.contains(&name) {
+                    return;
+                }
+                if self.preserve.contains(name) {
+                    push_verbatim(document, id, output);
+                } else {
+                    for &child in content_children(document, id) {
+                        self.print_inline(document, child, output);
+                    }
+                }
+                push_end_tag(name, output);
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns `true` if a node is laid out inline.
+    fn is_inline(&self, document: &Document, id: NodeId) -> bool {
+        let node = document.node(id);
+        match &node.data {
+            NodeData::Text(_) => true,
+            NodeData::Element { .. } => node
+                .element_name()
+                .map_or(false, |name| INLINE_ELEMENTS.contains(&name)),
+            _ => false,
+        }
+    }
+
+    fn push_indent(&self, depth: usize, output: &mut String) {
+        output.extend(
+            std::iter::repeat(' ').take(depth * self.indent_size),
+        );
+    }
+}
+
+impl Default for PrettyPrinter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns `true` if the input is a complete document rather than a
+/// fragment.
+fn is_document(html: &str) -> bool {
+    let start: String = html
+        .trim_start()
+        .chars()
+        .take(9)
+        .collect::<String>()
+        .to_ascii_lowercase();
+    start.starts_with("<!doctype") || start.starts_with("<html")
+}
+
+/// Returns the children of an element, following `<template>` contents.
+fn content_children(document: &Document, id: NodeId) -> &[NodeId] {
+    match &document.node(id).data {
+        NodeData::Element {
+            template_contents: Some(contents),
+            ..
+        } => &document.node(*contents).children,
+        _ => &document.node(id).children,
+    }
+}
+
+/// Writes an element's start tag with escaped attributes.
+fn push_start_tag(
+    document: &Document,
+    id: NodeId,
+    output: &mut String,
+) {
+    if let NodeData::Element { name, attrs, .. } =
+        &document.node(id).data
+    {
+        output.push('<');
+        output.push_str(&name.local);
+        for attr in attrs {
+            output.push(' ');
+            if let Some(prefix) = &attr.name.prefix {
+                output.push_str(prefix);
+                output.push(':');
+            }
+            output.push_str(&attr.name.local);
+            output.push_str("=\"");
+            for c in attr.value.chars() {
+                match c {
+                    '&' => output.push_str("&amp;"),
+                    '"' => output.push_str("&quot;"),
+                    '\u{a0}' => output.push_str("&nbsp;"),
+                    _ => output.push(c),
+                }
+            }
+            output.push('"');
+        }
+        output.push('>');
+    }
+}
+
+fn push_end_tag(name: &str, output: &mut String) {
+    output.push_str("</");
+    output.push_str(name);
+    output.push('>');
+}
+
+/// Writes an element's content exactly as parsed.
+fn push_verbatim(document: &Document, id: NodeId, output: &mut String) {
+    let name = document.node(id).element_name().unwrap_or_default();
+    // The parser drops a newline directly after these start tags
+    if matches!(name, "pre" | "textarea" | "listing") {
+        if let Some(&first) = content_children(document, id).first() {
+            if let NodeData::Text(text) = &document.node(first).data {
+                if text.starts_with('\n') {
+                    output.push('\n');
+                }
+            }
+        }
+    }
+    push_serialized_children(document, id, output);
+}
+
+/// Serializes the children of a node without any formatting.
+fn push_serialized_children(
+    document: &Document,
+    id: NodeId,
+    output: &mut String,
+) {
+    let raw_text = document
+        .node(id)
+        .element_name()
+        .map_or(false, |name| RAW_TEXT_ELEMENTS.contains(&name));
+    for &child in content_children(document, id) {
+        let node = document.node(child);
+        match &node.data {
+            NodeData::Text(text) if raw_text => output.push_str(text),
+            NodeData::Text(text) => push_escaped_text(text, output),
+            NodeData::Comment(text) => {
+                output.push_str("<!--");
+                output.push_str(text);
+                output.push_str("-->");
+            }
+            NodeData::Element { .. } => {
+                let name = node.element_name().unwrap_or_default();
+                push_start_tag(document, child, output);
+                if !VOID_ELEMENTS.contains(&name) {
+                    push_serialized_children(document, child, output);
+                    push_end_tag(name, output);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn push_escaped_text(text: &str, output: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '\u{a0}' => output.push_str("&nbsp;"),
+            _ => output.push(c),
+        }
+    }
+}
+
+/// Writes escaped text with runs of whitespace collapsed to one space.
+fn push_collapsed_text(text: &str, output: &mut String) {
+    let mut previous_space = output.ends_with(' ');
+    for c in text.chars() {
+        if c.is_ascii_whitespace() {
+            if !previous_space {
+                output.push(' ');
+            }
+            previous_space = true;
+            continue;
+        }
+        previous_space = false;
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '\u{a0}' => output.push_str("&nbsp;"),
+            _ => output.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inline_elements_stay_on_one_line() {
+        let html = PrettyPrinter::new().print(
+            "<ul><li>One <em>two</em></li><li><a href=\"/x\">three</a>\n  four</li></ul>",
+        );
+        assert_eq!(
+            html,
+            "<ul>\n    <li>One <em>two</em></li>\n    <li><a href=\"/x\">three</a> four</li>\n</ul>\n"
+        );
+    }
+
+    #[test]
+    fn test_preserved_content() {
+        let input = "<div><pre>\n\nfn main() {\n    1 < 2;\n}</pre><script>if (a < b) {}\n</script></div>";
+        let html = PrettyPrinter::new().print(input);
+        assert!(html
+            .contains("<pre>\n\nfn main() {\n    1 &lt; 2;\n}</pre>"));
+        assert!(html.contains("<script>if (a < b) {}\n</script>"));
+
+        // Custom preserve lists apply to any element
+        let html = PrettyPrinter::new()
+            .with_preserve(["div"])
+            .print("<div>\n  <p>x</p>\n</div>");
+        assert_eq!(html, "<div>\n  <p>x</p>\n</div>\n");
+    }
+
+    #[test]
+    fn test_full_document() {
+        let html = PrettyPrinter::new().with_indent_size(2).print(
+            "<!DOCTYPE html><html><head><title>T &amp; U</title></head><body><h1>Hi</h1>Text<br>more<p>x</p></body></html>",
+        );
+        assert_eq!(
+            html,
+            "<!DOCTYPE html>\n<html>\n  <head>\n    <title>T &amp; U</title>\n  </head>\n  <body>\n    <h1>Hi</h1>\n    Text<br>more\n    <p>x</p>\n  </body>\n</html>\n"
+        );
+    }
+}
@@ -0,0 +1,269 @@
+//! # Build Preview
+//!
+//! Compares the current output directory with the output a build would
+//! produce, so a dry run can report which files would be created,
+//! updated or deleted, and how their sizes would change, before anything
+//! is written.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use nucleusflow::generators::preview::BuildPreview;
+//! use std::path::Path;
+//!
+//! let preview =
+//!     BuildPreview::compare(Path::new("public"), Path::new("/tmp/next"))
+//!         .unwrap();
+//! print!("{}", preview);
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use crate::{ProcessingError, Result};
+
+/// How a build would change a single output file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// The file does not exist yet
+    Created,
+    /// The file exists with different content
+    Updated,
+    /// The file exists but the build no longer produces it
+    Deleted,
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ChangeKind::Created => "create",
+            ChangeKind::Updated => "update",
+            ChangeKind::Deleted => "delete",
+        })
+    }
+}
+
+/// A single output file a build would change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    /// The file's path relative to the output directory
+    pub path: PathBuf,
+
+    /// How the file would change
+    pub kind: ChangeKind,
+
+    /// The current size in bytes, if the file exists
+    pub old_size: Option<u64>,
+
+    /// The size after the build in bytes, unless it would be deleted
+    pub new_size: Option<u64>,
+}
+
+impl FileChange {
+    /// Returns the change in size in bytes.
+    pub fn size_delta(&self) -> i64 {
+        self.new_size.unwrap_or(0) as i64
+            - self.old_size.unwrap_or(0) as i64
+    }
+}
+
+/// The changes a build would make to an output directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuildPreview {
+    /// The changed files, ordered by path
+    pub changes: Vec<FileChange>,
+}
+
+impl BuildPreview {
+    /// Compares an output directory with a freshly built one.
+    ///
+    /// Files present in both with identical content are not reported.
+    ///
+    /// # Arguments
+    /// * `current` - The existing output directory, which may not exist.
+    /// * `proposed` - The directory the new build was written to.
+    ///
+    /// # Returns
+    /// * `Result<Self>` - The changes, or an error if either directory
+    ///   cannot be read.
+    pub fn compare(current: &Path, proposed: &Path) -> Result<Self> {
+        let old_files = list_files(current)?;
+        let new_files = list_files(proposed)?;
+
+        let mut changes = Vec::new();
+        for (path, new_size) in &new_files {
+            let kind = match old_files.get(path) {
+                None => ChangeKind::Created,
+                Some(old_size)
+                    if old_size != new_size
+                        || read(&current.join(path))?
+                            != read(&proposed.join(path))? =>
+                {
+                    ChangeKind::Updated
+                }
+                Some(_) => continue,
+            };
+            changes.push(FileChange {
+                path: path.clone(),
+                kind,
+                old_size: old_files.get(path).copied(),
+                new_size: Some(*new_size),
+            });
+        }
+        for (path, old_size) in &old_files {
+            if !new_files.contains_key(path) {
+                changes.push(FileChange {
+                    path: path.clone(),
+                    kind: ChangeKind::Deleted,
+                    old_size: Some(*old_size),
+                    new_size: None,
+                });
+            }
+        }
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+
+        Ok(Self { changes })
+    }
+
+    /// Returns the number of changes of the given kind.
+    pub fn count(&self, kind: ChangeKind) -> usize {
+        self.changes.iter().filter(|c| c.kind == kind).count()
+    }
+
+    /// Returns the total change in output size in bytes.
+    pub fn size_delta(&self) -> i64 {
+        self.changes.iter().map(FileChange::size_delta).sum()
+    }
+
+    /// Returns `true` if the build would not change any files.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl fmt::Display for BuildPreview {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(
+                f,
+                "{}  {} ({:+} bytes)",
+                change.kind,
+                change.path.display(),
+                change.size_delta()
+            )?;
+        }
+        writeln!(
+            f,
+            "{} created, {} updated, {} deleted ({:+} bytes)",
+            self.count(ChangeKind::Created),
+            self.count(ChangeKind::Updated),
+            self.count(ChangeKind::Deleted),
+            self.size_delta()
+        )
+    }
+}
+
+/// Lists the files under a directory with their sizes, keyed by path
+/// relative to it.
+fn list_files(dir: &Path) -> Result<BTreeMap<PathBuf, u64>> {
+    let mut files = BTreeMap::new();
+    if !dir.is_dir() {
+        return Ok(files);
+    }
+
+    for entry in WalkDir::new(dir) {
+        let entry = entry.map_err(|e| {
+            ProcessingError::content_processing(
+                "Failed to read output directory",
+                Some(Box::new(e)),
+            )
+        })?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let size = entry
+            .metadata()
+            .map_err(|e| {
+                ProcessingError::content_processing(
+                    "Failed to read file metadata",
+                    Some(Box::new(e)),
+                )
+            })?
+            .len();
+        if let Ok(relative) = entry.path().strip_prefix(dir) {
+            _ = files.insert(relative.to_path_buf(), size);
+        }
+    }
+    Ok(files)
+}
+
+fn read(path: &Path) -> Result<Vec<u8>> {
+    fs::read(path)
+        .map_err(|e| ProcessingError::io_error(path.to_path_buf(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_compare() {
+        let current = TempDir::new().unwrap();
+        let proposed = TempDir::new().unwrap();
+        fs::write(current.path().join("same.html"), "same").unwrap();
+        fs::write(proposed.path().join("same.html"), "same").unwrap();
+        fs::write(current.path().join("page.html"), "old").unwrap();
+        fs::write(proposed.path().join("page.html"), "new!").unwrap();
+        fs::write(current.path().join("gone.html"), "gone").unwrap();
+        fs::create_dir(proposed.path().join("blog")).unwrap();
+        fs::write(proposed.path().join("blog/post.html"), "post")
+            .unwrap();
+
+        let preview =
+            BuildPreview::compare(current.path(), proposed.path())
+                .unwrap();
+
+        let summary: Vec<_> = preview
+            .changes
+            .iter()
+            .map(|c| (c.path.to_str().unwrap(), c.kind, c.size_delta()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("blog/post.html", ChangeKind::Created, 4),
+                ("gone.html", ChangeKind::Deleted, -4),
+                ("page.html", ChangeKind::Updated, 1),
+            ]
+        );
+        assert_eq!(preview.size_delta(), 1);
+        assert!(preview
+            .to_string()
+            .ends_with("1 created, 1 updated, 1 deleted (+1 bytes)\n"));
+    }
+
+    #[test]
+    fn test_same_size_update_and_missing_output() {
+        let current = TempDir::new().unwrap();
+        let proposed = TempDir::new().unwrap();
+        fs::write(current.path().join("a.txt"), "abc").unwrap();
+        fs::write(proposed.path().join("a.txt"), "xyz").unwrap();
+
+        let preview =
+            BuildPreview::compare(current.path(), proposed.path())
+                .unwrap();
+        assert_eq!(preview.count(ChangeKind::Updated), 1);
+
+        let preview = BuildPreview::compare(
+            &current.path().join("missing"),
+            proposed.path(),
+        )
+        .unwrap();
+        assert_eq!(preview.count(ChangeKind::Created), 1);
+    }
+}
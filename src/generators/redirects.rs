@@ -0,0 +1,726 @@
+//! # Redirect Map Generation
+//!
+//! This module collects redirect mappings (old URL to new URL) into a single
+//! [`RedirectMap`] that acts as the source of truth for every redirect a site
+//! emits. The map can be exported as machine-readable JSON or CSV so that
+//! external reverse proxies and CDNs can consume the same data as the
+//! generated site.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::generators::redirects::{RedirectFormat, RedirectMap};
+//!
+//! let mut map = RedirectMap::new();
+//! map.add("/old-post/", "/blog/new-post/", 301).unwrap();
+//!
+//! let json = map.render(RedirectFormat::Json).unwrap();
+//! assert!(json.contains("/blog/new-post/"));
+//! ```
+//!
+//! # Shortlinks
+//!
+//! A site's `[shortlinks]` table maps short vanity paths to full URLs.
+//! Each shortlink becomes a redirect in the map and an HTML stub at its
+//! path, so the link works on hosts without redirect rules:
+//!
+//! ```toml
+//! [shortlinks]
+//! "/go/discord" = "https://discord.gg/example"
+//! ```
+//!
+//! ```rust
+//! use nucleusflow::generators::redirects::RedirectMap;
+//! use std::collections::BTreeMap;
+//!
+//! let mut links = BTreeMap::new();
+//! links.insert(
+//!     "/go/discord".to_string(),
+//!     "https://discord.gg/example".to_string(),
+//! );
+//! let map = RedirectMap::from_shortlinks(&links).unwrap();
+//! assert_eq!(map.get("/go/discord").unwrap().status, 302);
+//! ```
+//!
+//! # Aliases
+//!
+//! A page's `aliases` frontmatter lists the old URLs it used to live
+//! at. Each alias becomes a permanent redirect to the page and an HTML
+//! stub at the old path:
+//!
+//! ```yaml
+//! ---
+//! title: New post
+//! aliases: [/old-url/, /2019/01/post.html]
+//! ---
+//! ```
+//!
+//! # Moved Sections
+//!
+//! A site's `[moved_sections]` table records whole sections that moved,
+//! so their old URLs need not be listed page by page. Every page under
+//! a section's new path gets a permanent redirect from the same path
+//! under the old one, unless a page still lives there or an alias
+//! already claims it:
+//!
+//! ```toml
+//! [moved_sections]
+//! "/blog/" = "/posts/"
+//! ```
+//!
+//! The same redirects can be written as a `_redirects` file, which
+//! Netlify and Cloudflare Pages turn into real HTTP redirects, with
+//! [`RedirectFormat::Netlify`].
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::site::Page;
+use crate::{ProcessingError, Result};
+
+/// Default HTTP status code used for redirects.
+pub const DEFAULT_REDIRECT_STATUS: u16 = 301;
+
+/// HTTP status code used for shortlinks, whose targets may change.
+pub const SHORTLINK_STATUS: u16 = 302;
+
+/// A single redirect from an old path to a new location.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Redirect {
+    /// The site-relative path being redirected (e.g. `/old-url/`)
+    pub from: String,
+
+    /// The destination path or absolute URL
+    pub to: String,
+
+    /// The HTTP status code (301, 302, 307 or 308)
+    #[serde(default = "default_status")]
+    pub status: u16,
+}
+
+/// Machine-readable formats a redirect map can be exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RedirectFormat {
+    /// A JSON array of `{ "from", "to", "status" }` objects
+    Json,
+    /// A CSV file with a `from,to,status` header row
+    Csv,
+    /// A Netlify or Cloudflare Pages `_redirects` file, one
+    /// `from to status` rule per line
+    Netlify,
+}
+
+impl RedirectFormat {
+    /// Returns the default file name for the format.
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            RedirectFormat::Json => "redirects.json",
+            RedirectFormat::Csv => "redirects.csv",
+            RedirectFormat::Netlify => "_redirects",
+        }
+    }
+}
+
+/// An ordered collection of redirects keyed by source path.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RedirectMap {
+    redirects: BTreeMap<String, Redirect>,
+}
+
+impl RedirectMap {
+    /// Creates an empty redirect map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a redirect map from a `[shortlinks]` table.
+    ///
+    /// # Arguments
+    /// * `links` - Target URLs keyed by short path.
+    ///
+    /// # Returns
+    /// * `Result<Self>` - The shortlinks as temporary redirects, or an
+    ///   error if a shortlink is invalid.
+    pub fn from_shortlinks(
+        links: &BTreeMap<String, String>,
+    ) -> Result<Self> {
+        let mut map = Self::new();
+        for (from, to) in links {
+            map.add(from.as_str(), to.as_str(), SHORTLINK_STATUS)?;
+            _ = stub_path(from)?;
+        }
+        Ok(map)
+    }
+
+    /// Creates a redirect map from the `aliases` frontmatter of pages.
+    ///
+    /// # Arguments
+    /// * `pages` - The pages whose old URLs redirect to them.
+    ///
+    /// # Returns
+    /// * `Result<Self>` - A permanent redirect from each alias to its
+    ///   page, or an error if an alias is invalid or claimed by two
+    ///   pages.
+    pub fn from_aliases<'a, I>(pages: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = &'a Page>,
+    {
+        let mut map = Self::new();
+        for page in pages {
+            for alias in page.aliases() {
+                let added = map
+                    .add(
+                        alias,
+                        page.url.as_str(),
+                        DEFAULT_REDIRECT_STATUS,
+                    )
+                    .and_then(|()| stub_path(alias));
+                if let Err(e) = added {
+                    return Err(ProcessingError::validation(
+                        format!(
+                            "Invalid alias of '{}': {}",
+                            page.source.display(),
+                            e
+                        ),
+                        None::<String>,
+                    ));
+                }
+            }
+        }
+        Ok(map)
+    }
+
+    /// Creates a redirect map from a `[moved_sections]` table.
+    ///
+    /// # Arguments
+    /// * `moves` - The new path of each moved section, keyed by its old
+    ///   path; both start and end with `/`.
+    /// * `pages` - Every page of the site.
+    ///
+    /// # Returns
+    /// * `Result<Self>` - A permanent redirect to each page under a new
+    ///   path from its old URL, or an error if a move is invalid.
+    pub fn from_moves<'a, I>(
+        moves: &BTreeMap<String, String>,
+        pages: I,
+    ) -> Result<Self>
+    where
+        I: IntoIterator<Item = &'a Page>,
+    {
+        for (from, to) in moves {
+            validate_move(from, to)?;
+        }
+        let pages: Vec<&Page> = pages.into_iter().collect();
+        let taken: BTreeSet<&str> = pages
+            .iter()
+            .flat_map(|page| {
+                page.aliases().into_iter().chain([page.url.as_str()])
+            })
+            .collect();
+
+        let mut map = Self::new();
+        for page in &pages {
+            for (from, to) in moves {
+                let rest = match page.url.strip_prefix(to.as_str()) {
+                    Some(rest) => rest,
+                    None => continue,
+                };
+                let old = format!("{}{}", from, rest);
+                if taken.contains(old.as_str()) {
+                    continue;
+                }
+                _ = stub_path(&old)?;
+                map.add(
+                    old,
+                    page.url.as_str(),
+                    DEFAULT_REDIRECT_STATUS,
+                )?;
+            }
+        }
+        Ok(map)
+    }
+
+    /// Adds every redirect of another map to this one.
+    ///
+    /// # Returns
+    /// * `Result<()>` - An error if a redirect conflicts with one
+    ///   already in the map.
+    pub fn extend(&mut self, other: &RedirectMap) -> Result<()> {
+        for redirect in other.iter() {
+            self.add(
+                redirect.from.as_str(),
+                redirect.to.as_str(),
+                redirect.status,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Adds a redirect to the map.
+    ///
+    /// # Arguments
+    /// * `from` - The site-relative source path, which must start with `/`.
+    /// * `to` - The destination path or absolute URL.
+    /// * `status` - The HTTP redirect status code.
+    ///
+    /// # Returns
+    /// * `Result<()>` - An error if the redirect is invalid or conflicts with
+    ///   an existing redirect for the same source path.
+    pub fn add<S1: Into<String>, S2: Into<String>>(
+        &mut self,
+        from: S1,
+        to: S2,
+        status: u16,
+    ) -> Result<()> {
+        let redirect = Redirect {
+            from: from.into(),
+            to: to.into(),
+            status,
+        };
+        validate_redirect(&redirect)?;
+
+        match self.redirects.get(&redirect.from) {
+            Some(existing) if existing != &redirect => {
+                Err(ProcessingError::validation(
+                    format!(
+                        "Conflicting redirects for '{}': '{}' and '{}'",
+                        redirect.from, existing.to, redirect.to
+                    ),
+                    None::<String>,
+                ))
+            }
+            Some(_) => Ok(()),
+            None => {
+                _ = self
+                    .redirects
+                    .insert(redirect.from.clone(), redirect);
+                Ok(())
+            }
+        }
+    }
+
+    /// Gets the redirect registered for a source path.
+    pub fn get(&self, from: &str) -> Option<&Redirect> {
+        self.redirects.get(from)
+    }
+
+    /// Returns an iterator over the redirects, ordered by source path.
+    pub fn iter(&self) -> impl Iterator<Item = &Redirect> {
+        self.redirects.values()
+    }
+
+    /// Returns the number of redirects in the map.
+    pub fn len(&self) -> usize {
+        self.redirects.len()
+    }
+
+    /// Returns `true` if the map contains no redirects.
+    pub fn is_empty(&self) -> bool {
+        self.redirects.is_empty()
+    }
+
+    /// Renders the redirect map in the given format.
+    pub fn render(&self, format: RedirectFormat) -> Result<String> {
+        match format {
+            RedirectFormat::Json => {
+                let redirects: Vec<&Redirect> = self.iter().collect();
+                Ok(serde_json::to_string_pretty(&redirects)?)
+            }
+            RedirectFormat::Csv => {
+                let mut csv = String::from("from,to,status\n");
+                for redirect in self.iter() {
+                    csv.push_str(&format!(
+                        "{},{},{}\n",
+                        escape_csv(&redirect.from),
+                        escape_csv(&redirect.to),
+                        redirect.status
+                    ));
+                }
+                Ok(csv)
+            }
+            RedirectFormat::Netlify => Ok(self
+                .iter()
+                .map(|redirect| {
+                    format!(
+                        "{} {} {}\n",
+                        redirect.from.replace(' ', "%20"),
+                        redirect.to.replace(' ', "%20"),
+                        redirect.status
+                    )
+                })
+                .collect()),
+        }
+    }
+
+    /// Returns the output-relative path of the HTML stub written for
+    /// each redirect, keyed by source path.
+    ///
+    /// A source ending in `.html` is written as that file; any other
+    /// source is written as `index.html` in a directory of that name.
+    pub fn stub_paths(&self) -> Result<BTreeMap<String, PathBuf>> {
+        self.iter()
+            .map(|redirect| {
+                Ok((redirect.from.clone(), stub_path(&redirect.from)?))
+            })
+            .collect()
+    }
+
+    /// Writes an HTML stub that forwards to its target for each
+    /// redirect.
+    ///
+    /// # Arguments
+    /// * `output_dir` - The directory to write the stubs under.
+    ///
+    /// # Returns
+    /// * `Result<Vec<PathBuf>>` - The paths of the stubs written.
+    pub fn write_stubs(
+        &self,
+        output_dir: &Path,
+    ) -> Result<Vec<PathBuf>> {
+        let mut written = Vec::with_capacity(self.len());
+        for redirect in self.iter() {
+            let path = output_dir.join(stub_path(&redirect.from)?);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| {
+                    ProcessingError::io_error(parent.to_path_buf(), e)
+                })?;
+            }
+            fs::write(&path, stub_html(&redirect.to)).map_err(|e| {
+                ProcessingError::io_error(path.clone(), e)
+            })?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+
+    /// Writes the redirect map to `output_dir` in each requested format.
+    ///
+    /// # Returns
+    /// * `Result<Vec<PathBuf>>` - The paths of the files written.
+    pub fn export(
+        &self,
+        output_dir: &Path,
+        formats: &[RedirectFormat],
+    ) -> Result<Vec<PathBuf>> {
+        fs::create_dir_all(output_dir).map_err(|e| {
+            ProcessingError::io_error(output_dir.to_path_buf(), e)
+        })?;
+
+        let mut written = Vec::with_capacity(formats.len());
+        for format in formats {
+            let path = output_dir.join(format.file_name());
+            fs::write(&path, self.render(*format)?).map_err(|e| {
+                ProcessingError::io_error(path.clone(), e)
+            })?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+}
+
+/// Validates a redirect's paths and status code.
+fn validate_redirect(redirect: &Redirect) -> Result<()> {
+    if !redirect.from.starts_with('/') {
+        return Err(ProcessingError::validation(
+            format!(
+                "Redirect source '{}' must start with '/'",
+                redirect.from
+            ),
+            None::<String>,
+        ));
+    }
+    if redirect.to.trim().is_empty() {
+        return Err(ProcessingError::validation(
+            format!("Redirect target for '{}' is empty", redirect.from),
+            None::<String>,
+        ));
+    }
+    if redirect.from == redirect.to {
+        return Err(ProcessingError::validation(
+            format!("Redirect '{}' points to itself", redirect.from),
+            None::<String>,
+        ));
+    }
+    if ![301, 302, 307, 308].contains(&redirect.status) {
+        return Err(ProcessingError::validation(
+            format!(
+                "Unsupported redirect status {} for '{}'",
+                redirect.status, redirect.from
+            ),
+            None::<String>,
+        ));
+    }
+    Ok(())
+}
+
+/// Validates a moved section's old and new paths.
+fn validate_move(from: &str, to: &str) -> Result<()> {
+    for path in [from, to] {
+        if !path.starts_with('/') || !path.ends_with('/') {
+            return Err(ProcessingError::validation(
+                format!(
+                    "Moved section '{}' must start and end with '/'",
+                    path
+                ),
+                None::<String>,
+            ));
+        }
+    }
+    if from == "/" || from == to {
+        return Err(ProcessingError::validation(
+            format!("Cannot move section '{}' to '{}'", from, to),
+            None::<String>,
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the output-relative path of the stub for a source path.
+fn stub_path(from: &str) -> Result<PathBuf> {
+    let relative = Path::new(from.trim_start_matches('/'));
+    let escapes = relative
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_)));
+    if escapes || relative.as_os_str().is_empty() {
+        return Err(ProcessingError::validation(
+            format!("Redirect source '{}' is not a page path", from),
+            None::<String>,
+        ));
+    }
+    if relative.extension().map_or(false, |ext| ext == "html") {
+        Ok(relative.to_path_buf())
+    } else {
+        Ok(relative.join("index.html"))
+    }
+}
+
+/// Returns the HTML of a stub forwarding to `to`.
+fn stub_html(to: &str) -> String {
+    let to = to
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+    format!(
+        "<!DOCTYPE html>\n\
+         <html lang=\"en\">\n\
+         <head>\n\
+         <meta charset=\"utf-8\">\n\
+         <meta name=\"robots\" content=\"noindex\">\n\
+         <meta http-equiv=\"refresh\" content=\"0; url={0}\">\n\
+         <link rel=\"canonical\" href=\"{0}\">\n\
+         <title>Redirecting</title>\n\
+         </head>\n\
+         <body>\n\
+         <p>Redirecting to <a href=\"{0}\">{0}</a>.</p>\n\
+         </body>\n\
+         </html>\n",
+        to
+    )
+}
+
+/// Escapes a CSV field, quoting it if necessary.
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// Helper function for serde defaults
+fn default_status() -> u16 {
+    DEFAULT_REDIRECT_STATUS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::site::tests::page;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_add_and_conflicts() {
+        let mut map = RedirectMap::new();
+        map.add("/a/", "/b/", 301).unwrap();
+        // Identical redirects are idempotent
+        map.add("/a/", "/b/", 301).unwrap();
+        assert_eq!(map.len(), 1);
+
+        assert!(map.add("/a/", "/c/", 301).is_err());
+        assert!(map.add("relative", "/c/", 301).is_err());
+        assert!(map.add("/x/", "/x/", 301).is_err());
+        assert!(map.add("/y/", "/z/", 200).is_err());
+    }
+
+    #[test]
+    fn test_render_json() {
+        let mut map = RedirectMap::new();
+        map.add("/old/", "https://example.com/new/", 308).unwrap();
+
+        let json = map.render(RedirectFormat::Json).unwrap();
+        let parsed: Vec<Redirect> =
+            serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed[0].to, "https://example.com/new/");
+        assert_eq!(parsed[0].status, 308);
+    }
+
+    #[test]
+    fn test_render_csv_escaping() {
+        let mut map = RedirectMap::new();
+        map.add("/b/", "/target,with,commas/", 301).unwrap();
+        map.add("/a/", "/plain/", 302).unwrap();
+
+        let csv = map.render(RedirectFormat::Csv).unwrap();
+        assert_eq!(
+            csv,
+            "from,to,status\n/a/,/plain/,302\n/b/,\"/target,with,commas/\",301\n"
+        );
+    }
+
+    #[test]
+    fn test_shortlink_stubs() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut links = BTreeMap::new();
+        _ = links.insert(
+            "/go/discord".to_string(),
+            "https://discord.gg/a?b=1&c=2".to_string(),
+        );
+        _ = links.insert(
+            "/chat.html".to_string(),
+            "/go/discord".to_string(),
+        );
+        let map = RedirectMap::from_shortlinks(&links).unwrap();
+        assert_eq!(
+            map.get("/chat.html").unwrap().status,
+            SHORTLINK_STATUS
+        );
+
+        let written = map.write_stubs(temp_dir.path()).unwrap();
+        assert_eq!(
+            written,
+            [
+                temp_dir.path().join("chat.html"),
+                temp_dir.path().join("go/discord/index.html"),
+            ]
+        );
+        let stub = fs::read_to_string(&written[1]).unwrap();
+        assert!(stub.contains("url=https://discord.gg/a?b=1&amp;c=2"));
+
+        for from in ["/", "/go/../../etc", "go"] {
+            let mut links = BTreeMap::new();
+            _ = links.insert(from.to_string(), "/x/".to_string());
+            assert!(
+                RedirectMap::from_shortlinks(&links).is_err(),
+                "{}",
+                from
+            );
+        }
+    }
+
+    #[test]
+    fn test_aliases() {
+        let page = |path: &str, aliases: serde_json::Value| {
+            page(path, serde_json::json!({ "aliases": aliases }))
+        };
+        let pages = [
+            page(
+                "new.html",
+                serde_json::json!(["/old/", "/2019/a b/"]),
+            ),
+            page("other.html", serde_json::json!("/older.html")),
+        ];
+        let mut map = RedirectMap::from_aliases(&pages).unwrap();
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.get("/old/").unwrap().to, "/new.html");
+        assert_eq!(
+            map.get("/older.html").unwrap().status,
+            DEFAULT_REDIRECT_STATUS
+        );
+
+        let mut links = BTreeMap::new();
+        _ = links.insert("/go/x".to_string(), "/x/".to_string());
+        map.extend(&RedirectMap::from_shortlinks(&links).unwrap())
+            .unwrap();
+        assert_eq!(
+            map.render(RedirectFormat::Netlify).unwrap(),
+            "/2019/a%20b/ /new.html 301\n\
+             /go/x /x/ 302\n\
+             /old/ /new.html 301\n\
+             /older.html /other.html 301\n"
+        );
+
+        let clash = [
+            page("a.html", serde_json::json!(["/old/"])),
+            page("b.html", serde_json::json!(["/old/"])),
+        ];
+        assert!(RedirectMap::from_aliases(&clash).is_err());
+        let escape = [page("a.html", serde_json::json!(["/../x"]))];
+        assert!(RedirectMap::from_aliases(&escape).is_err());
+    }
+
+    #[test]
+    fn test_moves() {
+        let page = |path: &str, aliases: serde_json::Value| {
+            page(path, serde_json::json!({ "aliases": aliases }))
+        };
+        let pages = [
+            page("posts/index.html", serde_json::json!([])),
+            page("posts/a.html", serde_json::json!([])),
+            page("posts/b.html", serde_json::json!(["/blog/b.html"])),
+            page("posts/c.html", serde_json::json!([])),
+            page("blog/c.html", serde_json::json!([])),
+            page("about.html", serde_json::json!([])),
+        ];
+        let mut moves = BTreeMap::new();
+        _ = moves.insert("/blog/".to_string(), "/posts/".to_string());
+
+        let map = RedirectMap::from_moves(&moves, &pages).unwrap();
+        let redirects: Vec<(&str, &str)> = map
+            .iter()
+            .map(|r| (r.from.as_str(), r.to.as_str()))
+            .collect();
+        assert_eq!(
+            redirects,
+            [("/blog/", "/posts/"), ("/blog/a.html", "/posts/a.html")]
+        );
+        assert_eq!(
+            map.get("/blog/").unwrap().status,
+            DEFAULT_REDIRECT_STATUS
+        );
+
+        for (from, to) in
+            [("/blog", "/posts/"), ("/", "/posts/"), ("/a/", "/a/")]
+        {
+            let mut moves = BTreeMap::new();
+            _ = moves.insert(from.to_string(), to.to_string());
+            assert!(
+                RedirectMap::from_moves(&moves, &pages).is_err(),
+                "{} -> {}",
+                from,
+                to
+            );
+        }
+    }
+
+    #[test]
+    fn test_export() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut map = RedirectMap::new();
+        map.add("/old/", "/new/", 301).unwrap();
+
+        let written = map
+            .export(
+                temp_dir.path(),
+                &[RedirectFormat::Json, RedirectFormat::Csv],
+            )
+            .unwrap();
+        assert_eq!(written.len(), 2);
+        assert!(temp_dir.path().join("redirects.json").exists());
+        assert!(temp_dir.path().join("redirects.csv").exists());
+    }
+}
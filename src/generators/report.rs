@@ -0,0 +1,348 @@
+//! # Build Report
+//!
+//! Writes a machine-readable `build-report.json` into the output
+//! directory once a build has finished. It lists every generated file
+//! with the source it came from, its size and SHA-256 hash, along with
+//! the build duration, cache hit counts and the site's shortlinks, so
+//! CI pipelines can verify artifacts and decide what to cache or upload
+//! without walking the output themselves.
+//!
+//! Text files, such as pages, stylesheets and scripts, also list their
+//! gzip and brotli sizes, and the totals count what a browser would
+//! download from a server compressing them, so performance budgets can
+//! be checked against transfer sizes rather than sizes on disk.
+//!
+//! ```json
+//! {
+//!   "generated_at": "2024-03-01T12:00:00+00:00",
+//!   "duration_ms": 412,
+//!   "total_bytes": 2048,
+//!   "total_gzip_bytes": 812,
+//!   "total_brotli_bytes": 694,
+//!   "cache": {
+//!     "content": { "hits": 11, "misses": 1 },
+//!     "template": { "hits": 12, "misses": 0 },
+//!     "asset": null
+//!   },
+//!   "shortlinks": [
+//!     {
+//!       "from": "/go/discord",
+//!       "to": "https://discord.gg/example",
+//!       "status": 302
+//!     }
+//!   ],
+//!   "files": [
+//!     {
+//!       "path": "index.html",
+//!       "source": "content/index.md",
+//!       "size": 2048,
+//!       "gzip_size": 812,
+//!       "brotli_size": 694,
+//!       "sha256": "3f2a1b9c..."
+//!     }
+//!   ]
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Serialize;
+use walkdir::WalkDir;
+
+use crate::core::cache::CacheMetrics;
+use crate::core::config::PrecompressConfig;
+use crate::generators::compress::{brotli_bytes, gzip_bytes};
+use crate::generators::fingerprint::content_hash;
+use crate::generators::redirects::{Redirect, RedirectMap};
+use crate::{ProcessingError, Result};
+
+/// The name of the report file written to the output directory.
+pub const REPORT_FILE_NAME: &str = "build-report.json";
+
+/// Extensions of pre-compressed twins, which share their original's
+/// source.
+const TWIN_EXTENSIONS: [&str; 2] = ["gz", "br"];
+
+/// A single generated file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReportEntry {
+    /// The file's path relative to the output directory, using `/`
+    pub path: String,
+
+    /// The content or static file it was generated from, if known
+    pub source: Option<PathBuf>,
+
+    /// The file size in bytes
+    pub size: u64,
+
+    /// The file size in bytes once gzip-compressed, for text files
+    pub gzip_size: Option<u64>,
+
+    /// The file size in bytes once brotli-compressed, for text files
+    pub brotli_size: Option<u64>,
+
+    /// The hex-encoded SHA-256 hash of the file contents
+    pub sha256: String,
+}
+
+/// A summary of a finished build.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BuildReport {
+    /// When the build finished, as an RFC 3339 timestamp
+    pub generated_at: String,
+
+    /// How long the build took in milliseconds
+    pub duration_ms: u64,
+
+    /// The combined size of all generated files in bytes
+    pub total_bytes: u64,
+
+    /// The combined download size of the generated files served with
+    /// gzip, counting files that are not compressed at full size and
+    /// leaving out pre-compressed twins
+    pub total_gzip_bytes: u64,
+
+    /// The combined download size of the generated files served with
+    /// brotli, counted like `total_gzip_bytes`
+    pub total_brotli_bytes: u64,
+
+    /// The cache hits and misses of the build
+    pub cache: CacheMetrics,
+
+    /// The shortlinks the build wrote stubs for, ordered by path
+    pub shortlinks: Vec<Redirect>,
+
+    /// The generated files, ordered by path
+    pub files: Vec<ReportEntry>,
+}
+
+impl BuildReport {
+    /// Builds a report from the files in an output directory.
+    ///
+    /// A previous report in the directory is not listed.
+    ///
+    /// # Arguments
+    /// * `output_dir` - The directory the build was written to.
+    /// * `sources` - Source paths keyed by output-relative path.
+    ///   Pre-compressed twins inherit their original's source.
+    /// * `duration` - How long the build took.
+    /// * `generated_at` - The build's finishing timestamp.
+    ///
+    /// # Returns
+    /// * `Result<Self>` - The report, or an error if the output cannot
+    ///   be read.
+    pub fn collect(
+        output_dir: &Path,
+        sources: &BTreeMap<PathBuf, PathBuf>,
+        duration: Duration,
+        generated_at: String,
+    ) -> Result<Self> {
+        let text_extensions = PrecompressConfig::default().extensions;
+        let mut files = Vec::new();
+        for entry in WalkDir::new(output_dir).sort_by_file_name() {
+            let entry = entry.map_err(|e| {
+                ProcessingError::content_processing(
+                    "Failed to read output directory",
+                    Some(Box::new(e)),
+                )
+            })?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let relative = match entry.path().strip_prefix(output_dir) {
+                Ok(relative) => relative,
+                Err(_) => continue,
+            };
+            if relative == Path::new(REPORT_FILE_NAME) {
+                continue;
+            }
+
+            let bytes = fs::read(entry.path()).map_err(|e| {
+                ProcessingError::io_error(entry.path().to_path_buf(), e)
+            })?;
+            let is_text = has_extension(relative, &text_extensions);
+            let compressed_size =
+                |compress: fn(&[u8]) -> std::io::Result<Vec<u8>>| {
+                    if !is_text {
+                        return Ok(None);
+                    }
+                    let compressed = compress(&bytes).map_err(|e| {
+                        ProcessingError::io_error(
+                            entry.path().to_path_buf(),
+                            e,
+                        )
+                    })?;
+                    Ok::<_, ProcessingError>(Some(
+                        compressed.len() as u64
+                    ))
+                };
+            files.push(ReportEntry {
+                path: report_path(relative),
+                source: source_of(relative, sources),
+                size: bytes.len() as u64,
+                gzip_size: compressed_size(gzip_bytes)?,
+                brotli_size: compressed_size(brotli_bytes)?,
+                sha256: content_hash(&bytes),
+            });
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let transfer_bytes =
+            |compressed: fn(&ReportEntry) -> Option<u64>| {
+                files
+                    .iter()
+                    .filter(|file| {
+                        !has_extension(
+                            Path::new(&file.path),
+                            &TWIN_EXTENSIONS,
+                        )
+                    })
+                    .map(|file| compressed(file).unwrap_or(file.size))
+                    .sum()
+            };
+        Ok(Self {
+            generated_at,
+            duration_ms: duration.as_millis() as u64,
+            total_bytes: files.iter().map(|file| file.size).sum(),
+            total_gzip_bytes: transfer_bytes(|file| file.gzip_size),
+            total_brotli_bytes: transfer_bytes(|file| file.brotli_size),
+            cache: CacheMetrics::default(),
+            shortlinks: Vec::new(),
+            files,
+        })
+    }
+
+    /// Sets the cache counts of the build.
+    pub fn with_cache(mut self, cache: CacheMetrics) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Sets the shortlinks of the build.
+    pub fn with_shortlinks(mut self, shortlinks: &RedirectMap) -> Self {
+        self.shortlinks = shortlinks.iter().cloned().collect();
+        self
+    }
+
+    /// Writes the report to `build-report.json` in `output_dir`.
+    ///
+    /// # Returns
+    /// * `Result<PathBuf>` - The path of the report, or an error if it
+    ///   cannot be written.
+    pub fn write(&self, output_dir: &Path) -> Result<PathBuf> {
+        let path = output_dir.join(REPORT_FILE_NAME);
+        let json = serde_json::to_vec_pretty(self).map_err(|e| {
+            ProcessingError::content_processing(
+                "Failed to serialise build report",
+                Some(Box::new(e)),
+            )
+        })?;
+        fs::write(&path, json)
+            .map_err(|e| ProcessingError::io_error(path.clone(), e))?;
+        Ok(path)
+    }
+}
+
+/// Returns the source of an output file, falling back to the original's
+/// source for pre-compressed twins.
+fn source_of(
+    relative: &Path,
+    sources: &BTreeMap<PathBuf, PathBuf>,
+) -> Option<PathBuf> {
+    if let Some(source) = sources.get(relative) {
+        return Some(source.clone());
+    }
+    if has_extension(relative, &TWIN_EXTENSIONS) {
+        sources.get(&relative.with_extension("")).cloned()
+    } else {
+        None
+    }
+}
+
+/// Returns `true` if a path's extension is one of `extensions`,
+/// ignoring case.
+fn has_extension<S: AsRef<str>>(path: &Path, extensions: &[S]) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map_or(false, |ext| {
+            extensions.iter().any(|allowed| {
+                allowed.as_ref().eq_ignore_ascii_case(ext)
+            })
+        })
+}
+
+/// Returns a relative path joined with `/` on every platform.
+fn report_path(relative: &Path) -> String {
+    relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_collect_and_write() {
+        let temp_dir = TempDir::new().unwrap();
+        let output = temp_dir.path();
+        fs::create_dir(output.join("blog")).unwrap();
+        fs::write(output.join("blog/post.html"), "post").unwrap();
+        fs::write(output.join("blog/post.html.gz"), "gz").unwrap();
+        fs::write(output.join("robots.txt"), "robots").unwrap();
+        fs::write(output.join(REPORT_FILE_NAME), "{}").unwrap();
+
+        let mut sources = BTreeMap::new();
+        _ = sources.insert(
+            PathBuf::from("blog/post.html"),
+            PathBuf::from("content/blog/post.md"),
+        );
+
+        let report = BuildReport::collect(
+            output,
+            &sources,
+            Duration::from_millis(1500),
+            "2024-03-01T12:00:00+00:00".to_string(),
+        )
+        .unwrap();
+
+        let files: Vec<_> = report
+            .files
+            .iter()
+            .map(|f| (f.path.as_str(), f.source.clone(), f.size))
+            .collect();
+        let post = Some(PathBuf::from("content/blog/post.md"));
+        assert_eq!(
+            files,
+            [
+                ("blog/post.html", post.clone(), 4),
+                ("blog/post.html.gz", post, 2),
+                ("robots.txt", None, 6),
+            ]
+        );
+        assert_eq!(report.files[0].sha256, content_hash(b"post"));
+        assert_eq!(report.duration_ms, 1500);
+        assert_eq!(report.total_bytes, 12);
+        assert!(report.files[0].gzip_size.is_some());
+        assert_eq!(report.files[1].gzip_size, None);
+        assert_eq!(report.files[2].gzip_size, None);
+        assert_eq!(
+            report.total_gzip_bytes,
+            report.files[0].gzip_size.unwrap() + 6
+        );
+
+        let path = report.write(output).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_slice(&fs::read(path).unwrap()).unwrap();
+        assert_eq!(json["files"][2]["path"], "robots.txt");
+        assert_eq!(json["files"][2]["source"], serde_json::Value::Null);
+        assert_eq!(json["duration_ms"], 1500);
+        assert!(json["files"][0]["brotli_size"].is_u64());
+    }
+}
@@ -0,0 +1,304 @@
+//! # Page Screenshots
+//!
+//! Captures configured pages of a built site as PNG screenshots with a
+//! headless Chrome or Chromium, so visual regressions can be caught
+//! between builds.
+//!
+//! Each page is opened from the output directory as a `file://` URL in
+//! a window of the configured size. Its screenshot is written to the
+//! screenshot directory, named after its URL: `/` becomes `index.png`
+//! and `/blog/post.html` becomes `blog-post.png`. A screenshot left by
+//! the previous build is kept as `<name>.previous.png`, and the share of
+//! pixels that differ between the two is reported.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use nucleusflow::core::config::ScreenshotConfig;
+//! use nucleusflow::generators::screenshots::Screenshots;
+//! use std::path::Path;
+//!
+//! let config = ScreenshotConfig {
+//!     pages: vec!["/".to_string()],
+//!     ..ScreenshotConfig::default()
+//! };
+//! for shot in Screenshots::new(&config).capture(Path::new("public")).unwrap() {
+//!     if let Some(changed) = shot.changed_pixels {
+//!         println!("{}: {:.2}% changed", shot.url, changed * 100.0);
+//!     }
+//! }
+//! ```
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::core::config::ScreenshotConfig;
+use crate::{ProcessingError, Result};
+
+/// A screenshot captured by [`Screenshots::capture`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Screenshot {
+    /// The URL of the page captured
+    pub url: String,
+    /// The PNG written
+    pub path: PathBuf,
+    /// The share of pixels that differ from the previous build's
+    /// screenshot, from 0 to 1, or `None` if there was none
+    pub changed_pixels: Option<f64>,
+}
+
+/// Captures screenshots of a site's pages with a headless browser.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Screenshots {
+    config: ScreenshotConfig,
+}
+
+impl Screenshots {
+    /// Creates a capture of the pages a configuration lists.
+    pub fn new(config: &ScreenshotConfig) -> Self {
+        Self {
+            config: config.clone(),
+        }
+    }
+
+    /// Captures every configured page of a built site.
+    ///
+    /// # Arguments
+    /// * `output_dir` - The directory the site was built to.
+    ///
+    /// # Returns
+    /// * `Result<Vec<Screenshot>>` - The screenshots, in the configured
+    ///   order, or an error if a page was not built, the browser fails
+    ///   or a screenshot cannot be read.
+    pub fn capture(
+        &self,
+        output_dir: &Path,
+    ) -> Result<Vec<Screenshot>> {
+        let dir = &self.config.dir;
+        fs::create_dir_all(dir)
+            .map_err(|e| ProcessingError::io_error(dir.clone(), e))?;
+
+        let mut screenshots =
+            Vec::with_capacity(self.config.pages.len());
+        for url in &self.config.pages {
+            let page = output_dir.join(page_file(url));
+            let page = page.canonicalize().map_err(|_| {
+                ProcessingError::validation(
+                    format!(
+                        "Screenshot page '{}' was not built at '{}'",
+                        url,
+                        page.display()
+                    ),
+                    None::<String>,
+                )
+            })?;
+            let name = screenshot_name(url);
+            let path = dir.join(format!("{}.png", name));
+            let previous = dir.join(format!("{}.previous.png", name));
+            let had_previous = path.exists();
+            if had_previous {
+                fs::rename(&path, &previous).map_err(|e| {
+                    ProcessingError::io_error(path.clone(), e)
+                })?;
+            }
+
+            self.run_browser(url, &page, &path)?;
+            let changed_pixels = if had_previous {
+                Some(changed_pixels(&previous, &path)?)
+            } else {
+                None
+            };
+            screenshots.push(Screenshot {
+                url: url.clone(),
+                path,
+                changed_pixels,
+            });
+        }
+        Ok(screenshots)
+    }
+
+    /// Runs the browser to capture a page to `path`.
+    fn run_browser(
+        &self,
+        url: &str,
+        page: &Path,
+        path: &Path,
+    ) -> Result<()> {
+        let failed = |details: String| {
+            ProcessingError::content_processing(
+                format!("Screenshot of '{}' {}", url, details),
+                None,
+            )
+        };
+
+        let output = Command::new(&self.config.browser)
+            .args(["--headless", "--disable-gpu", "--hide-scrollbars"])
+            .arg(format!(
+                "--window-size={},{}",
+                self.config.width, self.config.height
+            ))
+            .arg(format!("--screenshot={}", path.display()))
+            .arg(format!("file://{}", page.display()))
+            .output()
+            .map_err(|e| {
+                failed(format!(
+                    "could not run '{}': {}",
+                    self.config.browser, e
+                ))
+            })?;
+        if !output.status.success() || !path.exists() {
+            return Err(failed(format!(
+                "failed with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Returns the output-relative file of a site-relative URL.
+fn page_file(url: &str) -> PathBuf {
+    let path = url.trim_start_matches('/');
+    if path.is_empty() || path.ends_with('/') {
+        Path::new(path).join("index.html")
+    } else {
+        PathBuf::from(path)
+    }
+}
+
+/// Returns the file name of a page's screenshot, without extension.
+fn screenshot_name(url: &str) -> String {
+    let path = url.trim_start_matches('/');
+    let path = path.strip_suffix("index.html").unwrap_or(path);
+    let path = path.strip_suffix(".html").unwrap_or(path);
+    let name = path.trim_matches('/').replace('/', "-");
+    if name.is_empty() {
+        "index".to_string()
+    } else {
+        name
+    }
+}
+
+/// Returns the share of pixels that differ between two images, which
+/// is 1 when their sizes differ.
+fn changed_pixels(previous: &Path, current: &Path) -> Result<f64> {
+    let open = |path: &Path| {
+        image::open(path)
+            .map(|image| image.to_rgba8())
+            .map_err(|e| {
+                ProcessingError::content_processing(
+                    format!(
+                        "Failed to read screenshot '{}'",
+                        path.display()
+                    ),
+                    Some(Box::new(e)),
+                )
+            })
+    };
+    let (previous, current) = (open(previous)?, open(current)?);
+    if previous.dimensions() != current.dimensions() {
+        return Ok(1.0);
+    }
+    let total = previous.pixels().len();
+    if total == 0 {
+        return Ok(0.0);
+    }
+    let changed = previous
+        .pixels()
+        .zip(current.pixels())
+        .filter(|(a, b)| a != b)
+        .count();
+    Ok(changed as f64 / total as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgba, RgbaImage};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_names() {
+        assert_eq!(page_file("/"), PathBuf::from("index.html"));
+        assert_eq!(
+            page_file("/blog/"),
+            PathBuf::from("blog/index.html")
+        );
+        assert_eq!(
+            page_file("/about.html"),
+            PathBuf::from("about.html")
+        );
+        assert_eq!(screenshot_name("/"), "index");
+        assert_eq!(screenshot_name("/index.html"), "index");
+        assert_eq!(screenshot_name("/blog/"), "blog");
+        assert_eq!(screenshot_name("/blog/post.html"), "blog-post");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_capture() -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let output = temp_dir.path().join("public");
+        fs::create_dir_all(&output)?;
+        fs::write(output.join("index.html"), "<p>home</p>")?;
+
+        // A stand-in browser copying `frame.png` to the screenshot path
+        let frame = temp_dir.path().join("frame.png");
+        let browser = temp_dir.path().join("browser.sh");
+        fs::write(
+            &browser,
+            format!(
+                "#!/bin/sh\nfor arg; do case $arg in --screenshot=*) \
+                 cp '{}' \"${{arg#--screenshot=}}\";; esac; done\n",
+                frame.display()
+            ),
+        )?;
+        fs::set_permissions(
+            &browser,
+            fs::Permissions::from_mode(0o755),
+        )?;
+
+        let config = ScreenshotConfig {
+            pages: vec!["/".to_string()],
+            browser: browser.display().to_string(),
+            dir: temp_dir.path().join("screenshots"),
+            ..ScreenshotConfig::default()
+        };
+        let screenshots = Screenshots::new(&config);
+        let write_frame = |changed: u32| {
+            let mut image = RgbaImage::from_pixel(2, 2, Rgba([0; 4]));
+            for x in 0..changed {
+                image.put_pixel(x, 0, Rgba([255; 4]));
+            }
+            image.save(&frame).unwrap();
+        };
+
+        write_frame(0);
+        let first = screenshots.capture(&output)?;
+        assert_eq!(first[0].path, config.dir.join("index.png"));
+        assert_eq!(first[0].changed_pixels, None);
+
+        write_frame(1);
+        let second = screenshots.capture(&output)?;
+        assert_eq!(second[0].changed_pixels, Some(0.25));
+        assert!(config.dir.join("index.previous.png").exists());
+
+        let missing = ScreenshotConfig {
+            pages: vec!["/gone.html".to_string()],
+            ..config.clone()
+        };
+        assert!(Screenshots::new(&missing).capture(&output).is_err());
+        let no_browser = ScreenshotConfig {
+            browser: "nucleusflow-missing-browser".to_string(),
+            ..config
+        };
+        assert!(Screenshots::new(&no_browser)
+            .capture(&output)
+            .is_err());
+        Ok(())
+    }
+}
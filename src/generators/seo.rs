@@ -0,0 +1,448 @@
+//! # Social Meta Tags
+//!
+//! Adds OpenGraph (`og:*`) and Twitter card (`twitter:*`) tags to each
+//! page's `<head>`, so links to the site get a title, description and
+//! image when shared. The tags are derived from the page's frontmatter
+//! and the site's [`SeoConfig`]:
+//!
+//! | Tag | Source |
+//! |-----|--------|
+//! | `og:title`, `twitter:title` | `title` |
+//! | `og:description`, `twitter:description` | `description`, `summary`, or the page's extracted summary |
+//! | `og:image`, `twitter:image` | `image`, or the site's `default_image` |
+//! | `og:url` | the page's URL under the site's `base_url` |
+//! | `og:type` | `og_type`, or `article` for pages with a `date`, else `website` |
+//! | `og:site_name` | the site's `site_name` |
+//! | `twitter:card` | the site's `twitter_card` with an image, else `summary` |
+//! | `twitter:site` | the site's `twitter_site` |
+//!
+//! Root-relative image URLs are made absolute with the site's
+//! `base_url`. A tag the page's template already sets is left alone, and
+//! pages without a `</head>` are not changed.
+//!
+//! [`CanonicalLink`] adds a `<link rel="canonical">` to each page in the
+//! same way, naming the page's own URL under the site's base URL. A
+//! page's `canonical` frontmatter overrides it with another URL, which
+//! may be root-relative, or leaves the link out when `false`. Utility
+//! pages get no canonical link.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::core::config::SeoConfig;
+//! use nucleusflow::core::traits::PostProcessor;
+//! use nucleusflow::generators::seo::SocialMetaTags;
+//! use nucleusflow::site::Page;
+//! use serde_json::json;
+//!
+//! let tags = SocialMetaTags::new(SeoConfig {
+//!     base_url: Some("https://example.com".to_string()),
+//!     ..SeoConfig::default()
+//! });
+//! let page = Page::new(
+//!     "content/about.md",
+//!     "about.html",
+//!     json!({ "title": "About" })
+//!         .as_object()
+//!         .cloned()
+//!         .unwrap(),
+//! );
+//! let html = tags.process("<head></head>", &page).unwrap();
+//! assert!(html.contains(r#"<meta property="og:title" content="About">"#));
+//! assert!(html.contains(
+//!     r#"<meta property="og:url" content="https://example.com/about.html">"#
+//! ));
+//! ```
+
+use serde_json::Value as JsonValue;
+
+use crate::core::config::SeoConfig;
+use crate::core::error::Result;
+use crate::core::traits::PostProcessor;
+use crate::extract::plain_text;
+use crate::site::Page;
+
+/// The card type used for pages with an image when the site does not
+/// set one.
+const DEFAULT_TWITTER_CARD: &str = "summary_large_image";
+
+/// Adds OpenGraph and Twitter card tags to every page.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SocialMetaTags {
+    config: SeoConfig,
+}
+
+impl SocialMetaTags {
+    /// Creates a post-processor using the site's SEO settings.
+    pub fn new(config: SeoConfig) -> Self {
+        Self { config }
+    }
+
+    /// Returns the tags for a page, as `(attribute, key, value)` in the
+    /// order they are written.
+    ///
+    /// # Arguments
+    /// * `page` - The page whose frontmatter the tags describe.
+    ///
+    /// # Returns
+    /// * `Vec<(&str, &str, String)>` - `property` for OpenGraph tags and
+    ///   `name` for Twitter tags, the tag's key, and its unescaped value.
+    pub fn tags(
+        &self,
+        page: &Page,
+    ) -> Vec<(&'static str, &'static str, String)> {
+        let config = &self.config;
+        let field = |key: &str| {
+            page.frontmatter
+                .get(key)
+                .and_then(JsonValue::as_str)
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(String::from)
+        };
+        let title = field("title");
+        let description = field("description")
+            .or_else(|| field("summary"))
+            .or_else(|| page.summary.as_deref().map(plain_text))
+            .filter(|description| !description.is_empty());
+        let image = field("image")
+            .or_else(|| config.default_image.clone())
+            .map(|image| self.absolute_url(&image));
+        let og_type = field("og_type").unwrap_or_else(|| {
+            let kind = if page.frontmatter.contains_key("date") {
+                "article"
+            } else {
+                "website"
+            };
+            kind.to_string()
+        });
+        let card = if image.is_some() {
+            config
+                .twitter_card
+                .clone()
+                .unwrap_or_else(|| DEFAULT_TWITTER_CARD.to_string())
+        } else {
+            "summary".to_string()
+        };
+
+        let tags = [
+            ("property", "og:title", title.clone()),
+            ("property", "og:description", description.clone()),
+            ("property", "og:image", image.clone()),
+            (
+                "property",
+                "og:url",
+                config
+                    .base_url
+                    .as_ref()
+                    .map(|_| self.absolute_url(&page.url)),
+            ),
+            ("property", "og:type", Some(og_type)),
+            ("property", "og:site_name", config.site_name.clone()),
+            ("name", "twitter:card", Some(card)),
+            ("name", "twitter:site", config.twitter_site.clone()),
+            ("name", "twitter:title", title),
+            ("name", "twitter:description", description),
+            ("name", "twitter:image", image),
+        ];
+        tags.into_iter()
+            .filter_map(|(attribute, key, value)| {
+                value.map(|value| (attribute, key, value))
+            })
+            .collect()
+    }
+
+    /// Makes a root-relative URL absolute with the site's base URL.
+    fn absolute_url(&self, url: &str) -> String {
+        absolute_url(self.config.base_url.as_deref(), url)
+    }
+}
+
+/// Adds a canonical link to every page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CanonicalLink {
+    base_url: String,
+}
+
+impl CanonicalLink {
+    /// Creates a post-processor for a site published at `base_url`.
+    pub fn new<S: Into<String>>(base_url: S) -> Self {
+        Self {
+            base_url: base_url.into(),
+        }
+    }
+
+    /// Returns a page's canonical URL: its `canonical` frontmatter, or
+    /// its own URL, made absolute.
+    ///
+    /// # Arguments
+    /// * `page` - The page to link.
+    ///
+    /// # Returns
+    /// * `Option<String>` - The URL, or `None` for utility pages and
+    ///   pages setting `canonical: false`.
+    pub fn href(&self, page: &Page) -> Option<String> {
+        let url = match page.frontmatter.get("canonical") {
+            Some(JsonValue::String(url)) if !url.trim().is_empty() => {
+                url.trim()
+            }
+            Some(JsonValue::Bool(false)) => return None,
+            _ if page.is_utility() => return None,
+            _ => page.url.as_str(),
+        };
+        Some(absolute_url(Some(&self.base_url), url))
+    }
+
+    /// Returns `true` unless a page names another page as canonical,
+    /// in which case it is a duplicate that sitemaps leave out.
+    pub fn is_canonical(&self, page: &Page) -> bool {
+        match page.frontmatter.get("canonical") {
+            Some(JsonValue::String(_)) => {
+                self.href(page)
+                    == Some(absolute_url(
+                        Some(&self.base_url),
+                        &page.url,
+                    ))
+            }
+            _ => true,
+        }
+    }
+}
+
+impl PostProcessor for CanonicalLink {
+    fn name(&self) -> &str {
+        "canonical-link"
+    }
+
+    fn process(&self, html: &str, page: &Page) -> Result<String> {
+        let lower = html.to_ascii_lowercase();
+        let index = match lower.rfind("</head>") {
+            Some(index) => index,
+            None => return Ok(html.to_string()),
+        };
+        let href = match self.href(page) {
+            Some(href)
+                if !lower[..index].contains("rel=\"canonical\"") =>
+            {
+                href
+            }
+            _ => return Ok(html.to_string()),
+        };
+
+        let link = format!(
+            r#"<link rel="canonical" href="{}">"#,
+            handlebars::html_escape(&href)
+        );
+        let mut output = String::with_capacity(html.len() + link.len());
+        output.push_str(&html[..index]);
+        output.push_str(&link);
+        output.push_str(&html[index..]);
+        Ok(output)
+    }
+}
+
+/// Makes a root-relative URL absolute with a base URL, leaving other
+/// URLs, or all URLs without a base, as they are.
+pub(crate) fn absolute_url(
+    base_url: Option<&str>,
+    url: &str,
+) -> String {
+    match base_url {
+        Some(base)
+            if url.starts_with('/') && !url.starts_with("//") =>
+        {
+            format!("{}{}", base.trim_end_matches('/'), url)
+        }
+        _ => url.to_string(),
+    }
+}
+
+impl PostProcessor for SocialMetaTags {
+    fn name(&self) -> &str {
+        "social-meta"
+    }
+
+    fn process(&self, html: &str, page: &Page) -> Result<String> {
+        let lower = html.to_ascii_lowercase();
+        let index = match lower.rfind("</head>") {
+            Some(index) => index,
+            None => return Ok(html.to_string()),
+        };
+
+        let mut tags = String::new();
+        for (attribute, key, value) in self.tags(page) {
+            let existing = format!("{}=\"{}\"", attribute, key);
+            if lower[..index].contains(&existing) {
+                continue;
+            }
+            tags.push_str(&format!(
+                r#"<meta {}="{}" content="{}">"#,
+                attribute,
+                key,
+                handlebars::html_escape(&value)
+            ));
+        }
+
+        let mut output = String::with_capacity(html.len() + tags.len());
+        output.push_str(&html[..index]);
+        output.push_str(&tags);
+        output.push_str(&html[index..]);
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::site::tests::page;
+    use serde_json::json;
+
+    fn config() -> SeoConfig {
+        SeoConfig {
+            base_url: Some("https://example.com/".to_string()),
+            site_name: Some("Example".to_string()),
+            default_image: Some("/card.png".to_string()),
+            twitter_site: Some("@example".to_string()),
+            twitter_card: None,
+            article_type: None,
+            breadcrumbs: None,
+        }
+    }
+
+    #[test]
+    fn test_tags() {
+        let post = page(
+            "blog/post.html",
+            json!({
+                "title": "Hello & welcome",
+                "summary": "A post",
+                "date": "2024-03-01",
+            }),
+        );
+        let tags = SocialMetaTags::new(config()).tags(&post);
+        let value = |key: &str| {
+            tags.iter()
+                .find(|(_, k, _)| *k == key)
+                .map(|(_, _, v)| v.as_str())
+        };
+        assert_eq!(value("og:title"), Some("Hello & welcome"));
+        assert_eq!(value("og:description"), Some("A post"));
+        assert_eq!(
+            value("og:image"),
+            Some("https://example.com/card.png")
+        );
+        assert_eq!(
+            value("og:url"),
+            Some("https://example.com/blog/post.html")
+        );
+        assert_eq!(value("og:type"), Some("article"));
+        assert_eq!(value("twitter:card"), Some("summary_large_image"));
+        assert_eq!(value("twitter:site"), Some("@example"));
+        assert_eq!(tags.len(), 11);
+
+        let bare = SocialMetaTags::default().tags(&page(
+            "blog/post.html",
+            json!({
+                "image": "https://cdn.example.com/a.png",
+            }),
+        ));
+        assert_eq!(
+            bare,
+            [
+                (
+                    "property",
+                    "og:image",
+                    "https://cdn.example.com/a.png".to_string()
+                ),
+                ("property", "og:type", "website".to_string()),
+                (
+                    "name",
+                    "twitter:card",
+                    "summary_large_image".to_string()
+                ),
+                (
+                    "name",
+                    "twitter:image",
+                    "https://cdn.example.com/a.png".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_process() {
+        let post = page(
+            "blog/post.html",
+            json!({ "title": "A \"quoted\" title" }),
+        );
+        let tags = SocialMetaTags::new(SeoConfig::default());
+        let html = tags
+            .process(
+                r#"<HEAD><meta property="og:type" content="book"></HEAD>"#,
+                &post,
+            )
+            .unwrap();
+        assert!(html.contains(
+            r#"<meta property="og:title" content="A &quot;quoted&quot; title">"#
+        ));
+        assert_eq!(html.matches("og:type").count(), 1);
+        assert!(html.ends_with("</HEAD>"));
+
+        assert_eq!(
+            tags.process("<p>x</p>", &post).unwrap(),
+            "<p>x</p>"
+        );
+    }
+
+    #[test]
+    fn test_canonical_link() {
+        let canonical = CanonicalLink::new("https://example.com/");
+        let post = page("blog/post.html", json!({}));
+        assert_eq!(
+            canonical.href(&post).as_deref(),
+            Some("https://example.com/blog/post.html")
+        );
+        assert!(canonical.is_canonical(&post));
+        assert_eq!(
+            canonical.process("<head></head>", &post).unwrap(),
+            r#"<head><link rel="canonical" href="https://example.com/blog/post.html"></head>"#
+        );
+
+        let moved = page(
+            "blog/post.html",
+            json!({ "canonical": "/blog/new.html" }),
+        );
+        assert_eq!(
+            canonical.href(&moved).as_deref(),
+            Some("https://example.com/blog/new.html")
+        );
+        assert!(!canonical.is_canonical(&moved));
+        let own = page(
+            "blog/post.html",
+            json!({ "canonical": "/blog/post.html" }),
+        );
+        assert!(canonical.is_canonical(&own));
+
+        let syndicated = page(
+            "blog/post.html",
+            json!({ "canonical": "https://dev.to/a" }),
+        );
+        assert_eq!(
+            canonical.href(&syndicated).as_deref(),
+            Some("https://dev.to/a")
+        );
+        let unlinked =
+            page("blog/post.html", json!({ "canonical": false }));
+        assert_eq!(canonical.href(&unlinked), None);
+        assert!(canonical.is_canonical(&unlinked));
+
+        let existing =
+            r#"<head><link rel="canonical" href="/x"></head>"#;
+        assert_eq!(
+            canonical.process(existing, &post).unwrap(),
+            existing
+        );
+        let not_found = page("404.html", json!({}));
+        assert_eq!(canonical.href(&not_found), None);
+    }
+}
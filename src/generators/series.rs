@@ -0,0 +1,306 @@
+//! # Series
+//!
+//! Groups posts into article series by their `series` frontmatter, such
+//! as `series: Rust in Production`, shows each post where it sits in
+//! its series, and generates an index page per series, such as
+//! `/series/rust-in-production/`. Where index pages are written and the
+//! template they are rendered with are set by [`SeriesConfig`].
+//!
+//! A series' posts are ordered by [`Page::listing_order`]: by `weight`,
+//! then by `date`, oldest first. Templates see a post's series as
+//! `page.series`, with:
+//!
+//! - `name`, the series' name, and `url`, its index page's URL,
+//! - `position`, the post's place in the series counting from 1, and
+//!   `total`, the number of posts, and
+//! - `pages`, the `url`, `title` and `date` of each post in order, with
+//!   the post itself marked `current`.
+//!
+//! Each index page is rendered like a content page, with the series'
+//! name as its `title` and its posts as `pages` in its frontmatter.
+//! Drafts and utility pages are left out. An index page is not
+//! generated where a content page is written.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::core::config::SeriesConfig;
+//! use nucleusflow::generators::series::{
+//!     series_contexts, series_pages,
+//! };
+//! use nucleusflow::site::Page;
+//!
+//! let part = |n: i64| {
+//!     Page::new(
+//!         format!("content/part-{}.md", n),
+//!         format!("part-{}.html", n),
+//!         serde_json::json!({ "series": "Tea", "weight": n })
+//!             .as_object()
+//!             .cloned()
+//!             .unwrap(),
+//!     )
+//! };
+//! let pages = [part(2), part(1)];
+//! let config = SeriesConfig::default();
+//!
+//! let contexts = series_contexts(&config, &pages).unwrap();
+//! let series = &contexts["/part-2.html"];
+//! assert_eq!(series["url"], "/series/tea/");
+//! assert_eq!(series["position"], 2);
+//! assert_eq!(series["total"], 2);
+//!
+//! let index = series_pages(&config, &pages).unwrap();
+//! assert_eq!(index[0].frontmatter["pages"][0]["url"], "/part-1.html");
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use serde_json::{json, Map, Value as JsonValue};
+
+use crate::core::config::SeriesConfig;
+use crate::import::slugify;
+use crate::site::{generated_path, page_url, Page};
+use crate::Result;
+
+/// A series index page, ready to be rendered.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeriesPage {
+    /// The index page's path relative to the output directory
+    pub path: PathBuf,
+
+    /// The index page's title, template and pages
+    pub frontmatter: Map<String, JsonValue>,
+}
+
+/// Lists the posts of each series in order.
+///
+/// # Arguments
+/// * `config` - Where index pages are written and their template.
+/// * `pages` - The site's pages.
+///
+/// # Returns
+/// * `Result<Vec<SeriesPage>>` - The index pages, ordered by series
+///   name, or an error if the configured path is outside the output
+///   directory.
+pub fn series_pages(
+    config: &SeriesConfig,
+    pages: &[Page],
+) -> Result<Vec<SeriesPage>> {
+    members(pages)
+        .into_iter()
+        .map(|(name, posts)| {
+            let mut frontmatter = Map::new();
+            _ = frontmatter.insert("title".to_string(), name.into());
+            _ = frontmatter.insert(
+                "template".to_string(),
+                config.template.clone().into(),
+            );
+            _ = frontmatter.insert(
+                "pages".to_string(),
+                listing(&posts, None).into(),
+            );
+            Ok(SeriesPage {
+                path: series_path(config, name)?,
+                frontmatter,
+            })
+        })
+        .collect()
+}
+
+/// Returns the series of each post, as templates see it.
+///
+/// # Arguments
+/// * `config` - Where index pages are written.
+/// * `pages` - The site's pages.
+///
+/// # Returns
+/// * `Result<HashMap<String, JsonValue>>` - The series of each post in
+///   one, keyed by the post's URL, or an error if the configured path
+///   is outside the output directory.
+pub fn series_contexts(
+    config: &SeriesConfig,
+    pages: &[Page],
+) -> Result<HashMap<String, JsonValue>> {
+    let mut contexts = HashMap::new();
+    for (name, posts) in members(pages) {
+        let url = page_url(&series_path(config, name)?);
+        for (index, post) in posts.iter().enumerate() {
+            _ = contexts.insert(
+                post.url.clone(),
+                json!({
+                    "name": name,
+                    "url": url,
+                    "position": index + 1,
+                    "total": posts.len(),
+                    "pages": listing(&posts, Some(index)),
+                }),
+            );
+        }
+    }
+    Ok(contexts)
+}
+
+/// Returns the listed posts of each series, in order, keyed by name.
+fn members(pages: &[Page]) -> BTreeMap<&str, Vec<&Page>> {
+    let mut series: BTreeMap<&str, Vec<&Page>> = BTreeMap::new();
+    for page in
+        pages.iter().filter(|p| !p.is_draft() && !p.is_utility())
+    {
+        if let Some(name) = page
+            .frontmatter
+            .get("series")
+            .and_then(JsonValue::as_str)
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+        {
+            series.entry(name).or_default().push(page);
+        }
+    }
+    for posts in series.values_mut() {
+        posts.sort_by(|a, b| a.listing_order(b));
+    }
+    series
+}
+
+/// Returns a series' posts for templates, marking the current one.
+fn listing(posts: &[&Page], current: Option<usize>) -> Vec<JsonValue> {
+    posts
+        .iter()
+        .enumerate()
+        .map(|(index, post)| {
+            let mut entry = json!({
+                "url": post.url,
+                "title": post.title(),
+                "date": post.date(),
+            });
+            if current.is_some() {
+                entry["current"] = (current == Some(index)).into();
+            }
+            entry
+        })
+        .collect()
+}
+
+/// Returns the output path of a series' index page.
+fn series_path(config: &SeriesConfig, name: &str) -> Result<PathBuf> {
+    let path = config.path.replace("{series}", &slugify(name));
+    generated_path("Series", &config.path, &path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::site::tests::page;
+
+    fn pages() -> Vec<Page> {
+        vec![
+            page(
+                "b.html",
+                json!({ "series": "Rust 101", "date": "2024-02-01" }),
+            ),
+            page(
+                "a.html",
+                json!({
+                    "series": "Rust 101",
+                    "date": "2024-03-01",
+                    "weight": 1,
+                    "title": "Intro",
+                }),
+            ),
+            page(
+                "c.html",
+                json!({ "series": "Rust 101", "date": "2024-01-01" }),
+            ),
+            page(
+                "d.html",
+                json!({ "series": "Rust 101", "draft": true }),
+            ),
+            page("e.html", json!({ "series": " Go " })),
+            page("f.html", json!({ "series": "" })),
+        ]
+    }
+
+    #[test]
+    fn test_series_contexts() -> Result<()> {
+        let contexts =
+            series_contexts(&SeriesConfig::default(), &pages())?;
+        assert_eq!(contexts.len(), 4);
+        assert_eq!(
+            contexts["/c.html"],
+            json!({
+                "name": "Rust 101",
+                "url": "/series/rust-101/",
+                "position": 2,
+                "total": 3,
+                "pages": [
+                    {
+                        "url": "/a.html",
+                        "title": "Intro",
+                        "date": "2024-03-01",
+                        "current": false,
+                    },
+                    {
+                        "url": "/c.html",
+                        "title": null,
+                        "date": "2024-01-01",
+                        "current": true,
+                    },
+                    {
+                        "url": "/b.html",
+                        "title": null,
+                        "date": "2024-02-01",
+                        "current": false,
+                    },
+                ],
+            })
+        );
+        assert_eq!(contexts["/e.html"]["name"], "Go");
+        assert_eq!(contexts["/e.html"]["url"], "/series/go/");
+        Ok(())
+    }
+
+    #[test]
+    fn test_series_pages() -> Result<()> {
+        let index = series_pages(&SeriesConfig::default(), &pages())?;
+        let listed: Vec<(PathBuf, &JsonValue, usize)> = index
+            .iter()
+            .map(|page| {
+                (
+                    page.path.clone(),
+                    &page.frontmatter["title"],
+                    page.frontmatter["pages"].as_array().unwrap().len(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            listed,
+            [
+                (
+                    PathBuf::from("series/go/index.html"),
+                    &json!("Go"),
+                    1
+                ),
+                (
+                    PathBuf::from("series/rust-101/index.html"),
+                    &json!("Rust 101"),
+                    3
+                ),
+            ]
+        );
+        assert_eq!(index[1].frontmatter["template"], "series");
+        assert!(index[1].frontmatter["pages"][0]
+            .get("current")
+            .is_none());
+
+        let config = SeriesConfig {
+            path: "../{series}.html".to_string(),
+            ..SeriesConfig::default()
+        };
+        assert!(series_pages(&config, &pages())
+            .unwrap_err()
+            .to_string()
+            .contains("outside the output directory"));
+        Ok(())
+    }
+}
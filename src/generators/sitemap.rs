@@ -0,0 +1,658 @@
+//! # Sitemap Generation
+//!
+//! Collects the pages of a site into a [`Sitemap`] and writes it as
+//! `sitemap.xml`. The sitemap protocol limits a single file to 50,000
+//! URLs and 50MB uncompressed, so larger sites are split into numbered
+//! shards (`sitemap-1.xml`, `sitemap-2.xml`, ...) and `sitemap.xml`
+//! becomes a sitemap index pointing at them.
+//!
+//! Every `lastmod` value is written in the site timezone with the same
+//! W3C datetime format, and each index entry carries the most recent
+//! `lastmod` of the URLs in its shard, so crawlers see consistent dates
+//! whether a site is sharded or not.
+//!
+//! A multilingual site gets a sitemap per language from
+//! [`LanguageSitemaps`], such as `sitemap-en.xml` and `sitemap-fr.xml`,
+//! behind a combined `sitemap.xml` index. Each URL lists its
+//! translations as `xhtml:link` hreflang alternates.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::core::time::SiteTimezone;
+//! use nucleusflow::generators::sitemap::Sitemap;
+//!
+//! let mut sitemap =
+//!     Sitemap::new("https://example.com", SiteTimezone::utc());
+//! sitemap.add("/", Some("2024-05-01")).unwrap();
+//! sitemap.add("/about/", None).unwrap();
+//!
+//! let xml = sitemap.render_urlset(0..sitemap.len());
+//! assert!(xml.contains("<loc>https://example.com/about/</loc>"));
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, SecondsFormat};
+use chrono_tz::Tz;
+
+use crate::core::time::SiteTimezone;
+use crate::{ProcessingError, Result};
+
+/// Maximum number of URLs allowed in a single sitemap file.
+pub const MAX_SITEMAP_URLS: usize = 50_000;
+
+/// Maximum uncompressed size of a single sitemap file in bytes.
+pub const MAX_SITEMAP_BYTES: usize = 50 * 1024 * 1024;
+
+/// The file name of the sitemap, or of the sitemap index when sharded.
+pub const SITEMAP_FILE_NAME: &str = "sitemap.xml";
+
+/// The name of the sitemap, before its `.xml` extension.
+const SITEMAP_NAME: &str = "sitemap";
+
+const XML_DECLARATION: &str =
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n";
+const SITEMAP_NAMESPACE: &str =
+    "http://www.sitemaps.org/schemas/sitemap/0.9";
+const XHTML_NAMESPACE: &str = "http://www.w3.org/1999/xhtml";
+
+/// A URL of a sitemap.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct SitemapEntry {
+    lastmod: Option<DateTime<Tz>>,
+    /// `(hreflang, URL)` pairs of the page's translations
+    alternates: Vec<(String, String)>,
+}
+
+/// A collection of page URLs with their last modification dates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sitemap {
+    base_url: String,
+    timezone: SiteTimezone,
+    max_urls: usize,
+    name: String,
+    entries: BTreeMap<String, SitemapEntry>,
+}
+
+impl Sitemap {
+    /// Creates an empty sitemap.
+    ///
+    /// # Arguments
+    /// * `base_url` - The site's absolute base URL, used to resolve
+    ///   site-relative paths.
+    /// * `timezone` - The timezone `lastmod` dates are read and written
+    ///   in.
+    pub fn new<S: Into<String>>(
+        base_url: S,
+        timezone: SiteTimezone,
+    ) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            timezone,
+            max_urls: MAX_SITEMAP_URLS,
+            name: SITEMAP_NAME.to_string(),
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the number of URLs per file before the sitemap is sharded.
+    ///
+    /// Values are clamped to between 1 and [`MAX_SITEMAP_URLS`].
+    pub fn with_max_urls(mut self, max_urls: usize) -> Self {
+        self.max_urls = max_urls.clamp(1, MAX_SITEMAP_URLS);
+        self
+    }
+
+    /// Sets the name the sitemap's files are written under: `name.xml`,
+    /// and `name-1.xml`, `name-2.xml`, ... when sharded.
+    pub fn with_name<S: Into<String>>(mut self, name: S) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Returns the file name of the sitemap, or of its index when
+    /// sharded.
+    pub fn file_name(&self) -> String {
+        format!("{}.xml", self.name)
+    }
+
+    /// Adds a page to the sitemap.
+    ///
+    /// Adding the same URL twice keeps the most recent `lastmod`.
+    ///
+    /// # Arguments
+    /// * `path` - A site-relative path starting with `/`, or an absolute
+    ///   URL under the base URL.
+    /// * `lastmod` - The page's last modification date as written in
+    ///   frontmatter, if known.
+    ///
+    /// # Returns
+    /// * `Result<()>` - An error if the path or date is invalid.
+    pub fn add(
+        &mut self,
+        path: &str,
+        lastmod: Option<&str>,
+    ) -> Result<()> {
+        self.add_with_alternates(path, lastmod, &[])
+    }
+
+    /// Adds a page to the sitemap with the URLs of its translations.
+    ///
+    /// # Arguments
+    /// * `path` - A site-relative path starting with `/`, or an absolute
+    ///   URL under the base URL.
+    /// * `lastmod` - The page's last modification date as written in
+    ///   frontmatter, if known.
+    /// * `alternates` - `(hreflang, URL)` pairs of the page's
+    ///   translations, with URLs in the same forms as `path`.
+    ///
+    /// # Returns
+    /// * `Result<()>` - An error if a URL or the date is invalid.
+    pub fn add_with_alternates(
+        &mut self,
+        path: &str,
+        lastmod: Option<&str>,
+        alternates: &[(String, String)],
+    ) -> Result<()> {
+        let loc = self.loc(path)?;
+        let lastmod = lastmod
+            .map(|date| self.timezone.parse_date(date))
+            .transpose()?;
+        let alternates = alternates
+            .iter()
+            .map(|(hreflang, url)| {
+                Ok((hreflang.clone(), self.loc(url)?))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let entry = self.entries.entry(loc).or_default();
+        if lastmod > entry.lastmod {
+            entry.lastmod = lastmod;
+        }
+        if !alternates.is_empty() {
+            entry.alternates = alternates;
+        }
+        Ok(())
+    }
+
+    /// Returns the number of URLs in the sitemap.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the sitemap contains no URLs.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Renders a range of URLs, ordered by URL, as a `<urlset>`.
+    pub fn render_urlset(&self, range: Range<usize>) -> String {
+        let entries: Vec<_> = self
+            .entries
+            .iter()
+            .skip(range.start)
+            .take(range.len())
+            .collect();
+        let mut xml = format!(
+            "{}<urlset xmlns=\"{}\"",
+            XML_DECLARATION, SITEMAP_NAMESPACE
+        );
+        if entries
+            .iter()
+            .any(|(_, entry)| !entry.alternates.is_empty())
+        {
+            xml.push_str(&format!(
+                " xmlns:xhtml=\"{}\"",
+                XHTML_NAMESPACE
+            ));
+        }
+        xml.push_str(">\n");
+        for (loc, entry) in entries {
+            xml.push_str(&render_url(loc, entry));
+        }
+        xml.push_str("</urlset>\n");
+        xml
+    }
+
+    /// Writes the sitemap to `output_dir`, sharding it behind a sitemap
+    /// index if it exceeds the per-file limits.
+    ///
+    /// Shards left over from an earlier, larger build are removed.
+    ///
+    /// # Returns
+    /// * `Result<Vec<PathBuf>>` - The paths of the files written, with
+    ///   the sitemap or its index first.
+    pub fn write(&self, output_dir: &Path) -> Result<Vec<PathBuf>> {
+        fs::create_dir_all(output_dir).map_err(|e| {
+            ProcessingError::io_error(output_dir.to_path_buf(), e)
+        })?;
+
+        let shards = self.shards();
+        let mut written = vec![output_dir.join(self.file_name())];
+        if shards.len() <= 1 {
+            write_file(
+                &written[0],
+                &self.render_urlset(0..self.len()),
+            )?;
+        } else {
+            for (number, range) in shards.iter().enumerate() {
+                let path = output_dir
+                    .join(shard_file_name(&self.name, number + 1));
+                write_file(&path, &self.render_urlset(range.clone()))?;
+                written.push(path);
+            }
+            write_file(
+                &written[0],
+                &render_index(&self.base_url, &self.urlsets()),
+            )?;
+        }
+
+        let first_stale = if shards.len() > 1 {
+            shards.len() + 1
+        } else {
+            1
+        };
+        for number in first_stale.. {
+            let path =
+                output_dir.join(shard_file_name(&self.name, number));
+            if !path.exists() {
+                break;
+            }
+            fs::remove_file(&path)
+                .map_err(|e| ProcessingError::io_error(path, e))?;
+        }
+
+        Ok(written)
+    }
+
+    /// Returns the file name and newest `lastmod` of each `<urlset>`
+    /// file [`Sitemap::write`] writes.
+    fn urlsets(&self) -> Vec<(String, Option<&DateTime<Tz>>)> {
+        let shards = self.shards();
+        let newest = |range: &Range<usize>| {
+            self.entries
+                .values()
+                .skip(range.start)
+                .take(range.len())
+                .filter_map(|entry| entry.lastmod.as_ref())
+                .max()
+        };
+        if shards.len() <= 1 {
+            return vec![(self.file_name(), newest(&(0..self.len())))];
+        }
+        shards
+            .iter()
+            .enumerate()
+            .map(|(number, range)| {
+                (shard_file_name(&self.name, number + 1), newest(range))
+            })
+            .collect()
+    }
+
+    /// Resolves a site-relative path or absolute URL against the base
+    /// URL.
+    fn loc(&self, path: &str) -> Result<String> {
+        if path.starts_with('/') {
+            Ok(format!("{}{}", self.base_url, path))
+        } else if path.starts_with(&self.base_url) {
+            Ok(path.to_string())
+        } else {
+            Err(ProcessingError::validation(
+                format!(
+                    "Sitemap URL '{}' is not under the base URL '{}'",
+                    path, self.base_url
+                ),
+                None::<String>,
+            ))
+        }
+    }
+
+    /// Splits the URLs into ranges that each fit in one sitemap file.
+    fn shards(&self) -> Vec<Range<usize>> {
+        let overhead = self.render_urlset(0..0).len()
+            + format!(" xmlns:xhtml=\"{}\"", XHTML_NAMESPACE).len();
+        let mut shards = Vec::new();
+        let mut start = 0;
+        let mut bytes = overhead;
+        for (index, (loc, entry)) in self.entries.iter().enumerate() {
+            let size = render_url(loc, entry).len();
+            if index > start
+                && (index - start == self.max_urls
+                    || bytes + size > MAX_SITEMAP_BYTES)
+            {
+                shards.push(start..index);
+                start = index;
+                bytes = overhead;
+            }
+            bytes += size;
+        }
+        if start < self.len() {
+            shards.push(start..self.len());
+        }
+        shards
+    }
+}
+
+/// The sitemaps of a multilingual site, one per language, behind a
+/// combined sitemap index.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LanguageSitemaps {
+    base_url: String,
+    timezone: SiteTimezone,
+    sitemaps: BTreeMap<String, Sitemap>,
+}
+
+impl LanguageSitemaps {
+    /// Creates empty sitemaps for a site's languages.
+    ///
+    /// # Arguments
+    /// * `base_url` - The site's absolute base URL.
+    /// * `timezone` - The timezone `lastmod` dates are read and written
+    ///   in.
+    pub fn new<S: Into<String>>(
+        base_url: S,
+        timezone: SiteTimezone,
+    ) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            timezone,
+            sitemaps: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a page to the sitemap of its language.
+    ///
+    /// # Arguments
+    /// * `language` - The page's language.
+    /// * `path` - A site-relative path starting with `/`, or an absolute
+    ///   URL under the base URL.
+    /// * `lastmod` - The page's last modification date, if known.
+    /// * `alternates` - `(hreflang, URL)` pairs of the page's
+    ///   translations.
+    ///
+    /// # Returns
+    /// * `Result<()>` - An error if a URL or the date is invalid.
+    pub fn add(
+        &mut self,
+        language: &str,
+        path: &str,
+        lastmod: Option<&str>,
+        alternates: &[(String, String)],
+    ) -> Result<()> {
+        let (base_url, timezone) = (&self.base_url, self.timezone);
+        self.sitemaps
+            .entry(language.to_string())
+            .or_insert_with(|| {
+                Sitemap::new(base_url.as_str(), timezone)
+                    .with_name(format!("{}-{}", SITEMAP_NAME, language))
+            })
+            .add_with_alternates(path, lastmod, alternates)
+    }
+
+    /// Returns the sitemap of a language, if it has pages.
+    pub fn get(&self, language: &str) -> Option<&Sitemap> {
+        self.sitemaps.get(language)
+    }
+
+    /// Returns the number of URLs across every language.
+    pub fn len(&self) -> usize {
+        self.sitemaps.values().map(Sitemap::len).sum()
+    }
+
+    /// Returns `true` if no language has any URLs.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Writes each language's sitemap to `output_dir` and `sitemap.xml`
+    /// as an index of them all.
+    ///
+    /// # Returns
+    /// * `Result<Vec<PathBuf>>` - The paths of the files written, with
+    ///   the index first.
+    pub fn write(&self, output_dir: &Path) -> Result<Vec<PathBuf>> {
+        let mut written = vec![output_dir.join(SITEMAP_FILE_NAME)];
+        let mut urlsets = Vec::new();
+        for sitemap in self.sitemaps.values() {
+            written.extend(sitemap.write(output_dir)?);
+            urlsets.extend(sitemap.urlsets());
+        }
+        write_file(
+            &written[0],
+            &render_index(&self.base_url, &urlsets),
+        )?;
+        Ok(written)
+    }
+}
+
+/// Renders a `<sitemapindex>` of `<urlset>` files with their newest
+/// `lastmod`.
+fn render_index(
+    base_url: &str,
+    urlsets: &[(String, Option<&DateTime<Tz>>)],
+) -> String {
+    let mut index = format!(
+        "{}<sitemapindex xmlns=\"{}\">\n",
+        XML_DECLARATION, SITEMAP_NAMESPACE
+    );
+    for (name, lastmod) in urlsets {
+        index.push_str("  <sitemap>\n");
+        index.push_str(&format!(
+            "    <loc>{}</loc>\n",
+            escape_xml(&format!("{}/{}", base_url, name))
+        ));
+        if let Some(lastmod) = lastmod {
+            index.push_str(&format!(
+                "    <lastmod>{}</lastmod>\n",
+                format_lastmod(lastmod)
+            ));
+        }
+        index.push_str("  </sitemap>\n");
+    }
+    index.push_str("</sitemapindex>\n");
+    index
+}
+
+/// Renders a single `<url>` element.
+fn render_url(loc: &str, entry: &SitemapEntry) -> String {
+    let mut xml =
+        format!("  <url>\n    <loc>{}</loc>\n", escape_xml(loc));
+    if let Some(lastmod) = &entry.lastmod {
+        xml.push_str(&format!(
+            "    <lastmod>{}</lastmod>\n",
+            format_lastmod(lastmod)
+        ));
+    }
+    for (hreflang, href) in &entry.alternates {
+        xml.push_str(&format!(
+            "    <xhtml:link rel=\"alternate\" hreflang=\"{}\" href=\"{}\"/>\n",
+            escape_xml(hreflang),
+            escape_xml(href)
+        ));
+    }
+    xml.push_str("  </url>\n");
+    xml
+}
+
+/// Formats a `lastmod` value as a W3C datetime with a UTC offset.
+fn format_lastmod(date: &DateTime<Tz>) -> String {
+    date.to_rfc3339_opts(SecondsFormat::Secs, false)
+}
+
+/// Returns the file name of a numbered shard of a sitemap.
+fn shard_file_name(name: &str, number: usize) -> String {
+    format!("{}-{}.xml", name, number)
+}
+
+fn write_file(path: &Path, content: &str) -> Result<()> {
+    fs::write(path, content)
+        .map_err(|e| ProcessingError::io_error(path.to_path_buf(), e))
+}
+
+/// Escapes the XML special characters in a text node.
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn sitemap(pages: usize) -> Sitemap {
+        let timezone = SiteTimezone::parse("Europe/Paris").unwrap();
+        let mut sitemap =
+            Sitemap::new("https://example.com/", timezone);
+        for page in 0..pages {
+            let date = format!("2024-01-{:02}", page + 1);
+            sitemap
+                .add(&format!("/page-{}/", page), Some(&date))
+                .unwrap();
+        }
+        sitemap
+    }
+
+    #[test]
+    fn test_add() {
+        let mut sitemap = sitemap(0);
+        sitemap.add("/a/", Some("2024-01-01")).unwrap();
+        sitemap
+            .add("https://example.com/a/", Some("2024-02-01"))
+            .unwrap();
+        sitemap.add("/a/", Some("2023-12-01")).unwrap();
+        assert_eq!(sitemap.len(), 1);
+
+        let xml = sitemap.render_urlset(0..1);
+        assert!(xml.contains("<loc>https://example.com/a/</loc>"));
+        assert!(xml
+            .contains("<lastmod>2024-02-01T00:00:00+01:00</lastmod>"));
+
+        assert!(sitemap.add("https://other.com/", None).is_err());
+        assert!(sitemap.add("/b/", Some("yesterday")).is_err());
+    }
+
+    #[test]
+    fn test_single_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let written = sitemap(3).write(temp_dir.path()).unwrap();
+
+        assert_eq!(written, [temp_dir.path().join("sitemap.xml")]);
+        let xml = fs::read_to_string(&written[0]).unwrap();
+        assert!(xml.starts_with(XML_DECLARATION));
+        assert_eq!(xml.matches("<url>").count(), 3);
+    }
+
+    #[test]
+    fn test_sharded_index() {
+        let temp_dir = TempDir::new().unwrap();
+        let sitemap = sitemap(5).with_max_urls(2);
+        let written = sitemap.write(temp_dir.path()).unwrap();
+        assert_eq!(written.len(), 4);
+
+        let index =
+            fs::read_to_string(temp_dir.path().join("sitemap.xml"))
+                .unwrap();
+        assert!(index.contains("<sitemapindex"));
+        assert_eq!(index.matches("<sitemap>").count(), 3);
+        assert!(index
+            .contains("<loc>https://example.com/sitemap-3.xml</loc>"));
+
+        // Each index entry carries the newest lastmod of its shard
+        for number in 1..=3 {
+            let shard = fs::read_to_string(
+                temp_dir
+                    .path()
+                    .join(shard_file_name("sitemap", number)),
+            )
+            .unwrap();
+            let newest = shard
+                .lines()
+                .filter(|line| line.contains("<lastmod>"))
+                .map(str::trim)
+                .max()
+                .unwrap();
+            assert!(index.contains(newest));
+        }
+
+        // A smaller rebuild removes the stale shards
+        let written =
+            sitemap.with_max_urls(10).write(temp_dir.path()).unwrap();
+        assert_eq!(written.len(), 1);
+        assert!(!temp_dir.path().join("sitemap-1.xml").exists());
+    }
+
+    #[test]
+    fn test_escaping() {
+        let mut sitemap = sitemap(0);
+        sitemap.add("/search/?q=a&b", None).unwrap();
+        assert!(sitemap.render_urlset(0..1).contains(
+            "<loc>https://example.com/search/?q=a&amp;b</loc>"
+        ));
+    }
+
+    #[test]
+    fn test_language_sitemaps() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut sitemaps = LanguageSitemaps::new(
+            "https://example.com",
+            SiteTimezone::utc(),
+        );
+        let alternates = [
+            ("en".to_string(), "/about.html".to_string()),
+            (
+                "fr".to_string(),
+                "https://example.com/fr/about.html".to_string(),
+            ),
+        ];
+        sitemaps
+            .add("en", "/about.html", Some("2024-03-01"), &alternates)
+            .unwrap();
+        sitemaps
+            .add(
+                "fr",
+                "/fr/about.html",
+                Some("2024-04-01"),
+                &alternates,
+            )
+            .unwrap();
+        sitemaps.add("en", "/solo.html", None, &[]).unwrap();
+        assert_eq!(sitemaps.len(), 3);
+        assert!(sitemaps
+            .add("en", "/x.html", None, &[("de".into(), "x".into())])
+            .is_err());
+
+        let written = sitemaps.write(temp_dir.path()).unwrap();
+        assert_eq!(
+            written,
+            [
+                temp_dir.path().join("sitemap.xml"),
+                temp_dir.path().join("sitemap-en.xml"),
+                temp_dir.path().join("sitemap-fr.xml"),
+            ]
+        );
+        let index = fs::read_to_string(&written[0]).unwrap();
+        assert!(index
+            .contains("<loc>https://example.com/sitemap-fr.xml</loc>"));
+        assert!(index.contains("<lastmod>2024-04-01T00:00:00+00:00"));
+
+        let english = fs::read_to_string(&written[1]).unwrap();
+        assert!(english.contains(&format!(
+            "<urlset xmlns=\"{}\" xmlns:xhtml=\"{}\">",
+            SITEMAP_NAMESPACE, XHTML_NAMESPACE
+        )));
+        assert!(english.contains(
+            r#"<xhtml:link rel="alternate" hreflang="fr" href="https://example.com/fr/about.html"/>"#
+        ));
+        assert_eq!(english.matches("<url>").count(), 2);
+        assert!(!english.contains("/fr/about.html</loc>"));
+    }
+}
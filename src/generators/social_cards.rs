@@ -0,0 +1,452 @@
+//! # Social Cards
+//!
+//! Renders a social card image for each page, shown when a link to it
+//! is shared: the page's title, its author and the site's name, set in
+//! the configured font over a background image or a solid colour. The
+//! text is laid out and rasterised in pure Rust, so no browser or
+//! system library is needed.
+//!
+//! A page's card is written next to it, so `blog/post.html` gets
+//! `blog/post.card.png`, and its URL is set as the page's `image`, which
+//! [`SocialMetaTags`](crate::generators::seo::SocialMetaTags) turns into
+//! the `og:image` and `twitter:image` tags. Pages that set their own
+//! `image`, utility pages and pages setting `social_card: false` get no
+//! card.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use nucleusflow::core::config::SocialCardConfig;
+//! use nucleusflow::generators::social_cards::SocialCards;
+//! use nucleusflow::site::Page;
+//! use serde_json::json;
+//! use std::path::Path;
+//!
+//! let cards = SocialCards::new(&SocialCardConfig {
+//!     font: Some("assets/fonts/Inter-Bold.ttf".into()),
+//!     site_name: Some("Example".to_string()),
+//!     ..SocialCardConfig::default()
+//! })
+//! .unwrap();
+//! let page = Page::new(
+//!     "content/post.md",
+//!     "post.html",
+//!     json!({ "title": "Hello", "author": "Ada" })
+//!         .as_object()
+//!         .cloned()
+//!         .unwrap(),
+//! );
+//! let page = cards.attach(&page, Path::new("public")).unwrap().unwrap();
+//! assert_eq!(page.frontmatter["image"], "/post.card.png");
+//! ```
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ab_glyph::{point, Font, FontVec, PxScale, ScaleFont};
+use image::imageops::FilterType;
+use image::{Rgba, RgbaImage};
+use serde_json::Value as JsonValue;
+
+use crate::core::config::{parse_hex_color, SocialCardConfig};
+use crate::site::Page;
+use crate::{ProcessingError, Result};
+
+/// The suffix replacing a page's `.html` extension in its card's name.
+const CARD_SUFFIX: &str = "card.png";
+
+/// The most lines a title is wrapped to before it is cut short.
+const MAX_TITLE_LINES: usize = 3;
+
+/// Renders social card images for pages.
+#[derive(Debug)]
+pub struct SocialCards {
+    config: SocialCardConfig,
+    font: FontVec,
+    background: RgbaImage,
+    text_color: [u8; 3],
+}
+
+impl SocialCards {
+    /// Loads the font and background a configuration names.
+    ///
+    /// # Arguments
+    /// * `config` - The cards' font, background, colours and size.
+    ///
+    /// # Returns
+    /// * `Result<Self>` - The renderer, or an error if no font is set,
+    ///   the font or background cannot be read, or a colour is invalid.
+    pub fn new(config: &SocialCardConfig) -> Result<Self> {
+        let invalid =
+            |details: String| ProcessingError::Configuration {
+                details,
+                path: None,
+                source: None,
+            };
+        let font_path = config.font.as_ref().ok_or_else(|| {
+            invalid("Social cards need a font".to_string())
+        })?;
+        let bytes = fs::read(font_path).map_err(|e| {
+            ProcessingError::io_error(font_path.clone(), e)
+        })?;
+        let font = FontVec::try_from_vec(bytes).map_err(|_| {
+            invalid(format!(
+                "Social card font '{}' is not a TrueType or OpenType font",
+                font_path.display()
+            ))
+        })?;
+        let color = |color: &str| {
+            parse_hex_color(color).ok_or_else(|| {
+                invalid(format!(
+                    "Social card colour '{}' must be of the form #rrggbb",
+                    color
+                ))
+            })
+        };
+        let text_color = color(&config.text_color)?;
+
+        let background = match &config.background {
+            Some(path) => image::open(path)
+                .map_err(|e| {
+                    ProcessingError::content_processing(
+                        format!(
+                            "Failed to read social card background '{}'",
+                            path.display()
+                        ),
+                        Some(Box::new(e)),
+                    )
+                })?
+                .resize_to_fill(
+                    config.width,
+                    config.height,
+                    FilterType::Lanczos3,
+                )
+                .to_rgba8(),
+            None => {
+                let [r, g, b] = color(&config.background_color)?;
+                RgbaImage::from_pixel(
+                    config.width,
+                    config.height,
+                    Rgba([r, g, b, 255]),
+                )
+            }
+        };
+
+        Ok(Self {
+            config: config.clone(),
+            font,
+            background,
+            text_color,
+        })
+    }
+
+    /// Renders a page's card and writes it next to the page.
+    ///
+    /// # Arguments
+    /// * `page` - The page to render a card for.
+    /// * `output_dir` - The directory the page is written to.
+    ///
+    /// # Returns
+    /// * `Result<Option<Page>>` - A copy of the page with the card's URL
+    ///   as its `image`, `None` if the page gets no card, or an error if
+    ///   the card cannot be written.
+    pub fn attach(
+        &self,
+        page: &Page,
+        output_dir: &Path,
+    ) -> Result<Option<Page>> {
+        let opted_out = matches!(
+            page.frontmatter.get("social_card"),
+            Some(JsonValue::Bool(false))
+        );
+        if opted_out
+            || page.is_utility()
+            || page.frontmatter.contains_key("image")
+        {
+            return Ok(None);
+        }
+
+        let path = card_path(&page.path);
+        let target = output_dir.join(&path);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| {
+                ProcessingError::io_error(parent.to_path_buf(), e)
+            })?;
+        }
+        self.render(page).save(&target).map_err(|e| {
+            ProcessingError::content_processing(
+                format!(
+                    "Failed to write social card '{}'",
+                    target.display()
+                ),
+                Some(Box::new(e)),
+            )
+        })?;
+
+        let mut page = page.clone();
+        _ = page.frontmatter.insert(
+            "image".to_string(),
+            JsonValue::from(card_url(&path)),
+        );
+        Ok(Some(page))
+    }
+
+    /// Renders a page's card: its title near the top, its `author`
+    /// below, and the site name at the bottom.
+    pub fn render(&self, page: &Page) -> RgbaImage {
+        let mut card = self.background.clone();
+        let (width, height) =
+            (card.width() as f32, card.height() as f32);
+        let padding = width / 15.0;
+        let max_width = width - 2.0 * padding;
+
+        let title_scale = PxScale::from(height / 9.0);
+        let title = page.title().unwrap_or_default();
+        let mut baseline = padding + title_scale.y;
+        for line in wrap(
+            &self.font,
+            title_scale,
+            title,
+            max_width,
+            MAX_TITLE_LINES,
+        ) {
+            self.draw(&mut card, title_scale, padding, baseline, &line);
+            baseline += title_scale.y * 1.2;
+        }
+
+        if let Some(author) =
+            page.frontmatter.get("author").and_then(JsonValue::as_str)
+        {
+            let scale = PxScale::from(height / 18.0);
+            let line = wrap(&self.font, scale, author, max_width, 1);
+            baseline += scale.y * 0.6;
+            for line in line {
+                self.draw(&mut card, scale, padding, baseline, &line);
+            }
+        }
+
+        if let Some(site_name) = &self.config.site_name {
+            let scale = PxScale::from(height / 20.0);
+            for line in wrap(&self.font, scale, site_name, max_width, 1)
+            {
+                self.draw(
+                    &mut card,
+                    scale,
+                    padding,
+                    height - padding,
+                    &line,
+                );
+            }
+        }
+        card
+    }
+
+    /// Draws a line of text with its baseline at `baseline`.
+    fn draw(
+        &self,
+        card: &mut RgbaImage,
+        scale: PxScale,
+        x: f32,
+        baseline: f32,
+        text: &str,
+    ) {
+        let scaled = self.font.as_scaled(scale);
+        let mut caret = x;
+        let mut previous = None;
+        for c in text.chars() {
+            let id = scaled.glyph_id(c);
+            if let Some(previous) = previous {
+                caret += scaled.kern(previous, id);
+            }
+            let glyph = id
+                .with_scale_and_position(scale, point(caret, baseline));
+            caret += scaled.h_advance(id);
+            previous = Some(id);
+
+            let outline = match self.font.outline_glyph(glyph) {
+                Some(outline) => outline,
+                None => continue,
+            };
+            let bounds = outline.px_bounds();
+            outline.draw(|gx, gy, coverage| {
+                let px = bounds.min.x as i64 + i64::from(gx);
+                let py = bounds.min.y as i64 + i64::from(gy);
+                if px < 0
+                    || py < 0
+                    || px >= i64::from(card.width())
+                    || py >= i64::from(card.height())
+                {
+                    return;
+                }
+                let pixel = card.get_pixel_mut(px as u32, py as u32);
+                let coverage = coverage.clamp(0.0, 1.0);
+                for (channel, text) in
+                    pixel.0.iter_mut().zip(self.text_color)
+                {
+                    *channel = (f32::from(*channel) * (1.0 - coverage)
+                        + f32::from(text) * coverage)
+                        .round() as u8;
+                }
+                pixel.0[3] =
+                    pixel.0[3].max((coverage * 255.0).round() as u8);
+            });
+        }
+    }
+}
+
+/// Returns the output-relative path of a page's card.
+fn card_path(page_path: &Path) -> PathBuf {
+    page_path.with_extension(CARD_SUFFIX)
+}
+
+/// Returns the site-relative URL of a card.
+fn card_url(path: &Path) -> String {
+    let segments: Vec<_> = path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect();
+    format!("/{}", segments.join("/"))
+}
+
+/// Returns the width of a line of text.
+fn text_width<F: Font>(font: &F, scale: PxScale, text: &str) -> f32 {
+    let scaled = font.as_scaled(scale);
+    let mut width = 0.0;
+    let mut previous = None;
+    for c in text.chars() {
+        let id = scaled.glyph_id(c);
+        if let Some(previous) = previous {
+            width += scaled.kern(previous, id);
+        }
+        width += scaled.h_advance(id);
+        previous = Some(id);
+    }
+    width
+}
+
+/// Wraps text at word boundaries into lines no wider than `max_width`,
+/// ending the last line with an ellipsis if the text does not fit in
+/// `max_lines`.
+fn wrap<F: Font>(
+    font: &F,
+    scale: PxScale,
+    text: &str,
+    max_width: f32,
+    max_lines: usize,
+) -> Vec<String> {
+    let fits = |line: &str| text_width(font, scale, line) <= max_width;
+    let mut lines: Vec<String> = Vec::new();
+    let mut truncated = false;
+    for word in text.split_whitespace() {
+        if let Some(line) = lines.last_mut() {
+            let joined = format!("{} {}", line, word);
+            if fits(&joined) {
+                *line = joined;
+                continue;
+            }
+        }
+        if lines.len() == max_lines {
+            truncated = true;
+            break;
+        }
+        lines.push(word.to_string());
+    }
+
+    for (index, line) in lines.iter_mut().enumerate() {
+        let last = index + 1 == max_lines;
+        if fits(line) && !(last && truncated) {
+            continue;
+        }
+        while !line.is_empty() && !fits(&format!("{}…", line)) {
+            _ = line.pop();
+        }
+        *line = format!("{}…", line.trim_end());
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::site::tests::page;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    /// A font commonly installed on Linux, used when present.
+    const TEST_FONT: &str =
+        "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf";
+
+    #[test]
+    fn test_card_paths() {
+        let path = card_path(Path::new("blog/post.html"));
+        assert_eq!(path, PathBuf::from("blog/post.card.png"));
+        assert_eq!(card_url(&path), "/blog/post.card.png");
+        assert_eq!(
+            card_url(&card_path(Path::new("index.html"))),
+            "/index.card.png"
+        );
+    }
+
+    #[test]
+    fn test_missing_font() {
+        assert!(SocialCards::new(&SocialCardConfig::default()).is_err());
+        let config = SocialCardConfig {
+            font: Some(PathBuf::from("missing.ttf")),
+            ..SocialCardConfig::default()
+        };
+        assert!(SocialCards::new(&config).is_err());
+    }
+
+    #[test]
+    fn test_attach() -> Result<()> {
+        if !Path::new(TEST_FONT).exists() {
+            eprintln!("skipping: {} is not installed", TEST_FONT);
+            return Ok(());
+        }
+        let temp_dir = TempDir::new().unwrap();
+        let cards = SocialCards::new(&SocialCardConfig {
+            font: Some(PathBuf::from(TEST_FONT)),
+            site_name: Some("Example".to_string()),
+            width: 600,
+            height: 315,
+            ..SocialCardConfig::default()
+        })?;
+
+        let post = page(
+            "blog/post.html",
+            json!({ "title": "Hello", "author": "Ada" }),
+        );
+        let attached = cards.attach(&post, temp_dir.path())?.unwrap();
+        assert_eq!(
+            attached.frontmatter["image"],
+            "/blog/post.card.png"
+        );
+        let card =
+            image::open(temp_dir.path().join("blog/post.card.png"))
+                .unwrap()
+                .to_rgba8();
+        assert_eq!(card.dimensions(), (600, 315));
+        // The title is drawn in white over the dark background
+        assert!(card.pixels().any(|pixel| pixel.0[0] > 200));
+
+        for skipped in [
+            json!({ "title": "x", "image": "/own.png" }),
+            json!({ "title": "x", "utility": true }),
+            json!({ "title": "x", "social_card": false }),
+        ] {
+            assert!(cards
+                .attach(&page("other.html", skipped), temp_dir.path())?
+                .is_none());
+        }
+
+        let long = "word ".repeat(60);
+        let lines =
+            wrap(&cards.font, PxScale::from(35.0), &long, 500.0, 3);
+        assert_eq!(lines.len(), 3);
+        assert!(lines[2].ends_with('…'));
+        assert!(lines.iter().all(|line| {
+            text_width(&cards.font, PxScale::from(35.0), line) <= 500.0
+        }));
+        Ok(())
+    }
+}
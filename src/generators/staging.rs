@@ -0,0 +1,367 @@
+//! # Staged Output
+//!
+//! Writing pages straight into `public/` means a build that fails half
+//! way, or is interrupted, leaves a mix of old and new files behind. A
+//! [`StagedOutput`] gives the build a fresh sibling directory to write
+//! into instead, and only swaps it into place once everything has been
+//! generated. If the build fails, the staging directory is discarded when
+//! the `StagedOutput` is dropped and the previous output is untouched.
+//!
+//! Two swap strategies are available:
+//!
+//! - [`SwapStrategy::Rename`] moves the previous output aside, renames
+//!   the staging directory into place and deletes the old copy. The
+//!   output path stays a plain directory, with a window of a few
+//!   microseconds where it does not exist.
+//! - [`SwapStrategy::Symlink`] keeps each build in its own hidden
+//!   directory and points the output path at it through a symbolic link,
+//!   replaced in a single atomic rename. Readers always see either the
+//!   complete old build or the complete new one. Only available on Unix;
+//!   elsewhere it falls back to `Rename`.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use nucleusflow::core::config::SwapStrategy;
+//! use nucleusflow::generators::staging::StagedOutput;
+//! use std::fs;
+//! use std::path::Path;
+//!
+//! let staged =
+//!     StagedOutput::new(Path::new("public"), SwapStrategy::Rename)
+//!         .unwrap();
+//! fs::write(staged.path().join("index.html"), "<p>Hello</p>").unwrap();
+//! staged.commit().unwrap();
+//! ```
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::core::config::SwapStrategy;
+use crate::{ProcessingError, Result};
+
+/// A staging directory that replaces an output directory on commit.
+///
+/// The staging directory is created next to the target so the final
+/// rename never crosses a filesystem boundary. Dropping a `StagedOutput`
+/// without committing it removes the staging directory.
+#[derive(Debug)]
+pub struct StagedOutput {
+    target: PathBuf,
+    staging: PathBuf,
+    strategy: SwapStrategy,
+    committed: bool,
+}
+
+impl StagedOutput {
+    /// Creates an empty staging directory for `target`.
+    ///
+    /// # Arguments
+    /// * `target` - The output directory the build will replace.
+    /// * `strategy` - How the staging directory is swapped into place.
+    ///
+    /// # Returns
+    /// * `Result<Self>` - The staged output, or an error if the staging
+    ///   directory cannot be created.
+    pub fn new(target: &Path, strategy: SwapStrategy) -> Result<Self> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos());
+        let staging = sibling(
+            target,
+            &format!("build-{}-{}", std::process::id(), nanos),
+        )?;
+        fs::create_dir_all(&staging).map_err(|e| {
+            ProcessingError::io_error(staging.clone(), e)
+        })?;
+
+        Ok(Self {
+            target: target.to_path_buf(),
+            staging,
+            strategy,
+            committed: false,
+        })
+    }
+
+    /// Returns the directory the build should write into.
+    pub fn path(&self) -> &Path {
+        &self.staging
+    }
+
+    /// Swaps the staging directory into place, replacing the previous
+    /// output.
+    ///
+    /// # Returns
+    /// * `Result<()>` - Indicates success, or an error if the swap
+    ///   fails. A failed swap leaves the previous output in place.
+    pub fn commit(mut self) -> Result<()> {
+        match self.strategy {
+            #[cfg(unix)]
+            SwapStrategy::Symlink => self.commit_symlink()?,
+            _ => self.commit_rename()?,
+        }
+        self.committed = true;
+        Ok(())
+    }
+
+    /// Replaces the target by renaming the staging directory over it.
+    fn commit_rename(&self) -> Result<()> {
+        let previous = self.detach_previous()?;
+        if let Err(e) = fs::rename(&self.staging, &self.target) {
+            if let Previous::Directory(backup) = &previous {
+                _ = fs::rename(backup, &self.target);
+            }
+            return Err(ProcessingError::io_error(
+                self.target.clone(),
+                e,
+            ));
+        }
+        self.remove_previous(previous);
+        Ok(())
+    }
+
+    /// Replaces the target by atomically renaming a new symlink over it.
+    #[cfg(unix)]
+    fn commit_symlink(&self) -> Result<()> {
+        let link = sibling(
+            &self.target,
+            &format!("link-{}", std::process::id()),
+        )?;
+        let destination = match self.staging.file_name() {
+            Some(name) => PathBuf::from(name),
+            None => self.staging.clone(),
+        };
+        _ = fs::remove_file(&link);
+        std::os::unix::fs::symlink(&destination, &link)
+            .map_err(|e| ProcessingError::io_error(link.clone(), e))?;
+
+        // A symlink cannot be renamed over a directory, so a plain
+        // output directory from an earlier build is moved aside first.
+        let previous = match fs::symlink_metadata(&self.target) {
+            Ok(metadata) if metadata.file_type().is_symlink() => {
+                Previous::Link(self.link_destination()?)
+            }
+            Ok(_) => self.detach_previous()?,
+            Err(_) => Previous::None,
+        };
+        if let Err(e) = fs::rename(&link, &self.target) {
+            _ = fs::remove_file(&link);
+            if let Previous::Directory(backup) = &previous {
+                _ = fs::rename(backup, &self.target);
+            }
+            return Err(ProcessingError::io_error(
+                self.target.clone(),
+                e,
+            ));
+        }
+        self.remove_previous(previous);
+        Ok(())
+    }
+
+    /// Moves an existing output directory aside, or removes an existing
+    /// symlink, so the target path is free.
+    fn detach_previous(&self) -> Result<Previous> {
+        let metadata = match fs::symlink_metadata(&self.target) {
+            Ok(metadata) => metadata,
+            Err(_) => return Ok(Previous::None),
+        };
+        if metadata.file_type().is_symlink() {
+            let destination = self.link_destination()?;
+            fs::remove_file(&self.target).map_err(|e| {
+                ProcessingError::io_error(self.target.clone(), e)
+            })?;
+            return Ok(Previous::Link(destination));
+        }
+
+        let backup = sibling(
+            &self.target,
+            &format!("old-{}", std::process::id()),
+        )?;
+        if backup.exists() {
+            fs::remove_dir_all(&backup).map_err(|e| {
+                ProcessingError::io_error(backup.clone(), e)
+            })?;
+        }
+        fs::rename(&self.target, &backup).map_err(|e| {
+            ProcessingError::io_error(self.target.clone(), e)
+        })?;
+        Ok(Previous::Directory(backup))
+    }
+
+    /// Returns the directory the target symlink currently points at, if
+    /// it is one of this output's build directories.
+    fn link_destination(&self) -> Result<Option<PathBuf>> {
+        let destination = fs::read_link(&self.target).map_err(|e| {
+            ProcessingError::io_error(self.target.clone(), e)
+        })?;
+        let destination = parent_dir(&self.target).join(destination);
+        let prefix = sibling(&self.target, "build-")?;
+        let is_build = destination
+            .to_str()
+            .zip(prefix.to_str())
+            .map_or(false, |(path, prefix)| path.starts_with(prefix));
+        Ok(if is_build { Some(destination) } else { None })
+    }
+
+    /// Deletes the output replaced by a successful swap.
+    ///
+    /// Failures are logged rather than returned, since the new output is
+    /// already in place.
+    fn remove_previous(&self, previous: Previous) {
+        let path = match previous {
+            Previous::Directory(path) | Previous::Link(Some(path)) => {
+                path
+            }
+            Previous::Link(None) | Previous::None => return,
+        };
+        if path != self.staging {
+            if let Err(e) = fs::remove_dir_all(&path) {
+                tracing::warn!(
+                    "Failed to remove previous output {}: {}",
+                    path.display(),
+                    e
+                );
+            }
+        }
+    }
+}
+
+impl Drop for StagedOutput {
+    fn drop(&mut self) {
+        if !self.committed {
+            _ = fs::remove_dir_all(&self.staging);
+        }
+    }
+}
+
+/// The output a swap replaced.
+#[derive(Debug)]
+enum Previous {
+    /// Nothing existed at the target path
+    None,
+    /// A plain directory, moved aside to this path
+    Directory(PathBuf),
+    /// A symlink, with its build directory if it pointed at one
+    Link(Option<PathBuf>),
+}
+
+/// Returns the directory containing `target`.
+fn parent_dir(target: &Path) -> &Path {
+    match target.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    }
+}
+
+/// Returns a hidden path next to `target`, named after it.
+fn sibling(target: &Path, suffix: &str) -> Result<PathBuf> {
+    let name = target
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| {
+            ProcessingError::configuration(
+                "Output directory must have a UTF-8 file name",
+                Some(target.to_path_buf()),
+                None,
+            )
+        })?;
+    Ok(parent_dir(target).join(format!(".{}.{}", name, suffix)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entries(dir: &Path) -> Vec<String> {
+        let mut names: Vec<_> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| {
+                entry
+                    .unwrap()
+                    .file_name()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_rename_swap() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("public");
+        fs::create_dir(&target).unwrap();
+        fs::write(target.join("stale.html"), "old").unwrap();
+
+        let staged =
+            StagedOutput::new(&target, SwapStrategy::Rename).unwrap();
+        fs::write(staged.path().join("index.html"), "new").unwrap();
+        assert!(target.join("stale.html").exists());
+        staged.commit().unwrap();
+
+        assert_eq!(entries(&target), ["index.html"]);
+        assert_eq!(entries(temp_dir.path()), ["public"]);
+    }
+
+    #[test]
+    fn test_dropped_stage_keeps_previous_output() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("public");
+        fs::create_dir(&target).unwrap();
+        fs::write(target.join("index.html"), "old").unwrap();
+
+        {
+            let staged =
+                StagedOutput::new(&target, SwapStrategy::Rename)
+                    .unwrap();
+            fs::write(staged.path().join("index.html"), "half")
+                .unwrap();
+        }
+
+        assert_eq!(
+            fs::read_to_string(target.join("index.html")).unwrap(),
+            "old"
+        );
+        assert_eq!(entries(temp_dir.path()), ["public"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_swap() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("public");
+        fs::create_dir(&target).unwrap();
+        fs::write(target.join("index.html"), "v0").unwrap();
+
+        for version in ["v1", "v2"] {
+            let staged =
+                StagedOutput::new(&target, SwapStrategy::Symlink)
+                    .unwrap();
+            fs::write(staged.path().join("index.html"), version)
+                .unwrap();
+            staged.commit().unwrap();
+
+            assert!(fs::symlink_metadata(&target)
+                .unwrap()
+                .file_type()
+                .is_symlink());
+            assert_eq!(
+                fs::read_to_string(target.join("index.html")).unwrap(),
+                version
+            );
+            // Only the link and the build it points at remain
+            assert_eq!(entries(temp_dir.path()).len(), 2);
+        }
+
+        // Switching back to a plain directory replaces the link
+        let staged =
+            StagedOutput::new(&target, SwapStrategy::Rename).unwrap();
+        fs::write(staged.path().join("index.html"), "v3").unwrap();
+        staged.commit().unwrap();
+        assert!(target.is_dir());
+        assert_eq!(entries(temp_dir.path()), ["public"]);
+    }
+}
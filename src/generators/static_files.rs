@@ -0,0 +1,364 @@
+//! # Static File Passthrough
+//!
+//! This module copies the contents of a site's `static/` directory verbatim
+//! into the output directory. Files are selected with glob-based include and
+//! exclude patterns, matched against paths relative to the static directory,
+//! and dotfiles are skipped unless explicitly enabled. Symlinks are handled
+//! according to a [`SymlinkPolicy`], followed by default.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use nucleusflow::core::config::StaticConfig;
+//! use nucleusflow::generators::static_files::StaticFileCopier;
+//! use std::path::Path;
+//!
+//! let config = StaticConfig {
+//!     exclude: vec!["**/*.psd".to_string()],
+//!     ..StaticConfig::default()
+//! };
+//! let copier = StaticFileCopier::new(&config).unwrap();
+//! let copied = copier.copy_to(Path::new("public")).unwrap();
+//! println!("Copied {} static files", copied.len());
+//! ```
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use walkdir::{DirEntry, WalkDir};
+
+use crate::core::config::{StaticConfig, SymlinkPolicy};
+use crate::core::ignore::IgnoreRules;
+use crate::{ProcessingError, Result};
+
+/// Copies static files to the output directory according to a
+/// [`StaticConfig`].
+#[derive(Debug, Clone)]
+pub struct StaticFileCopier {
+    source_dir: PathBuf,
+    include: GlobSet,
+    exclude: GlobSet,
+    include_dotfiles: bool,
+    ignore_rules: Option<IgnoreRules>,
+    symlinks: SymlinkPolicy,
+}
+
+impl StaticFileCopier {
+    /// Creates a new copier, compiling the configured glob patterns.
+    ///
+    /// # Arguments
+    /// * `config` - The static file configuration.
+    ///
+    /// # Returns
+    /// * `Result<Self>` - The copier, or a configuration error if a pattern
+    ///   is invalid.
+    pub fn new(config: &StaticConfig) -> Result<Self> {
+        Ok(Self {
+            source_dir: config.dir.clone(),
+            include: build_glob_set(&config.include)?,
+            exclude: build_glob_set(&config.exclude)?,
+            include_dotfiles: config.include_dotfiles,
+            ignore_rules: None,
+            symlinks: SymlinkPolicy::default(),
+        })
+    }
+
+    /// Excludes files matched by project ignore rules, such as those
+    /// loaded from `.nucleusignore`.
+    pub fn with_ignore_rules(mut self, rules: IgnoreRules) -> Self {
+        self.ignore_rules = Some(rules);
+        self
+    }
+
+    /// Sets how symlinks in the static directory are handled.
+    pub fn with_symlink_policy(
+        mut self,
+        policy: SymlinkPolicy,
+    ) -> Self {
+        self.symlinks = policy;
+        self
+    }
+
+    /// Returns the directory static files are copied from.
+    pub fn source_dir(&self) -> &Path {
+        &self.source_dir
+    }
+
+    /// Returns `true` if a path relative to the static directory should be
+    /// copied.
+    ///
+    /// # Arguments
+    /// * `relative` - The path relative to the static directory.
+    pub fn is_included(&self, relative: &Path) -> bool {
+        if !self.include_dotfiles && has_dot_component(relative) {
+            return false;
+        }
+        self.include.is_match(relative)
+            && !self.exclude.is_match(relative)
+    }
+
+    /// Returns `true` if a walked entry is excluded by the ignore rules.
+    fn is_ignored_entry(&self, entry: &DirEntry) -> bool {
+        self.ignore_rules.as_ref().map_or(false, |rules| {
+            rules.is_ignored(entry.path(), entry.file_type().is_dir())
+        })
+    }
+
+    /// Copies all matching files into `output_dir`, preserving their
+    /// relative paths.
+    ///
+    /// A missing static directory is not an error; nothing is copied.
+    /// Following symlinks, a symlinked directory pointing back at one of
+    /// its ancestors fails the copy.
+    ///
+    /// # Arguments
+    /// * `output_dir` - The destination directory.
+    ///
+    /// # Returns
+    /// * `Result<Vec<PathBuf>>` - The paths of the files written.
+    pub fn copy_to(&self, output_dir: &Path) -> Result<Vec<PathBuf>> {
+        let mut copied = Vec::new();
+        if !self.source_dir.is_dir() {
+            return Ok(copied);
+        }
+
+        let walker = WalkDir::new(&self.source_dir)
+            .follow_links(self.symlinks == SymlinkPolicy::Follow)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|entry| {
+                entry.depth() == 0
+                    || ((self.include_dotfiles || !is_dot_entry(entry))
+                        && !self.is_ignored_entry(entry))
+            });
+
+        for entry in walker {
+            let entry = entry.map_err(|e| match e.loop_ancestor() {
+                Some(ancestor) => symlink_cycle(
+                    e.path().unwrap_or(&self.source_dir),
+                    ancestor,
+                ),
+                None => ProcessingError::content_processing(
+                    "Failed to read static directory",
+                    Some(Box::new(e)),
+                ),
+            })?;
+            if entry.path_is_symlink()
+                && !self.symlinks.allows(entry.path())?
+            {
+                continue;
+            }
+            if !entry.file_type().is_file() {
+                continue;
+            }
+
+            let relative = entry
+                .path()
+                .strip_prefix(&self.source_dir)
+                .map_err(|e| {
+                    ProcessingError::content_processing(
+                        "Failed to determine relative static path",
+                        Some(Box::new(e)),
+                    )
+                })?;
+            if !self.is_included(relative) {
+                continue;
+            }
+
+            let target = output_dir.join(relative);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).map_err(|e| {
+                    ProcessingError::io_error(parent.to_path_buf(), e)
+                })?;
+            }
+            _ = fs::copy(entry.path(), &target).map_err(|e| {
+                ProcessingError::io_error(entry.path().to_path_buf(), e)
+            })?;
+            copied.push(target);
+        }
+
+        Ok(copied)
+    }
+}
+
+/// Returns the error for a symlinked directory pointing back at one of
+/// its ancestors.
+fn symlink_cycle(link: &Path, ancestor: &Path) -> ProcessingError {
+    ProcessingError::file_operation(
+        link,
+        format!(
+            "Symlink cycle: the link points back at '{}'",
+            ancestor.display()
+        ),
+        None,
+    )
+}
+
+/// Compiles a list of glob patterns into a single matcher.
+fn build_glob_set(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| {
+                ProcessingError::configuration(
+                    format!(
+                        "Invalid static file pattern '{}': {}",
+                        pattern, e
+                    ),
+                    None,
+                    None,
+                )
+            })?;
+        _ = builder.add(glob);
+    }
+    builder.build().map_err(|e| {
+        ProcessingError::configuration(
+            format!("Failed to compile static file patterns: {}", e),
+            None,
+            None,
+        )
+    })
+}
+
+/// Returns `true` if a directory entry's name starts with a dot.
+fn is_dot_entry(entry: &DirEntry) -> bool {
+    entry.file_name().to_string_lossy().starts_with('.')
+}
+
+/// Returns `true` if any component of a relative path starts with a dot.
+fn has_dot_component(path: &Path) -> bool {
+    path.components().any(|component| {
+        component.as_os_str().to_string_lossy().starts_with('.')
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn static_tree() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        for file in [
+            "robots.txt",
+            "css/site.css",
+            "css/drafts/wip.css",
+            "img/logo.png",
+            ".env",
+            ".well-known/security.txt",
+        ] {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, file).unwrap();
+        }
+        temp_dir
+    }
+
+    #[test]
+    fn test_copy_defaults_skip_dotfiles() {
+        let source = static_tree();
+        let output = TempDir::new().unwrap();
+        let config = StaticConfig {
+            dir: source.path().to_path_buf(),
+            ..StaticConfig::default()
+        };
+
+        let copied = StaticFileCopier::new(&config)
+            .unwrap()
+            .copy_to(output.path())
+            .unwrap();
+
+        assert_eq!(copied.len(), 4);
+        assert_eq!(
+            fs::read_to_string(output.path().join("css/site.css"))
+                .unwrap(),
+            "css/site.css"
+        );
+        assert!(!output.path().join(".env").exists());
+        assert!(!output.path().join(".well-known").exists());
+    }
+
+    #[test]
+    fn test_include_exclude_patterns() {
+        let config = StaticConfig {
+            include: vec!["css/**".to_string(), "*.txt".to_string()],
+            exclude: vec!["**/drafts/**".to_string()],
+            ..StaticConfig::default()
+        };
+        let copier = StaticFileCopier::new(&config).unwrap();
+
+        assert!(copier.is_included(Path::new("css/site.css")));
+        assert!(copier.is_included(Path::new("robots.txt")));
+        assert!(!copier.is_included(Path::new("css/drafts/wip.css")));
+        assert!(!copier.is_included(Path::new("img/logo.png")));
+        // `*` does not cross directory separators
+        assert!(!copier.is_included(Path::new("docs/notes.txt")));
+    }
+
+    #[test]
+    fn test_include_dotfiles() {
+        let source = static_tree();
+        let output = TempDir::new().unwrap();
+        let config = StaticConfig {
+            dir: source.path().to_path_buf(),
+            include_dotfiles: true,
+            ..StaticConfig::default()
+        };
+
+        let copied = StaticFileCopier::new(&config)
+            .unwrap()
+            .copy_to(output.path())
+            .unwrap();
+
+        assert_eq!(copied.len(), 6);
+        assert!(output
+            .path()
+            .join(".well-known/security.txt")
+            .exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_policy() {
+        use std::os::unix::fs::symlink;
+
+        let source = static_tree();
+        let shared = TempDir::new().unwrap();
+        fs::write(shared.path().join("shared.css"), "shared").unwrap();
+        symlink(shared.path(), source.path().join("shared")).unwrap();
+        let copy = |policy| {
+            let output = TempDir::new().unwrap();
+            let config = StaticConfig {
+                dir: source.path().to_path_buf(),
+                ..StaticConfig::default()
+            };
+            StaticFileCopier::new(&config)
+                .unwrap()
+                .with_symlink_policy(policy)
+                .copy_to(output.path())
+                .map(|copied| copied.len())
+        };
+
+        assert_eq!(copy(SymlinkPolicy::Follow).unwrap(), 5);
+        assert_eq!(copy(SymlinkPolicy::Ignore).unwrap(), 4);
+        assert!(copy(SymlinkPolicy::Error).is_err());
+
+        symlink(source.path(), source.path().join("css/loop")).unwrap();
+        let error = copy(SymlinkPolicy::Follow).unwrap_err();
+        assert!(error.to_string().contains("Symlink cycle"));
+        assert_eq!(copy(SymlinkPolicy::Ignore).unwrap(), 4);
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        let config = StaticConfig {
+            include: vec!["css/[".to_string()],
+            ..StaticConfig::default()
+        };
+        assert!(StaticFileCopier::new(&config).is_err());
+    }
+}
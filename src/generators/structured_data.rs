@@ -0,0 +1,465 @@
+//! # Structured Data
+//!
+//! Adds schema.org JSON-LD to each page's `<head>`, so search engines
+//! can show articles and their place in the site. A page gets:
+//!
+//! - an article, for pages with a `date`: a `BlogPosting`, or the
+//!   site's `article_type` (see [`ARTICLE_TYPES`]), and
+//! - a `BreadcrumbList` leading from the home page through the page's
+//!   parent directories to the page, for pages below the root, unless
+//!   the site's `breadcrumbs` is `false`.
+//!
+//! A page's `schema_type` frontmatter overrides its type: one of the
+//! article types, `WebPage`, or `none` to leave the page without
+//! structured data. Articles are filled in from the page's frontmatter
+//! and the site's [`SeoConfig`]:
+//!
+//! | Property | Source |
+//! |----------|--------|
+//! | `headline` | `title` |
+//! | `description` | `description`, or `summary` |
+//! | `image` | `image`, or the site's `default_image` |
+//! | `datePublished`, `dateModified` | `date`, and `lastmod` |
+//! | `author` | `author`, a name or a list of names |
+//! | `keywords` | `tags` |
+//! | `publisher` | the site's `site_name` |
+//! | `url` | the page's URL under the site's `base_url` |
+//!
+//! Utility pages, pages without a `</head>`, and pages whose template
+//! already includes JSON-LD are not changed.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::core::config::SeoConfig;
+//! use nucleusflow::core::traits::PostProcessor;
+//! use nucleusflow::generators::structured_data::StructuredData;
+//! use nucleusflow::site::Page;
+//! use serde_json::json;
+//!
+//! let data = StructuredData::new(SeoConfig {
+//!     base_url: Some("https://example.com".to_string()),
+//!     ..SeoConfig::default()
+//! });
+//! let page = Page::new(
+//!     "content/blog/hello.md",
+//!     "blog/hello.html",
+//!     json!({ "title": "Hello", "date": "2024-03-01" })
+//!         .as_object()
+//!         .cloned()
+//!         .unwrap(),
+//! );
+//! let graph = data.graph(&page).unwrap();
+//! assert_eq!(graph[0]["@type"], "BlogPosting");
+//! assert_eq!(graph[1]["@type"], "BreadcrumbList");
+//!
+//! let html = data.process("<head></head>", &page).unwrap();
+//! assert!(html.contains(r#"<script type="application/ld+json">"#));
+//! ```
+
+use serde_json::{Map, Value as JsonValue};
+
+use crate::core::config::SeoConfig;
+use crate::core::error::{ProcessingError, Result};
+use crate::core::traits::PostProcessor;
+use crate::generators::seo::absolute_url;
+use crate::site::{Breadcrumb, Page, HOME_TITLE};
+
+/// The schema.org article types a site or page can use.
+pub const ARTICLE_TYPES: [&str; 4] =
+    ["Article", "BlogPosting", "NewsArticle", "TechArticle"];
+
+/// The type of dated pages when the site does not set one.
+const DEFAULT_ARTICLE_TYPE: &str = "BlogPosting";
+
+/// The `schema_type` of pages that are neither articles nor `none`.
+const WEB_PAGE_TYPE: &str = "WebPage";
+
+/// The `schema_type` that leaves a page without structured data.
+const NO_SCHEMA_TYPE: &str = "none";
+
+/// Adds schema.org JSON-LD to every page.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StructuredData {
+    config: SeoConfig,
+}
+
+impl StructuredData {
+    /// Creates a post-processor using the site's SEO settings.
+    pub fn new(config: SeoConfig) -> Self {
+        Self { config }
+    }
+
+    /// Returns the structured data for a page.
+    ///
+    /// # Arguments
+    /// * `page` - The page the data describes.
+    ///
+    /// # Returns
+    /// * `Result<Vec<JsonValue>>` - The page's article or web page, then
+    ///   its breadcrumbs, each present only if the page has one; or an
+    ///   error if the page's `schema_type` is not a known type.
+    pub fn graph(&self, page: &Page) -> Result<Vec<JsonValue>> {
+        if page.is_utility() {
+            return Ok(Vec::new());
+        }
+        let schema_type = match string(page, "schema_type") {
+            Some(schema_type) => Some(schema_type),
+            None if page.date().is_some() => Some(
+                self.config
+                    .article_type
+                    .as_deref()
+                    .unwrap_or(DEFAULT_ARTICLE_TYPE),
+            ),
+            None => None,
+        };
+
+        let mut graph = Vec::new();
+        match schema_type {
+            Some(NO_SCHEMA_TYPE) => return Ok(graph),
+            Some(WEB_PAGE_TYPE) => graph.push(self.web_page(page)),
+            Some(kind) if ARTICLE_TYPES.contains(&kind) => {
+                graph.push(self.article(page, kind))
+            }
+            Some(kind) => {
+                return Err(ProcessingError::validation(
+                    format!(
+                        "Unknown schema_type '{}', expected one of: {}, {}, {}",
+                        kind,
+                        ARTICLE_TYPES.join(", "),
+                        WEB_PAGE_TYPE,
+                        NO_SCHEMA_TYPE
+                    ),
+                    Some(page.source.display().to_string()),
+                ))
+            }
+            None => {}
+        }
+        graph.extend(self.breadcrumbs(page));
+        Ok(graph)
+    }
+
+    /// Returns a page as an article of a type.
+    fn article(&self, page: &Page, kind: &str) -> JsonValue {
+        let mut article = self.web_page(page);
+        let fields = [
+            ("@type", Some(JsonValue::from(kind))),
+            ("headline", string(page, "title").map(JsonValue::from)),
+            (
+                "image",
+                string(page, "image")
+                    .or(self.config.default_image.as_deref())
+                    .map(|image| self.absolute_url(image).into()),
+            ),
+            ("datePublished", page.date().map(JsonValue::from)),
+            ("dateModified", page.lastmod().map(JsonValue::from)),
+            ("author", authors(page)),
+            ("keywords", strings(page, "tags").map(JsonValue::from)),
+            (
+                "publisher",
+                self.config.site_name.as_ref().map(|name| {
+                    serde_json::json!({
+                        "@type": "Organization",
+                        "name": name,
+                    })
+                }),
+            ),
+        ];
+        if let JsonValue::Object(map) = &mut article {
+            _ = map.remove("name");
+            for (key, value) in fields {
+                if let Some(value) = value {
+                    _ = map.insert(key.to_string(), value);
+                }
+            }
+        }
+        article
+    }
+
+    /// Returns a page as a web page.
+    fn web_page(&self, page: &Page) -> JsonValue {
+        let mut map = Map::new();
+        let fields = [
+            ("@type", Some(WEB_PAGE_TYPE)),
+            ("name", string(page, "title")),
+            (
+                "description",
+                string(page, "description")
+                    .or_else(|| string(page, "summary")),
+            ),
+        ];
+        for (key, value) in fields {
+            if let Some(value) = value {
+                _ = map.insert(key.to_string(), value.into());
+            }
+        }
+        _ = map.insert(
+            "url".to_string(),
+            self.absolute_url(&page.url).into(),
+        );
+        JsonValue::Object(map)
+    }
+
+    /// Returns the breadcrumbs from the home page to a page, for pages
+    /// below the root, unless the site turns them off.
+    fn breadcrumbs(&self, page: &Page) -> Option<JsonValue> {
+        if self.config.breadcrumbs == Some(false) {
+            return None;
+        }
+        let home =
+            self.config.site_name.as_deref().unwrap_or(HOME_TITLE);
+        let trail = Breadcrumb::trail(page, home, |_| None);
+        if trail.len() < 2 {
+            return None;
+        }
+
+        let items: Vec<JsonValue> = trail
+            .into_iter()
+            .enumerate()
+            .map(|(index, crumb)| {
+                serde_json::json!({
+                    "@type": "ListItem",
+                    "position": index + 1,
+                    "name": crumb.title,
+                    "item": self.absolute_url(&crumb.url),
+                })
+            })
+            .collect();
+        Some(serde_json::json!({
+            "@type": "BreadcrumbList",
+            "itemListElement": items,
+        }))
+    }
+
+    /// Makes a root-relative URL absolute with the site's base URL.
+    fn absolute_url(&self, url: &str) -> String {
+        absolute_url(self.config.base_url.as_deref(), url)
+    }
+}
+
+impl PostProcessor for StructuredData {
+    fn name(&self) -> &str {
+        "structured-data"
+    }
+
+    fn process(&self, html: &str, page: &Page) -> Result<String> {
+        let lower = html.to_ascii_lowercase();
+        let index = match lower.rfind("</head>") {
+            Some(index) => index,
+            None => return Ok(html.to_string()),
+        };
+        if lower.contains("application/ld+json") {
+            return Ok(html.to_string());
+        }
+        let graph = self.graph(page)?;
+        if graph.is_empty() {
+            return Ok(html.to_string());
+        }
+
+        let json = serde_json::json!({
+            "@context": "https://schema.org",
+            "@graph": graph,
+        })
+        .to_string()
+        .replace("</", "<\\/");
+        let script = format!(
+            r#"<script type="application/ld+json">{}</script>"#,
+            json
+        );
+        let mut output =
+            String::with_capacity(html.len() + script.len());
+        output.push_str(&html[..index]);
+        output.push_str(&script);
+        output.push_str(&html[index..]);
+        Ok(output)
+    }
+}
+
+/// Returns a non-empty string frontmatter value, trimmed.
+fn string<'a>(page: &'a Page, key: &str) -> Option<&'a str> {
+    page.frontmatter
+        .get(key)
+        .and_then(JsonValue::as_str)
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+/// Returns a frontmatter value that is a string or a list of strings
+/// as a list.
+fn strings(page: &Page, key: &str) -> Option<Vec<String>> {
+    let values: Vec<String> = match page.frontmatter.get(key)? {
+        JsonValue::String(value) => vec![value.clone()],
+        JsonValue::Array(values) => values
+            .iter()
+            .filter_map(JsonValue::as_str)
+            .map(String::from)
+            .collect(),
+        _ => return None,
+    };
+    (!values.is_empty()).then(|| values)
+}
+
+/// Returns a page's `author` names as a person or a list of people.
+fn authors(page: &Page) -> Option<JsonValue> {
+    let mut people: Vec<JsonValue> = strings(page, "author")?
+        .into_iter()
+        .map(|name| serde_json::json!({ "@type": "Person", "name": name }))
+        .collect();
+    Some(if people.len() == 1 {
+        people.remove(0)
+    } else {
+        JsonValue::Array(people)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::site::tests::page;
+    use serde_json::json;
+
+    fn config() -> SeoConfig {
+        SeoConfig {
+            base_url: Some("https://example.com".to_string()),
+            site_name: Some("Example".to_string()),
+            default_image: Some("/card.png".to_string()),
+            ..SeoConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_article() {
+        let post = page(
+            "blog/hello.html",
+            json!({
+                "title": "Hello",
+                "summary": "A post",
+                "date": "2024-03-01",
+                "lastmod": "2024-03-05",
+                "author": ["Ada", "Grace"],
+                "tags": ["rust"],
+                "layout": "post",
+            }),
+        );
+        let graph = StructuredData::new(config()).graph(&post).unwrap();
+        assert_eq!(
+            graph[0],
+            json!({
+                "@type": "BlogPosting",
+                "headline": "Hello",
+                "description": "A post",
+                "image": "https://example.com/card.png",
+                "datePublished": "2024-03-01",
+                "dateModified": "2024-03-05",
+                "author": [
+                    { "@type": "Person", "name": "Ada" },
+                    { "@type": "Person", "name": "Grace" },
+                ],
+                "keywords": ["rust"],
+                "publisher": { "@type": "Organization", "name": "Example" },
+                "url": "https://example.com/blog/hello.html",
+            })
+        );
+        assert_eq!(
+            graph[1]["itemListElement"],
+            json!([
+                {
+                    "@type": "ListItem",
+                    "position": 1,
+                    "name": "Example",
+                    "item": "https://example.com/",
+                },
+                {
+                    "@type": "ListItem",
+                    "position": 2,
+                    "name": "blog",
+                    "item": "https://example.com/blog/",
+                },
+                {
+                    "@type": "ListItem",
+                    "position": 3,
+                    "name": "Hello",
+                    "item": "https://example.com/blog/hello.html",
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_schema_types() {
+        let site = StructuredData::new(SeoConfig {
+            article_type: Some("Article".to_string()),
+            ..SeoConfig::default()
+        });
+        let types = |frontmatter: JsonValue| {
+            site.graph(&page("about.html", frontmatter)).map(|graph| {
+                graph
+                    .iter()
+                    .map(|item| {
+                        item["@type"].as_str().unwrap().to_string()
+                    })
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        assert_eq!(types(json!({})).unwrap(), ["BreadcrumbList"]);
+        assert_eq!(
+            types(json!({ "date": "2024-03-01" })).unwrap(),
+            ["Article", "BreadcrumbList"]
+        );
+        assert_eq!(
+            types(json!({ "date": "2024-03-01", "schema_type": "TechArticle" }))
+                .unwrap(),
+            ["TechArticle", "BreadcrumbList"]
+        );
+        assert_eq!(
+            types(json!({ "schema_type": "WebPage" })).unwrap(),
+            ["WebPage", "BreadcrumbList"]
+        );
+        assert!(types(
+            json!({ "date": "2024-03-01", "schema_type": "none" })
+        )
+        .unwrap()
+        .is_empty());
+        assert!(types(json!({ "schema_type": "Recipe" })).is_err());
+
+        let home = page("index.html", json!({ "date": "2024-03-01" }));
+        assert_eq!(site.graph(&home).unwrap().len(), 1);
+        assert!(site
+            .graph(&page("404.html", json!({ "date": "2024-03-01" })))
+            .unwrap()
+            .is_empty());
+
+        let site = StructuredData::new(SeoConfig {
+            breadcrumbs: Some(false),
+            ..SeoConfig::default()
+        });
+        assert!(site
+            .graph(&page("docs/about.html", json!({})))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_process() {
+        let data = StructuredData::new(SeoConfig::default());
+        let post = page(
+            "blog/post.html",
+            json!({ "title": "</script><b>", "date": "2024-03-01" }),
+        );
+        let html =
+            data.process("<HEAD></HEAD><p>x</p>", &post).unwrap();
+        assert!(html.starts_with(
+            r#"<HEAD><script type="application/ld+json">{"@context":"https://schema.org""#
+        ));
+        assert!(html.contains(r#""headline":"<\/script><b>""#));
+        assert!(html.ends_with("</script></HEAD><p>x</p>"));
+
+        let existing = r#"<head><script type="application/ld+json">{}</script></head>"#;
+        assert_eq!(data.process(existing, &post).unwrap(), existing);
+        assert_eq!(
+            data.process("<p>x</p>", &post).unwrap(),
+            "<p>x</p>"
+        );
+    }
+}
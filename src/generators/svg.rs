@@ -0,0 +1,225 @@
+//! # SVG Optimisation
+//!
+//! This module provides a lightweight SVG cleaner for the asset pipeline.
+//! Standalone SVG files are not touched by HTML minification, yet editors
+//! such as Inkscape, Illustrator and Sketch embed large amounts of metadata
+//! in them. The optimiser removes:
+//!
+//! - XML comments, processing instructions and doctype declarations
+//! - `<metadata>` blocks
+//! - Elements and attributes in editor-specific namespaces
+//! - Whitespace-only text between elements
+//!
+//! The document is parsed with an XML reader rather than pattern matching, so
+//! attribute values and text content are preserved exactly.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::generators::svg::optimize_svg;
+//!
+//! let svg = r#"<?xml version="1.0"?>
+//! <!-- Generator: Inkscape -->
+//! <svg xmlns="http://www.w3.org/2000/svg" xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape" inkscape:version="1.0">
+//!   <metadata>editor data</metadata>
+//!   <rect width="10" height="10"/>
+//! </svg>"#;
+//!
+//! let optimized = optimize_svg(svg).unwrap();
+//! assert_eq!(
+//!     optimized,
+//!     r#"<svg xmlns="http://www.w3.org/2000/svg"><rect width="10" height="10"/></svg>"#
+//! );
+//! ```
+
+use std::path::Path;
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::{Reader, Writer};
+
+use crate::{ProcessingError, Result};
+
+/// Namespace prefixes used by vector editors for their private data.
+const EDITOR_PREFIXES: &[&str] =
+    &["inkscape", "sodipodi", "sketch", "serif", "i"];
+
+/// Elements removed from the document along with their content.
+const REMOVED_ELEMENTS: &[&str] = &["metadata"];
+
+/// Returns `true` if the path points to an SVG file.
+pub fn is_svg(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.eq_ignore_ascii_case("svg"))
+        .unwrap_or(false)
+}
+
+/// Optimises an SVG document by removing comments, metadata and editor
+/// cruft.
+///
+/// # Arguments
+/// * `content` - The SVG source.
+///
+/// # Returns
+/// * `Result<String>` - The optimised SVG, or an error if the document is
+///   not well-formed XML.
+pub fn optimize_svg(content: &str) -> Result<String> {
+    let mut reader = Reader::from_str(content);
+    let mut writer = Writer::new(Vec::with_capacity(content.len()));
+    // Depth of the element subtree currently being skipped, if any
+    let mut skip_depth = 0usize;
+
+    loop {
+        let event = reader.read_event().map_err(|e| {
+            ProcessingError::content_processing(
+                format!(
+                    "Invalid SVG at byte {}: {}",
+                    reader.buffer_position(),
+                    e
+                ),
+                None,
+            )
+        })?;
+
+        match event {
+            Event::Eof => break,
+            Event::Start(start) => {
+                if skip_depth > 0 || is_removed_element(&start) {
+                    skip_depth += 1;
+                    continue;
+                }
+                write_event(
+                    &mut writer,
+                    Event::Start(clean_start(&start)?),
+                )?;
+            }
+            Event::Empty(start) => {
+                if skip_depth > 0 || is_removed_element(&start) {
+                    continue;
+                }
+                write_event(
+                    &mut writer,
+                    Event::Empty(clean_start(&start)?),
+                )?;
+            }
+            Event::End(end) => {
+                if skip_depth > 0 {
+                    skip_depth -= 1;
+                    continue;
+                }
+                write_event(&mut writer, Event::End(end))?;
+            }
+            Event::Text(text) => {
+                if skip_depth > 0
+                    || text.iter().all(u8::is_ascii_whitespace)
+                {
+                    continue;
+                }
+                write_event(&mut writer, Event::Text(text))?;
+            }
+            Event::CData(data) => {
+                if skip_depth == 0 {
+                    write_event(&mut writer, Event::CData(data))?;
+                }
+            }
+            Event::Comment(_)
+            | Event::Decl(_)
+            | Event::PI(_)
+            | Event::DocType(_) => {}
+        }
+    }
+
+    String::from_utf8(writer.into_inner()).map_err(|e| {
+        ProcessingError::content_processing(
+            "Optimised SVG is not valid UTF-8",
+            Some(Box::new(e)),
+        )
+    })
+}
+
+/// Returns `true` if the element should be dropped with its content.
+fn is_removed_element(start: &BytesStart<'_>) -> bool {
+    let name = start.name();
+    let name = String::from_utf8_lossy(name.as_ref());
+    REMOVED_ELEMENTS.contains(&name.as_ref()) || is_editor_name(&name)
+}
+
+/// Returns `true` if a qualified name belongs to an editor namespace.
+fn is_editor_name(name: &str) -> bool {
+    match name.split_once(':') {
+        Some(("xmlns", prefix)) => EDITOR_PREFIXES.contains(&prefix),
+        Some((prefix, _)) => EDITOR_PREFIXES.contains(&prefix),
+        None => false,
+    }
+}
+
+/// Copies a start tag, dropping editor-specific attributes.
+fn clean_start(start: &BytesStart<'_>) -> Result<BytesStart<'static>> {
+    let name =
+        String::from_utf8_lossy(start.name().as_ref()).into_owned();
+    let mut cleaned = BytesStart::new(name);
+    for attribute in start.attributes() {
+        let attribute = attribute.map_err(|e| {
+            ProcessingError::content_processing(
+                format!("Invalid SVG attribute: {}", e),
+                None,
+            )
+        })?;
+        let key = String::from_utf8_lossy(attribute.key.as_ref());
+        if !is_editor_name(&key) {
+            cleaned.push_attribute(attribute);
+        }
+    }
+    Ok(cleaned.into_owned())
+}
+
+/// Writes an event, mapping writer errors to `ProcessingError`.
+fn write_event(
+    writer: &mut Writer<Vec<u8>>,
+    event: Event<'_>,
+) -> Result<()> {
+    writer.write_event(event).map_err(|e| {
+        ProcessingError::content_processing(
+            "Failed to write optimised SVG",
+            Some(Box::new(e)),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_svg() {
+        assert!(is_svg(Path::new("icons/logo.SVG")));
+        assert!(!is_svg(Path::new("logo.png")));
+    }
+
+    #[test]
+    fn test_strips_editor_cruft() {
+        let svg = r##"<svg xmlns="http://www.w3.org/2000/svg" xmlns:sodipodi="http://sodipodi.sourceforge.net/DTD/sodipodi-0.dtd">
+  <sodipodi:namedview pagecolor="#ffffff"><inkscape:grid/></sodipodi:namedview>
+  <!-- comment -->
+  <g sodipodi:nodetypes="cc" id="layer1"><path d="M0 0L10 10"/></g>
+</svg>"##;
+        let optimized = optimize_svg(svg).unwrap();
+        assert_eq!(
+            optimized,
+            r#"<svg xmlns="http://www.w3.org/2000/svg"><g id="layer1"><path d="M0 0L10 10"/></g></svg>"#
+        );
+    }
+
+    #[test]
+    fn test_preserves_text_and_cdata() {
+        let svg = r#"<svg><title>A &amp; B</title><style><![CDATA[.a{fill:red}]]></style></svg>"#;
+        let optimized = optimize_svg(svg).unwrap();
+        assert!(optimized.contains("<title>A &amp; B</title>"));
+        assert!(optimized.contains("<![CDATA[.a{fill:red}]]>"));
+    }
+
+    #[test]
+    fn test_invalid_svg() {
+        assert!(optimize_svg("<svg><g></svg>").is_err());
+    }
+}
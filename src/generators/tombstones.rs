@@ -0,0 +1,251 @@
+//! # Content Tombstones
+//!
+//! Keeps the URLs of deleted pages answering instead of turning into
+//! 404s. Each build records the pages it generated in a manifest in the
+//! output directory; the next build compares its pages against that
+//! manifest and writes a small "410 Gone" stub at the path of every page
+//! that has disappeared, telling readers the page was removed on purpose
+//! and asking crawlers not to index it.
+//!
+//! Removed pages stay in the manifest, so their stubs are written again
+//! on every later build until a page or file takes their path back:
+//!
+//! ```json
+//! {
+//!   "pages": ["about.html", "index.html"],
+//!   "removed": ["old-post.html"]
+//! }
+//! ```
+//!
+//! Static hosts serve the stubs with status 200; the `removed` list can
+//! be used to configure a host to answer those paths with status 410.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ProcessingError, Result};
+
+/// The name of the manifest file written to the output directory.
+pub const MANIFEST_FILE_NAME: &str = "pages-manifest.json";
+
+/// The pages a build generated and the pages it wrote tombstones for.
+#[derive(
+    Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(default)]
+pub struct PageManifest {
+    /// Output paths of the generated pages, relative to the output
+    /// directory and using `/`
+    pub pages: BTreeSet<String>,
+
+    /// Output paths of removed pages that have a tombstone
+    pub removed: BTreeSet<String>,
+}
+
+impl PageManifest {
+    /// Reads the manifest left in an output directory by the previous
+    /// build.
+    ///
+    /// # Arguments
+    /// * `output_dir` - The output directory of the previous build.
+    ///
+    /// # Returns
+    /// * `Result<Self>` - The manifest, or an empty manifest if there is
+    ///   none, or an error if it cannot be read or parsed.
+    pub fn read(output_dir: &Path) -> Result<Self> {
+        let path = output_dir.join(MANIFEST_FILE_NAME);
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+        Self::read_file(&path)
+    }
+
+    /// Reads a manifest file.
+    ///
+    /// # Arguments
+    /// * `path` - The manifest's path.
+    ///
+    /// # Returns
+    /// * `Result<Self>` - The manifest, or an error if it cannot be read
+    ///   or parsed.
+    pub fn read_file(path: &Path) -> Result<Self> {
+        let json = fs::read(path).map_err(|e| {
+            ProcessingError::io_error(path.to_path_buf(), e)
+        })?;
+        serde_json::from_slice(&json).map_err(|e| {
+            ProcessingError::serialization(
+                format!(
+                    "Failed to parse page manifest '{}'",
+                    path.display()
+                ),
+                Some(Box::new(e)),
+            )
+        })
+    }
+
+    /// Returns the manifest for a new build, following on from this
+    /// one.
+    ///
+    /// Pages this manifest lists, as generated or removed, that the new
+    /// build does not generate are removed, unless another output file
+    /// now takes their path.
+    ///
+    /// # Arguments
+    /// * `pages` - Output paths of the pages the new build generated.
+    /// * `files` - Output paths of the other files it generated.
+    pub fn next(
+        &self,
+        pages: BTreeSet<String>,
+        files: &BTreeSet<String>,
+    ) -> Self {
+        let removed = self
+            .pages
+            .iter()
+            .chain(&self.removed)
+            .filter(|path| {
+                !pages.contains(*path) && !files.contains(*path)
+            })
+            .cloned()
+            .collect();
+        Self { pages, removed }
+    }
+
+    /// Writes a tombstone for every removed page, replacing any stale
+    /// output left at its path.
+    ///
+    /// # Arguments
+    /// * `output_dir` - The directory to write the tombstones under.
+    ///
+    /// # Returns
+    /// * `Result<Vec<PathBuf>>` - The paths of the written tombstones.
+    pub fn write_tombstones(
+        &self,
+        output_dir: &Path,
+    ) -> Result<Vec<PathBuf>> {
+        let mut written = Vec::with_capacity(self.removed.len());
+        for relative in &self.removed {
+            let path = output_dir.join(relative);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| {
+                    ProcessingError::io_error(parent.to_path_buf(), e)
+                })?;
+            }
+            fs::write(&path, tombstone_html(relative)).map_err(
+                |e| ProcessingError::io_error(path.clone(), e),
+            )?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+
+    /// Writes the manifest to the output directory.
+    ///
+    /// # Arguments
+    /// * `output_dir` - The directory to write the manifest into.
+    ///
+    /// # Returns
+    /// * `Result<PathBuf>` - The path of the written manifest.
+    pub fn write(&self, output_dir: &Path) -> Result<PathBuf> {
+        let path = output_dir.join(MANIFEST_FILE_NAME);
+        let json = serde_json::to_vec_pretty(self).map_err(|e| {
+            ProcessingError::serialization(
+                "Failed to serialise page manifest",
+                Some(Box::new(e)),
+            )
+        })?;
+        fs::write(&path, json)
+            .map_err(|e| ProcessingError::io_error(path.clone(), e))?;
+        Ok(path)
+    }
+}
+
+/// Returns a manifest path for an output-relative file path.
+pub fn manifest_path(relative: &Path) -> String {
+    relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Returns the HTML of the tombstone for a removed page.
+fn tombstone_html(relative: &str) -> String {
+    let path = relative
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+    format!(
+        "<!DOCTYPE html>\n\
+         <html lang=\"en\">\n\
+         <head>\n\
+         <meta charset=\"utf-8\">\n\
+         <meta name=\"robots\" content=\"noindex\">\n\
+         <title>410 Gone</title>\n\
+         </head>\n\
+         <body>\n\
+         <h1>410 Gone</h1>\n\
+         <p>The page <code>/{}</code> has been removed.</p>\n\
+         </body>\n\
+         </html>\n",
+        path
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn set(paths: &[&str]) -> BTreeSet<String> {
+        paths.iter().map(|path| path.to_string()).collect()
+    }
+
+    #[test]
+    fn test_next_tracks_removed_pages() {
+        let first = PageManifest::default()
+            .next(set(&["a.html", "b.html", "c.html"]), &set(&[]));
+        assert!(first.removed.is_empty());
+
+        let second = first.next(set(&["a.html"]), &set(&["c.html"]));
+        assert_eq!(second.removed, set(&["b.html"]));
+
+        let third = second.next(set(&["a.html"]), &set(&[]));
+        assert_eq!(third.removed, set(&["b.html"]));
+
+        let fourth = third.next(set(&["a.html", "b.html"]), &set(&[]));
+        assert!(fourth.removed.is_empty());
+    }
+
+    #[test]
+    fn test_write_and_read() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let output = temp_dir.path();
+        assert_eq!(
+            PageManifest::read(output)?,
+            PageManifest::default()
+        );
+
+        let manifest = PageManifest {
+            pages: set(&["index.html"]),
+            removed: set(&["blog/old.html"]),
+        };
+        fs::create_dir(output.join("blog")).unwrap();
+        fs::write(output.join("blog/old.html"), "stale").unwrap();
+        let written = manifest.write_tombstones(output)?;
+        assert_eq!(written, [output.join("blog/old.html")]);
+        let stub = fs::read_to_string(&written[0]).unwrap();
+        assert!(stub.contains("410 Gone"));
+        assert!(stub.contains("/blog/old.html"));
+        assert!(stub.contains("noindex"));
+
+        _ = manifest.write(output)?;
+        assert_eq!(PageManifest::read(output)?, manifest);
+
+        fs::write(output.join(MANIFEST_FILE_NAME), "[").unwrap();
+        assert!(PageManifest::read(output).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,386 @@
+//! # Golden Site Tests
+//!
+//! Builds a small fixture site and compares the whole output tree with a
+//! committed copy of the expected output, so theme and plugin
+//! repositories can catch any change to what they generate in CI.
+//!
+//! A fixture is a directory holding a site and its expected output:
+//!
+//! ```text
+//! tests/site/
+//! ├── content/      the fixture's content files
+//! ├── templates/    its templates
+//! ├── static/       its static files (optional)
+//! └── expected/     the committed output of a build
+//! ```
+//!
+//! The fixture is built into a scratch directory, which is removed once
+//! it has been compared. Any file that is missing, unexpected or
+//! different fails the test until the expected output is updated.
+//! Output that changes from build to build, such as the time a page was
+//! generated, should be kept out of fixture templates.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use nucleusflow::golden::GoldenSite;
+//! use nucleusflow::{
+//!     FileContentProcessor, HtmlOutputGenerator, HtmlTemplateRenderer,
+//!     NucleusFlow, NucleusFlowConfig,
+//! };
+//!
+//! let site = GoldenSite::new("tests/site");
+//! let report = site
+//!     .run(
+//!         |output| {
+//!             let config = NucleusFlowConfig::new(
+//!                 site.content_dir(),
+//!                 output.to_path_buf(),
+//!                 site.template_dir(),
+//!             )?;
+//!             NucleusFlow::new(
+//!                 config,
+//!                 Box::new(FileContentProcessor::new(site.content_dir())),
+//!                 Box::new(HtmlTemplateRenderer::new(site.template_dir())),
+//!                 Box::new(HtmlOutputGenerator::new(output.to_path_buf())),
+//!             )
+//!             .process()
+//!         },
+//!         false,
+//!     )
+//!     .unwrap();
+//! assert!(report.passed(), "{}", report);
+//! ```
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use crate::generators::preview::{BuildPreview, ChangeKind};
+use crate::snapshots::{compare, SnapshotOutcome};
+use crate::{ProcessingError, Result};
+
+/// The first line at which a built file differs from the expected one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineChange {
+    /// The differing line, counting from 1
+    pub line: usize,
+    /// The expected version of the line
+    pub expected: String,
+    /// The built version of the line
+    pub actual: String,
+}
+
+/// A file whose built output does not match the expected output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoldenDifference {
+    /// The file's path relative to the output directory
+    pub path: PathBuf,
+    /// `Created` if the file is unexpected, `Deleted` if it was not
+    /// built, `Updated` if its content differs
+    pub kind: ChangeKind,
+    /// The first differing line, for changed text files
+    pub change: Option<LineChange>,
+}
+
+/// The outcome of a golden site test.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GoldenReport {
+    /// The expected output directory
+    pub expected_dir: PathBuf,
+    /// Every file that differs, ordered by path
+    pub differences: Vec<GoldenDifference>,
+    /// Whether the expected output was replaced with the build
+    pub updated: bool,
+}
+
+impl GoldenReport {
+    /// Returns `true` if the build matched the expected output, or the
+    /// expected output was updated.
+    pub fn passed(&self) -> bool {
+        self.updated || self.differences.is_empty()
+    }
+}
+
+impl fmt::Display for GoldenReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let expected = self.expected_dir.display();
+        if self.updated {
+            return writeln!(
+                f,
+                "updated {} ({} files changed)",
+                expected,
+                self.differences.len()
+            );
+        }
+        for difference in &self.differences {
+            let path = difference.path.display();
+            match difference.kind {
+                ChangeKind::Created => writeln!(
+                    f,
+                    "unexpected  {} (not in expected)",
+                    path
+                )?,
+                ChangeKind::Deleted => {
+                    writeln!(f, "missing     {} (not built)", path)?
+                }
+                ChangeKind::Updated => match &difference.change {
+                    Some(change) => {
+                        writeln!(
+                            f,
+                            "changed     {} (line {})",
+                            path, change.line
+                        )?;
+                        writeln!(f, "  - {}", change.expected)?;
+                        writeln!(f, "  + {}", change.actual)?;
+                    }
+                    None => writeln!(f, "changed     {}", path)?,
+                },
+            }
+        }
+        if self.differences.is_empty() {
+            writeln!(f, "output matches {}", expected)
+        } else {
+            writeln!(
+                f,
+                "{} files differ from {}",
+                self.differences.len(),
+                expected
+            )
+        }
+    }
+}
+
+/// A fixture site with committed expected output.
+#[derive(Debug, Clone)]
+pub struct GoldenSite {
+    dir: PathBuf,
+}
+
+impl GoldenSite {
+    /// Creates a golden test for the fixture in a directory.
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Returns the fixture's content directory.
+    pub fn content_dir(&self) -> PathBuf {
+        self.dir.join("content")
+    }
+
+    /// Returns the fixture's template directory.
+    pub fn template_dir(&self) -> PathBuf {
+        self.dir.join("templates")
+    }
+
+    /// Returns the fixture's static files directory, which may not
+    /// exist.
+    pub fn static_dir(&self) -> PathBuf {
+        self.dir.join("static")
+    }
+
+    /// Returns the directory holding the expected output.
+    pub fn expected_dir(&self) -> PathBuf {
+        self.dir.join("expected")
+    }
+
+    /// Builds the fixture and compares the output with the expected
+    /// output, or replaces the expected output with it.
+    ///
+    /// # Arguments
+    /// * `build` - Builds the fixture into the directory it is given.
+    /// * `update` - Replaces the expected output with the build instead
+    ///   of failing on differences.
+    ///
+    /// # Returns
+    /// * `Result<GoldenReport>` - The files that differ, or an error if
+    ///   the build fails or a directory cannot be read or written.
+    pub fn run<F, T>(
+        &self,
+        build: F,
+        update: bool,
+    ) -> Result<GoldenReport>
+    where
+        F: FnOnce(&Path) -> Result<T>,
+    {
+        let scratch = tempfile::TempDir::new().map_err(|e| {
+            ProcessingError::io_error(std::env::temp_dir(), e)
+        })?;
+        _ = build(scratch.path())?;
+
+        let expected_dir = self.expected_dir();
+        let preview =
+            BuildPreview::compare(&expected_dir, scratch.path())?;
+        let mut differences = Vec::with_capacity(preview.changes.len());
+        for change in preview.changes {
+            let line_change = match change.kind {
+                ChangeKind::Updated => first_change(
+                    &expected_dir.join(&change.path),
+                    &scratch.path().join(&change.path),
+                ),
+                _ => None,
+            };
+            differences.push(GoldenDifference {
+                path: change.path,
+                kind: change.kind,
+                change: line_change,
+            });
+        }
+
+        if update {
+            replace_dir(scratch.path(), &expected_dir)?;
+        }
+        Ok(GoldenReport {
+            expected_dir,
+            differences,
+            updated: update,
+        })
+    }
+}
+
+/// Returns the first differing line of two files, if both are text.
+fn first_change(expected: &Path, actual: &Path) -> Option<LineChange> {
+    let expected = fs::read_to_string(expected).ok()?;
+    let actual = fs::read_to_string(actual).ok()?;
+    match compare(&expected, &actual) {
+        SnapshotOutcome::Changed {
+            line,
+            expected,
+            actual,
+        } => Some(LineChange {
+            line,
+            expected,
+            actual,
+        }),
+        _ => None,
+    }
+}
+
+/// Replaces the contents of `target` with a copy of `source`.
+fn replace_dir(source: &Path, target: &Path) -> Result<()> {
+    if target.exists() {
+        fs::remove_dir_all(target).map_err(|e| {
+            ProcessingError::io_error(target.to_path_buf(), e)
+        })?;
+    }
+    for entry in WalkDir::new(source) {
+        let entry = entry.map_err(|e| {
+            ProcessingError::content_processing(
+                "Failed to read build output",
+                Some(Box::new(e)),
+            )
+        })?;
+        let relative = match entry.path().strip_prefix(source) {
+            Ok(relative) => relative,
+            Err(_) => continue,
+        };
+        let destination = target.join(relative);
+        let copied = if entry.file_type().is_dir() {
+            fs::create_dir_all(&destination)
+        } else {
+            fs::copy(entry.path(), &destination).map(|_| ())
+        };
+        copied
+            .map_err(|e| ProcessingError::io_error(destination, e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn build_pages(
+        pages: &'static [(&'static str, &'static str)],
+    ) -> impl FnOnce(&Path) -> Result<()> {
+        move |output| {
+            for (path, content) in pages {
+                let path = output.join(path);
+                fs::create_dir_all(path.parent().unwrap())?;
+                fs::write(path, content)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_golden_run() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let site = GoldenSite::new(temp_dir.path());
+        let pages = &[
+            ("index.html", "<h1>Home</h1>\n"),
+            ("blog/post.html", "<p>Post</p>\n"),
+        ];
+
+        let report = site.run(build_pages(pages), false)?;
+        assert!(!report.passed());
+        assert_eq!(report.differences.len(), 2);
+        assert!(report
+            .differences
+            .iter()
+            .all(|d| d.kind == ChangeKind::Created));
+
+        let report = site.run(build_pages(pages), true)?;
+        assert!(report.passed());
+        assert_eq!(
+            fs::read_to_string(
+                site.expected_dir().join("blog/post.html")
+            )?,
+            "<p>Post</p>\n"
+        );
+        let report = site.run(build_pages(pages), false)?;
+        assert!(report.passed());
+        assert!(report.to_string().starts_with("output matches"));
+
+        let report = site.run(
+            build_pages(&[(
+                "index.html",
+                "<h1>Home</h1>\n<p>New</p>\n",
+            )]),
+            false,
+        )?;
+        assert_eq!(
+            report.differences,
+            [
+                GoldenDifference {
+                    path: PathBuf::from("blog/post.html"),
+                    kind: ChangeKind::Deleted,
+                    change: None,
+                },
+                GoldenDifference {
+                    path: PathBuf::from("index.html"),
+                    kind: ChangeKind::Updated,
+                    change: Some(LineChange {
+                        line: 2,
+                        expected: String::new(),
+                        actual: "<p>New</p>".to_string(),
+                    }),
+                },
+            ]
+        );
+        assert!(report.to_string().contains("2 files differ from"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_golden_build_error() {
+        let temp_dir = TempDir::new().unwrap();
+        let site = GoldenSite::new(temp_dir.path());
+        let result = site.run(
+            |_| -> Result<()> {
+                Err(ProcessingError::validation(
+                    "broken fixture",
+                    None::<String>,
+                ))
+            },
+            true,
+        );
+        assert!(result.is_err());
+        assert!(!site.expected_dir().exists());
+    }
+}
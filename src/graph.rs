@@ -0,0 +1,299 @@
+//! # Pipeline Graph
+//!
+//! Describes the stages a build runs, from the sources it reads to where
+//! the output ends up, so users can see how their configuration is wired
+//! together and why a file is or is not processed. A graph is printed as
+//! indented text or exported in Graphviz DOT format:
+//!
+//! ```text
+//! [sources]
+//!   content: content (top-level files and sections)
+//!   |
+//!   v
+//! [processors]
+//!   content processor: FileContentProcessor
+//! ...
+//! ```
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::graph::{PipelineGraph, StageKind};
+//!
+//! let mut graph = PipelineGraph::new();
+//! graph.add(StageKind::Sources, "content", Some("content"));
+//! graph.add(StageKind::Deployers, "output", Some("public"));
+//! assert!(graph.to_dot().contains("sources_0 -> deployers_0;"));
+//! ```
+
+use std::fmt;
+use std::path::PathBuf;
+
+/// A stage of the build pipeline, in the order the stages run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StageKind {
+    /// Where content and static files are read from
+    Sources,
+    /// Transformations applied to content before rendering
+    Processors,
+    /// Templates and helpers that turn content into pages
+    Renderers,
+    /// Writers and post-build passes over the output
+    Generators,
+    /// How the output is put in place
+    Deployers,
+}
+
+impl StageKind {
+    /// Every stage, in pipeline order.
+    pub const ALL: [StageKind; 5] = [
+        StageKind::Sources,
+        StageKind::Processors,
+        StageKind::Renderers,
+        StageKind::Generators,
+        StageKind::Deployers,
+    ];
+
+    /// Returns the stage's lowercase name.
+    pub fn name(self) -> &'static str {
+        match self {
+            StageKind::Sources => "sources",
+            StageKind::Processors => "processors",
+            StageKind::Renderers => "renderers",
+            StageKind::Generators => "generators",
+            StageKind::Deployers => "deployers",
+        }
+    }
+}
+
+impl fmt::Display for StageKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A single step within a stage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    /// What the step does
+    pub label: String,
+
+    /// Extra detail, such as a directory or the names it provides
+    pub detail: Option<String>,
+}
+
+/// The stages of a configured build pipeline.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PipelineGraph {
+    stages: Vec<(StageKind, Vec<Node>)>,
+}
+
+impl PipelineGraph {
+    /// Creates a graph with every stage empty.
+    pub fn new() -> Self {
+        Self {
+            stages: StageKind::ALL
+                .iter()
+                .map(|kind| (*kind, Vec::new()))
+                .collect(),
+        }
+    }
+
+    /// Appends a step to a stage.
+    pub fn add<L, D>(
+        &mut self,
+        kind: StageKind,
+        label: L,
+        detail: Option<D>,
+    ) -> &mut Self
+    where
+        L: Into<String>,
+        D: Into<String>,
+    {
+        if let Some((_, nodes)) =
+            self.stages.iter_mut().find(|(stage, _)| *stage == kind)
+        {
+            nodes.push(Node {
+                label: label.into(),
+                detail: detail.map(Into::into),
+            });
+        }
+        self
+    }
+
+    /// Returns the steps of a stage, in the order they run.
+    pub fn nodes(&self, kind: StageKind) -> &[Node] {
+        self.stages
+            .iter()
+            .find(|(stage, _)| *stage == kind)
+            .map_or(&[], |(_, nodes)| nodes.as_slice())
+    }
+
+    /// Renders the graph in Graphviz DOT format.
+    ///
+    /// Each stage is a cluster, and every step is linked to the steps of
+    /// the next non-empty stage.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from(
+            "digraph pipeline {\n  rankdir=LR;\n  node [shape=box];\n",
+        );
+        let stages: Vec<_> = self
+            .stages
+            .iter()
+            .filter(|(_, nodes)| !nodes.is_empty())
+            .collect();
+
+        for (kind, nodes) in &stages {
+            dot.push_str(&format!(
+                "  subgraph cluster_{0} {{\n    label=\"{0}\";\n",
+                kind
+            ));
+            for (index, node) in nodes.iter().enumerate() {
+                let label = match &node.detail {
+                    Some(detail) => format!(
+                        "{}\\n{}",
+                        escape(&node.label),
+                        escape(detail)
+                    ),
+                    None => escape(&node.label),
+                };
+                dot.push_str(&format!(
+                    "    {}_{} [label=\"{}\"];\n",
+                    kind, index, label
+                ));
+            }
+            dot.push_str("  }\n");
+        }
+
+        for pair in stages.windows(2) {
+            let ((from, from_nodes), (to, to_nodes)) =
+                (pair[0], pair[1]);
+            for index in 0..from_nodes.len() {
+                for target in 0..to_nodes.len() {
+                    dot.push_str(&format!(
+                        "  {}_{} -> {}_{};\n",
+                        from, index, to, target
+                    ));
+                }
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl fmt::Display for PipelineGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (position, (kind, nodes)) in self.stages.iter().enumerate()
+        {
+            if position > 0 {
+                writeln!(f, "  |\n  v")?;
+            }
+            writeln!(f, "[{}]", kind)?;
+            if nodes.is_empty() {
+                writeln!(f, "  (none)")?;
+            }
+            for node in nodes {
+                match &node.detail {
+                    Some(detail) => {
+                        writeln!(f, "  {}: {}", node.label, detail)?
+                    }
+                    None => writeln!(f, "  {}", node.label)?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether a build reads a given source file, and why not.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceStatus {
+    /// The file is processed into the given output-relative page
+    Processed(PathBuf),
+    /// The file does not exist
+    Missing,
+    /// The path is a directory rather than a file
+    NotAFile,
+    /// The file is outside the content directory
+    OutsideContent,
+    /// The file is in a subdirectory that is not a section, and only
+    /// top-level files and those of sections are read
+    Nested,
+    /// The file matches an ignore rule
+    Ignored,
+    /// The file holds defaults for the pages of its directory
+    Defaults,
+}
+
+impl fmt::Display for SourceStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceStatus::Processed(output) => {
+                write!(f, "processed into {}", output.display())
+            }
+            SourceStatus::Missing => f.write_str("skipped: file not found"),
+            SourceStatus::NotAFile => {
+                f.write_str("skipped: not a regular file")
+            }
+            SourceStatus::OutsideContent => {
+                f.write_str("skipped: not inside the content directory")
+            }
+            SourceStatus::Nested => f.write_str(
+                "skipped: only top-level files and files of sections are read",
+            ),
+            SourceStatus::Ignored => {
+                f.write_str("skipped: matched by an ignore rule")
+            }
+            SourceStatus::Defaults => f.write_str(
+                "skipped: holds defaults for the pages of its directory",
+            ),
+        }
+    }
+}
+
+/// Escapes text for a double-quoted DOT string.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> PipelineGraph {
+        let mut graph = PipelineGraph::new();
+        _ = graph
+            .add(StageKind::Sources, "content", Some("content"))
+            .add(StageKind::Sources, "static", Some("static"))
+            .add(
+                StageKind::Renderers,
+                "templates",
+                Some("my \"theme\""),
+            )
+            .add(StageKind::Deployers, "output", None::<String>);
+        graph
+    }
+
+    #[test]
+    fn test_display() {
+        let text = sample().to_string();
+        assert!(text.starts_with(
+            "[sources]\n  content: content\n  static: static\n  |\n  v\n[processors]\n  (none)\n"
+        ));
+        assert!(text.ends_with("[deployers]\n  output\n"));
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let dot = sample().to_dot();
+        assert!(dot.contains(
+            "    renderers_0 [label=\"templates\\nmy \\\"theme\\\"\"];\n"
+        ));
+        assert!(dot.contains("  sources_0 -> renderers_0;\n"));
+        assert!(dot.contains("  sources_1 -> renderers_0;\n"));
+        assert!(dot.contains("  renderers_0 -> deployers_0;\n"));
+        assert!(!dot.contains("processors"));
+        assert!(dot.ends_with("}\n"));
+    }
+}
@@ -0,0 +1,174 @@
+//! # Page Build Hooks
+//!
+//! A page can run a command while it is rendered by setting `exec` in
+//! its frontmatter. The command's output is exposed to the page's
+//! template as `exec`, which suits generated content such as an API
+//! status table:
+//!
+//! ```text
+//! ---
+//! title: Status
+//! exec: ["scripts/status.sh", "--json"]
+//! ---
+//! ```
+//!
+//! Output that parses as JSON is exposed as data, so the template above
+//! can loop over `exec.services`; any other output is exposed as a
+//! trimmed string. The command is given as a list of arguments, or as a
+//! string split on whitespace. It is run directly rather than through a
+//! shell, from the build's working directory, with `NUCLEUS_PAGE_SOURCE`
+//! and `NUCLEUS_PAGE_URL` set.
+//!
+//! # Security
+//!
+//! Page commands run with the permissions of the build, so anyone able
+//! to edit content can run programs. They are disabled unless enabled
+//! with `NucleusFlowConfig::with_page_exec`, and a page setting `exec`
+//! fails to build while they are disabled.
+
+use std::process::Command;
+
+use serde_json::Value as JsonValue;
+
+use crate::site::Page;
+use crate::{ProcessingError, Result};
+
+/// The frontmatter key holding a page's command.
+pub const EXEC_KEY: &str = "exec";
+
+/// A command set by a page's `exec` frontmatter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageCommand {
+    /// The program to run
+    pub program: String,
+
+    /// The arguments passed to the program
+    pub args: Vec<String>,
+}
+
+impl PageCommand {
+    /// Reads a command from an `exec` frontmatter value.
+    ///
+    /// # Arguments
+    /// * `value` - A string, split on whitespace, or a list of strings.
+    ///
+    /// # Returns
+    /// * `Result<Self>` - The command, or a validation error if the
+    ///   value is empty or not a string or list of strings.
+    pub fn from_frontmatter(value: &JsonValue) -> Result<Self> {
+        let words: Option<Vec<String>> = match value {
+            JsonValue::String(command) => Some(
+                command.split_whitespace().map(String::from).collect(),
+            ),
+            JsonValue::Array(words) => words
+                .iter()
+                .map(|word| word.as_str().map(String::from))
+                .collect(),
+            _ => None,
+        };
+        let mut words = words.unwrap_or_default().into_iter();
+        match words.next() {
+            Some(program) => Ok(Self {
+                program,
+                args: words.collect(),
+            }),
+            None => Err(ProcessingError::validation(
+                "`exec` must be a command string or a list of strings",
+                None::<String>,
+            )),
+        }
+    }
+
+    /// Runs the command for a page and returns its output as template
+    /// data.
+    ///
+    /// # Arguments
+    /// * `page` - The page the command belongs to.
+    ///
+    /// # Returns
+    /// * `Result<JsonValue>` - The output, parsed as JSON where
+    ///   possible, or an error if the command cannot be run, exits
+    ///   unsuccessfully or prints invalid UTF-8.
+    pub fn run(&self, page: &Page) -> Result<JsonValue> {
+        let failed = |details: String| {
+            ProcessingError::content_processing(
+                format!("Page command '{}' {}", self.program, details),
+                None,
+            )
+        };
+
+        let output = Command::new(&self.program)
+            .args(&self.args)
+            .env("NUCLEUS_PAGE_SOURCE", &page.source)
+            .env("NUCLEUS_PAGE_URL", &page.url)
+            .output()
+            .map_err(|e| failed(format!("could not be run: {}", e)))?;
+        if !output.status.success() {
+            return Err(failed(format!(
+                "failed with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        let stdout = String::from_utf8(output.stdout)
+            .map_err(|_| failed("printed invalid UTF-8".to_string()))?;
+        Ok(serde_json::from_str(&stdout).unwrap_or_else(|_| {
+            JsonValue::String(stdout.trim().into())
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::site::tests::page;
+    use serde_json::json;
+
+    #[test]
+    fn test_command_from_frontmatter() {
+        assert_eq!(
+            PageCommand::from_frontmatter(&json!("date  -u")).unwrap(),
+            PageCommand {
+                program: "date".to_string(),
+                args: vec!["-u".to_string()],
+            }
+        );
+        assert_eq!(
+            PageCommand::from_frontmatter(&json!(["echo", "a b"]))
+                .unwrap()
+                .args,
+            ["a b"]
+        );
+        for invalid in
+            [json!(""), json!([]), json!(["echo", 1]), json!(1)]
+        {
+            assert!(PageCommand::from_frontmatter(&invalid).is_err());
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_output() -> Result<()> {
+        let run = |value: JsonValue| {
+            PageCommand::from_frontmatter(&value)?
+                .run(&page("status.html", json!({})))
+        };
+
+        assert_eq!(
+            run(json!(["echo", r#"{"up": true}"#]))?,
+            json!({ "up": true })
+        );
+        assert_eq!(
+            run(json!("echo all systems go"))?,
+            "all systems go"
+        );
+        assert_eq!(
+            run(json!(["sh", "-c", "echo $NUCLEUS_PAGE_URL"]))?,
+            "/status.html"
+        );
+        assert!(run(json!("false")).is_err());
+        assert!(run(json!("nucleusflow-missing-command")).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,776 @@
+//! # Feed Import
+//!
+//! Bootstraps a migration to NucleusFlow by turning the posts of an
+//! existing RSS 2.0 or Atom feed, such as a Medium or Substack blog's,
+//! into content files.
+//!
+//! Each post becomes a Markdown file in the content directory, named
+//! after a slug of its title, with its title, date, summary, author,
+//! categories and original URL as frontmatter. The post's HTML is kept
+//! as the body, which Markdown passes through unchanged, so it can be
+//! tidied by hand afterwards. Files that already exist are left alone,
+//! so an import can be run again to pick up new posts.
+//!
+//! With [`FeedImporter::with_images`], the images a post embeds are
+//! downloaded through a [`Fetch`] and their `src` rewritten to the local
+//! copies. The HTTP fetcher, [`HttpFetch`], needs the `import` feature;
+//! an image that cannot be downloaded keeps its remote URL.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::import::{parse_feed, FeedImporter};
+//!
+//! let feed = r#"<rss version="2.0"><channel><item>
+//!     <title>Hello, world</title>
+//!     <link>https://example.medium.com/hello-world-1a2b</link>
+//!     <pubDate>Tue, 02 Jan 2024 10:00:00 GMT</pubDate>
+//!     <description><![CDATA[<p>First post.</p>]]></description>
+//! </item></channel></rss>"#;
+//!
+//! let entries = parse_feed(feed).unwrap();
+//! assert_eq!(entries[0].title, "Hello, world");
+//! assert_eq!(entries[0].date.as_deref(), Some("2024-01-02T10:00:00+00:00"));
+//!
+//! let dir = tempfile::tempdir().unwrap();
+//! let posts = FeedImporter::new(dir.path()).import(feed).unwrap();
+//! assert!(posts[0].path.ends_with("hello-world.md"));
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use crate::extract::plain_text;
+use crate::{ProcessingError, Result};
+
+/// Image extensions kept from an image's URL.
+const IMAGE_EXTENSIONS: [&str; 7] =
+    ["avif", "gif", "jpeg", "jpg", "png", "svg", "webp"];
+
+/// Downloads the files a feed import needs.
+pub trait Fetch: fmt::Debug + Send + Sync {
+    /// Downloads a URL.
+    ///
+    /// # Returns
+    /// * `Result<Vec<u8>>` - The response body, or an error if the URL
+    ///   cannot be fetched.
+    fn fetch(&self, url: &str) -> Result<Vec<u8>>;
+
+    /// Sends a request with headers, posting `body` as JSON if given,
+    /// such as a [headless CMS](crate::cms) query.
+    ///
+    /// Fetchers that only download URLs fail requests with headers or
+    /// a body.
+    ///
+    /// # Returns
+    /// * `Result<Vec<u8>>` - The response body, or an error if the
+    ///   request fails.
+    fn request(
+        &self,
+        url: &str,
+        headers: &BTreeMap<String, String>,
+        body: Option<&JsonValue>,
+    ) -> Result<Vec<u8>> {
+        if headers.is_empty() && body.is_none() {
+            return self.fetch(url);
+        }
+        Err(ProcessingError::content_processing(
+            format!(
+                "Failed to fetch '{}': requests with headers or a body \
+                 are not supported",
+                url
+            ),
+            None,
+        ))
+    }
+}
+
+/// Downloads files over HTTP.
+#[cfg(feature = "import")]
+#[derive(Debug, Clone)]
+pub struct HttpFetch {
+    agent: ureq::Agent,
+}
+
+#[cfg(feature = "import")]
+impl HttpFetch {
+    /// Creates a fetcher that gives up on a request after `timeout`.
+    pub fn new(timeout: std::time::Duration) -> Self {
+        Self {
+            agent: ureq::AgentBuilder::new()
+                .timeout(timeout)
+                .user_agent(&format!(
+                    "nucleusflow/{} (import)",
+                    crate::cli::VERSION
+                ))
+                .build(),
+        }
+    }
+}
+
+#[cfg(feature = "import")]
+impl Fetch for HttpFetch {
+    fn fetch(&self, url: &str) -> Result<Vec<u8>> {
+        self.request(url, &BTreeMap::new(), None)
+    }
+
+    fn request(
+        &self,
+        url: &str,
+        headers: &BTreeMap<String, String>,
+        body: Option<&JsonValue>,
+    ) -> Result<Vec<u8>> {
+        let failed = |details: String| {
+            ProcessingError::content_processing(
+                format!("Failed to fetch '{}': {}", url, details),
+                None,
+            )
+        };
+        let mut request = match body {
+            Some(_) => self.agent.post(url),
+            None => self.agent.get(url),
+        };
+        for (name, value) in headers {
+            request = request.set(name, value);
+        }
+        let response = match body {
+            Some(body) => request
+                .set("Content-Type", "application/json")
+                .send_string(&body.to_string()),
+            None => request.call(),
+        }
+        .map_err(|e| failed(e.to_string()))?;
+        let mut body = Vec::new();
+        _ = std::io::Read::read_to_end(
+            &mut response.into_reader(),
+            &mut body,
+        )
+        .map_err(|e| failed(e.to_string()))?;
+        Ok(body)
+    }
+}
+
+/// A post read from a feed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeedEntry {
+    /// The post's title
+    pub title: String,
+    /// The post's original URL
+    pub link: Option<String>,
+    /// When the post was published, as RFC 3339 where the feed's date
+    /// could be read
+    pub date: Option<String>,
+    /// The post's author
+    pub author: Option<String>,
+    /// The post's summary, as HTML
+    pub summary: Option<String>,
+    /// The post's full content, as HTML
+    pub content: Option<String>,
+    /// The post's categories
+    pub categories: Vec<String>,
+}
+
+/// Reads the posts of an RSS 2.0 or Atom feed.
+///
+/// # Arguments
+/// * `xml` - The feed document.
+///
+/// # Returns
+/// * `Result<Vec<FeedEntry>>` - The posts, in feed order, or an error if
+///   the document is not well-formed XML.
+pub fn parse_feed(xml: &str) -> Result<Vec<FeedEntry>> {
+    let mut reader = Reader::from_str(xml);
+    let mut entries = Vec::new();
+    let mut entry: Option<FeedEntry> = None;
+    let mut text = String::new();
+
+    loop {
+        let event = reader.read_event().map_err(|e| {
+            ProcessingError::content_processing(
+                format!(
+                    "Invalid feed at byte {}: {}",
+                    reader.buffer_position(),
+                    e
+                ),
+                None,
+            )
+        })?;
+
+        match event {
+            Event::Eof => break,
+            Event::Start(start) => {
+                let name = element_name(&start);
+                if name == "item" || name == "entry" {
+                    entry = Some(FeedEntry::default());
+                } else if let Some(entry) = entry.as_mut() {
+                    read_attributes(entry, &name, &start)?;
+                }
+                text.clear();
+            }
+            Event::Empty(start) => {
+                if let Some(entry) = entry.as_mut() {
+                    read_attributes(
+                        entry,
+                        &element_name(&start),
+                        &start,
+                    )?;
+                }
+            }
+            Event::Text(value) => {
+                text.push_str(&value.unescape().map_err(|e| {
+                    ProcessingError::content_processing(
+                        format!("Invalid text in feed: {}", e),
+                        None,
+                    )
+                })?);
+            }
+            Event::CData(data) => {
+                text.push_str(&String::from_utf8_lossy(&data));
+            }
+            Event::End(end) => {
+                let name = String::from_utf8_lossy(end.name().as_ref())
+                    .into_owned();
+                if name == "item" || name == "entry" {
+                    entries.extend(entry.take());
+                } else if let Some(entry) = entry.as_mut() {
+                    read_text(entry, &name, text.trim());
+                }
+                text.clear();
+            }
+            Event::Comment(_)
+            | Event::Decl(_)
+            | Event::PI(_)
+            | Event::DocType(_) => {}
+        }
+    }
+    Ok(entries)
+}
+
+/// Returns the qualified name of an element, such as `content:encoded`.
+fn element_name(start: &BytesStart<'_>) -> String {
+    String::from_utf8_lossy(start.name().as_ref()).into_owned()
+}
+
+/// Reads the fields Atom keeps in attributes: a `link`'s `href` and a
+/// `category`'s `term`.
+fn read_attributes(
+    entry: &mut FeedEntry,
+    name: &str,
+    start: &BytesStart<'_>,
+) -> Result<()> {
+    let attribute = |key: &str| -> Result<Option<String>> {
+        match start.try_get_attribute(key) {
+            Ok(Some(attribute)) => attribute
+                .unescape_value()
+                .map(|value| Some(value.into_owned()))
+                .map_err(|e| {
+                    ProcessingError::content_processing(
+                        format!("Invalid attribute in feed: {}", e),
+                        None,
+                    )
+                }),
+            Ok(None) => Ok(None),
+            Err(e) => Err(ProcessingError::content_processing(
+                format!("Invalid attribute in feed: {}", e),
+                None,
+            )),
+        }
+    };
+    match name {
+        "link" => {
+            let alternate = attribute("rel")?
+                .map_or(true, |rel| rel == "alternate");
+            if alternate && entry.link.is_none() {
+                entry.link = attribute("href")?;
+            }
+        }
+        "category" => entry.categories.extend(attribute("term")?),
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Reads the fields kept in an element's text.
+fn read_text(entry: &mut FeedEntry, name: &str, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    let text = text.to_string();
+    match name {
+        "title" => entry.title = text,
+        "link" => entry.link = entry.link.take().or(Some(text)),
+        "pubDate" | "published" | "dc:date" => {
+            entry.date = Some(feed_date(&text))
+        }
+        "updated" => {
+            entry.date = entry.date.take().or(Some(feed_date(&text)))
+        }
+        "author" | "name" | "dc:creator" => entry.author = Some(text),
+        "description" | "summary" => entry.summary = Some(text),
+        "content:encoded" | "content" => entry.content = Some(text),
+        "category" => entry.categories.push(text),
+        _ => {}
+    }
+}
+
+/// Converts an RSS date to RFC 3339, keeping dates that cannot be read
+/// as they are.
+fn feed_date(date: &str) -> String {
+    chrono::DateTime::parse_from_rfc2822(date)
+        .map(|date| date.to_rfc3339())
+        .unwrap_or_else(|_| date.to_string())
+}
+
+/// A content file written by an import.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedPost {
+    /// The content file written
+    pub path: PathBuf,
+    /// The images downloaded for the post
+    pub images: Vec<PathBuf>,
+}
+
+/// The frontmatter of an imported post.
+#[derive(Serialize)]
+struct ImportedFrontmatter<'a> {
+    title: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    author: Option<&'a str>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    tags: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    imported_from: Option<&'a str>,
+}
+
+/// Where an import downloads images to.
+#[derive(Debug, Clone)]
+struct ImageDownloads {
+    fetcher: Arc<dyn Fetch>,
+    dir: PathBuf,
+    url: String,
+}
+
+/// Writes the posts of a feed to a content directory.
+#[derive(Debug, Clone)]
+pub struct FeedImporter {
+    content_dir: PathBuf,
+    images: Option<ImageDownloads>,
+}
+
+impl FeedImporter {
+    /// Creates an importer writing posts to a content directory,
+    /// leaving their images where they are.
+    pub fn new<P: AsRef<Path>>(content_dir: P) -> Self {
+        Self {
+            content_dir: content_dir.as_ref().to_path_buf(),
+            images: None,
+        }
+    }
+
+    /// Downloads the images posts embed.
+    ///
+    /// # Arguments
+    /// * `fetcher` - Downloads the images.
+    /// * `dir` - The directory images are written to, usually in the
+    ///   site's static files.
+    /// * `url` - The URL `dir` is served at, such as `/images/`.
+    pub fn with_images<P, S>(
+        mut self,
+        fetcher: Arc<dyn Fetch>,
+        dir: P,
+        url: S,
+    ) -> Self
+    where
+        P: AsRef<Path>,
+        S: Into<String>,
+    {
+        let mut url = url.into();
+        if !url.ends_with('/') {
+            url.push('/');
+        }
+        self.images = Some(ImageDownloads {
+            fetcher,
+            dir: dir.as_ref().to_path_buf(),
+            url,
+        });
+        self
+    }
+
+    /// Writes a content file for each post of a feed that does not have
+    /// one yet.
+    ///
+    /// # Arguments
+    /// * `xml` - The feed document.
+    ///
+    /// # Returns
+    /// * `Result<Vec<ImportedPost>>` - The files written, in feed order,
+    ///   or an error if the feed cannot be read or a file cannot be
+    ///   written.
+    pub fn import(&self, xml: &str) -> Result<Vec<ImportedPost>> {
+        fs::create_dir_all(&self.content_dir).map_err(|e| {
+            ProcessingError::io_error(self.content_dir.clone(), e)
+        })?;
+
+        let mut slugs = BTreeSet::new();
+        let mut imported = Vec::new();
+        for (index, entry) in parse_feed(xml)?.iter().enumerate() {
+            let mut slug = slugify(&entry.title);
+            if slug.is_empty() {
+                slug = format!("post-{}", index + 1);
+            }
+            let base = slug.clone();
+            let mut n = 1;
+            while !slugs.insert(slug.clone()) {
+                n += 1;
+                slug = format!("{}-{}", base, n);
+            }
+
+            let path = self.content_dir.join(format!("{}.md", slug));
+            if path.exists() {
+                tracing::info!(
+                    "Skipping '{}': {} exists",
+                    entry.title,
+                    path.display()
+                );
+                continue;
+            }
+            let (body, images) = self.localise_images(
+                &slug,
+                entry
+                    .content
+                    .as_deref()
+                    .or(entry.summary.as_deref())
+                    .unwrap_or_default(),
+            )?;
+            fs::write(&path, post_file(entry, &body)?).map_err(
+                |e| ProcessingError::io_error(path.clone(), e),
+            )?;
+            imported.push(ImportedPost { path, images });
+        }
+        Ok(imported)
+    }
+
+    /// Downloads the images of a post's HTML, returning the HTML with
+    /// their `src` pointing at the copies.
+    fn localise_images(
+        &self,
+        slug: &str,
+        html: &str,
+    ) -> Result<(String, Vec<PathBuf>)> {
+        let images = match &self.images {
+            Some(images) => images,
+            None => return Ok((html.to_string(), Vec::new())),
+        };
+
+        let mut html = html.to_string();
+        let mut written = Vec::new();
+        let mut local: BTreeMap<String, String> = BTreeMap::new();
+        for src in image_sources(&html) {
+            if local.contains_key(&src) {
+                continue;
+            }
+            let url = src.replace("&amp;", "&");
+            let bytes = match images.fetcher.fetch(&url) {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    tracing::warn!(
+                        "Keeping remote image {}: {}",
+                        url,
+                        e
+                    );
+                    continue;
+                }
+            };
+            let name = format!(
+                "{}-{}.{}",
+                slug,
+                written.len() + 1,
+                image_extension(&url, &bytes)
+            );
+            fs::create_dir_all(&images.dir).map_err(|e| {
+                ProcessingError::io_error(images.dir.clone(), e)
+            })?;
+            let path = images.dir.join(&name);
+            fs::write(&path, bytes).map_err(|e| {
+                ProcessingError::io_error(path.clone(), e)
+            })?;
+            written.push(path);
+            _ = local.insert(src, format!("{}{}", images.url, name));
+        }
+        for (src, url) in &local {
+            for quote in ['"', '\''] {
+                html = html.replace(
+                    &format!("src={0}{1}{0}", quote, src),
+                    &format!("src={0}{1}{0}", quote, url),
+                );
+            }
+        }
+        Ok((html, written))
+    }
+}
+
+/// Returns the content file of a post: its frontmatter and HTML body.
+fn post_file(entry: &FeedEntry, body: &str) -> Result<String> {
+    let frontmatter = ImportedFrontmatter {
+        title: &entry.title,
+        date: entry.date.as_deref(),
+        description: entry
+            .summary
+            .as_deref()
+            .map(plain_text)
+            .filter(|text| !text.is_empty()),
+        author: entry.author.as_deref(),
+        tags: &entry.categories,
+        imported_from: entry.link.as_deref(),
+    };
+    let yaml = serde_yml::to_string(&frontmatter).map_err(|e| {
+        ProcessingError::serialization(
+            "Failed to write imported frontmatter",
+            Some(Box::new(e)),
+        )
+    })?;
+    Ok(format!("---\n{}---\n{}\n", yaml, body.trim()))
+}
+
+/// Returns the remote `src` of each `<img>` in some HTML, as written.
+fn image_sources(html: &str) -> Vec<String> {
+    let lower = html.to_ascii_lowercase();
+    let mut sources = Vec::new();
+    let mut rest = 0;
+    while let Some(start) = lower[rest..].find("<img") {
+        let start = rest + start;
+        let end = lower[start..]
+            .find('>')
+            .map_or(lower.len(), |end| start + end);
+        rest = end;
+        let tag = &lower[start..end];
+        let attribute = match tag.find(" src=") {
+            Some(index) => start + index + " src=".len(),
+            None => continue,
+        };
+        let quote = match html[attribute..].chars().next() {
+            Some(quote @ ('"' | '\'')) => quote,
+            _ => continue,
+        };
+        let value = attribute + 1;
+        if let Some(len) = html[value..end].find(quote) {
+            let src = &html[value..value + len];
+            if src.starts_with("http://") || src.starts_with("https://")
+            {
+                sources.push(src.to_string());
+            }
+        }
+    }
+    sources
+}
+
+/// Returns the extension to save an image with: its URL's, or the one
+/// of the format its bytes are in.
+fn image_extension(url: &str, bytes: &[u8]) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let extension = path
+        .rsplit('/')
+        .next()
+        .and_then(|name| name.rsplit_once('.'))
+        .map(|(_, extension)| extension.to_ascii_lowercase());
+    match extension {
+        Some(extension)
+            if IMAGE_EXTENSIONS.contains(&extension.as_str()) =>
+        {
+            extension
+        }
+        _ => image::guess_format(bytes)
+            .ok()
+            .and_then(|format| format.extensions_str().first())
+            .map_or("img", |extension| extension)
+            .to_string(),
+    }
+}
+
+/// Returns a lowercase, hyphenated slug of a title.
+pub(crate) fn slugify(title: &str) -> String {
+    let mut slug = String::with_capacity(title.len());
+    for c in title.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+    use tempfile::TempDir;
+
+    #[derive(Debug, Default)]
+    struct Offline {
+        fetched: Mutex<Vec<String>>,
+    }
+
+    impl Fetch for Offline {
+        fn fetch(&self, url: &str) -> Result<Vec<u8>> {
+            self.fetched.lock().push(url.to_string());
+            if url.contains("gone") {
+                return Err(ProcessingError::content_processing(
+                    "404", None,
+                ));
+            }
+            Ok(b"\x89PNG\r\n\x1a\n".to_vec())
+        }
+    }
+
+    const ATOM: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Blog</title>
+  <entry>
+    <title>Caf&#233; &amp; Code</title>
+    <link rel="replies" href="https://example.com/comments"/>
+    <link href="https://example.com/cafe-code"/>
+    <updated>2024-02-03T00:00:00Z</updated>
+    <published>2024-02-01T09:30:00Z</published>
+    <author><name>Ada</name></author>
+    <category term="rust"/>
+    <category term="coffee"/>
+    <summary type="html">&lt;p&gt;Two things &amp;amp; more.&lt;/p&gt;</summary>
+    <content type="html">&lt;p&gt;&lt;img src="https://cdn.example.com/a.JPG?w=800&amp;amp;q=1" alt="a"&gt;&lt;img src='https://cdn.example.com/gone.png'&gt;&lt;img src="/local.png"&gt;&lt;/p&gt;</content>
+  </entry>
+</feed>"#;
+
+    #[test]
+    fn test_parse_atom() {
+        let entries = parse_feed(ATOM).unwrap();
+        assert_eq!(entries.len(), 1);
+        let entry = &entries[0];
+        assert_eq!(entry.title, "Café & Code");
+        assert_eq!(
+            entry.link.as_deref(),
+            Some("https://example.com/cafe-code")
+        );
+        assert_eq!(entry.date.as_deref(), Some("2024-02-01T09:30:00Z"));
+        assert_eq!(entry.author.as_deref(), Some("Ada"));
+        assert_eq!(entry.categories, ["rust", "coffee"]);
+        assert!(entry
+            .content
+            .as_deref()
+            .unwrap()
+            .starts_with("<p><img"));
+    }
+
+    #[test]
+    fn test_parse_rss() {
+        let entries = parse_feed(
+            r#"<rss version="2.0" xmlns:dc="http://purl.org/dc/elements/1.1/"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/">
+            <channel><title>Newsletter</title>
+            <item>
+              <title>Issue #1</title>
+              <link>https://news.substack.com/p/issue-1</link>
+              <dc:creator><![CDATA[Grace]]></dc:creator>
+              <pubDate>Mon, 01 Jan 2024 08:00:00 +0100</pubDate>
+              <category><![CDATA[news]]></category>
+              <description>The first issue</description>
+              <content:encoded><![CDATA[<h2>Hi</h2>]]></content:encoded>
+            </item>
+            <item><title>Issue #2</title><pubDate>soon</pubDate></item>
+            </channel></rss>"#,
+        )
+        .unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].author.as_deref(), Some("Grace"));
+        assert_eq!(
+            entries[0].date.as_deref(),
+            Some("2024-01-01T08:00:00+01:00")
+        );
+        assert_eq!(entries[0].categories, ["news"]);
+        assert_eq!(
+            entries[0].summary.as_deref(),
+            Some("The first issue")
+        );
+        assert_eq!(entries[0].content.as_deref(), Some("<h2>Hi</h2>"));
+        assert_eq!(entries[1].date.as_deref(), Some("soon"));
+
+        assert!(parse_feed("<rss><channel></rss>").is_err());
+    }
+
+    #[test]
+    fn test_import() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content = temp_dir.path().join("content");
+        let images = temp_dir.path().join("static/images");
+        let fetcher = Arc::new(Offline::default());
+        let importer = FeedImporter::new(&content).with_images(
+            fetcher.clone(),
+            &images,
+            "/images",
+        );
+
+        let posts = importer.import(ATOM)?;
+        assert_eq!(posts.len(), 1);
+        assert_eq!(posts[0].path, content.join("café-code.md"));
+        assert_eq!(posts[0].images, [images.join("café-code-1.jpg")]);
+        assert_eq!(
+            *fetcher.fetched.lock(),
+            [
+                "https://cdn.example.com/a.JPG?w=800&q=1",
+                "https://cdn.example.com/gone.png",
+            ]
+        );
+
+        let file = fs::read_to_string(&posts[0].path)?;
+        assert!(
+            file.starts_with("---\ntitle: Café & Code\n"),
+            "{}",
+            file
+        );
+        assert!(file.contains("description: Two things & more.\n"));
+        assert!(file.contains("tags:\n- rust\n- coffee\n"));
+        assert!(file.contains(
+            "imported_from: https://example.com/cafe-code\n---\n"
+        ));
+        assert!(file.contains(
+            r#"<img src="/images/café-code-1.jpg" alt="a">"#
+        ));
+        assert!(file.contains("src='https://cdn.example.com/gone.png'"));
+        assert!(file.contains(r#"src="/local.png""#));
+
+        // Existing posts are left alone
+        fs::write(&posts[0].path, "edited")?;
+        assert!(importer.import(ATOM)?.is_empty());
+        assert_eq!(fs::read_to_string(&posts[0].path)?, "edited");
+        Ok(())
+    }
+
+    #[test]
+    fn test_helpers() {
+        assert_eq!(
+            slugify("  Hello, World! 2024 "),
+            "hello-world-2024"
+        );
+        assert_eq!(slugify("!!!"), "");
+        assert_eq!(
+            plain_text("<p>A&nbsp;<b>bold</b>\n claim</p>"),
+            "A bold claim"
+        );
+        assert_eq!(
+            image_extension("https://x/y/photo", b"GIF89a"),
+            "gif"
+        );
+        assert_eq!(image_extension("https://x/y.z/photo", b""), "img");
+    }
+}
@@ -19,37 +19,179 @@
 #![crate_name = "nucleusflow"]
 #![crate_type = "lib"]
 
-use crate::core::error::{ProcessingError, Result};
-use crate::core::traits::Generator;
+use crate::check::{CheckReport, OutputAnchors};
+use crate::cms::HeadlessCms;
+use crate::core::cache::{CacheMetrics, CacheStats, ContentCache};
+#[cfg(feature = "social-cards")]
+use crate::core::config::SocialCardConfig;
+use crate::core::config::{
+    snapshot_build_env, A11yConfig, ArchiveConfig, CollisionPolicy,
+    DataPagesConfig, FootnoteNumbering, I18nConfig, MemoryConfig,
+    MenuItem, PrecompressConfig, RoutesConfig, ScreenshotConfig,
+    SeriesConfig, StaticConfig, SummaryConfig, SwapStrategy,
+    SymlinkPolicy, TaxonomyConfig,
+};
+use crate::core::error::{
+    BuildStage, PageFailure, ProcessingError, Result, SourceSnippet,
+};
+use crate::core::ignore::{IgnoreRules, IGNORE_FILE_NAME};
+use crate::core::memory::{MemoryGuard, MemoryStatus};
+use crate::core::time::SiteTimezone;
+use crate::core::traits::{Generator, PostProcessor, Validator};
+use crate::core::validation::{
+    Diagnostic, Diagnostics, Severity, SourceFile,
+};
+use crate::extract::{plain_text, ExtractedPage};
+use crate::generators::archives::archive_pages;
+use crate::generators::authors::Authors;
+use crate::generators::compress::precompress_dir;
+use crate::generators::data_pages::data_pages;
+use crate::generators::debug_artifacts::strip_debug_artifacts;
+use crate::generators::dedup::deduplicate_images;
+use crate::generators::deploy::{DeployedFile, PreviewDeploy};
+use crate::generators::hreflang::HreflangLinks;
+use crate::generators::html::HtmlGenerator;
+use crate::generators::images::PlaceholderConfig;
+use crate::generators::menus::menus_context;
+use crate::generators::preview::BuildPreview;
+use crate::generators::redirects::{RedirectFormat, RedirectMap};
+use crate::generators::report::{BuildReport, REPORT_FILE_NAME};
+use crate::generators::screenshots::Screenshots;
+use crate::generators::seo::CanonicalLink;
+use crate::generators::series::{series_contexts, series_pages};
+use crate::generators::sitemap::{LanguageSitemaps, Sitemap};
+#[cfg(feature = "social-cards")]
+use crate::generators::social_cards::SocialCards;
+use crate::generators::staging::StagedOutput;
+use crate::generators::static_files::StaticFileCopier;
+use crate::generators::tombstones::{
+    manifest_path, PageManifest, MANIFEST_FILE_NAME,
+};
+use crate::graph::{PipelineGraph, SourceStatus, StageKind};
+use crate::hooks::{PageCommand, EXEC_KEY};
+use crate::linkcheck::ExternalLinkChecker;
+use crate::plugin::PluginRegistry;
+use crate::processors::citations::Citations;
+use crate::processors::frontmatter::{
+    is_defaults_file, is_section, merge_defaults, FrontmatterDefaults,
+};
+#[cfg(feature = "git")]
+use crate::processors::git::GitHistory;
+use crate::processors::includes::Includes;
+use crate::processors::markdown::{
+    count_footnotes, split_toc, MarkdownProcessor, ReadingStats,
+    DEFAULT_WORDS_PER_MINUTE,
+};
+use crate::processors::summary::extract_summary;
+use crate::processors::wikilinks::WikiLinks;
+use crate::result::{
+    BuildResult, BuildTimings, BuiltPage, SkippedPage,
+};
+#[cfg(feature = "scripting")]
+use crate::scripts::Scripts;
+use crate::site::{
+    page_url, Breadcrumb, Page, PageNavigation, Site, HOME_TITLE,
+    OUTPUT_PATH_KEYS,
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs;
 use std::io::Write;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 /// Module containing core utilities, such as configuration and error handling.
 pub mod core {
+    /// Counts cache hits and misses over a build.
+    pub mod cache;
     /// Handles configuration of the NucleusFlow application.
     pub mod config;
     /// Contains error types and handling for NucleusFlow.
     pub mod error;
+    /// Handles `.nucleusignore` rules for excluding project files.
+    pub mod ignore;
+    /// Enforces soft and hard memory limits during a build.
+    pub mod memory;
+    /// Provides site timezone handling for dates and build timestamps.
+    pub mod time;
     /// Defines common traits for content processing, rendering, and generation.
     pub mod traits;
+    /// Provides content validators and build diagnostics.
+    pub mod validation;
 }
 
+/// Provides accessibility linting of generated HTML.
+pub mod a11y;
+
+/// Provides CI annotations for the problems found by site checks.
+pub mod annotations;
+
+/// Provides site health checks run against an in-memory build.
+pub mod check;
+
 /// Provides command-line interface utilities.
 pub mod cli;
 
+/// Provides fetching of content from headless CMS endpoints.
+pub mod cms;
+
+/// Provides comparison of two builds of a site.
+pub mod compare;
+
+/// Provides plain text extraction of pages for search and
+/// translation workflows.
+pub mod extract;
+
 /// Provides output generation utilities.
 pub mod generators;
 
+/// Provides a description of the configured build pipeline.
+pub mod graph;
+
+/// Provides golden-file tests comparing a fixture site's build with
+/// committed output.
+pub mod golden;
+
+/// Provides page-level build hooks run while pages render.
+pub mod hooks;
+
+/// Provides importing of posts from RSS and Atom feeds.
+pub mod import;
+
+/// Provides concurrent, cached checking of external links.
+pub mod linkcheck;
+
+/// Provides the plugin API for template helpers and shortcodes.
+pub mod plugin;
+
 /// Provides processing pipeline utilities.
 pub mod process;
 
 /// Provides processors for content transformation.
 pub mod processors;
 
+/// Provides the structured result of a build.
+pub mod result;
+
+/// Provides Rhai scripts run on pages, with the `scripting` feature.
+#[cfg(feature = "scripting")]
+pub mod scripts;
+
+/// Provides a development server previewing built sites.
+pub mod serve;
+
+/// Provides the typed page and site model.
+pub mod site;
+
+/// Provides snapshot tests for templates.
+pub mod snapshots;
+
 /// Provides template rendering utilities.
 pub mod template;
 
+/// Provides polling of site sources for changes in watch mode.
+pub mod watch;
+
 /// Trait for content processing implementations.
 ///
 /// Implementations of this trait process content, transforming it based on
@@ -77,6 +219,30 @@ fn process(
     /// # Returns
     /// * `Result<()>` - Indicates success if the content is valid, or an error if invalid.
     fn validate(&self, content: &str) -> Result<()>;
+
+    /// Processes a page whose footnotes are numbered on from
+    /// `footnote_start`, which builds with continuous footnote
+    /// numbering call instead of [`ContentProcessor::process`].
+    ///
+    /// Processors that number footnotes, such as one wrapping
+    /// [`MarkdownProcessor::process_page`], override it; by default
+    /// the start is ignored.
+    ///
+    /// # Arguments
+    /// * `content` - The content to be processed.
+    /// * `footnote_start` - The number of footnotes on the pages
+    ///   before this one, in site order.
+    ///
+    /// # Returns
+    /// * `Result<String>` - The processed content, or an error if processing fails.
+    fn process_with_footnotes(
+        &self,
+        content: &str,
+        footnote_start: usize,
+    ) -> Result<String> {
+        _ = footnote_start;
+        self.process(content, None)
+    }
 }
 
 /// Trait for template rendering implementations.
@@ -110,6 +276,13 @@ fn validate(
         template: &str,
         context: &serde_json::Value,
     ) -> Result<()>;
+
+    /// Returns the hit and miss counts of the renderer's template cache.
+    ///
+    /// Renderers without a cache return `None`, the default.
+    fn cache_stats(&self) -> Option<CacheStats> {
+        None
+    }
 }
 
 /// Concrete implementation of `ContentProcessor` that processes file content.
@@ -233,10 +406,126 @@ pub struct NucleusFlowConfig {
     pub output_dir: PathBuf,
     /// The directory containing template files.
     pub template_dir: PathBuf,
+    /// Static files copied verbatim to the output directory, if enabled.
+    pub static_files: Option<StaticConfig>,
+    /// The placeholders generated for the static files' images, if
+    /// templates use them for progressive loading.
+    pub image_placeholders: Option<PlaceholderConfig>,
+    /// Renders an inline error page when a template fails instead of
+    /// aborting the build (development only).
+    pub error_recovery: bool,
+    /// Rules excluding files from content scanning and static copying.
+    pub ignore_rules: Option<IgnoreRules>,
+    /// The timezone naive frontmatter dates and build timestamps use.
+    pub timezone: SiteTimezone,
+    /// Removes duplicate images from the output after the build.
+    pub deduplicate_images: bool,
+    /// Pre-compressed twins written for output files after the build.
+    pub precompress: Option<PrecompressConfig>,
+    /// Keeps source maps and debug variants of assets in the output.
+    pub debug_artifacts: bool,
+    /// Builds into a staging directory swapped into place on success.
+    pub atomic_swap: Option<SwapStrategy>,
+    /// The site's base URL, if a sitemap is generated.
+    pub sitemap_base_url: Option<String>,
+    /// The URL the site is published at, if pages get canonical links.
+    pub base_url: Option<String>,
+    /// The site's languages, if translations get `hreflang` links.
+    pub i18n: Option<I18nConfig>,
+    /// Environment variables exposed to templates as `build.env`.
+    pub build_env: Vec<String>,
+    /// Plugins whose shortcodes are expanded in content.
+    pub plugins: Option<Arc<PluginRegistry>>,
+    /// Writes `build-report.json` into the output after the build.
+    pub build_report: bool,
+    /// The preview deploy target successful rebuilds push changed
+    /// files to.
+    pub preview_deploy: Option<PathBuf>,
+    /// The pages successful builds capture as screenshots.
+    pub screenshots: Option<ScreenshotConfig>,
+    /// The social card images rendered for pages' `og:image`.
+    #[cfg(feature = "social-cards")]
+    pub social_cards: Option<SocialCardConfig>,
+    /// Skips failing pages and reports them together at the end instead
+    /// of aborting on the first error.
+    pub continue_on_error: bool,
+    /// Memory limits checked before each page is processed and rendered.
+    pub memory: MemoryConfig,
+    /// How symlinks in the content and static directories are handled.
+    pub symlinks: SymlinkPolicy,
+    /// Frontmatter values merged beneath each content file's own.
+    pub frontmatter_defaults: FrontmatterDefaults,
+    /// The git history content files' dates and contributors are read
+    /// from, if any.
+    #[cfg(feature = "git")]
+    pub git_history: Option<GitHistory>,
+    /// The bibliography `[@key]` citations in content are resolved
+    /// against, if citations are enabled.
+    pub citations: Option<Citations>,
+    /// The scripts run on each page after its metadata is extracted and
+    /// before it is written, if any.
+    #[cfg(feature = "scripting")]
+    pub scripts: Option<Arc<Scripts>>,
+    /// The headless CMS endpoints content files are fetched from before
+    /// each build, if any.
+    pub cms: Option<HeadlessCms>,
+    /// Runs the commands pages set in their `exec` frontmatter.
+    pub page_exec: bool,
+    /// Writes 410 Gone stubs for pages removed since the previous build.
+    pub tombstones: bool,
+    /// Resolves `[[Page Title]]` wiki links between pages.
+    pub wiki_links: bool,
+    /// Short vanity paths written as stubs forwarding to their targets.
+    pub shortlinks: RedirectMap,
+    /// The new path of each moved section, keyed by its old path; the
+    /// old URLs of its pages are written as stubs forwarding to them.
+    pub moved_sections: BTreeMap<String, String>,
+    /// Files the site's shortlinks, page aliases and moved pages are
+    /// exported to, such as a Netlify `_redirects` file.
+    pub redirect_exports: Vec<RedirectFormat>,
+    /// Frontmatter keys `NucleusFlow::check` requires every page to set.
+    pub required_frontmatter: Vec<String>,
+    /// HTTP status codes whose error page is rendered from the template
+    /// of the same name when no content file provides one.
+    pub error_pages: Vec<u16>,
+    /// Verifies the external links found by `NucleusFlow::check`.
+    pub link_checker: Option<ExternalLinkChecker>,
+    /// Rule severities for the accessibility lint run by
+    /// `NucleusFlow::check`, which skips it when unset.
+    pub a11y: Option<A11yConfig>,
+    /// Paths content may not claim, and how content clashing with
+    /// generated pages is handled.
+    pub routes: RoutesConfig,
+    /// Synonyms merged into canonical `tags` and `categories` terms as
+    /// pages are collected.
+    pub taxonomies: TaxonomyConfig,
+    /// How page summaries are cut from pages without a `<!--more-->`
+    /// marker or `summary` frontmatter.
+    pub summary: SummaryConfig,
+    /// The reading speed page reading times are estimated with.
+    pub words_per_minute: usize,
+    /// Whether footnotes are numbered on across pages, in site order.
+    pub footnote_numbering: FootnoteNumbering,
+    /// Archive pages listing dated pages by year and month, if they are
+    /// generated.
+    pub archives: Option<ArchiveConfig>,
+    /// The site's authors, if author pages are generated.
+    pub authors: Option<Authors>,
+    /// Where series index pages are written, if article series are
+    /// linked.
+    pub series: Option<SeriesConfig>,
+    /// The collections of data records rendered as a page per record.
+    pub data_pages: Vec<DataPagesConfig>,
+    /// Navigation menus exposed to templates as `site.menus`, keyed by
+    /// name.
+    pub menus: BTreeMap<String, Vec<MenuItem>>,
 }
 
 impl NucleusFlowConfig {
     /// Creates a new `NucleusFlowConfig` and validates directory paths.
+    ///
+    /// The output directory is created when the site is built, not here,
+    /// so a dry run leaves the file system untouched.
     pub fn new<P: AsRef<Path>>(
         content_dir: P,
         output_dir: P,
@@ -256,22 +545,586 @@ pub fn new<P: AsRef<Path>>(
             }
         }
 
-        if !output_dir.exists() {
-            fs::create_dir_all(&output_dir).map_err(|e| {
-                ProcessingError::configuration(
-                    format!("Failed to create output directory: {}", e),
-                    Some(output_dir.clone()),
-                    None,
-                )
-            })?;
-        }
-
         Ok(Self {
             content_dir,
             output_dir,
             template_dir,
+            static_files: None,
+            image_placeholders: None,
+            error_recovery: false,
+            ignore_rules: None,
+            timezone: SiteTimezone::default(),
+            deduplicate_images: false,
+            precompress: None,
+            debug_artifacts: true,
+            atomic_swap: None,
+            sitemap_base_url: None,
+            base_url: None,
+            i18n: None,
+            build_env: Vec::new(),
+            plugins: None,
+            build_report: false,
+            preview_deploy: None,
+            screenshots: None,
+            #[cfg(feature = "social-cards")]
+            social_cards: None,
+            continue_on_error: false,
+            memory: MemoryConfig::default(),
+            symlinks: SymlinkPolicy::default(),
+            frontmatter_defaults: FrontmatterDefaults::default(),
+            #[cfg(feature = "git")]
+            git_history: None,
+            citations: None,
+            #[cfg(feature = "scripting")]
+            scripts: None,
+            cms: None,
+            page_exec: false,
+            tombstones: false,
+            wiki_links: false,
+            shortlinks: RedirectMap::new(),
+            moved_sections: BTreeMap::new(),
+            redirect_exports: Vec::new(),
+            required_frontmatter: Vec::new(),
+            error_pages: vec![404],
+            link_checker: None,
+            a11y: None,
+            routes: RoutesConfig::default(),
+            taxonomies: TaxonomyConfig::default(),
+            summary: SummaryConfig::default(),
+            words_per_minute: DEFAULT_WORDS_PER_MINUTE,
+            footnote_numbering: FootnoteNumbering::default(),
+            archives: None,
+            authors: None,
+            series: None,
+            data_pages: Vec::new(),
+            menus: BTreeMap::new(),
         })
     }
+
+    /// Enables static file passthrough using the given configuration.
+    ///
+    /// A missing static directory is tolerated and results in no files
+    /// being copied.
+    pub fn with_static_files(mut self, config: StaticConfig) -> Self {
+        self.static_files = Some(config);
+        self
+    }
+
+    /// Generates blurred placeholders for the images among the static
+    /// files before pages render, so templates can show them while the
+    /// images load: `{{placeholder "img/hero.jpg"}}` writes the
+    /// placeholder of `img/hero.jpg` in the static directory as a data
+    /// URI.
+    pub fn with_image_placeholders(
+        mut self,
+        config: PlaceholderConfig,
+    ) -> Self {
+        self.image_placeholders = Some(config);
+        self
+    }
+
+    /// Enables or disables template error recovery mode.
+    ///
+    /// When enabled, a page whose template fails to render is written as
+    /// an inline error page and the build continues with the remaining
+    /// pages. This mode is intended for local previews only.
+    pub fn with_error_recovery(mut self, enabled: bool) -> Self {
+        self.error_recovery = enabled;
+        self
+    }
+
+    /// Sets the ignore rules applied when scanning content and copying
+    /// static files.
+    pub fn with_ignore_rules(mut self, rules: IgnoreRules) -> Self {
+        self.ignore_rules = Some(rules);
+        self
+    }
+
+    /// Sets the site timezone used to interpret frontmatter dates and to
+    /// stamp generated pages.
+    pub fn with_timezone(mut self, timezone: SiteTimezone) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// Enables or disables image deduplication.
+    ///
+    /// When enabled, identical images in the output are reduced to a
+    /// single copy after the build and references are rewritten.
+    pub fn with_image_deduplication(mut self, enabled: bool) -> Self {
+        self.deduplicate_images = enabled;
+        self
+    }
+
+    /// Enables `.gz`/`.br` twins of output files, written once the build
+    /// has finished.
+    pub fn with_precompression(
+        mut self,
+        config: PrecompressConfig,
+    ) -> Self {
+        self.precompress = Some(config);
+        self
+    }
+
+    /// Keeps or strips the source maps, unminified `.debug` variants of
+    /// assets and `sourceMappingURL` comments in the output.
+    ///
+    /// They are kept by default, as in the development profile; pass
+    /// [`Config::debug_artifacts`](crate::core::config::Config::debug_artifacts)
+    /// to follow a configuration's profile. Stripping changes nothing
+    /// else, so development and production outputs can be compared.
+    pub fn with_debug_artifacts(mut self, enabled: bool) -> Self {
+        self.debug_artifacts = enabled;
+        self
+    }
+
+    /// Enables atomic output using the given swap strategy.
+    ///
+    /// Pages are generated into a staging directory next to the output
+    /// directory, which only replaces it once the whole build succeeds.
+    /// A failed or interrupted build leaves the previous output intact.
+    pub fn with_atomic_swap(mut self, strategy: SwapStrategy) -> Self {
+        self.atomic_swap = Some(strategy);
+        self
+    }
+
+    /// Enables `sitemap.xml` generation for a site served from
+    /// `base_url`.
+    ///
+    /// Sites with more URLs than a single sitemap allows are sharded
+    /// behind a sitemap index. A multilingual site (see
+    /// [`NucleusFlowConfig::with_i18n`]) gets a sitemap per language
+    /// with `hreflang` alternates, and `sitemap.xml` indexes them all.
+    pub fn with_sitemap<S: Into<String>>(
+        mut self,
+        base_url: S,
+    ) -> Self {
+        self.sitemap_base_url = Some(base_url.into());
+        self
+    }
+
+    /// Adds a `<link rel="canonical">` to every page for a site
+    /// published at `base_url`, and exposes the URL to templates as
+    /// `canonical_url`.
+    ///
+    /// A page's `canonical` frontmatter names another URL, such as the
+    /// original of a syndicated post, or `false` to leave the link out.
+    /// Pages naming another URL are left out of the sitemap.
+    pub fn with_base_url<S: Into<String>>(
+        mut self,
+        base_url: S,
+    ) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Links the translations of each page of a multilingual site with
+    /// `hreflang` alternates, made absolute with the base URL when one
+    /// is set, and exposes them to templates as `alternates`.
+    ///
+    /// Nothing is linked if `i18n` has no languages.
+    pub fn with_i18n(mut self, i18n: I18nConfig) -> Self {
+        self.i18n = i18n.is_enabled().then(|| i18n);
+        self
+    }
+
+    /// Sets the environment variables exposed to templates as
+    /// `build.env`.
+    ///
+    /// The variables are read once at the start of each build. Blocked
+    /// or secret-looking names fail the build.
+    pub fn with_build_env<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.build_env = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the plugins whose shortcodes are expanded in content before
+    /// it is processed.
+    pub fn with_plugins(mut self, plugins: PluginRegistry) -> Self {
+        self.plugins = Some(Arc::new(plugins));
+        self
+    }
+
+    /// Enables or disables the build report.
+    ///
+    /// When enabled, `build-report.json` is written to the output
+    /// directory once every other step has run, listing each generated
+    /// file with its source, size and hash, and the build duration.
+    pub fn with_build_report(mut self, enabled: bool) -> Self {
+        self.build_report = enabled;
+        self
+    }
+
+    /// Pushes the files each successful [`NucleusFlow::rebuild`]
+    /// changes to a preview deploy target, so watch-mode rebuilds
+    /// update a shared preview without re-uploading the site. This is
+    /// what `nucleusflow serve --watch --preview-deploy <DIR>` sets.
+    ///
+    /// Other builds, and rebuilds with failed pages, push nothing.
+    pub fn with_preview_deploy<P: Into<PathBuf>>(
+        mut self,
+        target: P,
+    ) -> Self {
+        self.preview_deploy = Some(target.into());
+        self
+    }
+
+    /// Captures screenshots of pages after each successful build, for
+    /// visual regression checks between builds.
+    ///
+    /// Each configured page is opened by a headless browser and
+    /// written as a PNG to the screenshot directory, beside the
+    /// previous build's screenshot. Nothing is captured if the
+    /// configuration lists no pages.
+    pub fn with_screenshots(
+        mut self,
+        screenshots: ScreenshotConfig,
+    ) -> Self {
+        self.screenshots =
+            screenshots.is_enabled().then(|| screenshots);
+        self
+    }
+
+    /// Renders a social card image for each page, showing its title,
+    /// author and the site's name, and sets it as the page's `image`.
+    ///
+    /// Cards are written next to their pages, such as
+    /// `blog/post.card.png`, and become the `og:image` of pages
+    /// post-processed by
+    /// [`SocialMetaTags`](crate::generators::seo::SocialMetaTags).
+    /// Pages that set their own `image` keep it. Nothing is rendered if
+    /// the configuration sets no font.
+    #[cfg(feature = "social-cards")]
+    pub fn with_social_cards(
+        mut self,
+        cards: SocialCardConfig,
+    ) -> Self {
+        self.social_cards = cards.is_enabled().then(|| cards);
+        self
+    }
+
+    /// Generates archive pages listing the dated pages of each year
+    /// and month, such as `/archive/2024/` and `/archive/2024/05/`;
+    /// see [`generators::archives`].
+    pub fn with_archives(mut self, archives: ArchiveConfig) -> Self {
+        self.archives = Some(archives);
+        self
+    }
+
+    /// Generates a page per author listing the pages naming them in
+    /// their `author` frontmatter, and exposes each page's authors to
+    /// templates as `page.authors`; see [`generators::authors`].
+    pub fn with_authors(mut self, authors: Authors) -> Self {
+        self.authors = Some(authors);
+        self
+    }
+
+    /// Groups pages sharing a `series` frontmatter value into an
+    /// article series, exposing the series to each of its pages as
+    /// `page.series` and generating an index page per series; see
+    /// [`generators::series`].
+    pub fn with_series(mut self, series: SeriesConfig) -> Self {
+        self.series = Some(series);
+        self
+    }
+
+    /// Generates a page per record of each collection of records in a
+    /// data file, such as a page per team member, rendered with the
+    /// collection's template; see [`generators::data_pages`].
+    pub fn with_data_pages(
+        mut self,
+        collections: Vec<DataPagesConfig>,
+    ) -> Self {
+        self.data_pages = collections;
+        self
+    }
+
+    /// Sets the navigation menus exposed to templates as `site.menus`,
+    /// usually the `[menus]` config section, with the entries linking
+    /// to the page being rendered flagged; see [`generators::menus`].
+    pub fn with_menus(
+        mut self,
+        menus: BTreeMap<String, Vec<MenuItem>>,
+    ) -> Self {
+        self.menus = menus;
+        self
+    }
+
+    /// Enables or disables continue-on-error mode.
+    ///
+    /// When enabled, a page that fails to read, validate, process,
+    /// render or write is skipped and the rest of the site is built.
+    /// The build then fails with a `BuildFailed` error listing every
+    /// skipped page, its stage and its error.
+    pub fn with_continue_on_error(mut self, enabled: bool) -> Self {
+        self.continue_on_error = enabled;
+        self
+    }
+
+    /// Sets the build's memory limits.
+    ///
+    /// Past the soft limit, the content and asset caches are dropped and
+    /// the build continues. Past the hard limit, the build stops with a
+    /// `MemoryLimitExceeded` error naming the page being worked on, even
+    /// in continue-on-error mode. Limits are only enforced where the
+    /// process's memory use can be read.
+    pub fn with_memory_limits(mut self, limits: MemoryConfig) -> Self {
+        self.memory = limits;
+        self
+    }
+
+    /// Sets how symlinks are handled when scanning content and copying
+    /// static files.
+    ///
+    /// Symlinks are followed by default. Ignored files are skipped
+    /// before the policy applies, so they never fail the build.
+    pub fn with_symlink_policy(
+        mut self,
+        policy: SymlinkPolicy,
+    ) -> Self {
+        self.symlinks = policy;
+        self
+    }
+
+    /// Sets the frontmatter defaults applied to content files by path.
+    ///
+    /// Each file's defaults are merged beneath its own frontmatter
+    /// before it is processed, so processors and templates see them as
+    /// if the file had set them. The `_defaults.toml` files and
+    /// `_index.md` cascades of content directories are merged over
+    /// these rules.
+    pub fn with_frontmatter_defaults(
+        mut self,
+        defaults: FrontmatterDefaults,
+    ) -> Self {
+        self.frontmatter_defaults = defaults;
+        self
+    }
+
+    /// Sets content files' `lastmod`, `created` and `contributors`
+    /// frontmatter from their git history, unless they set them
+    /// themselves; see [`processors::git`].
+    #[cfg(feature = "git")]
+    pub fn with_git_history(mut self, history: GitHistory) -> Self {
+        self.git_history = Some(history);
+        self
+    }
+
+    /// Resolves `[@key]` citations in content against a BibTeX
+    /// bibliography, appending the references each page cites; see
+    /// [`processors::citations`].
+    pub fn with_citations(mut self, citations: Citations) -> Self {
+        self.citations = Some(citations);
+        self
+    }
+
+    /// Runs Rhai scripts on each page once its metadata is extracted
+    /// and on its HTML before it is written; see [`scripts`].
+    #[cfg(feature = "scripting")]
+    pub fn with_scripts(mut self, scripts: Scripts) -> Self {
+        self.scripts = Some(Arc::new(scripts));
+        self
+    }
+
+    /// Writes the entries of headless CMS endpoints to the content
+    /// directory before each build, replacing those written by the
+    /// previous one; see [`cms`].
+    pub fn with_cms(mut self, cms: HeadlessCms) -> Self {
+        self.cms = Some(cms);
+        self
+    }
+
+    /// Enables or disables page commands.
+    ///
+    /// When enabled, a page whose frontmatter sets `exec` runs that
+    /// command while it is rendered, and its template receives the
+    /// output as `exec`. Commands run with the build's permissions, so
+    /// only enable this for trusted content. While disabled, a page
+    /// setting `exec` fails to build.
+    pub fn with_page_exec(mut self, enabled: bool) -> Self {
+        self.page_exec = enabled;
+        self
+    }
+
+    /// Enables or disables tombstones for removed pages.
+    ///
+    /// When enabled, each build records its pages in
+    /// `pages-manifest.json` in the output directory. A page listed by
+    /// the previous build's manifest that is no longer generated gets a
+    /// "410 Gone" stub at its old path, which later builds keep writing
+    /// until something takes the path back.
+    pub fn with_tombstones(mut self, enabled: bool) -> Self {
+        self.tombstones = enabled;
+        self
+    }
+
+    /// Enables or disables wiki links.
+    ///
+    /// When enabled, `[[Page Title]]`, `[[page-slug]]` and
+    /// `[[target|Label]]` in a page's processed content become links to
+    /// the matching pages; see [`processors::wikilinks`]. A page with a
+    /// link that matches no page, or several, fails to build.
+    pub fn with_wiki_links(mut self, enabled: bool) -> Self {
+        self.wiki_links = enabled;
+        self
+    }
+
+    /// Sets the site's shortlinks, usually built with
+    /// `RedirectMap::from_shortlinks` from the `[shortlinks]` table.
+    ///
+    /// Each shortlink is written as an HTML stub forwarding to its
+    /// target and listed in the build report. A shortlink whose stub
+    /// would replace a page or static file fails the build.
+    pub fn with_shortlinks(mut self, shortlinks: RedirectMap) -> Self {
+        self.shortlinks = shortlinks;
+        self
+    }
+
+    /// Sets the sections that moved, usually from the
+    /// `[moved_sections]` table.
+    ///
+    /// Each page under a section's new path gets a permanent redirect
+    /// from its old URL, written as a stub and exported with the other
+    /// redirects, unless a page or an alias already has that URL.
+    pub fn with_moved_sections(
+        mut self,
+        moves: BTreeMap<String, String>,
+    ) -> Self {
+        self.moved_sections = moves;
+        self
+    }
+
+    /// Sets the files the site's redirects are exported to.
+    ///
+    /// Every shortlink, page alias and moved page is written to each
+    /// file, so hosts such as Netlify and Cloudflare Pages
+    /// ([`RedirectFormat::Netlify`]) answer old URLs with a real HTTP
+    /// redirect rather than the HTML stub.
+    pub fn with_redirect_exports<I>(mut self, formats: I) -> Self
+    where
+        I: IntoIterator<Item = RedirectFormat>,
+    {
+        self.redirect_exports = formats.into_iter().collect();
+        self
+    }
+
+    /// Adds error pages for more HTTP status codes than 404.
+    ///
+    /// The page for a status such as 410 is `410.html`. When no content
+    /// file generates it, it is rendered from the `410` template, such
+    /// as `410.hbs` in the template directory, if there is one. Error
+    /// pages are utility pages, left out of sitemaps and feeds.
+    pub fn with_error_pages<I>(mut self, codes: I) -> Self
+    where
+        I: IntoIterator<Item = u16>,
+    {
+        for code in codes {
+            if !self.error_pages.contains(&code) {
+                self.error_pages.push(code);
+            }
+        }
+        self
+    }
+
+    /// Sets the frontmatter keys every page must set, such as `title`
+    /// or `description`.
+    ///
+    /// Missing keys are reported by `NucleusFlow::check`; they do not
+    /// fail a build.
+    pub fn with_required_frontmatter<I, S>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.required_frontmatter =
+            keys.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Enables external link checking in `NucleusFlow::check`.
+    ///
+    /// Builds never check external links; only `check` does, after
+    /// every page has been checked, so dead links are reported last.
+    pub fn with_link_checker(
+        mut self,
+        checker: ExternalLinkChecker,
+    ) -> Self {
+        self.link_checker = Some(checker);
+        self
+    }
+
+    /// Sets the paths content may not claim and the policy for content
+    /// whose output path is generated by the build, such as a
+    /// shortlink stub.
+    pub fn with_routes(mut self, routes: RoutesConfig) -> Self {
+        self.routes = routes;
+        self
+    }
+
+    /// Sets the synonyms merged into canonical taxonomy terms, such as
+    /// `rustlang` into `rust`.
+    pub fn with_taxonomies(
+        mut self,
+        taxonomies: TaxonomyConfig,
+    ) -> Self {
+        self.taxonomies = taxonomies;
+        self
+    }
+
+    /// Sets how page summaries are cut from pages that set no
+    /// `summary` frontmatter and have no `<!--more-->` marker: after a
+    /// number of words, or of paragraphs.
+    pub fn with_summary(mut self, summary: SummaryConfig) -> Self {
+        self.summary = summary;
+        self
+    }
+
+    /// Sets the reading speed, in words per minute, that page reading
+    /// times are estimated with, exposed to templates as
+    /// `page.reading_time`.
+    pub fn with_words_per_minute(
+        mut self,
+        words_per_minute: usize,
+    ) -> Self {
+        self.words_per_minute = words_per_minute;
+        self
+    }
+
+    /// Sets how footnotes are numbered. With
+    /// [`FootnoteNumbering::Continuous`], each page's footnotes are
+    /// counted in site order and the content processor is given the
+    /// count before the page through
+    /// [`ContentProcessor::process_with_footnotes`].
+    pub fn with_footnote_numbering(
+        mut self,
+        numbering: FootnoteNumbering,
+    ) -> Self {
+        self.footnote_numbering = numbering;
+        self
+    }
+
+    /// Enables the accessibility lint in `NucleusFlow::check`.
+    ///
+    /// Problems from rules set to `error` fail the check; those set to
+    /// `warning` are reported alongside.
+    pub fn with_accessibility_check(
+        mut self,
+        config: A11yConfig,
+    ) -> Self {
+        self.a11y = Some(config);
+        self
+    }
+
+    /// Returns `true` if the path is excluded by the ignore rules.
+    fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        self.ignore_rules
+            .as_ref()
+            .map_or(false, |rules| rules.is_ignored(path, is_dir))
+    }
 }
 
 /// Main content processing pipeline for NucleusFlow.
@@ -281,6 +1134,10 @@ pub struct NucleusFlow {
     content_processor: Box<dyn ContentProcessor>,
     template_renderer: Box<dyn TemplateRenderer>,
     output_generator: Box<dyn Generator>,
+    validators: Vec<(Severity, Box<dyn Validator<Input = SourceFile>>)>,
+    post_processors: Vec<Box<dyn PostProcessor>>,
+    content_cache: ContentCache,
+    memory: MemoryGuard,
 }
 
 impl NucleusFlow {
@@ -291,99 +1148,4417 @@ pub fn new(
         template_renderer: Box<dyn TemplateRenderer>,
         output_generator: Box<dyn Generator>,
     ) -> Self {
+        let memory = MemoryGuard::new(config.memory);
         Self {
             config,
             content_processor,
             template_renderer,
             output_generator,
+            validators: Vec::new(),
+            post_processors: Vec::new(),
+            content_cache: ContentCache::default(),
+            memory,
         }
     }
 
-    /// Processes content files, transforms, renders, and generates HTML output.
-    pub fn process(&self) -> Result<()> {
-        for entry in fs::read_dir(&self.config.content_dir)? {
-            let entry = entry?;
-            let path = entry.path();
+    /// Adds a validator run on every content file before it is
+    /// processed.
+    ///
+    /// Validators run in the order they are added. A failing `Error`
+    /// validator fails the build once every file has been checked; a
+    /// failing `Warning` validator is logged and the build continues.
+    pub fn with_validator<V>(
+        mut self,
+        severity: Severity,
+        validator: V,
+    ) -> Self
+    where
+        V: Validator<Input = SourceFile> + 'static,
+    {
+        self.validators.push((severity, Box::new(validator)));
+        self
+    }
 
-            if path.is_file() {
-                self.process_file(&path)?;
-            }
-        }
-        Ok(())
+    /// Adds a post-processor run on every page's rendered HTML before it
+    /// is written.
+    ///
+    /// Post-processors run in the order they are added, each one taking
+    /// the previous one's output.
+    pub fn with_post_processor<P>(mut self, post_processor: P) -> Self
+    where
+        P: PostProcessor + 'static,
+    {
+        self.post_processors.push(Box::new(post_processor));
+        self
     }
 
-    /// Processes a single file within the pipeline.
+    /// Returns the cache hit and miss counts since the pipeline was
+    /// created.
     ///
-    /// # Arguments
-    /// * `path` - The path to the file to be processed.
+    /// Pages whose source is unchanged since an earlier build on the
+    /// same pipeline are served from the content cache.
+    pub fn cache_metrics(&self) -> CacheMetrics {
+        CacheMetrics {
+            content: self.content_cache.stats(),
+            template: self.template_renderer.cache_stats(),
+            asset: self.output_generator.cache_stats(),
+        }
+    }
+
+    /// Runs the validators over every content file without building.
     ///
     /// # Returns
-    /// * `Result<()>` - Indicates success, or an error if processing fails.
-    fn process_file(&self, path: &Path) -> Result<()> {
-        let content = fs::read_to_string(path)?;
-        let processed =
-            self.content_processor.process(&content, None)?;
-        let context =
-            serde_json::json!({ "content": processed, "path": path });
-
-        let template_name = "default";
-        let rendered =
-            self.template_renderer.render(template_name, &context)?;
-
-        let relative_path = path
-            .strip_prefix(&self.config.content_dir)
-            .map_err(|e| ProcessingError::ContentProcessing {
-                details: format!(
-                    "Failed to determine relative path: {}",
-                    e
-                ),
-                source: None,
-            })?;
-        let output_path = self
-            .config
-            .output_dir
-            .join(relative_path)
-            .with_extension("html");
+    /// * `Result<Diagnostics>` - Every validation failure, or an error
+    ///   if the content cannot be read.
+    pub fn validate(&self) -> Result<Diagnostics> {
+        let mut diagnostics = Diagnostics::default();
+        for path in self.content_files()? {
+            let file =
+                SourceFile::new(&path, fs::read_to_string(&path)?);
+            _ = self.validate_source(&file, &mut diagnostics);
+        }
+        Ok(diagnostics)
+    }
 
-        self.output_generator.generate(
-            &rendered,
-            &output_path,
-            None,
-        )?;
+    /// Processes content files, transforms, renders, and generates HTML output.
+    ///
+    /// With atomic output enabled, the build is written to a staging
+    /// directory that only replaces the output directory on success.
+    ///
+    /// # Returns
+    /// * `Result<BuildResult>` - The pages built and skipped, the
+    ///   warnings raised, the manifests written and the build's timings.
+    #[tracing::instrument(
+        name = "build",
+        skip_all,
+        fields(output = %self.config.output_dir.display())
+    )]
+    pub fn process(&self) -> Result<BuildResult> {
+        let started = self.build_start();
+        if let Some(cms) = &self.config.cms {
+            let written = cms.materialise(&self.config.content_dir)?;
+            tracing::info!("Fetched {} CMS entries", written.len());
+        }
+        let mut failures = self.failure_log();
+        let site = self.collect_into(&mut failures)?;
+        let collect = started.time.elapsed();
+        self.run(&site, started, failures, collect)
+    }
 
-        Ok(())
+    /// Renders and generates output for a site collected with
+    /// [`NucleusFlow::collect`], which may have been changed since.
+    ///
+    /// # Returns
+    /// * `Result<BuildResult>` - The outcome of the build, as with
+    ///   [`NucleusFlow::process`], with no time spent collecting.
+    #[tracing::instrument(
+        name = "build",
+        skip_all,
+        fields(output = %self.config.output_dir.display())
+    )]
+    pub fn process_site(&self, site: &Site) -> Result<BuildResult> {
+        let started = self.build_start();
+        self.run(site, started, self.failure_log(), Duration::ZERO)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use tempfile::TempDir;
+    /// Builds a collected site, then captures screenshots once every
+    /// page has built.
+    fn run(
+        &self,
+        site: &Site,
+        started: BuildStart,
+        mut failures: FailureLog,
+        collect: Duration,
+    ) -> Result<BuildResult> {
+        let rendering = Instant::now();
+        let mut result = self.deploy(site, started, &mut failures)?;
+        let render = rendering.elapsed();
+        let (skipped, warnings) = failures.finish()?;
+        result.skipped = skipped;
+        result.warnings = warnings;
 
-    #[test]
-    fn test_nucleus_flow_config_new() {
-        let temp_dir = TempDir::new().unwrap();
-        let content_path = temp_dir.path().join("content");
-        let output_path = temp_dir.path().join("output");
-        let template_path = temp_dir.path().join("templates");
+        let publishing = Instant::now();
+        result.warnings.extend(self.capture_screenshots()?);
+        result.timings = BuildTimings {
+            collect,
+            render,
+            publish: publishing.elapsed(),
+            total: started.time.elapsed(),
+        };
+        Ok(result)
+    }
 
-        fs::create_dir(&content_path).unwrap();
-        fs::create_dir(&template_path).unwrap();
+    /// Captures the configured pages of the build as screenshots, if
+    /// any are configured.
+    ///
+    /// # Returns
+    /// * `Result<Vec<String>>` - A warning for each screenshot that
+    ///   changed since the previous build.
+    fn capture_screenshots(&self) -> Result<Vec<String>> {
+        let mut warnings = Vec::new();
+        if let Some(config) = &self.config.screenshots {
+            for shot in Screenshots::new(config)
+                .capture(&self.config.output_dir)?
+            {
+                match shot.changed_pixels {
+                    Some(changed) if changed > 0.0 => {
+                        let warning = format!(
+                            "Screenshot of {} changed: {:.2}% of pixels differ",
+                            shot.url,
+                            changed * 100.0
+                        );
+                        tracing::warn!("{}", warning);
+                        warnings.push(warning);
+                    }
+                    _ => tracing::debug!(
+                        "Captured {} to {}",
+                        shot.url,
+                        shot.path.display()
+                    ),
+                }
+            }
+        }
+        Ok(warnings)
+    }
 
-        let config = NucleusFlowConfig::new(
-            &content_path,
-            &output_path,
-            &template_path,
-        )
-        .unwrap();
+    /// Rebuilds the site after its sources changed, then pushes the
+    /// files the build changed to the preview deploy target, if there
+    /// is one. `nucleusflow serve --watch` calls this each time a
+    /// [`SourceWatcher`](crate::watch::SourceWatcher) sees a change.
+    ///
+    /// Only files whose hash differs from the target's deploy
+    /// manifest are pushed; nothing is pushed if a page fails.
+    ///
+    /// # Returns
+    /// * `Result<(BuildResult, Vec<DeployedFile>)>` - The outcome of
+    ///   the build, as with [`NucleusFlow::process`], and the files
+    ///   pushed.
+    pub fn rebuild(&self) -> Result<(BuildResult, Vec<DeployedFile>)> {
+        let result = self.process()?;
+        let pushed = match &self.config.preview_deploy {
+            Some(target) => {
+                let pushed = PreviewDeploy::new(target)
+                    .push(&self.config.output_dir)?;
+                tracing::info!(
+                    "Pushed {} changed files to {}",
+                    pushed.len(),
+                    target.display()
+                );
+                pushed
+            }
+            None => Vec::new(),
+        };
+        Ok((result, pushed))
+    }
 
-        assert_eq!(config.content_dir, content_path);
-        assert_eq!(config.output_dir, output_path);
-        assert_eq!(config.template_dir, template_path);
+    /// Reads every content file into the site model without rendering
+    /// anything.
+    ///
+    /// Only the files of the content directory and of its sections,
+    /// subdirectories holding an `_index.md` or `_defaults.toml`, that
+    /// are not ignored are read. Includes and shortcodes are expanded
+    /// and the content processor is run on each page.
+    ///
+    /// # Returns
+    /// * `Result<Site>` - The site's pages, ordered by output path.
+    pub fn collect(&self) -> Result<Site> {
+        let mut failures = self.failure_log();
+        let site = self.collect_into(&mut failures)?;
+        _ = failures.finish()?;
+        Ok(site)
     }
 
-    #[test]
-    fn test_nucleus_flow_process() -> Result<()> {
+    /// Extracts the plain text and metadata of every page, without
+    /// rendering anything.
+    ///
+    /// The text comes from each page's processed content. Drafts and
+    /// utility pages are left out.
+    ///
+    /// # Returns
+    /// * `Result<Vec<ExtractedPage>>` - The pages, ordered by output
+    ///   path.
+    pub fn extract(&self) -> Result<Vec<ExtractedPage>> {
+        Ok(self
+            .collect()?
+            .pages
+            .iter()
+            .filter(|page| !page.is_draft() && !page.is_utility())
+            .map(ExtractedPage::new)
+            .collect())
+    }
+
+    /// Reads every content file into the site model, recording pages
+    /// that fail.
+    fn collect_into(&self, failures: &mut FailureLog) -> Result<Site> {
+        let mut pages = Vec::new();
+        let mut diagnostics = Diagnostics::default();
+        let mut footnotes = 0;
+        for path in self.content_files()? {
+            let content = match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(e) => {
+                    let error =
+                        ProcessingError::io_error(path.clone(), e);
+                    failures.record(&path, BuildStage::Read, error)?;
+                    continue;
+                }
+            };
+            self.check_memory(&path)?;
+            let file = SourceFile::new(&path, content);
+            let first = diagnostics.entries.len();
+            if !self.validate_source(&file, &mut diagnostics) {
+                if failures.keep_going {
+                    let messages: Vec<_> = diagnostics.entries[first..]
+                        .iter()
+                        .filter(|d| d.severity == Severity::Error)
+                        .map(|d| d.message.as_str())
+                        .collect();
+                    let error = ProcessingError::validation(
+                        messages.join("; "),
+                        None::<String>,
+                    );
+                    failures.record(
+                        &path,
+                        BuildStage::Validate,
+                        error,
+                    )?;
+                }
+                continue;
+            }
+            match self.collect_page(file, &mut footnotes) {
+                Ok(page) => pages.push(page),
+                Err(e) => {
+                    failures.record(&path, BuildStage::Process, e)?
+                }
+            }
+        }
+
+        for diagnostic in &diagnostics.entries {
+            match diagnostic.severity {
+                Severity::Warning => {
+                    failures.warn(diagnostic.to_string())
+                }
+                Severity::Error => tracing::error!("{}", diagnostic),
+            }
+        }
+        if diagnostics.has_errors() && !failures.keep_going {
+            return Err(ProcessingError::validation(
+                format!(
+                    "{} content validation errors",
+                    diagnostics.count(Severity::Error)
+                ),
+                Some(diagnostics.to_string()),
+            ));
+        }
+        let mut pages = self.resolve_routes(pages, failures)?;
+        if self.config.wiki_links {
+            pages = resolve_wiki_links(pages, failures)?;
+        }
+        let error_pages = self.error_pages(&pages);
+        pages.extend(error_pages);
+        let archives = self.archive_pages(&pages)?;
+        pages.extend(archives);
+        let author_pages = self.author_pages(&pages)?;
+        pages.extend(author_pages);
+        let series_pages = self.series_pages(&pages)?;
+        pages.extend(series_pages);
+        let data_pages = self.data_pages(&pages)?;
+        pages.extend(data_pages);
+        Ok(Site::new(pages))
+    }
+
+    /// Returns the author pages to render, if authors are set, except
+    /// those written where a content page is.
+    fn author_pages(&self, pages: &[Page]) -> Result<Vec<Page>> {
+        let authors = match &self.config.authors {
+            Some(authors) => authors,
+            None => return Ok(Vec::new()),
+        };
+        Ok(authors
+            .pages(pages)?
+            .into_iter()
+            .filter_map(|author| {
+                self.generated_page(
+                    pages,
+                    author.path,
+                    author.frontmatter,
+                )
+            })
+            .collect())
+    }
+
+    /// Returns the archive pages to render, if archives are enabled,
+    /// except those written where a content page is.
+    fn archive_pages(&self, pages: &[Page]) -> Result<Vec<Page>> {
+        let config = match &self.config.archives {
+            Some(config) => config,
+            None => return Ok(Vec::new()),
+        };
+        Ok(archive_pages(config, &self.config.timezone, pages)?
+            .into_iter()
+            .filter_map(|archive| {
+                self.generated_page(
+                    pages,
+                    archive.path,
+                    archive.frontmatter,
+                )
+            })
+            .collect())
+    }
+
+    /// Returns the series index pages to render, if series are
+    /// enabled, except those written where a content page is.
+    fn series_pages(&self, pages: &[Page]) -> Result<Vec<Page>> {
+        let config = match &self.config.series {
+            Some(config) => config,
+            None => return Ok(Vec::new()),
+        };
+        Ok(series_pages(config, pages)?
+            .into_iter()
+            .filter_map(|index| {
+                self.generated_page(
+                    pages,
+                    index.path,
+                    index.frontmatter,
+                )
+            })
+            .collect())
+    }
+
+    /// Returns the pages of the configured data records, except those
+    /// written where a content page is.
+    fn data_pages(&self, pages: &[Page]) -> Result<Vec<Page>> {
+        let mut generated = Vec::new();
+        for config in &self.config.data_pages {
+            generated.extend(
+                data_pages(config)?.into_iter().filter_map(|record| {
+                    self.generated_page(
+                        pages,
+                        record.path,
+                        record.frontmatter,
+                    )
+                }),
+            );
+        }
+        Ok(generated)
+    }
+
+    /// Returns a page generated from its frontmatter's template, or
+    /// `None` if a content page is written at its path.
+    fn generated_page(
+        &self,
+        pages: &[Page],
+        path: PathBuf,
+        frontmatter: serde_json::Map<String, serde_json::Value>,
+    ) -> Option<Page> {
+        if pages.iter().any(|page| page.path == path) {
+            return None;
+        }
+        let template =
+            frontmatter["template"].as_str().unwrap_or_default();
+        Some(Page::new(
+            self.config.template_dir.join(format!("{}.hbs", template)),
+            path,
+            frontmatter,
+        ))
+    }
+
+    /// Returns the error pages to render from templates: those with a
+    /// template, such as `404.hbs`, and no content file generating
+    /// them.
+    fn error_pages(&self, pages: &[Page]) -> Vec<Page> {
+        self.config
+            .error_pages
+            .iter()
+            .filter_map(|code| {
+                let path = PathBuf::from(format!("{}.html", code));
+                let template = self
+                    .config
+                    .template_dir
+                    .join(format!("{}.hbs", code));
+                if pages.iter().any(|page| page.path == path)
+                    || !template.is_file()
+                {
+                    return None;
+                }
+                let frontmatter = serde_json::json!({
+                    "title": status_title(*code),
+                    "status": code,
+                    "template": code.to_string(),
+                    "utility": true,
+                });
+                Some(Page::new(
+                    template,
+                    path,
+                    frontmatter
+                        .as_object()
+                        .cloned()
+                        .unwrap_or_default(),
+                ))
+            })
+            .collect()
+    }
+
+    /// Fails the pages under reserved paths or written where an earlier
+    /// page is, and applies the collision policy to pages written where
+    /// the build generates a page.
+    fn resolve_routes(
+        &self,
+        pages: Vec<Page>,
+        failures: &mut FailureLog,
+    ) -> Result<Vec<Page>> {
+        let routes = &self.config.routes;
+        let generated: BTreeMap<PathBuf, String> = self
+            .config
+            .shortlinks
+            .stub_paths()?
+            .into_iter()
+            .map(|(from, stub)| (stub, format!("shortlink '{}'", from)))
+            .collect();
+        let mut taken: BTreeSet<PathBuf> =
+            pages.iter().map(|page| page.path.clone()).collect();
+
+        let mut written: BTreeMap<PathBuf, PathBuf> = BTreeMap::new();
+        let mut resolved = Vec::with_capacity(pages.len());
+        for mut page in pages {
+            if let Some(reserved) = routes.reserves(&page.url) {
+                let error = ProcessingError::validation(
+                    format!(
+                        "'{}' is under the reserved path '{}'",
+                        page.url, reserved
+                    ),
+                    None::<String>,
+                );
+                failures.record(
+                    &page.source,
+                    BuildStage::Validate,
+                    error,
+                )?;
+                continue;
+            }
+            if let Some(first) = written.get(&page.path) {
+                let error = ProcessingError::validation(
+                    format!(
+                        "'{}' is also written by {}",
+                        page.path.display(),
+                        first.display()
+                    ),
+                    None::<String>,
+                );
+                failures.record(
+                    &page.source,
+                    BuildStage::Validate,
+                    error,
+                )?;
+                continue;
+            }
+            _ = written.insert(page.path.clone(), page.source.clone());
+            let route = match generated.get(&page.path) {
+                Some(route) => route,
+                None => {
+                    resolved.push(page);
+                    continue;
+                }
+            };
+            match routes.on_collision {
+                CollisionPolicy::Error => {
+                    let error = ProcessingError::validation(
+                        format!(
+                            "'{}' is generated for {}",
+                            page.path.display(),
+                            route
+                        ),
+                        None::<String>,
+                    );
+                    failures.record(
+                        &page.source,
+                        BuildStage::Validate,
+                        error,
+                    )?;
+                }
+                CollisionPolicy::Skip => failures.skip(
+                    &page.source,
+                    format!(
+                        "'{}' is generated for {}",
+                        page.path.display(),
+                        route
+                    ),
+                ),
+                CollisionPolicy::Suffix => {
+                    let path = (1..)
+                        .map(|n| suffixed_path(&page.path, n))
+                        .find(|path| {
+                            !generated.contains_key(path)
+                                && !taken.contains(path)
+                        })
+                        .unwrap_or_default();
+                    failures.warn(format!(
+                        "Moving {} to '{}': '{}' is generated for {}",
+                        page.source.display(),
+                        path.display(),
+                        page.path.display(),
+                        route
+                    ));
+                    _ = taken.insert(path.clone());
+                    page.url = page_url(&path);
+                    page.path = path;
+                    resolved.push(page);
+                }
+            }
+        }
+        Ok(resolved)
+    }
+
+    /// Returns the time and cache counts a build starts from.
+    fn build_start(&self) -> BuildStart {
+        BuildStart {
+            time: Instant::now(),
+            cache: self.cache_metrics(),
+            dry_run: false,
+        }
+    }
+
+    /// Checks memory use before working on `page`, dropping caches past
+    /// the soft limit.
+    ///
+    /// Errors past the hard limit are returned rather than recorded, so
+    /// they stop the build in every error mode.
+    fn check_memory(&self, page: &Path) -> Result<()> {
+        if let MemoryStatus::ShedCaches { used_mb } =
+            self.memory.check(page)?
+        {
+            tracing::warn!(
+                "Memory use of {} MB passed the soft limit; dropping caches",
+                used_mb
+            );
+            self.content_cache.clear();
+            self.output_generator.shed_cache();
+        }
+        Ok(())
+    }
+
+    /// Runs the command a page sets in its `exec` frontmatter, if page
+    /// commands are enabled.
+    ///
+    /// A dry run checks the command but does not run it, and exposes
+    /// `null` as its output.
+    fn run_page_command(
+        &self,
+        page: &Page,
+        command: &serde_json::Value,
+        dry_run: bool,
+    ) -> Result<serde_json::Value> {
+        if !self.config.page_exec {
+            return Err(ProcessingError::validation(
+                "Page sets `exec`, but page commands are disabled; \
+                 enable them with `with_page_exec`",
+                None::<String>,
+            ));
+        }
+        let command = PageCommand::from_frontmatter(command)?;
+        if dry_run {
+            tracing::info!(
+                "Not running '{}' for {} in a dry run",
+                command.program,
+                page.source.display()
+            );
+            return Ok(serde_json::Value::Null);
+        }
+        tracing::debug_span!("exec", program = %command.program)
+            .in_scope(|| command.run(page))
+    }
+
+    /// Returns an empty failure log for the configured error mode.
+    fn failure_log(&self) -> FailureLog {
+        FailureLog {
+            keep_going: self.config.continue_on_error,
+            ..FailureLog::default()
+        }
+    }
+
+    /// Returns the content files a build reads: the files of the
+    /// content directory and of its sections that are not ignored,
+    /// other than directory defaults.
+    fn content_files(&self) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        self.section_files(&self.config.content_dir, &mut files)?;
+        files.sort();
+        Ok(files)
+    }
+
+    /// Adds the content files of a directory, and of the sections
+    /// beneath it, to `files`.
+    fn section_files(
+        &self,
+        dir: &Path,
+        files: &mut Vec<PathBuf>,
+    ) -> Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            let is_dir = path.is_dir();
+            if self.config.is_ignored(&path, is_dir)
+                || is_defaults_file(&path)
+            {
+                continue;
+            }
+            if entry.file_type()?.is_symlink()
+                && !self.config.symlinks.allows(&path)?
+            {
+                continue;
+            }
+            if path.is_file() {
+                files.push(path);
+            } else if is_dir && is_section(&path) {
+                self.section_files(&path, files)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs every validator on a file, recording failures.
+    ///
+    /// # Returns
+    /// * `bool` - `false` if an `Error` validator failed.
+    fn validate_source(
+        &self,
+        file: &SourceFile,
+        diagnostics: &mut Diagnostics,
+    ) -> bool {
+        let mut valid = true;
+        for (severity, validator) in &self.validators {
+            if let Err(e) = validator.validate(file) {
+                valid &= *severity != Severity::Error;
+                let message = match e {
+                    ProcessingError::Validation { details, .. } => {
+                        details
+                    }
+                    e => e.to_string(),
+                };
+                diagnostics.push(Diagnostic {
+                    path: file.path.clone(),
+                    severity: *severity,
+                    message,
+                });
+            }
+        }
+        valid
+    }
+
+    /// Writes a site to the output directory, directly or through a
+    /// staging directory.
+    fn deploy(
+        &self,
+        site: &Site,
+        started: BuildStart,
+        failures: &mut FailureLog,
+    ) -> Result<BuildResult> {
+        match self.config.atomic_swap {
+            Some(strategy) => {
+                let staged = StagedOutput::new(
+                    &self.config.output_dir,
+                    strategy,
+                )?;
+                let result =
+                    self.build(site, staged.path(), started, failures)?;
+                staged.commit()?;
+                Ok(result)
+            }
+            None => {
+                let output_dir = &self.config.output_dir;
+                fs::create_dir_all(output_dir).map_err(|e| {
+                    ProcessingError::configuration(
+                        format!(
+                            "Failed to create output directory: {}",
+                            e
+                        ),
+                        Some(output_dir.clone()),
+                        None,
+                    )
+                })?;
+                self.build(site, output_dir, started, failures)
+            }
+        }
+    }
+
+    /// Runs the full pipeline without touching the output directory and
+    /// reports how it would change.
+    ///
+    /// The build is written to a scratch directory under the system
+    /// temporary directory, which is removed once it has been compared
+    /// with the current output.
+    ///
+    /// Page `exec` commands are checked but not run, so a page using
+    /// their output is compared as rendered without it. The content
+    /// processor, diagram commands and Rhai scripts still run, since
+    /// they produce the pages being compared.
+    ///
+    /// # Returns
+    /// * `Result<BuildPreview>` - The files that would be created,
+    ///   updated or deleted.
+    #[tracing::instrument(name = "preview", skip_all)]
+    pub fn preview(&self) -> Result<BuildPreview> {
+        let scratch = tempfile::TempDir::new().map_err(|e| {
+            ProcessingError::io_error(std::env::temp_dir(), e)
+        })?;
+        let mut failures = self.failure_log();
+        let site = self.collect_into(&mut failures)?;
+        let started = BuildStart {
+            dry_run: true,
+            ..self.build_start()
+        };
+        _ =
+            self.build(&site, scratch.path(), started, &mut failures)?;
+        _ = failures.finish()?;
+        BuildPreview::compare(&self.config.output_dir, scratch.path())
+    }
+
+    /// Builds the site without touching the output directory and reports
+    /// the problems that would show once it is published.
+    ///
+    /// Pages that fail are skipped and reported, whatever the error
+    /// mode. The build is written to a scratch directory under the
+    /// system temporary directory, where every rendered page's internal
+    /// links and images are checked before it is removed. External links
+    /// are checked too when a link checker is configured.
+    ///
+    /// # Returns
+    /// * `Result<CheckReport>` - The problems found, or an error if the
+    ///   build could not run at all.
+    #[tracing::instrument(name = "check", skip_all)]
+    pub fn check(&self) -> Result<CheckReport> {
+        let scratch = tempfile::TempDir::new().map_err(|e| {
+            ProcessingError::io_error(std::env::temp_dir(), e)
+        })?;
+        let mut failures = FailureLog {
+            keep_going: true,
+            ..FailureLog::default()
+        };
+        let site = self.collect_into(&mut failures)?;
+        let mut report = CheckReport::new(site.pages.len());
+        report.check_duplicates(&site);
+        report.check_frontmatter(
+            &site,
+            &self.config.required_frontmatter,
+        );
+
+        _ = self.build(
+            &site,
+            scratch.path(),
+            self.build_start(),
+            &mut failures,
+        )?;
+        for failure in &failures.failures {
+            report.push_failure(
+                &failure.path,
+                failure.stage,
+                &failure.error,
+            );
+        }
+        let mut anchors = OutputAnchors::new(scratch.path());
+        for page in &site.pages {
+            if let Ok(html) =
+                fs::read_to_string(scratch.path().join(&page.path))
+            {
+                report.check_links(page, &html, &mut anchors);
+                if let Some(a11y) = &self.config.a11y {
+                    report.check_accessibility(page, &html, a11y);
+                }
+            }
+        }
+        if let Some(checker) = &self.config.link_checker {
+            let dead = checker.dead_links(
+                report.external_links.keys().map(String::as_str),
+            )?;
+            report.push_dead_links(&dead);
+        }
+        Ok(report)
+    }
+
+    /// Describes the stages the build runs with the current
+    /// configuration, including those provided by plugins.
+    pub fn graph(&self) -> PipelineGraph {
+        let config = &self.config;
+        let mut graph = PipelineGraph::new();
+        let dir = |path: &Path| Some(path.display().to_string());
+
+        _ = graph.add(
+            StageKind::Sources,
+            "content",
+            Some(format!(
+                "{} (top-level files and sections)",
+                config.content_dir.display()
+            )),
+        );
+        if let Some(static_config) = &config.static_files {
+            _ = graph.add(
+                StageKind::Sources,
+                "static files",
+                dir(&static_config.dir),
+            );
+        }
+        if config.ignore_rules.is_some() {
+            _ = graph.add(
+                StageKind::Sources,
+                "ignore rules",
+                Some(IGNORE_FILE_NAME),
+            );
+        }
+
+        if let Some(plugins) = &config.plugins {
+            let names: Vec<_> =
+                plugins.shortcode_names().map(String::as_str).collect();
+            if !names.is_empty() {
+                _ = graph.add(
+                    StageKind::Processors,
+                    "plugin shortcodes",
+                    Some(names.join(", ")),
+                );
+            }
+        }
+        for (severity, validator) in &self.validators {
+            _ = graph.add(
+                StageKind::Processors,
+                format!("{} validator", severity),
+                Some(type_label(validator)),
+            );
+        }
+        _ = graph.add(
+            StageKind::Processors,
+            "content processor",
+            Some(type_label(&self.content_processor)),
+        );
+        if config.wiki_links {
+            _ = graph.add(
+                StageKind::Processors,
+                "wiki links",
+                None::<String>,
+            );
+        }
+
+        _ = graph.add(
+            StageKind::Renderers,
+            "template renderer",
+            Some(format!(
+                "{} ({})",
+                type_label(&self.template_renderer),
+                config.template_dir.display()
+            )),
+        );
+        if let Some(plugins) = &config.plugins {
+            let names: Vec<_> = plugins
+                .helpers()
+                .map(|(name, _)| name.as_str())
+                .collect();
+            if !names.is_empty() {
+                _ = graph.add(
+                    StageKind::Renderers,
+                    "plugin helpers",
+                    Some(names.join(", ")),
+                );
+            }
+        }
+        if config.error_recovery {
+            _ = graph.add(
+                StageKind::Renderers,
+                "error recovery",
+                None::<String>,
+            );
+        }
+
+        if let Some(base_url) = &config.base_url {
+            _ = graph.add(
+                StageKind::Generators,
+                "canonical links",
+                Some(base_url.as_str()),
+            );
+        }
+        if let Some(i18n) = &config.i18n {
+            _ = graph.add(
+                StageKind::Generators,
+                "hreflang",
+                Some(i18n.languages.join(", ")),
+            );
+        }
+        for post_processor in &self.post_processors {
+            _ = graph.add(
+                StageKind::Generators,
+                "post-processor",
+                Some(post_processor.name()),
+            );
+        }
+        _ = graph.add(
+            StageKind::Generators,
+            "output generator",
+            Some(type_label(&self.output_generator)),
+        );
+        #[cfg(feature = "social-cards")]
+        if let Some(cards) = &config.social_cards {
+            _ = graph.add(
+                StageKind::Generators,
+                "social cards",
+                Some(format!("{}x{}", cards.width, cards.height)),
+            );
+        }
+        if let Some(base_url) = &config.sitemap_base_url {
+            _ = graph.add(
+                StageKind::Generators,
+                "sitemap",
+                Some(base_url.as_str()),
+            );
+        }
+        if !config.shortlinks.is_empty() {
+            _ = graph.add(
+                StageKind::Generators,
+                "shortlinks",
+                Some(format!("{} links", config.shortlinks.len())),
+            );
+        }
+        if !config.redirect_exports.is_empty() {
+            let files: Vec<&str> = config
+                .redirect_exports
+                .iter()
+                .map(RedirectFormat::file_name)
+                .collect();
+            _ = graph.add(
+                StageKind::Generators,
+                "redirect exports",
+                Some(files.join(", ")),
+            );
+        }
+        if config.tombstones {
+            _ = graph.add(
+                StageKind::Generators,
+                "tombstones",
+                Some(MANIFEST_FILE_NAME),
+            );
+        }
+        if config.deduplicate_images {
+            _ = graph.add(
+                StageKind::Generators,
+                "image deduplication",
+                None::<String>,
+            );
+        }
+        if !config.debug_artifacts {
+            _ = graph.add(
+                StageKind::Generators,
+                "debug artifact stripping",
+                None::<String>,
+            );
+        }
+        if config.precompress.is_some() {
+            _ = graph.add(
+                StageKind::Generators,
+                "pre-compression",
+                None::<String>,
+            );
+        }
+        if config.build_report {
+            _ = graph.add(
+                StageKind::Generators,
+                "build report",
+                Some(REPORT_FILE_NAME),
+            );
+        }
+
+        let strategy = match config.atomic_swap {
+            Some(SwapStrategy::Rename) => "staged, swapped by rename",
+            Some(SwapStrategy::Symlink) => "staged, swapped by symlink",
+            None => "written in place",
+        };
+        _ = graph.add(
+            StageKind::Deployers,
+            "output",
+            Some(format!(
+                "{} ({})",
+                config.output_dir.display(),
+                strategy
+            )),
+        );
+        if let Some(screenshots) = &config.screenshots {
+            _ = graph.add(
+                StageKind::Deployers,
+                "screenshots",
+                Some(format!(
+                    "{} pages to {}",
+                    screenshots.pages.len(),
+                    screenshots.dir.display()
+                )),
+            );
+        }
+        if let Some(target) = &config.preview_deploy {
+            _ = graph.add(
+                StageKind::Deployers,
+                "preview deploy",
+                Some(format!("{} on rebuilds", target.display())),
+            );
+        }
+        graph
+    }
+
+    /// Reports whether the build reads a source file, and if not, why.
+    ///
+    /// # Arguments
+    /// * `path` - The file, relative to the working directory or
+    ///   absolute, using the same form as the content directory.
+    pub fn source_status(&self, path: &Path) -> SourceStatus {
+        if !path.exists() {
+            return SourceStatus::Missing;
+        }
+        if !path.is_file() {
+            return SourceStatus::NotAFile;
+        }
+        let relative = match path.strip_prefix(&self.config.content_dir)
+        {
+            Ok(relative) => relative,
+            Err(_) => return SourceStatus::OutsideContent,
+        };
+        let mut dir = self.config.content_dir.clone();
+        for component in relative.parent().into_iter().flatten() {
+            dir.push(component);
+            if !is_section(&dir) {
+                return SourceStatus::Nested;
+            }
+            if self.config.is_ignored(&dir, true) {
+                return SourceStatus::Ignored;
+            }
+        }
+        if self.config.is_ignored(path, false) {
+            return SourceStatus::Ignored;
+        }
+        if is_defaults_file(path) {
+            return SourceStatus::Defaults;
+        }
+        SourceStatus::Processed(relative.with_extension("html"))
+    }
+
+    /// Generates the placeholders of the static files' images, keyed
+    /// by their path in the static directory, if they are enabled.
+    fn image_placeholders(&self) -> Result<Option<serde_json::Value>> {
+        let (config, dir) = match (
+            &self.config.image_placeholders,
+            &self.config.static_files,
+        ) {
+            (Some(config), Some(static_files))
+                if static_files.dir.is_dir() =>
+            {
+                (config, &static_files.dir)
+            }
+            _ => return Ok(None),
+        };
+        let mut generator = HtmlGenerator::new()
+            .with_asset_dir(dir)?
+            .with_placeholders(*config);
+        if let Some(rules) = &self.config.ignore_rules {
+            generator = generator.with_ignore_rules(rules.clone());
+        }
+        generator.generate_placeholders()?;
+        Ok(Some(generator.placeholders_context()))
+    }
+
+    /// Runs every build step for a collected site, writing output under
+    /// `output_dir`.
+    fn build(
+        &self,
+        site: &Site,
+        output_dir: &Path,
+        started: BuildStart,
+        failures: &mut FailureLog,
+    ) -> Result<BuildResult> {
+        let mut pages = Vec::with_capacity(site.pages.len());
+        let mut manifests = Vec::new();
+        let mut sources = BTreeMap::new();
+        let mut globals = serde_json::json!({
+            "build": {
+                "env": snapshot_build_env(&self.config.build_env)?,
+            },
+        });
+        if let Some(placeholders) = self.image_placeholders()? {
+            globals["placeholders"] = placeholders;
+        }
+        let hreflang = self.config.i18n.as_ref().map(|i18n| {
+            let links = HreflangLinks::new(i18n, site);
+            match &self.config.base_url {
+                Some(base_url) => {
+                    links.with_base_url(base_url.as_str())
+                }
+                None => links,
+            }
+        });
+        let mut sitemap =
+            self.config.sitemap_base_url.as_ref().map(|base_url| {
+                let canonical = CanonicalLink::new(
+                    self.config.base_url.as_ref().unwrap_or(base_url),
+                );
+                let timezone = self.config.timezone;
+                let sitemap = match &self.config.i18n {
+                    Some(i18n) => SiteSitemap::Languages(
+                        LanguageSitemaps::new(
+                            base_url.as_str(),
+                            timezone,
+                        ),
+                        HreflangLinks::new(i18n, site)
+                            .with_base_url(base_url.as_str()),
+                    ),
+                    None => SiteSitemap::Single(Sitemap::new(
+                        base_url.as_str(),
+                        timezone,
+                    )),
+                };
+                (sitemap, canonical)
+            });
+        #[cfg(feature = "social-cards")]
+        let social_cards = self
+            .config
+            .social_cards
+            .as_ref()
+            .map(SocialCards::new)
+            .transpose()?;
+        let links = SiteLinks {
+            navigation: site.navigation(),
+            series: match &self.config.series {
+                Some(config) => series_contexts(config, &site.pages)?,
+                None => HashMap::new(),
+            },
+            breadcrumbs: site.breadcrumbs(HOME_TITLE),
+        };
+        for page in &site.pages {
+            self.check_memory(&page.source)?;
+            #[cfg(feature = "social-cards")]
+            let carded = match &social_cards {
+                Some(cards) => match cards.attach(page, output_dir) {
+                    Ok(carded) => carded,
+                    Err(e) => {
+                        failures.record(
+                            &page.source,
+                            BuildStage::Generate,
+                            e,
+                        )?;
+                        continue;
+                    }
+                },
+                None => None,
+            };
+            #[cfg(feature = "social-cards")]
+            let page = carded.as_ref().unwrap_or(page);
+            if let Err((stage, e)) = self.render_page(
+                page,
+                output_dir,
+                &globals,
+                hreflang.as_ref(),
+                &links,
+                started.dry_run,
+            ) {
+                failures.record(&page.source, stage, e)?;
+                continue;
+            }
+            if let Some((sitemap, canonical)) = sitemap.as_mut() {
+                if !page.is_utility() && canonical.is_canonical(page) {
+                    sitemap.add(page)?;
+                }
+            }
+            _ = sources.insert(page.path.clone(), page.source.clone());
+            pages.push(BuiltPage {
+                source: page.source.clone(),
+                path: page.path.clone(),
+                url: page.url.clone(),
+            });
+        }
+
+        if let Some(static_config) = &self.config.static_files {
+            let mut copier = StaticFileCopier::new(static_config)?;
+            if let Some(rules) = &self.config.ignore_rules {
+                copier = copier.with_ignore_rules(rules.clone());
+            }
+            copier = copier.with_symlink_policy(self.config.symlinks);
+            let copied = copier.copy_to(output_dir)?;
+            tracing::debug!("Copied {} static files", copied.len());
+            for target in copied {
+                if let Ok(relative) = target.strip_prefix(output_dir) {
+                    let source = static_config.dir.join(relative);
+                    _ = sources.insert(relative.to_path_buf(), source);
+                }
+            }
+        }
+
+        let aliases = RedirectMap::from_aliases(&site.pages)?;
+        let moves = RedirectMap::from_moves(
+            &self.config.moved_sections,
+            &site.pages,
+        )?;
+        let redirects = [
+            ("Shortlink", &self.config.shortlinks),
+            ("Alias", &aliases),
+            ("Moved page", &moves),
+        ];
+        for (kind, redirects) in redirects {
+            if !redirects.is_empty() {
+                write_redirect_stubs(
+                    kind, redirects, &sources, output_dir,
+                )?;
+            }
+        }
+        if !self.config.redirect_exports.is_empty() {
+            let mut all = RedirectMap::new();
+            for (_, redirects) in redirects {
+                all.extend(redirects)?;
+            }
+            manifests.extend(
+                all.export(output_dir, &self.config.redirect_exports)?,
+            );
+        }
+
+        if self.config.tombstones {
+            let stubs = [&aliases, &moves];
+            manifests.push(self.write_tombstones(
+                site, &sources, &stubs, failures, output_dir,
+            )?);
+        }
+
+        if let Some((sitemap, _)) = &sitemap {
+            manifests.extend(sitemap.write(output_dir)?);
+        }
+
+        if self.config.deduplicate_images {
+            let report = deduplicate_images(
+                output_dir,
+                self.config.base_url.as_deref(),
+            )?;
+            tracing::info!(
+                "Removed {} duplicate images, saving {} bytes",
+                report.duplicates.len(),
+                report.bytes_saved
+            );
+        }
+
+        if !self.config.debug_artifacts {
+            let report = strip_debug_artifacts(output_dir)?;
+            tracing::info!(
+                "Stripped {} debug artifacts and {} source map links",
+                report.removed.len(),
+                report.unlinked.len()
+            );
+        }
+
+        if let Some(precompress) = &self.config.precompress {
+            let report = precompress_dir(output_dir, precompress)?;
+            tracing::info!(
+                "Pre-compressed {} files ({} bytes to {} bytes)",
+                report.files,
+                report.original_bytes,
+                report.compressed_bytes
+            );
+        }
+
+        let cache = self.cache_metrics().since(&started.cache);
+        for line in cache.to_string().lines() {
+            tracing::info!("{}", line);
+        }
+
+        if self.config.build_report {
+            let report = BuildReport::collect(
+                output_dir,
+                &sources,
+                started.time.elapsed(),
+                self.config.timezone.now().to_rfc3339(),
+            )?
+            .with_cache(cache)
+            .with_shortlinks(&self.config.shortlinks);
+            manifests.push(report.write(output_dir)?);
+            tracing::info!(
+                "Built {} files ({} bytes, {} gzipped, {} with brotli) in {} ms",
+                report.files.len(),
+                report.total_bytes,
+                report.total_gzip_bytes,
+                report.total_brotli_bytes,
+                report.duration_ms
+            );
+        }
+
+        pages.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(BuildResult {
+            pages,
+            manifests: manifests
+                .iter()
+                .map(|path| {
+                    path.strip_prefix(output_dir)
+                        .unwrap_or(path)
+                        .to_path_buf()
+                })
+                .collect(),
+            ..BuildResult::default()
+        })
+    }
+
+    /// Writes stubs for the pages removed since the previous build and
+    /// records this build's pages for the next one.
+    ///
+    /// Pages skipped in continue-on-error mode still count as generated,
+    /// so a failing page is not mistaken for a removed one.
+    ///
+    /// # Returns
+    /// * `Result<PathBuf>` - The path of the page manifest written.
+    fn write_tombstones(
+        &self,
+        site: &Site,
+        sources: &BTreeMap<PathBuf, PathBuf>,
+        stubs: &[&RedirectMap],
+        failures: &FailureLog,
+        output_dir: &Path,
+    ) -> Result<PathBuf> {
+        let mut pages: BTreeSet<String> = site
+            .pages
+            .iter()
+            .map(|page| manifest_path(&page.path))
+            .collect();
+        for failure in &failures.failures {
+            if let SourceStatus::Processed(path) =
+                self.source_status(&failure.path)
+            {
+                _ = pages.insert(manifest_path(&path));
+            }
+        }
+        let mut files: BTreeSet<String> =
+            sources.keys().map(|path| manifest_path(path)).collect();
+        for redirects in stubs.iter().chain([&&self.config.shortlinks])
+        {
+            for stub in redirects.stub_paths()?.values() {
+                _ = files.insert(manifest_path(stub));
+            }
+        }
+
+        let previous = PageManifest::read(&self.config.output_dir)?;
+        let manifest = previous.next(pages, &files);
+        let written = manifest.write_tombstones(output_dir)?;
+        let path = manifest.write(output_dir)?;
+        tracing::info!("Wrote {} tombstones", written.len());
+        Ok(path)
+    }
+
+    /// Turns a single content file into a page.
+    ///
+    /// # Arguments
+    /// * `file` - The content file, as read from disk.
+    /// * `footnotes` - The footnotes on the pages collected before it,
+    ///   which the page's footnotes are added to when footnotes are
+    ///   numbered continuously.
+    ///
+    /// # Returns
+    /// * `Result<Page>` - The processed page, or an error if processing
+    ///   fails.
+    #[tracing::instrument(
+        name = "process",
+        level = "debug",
+        skip_all,
+        fields(source = %file.path.display())
+    )]
+    fn collect_page(
+        &self,
+        file: SourceFile,
+        footnotes: &mut usize,
+    ) -> Result<Page> {
+        let SourceFile { path, mut content } = file;
+        let relative_path = path
+            .strip_prefix(&self.config.content_dir)
+            .map_err(|e| ProcessingError::ContentProcessing {
+                details: format!(
+                    "Failed to determine relative path: {}",
+                    e
+                ),
+                source: None,
+            })?;
+        content = Includes::new(&self.config.content_dir)
+            .expand(&path, &content)?;
+        if let Some(plugins) = &self.config.plugins {
+            content = plugins.expand_shortcodes(&content)?;
+        }
+        content = merge_defaults(
+            self.config
+                .frontmatter_defaults
+                .cascade_for(&self.config.content_dir, relative_path)?,
+            &content,
+        )?;
+        let mut frontmatter =
+            MarkdownProcessor::parse_frontmatter(&content)
+                .into_iter()
+                .collect();
+        #[cfg(feature = "git")]
+        if let Some(history) = &self.config.git_history {
+            history.apply(relative_path, &mut frontmatter);
+        }
+        self.config.taxonomies.apply(&mut frontmatter);
+        let processed = match self.config.footnote_numbering {
+            FootnoteNumbering::Page => self
+                .content_cache
+                .get_or_process(&path, &content, None, || {
+                    self.content_processor.process(&content, None)
+                })?,
+            FootnoteNumbering::Continuous => {
+                let start = *footnotes;
+                *footnotes += count_footnotes(&content);
+                self.content_cache.get_or_process(
+                    &path,
+                    &content,
+                    Some(start),
+                    || {
+                        self.content_processor
+                            .process_with_footnotes(&content, start)
+                    },
+                )?
+            }
+        };
+
+        let (toc, content) = split_toc(&processed);
+        let content = match &self.config.citations {
+            Some(citations) => citations.render(content)?,
+            None => content.to_string(),
+        };
+        #[cfg(feature = "scripting")]
+        let content = match &self.config.scripts {
+            Some(scripts) => {
+                let mut content = content;
+                scripts.on_page(
+                    &path,
+                    &mut frontmatter,
+                    &mut content,
+                )?;
+                content
+            }
+            None => content,
+        };
+        let (summary, content) = extract_summary(
+            &frontmatter,
+            &content,
+            &self.config.summary,
+        );
+        let reading = ReadingStats::new(
+            &plain_text(&content),
+            self.config.words_per_minute,
+        );
+        let output_relative =
+            match output_override(&path, &frontmatter)? {
+                Some(path) => path,
+                None => relative_path.with_extension("html"),
+            };
+
+        Ok(Page {
+            content,
+            toc: toc.map(str::to_string),
+            summary,
+            reading,
+            ..Page::new(path, output_relative, frontmatter)
+        })
+    }
+
+    /// Renders a single page and writes it within the pipeline.
+    ///
+    /// # Arguments
+    /// * `page` - The page to render.
+    /// * `output_dir` - The directory the page is written under.
+    /// * `globals` - Build-wide template data, such as `build` and
+    ///   `placeholders`, added to the page's context.
+    /// * `hreflang` - The site's translations, if it is multilingual.
+    /// * `links` - The page's place in the site: the pages before and
+    ///   after it in its section, exposed as `page.prev` and
+    ///   `page.next`, its article series, if any, as `page.series`,
+    ///   and its breadcrumb trail, as `page.breadcrumbs`.
+    /// * `dry_run` - Whether the page's command is checked but not run.
+    ///
+    /// # Returns
+    /// * `Result<(), (BuildStage, ProcessingError)>` - Indicates
+    ///   success, or the stage that failed with its error.
+    #[tracing::instrument(
+        name = "page",
+        level = "debug",
+        skip_all,
+        fields(url = %page.url)
+    )]
+    fn render_page(
+        &self,
+        page: &Page,
+        output_dir: &Path,
+        globals: &serde_json::Value,
+        hreflang: Option<&HreflangLinks>,
+        links: &SiteLinks,
+        dry_run: bool,
+    ) -> std::result::Result<(), (BuildStage, ProcessingError)> {
+        let render_error = |e| (BuildStage::Render, e);
+        let timezone = &self.config.timezone;
+        let mut context = serde_json::json!({
+            "content": page.content,
+            "path": page.source,
+            "page": page.to_json(),
+            "generated_at": timezone.now().to_rfc3339(),
+            "timezone": timezone.name(),
+        });
+        for (key, value) in globals.as_object().into_iter().flatten() {
+            context[key.as_str()] = value.clone();
+        }
+        if let Some(authors) = &self.config.authors {
+            context["page"]["authors"] = serde_json::json!(authors
+                .page_authors(page)
+                .map_err(render_error)?);
+        }
+        if let Some(series) = links.series.get(&page.url) {
+            context["page"]["series"] = series.clone();
+        }
+        if let Some(breadcrumbs) = links.breadcrumbs.get(&page.url) {
+            context["page"]["breadcrumbs"] =
+                serde_json::json!(breadcrumbs);
+        }
+        if !self.config.menus.is_empty() {
+            context["site"]["menus"] =
+                menus_context(&self.config.menus, &page.url);
+        }
+        if let Some(navigation) = links.navigation.get(&page.url) {
+            context["page"]["prev"] =
+                serde_json::json!(navigation.prev);
+            context["page"]["next"] =
+                serde_json::json!(navigation.next);
+        }
+        if let Some(date) = page.date() {
+            context["date"] = serde_json::json!(timezone
+                .parse_date(date)
+                .map_err(|e| {
+                    render_error(frontmatter_error(
+                        &page.source,
+                        "date",
+                        e,
+                    ))
+                })?
+                .to_rfc3339());
+        }
+        let canonical =
+            self.config.base_url.as_ref().map(CanonicalLink::new);
+        if let Some(href) = canonical
+            .as_ref()
+            .and_then(|canonical| canonical.href(page))
+        {
+            context["canonical_url"] = serde_json::json!(href);
+        }
+        if let Some(hreflang) = hreflang {
+            let alternates: Vec<_> = hreflang
+                .alternates(page)
+                .into_iter()
+                .map(|(lang, url)| serde_json::json!({ "lang": lang, "url": url }))
+                .collect();
+            context["lang"] =
+                serde_json::json!(hreflang.language(page));
+            context["alternates"] = serde_json::json!(alternates);
+        }
+        if let Some(command) = page.frontmatter.get(EXEC_KEY) {
+            context[EXEC_KEY] = self
+                .run_page_command(page, command, dry_run)
+                .map_err(|e| {
+                    render_error(frontmatter_error(
+                        &page.source,
+                        EXEC_KEY,
+                        e,
+                    ))
+                })?;
+        }
+
+        let template_name = page
+            .frontmatter
+            .get("template")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or("default");
+        let rendered = match tracing::debug_span!(
+            "render",
+            template = template_name
+        )
+        .in_scope(|| {
+            self.template_renderer.render(template_name, &context)
+        }) {
+            Ok(rendered) => rendered,
+            Err(e) if self.config.error_recovery => {
+                tracing::warn!(
+                    error = %e,
+                    "Template '{}' failed for {}",
+                    template_name,
+                    page.source.display()
+                );
+                template::render_error_placeholder(
+                    template_name,
+                    &page.source,
+                    &e,
+                )
+            }
+            Err(e) => return Err(render_error(e)),
+        };
+        let rendered = self
+            .post_processors
+            .iter()
+            .try_fold(rendered, |html, post_processor| {
+                tracing::debug_span!(
+                    "post_process",
+                    name = post_processor.name()
+                )
+                .in_scope(|| post_processor.process(&html, page))
+            })
+            .and_then(|html| match &canonical {
+                Some(canonical) => canonical.process(&html, page),
+                None => Ok(html),
+            })
+            .and_then(|html| match hreflang {
+                Some(hreflang) => hreflang.process(&html, page),
+                None => Ok(html),
+            })
+            .map_err(|e| (BuildStage::PostProcess, e))?;
+        #[cfg(feature = "scripting")]
+        let rendered = match &self.config.scripts {
+            Some(scripts) => scripts
+                .on_output(page, rendered)
+                .map_err(|e| (BuildStage::PostProcess, e))?,
+            None => rendered,
+        };
+
+        let options = page
+            .frontmatter
+            .get("bundles")
+            .map(|bundles| serde_json::json!({ "bundles": bundles }));
+        let output_path = output_dir.join(&page.path);
+        tracing::debug_span!("generate", output = %output_path.display())
+            .in_scope(|| {
+                self.output_generator.generate(
+                    &rendered,
+                    &output_path,
+                    options.as_ref(),
+                )
+            })
+            .map_err(|e| (BuildStage::Generate, e))
+    }
+}
+
+/// Each page's place in the site, worked out from every page before
+/// any is rendered, keyed by page URL.
+#[derive(Debug, Default)]
+struct SiteLinks {
+    navigation: HashMap<String, PageNavigation>,
+    series: HashMap<String, serde_json::Value>,
+    breadcrumbs: HashMap<String, Vec<Breadcrumb>>,
+}
+
+/// When a build started, the cache counts at that point, and whether
+/// it is a dry run, which runs no page commands.
+#[derive(Debug, Clone, Copy)]
+struct BuildStart {
+    time: Instant,
+    cache: CacheMetrics,
+    dry_run: bool,
+}
+
+/// The pages a build has skipped, or, outside continue-on-error mode,
+/// the first page error, which stops the build, along with the pages
+/// left out and the warnings raised without failing it.
+#[derive(Debug, Default)]
+struct FailureLog {
+    keep_going: bool,
+    failures: Vec<PageFailure>,
+    skipped: Vec<SkippedPage>,
+    warnings: Vec<String>,
+}
+
+impl FailureLog {
+    /// Records a failed page, or returns its error if the build should
+    /// stop.
+    fn record(
+        &mut self,
+        path: &Path,
+        stage: BuildStage,
+        error: ProcessingError,
+    ) -> Result<()> {
+        if !self.keep_going {
+            return Err(error);
+        }
+        tracing::error!(
+            error = %error,
+            "Skipping {} after it failed to {}",
+            path.display(),
+            stage
+        );
+        self.failures.push(PageFailure {
+            path: path.to_path_buf(),
+            stage,
+            error,
+        });
+        Ok(())
+    }
+
+    /// Records a content file left out of the build without failing
+    /// it.
+    fn skip(&mut self, source: &Path, reason: String) {
+        tracing::warn!("Skipping {}: {}", source.display(), reason);
+        self.skipped.push(SkippedPage {
+            source: source.to_path_buf(),
+            reason,
+        });
+    }
+
+    /// Records a warning.
+    fn warn(&mut self, warning: String) {
+        tracing::warn!("{}", warning);
+        self.warnings.push(warning);
+    }
+
+    /// Returns a `BuildFailed` error if any page failed, and the pages
+    /// left out and warnings raised otherwise.
+    fn finish(self) -> Result<(Vec<SkippedPage>, Vec<String>)> {
+        if self.failures.is_empty() {
+            Ok((self.skipped, self.warnings))
+        } else {
+            Err(ProcessingError::build_failed(self.failures))
+        }
+    }
+}
+
+/// Returns the type name from a value's `Debug` output, for labelling
+/// pipeline stages.
+fn type_label(value: &dyn std::fmt::Debug) -> String {
+    let debug = format!("{:?}", value);
+    debug
+        .split(|c: char| c == ' ' || c == '{' || c == '(')
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Replaces the wiki links in every page's content with links to their
+/// pages, failing the pages with links that do not resolve.
+fn resolve_wiki_links(
+    pages: Vec<Page>,
+    failures: &mut FailureLog,
+) -> Result<Vec<Page>> {
+    let links = WikiLinks::new(&pages);
+    let mut resolved = Vec::with_capacity(pages.len());
+    for mut page in pages {
+        match links.rewrite(&page.content) {
+            Ok(content) => {
+                page.content = content;
+                resolved.push(page);
+            }
+            Err(errors) => {
+                let error = ProcessingError::validation(
+                    format!(
+                        "Unresolved wiki links: {}",
+                        errors.join("; ")
+                    ),
+                    None::<String>,
+                );
+                failures.record(
+                    &page.source,
+                    BuildStage::Process,
+                    error,
+                )?;
+            }
+        }
+    }
+    Ok(resolved)
+}
+
+/// Returns the output path a page's frontmatter asks for, if any:
+/// `output_path` names the file written, such as
+/// `.well-known/security.txt`, while `url` names the address served,
+/// with directory URLs written as their `index.html`. `output_path`
+/// wins when both are set.
+///
+/// # Arguments
+/// * `source` - The page's content file, for error snippets.
+/// * `frontmatter` - The page's frontmatter.
+///
+/// # Returns
+/// * `Result<Option<PathBuf>>` - The output-relative path, or an error
+///   if the key is not a string or leaves the output directory.
+fn output_override(
+    source: &Path,
+    frontmatter: &serde_json::Map<String, serde_json::Value>,
+) -> Result<Option<PathBuf>> {
+    let (key, value) = match OUTPUT_PATH_KEYS
+        .iter()
+        .find_map(|key| Some((*key, frontmatter.get(*key)?)))
+    {
+        Some(entry) => entry,
+        None => return Ok(None),
+    };
+    let invalid = |reason: &str| {
+        frontmatter_error(
+            source,
+            key,
+            ProcessingError::validation(
+                format!("`{}` {}", key, reason),
+                None::<String>,
+            ),
+        )
+    };
+    let requested =
+        value.as_str().ok_or_else(|| invalid("must be a string"))?;
+    let relative = Path::new(requested.trim_start_matches('/'));
+    let mut components = Vec::new();
+    for component in relative.components() {
+        match component {
+            Component::Normal(name) => components.push(name),
+            Component::CurDir => {}
+            _ => {
+                return Err(invalid(&format!(
+                    "'{}' is outside the output directory",
+                    requested
+                )))
+            }
+        }
+    }
+    let relative: PathBuf = components.into_iter().collect();
+    let is_file = relative.file_name().is_some()
+        && !requested.ends_with('/')
+        && (key == "output_path" || relative.extension().is_some());
+    if is_file {
+        Ok(Some(relative))
+    } else if key == "output_path" {
+        Err(invalid(&format!("'{}' does not name a file", requested)))
+    } else {
+        Ok(Some(relative.join("index.html")))
+    }
+}
+
+/// Adds a numeric suffix to an output path: `about.html` becomes
+/// `about-1.html`, and `go/discord/index.html` becomes
+/// `go/discord-1/index.html`, so directory URLs stay directory URLs.
+fn suffixed_path(path: &Path, n: usize) -> PathBuf {
+    let is_index =
+        path.file_name().map_or(false, |name| name == "index.html");
+    let dir = path.parent().filter(|dir| dir.file_name().is_some());
+    match dir {
+        Some(dir) if is_index => {
+            let mut name =
+                dir.file_name().unwrap_or_default().to_owned();
+            name.push(format!("-{}", n));
+            dir.with_file_name(name).join("index.html")
+        }
+        _ => {
+            let stem = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            path.with_file_name(format!("{}-{}.html", stem, n))
+        }
+    }
+}
+
+/// The sitemap of a build: a single one, or one per language.
+#[derive(Debug)]
+enum SiteSitemap {
+    Single(Sitemap),
+    Languages(LanguageSitemaps, HreflangLinks),
+}
+
+impl SiteSitemap {
+    /// Adds a page, with its translations on multilingual sites.
+    fn add(&mut self, page: &Page) -> Result<()> {
+        match self {
+            Self::Single(sitemap) => {
+                sitemap.add(&page.url, page.lastmod())
+            }
+            Self::Languages(sitemaps, hreflang) => sitemaps.add(
+                hreflang.language(page),
+                &page.url,
+                page.lastmod(),
+                &hreflang.alternates(page),
+            ),
+        }
+    }
+
+    /// Writes the sitemap files to `output_dir`, returning their paths.
+    fn write(&self, output_dir: &Path) -> Result<Vec<PathBuf>> {
+        let (urls, written) = match self {
+            Self::Single(sitemap) => {
+                (sitemap.len(), sitemap.write(output_dir)?)
+            }
+            Self::Languages(sitemaps, _) => {
+                (sitemaps.len(), sitemaps.write(output_dir)?)
+            }
+        };
+        tracing::debug!(
+            "Wrote {} URLs to {} sitemap files",
+            urls,
+            written.len()
+        );
+        Ok(written)
+    }
+}
+
+/// Writes the stubs of a set of redirects, refusing to replace a
+/// generated page or static file.
+///
+/// # Arguments
+/// * `kind` - What the redirects are, such as `Shortlink`, for errors.
+/// * `redirects` - The redirects to write stubs for.
+/// * `sources` - The source of each file written so far, by output path.
+/// * `output_dir` - The directory to write the stubs under.
+fn write_redirect_stubs(
+    kind: &str,
+    redirects: &RedirectMap,
+    sources: &BTreeMap<PathBuf, PathBuf>,
+    output_dir: &Path,
+) -> Result<()> {
+    for (from, stub) in redirects.stub_paths()? {
+        if let Some(source) = sources.get(&stub) {
+            return Err(ProcessingError::validation(
+                format!(
+                    "{} '{}' would replace '{}', generated from '{}'",
+                    kind,
+                    from,
+                    stub.display(),
+                    source.display()
+                ),
+                None::<String>,
+            ));
+        }
+    }
+    let written = redirects.write_stubs(output_dir)?;
+    tracing::debug!(
+        "Wrote {} {} stubs",
+        written.len(),
+        kind.to_lowercase()
+    );
+    Ok(())
+}
+
+/// Returns the title of an error page: the reason phrase of its HTTP
+/// status.
+fn status_title(code: u16) -> &'static str {
+    match code {
+        400 => "Bad request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Page not found",
+        410 => "Page gone",
+        429 => "Too many requests",
+        500 => "Server error",
+        503 => "Service unavailable",
+        _ => "Error",
+    }
+}
+
+/// Points an error about a frontmatter key at the line that sets it in
+/// a page's source, when the source can still be read.
+fn frontmatter_error(
+    source: &Path,
+    key: &str,
+    error: ProcessingError,
+) -> ProcessingError {
+    let snippet = fs::read_to_string(source).ok().and_then(|content| {
+        let line = SourceFile::new(source, content.as_str())
+            .frontmatter_line(key)?;
+        SourceSnippet::line(
+            source.display().to_string(),
+            content,
+            line,
+            format!("invalid `{}`", key),
+        )
+    });
+    match snippet {
+        Some(snippet) => error.with_snippet(snippet),
+        None => error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::check::IssueKind;
+    use crate::core::config::{
+        AuthorConfig, FootnoteConfig, FootnoteNumbering,
+    };
+    use crate::generators::preview::ChangeKind;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_nucleus_flow_config_new() {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path).unwrap();
+        fs::create_dir(&template_path).unwrap();
+
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )
+        .unwrap();
+
+        assert_eq!(config.content_dir, content_path);
+        assert_eq!(config.output_dir, output_path);
+        assert_eq!(config.template_dir, template_path);
+    }
+
+    #[test]
+    fn test_nucleus_flow_process() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+
+        let test_content = "test content";
+        let content_file = content_path.join("test.txt");
+        fs::write(&content_file, test_content)?;
+
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?;
+
+        let nucleus = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(HtmlTemplateRenderer::new(template_path.clone())),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        );
+
+        _ = nucleus.process()?;
+
+        let output_file = output_path.join("test.html");
+        assert!(output_file.exists());
+
+        let output_content = fs::read_to_string(output_file)?;
+        assert_eq!(output_content, "<html>TEST CONTENT</html>");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_static_files() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+        let static_path = temp_dir.path().join("static");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::create_dir_all(static_path.join("css"))?;
+        fs::write(static_path.join("css/site.css"), "body{}")?;
+        fs::write(static_path.join("notes.psd"), "binary")?;
+
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?
+        .with_static_files(StaticConfig {
+            dir: static_path,
+            exclude: vec!["*.psd".to_string()],
+            ..StaticConfig::default()
+        });
+
+        let nucleus = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(HtmlTemplateRenderer::new(template_path.clone())),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        );
+
+        _ = nucleus.process()?;
+
+        assert_eq!(
+            fs::read_to_string(output_path.join("css/site.css"))?,
+            "body{}"
+        );
+        assert!(!output_path.join("notes.psd").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_debug_artifacts() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let template_path = temp_dir.path().join("templates");
+        let static_path = temp_dir.path().join("static");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::create_dir_all(static_path.join("js"))?;
+        fs::write(content_path.join("index.txt"), "Home")?;
+        fs::write(
+            static_path.join("js/app.js"),
+            "run();\n//# sourceMappingURL=app.js.map\n",
+        )?;
+        fs::write(static_path.join("js/app.js.map"), "{}")?;
+        fs::write(static_path.join("js/app.debug.js"), "run();\n")?;
+
+        let build = |name: &str, debug_artifacts: bool| -> Result<_> {
+            let output_path = temp_dir.path().join(name);
+            let config = NucleusFlowConfig::new(
+                &content_path,
+                &output_path,
+                &template_path,
+            )?
+            .with_static_files(StaticConfig {
+                dir: static_path.clone(),
+                ..StaticConfig::default()
+            })
+            .with_debug_artifacts(debug_artifacts);
+            _ = NucleusFlow::new(
+                config,
+                Box::new(FileContentProcessor::new(
+                    content_path.clone(),
+                )),
+                Box::new(HtmlTemplateRenderer::new(
+                    template_path.clone(),
+                )),
+                Box::new(HtmlOutputGenerator::new(output_path.clone())),
+            )
+            .process()?;
+            let mut files = BTreeMap::new();
+            for entry in walkdir::WalkDir::new(&output_path) {
+                let entry = entry.unwrap();
+                if entry.file_type().is_file() {
+                    let relative = entry
+                        .path()
+                        .strip_prefix(&output_path)
+                        .unwrap();
+                    _ = files.insert(
+                        relative.to_path_buf(),
+                        fs::read_to_string(entry.path())?,
+                    );
+                }
+            }
+            Ok(files)
+        };
+
+        let development = build("development", true)?;
+        let mut production = build("production", false)?;
+        assert_eq!(
+            development[Path::new("js/app.js")],
+            "run();\n//# sourceMappingURL=app.js.map\n"
+        );
+        assert!(development.contains_key(Path::new("js/app.js.map")));
+        assert!(development.contains_key(Path::new("js/app.debug.js")));
+        assert_eq!(production[Path::new("js/app.js")], "run();\n");
+
+        // Apart from the debug artifacts, the two trees are identical
+        let mut stripped = development.clone();
+        _ = stripped.remove(Path::new("js/app.js.map"));
+        _ = stripped.remove(Path::new("js/app.debug.js"));
+        _ = stripped.remove(Path::new("js/app.js"));
+        _ = production.remove(Path::new("js/app.js"));
+        assert_eq!(stripped, production);
+
+        Ok(())
+    }
+
+    /// A renderer that fails for every page.
+    #[derive(Debug)]
+    struct FailingRenderer;
+
+    impl TemplateRenderer for FailingRenderer {
+        fn render(
+            &self,
+            template: &str,
+            _context: &serde_json::Value,
+        ) -> Result<String> {
+            Err(ProcessingError::TemplateProcessing {
+                details: "unclosed block <each>".to_string(),
+                template_name: template.to_string(),
+                source: None,
+            })
+        }
+
+        fn validate(
+            &self,
+            _template: &str,
+            _context: &serde_json::Value,
+        ) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_nucleus_flow_error_recovery() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(content_path.join("a.txt"), "a")?;
+        fs::write(content_path.join("b.txt"), "b")?;
+
+        let build = |recover: bool| -> Result<BuildResult> {
+            let config = NucleusFlowConfig::new(
+                &content_path,
+                &output_path,
+                &template_path,
+            )?
+            .with_error_recovery(recover);
+            NucleusFlow::new(
+                config,
+                Box::new(FileContentProcessor::new(
+                    content_path.clone(),
+                )),
+                Box::new(FailingRenderer),
+                Box::new(HtmlOutputGenerator::new(output_path.clone())),
+            )
+            .process()
+        };
+
+        assert!(build(false).is_err());
+        _ = build(true)?;
+
+        for page in ["a.html", "b.html"] {
+            let output = fs::read_to_string(output_path.join(page))?;
+            assert!(output.contains("Template error"));
+            assert!(output.contains("unclosed block &lt;each&gt;"));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_image_placeholders() -> Result<()> {
+        use crate::template::HandlebarsRenderer;
+
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+        let static_path = temp_dir.path().join("static");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::create_dir_all(static_path.join("img"))?;
+        fs::write(content_path.join("index.md"), "# Home")?;
+        fs::write(
+            template_path.join("default.hbs"),
+            "<img data-src=\"/img/hero.png\" \
+             src=\"{{placeholder \"img/hero.png\"}}\">",
+        )?;
+        image::RgbImage::from_pixel(16, 8, image::Rgb([200, 100, 50]))
+            .save(static_path.join("img/hero.png"))
+            .unwrap();
+
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?
+        .with_static_files(StaticConfig {
+            dir: static_path,
+            ..StaticConfig::default()
+        })
+        .with_image_placeholders(PlaceholderConfig::default());
+        _ = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(HandlebarsRenderer::new(&template_path)?),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        )
+        .process()?;
+
+        let html = fs::read_to_string(output_path.join("index.html"))?;
+        assert!(
+            html.contains("src=\"data:image/png;base64,"),
+            "{}",
+            html
+        );
+        assert!(output_path.join("img/hero.png").is_file());
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_ignore_rules() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+        let static_path = temp_dir.path().join("static");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::create_dir(&static_path)?;
+        fs::write(content_path.join("post.txt"), "post")?;
+        fs::write(content_path.join("post.txt~"), "backup")?;
+        fs::write(static_path.join("site.css"), "body{}")?;
+        fs::write(static_path.join("site.css~"), "backup")?;
+
+        let rules =
+            IgnoreRules::from_patterns(temp_dir.path(), &["*~"])?;
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?
+        .with_static_files(StaticConfig {
+            dir: static_path,
+            ..StaticConfig::default()
+        })
+        .with_ignore_rules(rules);
+
+        _ = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(HtmlTemplateRenderer::new(template_path.clone())),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        )
+        .process()?;
+
+        let mut written: Vec<_> = fs::read_dir(&output_path)?
+            .map(|entry| entry.map(|e| e.file_name()))
+            .collect::<std::io::Result<_>>()?;
+        written.sort();
+        assert_eq!(written, ["post.html", "site.css"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_atomic_swap() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(content_path.join("a.txt"), "a")?;
+
+        let build =
+            |renderer: Box<dyn TemplateRenderer>| -> Result<BuildResult> {
+                let config = NucleusFlowConfig::new(
+                    &content_path,
+                    &output_path,
+                    &template_path,
+                )?
+                .with_atomic_swap(SwapStrategy::Rename);
+                NucleusFlow::new(
+                    config,
+                    Box::new(FileContentProcessor::new(
+                        content_path.clone(),
+                    )),
+                    renderer,
+                    Box::new(HtmlOutputGenerator::new(
+                        output_path.clone(),
+                    )),
+                )
+                .process()
+            };
+
+        _ = build(Box::new(HtmlTemplateRenderer::new(
+            template_path.clone(),
+        )))?;
+        fs::write(content_path.join("b.txt"), "b")?;
+        assert!(build(Box::new(FailingRenderer)).is_err());
+
+        // The failed build left the previous output untouched
+        assert_eq!(
+            fs::read_to_string(output_path.join("a.html"))?,
+            "<html>A</html>"
+        );
+        assert!(!output_path.join("b.html").exists());
+        assert_eq!(fs::read_dir(temp_dir.path())?.count(), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_citations() -> Result<()> {
+        use crate::core::config::CitationConfig;
+        use crate::processors::citations::parse_bibtex;
+
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(
+            content_path.join("paper.md"),
+            "As shown [@knuth84].",
+        )?;
+        fs::write(content_path.join("draft.md"), "See [@nobody].")?;
+
+        let entries = parse_bibtex(
+            "@book{KNUTH84, author = {Donald Knuth}, year = 1984}",
+        )?;
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?
+        .with_citations(Citations::new(
+            CitationConfig::default(),
+            entries,
+        ))
+        .with_continue_on_error(true);
+        let flow = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(HtmlTemplateRenderer::new(template_path.clone())),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        );
+        let error = flow.process().unwrap_err().to_string();
+        assert!(error.contains("1 page"), "{}", error);
+        let html = fs::read_to_string(output_path.join("paper.html"))?;
+        assert!(
+            html.contains("<a href=\"#ref-KNUTH84\">Knuth, 1984</a>")
+        );
+        assert!(html.contains("<li id=\"ref-KNUTH84\">"));
+        assert!(!output_path.join("draft.html").exists());
+        Ok(())
+    }
+
+    #[cfg(feature = "scripting")]
+    #[test]
+    fn test_nucleus_flow_scripts() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(
+            content_path.join("post.txt"),
+            "---\ntitle: Post\n---\nbody",
+        )?;
+
+        let mut scripts = Scripts::new();
+        scripts.add(
+            "draft.rhai",
+            r#"
+            fn on_page(page) {
+                page.frontmatter.title += " (draft)";
+                page
+            }
+            fn on_output(page, html) { html + "\n<!-- script -->" }
+            "#,
+        )?;
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?
+        .with_scripts(scripts);
+        let flow = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(TemplateNameRenderer),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        );
+        _ = flow.process()?;
+
+        assert_eq!(
+            fs::read_to_string(output_path.join("post.html"))?,
+            "default: Post (draft)\n<!-- script -->"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_cms() -> Result<()> {
+        use crate::cms::CMS_KEY;
+        use crate::core::config::{CmsFormat, CmsSource};
+        use crate::import::Fetch;
+
+        #[derive(Debug)]
+        struct Api;
+
+        impl Fetch for Api {
+            fn fetch(&self, _url: &str) -> Result<Vec<u8>> {
+                Ok(br#"[{"id": "hello", "text": "Hi"}]"#.to_vec())
+            }
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(content_path.join("about.txt"), "about")?;
+
+        let source = CmsSource {
+            name: "posts".to_string(),
+            url: "https://cms.example.com/posts".to_string(),
+            query: None,
+            headers: Default::default(),
+            items: String::new(),
+            slug: "/id".to_string(),
+            body: "/text".to_string(),
+            format: CmsFormat::Markdown,
+            fields: Default::default(),
+        };
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?
+        .with_cms(HeadlessCms::new(vec![source], Arc::new(Api)));
+        let flow = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(TemplateNameRenderer),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        );
+        _ = flow.process()?;
+
+        assert!(fs::read_to_string(content_path.join("hello.md"))?
+            .contains(&format!("{}: posts", CMS_KEY)));
+        assert!(output_path.join("hello.html").exists());
+        assert!(output_path.join("about.html").exists());
+        Ok(())
+    }
+
+    #[cfg(feature = "git")]
+    #[test]
+    fn test_nucleus_flow_git_history() -> Result<()> {
+        use crate::core::config::GitConfig;
+        use crate::processors::git::FileHistory;
+
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(content_path.join("post.txt"), "post")?;
+
+        let date = |date: &str| {
+            chrono::DateTime::parse_from_rfc3339(date).unwrap()
+        };
+        let history = FileHistory {
+            created: date("2024-01-02T09:00:00+00:00"),
+            lastmod: date("2024-05-01T10:00:00+00:00"),
+            contributors: Vec::new(),
+        };
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?
+        .with_sitemap("https://example.com")
+        .with_git_history(GitHistory::new(
+            GitConfig::default(),
+            [(PathBuf::from("post.txt"), history)]
+                .into_iter()
+                .collect(),
+        ));
+        _ = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(HtmlTemplateRenderer::new(template_path.clone())),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        )
+        .process()?;
+
+        assert!(fs::read_to_string(output_path.join("sitemap.xml"))?
+            .contains("<lastmod>2024-05-01T10:00:00+00:00</lastmod>"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_sitemap() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(content_path.join("index.txt"), "home")?;
+        fs::write(
+            content_path.join("post.txt"),
+            "---\ndate: 2024-03-01\n---\npost",
+        )?;
+        fs::write(content_path.join("404.txt"), "not found")?;
+
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?
+        .with_sitemap("https://example.com");
+        _ = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(HtmlTemplateRenderer::new(template_path.clone())),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        )
+        .process()?;
+
+        let sitemap =
+            fs::read_to_string(output_path.join("sitemap.xml"))?;
+        assert!(sitemap.contains("<loc>https://example.com/</loc>"));
+        assert!(sitemap
+            .contains("<loc>https://example.com/post.html</loc>"));
+        assert!(sitemap
+            .contains("<lastmod>2024-03-01T00:00:00+00:00</lastmod>"));
+        assert!(output_path.join("404.html").exists());
+        assert!(!sitemap.contains("404.html"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_canonical_links() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(content_path.join("post.txt"), "<head></head>")?;
+        fs::write(
+            content_path.join("copy.txt"),
+            "---\ncanonical: /post.html\n---\n<head></head>",
+        )?;
+
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?
+        .with_sitemap("https://example.com")
+        .with_base_url("https://example.com");
+        _ = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(HtmlTemplateRenderer::new(template_path.clone())),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        )
+        .process()?;
+
+        let canonical = r#"<link rel="canonical" href="https://example.com/post.html">"#;
+        for page in ["post.html", "copy.html"] {
+            let html = fs::read_to_string(output_path.join(page))?;
+            assert!(html.contains(canonical), "{}", html);
+        }
+        let sitemap =
+            fs::read_to_string(output_path.join("sitemap.xml"))?;
+        assert!(sitemap.contains("/post.html"));
+        assert!(!sitemap.contains("/copy.html"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_language_sitemaps() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(
+            content_path.join("about.txt"),
+            "---\ntranslation_key: about\n---\nAbout",
+        )?;
+        fs::write(
+            content_path.join("a-propos.txt"),
+            "---\nlang: fr\ntranslation_key: about\n---\nA propos",
+        )?;
+
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?
+        .with_sitemap("https://example.com")
+        .with_i18n(I18nConfig {
+            default_language: "en".to_string(),
+            languages: vec!["en".to_string(), "fr".to_string()],
+        });
+        _ = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(HtmlTemplateRenderer::new(template_path.clone())),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        )
+        .process()?;
+
+        let index =
+            fs::read_to_string(output_path.join("sitemap.xml"))?;
+        assert!(index.contains("<sitemapindex"));
+        assert!(index
+            .contains("<loc>https://example.com/sitemap-fr.xml</loc>"));
+        let french =
+            fs::read_to_string(output_path.join("sitemap-fr.xml"))?;
+        assert!(french
+            .contains("<loc>https://example.com/a-propos.html</loc>"));
+        assert!(!french.contains("<loc>https://example.com/about.html"));
+        assert!(french.contains(
+            r#"<xhtml:link rel="alternate" hreflang="en" href="https://example.com/about.html"/>"#
+        ));
+        assert!(output_path.join("sitemap-en.xml").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_hreflang() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(
+            content_path.join("about.txt"),
+            "---\ntranslation_key: about\n---\n<head></head>",
+        )?;
+        fs::write(
+            content_path.join("a-propos.txt"),
+            "---\nlang: fr\ntranslation_key: about\n---\n<head></head>",
+        )?;
+        fs::write(content_path.join("solo.txt"), "<head></head>")?;
+
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?
+        .with_base_url("https://example.com")
+        .with_i18n(I18nConfig {
+            default_language: "en".to_string(),
+            languages: vec!["en".to_string(), "fr".to_string()],
+        });
+        _ = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(HtmlTemplateRenderer::new(template_path.clone())),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        )
+        .process()?;
+
+        let about = fs::read_to_string(output_path.join("about.html"))?;
+        assert!(about.contains(
+            r#"<link rel="alternate" hreflang="fr" href="https://example.com/a-propos.html">"#
+        ));
+        assert!(about.contains(
+            r#"<link rel="alternate" hreflang="x-default" href="https://example.com/about.html">"#
+        ));
+        let solo = fs::read_to_string(output_path.join("solo.html"))?;
+        assert!(!solo.contains("hreflang"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_preview_deploy() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+        let preview_path = temp_dir.path().join("preview");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(content_path.join("a.txt"), "a")?;
+        fs::write(content_path.join("b.txt"), "b")?;
+
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?
+        .with_preview_deploy(&preview_path);
+        let nucleus = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(HtmlTemplateRenderer::new(template_path.clone())),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        );
+        _ = nucleus.process()?;
+        assert!(!preview_path.exists());
+
+        let (_, pushed) = nucleus.rebuild()?;
+        assert_eq!(pushed.len(), 2);
+        assert_eq!(
+            fs::read_to_string(preview_path.join("a.html"))?,
+            "<html>A</html>"
+        );
+
+        fs::write(content_path.join("a.txt"), "changed")?;
+        fs::write(preview_path.join("b.html"), "untouched")?;
+        let (_, pushed) = nucleus.rebuild()?;
+        assert_eq!(
+            pushed,
+            vec![DeployedFile {
+                path: PathBuf::from("a.html"),
+                kind: ChangeKind::Updated,
+            }]
+        );
+        assert_eq!(
+            fs::read_to_string(preview_path.join("a.html"))?,
+            "<html>CHANGED</html>"
+        );
+        assert_eq!(
+            fs::read_to_string(preview_path.join("b.html"))?,
+            "untouched"
+        );
+
+        Ok(())
+    }
+
+    /// A content processor numbering footnotes on across pages from
+    /// the start the build passes.
+    #[derive(Debug)]
+    struct FootnoteProcessor(MarkdownProcessor);
+
+    impl ContentProcessor for FootnoteProcessor {
+        fn process(
+            &self,
+            content: &str,
+            context: Option<&serde_json::Value>,
+        ) -> Result<String> {
+            self.0.process_page(content.to_string(), context, 0)
+        }
+
+        fn validate(&self, _content: &str) -> Result<()> {
+            Ok(())
+        }
+
+        fn process_with_footnotes(
+            &self,
+            content: &str,
+            footnote_start: usize,
+        ) -> Result<String> {
+            self.0.process_page(
+                content.to_string(),
+                None,
+                footnote_start,
+            )
+        }
+    }
+
+    #[test]
+    fn test_nucleus_flow_continuous_footnotes() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(content_path.join("a.md"), "A[^x].\n\n[^x]: X.\n")?;
+        fs::write(
+            content_path.join("b.md"),
+            "B[^y] and [^z].\n\n[^y]: Y.\n\n[^z]: Z.\n",
+        )?;
+        fs::write(content_path.join("c.md"), "C[^w].\n\n[^w]: W.\n")?;
+
+        let markdown = MarkdownProcessor::new()
+            .with_footnotes(true)
+            .with_footnote_config(FootnoteConfig {
+                numbering: FootnoteNumbering::Continuous,
+                ..FootnoteConfig::default()
+            });
+        let nucleus = |numbering| -> Result<NucleusFlow> {
+            let config = NucleusFlowConfig::new(
+                &content_path,
+                &output_path,
+                &template_path,
+            )?
+            .with_footnote_numbering(numbering);
+            Ok(NucleusFlow::new(
+                config,
+                Box::new(FootnoteProcessor(markdown.clone())),
+                Box::new(HtmlTemplateRenderer::new(
+                    template_path.clone(),
+                )),
+                Box::new(HtmlOutputGenerator::new(output_path.clone())),
+            ))
+        };
+        let read =
+            |name: &str| fs::read_to_string(output_path.join(name));
+
+        _ = nucleus(FootnoteNumbering::Page)?.process()?;
+        assert!(read("c.html")?.contains("<li id=\"fn-1\">"));
+
+        let nucleus = nucleus(FootnoteNumbering::Continuous)?;
+        _ = nucleus.process()?;
+        let built = read("c.html")?;
+        assert!(built.contains("<li id=\"fn-4\">"));
+        assert!(read("b.html")?.contains("<ol start=\"2\">"));
+
+        _ = nucleus.process()?;
+        assert_eq!(nucleus.cache_metrics().content.hits, 3);
+        assert_eq!(read("c.html")?, built);
+
+        fs::write(
+            content_path.join("a.md"),
+            "A[^x][^v].\n\n[^x]: X.\n\n[^v]: V.\n",
+        )?;
+        _ = nucleus.process()?;
+        assert!(read("c.html")?.contains("<li id=\"fn-5\">"));
+        assert!(read("b.html")?.contains("<ol start=\"3\">"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_build_report() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(content_path.join("index.txt"), "home")?;
+
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?
+        .with_build_report(true);
+        let nucleus = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(HtmlTemplateRenderer::new(template_path.clone())),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        );
+        let read_report = || -> Result<serde_json::Value> {
+            Ok(serde_json::from_str(&fs::read_to_string(
+                output_path.join("build-report.json"),
+            )?)
+            .unwrap())
+        };
+
+        _ = nucleus.process()?;
+        let report = read_report()?;
+        assert_eq!(
+            report["cache"],
+            serde_json::json!({
+                "content": { "hits": 0, "misses": 1 },
+                "template": null,
+                "asset": null,
+            })
+        );
+
+        _ = nucleus.process()?;
+        let report = read_report()?;
+        assert_eq!(
+            report["cache"]["content"],
+            serde_json::json!({ "hits": 1, "misses": 0 })
+        );
+        assert_eq!(nucleus.cache_metrics().content.lookups(), 2);
+        let files = report["files"].as_array().unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0]["path"], "index.html");
+        assert_eq!(
+            files[0]["source"],
+            serde_json::json!(content_path.join("index.txt"))
+        );
+        assert_eq!(
+            files[0]["size"],
+            fs::metadata(output_path.join("index.html"))?.len()
+        );
+        assert!(report["duration_ms"].is_u64());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_graph() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let template_path = temp_dir.path().join("templates");
+        fs::create_dir_all(content_path.join("blog"))?;
+        fs::create_dir(&template_path)?;
+        fs::write(content_path.join("index.md"), "home")?;
+        fs::write(content_path.join("blog/post.md"), "post")?;
+
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &temp_dir.path().join("output"),
+            &template_path,
+        )?
+        .with_sitemap("https://example.com")
+        .with_atomic_swap(SwapStrategy::Symlink);
+        let nucleus = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(HtmlTemplateRenderer::new(template_path.clone())),
+            Box::new(HtmlOutputGenerator::new(temp_dir.path().into())),
+        );
+
+        let graph = nucleus.graph();
+        assert_eq!(
+            graph.nodes(StageKind::Processors)[0].detail.as_deref(),
+            Some("FileContentProcessor")
+        );
+        let generators: Vec<_> = graph
+            .nodes(StageKind::Generators)
+            .iter()
+            .map(|node| node.label.as_str())
+            .collect();
+        assert_eq!(generators, ["output generator", "sitemap"]);
+        assert!(graph.to_string().contains("swapped by symlink"));
+
+        assert_eq!(
+            nucleus.source_status(&content_path.join("index.md")),
+            SourceStatus::Processed(PathBuf::from("index.html"))
+        );
+        assert_eq!(
+            nucleus.source_status(&content_path.join("blog/post.md")),
+            SourceStatus::Nested
+        );
+        assert_eq!(
+            nucleus.source_status(&content_path.join("blog")),
+            SourceStatus::NotAFile
+        );
+        assert_eq!(
+            nucleus.source_status(&template_path.join("missing.hbs")),
+            SourceStatus::Missing
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_collect_and_process_site() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(
+            content_path.join("draft.txt"),
+            "---\ndraft: true\n---\ndraft",
+        )?;
+        fs::write(
+            content_path.join("post.txt"),
+            "---\ntitle: Post\n---\npost",
+        )?;
+
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?;
+        let nucleus = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(HtmlTemplateRenderer::new(template_path.clone())),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        );
+
+        let mut site = nucleus.collect()?;
+        let urls: Vec<_> =
+            site.pages.iter().map(|page| page.url.as_str()).collect();
+        assert_eq!(urls, ["/draft.html", "/post.html"]);
+        assert_eq!(site.pages[1].title(), Some("Post"));
+
+        site.pages.retain(|page| !page.is_draft());
+        _ = nucleus.process_site(&site)?;
+        assert!(output_path.join("post.html").exists());
+        assert!(!output_path.join("draft.html").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_validators() -> Result<()> {
+        use crate::core::validation::{MaxSize, RequiredFields};
+
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(
+            content_path.join("good.txt"),
+            "---\ntitle: Good\n---\ngood",
+        )?;
+        fs::write(content_path.join("untitled.txt"), "untitled")?;
+
+        let nucleus = |severity| -> Result<NucleusFlow> {
+            let config = NucleusFlowConfig::new(
+                &content_path,
+                &output_path,
+                &template_path,
+            )?;
+            Ok(NucleusFlow::new(
+                config,
+                Box::new(FileContentProcessor::new(
+                    content_path.clone(),
+                )),
+                Box::new(HtmlTemplateRenderer::new(
+                    template_path.clone(),
+                )),
+                Box::new(HtmlOutputGenerator::new(output_path.clone())),
+            )
+            .with_validator(Severity::Error, MaxSize(1024))
+            .with_validator(severity, RequiredFields::new(["title"])))
+        };
+
+        let strict = nucleus(Severity::Error)?;
+        let diagnostics = strict.validate()?;
+        assert_eq!(diagnostics.entries.len(), 1);
+        assert_eq!(
+            diagnostics.entries[0].message,
+            "Missing frontmatter fields: title"
+        );
+        let error = strict.process().unwrap_err();
+        assert!(matches!(error, ProcessingError::Validation { .. }));
+        assert!(!output_path.join("good.html").exists());
+
+        _ = nucleus(Severity::Warning)?.process()?;
+        assert!(output_path.join("good.html").exists());
+        assert!(output_path.join("untitled.html").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_shortlinks() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(content_path.join("index.txt"), "home")?;
+
+        let build = |from: &str| -> Result<BuildResult> {
+            let mut links = BTreeMap::new();
+            _ = links.insert(
+                from.to_string(),
+                "https://discord.gg/example".to_string(),
+            );
+            let config = NucleusFlowConfig::new(
+                &content_path,
+                &output_path,
+                &template_path,
+            )?
+            .with_shortlinks(RedirectMap::from_shortlinks(&links)?)
+            .with_build_report(true);
+            NucleusFlow::new(
+                config,
+                Box::new(FileContentProcessor::new(
+                    content_path.clone(),
+                )),
+                Box::new(HtmlTemplateRenderer::new(
+                    template_path.clone(),
+                )),
+                Box::new(HtmlOutputGenerator::new(output_path.clone())),
+            )
+            .process()
+        };
+
+        _ = build("/go/discord")?;
+        let stub = fs::read_to_string(
+            output_path.join("go/discord/index.html"),
+        )?;
+        assert!(stub.contains("url=https://discord.gg/example"));
+        let report: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(output_path.join(REPORT_FILE_NAME))?,
+        )
+        .unwrap();
+        assert_eq!(report["shortlinks"][0]["from"], "/go/discord");
+        assert_eq!(report["shortlinks"][0]["status"], 302);
+
+        assert!(build("/index.html").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_aliases() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(
+            content_path.join("post.txt"),
+            "---\naliases: [/old-url/, /2019/post.html]\n---\npost",
+        )?;
+
+        let build = || {
+            let mut links = BTreeMap::new();
+            _ = links.insert(
+                "/go/post".to_string(),
+                "/post.html".to_string(),
+            );
+            let config = NucleusFlowConfig::new(
+                &content_path,
+                &output_path,
+                &template_path,
+            )?
+            .with_shortlinks(RedirectMap::from_shortlinks(&links)?)
+            .with_redirect_exports([RedirectFormat::Netlify]);
+            NucleusFlow::new(
+                config,
+                Box::new(FileContentProcessor::new(
+                    content_path.clone(),
+                )),
+                Box::new(HtmlTemplateRenderer::new(
+                    template_path.clone(),
+                )),
+                Box::new(HtmlOutputGenerator::new(output_path.clone())),
+            )
+            .process()
+        };
+
+        _ = build()?;
+        let stub =
+            fs::read_to_string(output_path.join("old-url/index.html"))?;
+        assert!(stub.contains("url=/post.html"));
+        assert!(output_path.join("2019/post.html").exists());
+        assert_eq!(
+            fs::read_to_string(output_path.join("_redirects"))?,
+            "/2019/post.html /post.html 301\n\
+             /go/post /post.html 302\n\
+             /old-url/ /post.html 301\n"
+        );
+
+        fs::write(
+            content_path.join("other.txt"),
+            "---\naliases: /post.html\n---\nother",
+        )?;
+        assert!(build().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_moved_sections() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(content_path.join("a.txt"), "a")?;
+        fs::write(
+            content_path.join("b.txt"),
+            "---\naliases: [/blog/b.html]\n---\nb",
+        )?;
+
+        let mut moves = BTreeMap::new();
+        _ = moves.insert("/blog/".to_string(), "/posts/".to_string());
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?
+        .with_moved_sections(moves)
+        .with_redirect_exports([RedirectFormat::Netlify]);
+        let nucleus = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(HtmlTemplateRenderer::new(template_path.clone())),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        );
+
+        // Move the collected pages into the new section
+        let mut site = nucleus.collect()?;
+        for page in &mut site.pages {
+            page.path = Path::new("posts").join(&page.path);
+            page.url = page_url(&page.path);
+        }
+        _ = nucleus.process_site(&site)?;
+
+        let stub = fs::read_to_string(output_path.join("blog/a.html"))?;
+        assert!(stub.contains("url=/posts/a.html"));
+        assert_eq!(
+            fs::read_to_string(output_path.join("_redirects"))?,
+            "/blog/a.html /posts/a.html 301\n\
+             /blog/b.html /posts/b.html 301\n"
+        );
+        Ok(())
+    }
+
+    /// A renderer that echoes the template name and page title.
+    #[derive(Debug)]
+    struct TemplateNameRenderer;
+
+    impl TemplateRenderer for TemplateNameRenderer {
+        fn render(
+            &self,
+            template: &str,
+            context: &serde_json::Value,
+        ) -> Result<String> {
+            Ok(format!(
+                "{}: {}",
+                template,
+                context["page"]["frontmatter"]["title"]
+                    .as_str()
+                    .unwrap_or_default()
+            ))
+        }
+
+        fn validate(
+            &self,
+            _template: &str,
+            _context: &serde_json::Value,
+        ) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    /// A renderer that echoes the URLs of the previous and next pages.
+    #[derive(Debug)]
+    struct NavigationRenderer;
+
+    impl TemplateRenderer for NavigationRenderer {
+        fn render(
+            &self,
+            _template: &str,
+            context: &serde_json::Value,
+        ) -> Result<String> {
+            let url = |link: &str| {
+                context["page"][link]["url"]
+                    .as_str()
+                    .unwrap_or("-")
+                    .to_string()
+            };
+            Ok(format!("{} {}", url("prev"), url("next")))
+        }
+
+        fn validate(
+            &self,
+            _template: &str,
+            _context: &serde_json::Value,
+        ) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Renders the titles of a page's breadcrumb trail.
+    #[derive(Debug)]
+    struct BreadcrumbRenderer;
+
+    impl TemplateRenderer for BreadcrumbRenderer {
+        fn render(
+            &self,
+            _template: &str,
+            context: &serde_json::Value,
+        ) -> Result<String> {
+            let titles: Vec<&str> = context["page"]["breadcrumbs"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|crumb| crumb["title"].as_str())
+                .collect();
+            Ok(titles.join(" > "))
+        }
+
+        fn validate(
+            &self,
+            _template: &str,
+            _context: &serde_json::Value,
+        ) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Renders the main menu, bracketing the active entry.
+    #[derive(Debug)]
+    struct MenuRenderer;
+
+    impl TemplateRenderer for MenuRenderer {
+        fn render(
+            &self,
+            _template: &str,
+            context: &serde_json::Value,
+        ) -> Result<String> {
+            let entries: Vec<String> = context["site"]["menus"]["main"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|entry| {
+                    let title = entry["title"].as_str().unwrap_or("-");
+                    if entry["is_active"] == true {
+                        format!("[{}]", title)
+                    } else {
+                        title.to_string()
+                    }
+                })
+                .collect();
+            Ok(entries.join(" "))
+        }
+
+        fn validate(
+            &self,
+            _template: &str,
+            _context: &serde_json::Value,
+        ) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Renders a page's place in its series, or a series index page's
+    /// first page.
+    #[derive(Debug)]
+    struct SeriesRenderer;
+
+    impl TemplateRenderer for SeriesRenderer {
+        fn render(
+            &self,
+            template: &str,
+            context: &serde_json::Value,
+        ) -> Result<String> {
+            let page = &context["page"];
+            Ok(if template == "series" {
+                format!(
+                    "{} {}",
+                    page["frontmatter"]["title"]
+                        .as_str()
+                        .unwrap_or("-"),
+                    page["frontmatter"]["pages"][0]["url"]
+                        .as_str()
+                        .unwrap_or("-")
+                )
+            } else {
+                let series = &page["series"];
+                format!(
+                    "{} {}/{} {}",
+                    series["name"].as_str().unwrap_or("-"),
+                    series["position"],
+                    series["total"],
+                    series["url"].as_str().unwrap_or("-")
+                )
+            })
+        }
+
+        fn validate(
+            &self,
+            _template: &str,
+            _context: &serde_json::Value,
+        ) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Renders a page's first author, or an author page's first page.
+    #[derive(Debug)]
+    struct AuthorRenderer;
+
+    impl TemplateRenderer for AuthorRenderer {
+        fn render(
+            &self,
+            template: &str,
+            context: &serde_json::Value,
+        ) -> Result<String> {
+            let page = &context["page"];
+            Ok(if template == "author" {
+                format!(
+                    "author: {} {}",
+                    page["frontmatter"]["title"]
+                        .as_str()
+                        .unwrap_or("-"),
+                    page["frontmatter"]["pages"][0]["url"]
+                        .as_str()
+                        .unwrap_or("-")
+                )
+            } else {
+                format!(
+                    "page: {} {}",
+                    page["authors"][0]["name"].as_str().unwrap_or("-"),
+                    page["authors"][0]["url"].as_str().unwrap_or("-")
+                )
+            })
+        }
+
+        fn validate(
+            &self,
+            _template: &str,
+            _context: &serde_json::Value,
+        ) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_nucleus_flow_navigation() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        for (name, date) in [
+            ("new", "2024-03-01"),
+            ("old", "2024-01-01"),
+            ("mid", "2024-02-01"),
+        ] {
+            fs::write(
+                content_path.join(format!("{}.txt", name)),
+                format!("---\ndate: {}\n---\n", date),
+            )?;
+        }
+        fs::write(content_path.join("index.txt"), "home")?;
+
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?;
+        let flow = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(NavigationRenderer),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        );
+        _ = flow.process()?;
+
+        let read = |name: &str| {
+            fs::read_to_string(output_path.join(name)).unwrap()
+        };
+        assert_eq!(read("old.html"), "- /mid.html");
+        assert_eq!(read("mid.html"), "/old.html /new.html");
+        assert_eq!(read("new.html"), "/mid.html -");
+        assert_eq!(read("index.html"), "- -");
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_breadcrumbs() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(
+            content_path.join("docs.txt"),
+            "---\ntitle: Docs\nurl: /docs/\n---\n",
+        )?;
+        fs::write(
+            content_path.join("install.txt"),
+            "---\ntitle: Install\nurl: /docs/install/\n---\n",
+        )?;
+
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?;
+        let flow = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(BreadcrumbRenderer),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        );
+        _ = flow.process()?;
+        assert_eq!(
+            fs::read_to_string(
+                output_path.join("docs/install/index.html")
+            )?,
+            "Home > Docs > Install"
+        );
+        assert_eq!(
+            fs::read_to_string(output_path.join("docs/index.html"))?,
+            "Home > Docs"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_directory_defaults() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(
+            content_path.join("_defaults.toml"),
+            "template = \"post\"\ntitle = \"Untitled\"\n",
+        )?;
+        fs::write(content_path.join("a.txt"), "a")?;
+        fs::write(content_path.join("b.txt"), "---\ntitle: B\n---\n")?;
+        fs::write(
+            content_path.join("_index.md"),
+            "---\ncascade:\n  author: Ada\n---\n",
+        )?;
+        for dir in ["docs/api", "blog", "snippets"] {
+            fs::create_dir_all(content_path.join(dir))?;
+        }
+        fs::write(
+            content_path.join("docs/_defaults.toml"),
+            "template = \"doc\"\n",
+        )?;
+        fs::write(content_path.join("docs/guide.txt"), "guide")?;
+        fs::write(content_path.join("docs/api/_defaults.toml"), "")?;
+        fs::write(content_path.join("docs/api/ref.txt"), "ref")?;
+        fs::write(
+            content_path.join("blog/_index.md"),
+            "---\ncascade:\n  title: Blog post\n---\n",
+        )?;
+        fs::write(content_path.join("blog/post.txt"), "post")?;
+        fs::write(content_path.join("snippets/note.txt"), "note")?;
+
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?;
+        let flow = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(TemplateNameRenderer),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        );
+        _ = flow.process()?;
+        assert_eq!(
+            fs::read_to_string(output_path.join("a.html"))?,
+            "post: Untitled"
+        );
+        assert_eq!(
+            fs::read_to_string(output_path.join("b.html"))?,
+            "post: B"
+        );
+        assert!(!output_path.join("_defaults.html").exists());
+        assert!(!output_path.join("_index.html").exists());
+        assert_eq!(
+            fs::read_to_string(output_path.join("docs/guide.html"))?,
+            "doc: Untitled"
+        );
+        assert_eq!(
+            fs::read_to_string(output_path.join("docs/api/ref.html"))?,
+            "doc: Untitled"
+        );
+        assert_eq!(
+            fs::read_to_string(output_path.join("blog/post.html"))?,
+            "post: Blog post"
+        );
+        assert!(!output_path.join("blog/_index.html").exists());
+        assert!(!output_path.join("snippets").exists());
+
+        let site = flow.collect()?;
+        let post = site
+            .pages
+            .iter()
+            .find(|page| page.url == "/blog/post.html")
+            .unwrap();
+        assert_eq!(post.frontmatter["author"], "Ada");
+        assert_eq!(
+            flow.source_status(&content_path.join("blog/_index.md")),
+            SourceStatus::Defaults
+        );
+        assert_eq!(
+            flow.source_status(&content_path.join("snippets/note.txt")),
+            SourceStatus::Nested
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_archives() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(
+            content_path.join("post.txt"),
+            "---\ndate: 2024-05-03\n---\n",
+        )?;
+        fs::write(content_path.join("about.txt"), "about")?;
+
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?
+        .with_archives(ArchiveConfig {
+            month_template: "month".to_string(),
+            ..ArchiveConfig::default()
+        });
+        let flow = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(TemplateNameRenderer),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        );
+        let site = flow.collect()?;
+        let archive = site.page("/archive/2024/").unwrap();
+        assert_eq!(
+            archive.frontmatter["pages"][0]["url"],
+            "/post.html"
+        );
+        assert_eq!(
+            archive.frontmatter["pages"].as_array().unwrap().len(),
+            1
+        );
+
+        _ = flow.process()?;
+        assert_eq!(
+            fs::read_to_string(
+                output_path.join("archive/2024/index.html")
+            )?,
+            "archive: 2024"
+        );
+        assert_eq!(
+            fs::read_to_string(
+                output_path.join("archive/2024/05/index.html")
+            )?,
+            "month: May 2024"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_authors() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+        let data_path = temp_dir.path().join("authors.toml");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(
+            content_path.join("post.txt"),
+            "---\ntitle: Post\nauthor: ada\n---\n",
+        )?;
+        fs::write(&data_path, "[ada]\nname = \"Ada Lovelace\"\n")?;
+
+        let authors = Authors::read(AuthorConfig {
+            data: data_path,
+            ..AuthorConfig::default()
+        })?;
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?
+        .with_authors(authors);
+        let flow = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(AuthorRenderer),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        );
+        _ = flow.process()?;
+        assert_eq!(
+            fs::read_to_string(output_path.join("post.html"))?,
+            "page: Ada Lovelace /authors/ada/"
+        );
+        assert_eq!(
+            fs::read_to_string(
+                output_path.join("authors/ada/index.html")
+            )?,
+            "author: Ada Lovelace /post.html"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_data_pages() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+        let data_path = temp_dir.path().join("team.json");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(content_path.join("index.txt"), "home")?;
+        fs::write(
+            &data_path,
+            r#"[{"id": "ada", "title": "Ada Lovelace"}]"#,
+        )?;
+
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?
+        .with_data_pages(vec![DataPagesConfig {
+            data: data_path,
+            items: String::new(),
+            path: "team/{id}/".to_string(),
+            template: "member".to_string(),
+        }]);
+        let flow = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(TemplateNameRenderer),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        );
+        _ = flow.process()?;
+        assert_eq!(
+            fs::read_to_string(
+                output_path.join("team/ada/index.html")
+            )?,
+            "member: Ada Lovelace"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_series() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        for (name, weight) in [("one", 1), ("two", 2)] {
+            fs::write(
+                content_path.join(format!("{}.txt", name)),
+                format!("---\nseries: Tea\nweight: {}\n---\n", weight),
+            )?;
+        }
+
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?
+        .with_series(SeriesConfig::default());
+        let flow = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(SeriesRenderer),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        );
+        _ = flow.process()?;
+        assert_eq!(
+            fs::read_to_string(output_path.join("two.html"))?,
+            "Tea 2/2 /series/tea/"
+        );
+        assert_eq!(
+            fs::read_to_string(
+                output_path.join("series/tea/index.html")
+            )?,
+            "Tea /one.html"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_menus() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(content_path.join("about.txt"), "about")?;
+        fs::write(content_path.join("blog.txt"), "blog")?;
+
+        let item = |title: &str, url: &str| MenuItem {
+            title: title.to_string(),
+            url: url.to_string(),
+            weight: 0,
+            children: Vec::new(),
+        };
+        let menus = [(
+            "main".to_string(),
+            vec![
+                item("About", "/about.html"),
+                item("Blog", "/blog.html"),
+            ],
+        )]
+        .into_iter()
+        .collect();
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?
+        .with_menus(menus);
+        let flow = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(MenuRenderer),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        );
+        _ = flow.process()?;
+        assert_eq!(
+            fs::read_to_string(output_path.join("about.html"))?,
+            "[About] Blog"
+        );
+        assert_eq!(
+            fs::read_to_string(output_path.join("blog.html"))?,
+            "About [Blog]"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_error_pages() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(content_path.join("index.txt"), "home")?;
+        for template in ["404.hbs", "410.hbs", "500.hbs"] {
+            fs::write(template_path.join(template), "{{title}}")?;
+        }
+        fs::write(
+            content_path.join("500.txt"),
+            "---\ntitle: Oops\n---\n",
+        )?;
+
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?
+        .with_error_pages([410, 500, 503])
+        .with_sitemap("https://example.com");
+        let flow = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(TemplateNameRenderer),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        );
+        _ = flow.process()?;
+
+        assert_eq!(
+            fs::read_to_string(output_path.join("404.html"))?,
+            "404: Page not found"
+        );
+        assert_eq!(
+            fs::read_to_string(output_path.join("410.html"))?,
+            "410: Page gone"
+        );
+        assert_eq!(
+            fs::read_to_string(output_path.join("500.html"))?,
+            "default: Oops"
+        );
+        assert!(!output_path.join("503.html").exists());
+        let sitemap =
+            fs::read_to_string(output_path.join("sitemap.xml"))?;
+        assert!(!sitemap.contains("404.html"));
+        assert!(!sitemap.contains("410.html"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_check() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(
+            content_path.join("index.txt"),
+            r#"<a href="/">Home</a><a href="/missing">Gone</a>"#,
+        )?;
+        fs::write(
+            content_path.join("about.txt"),
+            "---\ntitle: About\n---\n<img src=\"/logo.png\">",
+        )?;
+
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?
+        .with_required_frontmatter(["title"]);
+        let report = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(HtmlTemplateRenderer::new(template_path.clone())),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        )
+        .check()?;
+
+        assert_eq!(report.pages, 2);
+        assert_eq!(report.count(IssueKind::BrokenLink), 1);
+        assert_eq!(report.count(IssueKind::MissingImage), 1);
+        assert_eq!(report.count(IssueKind::MissingFrontmatter), 1);
+        assert_eq!(report.issues.len(), 3);
+        assert!(!output_path.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_tombstones() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(content_path.join("a.txt"), "a")?;
+        fs::write(content_path.join("b.txt"), "b")?;
+
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?
+        .with_tombstones(true);
+        let nucleus = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(HtmlTemplateRenderer::new(template_path.clone())),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        );
+
+        _ = nucleus.process()?;
+        assert_eq!(
+            fs::read_to_string(output_path.join("b.html"))?,
+            "<html>B</html>"
+        );
+
+        fs::remove_file(content_path.join("b.txt"))?;
+        _ = nucleus.process()?;
+        _ = nucleus.process()?;
+        let stub = fs::read_to_string(output_path.join("b.html"))?;
+        assert!(stub.contains("410 Gone"));
+        let manifest = PageManifest::read(&output_path)?;
+        assert!(manifest.pages.contains("a.html"));
+        assert!(manifest.removed.contains("b.html"));
+
+        fs::write(content_path.join("b.txt"), "back")?;
+        _ = nucleus.process()?;
+        assert_eq!(
+            fs::read_to_string(output_path.join("b.html"))?,
+            "<html>BACK</html>"
+        );
+        assert!(PageManifest::read(&output_path)?.removed.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_continue_on_error() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(
+            content_path.join("bad.txt"),
+            "---\ndate: never\n---\n",
+        )?;
+        fs::write(content_path.join("good.txt"), "good")?;
+
+        let nucleus = |keep_going| -> Result<NucleusFlow> {
+            let config = NucleusFlowConfig::new(
+                &content_path,
+                &output_path,
+                &template_path,
+            )?
+            .with_continue_on_error(keep_going);
+            Ok(NucleusFlow::new(
+                config,
+                Box::new(FileContentProcessor::new(
+                    content_path.clone(),
+                )),
+                Box::new(HtmlTemplateRenderer::new(
+                    template_path.clone(),
+                )),
+                Box::new(HtmlOutputGenerator::new(output_path.clone())),
+            ))
+        };
+
+        let error = nucleus(false)?.process().unwrap_err();
+        assert!(!matches!(error, ProcessingError::BuildFailed { .. }));
+        assert!(!output_path.join("good.html").exists());
+
+        match nucleus(true)?.process() {
+            Err(ProcessingError::BuildFailed { failures }) => {
+                assert_eq!(failures.len(), 1);
+                assert_eq!(
+                    failures[0].path,
+                    content_path.join("bad.txt")
+                );
+                assert_eq!(failures[0].stage, BuildStage::Render);
+                let snippet = failures[0].error.snippet().unwrap();
+                assert_eq!(snippet.text(), "date: never");
+                assert_eq!(snippet.label(), "invalid `date`");
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+        assert!(output_path.join("good.html").exists());
+        assert!(!output_path.join("bad.html").exists());
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_nucleus_flow_page_exec() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(
+            content_path.join("status.txt"),
+            "---\nexec: echo up\n---\n",
+        )?;
+
+        let nucleus = |enabled| -> Result<NucleusFlow> {
+            let config = NucleusFlowConfig::new(
+                &content_path,
+                &output_path,
+                &template_path,
+            )?
+            .with_page_exec(enabled);
+            Ok(NucleusFlow::new(
+                config,
+                Box::new(FileContentProcessor::new(
+                    content_path.clone(),
+                )),
+                Box::new(HtmlTemplateRenderer::new(
+                    template_path.clone(),
+                )),
+                Box::new(HtmlOutputGenerator::new(output_path.clone())),
+            ))
+        };
+
+        let error = nucleus(false)?.process().unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("page commands are disabled"));
+        assert_eq!(error.snippet().unwrap().text(), "exec: echo up");
+
+        _ = nucleus(true)?.process()?;
+        assert!(output_path.join("status.html").exists());
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_nucleus_flow_preview_skips_page_exec() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+        let marker = temp_dir.path().join("ran");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(
+            content_path.join("status.txt"),
+            format!("---\nexec: [touch, {}]\n---\n", marker.display()),
+        )?;
+
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?
+        .with_page_exec(true);
+        let nucleus = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(HtmlTemplateRenderer::new(template_path.clone())),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        );
+
+        let preview = nucleus.preview()?;
+        assert_eq!(preview.count(ChangeKind::Created), 1);
+        assert!(!marker.exists());
+
+        _ = nucleus.process()?;
+        assert!(marker.exists());
+        Ok(())
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_nucleus_flow_memory_hard_limit() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(content_path.join("good.txt"), "good")?;
+
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?
+        .with_continue_on_error(true)
+        .with_memory_limits(MemoryConfig {
+            soft_limit_mb: None,
+            hard_limit_mb: Some(1),
+        });
+        let nucleus = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(HtmlTemplateRenderer::new(template_path.clone())),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        );
+
+        match nucleus.process() {
+            Err(ProcessingError::MemoryLimitExceeded {
+                page,
+                limit_mb,
+                ..
+            }) => {
+                assert_eq!(page, content_path.join("good.txt"));
+                assert_eq!(limit_mb, 1);
+            }
+            result => panic!("unexpected result: {:?}", result),
+        }
+        assert!(!output_path.join("good.html").exists());
+
+        Ok(())
+    }
+
+    /// A post-processor that appends its tag to the page.
+    #[derive(Debug)]
+    struct AppendTag(&'static str);
+
+    impl PostProcessor for AppendTag {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn process(&self, html: &str, page: &Page) -> Result<String> {
+            assert_eq!(page.url, "/good.html");
+            Ok(format!("{}[{}]", html, self.0))
+        }
+    }
+
+    #[test]
+    fn test_nucleus_flow_post_processors() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(content_path.join("good.txt"), "good")?;
+
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?;
+        let nucleus = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(HtmlTemplateRenderer::new(template_path.clone())),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        )
+        .with_post_processor(AppendTag("first"))
+        .with_post_processor(AppendTag("second"));
+
+        let labels: Vec<_> = nucleus
+            .graph()
+            .nodes(StageKind::Generators)
+            .iter()
+            .filter_map(|node| node.detail.clone())
+            .take(2)
+            .collect();
+        assert_eq!(labels, ["first", "second"]);
+
+        _ = nucleus.process()?;
+        let html = fs::read_to_string(output_path.join("good.html"))?;
+        assert!(html.contains("[first][second]"));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "social-cards")]
+    #[test]
+    fn test_nucleus_flow_social_cards() -> Result<()> {
+        use crate::core::config::SeoConfig;
+        use crate::generators::seo::SocialMetaTags;
+
+        let font = Path::new(
+            "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+        );
+        if !font.exists() {
+            eprintln!("skipping: {} is not installed", font.display());
+            return Ok(());
+        }
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(
+            content_path.join("post.txt"),
+            "---\ntitle: Hello\nauthor: Ada\n---\n<head></head>",
+        )?;
+        fs::write(
+            content_path.join("own.txt"),
+            "---\ntitle: Own\nimage: /own.png\n---\n<head></head>",
+        )?;
+
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?
+        .with_social_cards(SocialCardConfig {
+            font: Some(font.to_path_buf()),
+            ..SocialCardConfig::default()
+        });
+        _ = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(HtmlTemplateRenderer::new(template_path.clone())),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        )
+        .with_post_processor(SocialMetaTags::new(SeoConfig {
+            base_url: Some("https://example.com".to_string()),
+            ..SeoConfig::default()
+        }))
+        .process()?;
+
+        assert!(output_path.join("post.card.png").exists());
+        assert!(!output_path.join("own.card.png").exists());
+        let post = fs::read_to_string(output_path.join("post.html"))?;
+        assert!(post.contains(
+            r#"<meta property="og:image" content="https://example.com/post.card.png">"#
+        ));
+        let own = fs::read_to_string(output_path.join("own.html"))?;
+        assert!(own.contains("https://example.com/own.png"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_output_path_overrides() -> Result<()> {
         let temp_dir = TempDir::new().unwrap();
         let content_path = temp_dir.path().join("content");
         let output_path = temp_dir.path().join("output");
@@ -391,32 +5566,648 @@ fn test_nucleus_flow_process() -> Result<()> {
 
         fs::create_dir(&content_path)?;
         fs::create_dir(&template_path)?;
+        fs::write(
+            content_path.join("security.txt"),
+            "---\noutput_path: /.well-known/security.txt\n---\nkeys",
+        )?;
+        fs::write(
+            content_path.join("about.txt"),
+            "---\nurl: /about/\n---\nabout",
+        )?;
+        fs::write(
+            content_path.join("home.txt"),
+            "---\nurl: /\n---\nhome",
+        )?;
 
-        let test_content = "test content";
-        let content_file = content_path.join("test.txt");
-        fs::write(&content_file, test_content)?;
+        let nucleus = || -> Result<NucleusFlow> {
+            Ok(NucleusFlow::new(
+                NucleusFlowConfig::new(
+                    &content_path,
+                    &output_path,
+                    &template_path,
+                )?,
+                Box::new(FileContentProcessor::new(
+                    content_path.clone(),
+                )),
+                Box::new(HtmlTemplateRenderer::new(
+                    template_path.clone(),
+                )),
+                Box::new(HtmlOutputGenerator::new(output_path.clone())),
+            ))
+        };
+        let result = nucleus()?.process()?;
+        let urls: Vec<_> =
+            result.pages.iter().map(|page| page.url.as_str()).collect();
+        assert_eq!(urls, ["/.well-known/security.txt", "/about/", "/"]);
+        assert!(fs::read_to_string(
+            output_path.join(".well-known/security.txt")
+        )?
+        .ends_with("KEYS</html>"));
+        assert!(output_path.join("about/index.html").exists());
+        assert!(output_path.join("index.html").exists());
+        assert!(!output_path.join("about.html").exists());
+
+        fs::write(
+            content_path.join("team.txt"),
+            "---\nurl: /about/\n---\nteam",
+        )?;
+        let error = nucleus()?.process().unwrap_err().to_string();
+        assert!(error.contains("is also written by"), "{}", error);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_output_override() {
+        let source = Path::new("content/page.md");
+        let resolve = |frontmatter: serde_json::Value| {
+            output_override(source, frontmatter.as_object().unwrap())
+        };
+        assert_eq!(resolve(serde_json::json!({})).unwrap(), None);
+        assert_eq!(
+            resolve(serde_json::json!({ "url": "/blog/post" }))
+                .unwrap(),
+            Some(PathBuf::from("blog/post/index.html"))
+        );
+        assert_eq!(
+            resolve(serde_json::json!({ "url": "/feed.xml" })).unwrap(),
+            Some(PathBuf::from("feed.xml"))
+        );
+        assert_eq!(
+            resolve(serde_json::json!({
+                "url": "/ignored/",
+                "output_path": "./robots.txt",
+            }))
+            .unwrap(),
+            Some(PathBuf::from("robots.txt"))
+        );
+        for frontmatter in [
+            serde_json::json!({ "output_path": "../escape.html" }),
+            serde_json::json!({ "url": "/a/../../b/" }),
+            serde_json::json!({ "output_path": "docs/" }),
+            serde_json::json!({ "url": 3 }),
+        ] {
+            assert!(resolve(frontmatter).is_err());
+        }
+    }
+
+    #[test]
+    fn test_page_url() {
+        assert_eq!(page_url(Path::new("index.html")), "/");
+        assert_eq!(page_url(Path::new("blog/index.html")), "/blog/");
+        assert_eq!(
+            page_url(Path::new("blog/post.html")),
+            "/blog/post.html"
+        );
+    }
+
+    #[test]
+    fn test_suffixed_path() {
+        assert_eq!(
+            suffixed_path(Path::new("about.html"), 1),
+            Path::new("about-1.html")
+        );
+        assert_eq!(
+            suffixed_path(Path::new("go/discord/index.html"), 2),
+            Path::new("go/discord-2/index.html")
+        );
+        assert_eq!(
+            suffixed_path(Path::new("index.html"), 1),
+            Path::new("index-1.html")
+        );
+    }
+
+    #[test]
+    fn test_nucleus_flow_taxonomy_synonyms() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(
+            content_path.join("old.txt"),
+            "---\ntags: [rustlang, cli]\n---\nold",
+        )?;
+        fs::write(
+            content_path.join("new.txt"),
+            "---\ntags: [rust]\n---\nnew",
+        )?;
+
+        let mut taxonomies = TaxonomyConfig::default();
+        _ = taxonomies
+            .synonyms
+            .insert("rustlang".to_string(), "rust".to_string());
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?
+        .with_taxonomies(taxonomies);
+        let site = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(HtmlTemplateRenderer::new(template_path.clone())),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        )
+        .collect()?;
+
+        let tags: Vec<_> = site
+            .pages
+            .iter()
+            .map(|page| page.frontmatter["tags"].clone())
+            .collect();
+        assert_eq!(
+            tags,
+            [
+                serde_json::json!(["rust"]),
+                serde_json::json!(["rust", "cli"])
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_extract() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(
+            content_path.join("about.txt"),
+            "<h1>About</h1>\n<script>track()</script><p>Us and you</p>",
+        )?;
+        fs::write(
+            content_path.join("draft.txt"),
+            "---\ndraft: true\n---\nx",
+        )?;
+        fs::write(content_path.join("404.txt"), "Not found")?;
+
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?;
+        let pages = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(HtmlTemplateRenderer::new(template_path.clone())),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        )
+        .extract()?;
+
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].url, "/about.html");
+        assert_eq!(pages[0].source, content_path.join("about.txt"));
+        assert_eq!(pages[0].text, "ABOUT US AND YOU");
+        assert!(!output_path.exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_includes() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir_all(content_path.join("snippets"))?;
+        fs::create_dir(&template_path)?;
+        fs::write(content_path.join("snippets/note.txt"), "shared\n")?;
+        fs::write(
+            content_path.join("page.txt"),
+            "before {{include \"snippets/note.txt\"}} after",
+        )?;
 
         let config = NucleusFlowConfig::new(
             &content_path,
             &output_path,
             &template_path,
         )?;
+        let flow = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(HtmlTemplateRenderer::new(template_path.clone())),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        );
+        _ = flow.process()?;
+        assert_eq!(
+            fs::read_to_string(output_path.join("page.html"))?,
+            "<html>BEFORE SHARED AFTER</html>"
+        );
+
+        fs::write(
+            content_path.join("page.txt"),
+            "{{include \"page.txt\"}}",
+        )?;
+        assert!(flow
+            .process()
+            .unwrap_err()
+            .to_string()
+            .contains("Include cycle"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_summary() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(content_path.join("post.txt"), "one two three")?;
+        fs::write(content_path.join("empty.txt"), "")?;
 
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?
+        .with_summary(SummaryConfig {
+            words: 2,
+            paragraphs: None,
+        });
         let nucleus = NucleusFlow::new(
             config,
             Box::new(FileContentProcessor::new(content_path.clone())),
             Box::new(HtmlTemplateRenderer::new(template_path.clone())),
             Box::new(HtmlOutputGenerator::new(output_path.clone())),
         );
+        let site = nucleus.collect()?;
+        let post = site.page("/post.html").unwrap();
+        assert_eq!(post.summary.as_deref(), Some("ONE TWO\u{2026}"));
+        assert_eq!(post.to_json()["summary"], "ONE TWO\u{2026}");
+        assert_eq!(site.page("/empty.html").unwrap().summary, None);
+        assert_eq!(post.reading.word_count, 3);
+        assert_eq!(post.to_json()["reading_time"], 1);
+        Ok(())
+    }
 
-        nucleus.process()?;
+    #[test]
+    fn test_nucleus_flow_wiki_links() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
 
-        let output_file = output_path.join("test.html");
-        assert!(output_file.exists());
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(content_path.join("about.txt"), "about")?;
+        fs::write(
+            content_path.join("index.txt"),
+            "see [[about|us]] and [[About#team]]",
+        )?;
 
-        let output_content = fs::read_to_string(output_file)?;
-        assert_eq!(output_content, "<html>TEST CONTENT</html>");
+        let flow = |wiki_links: bool| -> Result<NucleusFlow> {
+            let config = NucleusFlowConfig::new(
+                &content_path,
+                &output_path,
+                &template_path,
+            )?
+            .with_wiki_links(wiki_links);
+            Ok(NucleusFlow::new(
+                config,
+                Box::new(FileContentProcessor::new(
+                    content_path.clone(),
+                )),
+                Box::new(HtmlTemplateRenderer::new(
+                    template_path.clone(),
+                )),
+                Box::new(HtmlOutputGenerator::new(output_path.clone())),
+            ))
+        };
+
+        _ = flow(true)?.process()?;
+        assert_eq!(
+            fs::read_to_string(output_path.join("index.html"))?,
+            "<html>SEE <a href=\"/about.html\">US</a> AND \
+             <a href=\"/about.html#team\">ABOUT#TEAM</a></html>"
+        );
+
+        fs::write(content_path.join("broken.txt"), "[[nowhere]]")?;
+        let error = flow(true)?.process().unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("'NOWHERE' matches no page"));
+        _ = flow(false)?.process()?;
+        assert_eq!(
+            fs::read_to_string(output_path.join("broken.html"))?,
+            "<html>[[NOWHERE]]</html>"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_routes() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(content_path.join("about.txt"), "about")?;
+        fs::write(content_path.join("status.txt"), "status")?;
+
+        let collect = |on_collision| -> Result<Site> {
+            let mut links = BTreeMap::new();
+            _ = links.insert(
+                "/about.html".to_string(),
+                "https://example.com/about".to_string(),
+            );
+            let config = NucleusFlowConfig::new(
+                &content_path,
+                &output_path,
+                &template_path,
+            )?
+            .with_shortlinks(RedirectMap::from_shortlinks(&links)?)
+            .with_routes(RoutesConfig {
+                reserved: vec!["/status".to_string()],
+                on_collision,
+            })
+            .with_continue_on_error(true);
+            NucleusFlow::new(
+                config,
+                Box::new(FileContentProcessor::new(
+                    content_path.clone(),
+                )),
+                Box::new(HtmlTemplateRenderer::new(
+                    template_path.clone(),
+                )),
+                Box::new(HtmlOutputGenerator::new(output_path.clone())),
+            )
+            .collect()
+        };
+
+        match collect(CollisionPolicy::Error) {
+            Err(ProcessingError::BuildFailed { failures }) => {
+                assert_eq!(failures.len(), 2);
+            }
+            other => panic!("expected a failed build, got {:?}", other),
+        }
+
+        fs::remove_file(content_path.join("status.txt"))?;
+        let site = collect(CollisionPolicy::Suffix)?;
+        assert_eq!(site.pages.len(), 1);
+        assert_eq!(site.pages[0].url, "/about-1.html");
+        assert!(collect(CollisionPolicy::Skip)?.pages.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_build_result() -> Result<()> {
+        use crate::core::validation::MaxSize;
+
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(content_path.join("about.txt"), "about")?;
+        fs::write(content_path.join("index.txt"), "home")?;
+        fs::write(content_path.join("short.txt"), "short")?;
+
+        let mut links = BTreeMap::new();
+        _ = links.insert(
+            "/about.html".to_string(),
+            "https://example.com/about".to_string(),
+        );
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?
+        .with_shortlinks(RedirectMap::from_shortlinks(&links)?)
+        .with_routes(RoutesConfig {
+            on_collision: CollisionPolicy::Skip,
+            ..RoutesConfig::default()
+        })
+        .with_sitemap("https://example.com")
+        .with_build_report(true);
+        let result = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(HtmlTemplateRenderer::new(template_path.clone())),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        )
+        .with_validator(Severity::Warning, MaxSize(4))
+        .process()?;
+
+        assert_eq!(
+            result.pages,
+            [
+                BuiltPage {
+                    source: content_path.join("index.txt"),
+                    path: PathBuf::from("index.html"),
+                    url: "/".to_string(),
+                },
+                BuiltPage {
+                    source: content_path.join("short.txt"),
+                    path: PathBuf::from("short.html"),
+                    url: "/short.html".to_string(),
+                },
+            ]
+        );
+        assert_eq!(result.skipped.len(), 1);
+        assert_eq!(
+            result.skipped[0].source,
+            content_path.join("about.txt")
+        );
+        assert!(result.skipped[0].reason.contains("shortlink"));
+        assert_eq!(result.warnings.len(), 2);
+        assert!(result.warnings[1].contains("short.txt"));
+        assert_eq!(
+            result.manifests,
+            [
+                PathBuf::from("sitemap.xml"),
+                PathBuf::from(REPORT_FILE_NAME)
+            ]
+        );
+        assert!(result.timings.total >= result.timings.render);
+        assert!(result.to_string().starts_with("Built 2 pages in "));
+
+        Ok(())
+    }
+
+    /// A renderer that echoes the `build` context as JSON.
+    #[derive(Debug)]
+    struct ContextRenderer;
+
+    impl TemplateRenderer for ContextRenderer {
+        fn render(
+            &self,
+            _template: &str,
+            context: &serde_json::Value,
+        ) -> Result<String> {
+            Ok(context["build"].to_string())
+        }
+
+        fn validate(
+            &self,
+            _template: &str,
+            _context: &serde_json::Value,
+        ) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_nucleus_flow_build_env() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(content_path.join("page.txt"), "page")?;
+        std::env::set_var("NUCLEUSFLOW_TEST_COMMIT_SHA", "abc123");
+
+        let build = |names: &[&str]| -> Result<BuildResult> {
+            let config = NucleusFlowConfig::new(
+                &content_path,
+                &output_path,
+                &template_path,
+            )?
+            .with_build_env(names.iter().copied());
+            NucleusFlow::new(
+                config,
+                Box::new(FileContentProcessor::new(
+                    content_path.clone(),
+                )),
+                Box::new(ContextRenderer),
+                Box::new(HtmlOutputGenerator::new(output_path.clone())),
+            )
+            .process()
+        };
+
+        _ = build(&["NUCLEUSFLOW_TEST_COMMIT_SHA"])?;
+        assert_eq!(
+            fs::read_to_string(output_path.join("page.html"))?,
+            r#"{"env":{"NUCLEUSFLOW_TEST_COMMIT_SHA":"abc123"}}"#
+        );
+        assert!(build(&["HOME"]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nucleus_flow_preview() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(content_path.join("a.txt"), "a")?;
+
+        let nucleus = NucleusFlow::new(
+            NucleusFlowConfig::new(
+                &content_path,
+                &output_path,
+                &template_path,
+            )?,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(HtmlTemplateRenderer::new(template_path.clone())),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        );
+
+        let preview = nucleus.preview()?;
+        assert_eq!(preview.count(ChangeKind::Created), 1);
+        assert!(!output_path.exists());
+
+        _ = nucleus.process()?;
+        assert!(nucleus.preview()?.is_empty());
+
+        fs::write(content_path.join("a.txt"), "changed")?;
+        fs::write(output_path.join("stale.html"), "stale")?;
+        let preview = nucleus.preview()?;
+        assert_eq!(preview.count(ChangeKind::Updated), 1);
+        assert_eq!(preview.count(ChangeKind::Deleted), 1);
+        assert_eq!(
+            fs::read_to_string(output_path.join("a.html"))?,
+            "<html>A</html>"
+        );
+
+        Ok(())
+    }
+
+    #[derive(Debug)]
+    struct Year;
+
+    impl plugin::Shortcode for Year {
+        fn render(
+            &self,
+            _args: &BTreeMap<String, String>,
+            _body: Option<&str>,
+        ) -> Result<String> {
+            Ok("2024".to_string())
+        }
+    }
+
+    #[derive(Debug)]
+    struct YearPlugin;
+
+    impl plugin::Plugin for YearPlugin {
+        fn name(&self) -> &str {
+            "dates"
+        }
+
+        fn register(
+            &self,
+            context: &mut plugin::PluginContext,
+        ) -> Result<()> {
+            context.shortcode("year", Year)
+        }
+    }
+
+    #[test]
+    fn test_nucleus_flow_shortcodes() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("content");
+        let output_path = temp_dir.path().join("output");
+        let template_path = temp_dir.path().join("templates");
+
+        fs::create_dir(&content_path)?;
+        fs::create_dir(&template_path)?;
+        fs::write(
+            content_path.join("a.txt"),
+            "since {{< dates:year >}}",
+        )?;
+
+        let mut plugins = PluginRegistry::new();
+        _ = plugins.register(&YearPlugin)?;
+        let config = NucleusFlowConfig::new(
+            &content_path,
+            &output_path,
+            &template_path,
+        )?
+        .with_plugins(plugins);
+        _ = NucleusFlow::new(
+            config,
+            Box::new(FileContentProcessor::new(content_path.clone())),
+            Box::new(HtmlTemplateRenderer::new(template_path.clone())),
+            Box::new(HtmlOutputGenerator::new(output_path.clone())),
+        )
+        .process()?;
 
+        assert_eq!(
+            fs::read_to_string(output_path.join("a.html"))?,
+            "<html>SINCE 2024</html>"
+        );
         Ok(())
     }
 }
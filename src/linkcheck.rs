@@ -0,0 +1,553 @@
+//! # External Link Checking
+//!
+//! Verifies the outbound `http` and `https` links of a site, so links to
+//! pages that have moved or disappeared elsewhere are caught along with
+//! the site's own broken links.
+//!
+//! Links are checked concurrently by an [`ExternalLinkChecker`] through a
+//! [`LinkProbe`]. The HTTP probe, [`HttpProbe`], needs the `link-check`
+//! feature; other probes can be supplied to check links some other way,
+//! or to test without a network. Each link is checked once however many
+//! pages use it, requests to the same host are spaced out by
+//! `host_interval_ms`, and links on skipped domains are not checked at
+//! all.
+//!
+//! Links that answered are recorded in an optional cache file with the
+//! time they were checked, and are trusted until `cache_hours` have
+//! passed. Dead links are never cached, so they are checked again on
+//! every run until they are fixed.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::core::config::LinkCheckConfig;
+//! use nucleusflow::linkcheck::{
+//!     ExternalLinkChecker, LinkProbe, LinkStatus,
+//! };
+//! use std::sync::Arc;
+//!
+//! #[derive(Debug)]
+//! struct Offline;
+//!
+//! impl LinkProbe for Offline {
+//!     fn probe(&self, url: &str) -> LinkStatus {
+//!         if url.contains("gone") {
+//!             LinkStatus::Dead(404)
+//!         } else {
+//!             LinkStatus::Alive
+//!         }
+//!     }
+//! }
+//!
+//! let config = LinkCheckConfig {
+//!     skip_domains: vec!["example.org".to_string()],
+//!     ..LinkCheckConfig::default()
+//! };
+//! let checker = ExternalLinkChecker::new(config, Arc::new(Offline));
+//! let dead = checker
+//!     .dead_links([
+//!         "https://example.com/",
+//!         "https://example.com/gone",
+//!         "https://example.org/gone",
+//!     ])
+//!     .unwrap();
+//! assert_eq!(dead.len(), 1);
+//! assert_eq!(dead["https://example.com/gone"], LinkStatus::Dead(404));
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::core::config::LinkCheckConfig;
+use crate::{ProcessingError, Result};
+
+/// Seconds in an hour, the unit the cache lifetime is configured in.
+const HOUR_SECS: u64 = 60 * 60;
+
+/// What checking a link found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkStatus {
+    /// The link answered successfully, possibly after redirects
+    Alive,
+    /// The link answered with an error status
+    Dead(u16),
+    /// The link could not be reached
+    Unreachable(String),
+}
+
+impl LinkStatus {
+    /// Returns `true` if the link answered successfully.
+    pub fn is_alive(&self) -> bool {
+        matches!(self, LinkStatus::Alive)
+    }
+}
+
+impl fmt::Display for LinkStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinkStatus::Alive => f.write_str("answered"),
+            LinkStatus::Dead(status) => {
+                write!(f, "answered with status {}", status)
+            }
+            LinkStatus::Unreachable(error) => {
+                write!(f, "could not be reached: {}", error)
+            }
+        }
+    }
+}
+
+/// Checks whether a single external link answers.
+pub trait LinkProbe: fmt::Debug + Send + Sync {
+    /// Requests a link and reports what happened.
+    fn probe(&self, url: &str) -> LinkStatus;
+}
+
+/// Checks links over HTTP, with a `HEAD` request that falls back to
+/// `GET` for servers that refuse `HEAD`.
+#[cfg(feature = "link-check")]
+#[derive(Debug, Clone)]
+pub struct HttpProbe {
+    agent: ureq::Agent,
+}
+
+#[cfg(feature = "link-check")]
+impl HttpProbe {
+    /// Creates a probe that gives up on a request after `timeout`.
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            agent: ureq::AgentBuilder::new()
+                .timeout(timeout)
+                .user_agent(&format!(
+                    "nucleusflow/{} (link check)",
+                    crate::cli::VERSION
+                ))
+                .build(),
+        }
+    }
+}
+
+#[cfg(feature = "link-check")]
+impl LinkProbe for HttpProbe {
+    fn probe(&self, url: &str) -> LinkStatus {
+        let response = match self.agent.head(url).call() {
+            Err(ureq::Error::Status(403 | 405 | 501, _)) => {
+                self.agent.get(url).call()
+            }
+            response => response,
+        };
+        match response {
+            Ok(_) => LinkStatus::Alive,
+            Err(ureq::Error::Status(status, _)) => {
+                LinkStatus::Dead(status)
+            }
+            Err(e) => LinkStatus::Unreachable(e.to_string()),
+        }
+    }
+}
+
+/// The links that answered on earlier runs, and when they were checked.
+#[derive(
+    Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(default)]
+pub struct LinkCache {
+    /// Seconds since the Unix epoch at which each link last answered
+    pub checked: BTreeMap<String, u64>,
+}
+
+impl LinkCache {
+    /// Reads a cache file.
+    ///
+    /// # Returns
+    /// * `Result<Self>` - The cache, or an empty cache if the file does
+    ///   not exist, or an error if it cannot be read or parsed.
+    pub fn read(path: &Path) -> Result<Self> {
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+        let json = fs::read(path).map_err(|e| {
+            ProcessingError::io_error(path.to_path_buf(), e)
+        })?;
+        serde_json::from_slice(&json).map_err(|e| {
+            ProcessingError::serialization(
+                format!(
+                    "Failed to parse link cache '{}'",
+                    path.display()
+                ),
+                Some(Box::new(e)),
+            )
+        })
+    }
+
+    /// Writes the cache file, creating its directory if needed.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        if let Some(parent) =
+            path.parent().filter(|p| !p.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent).map_err(|e| {
+                ProcessingError::io_error(parent.to_path_buf(), e)
+            })?;
+        }
+        let json = serde_json::to_vec_pretty(self).map_err(|e| {
+            ProcessingError::serialization(
+                "Failed to serialise link cache",
+                Some(Box::new(e)),
+            )
+        })?;
+        fs::write(path, json).map_err(|e| {
+            ProcessingError::io_error(path.to_path_buf(), e)
+        })
+    }
+
+    /// Returns `true` if a link answered less than `max_age` seconds
+    /// before `now`.
+    pub fn is_fresh(&self, url: &str, now: u64, max_age: u64) -> bool {
+        self.checked.get(url).map_or(false, |&checked| {
+            now.saturating_sub(checked) < max_age
+        })
+    }
+}
+
+/// Checks external links concurrently, with caching, per-host rate
+/// limiting and skipped domains.
+#[derive(Debug, Clone)]
+pub struct ExternalLinkChecker {
+    config: LinkCheckConfig,
+    probe: Arc<dyn LinkProbe>,
+    cache_file: Option<PathBuf>,
+}
+
+impl ExternalLinkChecker {
+    /// Creates a checker that requests links through `probe`.
+    pub fn new(
+        config: LinkCheckConfig,
+        probe: Arc<dyn LinkProbe>,
+    ) -> Self {
+        Self {
+            config,
+            probe,
+            cache_file: None,
+        }
+    }
+
+    /// Creates a checker that requests links over HTTP.
+    #[cfg(feature = "link-check")]
+    pub fn http(config: LinkCheckConfig) -> Self {
+        let timeout = Duration::from_secs(config.timeout_secs);
+        Self::new(config, Arc::new(HttpProbe::new(timeout)))
+    }
+
+    /// Remembers links that answer in a cache file, so they are not
+    /// checked again until the configured lifetime has passed.
+    pub fn with_cache_file<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.cache_file = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Returns `true` if a link is not checked: it is not an `http` or
+    /// `https` URL, or its host is on a skipped domain.
+    pub fn is_skipped(&self, url: &str) -> bool {
+        let host = match url_host(url) {
+            Some(host) => host,
+            None => return true,
+        };
+        self.config.skip_domains.iter().any(|domain| {
+            let domain = domain.trim_start_matches('.').to_lowercase();
+            host == domain || host.ends_with(&format!(".{}", domain))
+        })
+    }
+
+    /// Checks links and returns those that are dead.
+    ///
+    /// # Arguments
+    /// * `urls` - The links to check; duplicates are checked once.
+    ///
+    /// # Returns
+    /// * `Result<BTreeMap<String, LinkStatus>>` - Each dead link with
+    ///   what checking it found, or an error if the cache file cannot be
+    ///   read or written.
+    pub fn dead_links<'a, I>(
+        &self,
+        urls: I,
+    ) -> Result<BTreeMap<String, LinkStatus>>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let mut cache = match &self.cache_file {
+            Some(path) => LinkCache::read(path)?,
+            None => LinkCache::default(),
+        };
+        let now = unix_time();
+        let max_age = self.config.cache_hours.saturating_mul(HOUR_SECS);
+        let pending: BTreeSet<&str> = urls
+            .into_iter()
+            .filter(|url| !self.is_skipped(url))
+            .filter(|url| !cache.is_fresh(url, now, max_age))
+            .collect();
+        tracing::info!("Checking {} external links", pending.len());
+
+        let mut dead = BTreeMap::new();
+        for (url, status) in self.probe_all(pending) {
+            if status.is_alive() {
+                _ = cache.checked.insert(url, now);
+            } else {
+                _ = cache.checked.remove(&url);
+                _ = dead.insert(url, status);
+            }
+        }
+
+        if let Some(path) = &self.cache_file {
+            cache.checked.retain(|_, checked| {
+                now.saturating_sub(*checked) < max_age
+            });
+            cache.write(path)?;
+        }
+        Ok(dead)
+    }
+
+    /// Probes links on worker threads, spacing out requests to each
+    /// host.
+    fn probe_all(
+        &self,
+        urls: BTreeSet<&str>,
+    ) -> Vec<(String, LinkStatus)> {
+        let workers =
+            self.config.concurrency.clamp(1, urls.len().max(1));
+        let urls: Vec<String> =
+            urls.into_iter().map(String::from).collect();
+        let queue = Arc::new(Mutex::new(urls.into_iter()));
+        let limiter = Arc::new(HostLimiter::new(
+            Duration::from_millis(self.config.host_interval_ms),
+        ));
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                let queue = Arc::clone(&queue);
+                let limiter = Arc::clone(&limiter);
+                let probe = Arc::clone(&self.probe);
+                thread::spawn(move || {
+                    let mut results = Vec::new();
+                    loop {
+                        let url = match queue.lock().next() {
+                            Some(url) => url,
+                            None => break,
+                        };
+                        limiter.wait(&url);
+                        let status = probe.probe(&url);
+                        results.push((url, status));
+                    }
+                    results
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap_or_default())
+            .collect()
+    }
+}
+
+/// Hands out request times so that requests to the same host are at
+/// least an interval apart.
+#[derive(Debug)]
+struct HostLimiter {
+    interval: Duration,
+    next: Mutex<HashMap<String, Instant>>,
+}
+
+impl HostLimiter {
+    fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Blocks until a request to the link's host may be made.
+    fn wait(&self, url: &str) {
+        let host = url_host(url).unwrap_or_default();
+        let now = Instant::now();
+        let slot = {
+            let mut next = self.next.lock();
+            let slot = next.get(&host).map_or(now, |&at| at.max(now));
+            _ = next.insert(host, slot + self.interval);
+            slot
+        };
+        if slot > now {
+            thread::sleep(slot - now);
+        }
+    }
+}
+
+/// Returns the lowercased host of an `http` or `https` URL.
+fn url_host(url: &str) -> Option<String> {
+    let (scheme, rest) = url.split_once("://")?;
+    if !scheme.eq_ignore_ascii_case("http")
+        && !scheme.eq_ignore_ascii_case("https")
+    {
+        return None;
+    }
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let authority = authority.rsplit('@').next()?;
+    let host = match authority.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next()?,
+        None => authority.split(':').next()?,
+    };
+    (!host.is_empty()).then(|| host.to_lowercase())
+}
+
+/// Returns the current time in seconds since the Unix epoch.
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tempfile::TempDir;
+
+    /// Answers every link except those containing `dead`, counting
+    /// requests.
+    #[derive(Debug, Default)]
+    struct FakeProbe {
+        requests: AtomicUsize,
+    }
+
+    impl LinkProbe for FakeProbe {
+        fn probe(&self, url: &str) -> LinkStatus {
+            _ = self.requests.fetch_add(1, Ordering::SeqCst);
+            if url.contains("dead") {
+                LinkStatus::Dead(404)
+            } else {
+                LinkStatus::Alive
+            }
+        }
+    }
+
+    fn checker(
+        config: LinkCheckConfig,
+    ) -> (ExternalLinkChecker, Arc<FakeProbe>) {
+        let probe = Arc::new(FakeProbe::default());
+        (ExternalLinkChecker::new(config, probe.clone()), probe)
+    }
+
+    #[test]
+    fn test_url_host() {
+        let host = |url| url_host(url);
+        assert_eq!(
+            host("https://Example.com/a"),
+            Some("example.com".into())
+        );
+        assert_eq!(
+            host("http://user@example.com:8080?q"),
+            Some("example.com".into())
+        );
+        assert_eq!(host("https://[::1]:443/"), Some("::1".into()));
+        assert_eq!(host("mailto:me@example.com"), None);
+        assert_eq!(host("ftp://example.com/"), None);
+        assert_eq!(host("https:///path"), None);
+    }
+
+    #[test]
+    fn test_skipped_domains() {
+        let (checker, _) = checker(LinkCheckConfig {
+            skip_domains: vec!["Example.com".to_string()],
+            ..LinkCheckConfig::default()
+        });
+        assert!(checker.is_skipped("https://example.com/"));
+        assert!(checker.is_skipped("https://www.example.com/"));
+        assert!(checker.is_skipped("mailto:me@example.com"));
+        assert!(!checker.is_skipped("https://notexample.com/"));
+    }
+
+    #[test]
+    fn test_dead_links_with_cache() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let cache_file = temp_dir.path().join("cache/links.json");
+        let (checker, probe) = checker(LinkCheckConfig {
+            host_interval_ms: 0,
+            ..LinkCheckConfig::default()
+        });
+        let checker = checker.with_cache_file(&cache_file);
+        let urls = [
+            "https://a.test/",
+            "https://a.test/dead",
+            "https://b.test/",
+            "https://b.test/",
+        ];
+
+        let dead = checker.dead_links(urls)?;
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead["https://a.test/dead"], LinkStatus::Dead(404));
+        assert_eq!(probe.requests.load(Ordering::SeqCst), 3);
+
+        let cache = LinkCache::read(&cache_file)?;
+        assert_eq!(cache.checked.len(), 2);
+
+        let dead = checker.dead_links(urls)?;
+        assert_eq!(dead.len(), 1);
+        assert_eq!(probe.requests.load(Ordering::SeqCst), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_host_interval() -> Result<()> {
+        let (checker, probe) = checker(LinkCheckConfig {
+            host_interval_ms: 50,
+            ..LinkCheckConfig::default()
+        });
+        let started = Instant::now();
+        let dead = checker.dead_links([
+            "https://a.test/1",
+            "https://a.test/2",
+            "https://a.test/3",
+        ])?;
+        assert!(dead.is_empty());
+        assert_eq!(probe.requests.load(Ordering::SeqCst), 3);
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        Ok(())
+    }
+
+    #[cfg(feature = "link-check")]
+    #[test]
+    fn test_http_probe() {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            for status in ["200 OK", "404 Not Found"] {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = [0; 1024];
+                _ = stream.read(&mut request).unwrap();
+                write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    status
+                )
+                .unwrap();
+            }
+        });
+
+        let probe = HttpProbe::new(Duration::from_secs(5));
+        let url = format!("http://{}/", address);
+        assert_eq!(probe.probe(&url), LinkStatus::Alive);
+        assert_eq!(probe.probe(&url), LinkStatus::Dead(404));
+        server.join().unwrap();
+        assert!(matches!(
+            probe.probe(&url),
+            LinkStatus::Unreachable(_)
+        ));
+    }
+}
@@ -8,7 +8,7 @@
 //!
 //! - Project creation with customizable templates
 //! - Site building with configurable options
-//! - Development server with live reload
+//! - Development server previewing built sites, with their 404 page
 //! - Asset management and optimization
 //!
 //! ## Usage Examples
@@ -28,19 +28,109 @@
 //! ```bash
 //! nucleusflow serve --port 3000 --watch
 //! ```
+//!
+//! Rebuild on changes and push each rebuild's changed files to a
+//! shared preview:
+//! ```bash
+//! nucleusflow serve --watch --preview-deploy /mnt/preview
+//! ```
+//!
+//! Show the build pipeline, or export it for Graphviz:
+//! ```bash
+//! nucleusflow graph
+//! nucleusflow graph --dot | dot -Tsvg > pipeline.svg
+//! ```
+//!
+//! Check a site for broken links, missing images, template errors and
+//! missing frontmatter without writing any output:
+//! ```bash
+//! nucleusflow check --require title --require description
+//! ```
+//!
+//! With the `link-check` feature, check external links as well:
+//! ```bash
+//! nucleusflow check --external --skip-domain linkedin.com
+//! ```
+//!
+//! Lint the generated pages for accessibility problems, with rule
+//! severities from the `[a11y]` table of `nucleusflow.toml`:
+//! ```bash
+//! nucleusflow check --a11y
+//! ```
+//!
+//! Report the problems inline on pull requests, as GitHub Actions
+//! annotations or a GitLab Code Quality report:
+//! ```bash
+//! nucleusflow check --annotations github
+//! nucleusflow check --annotations gitlab > gl-code-quality-report.json
+//! ```
+//!
+//! Build a fixture site and compare it with its committed output:
+//! ```bash
+//! nucleusflow test --golden tests/site
+//! nucleusflow test --golden tests/site --update
+//! ```
+//!
+//! Export the configuration file's JSON Schema for editors and CI:
+//! ```bash
+//! nucleusflow config schema > nucleusflow.schema.json
+//! ```
+//!
+//! Check a configuration file, or print it with defaults applied:
+//! ```bash
+//! nucleusflow config validate -f nucleusflow.toml
+//! nucleusflow config show -f nucleusflow.toml
+//! ```
+//!
+//! Check templates against their snapshots in `tests/templates/`:
+//! ```bash
+//! nucleusflow template test
+//! nucleusflow template test --update
+//! ```
+//!
+//! Import the posts of an RSS or Atom feed as content, downloading
+//! their images (needs the `import` feature unless `--no-images` is
+//! given and the feed is a local file):
+//! ```bash
+//! nucleusflow import https://example.substack.com/feed
+//! nucleusflow import export.xml --no-images
+//! ```
 
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
-use log::{debug, error, info, warn};
+use clap::{Args, Parser, Subcommand};
+use nucleusflow::annotations::AnnotationFormat;
+use nucleusflow::compare::BuildComparison;
+use nucleusflow::core::config::{
+    A11yConfig, Config, ConfigBuilder, LinkCheckConfig, StaticConfig,
+};
+use nucleusflow::core::error::ProcessingError;
+use nucleusflow::core::ignore::{IgnoreRules, IGNORE_FILE_NAME};
+use nucleusflow::extract::ExtractFormat;
+use nucleusflow::golden::GoldenSite;
+#[cfg(feature = "import")]
+use nucleusflow::import::HttpFetch;
+use nucleusflow::import::{Fetch, FeedImporter};
+use nucleusflow::linkcheck::ExternalLinkChecker;
+use nucleusflow::serve::DevServer;
+use nucleusflow::snapshots::TemplateSnapshots;
+use nucleusflow::template::HandlebarsRenderer;
+use nucleusflow::watch::{SourceWatcher, WATCH_INTERVAL_MS};
 use nucleusflow::{
     FileContentProcessor, HtmlOutputGenerator, HtmlTemplateRenderer,
     NucleusFlow, NucleusFlowConfig,
 };
 use std::{
     env,
+    net::TcpListener,
     path::{Path, PathBuf},
     process::exit,
+    sync::mpsc::{self, RecvTimeoutError},
+    sync::Arc,
+    thread,
+    time::Duration,
 };
+use tracing::{debug, error, info, warn};
+use tracing_subscriber::filter::{EnvFilter, LevelFilter};
 
 /// Command-line interface configuration for NucleusFlow.
 #[derive(Parser, Debug)]
@@ -75,17 +165,9 @@ enum Commands {
 
     /// Build the static site
     Build {
-        /// Path to content directory
-        #[arg(short = 'c', long, default_value = "content")]
-        content_dir: PathBuf,
-
-        /// Path to output directory
-        #[arg(short = 'o', long, default_value = "public")]
-        output_dir: PathBuf,
-
-        /// Path to template directory
-        #[arg(short = 't', long, default_value = "templates")]
-        template_dir: PathBuf,
+        /// Site directories
+        #[command(flatten)]
+        dirs: SiteDirs,
 
         /// Enable minification of output files
         #[arg(short = 'm', long)]
@@ -94,6 +176,131 @@ enum Commands {
         /// Build configuration file
         #[arg(short = 'f', long, default_value = "nucleusflow.toml")]
         config: PathBuf,
+
+        /// Show which files would change without writing anything or
+        /// running page `exec` commands
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Skip pages that fail and report every failure at the end
+        #[arg(short = 'k', long)]
+        keep_going: bool,
+    },
+
+    /// Show the configured build pipeline
+    Graph {
+        /// Site directories
+        #[command(flatten)]
+        dirs: SiteDirs,
+
+        /// Emit Graphviz DOT instead of text
+        #[arg(long)]
+        dot: bool,
+
+        /// Explain whether these source files are processed
+        #[arg(long = "file")]
+        files: Vec<PathBuf>,
+    },
+
+    /// Build the site in memory and report problems with it
+    Check {
+        /// Site directories
+        #[command(flatten)]
+        dirs: SiteDirs,
+
+        /// Frontmatter key every page must set (repeatable)
+        #[arg(long = "require")]
+        required: Vec<String>,
+
+        /// Also lint pages for accessibility problems
+        #[arg(long)]
+        a11y: bool,
+
+        /// Print the problems as CI annotations (github, gitlab)
+        /// instead of a report
+        #[arg(long, value_name = "FORMAT")]
+        annotations: Option<AnnotationFormat>,
+
+        /// Configuration file with `[link_check]` and `[a11y]`
+        /// settings, read if present
+        #[arg(short = 'f', long, default_value = "nucleusflow.toml")]
+        config: PathBuf,
+
+        /// External link checking options
+        #[command(flatten)]
+        external: ExternalLinks,
+    },
+
+    /// Compare two builds of the site
+    Compare {
+        /// First output directory, or page manifest
+        old: PathBuf,
+
+        /// Second output directory, or page manifest
+        new: PathBuf,
+    },
+
+    /// Print the plain text and metadata of every page
+    Extract {
+        /// Site directories
+        #[command(flatten)]
+        dirs: SiteDirs,
+
+        /// Output format (jsonl, json)
+        #[arg(long, default_value = "jsonl")]
+        format: ExtractFormat,
+
+        /// Write the pages to this file instead of standard output
+        #[arg(long, value_name = "FILE")]
+        out: Option<PathBuf>,
+    },
+
+    /// Inspect the configuration file format
+    Config {
+        /// The configuration action to perform
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+
+    /// Work with the site's templates
+    Template {
+        /// The template action to perform
+        #[command(subcommand)]
+        action: TemplateCommand,
+    },
+
+    /// Build a fixture site and compare it with its expected output
+    Test {
+        /// Fixture directory holding `content/`, `templates/`,
+        /// `static/` and `expected/`
+        #[arg(long, default_value = "tests/site")]
+        golden: PathBuf,
+
+        /// Replace the expected output with the build
+        #[arg(short = 'u', long)]
+        update: bool,
+    },
+
+    /// Import the posts of an RSS or Atom feed as content files
+    Import {
+        /// URL or path of the feed
+        feed: String,
+
+        /// Directory the posts are written to
+        #[arg(short = 'c', long, default_value = "content")]
+        content_dir: PathBuf,
+
+        /// Directory downloaded images are written to
+        #[arg(long, default_value = "static/images")]
+        images_dir: PathBuf,
+
+        /// URL the images directory is served at
+        #[arg(long, default_value = "/images/")]
+        images_url: String,
+
+        /// Keep images at their remote URLs
+        #[arg(long)]
+        no_images: bool,
     },
 
     /// Start the development server
@@ -102,32 +309,193 @@ enum Commands {
         #[arg(short = 'p', long, default_value = "3000")]
         port: u16,
 
-        /// Enable file watching
+        /// Rebuild into the served directory when sources change
         #[arg(short = 'w', long)]
         watch: bool,
 
         /// Base directory to serve from
         #[arg(short = 'd', long, default_value = "public")]
         dir: PathBuf,
+
+        /// Path to content directory, watched with --watch
+        #[arg(short = 'c', long, default_value = "content")]
+        content_dir: PathBuf,
+
+        /// Path to template directory, watched with --watch
+        #[arg(short = 't', long, default_value = "templates")]
+        template_dir: PathBuf,
+
+        /// Path to static files directory, watched with --watch
+        #[arg(short = 's', long, default_value = "static")]
+        static_dir: PathBuf,
+
+        /// Directory each watch rebuild pushes its changed files to
+        #[arg(long, requires = "watch")]
+        preview_deploy: Option<PathBuf>,
+    },
+}
+
+/// Configuration subcommands.
+#[derive(Subcommand, Debug)]
+enum ConfigCommand {
+    /// Print the JSON Schema of the configuration file
+    Schema,
+
+    /// Load a configuration file and report every problem with it
+    Validate {
+        /// Configuration file to load
+        #[command(flatten)]
+        file: ConfigFile,
     },
+
+    /// Print the effective configuration, with secrets masked
+    Show {
+        /// Configuration file to load
+        #[command(flatten)]
+        file: ConfigFile,
+    },
+}
+
+/// Template subcommands.
+#[derive(Subcommand, Debug)]
+enum TemplateCommand {
+    /// Render templates against sample contexts and compare snapshots
+    Test {
+        /// Path to template directory
+        #[arg(short = 't', long, default_value = "templates")]
+        template_dir: PathBuf,
+
+        /// Directory of sample contexts and their snapshots
+        #[arg(long, default_value = "tests/templates")]
+        fixtures: PathBuf,
+
+        /// Rewrite the snapshots with the current output
+        #[arg(short = 'u', long)]
+        update: bool,
+    },
+}
+
+/// A configuration file and the environment variables overriding it.
+#[derive(Args, Debug)]
+struct ConfigFile {
+    /// Configuration file
+    #[arg(short = 'f', long, default_value = "nucleusflow.toml")]
+    config: PathBuf,
+
+    /// Prefix of environment variables overriding configuration values
+    #[arg(long, default_value = "NUCLEUS_")]
+    env_prefix: String,
+}
+
+impl ConfigFile {
+    /// Loads the configuration with its overlays and environment
+    /// overrides applied, without validating it.
+    fn resolve(&self) -> Result<Config> {
+        ConfigBuilder::new()
+            .with_file(&self.config)
+            .with_env_prefix(&self.env_prefix)
+            .resolve()
+            .map_err(|error| {
+                eprint!("{}", error.render_diagnostic());
+                anyhow::anyhow!(
+                    "Failed to load {} [{}]",
+                    self.config.display(),
+                    error.code()
+                )
+            })
+    }
+}
+
+/// Options for checking a site's external links.
+#[derive(Args, Debug)]
+struct ExternalLinks {
+    /// Also check external links (needs the `link-check` feature)
+    #[arg(long = "external")]
+    enabled: bool,
+
+    /// Domain whose links are not checked (repeatable)
+    #[arg(long = "skip-domain")]
+    skip_domains: Vec<String>,
+
+    /// File remembering the external links that answered
+    #[arg(long, default_value = ".nucleusflow/link-cache.json")]
+    link_cache: PathBuf,
+}
+
+impl ExternalLinks {
+    /// Returns the external link checker to use, if enabled.
+    fn checker(
+        self,
+        mut config: LinkCheckConfig,
+    ) -> Result<Option<ExternalLinkChecker>> {
+        if !self.enabled {
+            return Ok(None);
+        }
+        config.skip_domains.extend(self.skip_domains);
+        http_link_checker(config)
+            .map(|checker| Some(checker.with_cache_file(self.link_cache)))
+    }
+}
+
+/// Creates an external link checker that requests links over HTTP.
+#[cfg(feature = "link-check")]
+fn http_link_checker(config: LinkCheckConfig) -> Result<ExternalLinkChecker> {
+    Ok(ExternalLinkChecker::http(config))
+}
+
+/// Reports that external links cannot be checked by this build.
+#[cfg(not(feature = "link-check"))]
+fn http_link_checker(_config: LinkCheckConfig) -> Result<ExternalLinkChecker> {
+    Err(anyhow::anyhow!(
+        "External links can only be checked when nucleusflow is built \
+         with the `link-check` feature"
+    ))
+}
+
+/// Directories a site is built from and into.
+#[derive(Args, Debug)]
+struct SiteDirs {
+    /// Path to content directory
+    #[arg(short = 'c', long, default_value = "content")]
+    content_dir: PathBuf,
+
+    /// Path to output directory
+    #[arg(short = 'o', long, default_value = "public")]
+    output_dir: PathBuf,
+
+    /// Path to template directory
+    #[arg(short = 't', long, default_value = "templates")]
+    template_dir: PathBuf,
+
+    /// Path to static files directory
+    #[arg(short = 's', long, default_value = "static")]
+    static_dir: PathBuf,
+}
+
+/// Maps the `-v` count to the most detailed level that is logged.
+fn log_level(verbosity: u8) -> LevelFilter {
+    match verbosity {
+        0 => LevelFilter::WARN,
+        1 => LevelFilter::INFO,
+        2 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    }
 }
 
-/// Initialize the logger with appropriate verbosity.
+/// Initialize the tracing subscriber with appropriate verbosity.
+///
+/// `RUST_LOG` directives, such as `nucleusflow=trace`, refine the level
+/// chosen with `-v`.
 fn setup_logging(verbosity: u8) {
-    let env = env_logger::Env::default();
-    let mut builder = env_logger::Builder::from_env(env);
-
-    let log_level = match verbosity {
-        0 => log::LevelFilter::Warn,
-        1 => log::LevelFilter::Info,
-        2 => log::LevelFilter::Debug,
-        _ => log::LevelFilter::Trace,
-    };
+    let log_level = log_level(verbosity);
+    let filter = EnvFilter::builder()
+        .with_default_directive(log_level.into())
+        .from_env_lossy();
 
-    builder
-        .filter_level(log_level)
-        .format_timestamp(None)
-        .format_module_path(false)
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .without_time()
+        .with_target(false)
         .init();
 
     debug!("Logging initialized at level: {:?}", log_level);
@@ -199,6 +567,16 @@ fn create_project_structure(project_dir: &Path, template: &str) -> Result<()> {
     std::fs::write(&config_path, config_content)
         .context("Failed to write config file")?;
 
+    // Create default ignore file
+    let ignore_content = r#"# Files excluded from content scanning and asset copying
+*.swp
+*~
+.DS_Store
+_drafts/
+"#;
+    std::fs::write(project_dir.join(IGNORE_FILE_NAME), ignore_content)
+        .context("Failed to write ignore file")?;
+
     // Copy template files if they exist
     if let Err(e) = copy_template_files(project_dir, template) {
         warn!("Failed to copy template files: {}", e);
@@ -226,48 +604,385 @@ fn copy_template_files(_project_dir: &Path, template: &str) -> Result<()> {
 
 /// Builds the static site.
 fn handle_build(
-    content_dir: PathBuf,
-    output_dir: PathBuf,
-    template_dir: PathBuf,
+    dirs: SiteDirs,
     minify: bool,
     config_path: PathBuf,
+    dry_run: bool,
+    keep_going: bool,
 ) -> Result<()> {
     info!("Building site with configuration:");
-    info!("  Content directory: {:?}", content_dir);
-    info!("  Output directory: {:?}", output_dir);
-    info!("  Template directory: {:?}", template_dir);
+    info!("  Content directory: {:?}", dirs.content_dir);
+    info!("  Output directory: {:?}", dirs.output_dir);
+    info!("  Template directory: {:?}", dirs.template_dir);
+    info!("  Static directory: {:?}", dirs.static_dir);
     info!("  Minification: {}", minify);
     info!("  Config file: {:?}", config_path);
+    info!("  Dry run: {}", dry_run);
+    info!("  Keep going: {}", keep_going);
+
+    let nucleus = create_pipeline(dirs, keep_going)?;
+
+    if dry_run {
+        let preview = nucleus.preview().context("Failed to preview site")?;
+        print!("{}", preview);
+        return Ok(());
+    }
+
+    let result = nucleus.process().map_err(|error| {
+        eprint!("{}", error.render_diagnostic());
+        match &error {
+            ProcessingError::BuildFailed { failures } => anyhow::anyhow!(
+                "Site built with {} failed page(s) [{}]",
+                failures.len(),
+                error.code()
+            ),
+            _ => anyhow::anyhow!(
+                "Failed to process site [{}]",
+                error.code()
+            ),
+        }
+    })?;
+
+    info!("Site built successfully! {}", result);
+    Ok(())
+}
+
+/// Creates the build pipeline from the command-line directories.
+fn create_pipeline(
+    dirs: SiteDirs,
+    continue_on_error: bool,
+) -> Result<NucleusFlow> {
+    create_pipeline_with(dirs, continue_on_error, |config| config)
+}
+
+/// Creates the build pipeline from the command-line directories,
+/// adjusting its configuration with `configure`.
+fn create_pipeline_with(
+    dirs: SiteDirs,
+    continue_on_error: bool,
+    configure: impl FnOnce(NucleusFlowConfig) -> NucleusFlowConfig,
+) -> Result<NucleusFlow> {
+    let SiteDirs {
+        content_dir,
+        output_dir,
+        template_dir,
+        static_dir,
+    } = dirs;
+    let ignore_rules = IgnoreRules::load(".")
+        .context(format!("Failed to load {}", IGNORE_FILE_NAME))?;
 
     // Initialize NucleusFlow components
     let config = NucleusFlowConfig::new(&content_dir, &output_dir, &template_dir)
-        .context("Failed to create NucleusFlow configuration")?;
+        .context("Failed to create NucleusFlow configuration")?
+        .with_static_files(StaticConfig {
+            dir: static_dir,
+            ..StaticConfig::default()
+        })
+        .with_ignore_rules(ignore_rules)
+        .with_continue_on_error(continue_on_error);
+    let config = configure(config);
 
     let content_processor = FileContentProcessor::new(content_dir);
     let template_renderer = HtmlTemplateRenderer::new(template_dir);
     let output_generator = HtmlOutputGenerator::new(output_dir);
 
-    let nucleus = NucleusFlow::new(
+    Ok(NucleusFlow::new(
         config,
         Box::new(content_processor),
         Box::new(template_renderer),
         Box::new(output_generator),
-    );
+    ))
+}
 
-    nucleus.process().context("Failed to process site")?;
+/// Prints the build pipeline, and whether each given file is processed.
+fn handle_graph(dirs: SiteDirs, dot: bool, files: &[PathBuf]) -> Result<()> {
+    let nucleus = create_pipeline(dirs, false)?;
+    let graph = nucleus.graph();
 
-    info!("Site built successfully!");
+    if dot {
+        print!("{}", graph.to_dot());
+    } else {
+        print!("{}", graph);
+    }
+    for file in files {
+        println!("{}: {}", file.display(), nucleus.source_status(file));
+    }
+    Ok(())
+}
+
+/// Prints the differences between two builds.
+fn handle_compare(old: &Path, new: &Path) -> Result<()> {
+    let comparison = BuildComparison::compare(old, new).context(format!(
+        "Failed to compare {} with {}",
+        old.display(),
+        new.display()
+    ))?;
+    print!("{}", comparison);
+    Ok(())
+}
+
+/// Prints or writes the plain text and metadata of every page.
+fn handle_extract(
+    dirs: SiteDirs,
+    format: ExtractFormat,
+    out: Option<PathBuf>,
+) -> Result<()> {
+    let nucleus = create_pipeline(dirs, false)?;
+    let pages = nucleus.extract().context("Failed to extract pages")?;
+    let text = format.render(&pages)?;
+    match out {
+        Some(path) => {
+            std::fs::write(&path, text)
+                .context(format!("Failed to write {}", path.display()))?;
+            info!("Extracted {} pages to {}", pages.len(), path.display());
+        }
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+/// Checks the site and prints every problem found.
+fn handle_check(
+    dirs: SiteDirs,
+    required: Vec<String>,
+    a11y: bool,
+    annotations: Option<AnnotationFormat>,
+    config: PathBuf,
+    external: ExternalLinks,
+) -> Result<()> {
+    let (link_check, a11y_config) = if config.is_file() {
+        let site_config = ConfigFile {
+            config,
+            env_prefix: "NUCLEUS_".to_string(),
+        }
+        .resolve()?;
+        (site_config.link_check, site_config.a11y)
+    } else {
+        (LinkCheckConfig::default(), A11yConfig::default())
+    };
+    let link_checker = external.checker(link_check)?;
+    let nucleus = create_pipeline_with(dirs, true, |config| {
+        let mut config = config.with_required_frontmatter(required);
+        if a11y {
+            config = config.with_accessibility_check(a11y_config);
+        }
+        match link_checker {
+            Some(checker) => config.with_link_checker(checker),
+            None => config,
+        }
+    })?;
+    let report = nucleus.check().context("Failed to check site")?;
+    match annotations {
+        Some(format) => print!("{}", format.render(&report)),
+        None => print!("{}", report),
+    }
+    if !report.is_healthy() {
+        return Err(anyhow::anyhow!(
+            "Site check found {} problem(s)",
+            report.issues.len()
+        ));
+    }
     Ok(())
 }
 
+/// Runs a configuration subcommand.
+fn handle_config(action: ConfigCommand) -> Result<()> {
+    match action {
+        ConfigCommand::Schema => {
+            let schema = Config::json_schema()
+                .context("Failed to generate configuration schema")?;
+            println!("{}", serde_json::to_string_pretty(&schema)?);
+        }
+        ConfigCommand::Validate { file } => {
+            let problems = file.resolve()?.problems();
+            if !problems.is_empty() {
+                for problem in &problems {
+                    eprint!("{}", problem.render_diagnostic());
+                }
+                return Err(anyhow::anyhow!(
+                    "{} has {} problem(s)",
+                    file.config.display(),
+                    problems.len()
+                ));
+            }
+            println!("{} is valid", file.config.display());
+        }
+        ConfigCommand::Show { file } => {
+            let config = file.resolve()?;
+            print!(
+                "{}",
+                config
+                    .to_masked_toml()
+                    .context("Failed to render configuration")?
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Runs a template subcommand.
+fn handle_template(action: TemplateCommand) -> Result<()> {
+    match action {
+        TemplateCommand::Test {
+            template_dir,
+            fixtures,
+            update,
+        } => {
+            let renderer = HandlebarsRenderer::new(&template_dir)
+                .context("Failed to load templates")?;
+            let report = TemplateSnapshots::new(&fixtures)
+                .run(&renderer, update)
+                .context("Failed to run template snapshots")?;
+            print!("{}", report);
+            if !report.passed() {
+                return Err(anyhow::anyhow!(
+                    "{} template snapshot(s) failed",
+                    report.failures()
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Builds a golden fixture site and compares it with its expected
+/// output.
+fn handle_test(golden: &Path, update: bool) -> Result<()> {
+    let site = GoldenSite::new(golden);
+    let report = site
+        .run(
+            |output| {
+                let config = NucleusFlowConfig::new(
+                    site.content_dir(),
+                    output.to_path_buf(),
+                    site.template_dir(),
+                )?
+                .with_static_files(StaticConfig {
+                    dir: site.static_dir(),
+                    ..StaticConfig::default()
+                });
+                NucleusFlow::new(
+                    config,
+                    Box::new(FileContentProcessor::new(site.content_dir())),
+                    Box::new(HtmlTemplateRenderer::new(site.template_dir())),
+                    Box::new(HtmlOutputGenerator::new(output.to_path_buf())),
+                )
+                .process()
+            },
+            update,
+        )
+        .map_err(|error| {
+            eprint!("{}", error.render_diagnostic());
+            anyhow::anyhow!(
+                "Failed to build golden site {} [{}]",
+                golden.display(),
+                error.code()
+            )
+        })?;
+    print!("{}", report);
+    if !report.passed() {
+        return Err(anyhow::anyhow!(
+            "{} file(s) differ from the expected output",
+            report.differences.len()
+        ));
+    }
+    Ok(())
+}
+
+/// Imports the posts of a feed as content files.
+fn handle_import(
+    feed: &str,
+    content_dir: &Path,
+    images_dir: &Path,
+    images_url: &str,
+    no_images: bool,
+) -> Result<()> {
+    let remote =
+        feed.starts_with("http://") || feed.starts_with("https://");
+    let fetcher = if remote || !no_images {
+        Some(http_fetcher()?)
+    } else {
+        None
+    };
+    let xml = match &fetcher {
+        Some(fetcher) if remote => {
+            String::from_utf8(fetcher.fetch(feed)?)
+                .context("Feed is not valid UTF-8")?
+        }
+        _ => std::fs::read_to_string(feed)
+            .with_context(|| format!("Failed to read feed {}", feed))?,
+    };
+
+    let mut importer = FeedImporter::new(content_dir);
+    if let (Some(fetcher), false) = (fetcher, no_images) {
+        importer =
+            importer.with_images(fetcher, images_dir, images_url);
+    }
+    let posts = importer.import(&xml)?;
+    for post in &posts {
+        info!(
+            "Imported {} with {} images",
+            post.path.display(),
+            post.images.len()
+        );
+    }
+    info!("Imported {} posts from {}", posts.len(), feed);
+    Ok(())
+}
+
+/// Creates a fetcher that downloads feeds and images over HTTP.
+#[cfg(feature = "import")]
+fn http_fetcher() -> Result<Arc<dyn Fetch>> {
+    Ok(Arc::new(HttpFetch::new(Duration::from_secs(30))))
+}
+
+/// Reports that feeds and images cannot be downloaded by this build.
+#[cfg(not(feature = "import"))]
+fn http_fetcher() -> Result<Arc<dyn Fetch>> {
+    Err(anyhow::anyhow!(
+        "Feeds and images can only be downloaded when nucleusflow is \
+         built with the `import` feature; pass a local feed with \
+         --no-images instead"
+    ))
+}
+
 /// Starts the development server.
-fn handle_serve(port: u16, watch: bool, dir: PathBuf) -> Result<()> {
+fn handle_serve(
+    port: u16,
+    dir: PathBuf,
+    watch: Option<(SiteDirs, Option<PathBuf>)>,
+) -> Result<()> {
     info!(
         "Starting development server on port {} (watch mode: {})",
-        port, watch
+        port,
+        watch.is_some()
     );
     info!("Serving directory: {:?}", dir);
 
+    let watched = match watch {
+        Some((dirs, preview_deploy)) => {
+            let roots = [
+                dirs.content_dir.clone(),
+                dirs.template_dir.clone(),
+                dirs.static_dir.clone(),
+            ];
+            let nucleus = create_pipeline_with(dirs, true, |config| {
+                match preview_deploy {
+                    Some(target) => config.with_preview_deploy(target),
+                    None => config,
+                }
+            })?;
+            let ignore_rules = IgnoreRules::load(".").context(
+                format!("Failed to load {}", IGNORE_FILE_NAME),
+            )?;
+            let watcher = SourceWatcher::new(&roots)
+                .with_ignore_rules(ignore_rules);
+            rebuild(&nucleus);
+            Some((nucleus, watcher))
+        }
+        None => None,
+    };
+
     if !dir.exists() {
         return Err(anyhow::anyhow!(
             "Directory does not exist: {:?}",
@@ -275,10 +990,53 @@ fn handle_serve(port: u16, watch: bool, dir: PathBuf) -> Result<()> {
         ));
     }
 
-    // Implement development server logic here
-    // This is a placeholder for now
-    info!("Development server functionality not yet implemented");
-    Ok(())
+    let listener = TcpListener::bind(("127.0.0.1", port))?;
+    info!("Listening on http://127.0.0.1:{}/", port);
+    let server = DevServer::new(&dir);
+    let (nucleus, mut watcher) = match watched {
+        Some(watched) => watched,
+        None => {
+            server.serve(&listener)?;
+            return Ok(());
+        }
+    };
+
+    // Serve from another thread, polling for changes until the
+    // server fails.
+    let (failed, failure) = mpsc::channel();
+    _ = thread::spawn(move || _ = failed.send(server.serve(&listener)));
+    let interval = Duration::from_millis(WATCH_INTERVAL_MS);
+    loop {
+        match failure.recv_timeout(interval) {
+            Ok(served) => return Ok(served?),
+            Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            Err(RecvTimeoutError::Timeout) => {}
+        }
+        let changed = watcher.poll();
+        if !changed.is_empty() {
+            info!("{} source files changed, rebuilding", changed.len());
+            rebuild(&nucleus);
+        }
+    }
+}
+
+/// Rebuilds a watched site, logging rather than returning failures so
+/// the server keeps running until the sources are fixed.
+fn rebuild(nucleus: &NucleusFlow) {
+    match nucleus.rebuild() {
+        Ok((result, pushed)) if pushed.is_empty() => {
+            info!("Site rebuilt! {}", result)
+        }
+        Ok((result, pushed)) => info!(
+            "Site rebuilt! {}; pushed {} changed files",
+            result,
+            pushed.len()
+        ),
+        Err(error) => {
+            eprint!("{}", error.render_diagnostic());
+            warn!("Rebuild failed [{}]", error.code());
+        }
+    }
 }
 
 fn main() {
@@ -291,20 +1049,71 @@ fn main() {
     let result = match cli.command {
         Commands::New { name, template } => handle_new(&name, &template),
         Commands::Build {
-            content_dir,
-            output_dir,
-            template_dir,
+            dirs,
             minify,
             config,
-        } => handle_build(
-            content_dir,
-            output_dir,
-            template_dir,
-            minify,
+            dry_run,
+            keep_going,
+        } => handle_build(dirs, minify, config, dry_run, keep_going),
+        Commands::Graph { dirs, dot, files } => {
+            handle_graph(dirs, dot, &files)
+        }
+        Commands::Check {
+            dirs,
+            required,
+            a11y,
+            annotations,
             config,
+            external,
+        } => handle_check(
+            dirs,
+            required,
+            a11y,
+            annotations,
+            config,
+            external,
         ),
-        Commands::Serve { port, watch, dir } => {
-            handle_serve(port, watch, dir)
+        Commands::Compare { old, new } => handle_compare(&old, &new),
+        Commands::Extract { dirs, format, out } => {
+            handle_extract(dirs, format, out)
+        }
+        Commands::Config { action } => handle_config(action),
+        Commands::Template { action } => handle_template(action),
+        Commands::Test { golden, update } => handle_test(&golden, update),
+        Commands::Import {
+            feed,
+            content_dir,
+            images_dir,
+            images_url,
+            no_images,
+        } => handle_import(
+            &feed,
+            &content_dir,
+            &images_dir,
+            &images_url,
+            no_images,
+        ),
+        Commands::Serve {
+            port,
+            watch,
+            dir,
+            content_dir,
+            template_dir,
+            static_dir,
+            preview_deploy,
+        } => {
+            let watch = if watch {
+                let dirs = SiteDirs {
+                    content_dir,
+                    output_dir: dir.clone(),
+                    template_dir,
+                    static_dir,
+                };
+                Some((dirs, preview_deploy))
+            } else {
+                None
+            };
+            handle_serve(port, dir, watch)
         }
     };
 
@@ -327,9 +1136,9 @@ mod tests {
     /// Initialize the logger for tests
     fn init_test_logger() {
         INIT.call_once(|| {
-            env_logger::Builder::new()
-                .filter_level(log::LevelFilter::Debug)
-                .is_test(true)
+            tracing_subscriber::fmt()
+                .with_max_level(LevelFilter::DEBUG)
+                .with_test_writer()
                 .init();
         });
     }
@@ -391,6 +1200,10 @@ fn test_project_creation() -> Result<()> {
             "Config file does not contain expected template setting"
         );
 
+        // Verify the default ignore file is picked up
+        let rules = IgnoreRules::load(&project_path)?;
+        assert!(rules.is_ignored(Path::new("content/_drafts/post.md"), false));
+
         Ok(())
     }
 
@@ -398,19 +1211,14 @@ fn test_project_creation() -> Result<()> {
     fn test_logging_setup() {
         // Test verbosity levels mapping without actual initialization
         let test_cases = [
-            (0, log::LevelFilter::Warn),
-            (1, log::LevelFilter::Info),
-            (2, log::LevelFilter::Debug),
-            (3, log::LevelFilter::Trace),
+            (0, LevelFilter::WARN),
+            (1, LevelFilter::INFO),
+            (2, LevelFilter::DEBUG),
+            (3, LevelFilter::TRACE),
         ];
 
         for (verbosity, expected_level) in test_cases {
-            let level = match verbosity {
-                0 => log::LevelFilter::Warn,
-                1 => log::LevelFilter::Info,
-                2 => log::LevelFilter::Debug,
-                _ => log::LevelFilter::Trace,
-            };
+            let level = log_level(verbosity);
             assert_eq!(level, expected_level, "Incorrect log level for verbosity {}", verbosity);
         }
     }
@@ -0,0 +1,960 @@
+//! # Plugin API
+//!
+//! Plugins extend NucleusFlow with template helpers and content
+//! shortcodes. Each plugin registers its extensions through a
+//! [`PluginContext`], which places them in the plugin's own namespace:
+//! a `gallery` plugin registering a `grid` shortcode exposes it as
+//! `gallery:grid`, so two plugins can use the same names without
+//! colliding.
+//!
+//! Shortcodes are expanded in content before it is processed:
+//!
+//! ```text
+//! {{< gallery:image src="/img/cat.jpg" alt="A cat" >}}
+//!
+//! {{< notice:box kind="warning" >}}
+//! Paired shortcodes receive the text between their tags.
+//! {{< /notice:box >}}
+//! ```
+//!
+//! Template helpers are called from Handlebars templates by their
+//! namespaced name, e.g. `{{gallery:count images}}`, once the renderer is
+//! created with `HandlebarsRenderer::with_plugins`.
+//!
+//! Plugins can be switched off in configuration without removing them
+//! from the build:
+//!
+//! ```toml
+//! [plugins.gallery]
+//! enabled = false
+//! ```
+//!
+//! Any other keys in a plugin's table are its own options. A plugin that
+//! declares a [`PluginSchema`] has them checked before it is registered:
+//! unknown keys, missing required options and values of the wrong type
+//! are reported with the line of the configuration file they come from,
+//! and defaults are filled in. The plugin reads the result as a typed
+//! struct through [`PluginContext::config`].
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::core::error::Result;
+//! use nucleusflow::plugin::{
+//!     Plugin, PluginContext, PluginRegistry, Shortcode,
+//! };
+//! use std::collections::BTreeMap;
+//!
+//! #[derive(Debug)]
+//! struct Greeting;
+//!
+//! impl Shortcode for Greeting {
+//!     fn render(
+//!         &self,
+//!         args: &BTreeMap<String, String>,
+//!         _body: Option<&str>,
+//!     ) -> Result<String> {
+//!         Ok(format!("Hello, {}!", args["name"]))
+//!     }
+//! }
+//!
+//! #[derive(Debug)]
+//! struct GreetingPlugin;
+//!
+//! impl Plugin for GreetingPlugin {
+//!     fn name(&self) -> &str {
+//!         "greet"
+//!     }
+//!
+//!     fn register(&self, context: &mut PluginContext) -> Result<()> {
+//!         context.shortcode("hello", Greeting)
+//!     }
+//! }
+//!
+//! let mut plugins = PluginRegistry::new();
+//! plugins.register(&GreetingPlugin).unwrap();
+//!
+//! let content = r#"{{< greet:hello name="World" >}}"#;
+//! assert_eq!(plugins.expand_shortcodes(content).unwrap(), "Hello, World!");
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::{self, Debug};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde::de::DeserializeOwned;
+use toml::Value as TomlValue;
+
+use crate::core::config::PluginConfig;
+use crate::template::TemplateHelper;
+use crate::{ProcessingError, Result};
+
+/// Separator between a plugin's namespace and an extension's name.
+pub const NAMESPACE_SEPARATOR: char = ':';
+
+const SHORTCODE_OPEN: &str = "{{<";
+const SHORTCODE_CLOSE: &str = ">}}";
+
+/// An extension that registers template helpers and shortcodes.
+pub trait Plugin: Send + Sync + Debug {
+    /// Returns the plugin's name, used as its namespace and as the key
+    /// of its `[plugins.<name>]` configuration table.
+    fn name(&self) -> &str;
+
+    /// Returns the schema the plugin's options are validated against.
+    ///
+    /// Plugins without a schema receive their options unchecked.
+    fn schema(&self) -> Option<PluginSchema> {
+        None
+    }
+
+    /// Registers the plugin's helpers and shortcodes.
+    fn register(&self, context: &mut PluginContext) -> Result<()>;
+}
+
+/// The type of value a plugin option accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptionKind {
+    /// A string
+    String,
+    /// An integer
+    Integer,
+    /// A floating point number; integers are accepted too
+    Float,
+    /// `true` or `false`
+    Boolean,
+    /// An array of any values
+    Array,
+    /// A nested table
+    Table,
+}
+
+impl OptionKind {
+    /// Returns `true` if `value` is of this kind.
+    fn matches(self, value: &TomlValue) -> bool {
+        matches!(
+            (self, value),
+            (OptionKind::String, TomlValue::String(_))
+                | (OptionKind::Integer, TomlValue::Integer(_))
+                | (OptionKind::Float, TomlValue::Float(_))
+                | (OptionKind::Float, TomlValue::Integer(_))
+                | (OptionKind::Boolean, TomlValue::Boolean(_))
+                | (OptionKind::Array, TomlValue::Array(_))
+                | (OptionKind::Table, TomlValue::Table(_))
+        )
+    }
+}
+
+impl fmt::Display for OptionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            OptionKind::String => "a string",
+            OptionKind::Integer => "an integer",
+            OptionKind::Float => "a number",
+            OptionKind::Boolean => "a boolean",
+            OptionKind::Array => "an array",
+            OptionKind::Table => "a table",
+        })
+    }
+}
+
+/// A single option declared by a [`PluginSchema`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct OptionSpec {
+    /// The type of value the option accepts
+    pub kind: OptionKind,
+
+    /// The value used when the option is not set, or `None` if it must
+    /// be set
+    pub default: Option<TomlValue>,
+
+    /// A short description of the option
+    pub description: String,
+}
+
+/// The options a plugin accepts in its `[plugins.<name>]` table.
+///
+/// ```rust
+/// use nucleusflow::plugin::{OptionKind, PluginSchema};
+///
+/// let schema = PluginSchema::new()
+///     .required("album", OptionKind::String, "The album to show")
+///     .optional("columns", OptionKind::Integer, 3, "Images per row");
+/// assert_eq!(schema.options().count(), 2);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PluginSchema {
+    options: BTreeMap<String, OptionSpec>,
+}
+
+impl PluginSchema {
+    /// Creates a schema that accepts no options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares an option that must be set.
+    pub fn required<S: Into<String>>(
+        mut self,
+        name: &str,
+        kind: OptionKind,
+        description: S,
+    ) -> Self {
+        _ = self.options.insert(
+            name.to_string(),
+            OptionSpec {
+                kind,
+                default: None,
+                description: description.into(),
+            },
+        );
+        self
+    }
+
+    /// Declares an option with a default value.
+    pub fn optional<V, S>(
+        mut self,
+        name: &str,
+        kind: OptionKind,
+        default: V,
+        description: S,
+    ) -> Self
+    where
+        V: Into<TomlValue>,
+        S: Into<String>,
+    {
+        _ = self.options.insert(
+            name.to_string(),
+            OptionSpec {
+                kind,
+                default: Some(default.into()),
+                description: description.into(),
+            },
+        );
+        self
+    }
+
+    /// Returns the declared options by name.
+    pub fn options(
+        &self,
+    ) -> impl Iterator<Item = (&String, &OptionSpec)> {
+        self.options.iter()
+    }
+
+    /// Checks a plugin's options and fills in defaults.
+    ///
+    /// Returns the offending key, if any, with the error message.
+    fn check(
+        &self,
+        options: &HashMap<String, TomlValue>,
+    ) -> std::result::Result<
+        BTreeMap<String, TomlValue>,
+        (Option<String>, String),
+    > {
+        let mut unknown: Vec<_> = options
+            .keys()
+            .filter(|key| !self.options.contains_key(*key))
+            .collect();
+        unknown.sort();
+        if let Some(key) = unknown.first() {
+            let expected: Vec<_> =
+                self.options.keys().map(String::as_str).collect();
+            return Err((
+                Some(key.to_string()),
+                format!(
+                    "Unknown option '{}' (expected one of: {})",
+                    key,
+                    if expected.is_empty() {
+                        "none".to_string()
+                    } else {
+                        expected.join(", ")
+                    }
+                ),
+            ));
+        }
+
+        let mut checked = BTreeMap::new();
+        for (name, spec) in &self.options {
+            let value = match (options.get(name), &spec.default) {
+                (Some(value), _) => value,
+                (None, Some(default)) => default,
+                (None, None) => {
+                    return Err((
+                        None,
+                        format!("Missing required option '{}'", name),
+                    ))
+                }
+            };
+            if !spec.kind.matches(value) {
+                return Err((
+                    Some(name.clone()),
+                    format!(
+                        "Option '{}' must be {}, found {}",
+                        name,
+                        spec.kind,
+                        value.type_str()
+                    ),
+                ));
+            }
+            _ = checked.insert(name.clone(), value.clone());
+        }
+        Ok(checked)
+    }
+}
+
+/// A named fragment expanded into content, with optional arguments and
+/// body.
+pub trait Shortcode: Send + Sync + Debug {
+    /// Renders the shortcode.
+    ///
+    /// # Arguments
+    /// * `args` - The `key="value"` arguments from the opening tag.
+    /// * `body` - The content between paired tags, with any nested
+    ///   shortcodes already expanded, or `None` for a single tag.
+    fn render(
+        &self,
+        args: &BTreeMap<String, String>,
+        body: Option<&str>,
+    ) -> Result<String>;
+}
+
+/// The extensions registered by every enabled plugin.
+#[derive(Default)]
+pub struct PluginRegistry {
+    config: HashMap<String, PluginConfig>,
+    config_path: Option<PathBuf>,
+    plugins: BTreeSet<String>,
+    helpers: BTreeMap<String, Arc<dyn TemplateHelper>>,
+    shortcodes: BTreeMap<String, Arc<dyn Shortcode>>,
+}
+
+impl Debug for PluginRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PluginRegistry")
+            .field("plugins", &self.plugins)
+            .field("helpers", &self.helpers.keys())
+            .field("shortcodes", &self.shortcodes.keys())
+            .finish()
+    }
+}
+
+impl PluginRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the per-plugin configuration used to decide which plugins
+    /// are enabled.
+    pub fn with_config(
+        mut self,
+        config: HashMap<String, PluginConfig>,
+    ) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Sets the file the plugin configuration was read from, so option
+    /// errors can point at the offending line.
+    pub fn with_config_path<P: Into<PathBuf>>(
+        mut self,
+        path: P,
+    ) -> Self {
+        self.config_path = Some(path.into());
+        self
+    }
+
+    /// Registers a plugin's helpers and shortcodes.
+    ///
+    /// The plugin's options are checked against its schema, if it has
+    /// one, before it is handed them.
+    ///
+    /// # Returns
+    /// * `Result<bool>` - `false` if the plugin is disabled in
+    ///   configuration and nothing was registered, or an error if the
+    ///   plugin's name is invalid or already registered, or its options
+    ///   do not match its schema.
+    pub fn register(&mut self, plugin: &dyn Plugin) -> Result<bool> {
+        let name = plugin.name();
+        validate_name(name, "plugin")?;
+        if self.plugins.contains(name) {
+            return Err(ProcessingError::validation(
+                format!("Plugin '{}' is registered twice", name),
+                None::<String>,
+            ));
+        }
+        let config = self.config.get(name);
+        if config.map_or(false, |config| !config.enabled) {
+            tracing::debug!("Plugin '{}' is disabled", name);
+            return Ok(false);
+        }
+
+        let options = config.map(|config| &config.options);
+        let options = match (plugin.schema(), options) {
+            (Some(schema), options) => schema
+                .check(options.unwrap_or(&HashMap::new()))
+                .map_err(|(key, details)| {
+                    self.option_error(name, key.as_deref(), details)
+                })?,
+            (None, Some(options)) => options
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+            (None, None) => BTreeMap::new(),
+        };
+
+        let mut context = PluginContext {
+            namespace: name.to_string(),
+            options,
+            helpers: BTreeMap::new(),
+            shortcodes: BTreeMap::new(),
+        };
+        plugin.register(&mut context)?;
+
+        _ = self.plugins.insert(name.to_string());
+        self.helpers.extend(context.helpers);
+        self.shortcodes.extend(context.shortcodes);
+        Ok(true)
+    }
+
+    /// Builds a configuration error for a plugin option, located in the
+    /// configuration file when it is known.
+    fn option_error(
+        &self,
+        plugin: &str,
+        key: Option<&str>,
+        details: String,
+    ) -> ProcessingError {
+        let table = format!("[plugins.{}]", plugin);
+        let location = self.config_path.as_ref().map(|path| {
+            let line = fs::read_to_string(path)
+                .ok()
+                .and_then(|source| find_line(&source, &table, key));
+            match line {
+                Some(line) => format!("{}:{}", path.display(), line),
+                None => path.display().to_string(),
+            }
+        });
+        ProcessingError::configuration(
+            match location {
+                Some(location) => {
+                    format!("{} in {} at {}", details, table, location)
+                }
+                None => format!("{} in {}", details, table),
+            },
+            self.config_path.clone(),
+            None,
+        )
+    }
+
+    /// Returns `true` if a plugin with the given name is registered and
+    /// enabled.
+    pub fn is_registered(&self, name: &str) -> bool {
+        self.plugins.contains(name)
+    }
+
+    /// Returns the registered helpers by namespaced name.
+    pub fn helpers(
+        &self,
+    ) -> impl Iterator<Item = (&String, &Arc<dyn TemplateHelper>)> {
+        self.helpers.iter()
+    }
+
+    /// Returns the namespaced names of the registered shortcodes.
+    pub fn shortcode_names(&self) -> impl Iterator<Item = &String> {
+        self.shortcodes.keys()
+    }
+
+    /// Expands every shortcode in `content`.
+    ///
+    /// A paired shortcode's body ends at the first matching closing tag,
+    /// so a shortcode cannot be nested inside itself.
+    ///
+    /// # Returns
+    /// * `Result<String>` - The expanded content, or a validation error
+    ///   for unknown, unclosed or malformed shortcodes.
+    pub fn expand_shortcodes(&self, content: &str) -> Result<String> {
+        let mut output = String::with_capacity(content.len());
+        let mut rest = content;
+        while let Some(start) = rest.find(SHORTCODE_OPEN) {
+            output.push_str(&rest[..start]);
+            let after = &rest[start + SHORTCODE_OPEN.len()..];
+            let end = after.find(SHORTCODE_CLOSE).ok_or_else(|| {
+                ProcessingError::validation(
+                    "Unclosed shortcode tag",
+                    Some(truncate(&rest[start..])),
+                )
+            })?;
+            let (name, args) = parse_tag(&after[..end])?;
+            rest = &after[end + SHORTCODE_CLOSE.len()..];
+
+            let closing = format!(
+                "{} /{} {}",
+                SHORTCODE_OPEN, name, SHORTCODE_CLOSE
+            );
+            let body = match rest.find(&closing) {
+                Some(index) => {
+                    let body =
+                        self.expand_shortcodes(&rest[..index])?;
+                    rest = &rest[index + closing.len()..];
+                    Some(body)
+                }
+                None => None,
+            };
+
+            let shortcode =
+                self.shortcodes.get(&name).ok_or_else(|| {
+                    ProcessingError::validation(
+                        format!("Unknown shortcode '{}'", name),
+                        Some("check that the plugin providing it is enabled"),
+                    )
+                })?;
+            output.push_str(&shortcode.render(&args, body.as_deref())?);
+        }
+        output.push_str(rest);
+        Ok(output)
+    }
+}
+
+/// The registration interface handed to [`Plugin::register`].
+///
+/// Names registered here are prefixed with the plugin's namespace.
+pub struct PluginContext {
+    namespace: String,
+    options: BTreeMap<String, TomlValue>,
+    helpers: BTreeMap<String, Arc<dyn TemplateHelper>>,
+    shortcodes: BTreeMap<String, Arc<dyn Shortcode>>,
+}
+
+impl Debug for PluginContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PluginContext")
+            .field("namespace", &self.namespace)
+            .finish()
+    }
+}
+
+impl PluginContext {
+    /// Returns the namespace the plugin's extensions are registered in.
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// Returns the plugin's options, with schema defaults filled in.
+    pub fn options(&self) -> &BTreeMap<String, TomlValue> {
+        &self.options
+    }
+
+    /// Deserialises the plugin's options into a typed configuration.
+    ///
+    /// # Returns
+    /// * `Result<T>` - The configuration, or an error if the options do
+    ///   not fit `T`.
+    pub fn config<T: DeserializeOwned>(&self) -> Result<T> {
+        let table: toml::Table = self
+            .options
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        TomlValue::Table(table).try_into().map_err(|e| {
+            ProcessingError::configuration(
+                format!(
+                    "Invalid configuration for plugin '{}': {}",
+                    self.namespace, e
+                ),
+                None,
+                None,
+            )
+        })
+    }
+
+    /// Registers a template helper as `namespace:name`.
+    pub fn helper<H>(&mut self, name: &str, helper: H) -> Result<()>
+    where
+        H: TemplateHelper + 'static,
+    {
+        let name = self.qualify(name, "helper")?;
+        if self.helpers.contains_key(&name) {
+            return Err(duplicate("helper", &name));
+        }
+        _ = self.helpers.insert(name, Arc::new(helper));
+        Ok(())
+    }
+
+    /// Registers a shortcode as `namespace:name`.
+    pub fn shortcode<S>(
+        &mut self,
+        name: &str,
+        shortcode: S,
+    ) -> Result<()>
+    where
+        S: Shortcode + 'static,
+    {
+        let name = self.qualify(name, "shortcode")?;
+        if self.shortcodes.contains_key(&name) {
+            return Err(duplicate("shortcode", &name));
+        }
+        _ = self.shortcodes.insert(name, Arc::new(shortcode));
+        Ok(())
+    }
+
+    fn qualify(&self, name: &str, kind: &str) -> Result<String> {
+        validate_name(name, kind)?;
+        Ok(format!("{}{}{}", self.namespace, NAMESPACE_SEPARATOR, name))
+    }
+}
+
+/// Checks that a plugin, helper or shortcode name is a lowercase
+/// identifier.
+fn validate_name(name: &str, kind: &str) -> Result<()> {
+    let valid_chars = |c: char| {
+        c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_'
+    };
+    if name.is_empty()
+        || !name.starts_with(|c: char| c.is_ascii_lowercase())
+        || !name.chars().all(valid_chars)
+    {
+        return Err(ProcessingError::validation(
+            format!("Invalid {} name '{}'", kind, name),
+            Some("names must be lowercase letters, digits and underscores"),
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the 1-based line of `key` within `table` in a TOML source,
+/// or of the table header when the key is not found.
+fn find_line(
+    source: &str,
+    table: &str,
+    key: Option<&str>,
+) -> Option<usize> {
+    let mut header = None;
+    for (index, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            if header.is_some() {
+                break;
+            }
+            if line == table {
+                header = Some(index + 1);
+            }
+            continue;
+        }
+        let line_key = line.split('=').next().map(str::trim);
+        if header.is_some() && key.is_some() && line_key == key {
+            return Some(index + 1);
+        }
+    }
+    header
+}
+
+fn duplicate(kind: &str, name: &str) -> ProcessingError {
+    ProcessingError::validation(
+        format!("The {} '{}' is registered twice", kind, name),
+        None::<String>,
+    )
+}
+
+/// Parses the inside of an opening tag into a name and its arguments.
+fn parse_tag(tag: &str) -> Result<(String, BTreeMap<String, String>)> {
+    let invalid = |details: String| {
+        ProcessingError::validation(details, Some(truncate(tag)))
+    };
+
+    let tag = tag.trim();
+    let (name, mut rest) = match tag.find(char::is_whitespace) {
+        Some(index) => (&tag[..index], tag[index..].trim_start()),
+        None => (tag, ""),
+    };
+    if name.starts_with('/') {
+        return Err(invalid(format!(
+            "Closing shortcode tag '{}' has no opening tag",
+            name
+        )));
+    }
+    if !name.contains(NAMESPACE_SEPARATOR) {
+        return Err(invalid(format!(
+            "Shortcode '{}' must be namespaced as plugin{}name",
+            name, NAMESPACE_SEPARATOR
+        )));
+    }
+
+    let mut args = BTreeMap::new();
+    while !rest.is_empty() {
+        let (key, value) = match rest.split_once("=\"") {
+            Some(pair) => pair,
+            None => {
+                return Err(invalid(format!(
+                "Shortcode '{}' arguments must be key=\"value\" pairs",
+                name
+            )))
+            }
+        };
+        let end = value.find('"').ok_or_else(|| {
+            invalid(format!("Unterminated argument in '{}'", name))
+        })?;
+        _ = args
+            .insert(key.trim().to_string(), value[..end].to_string());
+        rest = value[end + 1..].trim_start();
+    }
+    Ok((name.to_string(), args))
+}
+
+/// Shortens text quoted in error messages.
+fn truncate(text: &str) -> String {
+    text.chars().take(60).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::Config;
+    use serde_json::Value as JsonValue;
+    use std::path::Path;
+
+    #[derive(Debug)]
+    struct Wrap;
+
+    impl Shortcode for Wrap {
+        fn render(
+            &self,
+            args: &BTreeMap<String, String>,
+            body: Option<&str>,
+        ) -> Result<String> {
+            let tag = args.get("tag").map_or("div", String::as_str);
+            Ok(format!("<{0}>{1}</{0}>", tag, body.unwrap_or_default()))
+        }
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    struct Shout;
+
+    impl TemplateHelper for Shout {
+        fn execute(
+            &self,
+            params: &[JsonValue],
+            _context: &JsonValue,
+        ) -> Result<JsonValue> {
+            Ok(JsonValue::String(format!(
+                "{}!",
+                params[0].as_str().unwrap_or_default()
+            )))
+        }
+
+        fn name(&self) -> &str {
+            "shout"
+        }
+    }
+
+    #[derive(Debug)]
+    struct TestPlugin(&'static str);
+
+    impl Plugin for TestPlugin {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn register(&self, context: &mut PluginContext) -> Result<()> {
+            context.shortcode("wrap", Wrap)?;
+            context.helper("shout", Shout)
+        }
+    }
+
+    #[test]
+    fn test_namespacing() {
+        let mut plugins = PluginRegistry::new();
+        assert!(plugins.register(&TestPlugin("one")).unwrap());
+        assert!(plugins.register(&TestPlugin("two")).unwrap());
+        assert!(plugins.register(&TestPlugin("one")).is_err());
+        assert!(plugins.register(&TestPlugin("Bad-Name")).is_err());
+
+        let names: Vec<_> = plugins.shortcode_names().collect();
+        assert_eq!(names, ["one:wrap", "two:wrap"]);
+        let helpers: Vec<_> =
+            plugins.helpers().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(helpers, ["one:shout", "two:shout"]);
+    }
+
+    #[test]
+    fn test_disabled_plugin() {
+        let mut config = HashMap::new();
+        _ = config.insert(
+            "one".to_string(),
+            PluginConfig {
+                enabled: false,
+                ..PluginConfig::default()
+            },
+        );
+        let mut plugins = PluginRegistry::new().with_config(config);
+
+        assert!(!plugins.register(&TestPlugin("one")).unwrap());
+        assert!(!plugins.is_registered("one"));
+        assert!(plugins.expand_shortcodes("{{< one:wrap >}}").is_err());
+    }
+
+    #[test]
+    fn test_expand_shortcodes() {
+        let mut plugins = PluginRegistry::new();
+        _ = plugins.register(&TestPlugin("ui")).unwrap();
+
+        assert_eq!(
+            plugins
+                .expand_shortcodes(
+                    r#"a {{< ui:wrap tag="p" >}}b {{< ui:wrap tag="em" >}} c{{< /ui:wrap >}} d {{< ui:wrap >}}"#
+                )
+                .unwrap(),
+            "a <p>b <em></em> c</p> d <div></div>"
+        );
+
+        for invalid in [
+            "{{< ui:wrap",
+            "{{< /ui:wrap >}}",
+            "{{< wrap >}}",
+            "{{< ui:wrap tag=p >}}",
+            "{{< ui:missing >}}",
+        ] {
+            assert!(
+                plugins.expand_shortcodes(invalid).is_err(),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    struct GalleryConfig {
+        album: String,
+        columns: i64,
+    }
+
+    #[derive(Debug)]
+    struct Gallery;
+
+    impl Plugin for Gallery {
+        fn name(&self) -> &str {
+            "gallery"
+        }
+
+        fn schema(&self) -> Option<PluginSchema> {
+            Some(
+                PluginSchema::new()
+                    .required("album", OptionKind::String, "Album")
+                    .optional(
+                        "columns",
+                        OptionKind::Integer,
+                        3,
+                        "Columns",
+                    ),
+            )
+        }
+
+        fn register(&self, context: &mut PluginContext) -> Result<()> {
+            let config: GalleryConfig = context.config()?;
+            assert_eq!(config.album, "cats");
+            assert_eq!(config.columns, 3);
+            Ok(())
+        }
+    }
+
+    fn register_gallery(path: &Path, source: &str) -> Result<bool> {
+        fs::write(path, source).unwrap();
+        let config: Config = toml::from_str(source).unwrap();
+        PluginRegistry::new()
+            .with_config(config.plugins)
+            .with_config_path(path)
+            .register(&Gallery)
+    }
+
+    #[test]
+    fn test_plugin_schema() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.toml");
+        assert!(register_gallery(
+            &path,
+            "[plugins.gallery]\nalbum = \"cats\"\n"
+        )
+        .unwrap());
+
+        let cases = [
+            (
+                "[plugins.gallery]\nalbum = \"cats\"\ncolumns = \"4\"\n",
+                "Option 'columns' must be an integer, found string",
+            ),
+            (
+                "title = \"x\"\n\n[plugins.gallery]\ncolumns = 4\n",
+                "Missing required option 'album'",
+            ),
+            (
+                "[plugins.gallery]\nalbum = \"cats\"\nsize = 2\n",
+                "Unknown option 'size' (expected one of: album, columns)",
+            ),
+        ];
+        for (source, expected) in cases {
+            let error = register_gallery(&path, source).unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                format!(
+                    "Configuration error: {} in [plugins.gallery] at {}:3",
+                    expected,
+                    path.display()
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn test_unchecked_options() {
+        #[derive(Debug)]
+        struct Echo;
+
+        impl Plugin for Echo {
+            fn name(&self) -> &str {
+                "echo"
+            }
+
+            fn register(
+                &self,
+                context: &mut PluginContext,
+            ) -> Result<()> {
+                assert_eq!(
+                    context.options().get("anything"),
+                    Some(&TomlValue::Integer(1))
+                );
+                Ok(())
+            }
+        }
+
+        let config: Config =
+            toml::from_str("[plugins.echo]\nanything = 1\n").unwrap();
+        assert!(PluginRegistry::new()
+            .with_config(config.plugins)
+            .register(&Echo)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_renderer_helpers() {
+        use crate::template::HandlebarsRenderer;
+        use crate::TemplateRenderer;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("page.hbs"),
+            "{{one:shout title}}",
+        )
+        .unwrap();
+        let mut plugins = PluginRegistry::new();
+        _ = plugins.register(&TestPlugin("one")).unwrap();
+
+        let renderer =
+            HandlebarsRenderer::with_plugins(temp_dir.path(), &plugins)
+                .unwrap();
+        let html = renderer
+            .render("page", &serde_json::json!({ "title": "hi" }))
+            .unwrap();
+        assert_eq!(html, "hi!");
+    }
+}
@@ -0,0 +1,274 @@
+//! # Markdown Chunking
+//!
+//! Splits long Markdown documents into chunks that render to the same
+//! HTML separately as they do together, so book-length pages can be
+//! parsed in parallel when the `parallel-markdown` feature is enabled.
+//!
+//! A document is only split before an ATX heading (`# Title`) that
+//! starts a line after a blank line, outside frontmatter, fenced code
+//! and multi-line HTML blocks. Nothing open before such a heading can
+//! continue past it, so each chunk parses on its own. Documents that use
+//! link reference definitions or footnotes are never split, since those
+//! are resolved across the whole document.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::processors::chunks::ChunkConfig;
+//!
+//! let config = ChunkConfig {
+//!     min_document_bytes: 0,
+//!     chunk_bytes: 10,
+//! };
+//! let chunks = config.split("# One\n\nFirst.\n\n# Two\n\nSecond.\n");
+//! assert_eq!(chunks, ["# One\n\nFirst.\n\n", "# Two\n\nSecond.\n"]);
+//! ```
+
+/// How long documents are split into chunks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkConfig {
+    /// Documents shorter than this many bytes are not split
+    pub min_document_bytes: usize,
+    /// The size a chunk grows to before it is ended at the next
+    /// boundary
+    pub chunk_bytes: usize,
+}
+
+impl Default for ChunkConfig {
+    fn default() -> Self {
+        Self {
+            min_document_bytes: 256 * 1024,
+            chunk_bytes: 64 * 1024,
+        }
+    }
+}
+
+impl ChunkConfig {
+    /// Splits a document into chunks at top-level block boundaries.
+    ///
+    /// # Arguments
+    /// * `content` - The Markdown document, including any frontmatter.
+    ///
+    /// # Returns
+    /// * `Vec<&str>` - The chunks, in order, which concatenate back to
+    ///   `content`; a single chunk if the document is short or cannot
+    ///   be split safely.
+    pub fn split<'a>(&self, content: &'a str) -> Vec<&'a str> {
+        if content.len() < self.min_document_bytes
+            || has_document_references(content)
+        {
+            return vec![content];
+        }
+
+        let mut chunks = Vec::new();
+        let mut start = 0;
+        for boundary in block_boundaries(content) {
+            if boundary - start >= self.chunk_bytes {
+                chunks.push(&content[start..boundary]);
+                start = boundary;
+            }
+        }
+        chunks.push(&content[start..]);
+        chunks
+    }
+}
+
+/// Returns `true` if the document has link reference definitions or
+/// footnotes, which tie its blocks together.
+fn has_document_references(content: &str) -> bool {
+    content.contains("[^")
+        || content.lines().any(|line| {
+            let line = line.trim_start();
+            line.starts_with('[')
+                && line.find("]:").map_or(false, |end| end > 1)
+        })
+}
+
+/// Returns the byte offsets of the lines a document can be split
+/// before.
+fn block_boundaries(content: &str) -> Vec<usize> {
+    let mut boundaries = Vec::new();
+    let mut offset = 0;
+    let mut previous_blank = false;
+    let mut open: Option<Closer> = None;
+    let mut lines = content.split_inclusive('\n');
+
+    if content.starts_with("---\n") {
+        open = Some(Closer::Frontmatter);
+        offset += lines.next().map_or(0, str::len);
+    }
+
+    for line in lines {
+        let start = offset;
+        offset += line.len();
+        let text = line.trim_end_matches(['\n', '\r']);
+
+        if let Some(closer) = &open {
+            if closer.closes(text) {
+                open = None;
+            }
+            previous_blank = false;
+            continue;
+        }
+
+        if previous_blank && is_atx_heading(text) {
+            boundaries.push(start);
+        }
+        open = Closer::opened_by(text);
+        previous_blank = text.trim().is_empty();
+    }
+    boundaries
+}
+
+/// Returns `true` if a line is an ATX heading starting in the first
+/// column.
+fn is_atx_heading(line: &str) -> bool {
+    let hashes = line.bytes().take_while(|&b| b == b'#').count();
+    (1..=6).contains(&hashes)
+        && line[hashes..].chars().next().map_or(true, |c| c == ' ')
+}
+
+/// The starts of HTML blocks that can span blank lines, and the text
+/// that ends each, lowercased.
+const HTML_BLOCKS: &[(&str, &str)] = &[
+    ("<script", "</script>"),
+    ("<pre", "</pre>"),
+    ("<style", "</style>"),
+    ("<textarea", "</textarea>"),
+    ("<!--", "-->"),
+    ("<?", "?>"),
+    ("<![cdata[", "]]>"),
+    ("<!", ">"),
+];
+
+/// A block that can span blank lines, and what ends it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Closer {
+    /// Frontmatter, ended by a `---` line
+    Frontmatter,
+    /// Fenced code, ended by a fence of the same character at least as
+    /// long
+    Fence(char, usize),
+    /// An HTML block, ended by a line containing the text
+    Html(&'static str),
+}
+
+impl Closer {
+    /// Returns the block a line opens, if it opens one that can span
+    /// blank lines.
+    fn opened_by(line: &str) -> Option<Self> {
+        let line = line.trim_start();
+        if let Some((fence, length)) = fence(line) {
+            return Some(Closer::Fence(fence, length));
+        }
+
+        let lower = line.to_ascii_lowercase();
+        let (rest, end) =
+            HTML_BLOCKS.iter().find_map(|(start, end)| {
+                let rest = lower.strip_prefix(start)?;
+                let is_tag =
+                    start.ends_with(|c: char| c.is_alphabetic());
+                let at_name_end = rest
+                    .chars()
+                    .next()
+                    .map_or(true, |c| c == '>' || c.is_whitespace());
+                (!is_tag || at_name_end).then(|| (rest, *end))
+            })?;
+        if rest.contains(end) {
+            return None;
+        }
+        Some(Closer::Html(end))
+    }
+
+    /// Returns `true` if a line ends the block.
+    fn closes(&self, line: &str) -> bool {
+        match self {
+            Closer::Frontmatter => line == "---",
+            Closer::Fence(open, open_length) => {
+                let line = line.trim();
+                fence(line).map_or(false, |(fence, length)| {
+                    fence == *open
+                        && length >= *open_length
+                        && line.trim_start_matches(fence).is_empty()
+                })
+            }
+            Closer::Html(end) => {
+                line.to_ascii_lowercase().contains(end)
+            }
+        }
+    }
+}
+
+/// Returns the character and length of a code fence opening a line.
+fn fence(line: &str) -> Option<(char, usize)> {
+    let first =
+        line.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let length = line.chars().take_while(|c| *c == first).count();
+    (length >= 3).then(|| (first, length))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(content: &str) -> Vec<&str> {
+        ChunkConfig {
+            min_document_bytes: 0,
+            chunk_bytes: 1,
+        }
+        .split(content)
+    }
+
+    #[test]
+    fn test_split_at_headings() {
+        let content = "# A\n\nText\n\n## B\n\n- item\n\n# C\n";
+        let chunks = split(content);
+        assert_eq!(
+            chunks,
+            ["# A\n\nText\n\n", "## B\n\n- item\n\n", "# C\n"]
+        );
+        assert_eq!(chunks.concat(), content);
+
+        assert_eq!(split("# A\nText\n# B\n").len(), 1);
+        assert_eq!(split("# A\n\n#hashtag\n").len(), 1);
+    }
+
+    #[test]
+    fn test_no_split_inside_blocks() {
+        for content in [
+            "---\ntitle: A\n\n# comment\n---\n# A\n",
+            "```\n\n# not a heading\n```\n",
+            "~~~~\n\n# x\n~~~\n\n# y\n~~~~\n",
+            "<!--\n\n# x\n-->\n",
+            "<pre>\n\n# x\n</pre>\n",
+        ] {
+            assert_eq!(split(content), [content], "{}", content);
+        }
+        assert_eq!(split("```\ncode\n```\n\n# A\n").len(), 2);
+        assert_eq!(split("<!-- done -->\n\n# A\n").len(), 2);
+    }
+
+    #[test]
+    fn test_no_split_with_references() {
+        assert_eq!(split("[a]\n\n# A\n\n[a]: /a\n").len(), 1);
+        assert_eq!(split("Note[^1]\n\n# A\n\n[^1]: x\n").len(), 1);
+    }
+
+    #[test]
+    fn test_chunk_sizes() {
+        let content = "# A\n\nOne\n\n# B\n\nTwo\n\n# C\n\nThree\n";
+        let config = ChunkConfig {
+            min_document_bytes: 0,
+            chunk_bytes: 15,
+        };
+        assert_eq!(
+            config.split(content),
+            ["# A\n\nOne\n\n# B\n\nTwo\n\n", "# C\n\nThree\n"]
+        );
+        let config = ChunkConfig {
+            min_document_bytes: content.len() + 1,
+            chunk_bytes: 1,
+        };
+        assert_eq!(config.split(content), [content]);
+    }
+}
@@ -0,0 +1,684 @@
+//! # Citations
+//!
+//! Resolves citations of the entries of a BibTeX bibliography, set by
+//! [`CitationConfig`], and lists the entries a page cites in a
+//! references section at its end:
+//!
+//! - `[@knuth84]` cites an entry by its key,
+//! - `[@knuth84, p. 12]` adds a locator, and
+//! - `[@knuth84; @lamport94]` cites several entries at once.
+//!
+//! With the `author-year` style, citations read `(Knuth, 1984)` and
+//! references are ordered by author; with the `numeric` style, they
+//! read `[1]` and references are numbered in the order they are first
+//! cited. Each citation links to its reference, whose `id` is `ref-`
+//! followed by the entry's key.
+//!
+//! Citations are resolved in each page's processed HTML. Text inside
+//! `<code>` and `<pre>` elements is left alone. A key missing from the
+//! bibliography fails the page citing it.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::core::config::CitationConfig;
+//! use nucleusflow::processors::citations::{parse_bibtex, Citations};
+//!
+//! let bib = r#"@article{knuth84,
+//!     author = {Knuth, Donald E.},
+//!     title = {Literate Programming},
+//!     journal = {The Computer Journal},
+//!     year = 1984,
+//! }"#;
+//! let entries = parse_bibtex(bib).unwrap();
+//! let citations = Citations::new(CitationConfig::default(), entries);
+//!
+//! let html = citations.render("<p>As shown [@knuth84].</p>").unwrap();
+//! assert!(html.starts_with(
+//!     "<p>As shown <span class=\"citation\">\
+//!      (<a href=\"#ref-knuth84\">Knuth, 1984</a>)</span>.</p>"
+//! ));
+//! assert!(html
+//!     .contains("<li id=\"ref-knuth84\">Knuth, Donald E. (1984)."));
+//! assert!(citations.render("<p>[@missing]</p>").is_err());
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+
+use crate::core::config::{CitationConfig, CitationStyle};
+use crate::processors::markdown::escape_html;
+use crate::{ProcessingError, Result};
+
+/// An entry of a BibTeX bibliography.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BibEntry {
+    /// The entry's type, lowercased, such as `article` or `book`
+    pub kind: String,
+
+    /// The key the entry is cited by
+    pub key: String,
+
+    /// The entry's fields, keyed by lowercased name, with braces
+    /// removed from their values
+    pub fields: BTreeMap<String, String>,
+}
+
+impl BibEntry {
+    /// Returns the value of a field, if the entry sets it.
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .get(name)
+            .map(String::as_str)
+            .filter(|value| !value.is_empty())
+    }
+
+    /// Returns the entry's authors, or its editors if it has none, as
+    /// `(given names, family name)` pairs.
+    fn names(&self) -> Vec<(String, String)> {
+        self.field("author")
+            .or_else(|| self.field("editor"))
+            .map(|names| {
+                names
+                    .split(" and ")
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .map(|name| match name.split_once(',') {
+                        Some((family, given)) => (
+                            given.trim().to_string(),
+                            family.to_string(),
+                        ),
+                        None => match name.rsplit_once(' ') {
+                            Some((given, family)) => {
+                                (given.to_string(), family.to_string())
+                            }
+                            None => (String::new(), name.to_string()),
+                        },
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns the entry's year, or `n.d.` if it has none.
+    fn year(&self) -> &str {
+        self.field("year").unwrap_or("n.d.")
+    }
+
+    /// Returns where the entry was published, if known.
+    fn container(&self) -> Option<&str> {
+        self.field("journal")
+            .or_else(|| self.field("booktitle"))
+            .or_else(|| self.field("publisher"))
+    }
+}
+
+/// Parses the entries of a BibTeX file, skipping `@comment`,
+/// `@preamble` and `@string` blocks.
+///
+/// # Returns
+/// * `Result<Vec<BibEntry>>` - The entries, in file order, or a content
+///   processing error if an entry is malformed.
+pub fn parse_bibtex(text: &str) -> Result<Vec<BibEntry>> {
+    let mut parser = BibParser {
+        text,
+        pos: 0,
+        last_len: 0,
+    };
+    let mut entries = Vec::new();
+    while let Some(at) = parser.text[parser.pos..].find('@') {
+        parser.pos += at + 1;
+        let kind = parser.take_while(|c| c.is_alphanumeric());
+        let kind = kind.to_lowercase();
+        parser.skip_whitespace();
+        let close = match parser.bump() {
+            Some('{') => '}',
+            Some('(') => ')',
+            _ => continue,
+        };
+        if matches!(kind.as_str(), "comment" | "preamble" | "string") {
+            parser.pos -= 1;
+            _ = parser.balanced()?;
+            continue;
+        }
+
+        parser.skip_whitespace();
+        let key = parser
+            .take_while(|c| {
+                c != ',' && c != close && !c.is_whitespace()
+            })
+            .to_string();
+        if key.is_empty() {
+            return Err(parser.error("expected a citation key"));
+        }
+        let mut entry = BibEntry {
+            kind,
+            key,
+            fields: BTreeMap::new(),
+        };
+        loop {
+            parser.skip_whitespace();
+            match parser.bump() {
+                Some(',') => continue,
+                Some(c) if c == close => break,
+                Some(_) => parser.pos -= parser.last_len,
+                None => return Err(parser.error("unterminated entry")),
+            }
+            let name = parser
+                .take_while(|c| c != '=' && c != close && c != ',')
+                .trim()
+                .to_lowercase();
+            if parser.bump() != Some('=') || name.is_empty() {
+                return Err(parser.error("expected a field"));
+            }
+            let value = parser.value(close)?;
+            _ = entry.fields.insert(name, value);
+        }
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// A cursor over a BibTeX file.
+struct BibParser<'a> {
+    text: &'a str,
+    pos: usize,
+    last_len: usize,
+}
+
+impl<'a> BibParser<'a> {
+    fn bump(&mut self) -> Option<char> {
+        let c = self.text[self.pos..].chars().next()?;
+        self.last_len = c.len_utf8();
+        self.pos += self.last_len;
+        Some(c)
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let start = self.pos;
+        let rest = &self.text[start..];
+        let len = rest.find(|c| !f(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &self.text[start..self.pos]
+    }
+
+    fn skip_whitespace(&mut self) {
+        _ = self.take_while(char::is_whitespace);
+    }
+
+    /// Reads a `{...}`, `(...)` or `"..."` group, with any braces
+    /// nested in it, returning its contents.
+    fn balanced(&mut self) -> Result<&'a str> {
+        let close = match self.bump() {
+            Some('{') => '}',
+            Some('(') => ')',
+            _ => '"',
+        };
+        let start = self.pos;
+        let mut depth = 0usize;
+        while let Some(c) = self.bump() {
+            if c == close && depth == 0 {
+                return Ok(&self.text[start..self.pos - 1]);
+            }
+            match c {
+                '{' => depth += 1,
+                '}' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        Err(self.error("unbalanced braces"))
+    }
+
+    /// Reads a field's value: braced or quoted groups and bare words,
+    /// joined with `#`.
+    fn value(&mut self, close: char) -> Result<String> {
+        let mut value = String::new();
+        loop {
+            self.skip_whitespace();
+            match self.text[self.pos..].chars().next() {
+                Some('{' | '"') => value.push_str(self.balanced()?),
+                Some(_) => value.push_str(self.take_while(|c| {
+                    c.is_alphanumeric() || "-_.:/+".contains(c)
+                })),
+                None => return Err(self.error("unterminated field")),
+            }
+            self.skip_whitespace();
+            match self.text[self.pos..].chars().next() {
+                Some('#') => {
+                    _ = self.bump();
+                }
+                Some(c) if c == ',' || c == close => break,
+                _ => {
+                    return Err(self.error("expected ',' after a field"))
+                }
+            }
+        }
+        let value = value.replace(['{', '}'], "").replace("\\&", "&");
+        Ok(value.split_whitespace().collect::<Vec<_>>().join(" "))
+    }
+
+    fn error(&self, details: &str) -> ProcessingError {
+        ProcessingError::content_processing(
+            format!("Invalid BibTeX at byte {}: {}", self.pos, details),
+            None,
+        )
+    }
+}
+
+/// The entries of a bibliography, and how citations of them are
+/// written.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Citations {
+    config: CitationConfig,
+    entries: BTreeMap<String, BibEntry>,
+}
+
+impl Citations {
+    /// Creates the citations of a bibliography's entries.
+    pub fn new(config: CitationConfig, entries: Vec<BibEntry>) -> Self {
+        let entries = entries
+            .into_iter()
+            .map(|entry| (entry.key.clone(), entry))
+            .collect();
+        Self { config, entries }
+    }
+
+    /// Reads the entries of the configured bibliography.
+    ///
+    /// # Returns
+    /// * `Result<Self>` - The citations, or an error if the file cannot
+    ///   be read or is not valid BibTeX.
+    pub fn read(config: CitationConfig) -> Result<Self> {
+        let text =
+            fs::read_to_string(&config.bibliography).map_err(|e| {
+                ProcessingError::io_error(
+                    config.bibliography.clone(),
+                    e,
+                )
+            })?;
+        let entries = parse_bibtex(&text)?;
+        Ok(Self::new(config, entries))
+    }
+
+    /// Replaces the citations in some HTML with links to their
+    /// references, listed in a section appended to it.
+    ///
+    /// # Arguments
+    /// * `html` - A page's processed content.
+    ///
+    /// # Returns
+    /// * `Result<String>` - The HTML with every citation replaced, or a
+    ///   validation error naming the keys missing from the
+    ///   bibliography.
+    pub fn render(&self, html: &str) -> Result<String> {
+        let mut output = String::with_capacity(html.len());
+        let mut cited: Vec<&BibEntry> = Vec::new();
+        let mut missing: Vec<&str> = Vec::new();
+        let mut code_depth = 0usize;
+        let mut rest = html;
+
+        while let Some(next) = rest.find(['<', '[']) {
+            output.push_str(&rest[..next]);
+            rest = &rest[next..];
+
+            if rest.starts_with('<') {
+                let end = rest.find('>').map_or(rest.len(), |i| i + 1);
+                let tag = rest[1..end].trim_end_matches('>');
+                let (closing, name) = match tag.strip_prefix('/') {
+                    Some(name) => (true, name),
+                    None => (false, tag),
+                };
+                let name: String = name
+                    .chars()
+                    .take_while(char::is_ascii_alphanumeric)
+                    .collect();
+                if name.eq_ignore_ascii_case("code")
+                    || name.eq_ignore_ascii_case("pre")
+                {
+                    code_depth = if closing {
+                        code_depth.saturating_sub(1)
+                    } else {
+                        code_depth + 1
+                    };
+                }
+                output.push_str(&rest[..end]);
+                rest = &rest[end..];
+                continue;
+            }
+
+            let group = rest
+                .strip_prefix("[@")
+                .filter(|_| code_depth == 0)
+                .and_then(|inner| {
+                    inner.find(']').map(|end| &inner[..end])
+                })
+                .filter(|group| !group.contains(['<', '[', '\n']));
+            let group = match group {
+                Some(group) => group,
+                None => {
+                    output.push('[');
+                    rest = &rest[1..];
+                    continue;
+                }
+            };
+            rest = &rest[group.len() + 3..];
+
+            let mut links = Vec::new();
+            for (index, citation) in group.split(';').enumerate() {
+                let citation = citation.trim();
+                let citation = if index == 0 {
+                    citation
+                } else {
+                    citation.strip_prefix('@').unwrap_or(citation)
+                };
+                let (key, locator) = match citation.split_once(',') {
+                    Some((key, locator)) => {
+                        (key.trim(), locator.trim())
+                    }
+                    None => (citation, ""),
+                };
+                let entry = match self.entries.get(key) {
+                    Some(entry) => entry,
+                    None => {
+                        missing.push(key);
+                        continue;
+                    }
+                };
+                if !cited.iter().any(|cited| cited.key == entry.key) {
+                    cited.push(entry);
+                }
+                let label = match self.config.style {
+                    CitationStyle::AuthorYear => {
+                        format!(
+                            "{}, {}",
+                            short_names(entry),
+                            entry.year()
+                        )
+                    }
+                    CitationStyle::Numeric => {
+                        let number = cited
+                            .iter()
+                            .position(|cited| cited.key == entry.key)
+                            .map_or(0, |index| index + 1);
+                        number.to_string()
+                    }
+                };
+                let mut link = format!(
+                    "<a href=\"#ref-{}\">{}</a>",
+                    escape_html(key),
+                    escape_html(&label)
+                );
+                if !locator.is_empty() {
+                    link.push_str(", ");
+                    link.push_str(locator);
+                }
+                links.push(link);
+            }
+            let (open, close) = match self.config.style {
+                CitationStyle::AuthorYear => ('(', ')'),
+                CitationStyle::Numeric => ('[', ']'),
+            };
+            output.push_str(&format!(
+                "<span class=\"citation\">{}{}{}</span>",
+                open,
+                links.join("; "),
+                close
+            ));
+        }
+        output.push_str(rest);
+
+        if !missing.is_empty() {
+            return Err(ProcessingError::validation(
+                format!(
+                    "Citation keys missing from {}: {}",
+                    self.config.bibliography.display(),
+                    missing.join(", ")
+                ),
+                None::<String>,
+            ));
+        }
+        if !cited.is_empty() {
+            output.push_str(&self.references(cited));
+        }
+        Ok(output)
+    }
+
+    /// Returns the references section listing the cited entries.
+    fn references(&self, mut cited: Vec<&BibEntry>) -> String {
+        let list = match self.config.style {
+            CitationStyle::AuthorYear => {
+                cited.sort_by(|a, b| {
+                    short_names(a)
+                        .cmp(&short_names(b))
+                        .then_with(|| a.year().cmp(b.year()))
+                        .then_with(|| a.key.cmp(&b.key))
+                });
+                "ul"
+            }
+            CitationStyle::Numeric => "ol",
+        };
+        let mut section = format!(
+            "\n<section class=\"references\">\n<h2>{}</h2>\n<{}>\n",
+            escape_html(&self.config.title),
+            list
+        );
+        for entry in cited {
+            section.push_str(&format!(
+                "<li id=\"ref-{}\">{}</li>\n",
+                escape_html(&entry.key),
+                self.reference(entry)
+            ));
+        }
+        section.push_str(&format!("</{}>\n</section>\n", list));
+        section
+    }
+
+    /// Returns an entry's reference, as HTML.
+    fn reference(&self, entry: &BibEntry) -> String {
+        let names = entry.names();
+        let title = entry.field("title").unwrap_or(&entry.key);
+        let container = entry.container().map(|container| {
+            format!("<em>{}</em>", escape_html(container))
+        });
+        let text = match self.config.style {
+            CitationStyle::AuthorYear => {
+                let authors: Vec<String> = names
+                    .iter()
+                    .map(|(given, family)| {
+                        if given.is_empty() {
+                            family.clone()
+                        } else {
+                            format!("{}, {}", family, given)
+                        }
+                    })
+                    .collect();
+                let mut text = if authors.is_empty() {
+                    format!(
+                        "{} ({}).",
+                        escape_html(title),
+                        escape_html(entry.year())
+                    )
+                } else {
+                    format!(
+                        "{} ({}). {}.",
+                        escape_html(&join_names(&authors)),
+                        escape_html(entry.year()),
+                        escape_html(title)
+                    )
+                };
+                if let Some(container) = container {
+                    text.push_str(&format!(" {}.", container));
+                }
+                text
+            }
+            CitationStyle::Numeric => {
+                let authors: Vec<String> = names
+                    .iter()
+                    .map(|(given, family)| {
+                        format!("{} {}", given, family)
+                            .trim()
+                            .to_string()
+                    })
+                    .collect();
+                let mut parts = vec![format!(
+                    "&ldquo;{},&rdquo;",
+                    escape_html(title)
+                )];
+                if !authors.is_empty() {
+                    parts.insert(
+                        0,
+                        format!(
+                            "{},",
+                            escape_html(&join_names(&authors))
+                        ),
+                    );
+                }
+                parts.extend(container.map(|c| format!("{},", c)));
+                parts.push(format!("{}.", escape_html(entry.year())));
+                parts.join(" ")
+            }
+        };
+        text
+    }
+}
+
+/// Returns the family names an entry is cited by: one, two joined by
+/// `and`, or the first followed by `et al.`
+fn short_names(entry: &BibEntry) -> String {
+    let names = entry.names();
+    match names.as_slice() {
+        [] => entry.field("title").unwrap_or(&entry.key).to_string(),
+        [(_, one)] => one.clone(),
+        [(_, one), (_, two)] => format!("{} and {}", one, two),
+        [(_, first), ..] => format!("{} et al.", first),
+    }
+}
+
+/// Joins names with commas, and `and` before the last.
+fn join_names(names: &[String]) -> String {
+    match names {
+        [] => String::new(),
+        [one] => one.clone(),
+        [rest @ .., last] => {
+            format!("{} and {}", rest.join(", "), last)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BIB: &str = r#"
+        @comment{Sample references}
+        @string{tcj = "The Computer Journal"}
+
+        @article{knuth84,
+            author = {Knuth, Donald E.},
+            title = {Literate {P}rogramming},
+            journal = "The Computer " # "Journal",
+            year = 1984,
+        }
+
+        @book(lamport94,
+            author = {Leslie Lamport and Donald Knuth and Others},
+            title = {\LaTeX: A Document Preparation System},
+            publisher = {Addison-Wesley},
+            year = {1994}
+        )
+    "#;
+
+    fn citations(style: CitationStyle) -> Citations {
+        Citations::new(
+            CitationConfig {
+                style,
+                ..CitationConfig::default()
+            },
+            parse_bibtex(BIB).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_parse_bibtex() {
+        let entries = parse_bibtex(BIB).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].kind, "article");
+        assert_eq!(entries[0].key, "knuth84");
+        assert_eq!(
+            entries[0].field("title"),
+            Some("Literate Programming")
+        );
+        assert_eq!(
+            entries[0].field("journal"),
+            Some("The Computer Journal")
+        );
+        assert_eq!(entries[0].field("year"), Some("1984"));
+        assert_eq!(entries[1].kind, "book");
+        assert_eq!(entries[1].field("year"), Some("1994"));
+
+        assert!(parse_bibtex("@article{key, title = {Open")
+            .unwrap_err()
+            .to_string()
+            .contains("Invalid BibTeX"));
+        assert!(parse_bibtex("@article{, title = {T}}").is_err());
+    }
+
+    #[test]
+    fn test_render_author_year() {
+        let html = citations(CitationStyle::AuthorYear)
+            .render(
+                "<p>See [@lamport94; @knuth84, p. 12] and [x].</p>\
+                 <pre><code>[@missing]</code></pre>",
+            )
+            .unwrap();
+        assert_eq!(
+            html,
+            "<p>See <span class=\"citation\">\
+             (<a href=\"#ref-lamport94\">Lamport et al., 1994</a>; \
+             <a href=\"#ref-knuth84\">Knuth, 1984</a>, p. 12)</span> \
+             and [x].</p><pre><code>[@missing]</code></pre>\n\
+             <section class=\"references\">\n\
+             <h2>References</h2>\n<ul>\n\
+             <li id=\"ref-knuth84\">Knuth, Donald E. (1984). Literate \
+             Programming. <em>The Computer Journal</em>.</li>\n\
+             <li id=\"ref-lamport94\">Lamport, Leslie, Knuth, Donald \
+             and Others (1994). \\LaTeX: A Document Preparation \
+             System. <em>Addison-Wesley</em>.</li>\n\
+             </ul>\n</section>\n"
+        );
+    }
+
+    #[test]
+    fn test_render_numeric() {
+        let citations = citations(CitationStyle::Numeric);
+        let html = citations
+            .render("<p>[@lamport94] [@knuth84] [@lamport94]</p>")
+            .unwrap();
+        assert!(html.starts_with(
+            "<p><span class=\"citation\">\
+             [<a href=\"#ref-lamport94\">1</a>]</span> \
+             <span class=\"citation\">\
+             [<a href=\"#ref-knuth84\">2</a>]</span> \
+             <span class=\"citation\">\
+             [<a href=\"#ref-lamport94\">1</a>]</span></p>"
+        ));
+        assert!(html.contains(
+            "<ol>\n<li id=\"ref-lamport94\">Leslie Lamport, Donald \
+             Knuth and Others, &ldquo;\\LaTeX: A Document Preparation \
+             System,&rdquo; <em>Addison-Wesley</em>, 1994.</li>\n\
+             <li id=\"ref-knuth84\">Donald E. Knuth, &ldquo;Literate \
+             Programming,&rdquo; <em>The Computer Journal</em>, \
+             1984.</li>\n</ol>"
+        ));
+
+        assert_eq!(
+            citations.render("<p>No citations</p>").unwrap(),
+            "<p>No citations</p>"
+        );
+        assert!(citations
+            .render("<p>[@knuth84; @nope] [@gone]</p>")
+            .unwrap_err()
+            .to_string()
+            .contains("data/references.bib: nope, gone"));
+    }
+}
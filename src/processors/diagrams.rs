@@ -0,0 +1,223 @@
+//! # Diagrams
+//!
+//! Fenced code blocks written in a diagram language, such as
+//! ` ```mermaid ` or ` ```plantuml `, are rendered as diagrams rather
+//! than as code.
+//!
+//! By default a diagram is wrapped for client-side rendering as
+//! `<pre class="mermaid">`, the element Mermaid's and similar scripts
+//! look for. A diagram language can instead be given a command that
+//! renders it to SVG during the build: the command reads the diagram's
+//! source on stdin and prints the SVG on stdout. It is run directly
+//! rather than through a shell. A command is killed once it runs for
+//! 30 seconds, or prints more than 16 MiB. A diagram whose command
+//! fails is wrapped for client-side rendering with a warning, so one
+//! bad diagram does not fail the build.
+//!
+//! Any fence language given a configuration is a diagram language, so
+//! other tools, such as Graphviz, can be added alongside the built-in
+//! ones.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::processors::diagrams::{Diagram, DiagramConfig};
+//!
+//! let client = DiagramConfig::default();
+//! assert_eq!(
+//!     client.render("mermaid", "graph TD; A-->B"),
+//!     Diagram::Client(
+//!         "<pre class=\"mermaid\">graph TD; A--&gt;B</pre>\n".to_string()
+//!     )
+//! );
+//!
+//! let plantuml = DiagramConfig {
+//!     command: vec!["plantuml".into(), "-tsvg".into(), "-pipe".into()],
+//! };
+//! // Renders the diagram to SVG with PlantUML, if it is installed
+//! let _diagram = plantuml.render("plantuml", "@startuml\nA -> B\n@enduml");
+//! ```
+
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::processors::exec::run_piped;
+use crate::{ProcessingError, Result};
+
+/// How long a diagram command may run before it is killed.
+const DIAGRAM_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The size of the largest SVG a diagram command may print, in bytes.
+const MAX_SVG_BYTES: usize = 16 * 1024 * 1024;
+
+/// Fence languages rendered as diagrams without any configuration.
+pub const DIAGRAM_LANGUAGES: [&str; 2] = ["mermaid", "plantuml"];
+
+/// How diagrams of one language are rendered.
+///
+/// ```toml
+/// [diagrams.plantuml]
+/// command = ["plantuml", "-tsvg", "-pipe"]
+/// ```
+#[derive(
+    Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(deny_unknown_fields)]
+pub struct DiagramConfig {
+    /// The program and arguments that render a diagram from stdin to
+    /// SVG on stdout; diagrams are rendered client-side if empty
+    #[serde(default)]
+    pub command: Vec<String>,
+}
+
+/// A rendered diagram.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Diagram {
+    /// The diagram's source, wrapped for client-side rendering
+    Client(String),
+    /// SVG rendered during the build
+    Svg(String),
+}
+
+impl DiagramConfig {
+    /// Renders a diagram: to SVG with the configured command, or
+    /// wrapped for client-side rendering.
+    ///
+    /// # Arguments
+    /// * `language` - The diagram's fence language, such as `mermaid`.
+    /// * `source` - The diagram's source.
+    ///
+    /// # Returns
+    /// * `Diagram` - The SVG, or the wrapped source if there is no
+    ///   command or it fails.
+    pub fn render(&self, language: &str, source: &str) -> Diagram {
+        if self.command.is_empty() {
+            return Diagram::Client(client_html(language, source));
+        }
+        match self.run(source) {
+            Ok(svg) => Diagram::Svg(svg),
+            Err(e) => {
+                tracing::warn!(
+                    error = %e,
+                    "Rendering the {} diagram client-side",
+                    language
+                );
+                Diagram::Client(client_html(language, source))
+            }
+        }
+    }
+
+    /// Runs the command on a diagram's source.
+    ///
+    /// # Returns
+    /// * `Result<String>` - The SVG printed, or an error if the command
+    ///   cannot be run, exits unsuccessfully or prints no SVG.
+    fn run(&self, source: &str) -> Result<String> {
+        let program = &self.command[0];
+        let failed = |details: String| {
+            ProcessingError::content_processing(
+                format!("Diagram command '{}' {}", program, details),
+                None,
+            )
+        };
+
+        let mut command = Command::new(program);
+        _ = command
+            .args(&self.command[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let stdout = run_piped(
+            &mut command,
+            source,
+            DIAGRAM_TIMEOUT,
+            MAX_SVG_BYTES,
+        )
+        .map_err(failed)?;
+
+        let stdout = String::from_utf8_lossy(&stdout);
+        match stdout.find("<svg") {
+            Some(start) => Ok(stdout[start..].trim_end().to_string()),
+            None => Err(failed("printed no SVG".to_string())),
+        }
+    }
+}
+
+/// Returns a diagram's source wrapped for client-side rendering.
+fn client_html(language: &str, source: &str) -> String {
+    let source = source
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+    format!("<pre class=\"{}\">{}</pre>\n", language, source.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_diagram() {
+        assert_eq!(
+            DiagramConfig::default().render("plantuml", "A -> B <b>\n"),
+            Diagram::Client(
+                "<pre class=\"plantuml\">A -&gt; B &lt;b&gt;</pre>\n"
+                    .to_string()
+            )
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_diagram() {
+        let svg = DiagramConfig {
+            command: vec![
+                "sh".to_string(),
+                "-c".to_string(),
+                "echo '<?xml?>'; printf '<svg>%s</svg>\\n' \"$(cat)\""
+                    .to_string(),
+            ],
+        };
+        assert_eq!(
+            svg.render("mermaid", "A-->B"),
+            Diagram::Svg("<svg>A-->B</svg>".to_string())
+        );
+
+        let failing = DiagramConfig {
+            command: vec!["false".to_string()],
+        };
+        assert!(matches!(
+            failing.render("mermaid", "A-->B"),
+            Diagram::Client(_)
+        ));
+        let missing = DiagramConfig {
+            command: vec!["nucleusflow-missing-renderer".to_string()],
+        };
+        assert!(matches!(
+            missing.render("mermaid", "A-->B"),
+            Diagram::Client(_)
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_printing_before_reading() {
+        // Prints more than a pipe holds before reading a diagram that
+        // is larger than a pipe holds too
+        let eager = DiagramConfig {
+            command: vec![
+                "sh".to_string(),
+                "-c".to_string(),
+                "head -c 200000 /dev/zero; echo '<svg/>'; \
+                 cat >/dev/null"
+                    .to_string(),
+            ],
+        };
+        assert_eq!(
+            eager.render("mermaid", &"A-->B\n".repeat(50_000)),
+            Diagram::Svg("<svg/>".to_string())
+        );
+    }
+}
@@ -0,0 +1,393 @@
+//! # External Command Processor
+//!
+//! Pipes content through an external program, such as Pandoc or a
+//! custom script, so content can be converted without writing Rust.
+//! The program reads a page's content on stdin and prints the processed
+//! content on stdout. It is run directly rather than through a shell.
+//!
+//! # Limits
+//!
+//! A program is killed, and the page fails, if it runs longer than its
+//! timeout, which is 30 seconds by default. Content larger than the
+//! input limit is not passed to the program, and a program printing
+//! more than the output limit is stopped; both limits are 16 MiB by
+//! default. A program that exits unsuccessfully, or prints invalid
+//! UTF-8, fails the page with what it printed to stderr.
+//!
+//! # Environment
+//!
+//! The program does not inherit the build's environment, which may
+//! hold secrets such as deployment tokens. It sees only the variables
+//! it is given and those it inherits by name, which are `PATH`, `HOME`,
+//! `LANG`, `TMPDIR` and, for Windows, `SYSTEMROOT` by default.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use nucleusflow::processors::exec::ExecProcessor;
+//! use nucleusflow::ContentProcessor;
+//! use std::time::Duration;
+//!
+//! // Converts Markdown to HTML with Pandoc, if it is installed
+//! let pandoc = ExecProcessor::new("pandoc")
+//!     .with_args(["--from", "markdown", "--to", "html"])
+//!     .with_timeout(Duration::from_secs(10));
+//! let html = pandoc.process("# Hello", None).unwrap();
+//! assert!(html.contains("<h1"));
+//! ```
+
+use std::collections::BTreeMap;
+use std::io::{self, Read, Write};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{ContentProcessor, ProcessingError, Result};
+
+/// The environment variables a program inherits by default.
+pub const DEFAULT_INHERITED_ENV: [&str; 5] =
+    ["PATH", "HOME", "LANG", "TMPDIR", "SYSTEMROOT"];
+
+/// The default size limit of a program's input and output, in bytes.
+const DEFAULT_MAX_BYTES: usize = 16 * 1024 * 1024;
+
+/// How often a running program is checked for having exited.
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// A content processor that pipes content through an external program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecProcessor {
+    program: String,
+    args: Vec<String>,
+    env: BTreeMap<String, String>,
+    inherited_env: Vec<String>,
+    timeout: Duration,
+    max_input: usize,
+    max_output: usize,
+}
+
+impl ExecProcessor {
+    /// Creates a processor running a program without arguments.
+    ///
+    /// # Arguments
+    /// * `program` - The program's name, looked up on `PATH`, or path.
+    pub fn new(program: impl Into<String>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+            env: BTreeMap::new(),
+            inherited_env: DEFAULT_INHERITED_ENV
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+            timeout: Duration::from_secs(30),
+            max_input: DEFAULT_MAX_BYTES,
+            max_output: DEFAULT_MAX_BYTES,
+        }
+    }
+
+    /// Sets the arguments passed to the program.
+    pub fn with_args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets an environment variable for the program.
+    pub fn with_env(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        _ = self.env.insert(name.into(), value.into());
+        self
+    }
+
+    /// Sets the environment variables the program inherits from the
+    /// build, replacing the defaults; no variable is inherited if
+    /// `names` is empty.
+    pub fn with_inherited_env<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.inherited_env =
+            names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets how long the program may run before it is killed.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the size of the largest content passed to the program, in
+    /// bytes.
+    pub fn with_max_input(mut self, bytes: usize) -> Self {
+        self.max_input = bytes;
+        self
+    }
+
+    /// Sets the size of the largest output the program may print, in
+    /// bytes.
+    pub fn with_max_output(mut self, bytes: usize) -> Self {
+        self.max_output = bytes;
+        self
+    }
+
+    /// Returns an error about the program.
+    fn failed(
+        &self,
+        details: impl std::fmt::Display,
+    ) -> ProcessingError {
+        ProcessingError::content_processing(
+            format!("Command '{}' {}", self.program, details),
+            None,
+        )
+    }
+
+    /// Builds the command, with only the configured environment.
+    fn command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        _ = command
+            .args(&self.args)
+            .env_clear()
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        for name in &self.inherited_env {
+            if let Some(value) = std::env::var_os(name) {
+                _ = command.env(name, value);
+            }
+        }
+        _ = command.envs(&self.env);
+        command
+    }
+}
+
+/// Runs a command, writing `input` to its stdin, and returns what it
+/// printed to stdout.
+///
+/// The input is written from a thread while the output is read, so a
+/// program printing before it has read all its input cannot deadlock.
+/// The program is killed once `timeout` passes, and stopped once it
+/// prints more than `max_output` bytes; it is always waited for.
+///
+/// # Arguments
+/// * `command` - The command, with its stdin, stdout and stderr piped.
+/// * `input` - What the program reads on stdin.
+/// * `timeout` - How long the program may run.
+/// * `max_output` - The size of the largest output, in bytes.
+///
+/// # Returns
+/// * `std::result::Result<Vec<u8>, String>` - The output, or why the
+///   program failed, for the caller to name the program in.
+pub(crate) fn run_piped(
+    command: &mut Command,
+    input: &str,
+    timeout: Duration,
+    max_output: usize,
+) -> std::result::Result<Vec<u8>, String> {
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("could not be run: {}", e))?;
+    let stdin = child.stdin.take();
+    let input = input.to_string();
+    let writer = thread::spawn(move || match stdin {
+        Some(mut stdin) => match stdin.write_all(input.as_bytes()) {
+            // A program may exit without reading all its input
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+            result => result,
+        },
+        None => Ok(()),
+    });
+    let stdout = read_limited(child.stdout.take(), max_output);
+    let stderr = read_limited(child.stderr.take(), max_output);
+
+    let status = wait(&mut child, timeout)?;
+    let joined = |handle: thread::JoinHandle<io::Result<Vec<u8>>>| {
+        handle
+            .join()
+            .unwrap_or_else(|_| Ok(Vec::new()))
+            .map_err(|e| format!("could not be read: {}", e))
+    };
+    let stdout = joined(stdout)?;
+    let stderr = joined(stderr)?;
+    if stdout.len() > max_output {
+        return Err(format!("printed more than {} bytes", max_output));
+    }
+    if !status.success() {
+        return Err(format!(
+            "failed with {}: {}",
+            status,
+            String::from_utf8_lossy(&stderr).trim()
+        ));
+    }
+    writer
+        .join()
+        .unwrap_or(Ok(()))
+        .map_err(|e| format!("could not be written: {}", e))?;
+    Ok(stdout)
+}
+
+/// Waits for a program to exit, killing it once the timeout passes.
+///
+/// # Returns
+/// * `std::result::Result<ExitStatus, String>` - How the program
+///   exited, or why it could not be waited for.
+fn wait(
+    child: &mut Child,
+    timeout: Duration,
+) -> std::result::Result<ExitStatus, String> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return Ok(status),
+            Ok(None) if Instant::now() < deadline => {
+                thread::sleep(POLL_INTERVAL);
+            }
+            Ok(None) => {
+                _ = child.kill();
+                _ = child.wait();
+                return Err(format!("timed out after {:?}", timeout));
+            }
+            Err(e) => {
+                _ = child.kill();
+                _ = child.wait();
+                return Err(format!("could not be waited for: {}", e));
+            }
+        }
+    }
+}
+
+/// Reads up to one byte more than `limit` from a pipe on a thread, and
+/// closes the pipe once the limit is passed so the program stops.
+fn read_limited<R: Read + Send + 'static>(
+    pipe: Option<R>,
+    limit: usize,
+) -> thread::JoinHandle<io::Result<Vec<u8>>> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        if let Some(pipe) = pipe {
+            _ = pipe.take(limit as u64 + 1).read_to_end(&mut bytes)?;
+        }
+        Ok(bytes)
+    })
+}
+
+impl ContentProcessor for ExecProcessor {
+    fn process(
+        &self,
+        content: &str,
+        _context: Option<&serde_json::Value>,
+    ) -> Result<String> {
+        self.validate(content)?;
+        let stdout = run_piped(
+            &mut self.command(),
+            content,
+            self.timeout,
+            self.max_output,
+        )
+        .map_err(|e| self.failed(e))?;
+        String::from_utf8(stdout)
+            .map_err(|_| self.failed("printed invalid UTF-8"))
+    }
+
+    fn validate(&self, content: &str) -> Result<()> {
+        if content.len() > self.max_input {
+            return Err(ProcessingError::validation(
+                format!(
+                    "Content of {} bytes exceeds the {} byte input \
+                     limit of command '{}'",
+                    content.len(),
+                    self.max_input,
+                    self.program
+                ),
+                None::<String>,
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sh(script: &str) -> ExecProcessor {
+        ExecProcessor::new("sh").with_args(["-c", script])
+    }
+
+    #[test]
+    fn test_input_limit() {
+        let processor = ExecProcessor::new("cat").with_max_input(4);
+        assert!(processor.validate("1234").is_ok());
+        assert!(processor
+            .process("12345", None)
+            .unwrap_err()
+            .to_string()
+            .contains("exceeds the 4 byte input limit"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_process() -> Result<()> {
+        assert_eq!(
+            sh("tr a-z A-Z").process("hello\n", None)?,
+            "HELLO\n"
+        );
+        assert_eq!(
+            sh("echo ignored").process("input", None)?,
+            "ignored\n"
+        );
+
+        let error = |processor: ExecProcessor| {
+            processor.process("input", None).unwrap_err().to_string()
+        };
+        assert!(error(sh("echo oops >&2; exit 3")).contains("oops"));
+        assert!(error(ExecProcessor::new(
+            "nucleusflow-missing-command"
+        ))
+        .contains("could not be run"));
+        assert!(error(sh("printf '\\377'")).contains("invalid UTF-8"));
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_limits() {
+        let started = Instant::now();
+        let slow =
+            sh("sleep 5").with_timeout(Duration::from_millis(100));
+        assert!(slow
+            .process("", None)
+            .unwrap_err()
+            .to_string()
+            .contains("timed out"));
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        let verbose = sh("yes").with_max_output(1024);
+        assert!(verbose
+            .process("", None)
+            .unwrap_err()
+            .to_string()
+            .contains("printed more than 1024 bytes"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_environment() -> Result<()> {
+        std::env::set_var("NUCLEUSFLOW_EXEC_SECRET", "secret");
+        let env = sh("echo \"$NUCLEUSFLOW_EXEC_SECRET$THEME\"")
+            .with_env("THEME", "dark");
+        assert_eq!(env.process("", None)?, "dark\n");
+        let inherited =
+            env.with_inherited_env(["NUCLEUSFLOW_EXEC_SECRET"]);
+        assert_eq!(inherited.process("", None)?, "secretdark\n");
+        Ok(())
+    }
+}
@@ -0,0 +1,520 @@
+//! # Frontmatter Defaults
+//!
+//! Applies the `[[frontmatter_defaults]]` rules of a site's configuration
+//! to its content files, so settings shared by a whole section, such as
+//! a template or table of contents for everything under `docs/`, do not
+//! need repeating in every file.
+//!
+//! Values shared by every page, such as an `author`, can be set in the
+//! `[content.frontmatter_defaults]` table instead, beneath the rules:
+//!
+//! ```toml
+//! [content.frontmatter_defaults]
+//! author = "Ada Lovelace"
+//! layout = "post"
+//!
+//! [[frontmatter_defaults]]
+//! path = "docs/**"
+//! values = { layout = "doc" }
+//! ```
+//!
+//! Content directories can also set defaults for every page beneath
+//! them, however deeply nested, with:
+//!
+//! - a `_defaults.toml` file, whose values cascade down, and
+//! - the `cascade` table of an `_index.md` page's frontmatter.
+//!
+//! Where both are present, the index page's values win, and a
+//! directory's values win over those of the directories above it and
+//! over the configured rules.
+//!
+//! Either file also makes its subdirectory a section, whose files are
+//! built as pages alongside the top-level files of the content
+//! directory; see [`is_section`]. Neither file is built as a page.
+//!
+//! Defaults are merged beneath a file's own frontmatter before the file
+//! is processed: keys the file sets win, and tables are merged key by
+//! key. Files matching no rule are left untouched.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::core::config::FrontmatterDefault;
+//! use nucleusflow::processors::frontmatter::FrontmatterDefaults;
+//! use std::path::Path;
+//!
+//! let defaults = FrontmatterDefaults::new(&[FrontmatterDefault {
+//!     path: "docs/**".to_string(),
+//!     values: toml::from_str("toc = true\ntemplate = \"doc\"").unwrap(),
+//! }])
+//! .unwrap();
+//!
+//! let content = "---\ntemplate: guide\n---\n# Install\n";
+//! let merged = defaults
+//!     .apply(Path::new("docs/install.md"), content)
+//!     .unwrap();
+//! assert!(merged.contains("template: guide"));
+//! assert!(merged.contains("toc: true"));
+//! assert!(merged.ends_with("---\n# Install\n"));
+//! ```
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use globset::{GlobBuilder, GlobMatcher};
+use serde_json::{Map, Value as JsonValue};
+
+use crate::core::config::{Config, FrontmatterDefault};
+use crate::{ProcessingError, Result};
+
+/// The file of a content directory whose values cascade to every page
+/// beneath it.
+pub const DEFAULTS_FILE_NAME: &str = "_defaults.toml";
+
+/// The page of a content directory whose `cascade` frontmatter applies
+/// to every page beneath it.
+pub const INDEX_FILE_NAME: &str = "_index.md";
+
+/// The frontmatter key of an index page's cascading values.
+pub const CASCADE_KEY: &str = "cascade";
+
+/// Compiled frontmatter default rules.
+#[derive(Debug, Clone, Default)]
+pub struct FrontmatterDefaults {
+    rules: Vec<(GlobMatcher, Map<String, JsonValue>)>,
+}
+
+impl FrontmatterDefaults {
+    /// Compiles frontmatter default rules, keeping their order.
+    ///
+    /// # Arguments
+    /// * `defaults` - The rules, as configured.
+    ///
+    /// # Returns
+    /// * `Result<Self>` - The compiled rules, or a configuration error if
+    ///   a pattern is invalid.
+    pub fn new(defaults: &[FrontmatterDefault]) -> Result<Self> {
+        let mut rules = Vec::with_capacity(defaults.len());
+        for default in defaults {
+            let glob = GlobBuilder::new(&default.path)
+                .literal_separator(true)
+                .build()
+                .map_err(|e| {
+                    ProcessingError::configuration(
+                        format!(
+                            "Invalid frontmatter default pattern '{}': {}",
+                            default.path, e
+                        ),
+                        None,
+                        None,
+                    )
+                })?;
+            let values = serde_json::to_value(&default.values)?;
+            let values = match values {
+                JsonValue::Object(values) => values,
+                _ => Map::new(),
+            };
+            rules.push((glob.compile_matcher(), values));
+        }
+        Ok(Self { rules })
+    }
+
+    /// Compiles the frontmatter defaults of a site's configuration: its
+    /// `[content.frontmatter_defaults]` values for every file, beneath
+    /// its `[[frontmatter_defaults]]` rules.
+    ///
+    /// # Returns
+    /// * `Result<Self>` - The compiled rules, or a configuration error if
+    ///   a pattern is invalid.
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let global = &config.content.frontmatter_defaults;
+        let rules: Vec<FrontmatterDefault> = (!global.is_empty())
+            .then(|| FrontmatterDefault {
+                path: "**".to_string(),
+                values: global.clone(),
+            })
+            .into_iter()
+            .chain(config.frontmatter_defaults.iter().cloned())
+            .collect();
+        Self::new(&rules)
+    }
+
+    /// Returns `true` if there are no rules.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Returns the defaults for a file, merged across every matching
+    /// rule in order.
+    ///
+    /// # Arguments
+    /// * `relative` - The file's path relative to the content directory.
+    pub fn defaults_for(
+        &self,
+        relative: &Path,
+    ) -> Map<String, JsonValue> {
+        let mut merged = Map::new();
+        for (glob, values) in &self.rules {
+            if glob.is_match(relative) {
+                merge_beneath(values.clone(), &mut merged);
+            }
+        }
+        merged
+    }
+
+    /// Returns the defaults for a file, with those of the content
+    /// directories above it merged over the matching rules'.
+    ///
+    /// # Arguments
+    /// * `content_dir` - The content directory.
+    /// * `relative` - The file's path relative to the content directory.
+    ///
+    /// # Returns
+    /// * `Result<Map<String, JsonValue>>` - The merged defaults, or an
+    ///   error if a directory's defaults cannot be read or parsed.
+    pub fn cascade_for(
+        &self,
+        content_dir: &Path,
+        relative: &Path,
+    ) -> Result<Map<String, JsonValue>> {
+        let mut merged = self.defaults_for(relative);
+        let mut dir = content_dir.to_path_buf();
+        merge_beneath(directory_defaults(&dir)?, &mut merged);
+        if let Some(parent) = relative.parent() {
+            for component in parent.components() {
+                dir.push(component);
+                merge_beneath(directory_defaults(&dir)?, &mut merged);
+            }
+        }
+        Ok(merged)
+    }
+
+    /// Merges a file's defaults beneath the frontmatter of its content.
+    ///
+    /// Content with no defaults, or with frontmatter that does not
+    /// parse, is returned unchanged so the processor reports the
+    /// problem. Content without frontmatter gains a block holding its
+    /// defaults.
+    ///
+    /// # Arguments
+    /// * `relative` - The file's path relative to the content directory.
+    /// * `content` - The file's content.
+    ///
+    /// # Returns
+    /// * `Result<String>` - The content with merged frontmatter.
+    pub fn apply(
+        &self,
+        relative: &Path,
+        content: &str,
+    ) -> Result<String> {
+        merge_defaults(self.defaults_for(relative), content)
+    }
+}
+
+/// Returns `true` if a content subdirectory is a section, whose files
+/// are built as pages: one holding a `_defaults.toml` or `_index.md`.
+///
+/// # Arguments
+/// * `dir` - The subdirectory.
+pub fn is_section(dir: &Path) -> bool {
+    dir.join(DEFAULTS_FILE_NAME).is_file()
+        || dir.join(INDEX_FILE_NAME).is_file()
+}
+
+/// Returns `true` if a content file holds defaults for the pages of its
+/// directory, rather than being a page itself.
+///
+/// # Arguments
+/// * `path` - The file.
+pub fn is_defaults_file(path: &Path) -> bool {
+    path.file_name().map_or(false, |name| {
+        name == DEFAULTS_FILE_NAME || name == INDEX_FILE_NAME
+    })
+}
+
+/// Merges defaults beneath the frontmatter of some content, as
+/// [`FrontmatterDefaults::apply`] does.
+///
+/// # Arguments
+/// * `defaults` - The defaults, such as from
+///   [`FrontmatterDefaults::cascade_for`].
+/// * `content` - The file's content.
+///
+/// # Returns
+/// * `Result<String>` - The content with merged frontmatter.
+pub fn merge_defaults(
+    defaults: Map<String, JsonValue>,
+    content: &str,
+) -> Result<String> {
+    if defaults.is_empty() {
+        return Ok(content.to_string());
+    }
+
+    let (frontmatter, body) = match split_frontmatter(content) {
+        Some((yaml, body)) if yaml.trim().is_empty() => {
+            (Map::new(), body)
+        }
+        Some((yaml, body)) => match serde_yml::from_str(yaml) {
+            Ok(frontmatter) => (frontmatter, body),
+            Err(_) => return Ok(content.to_string()),
+        },
+        None if content.starts_with("---\n") => {
+            return Ok(content.to_string())
+        }
+        None => (Map::new(), content),
+    };
+
+    let mut merged = defaults;
+    for (key, value) in frontmatter {
+        merge_value(&mut merged, key, value);
+    }
+    let yaml = serde_yml::to_string(&merged).map_err(|e| {
+        ProcessingError::serialization(
+            "Failed to write frontmatter defaults",
+            Some(Box::new(e)),
+        )
+    })?;
+    Ok(format!("---\n{}---\n{}", yaml, body))
+}
+
+/// Returns the values a content directory's `_defaults.toml` and
+/// `_index.md` cascade to the pages beneath it.
+fn directory_defaults(dir: &Path) -> Result<Map<String, JsonValue>> {
+    let read = |name: &str| {
+        let path = dir.join(name);
+        match fs::read_to_string(&path) {
+            Ok(content) => Ok(Some(content)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(ProcessingError::io_error(path, e)),
+        }
+    };
+    let invalid = |name: &str, e: String| {
+        ProcessingError::validation(
+            format!(
+                "Invalid directory defaults in '{}': {}",
+                dir.join(name).display(),
+                e
+            ),
+            None::<String>,
+        )
+    };
+
+    let mut merged = Map::new();
+    if let Some(toml) = read(DEFAULTS_FILE_NAME)? {
+        let values: toml::Table = toml::from_str(&toml)
+            .map_err(|e| invalid(DEFAULTS_FILE_NAME, e.to_string()))?;
+        if let JsonValue::Object(values) = serde_json::to_value(values)?
+        {
+            merged = values;
+        }
+    }
+    if let Some(index) = read(INDEX_FILE_NAME)? {
+        let frontmatter: Map<String, JsonValue> =
+            match split_frontmatter(&index) {
+                Some((yaml, _)) if !yaml.trim().is_empty() => {
+                    serde_yml::from_str(yaml).map_err(|e| {
+                        invalid(INDEX_FILE_NAME, e.to_string())
+                    })?
+                }
+                _ => Map::new(),
+            };
+        match frontmatter.get(CASCADE_KEY) {
+            Some(JsonValue::Object(values)) => {
+                merge_beneath(values.clone(), &mut merged)
+            }
+            Some(_) => {
+                return Err(invalid(
+                    INDEX_FILE_NAME,
+                    format!("`{}` must be a table", CASCADE_KEY),
+                ))
+            }
+            None => {}
+        }
+    }
+    Ok(merged)
+}
+
+/// Splits content into its frontmatter and body, if it opens with a
+/// closed `---` block.
+pub(crate) fn split_frontmatter(content: &str) -> Option<(&str, &str)> {
+    let rest = content.strip_prefix("---\n")?;
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return Some((
+                &rest[..offset],
+                &rest[offset + line.len()..],
+            ));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Merges `values` into `merged`, with `values` taking precedence.
+fn merge_beneath(
+    values: Map<String, JsonValue>,
+    merged: &mut Map<String, JsonValue>,
+) {
+    for (key, value) in values {
+        merge_value(merged, key, value);
+    }
+}
+
+/// Sets `key` to `value`, merging into an existing table key by key.
+fn merge_value(
+    map: &mut Map<String, JsonValue>,
+    key: String,
+    value: JsonValue,
+) {
+    match (map.get_mut(&key), value) {
+        (
+            Some(JsonValue::Object(existing)),
+            JsonValue::Object(value),
+        ) => merge_beneath(value, existing),
+        (_, value) => {
+            _ = map.insert(key, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn defaults() -> FrontmatterDefaults {
+        let rule = |path: &str, values: &str| FrontmatterDefault {
+            path: path.to_string(),
+            values: toml::from_str(values).unwrap(),
+        };
+        FrontmatterDefaults::new(&[
+            rule("docs/**", "template = \"doc\"\ntoc = true"),
+            rule("docs/api/*", "markdown = { tables = true }"),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_from_config() -> Result<()> {
+        let mut config = Config::default();
+        config.content.frontmatter_defaults =
+            toml::from_str("author = \"Ada\"\nlayout = \"post\"")
+                .unwrap();
+        config.frontmatter_defaults = vec![FrontmatterDefault {
+            path: "docs/*".to_string(),
+            values: toml::from_str("layout = \"doc\"").unwrap(),
+        }];
+        let defaults = FrontmatterDefaults::from_config(&config)?;
+
+        let merged = defaults.defaults_for(Path::new("about.md"));
+        assert_eq!(merged["author"], "Ada");
+        assert_eq!(merged["layout"], "post");
+        let merged = defaults.defaults_for(Path::new("docs/a/b.md"));
+        assert_eq!(merged["layout"], "post");
+        let merged =
+            defaults.defaults_for(Path::new("docs/install.md"));
+        assert_eq!(merged["author"], "Ada");
+        assert_eq!(merged["layout"], "doc");
+
+        assert!(FrontmatterDefaults::from_config(&Config::default())?
+            .is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_defaults_for_matching_rules() {
+        let defaults = defaults();
+        assert!(defaults
+            .defaults_for(Path::new("index.md"))
+            .is_empty());
+
+        let merged =
+            defaults.defaults_for(Path::new("docs/api/ref.md"));
+        assert_eq!(merged["template"], "doc");
+        assert_eq!(merged["markdown"]["tables"], true);
+    }
+
+    #[test]
+    fn test_cascade_for() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let content = temp_dir.path();
+        fs::create_dir_all(content.join("docs/api"))?;
+        fs::write(
+            content.join(DEFAULTS_FILE_NAME),
+            "template = \"page\"\ntags = [\"site\"]\n\
+             [markdown]\nfootnotes = true\n",
+        )?;
+        fs::write(
+            content.join("docs").join(INDEX_FILE_NAME),
+            "---\ntitle: Docs\ncascade:\n  template: guide\n---\n",
+        )?;
+        fs::write(
+            content.join("docs/api").join(DEFAULTS_FILE_NAME),
+            "tags = [\"api\"]\n",
+        )?;
+        let defaults = defaults();
+
+        let merged = defaults
+            .cascade_for(content, Path::new("docs/api/ref.md"))?;
+        assert_eq!(merged["template"], "guide");
+        assert_eq!(merged["tags"], serde_json::json!(["api"]));
+        assert_eq!(merged["toc"], true);
+        assert_eq!(merged["markdown"]["tables"], true);
+        assert_eq!(merged["markdown"]["footnotes"], true);
+        assert!(merged.get("title").is_none());
+
+        let merged =
+            defaults.cascade_for(content, Path::new("about.md"))?;
+        assert_eq!(merged["template"], "page");
+
+        fs::write(
+            content.join("docs").join(INDEX_FILE_NAME),
+            "---\ncascade: guide\n---\n",
+        )?;
+        assert!(defaults
+            .cascade_for(content, Path::new("docs/ref.md"))
+            .unwrap_err()
+            .to_string()
+            .contains("must be a table"));
+        fs::write(content.join(DEFAULTS_FILE_NAME), "template = ")?;
+        assert!(defaults
+            .cascade_for(content, Path::new("about.md"))
+            .unwrap_err()
+            .to_string()
+            .contains(DEFAULTS_FILE_NAME));
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_keeps_own_frontmatter() -> Result<()> {
+        let defaults = defaults();
+        let path = Path::new("docs/api/ref.md");
+
+        let merged = defaults.apply(
+            path,
+            "---\ntoc: false\nmarkdown:\n  footnotes: true\n---\nBody\n",
+        )?;
+        let (yaml, body) = split_frontmatter(&merged).unwrap();
+        let frontmatter: Map<String, JsonValue> =
+            serde_yml::from_str(yaml).unwrap();
+        assert_eq!(frontmatter["toc"], false);
+        assert_eq!(frontmatter["template"], "doc");
+        assert_eq!(frontmatter["markdown"]["tables"], true);
+        assert_eq!(frontmatter["markdown"]["footnotes"], true);
+        assert_eq!(body, "Body\n");
+
+        let merged = defaults.apply(path, "Body\n")?;
+        assert!(merged.starts_with("---\n"));
+        assert!(merged.ends_with("---\nBody\n"));
+
+        for unchanged in ["---\nunclosed\n", "---\n: [\n---\nBody\n"] {
+            assert_eq!(defaults.apply(path, unchanged)?, unchanged);
+        }
+        assert_eq!(
+            defaults.apply(Path::new("index.md"), "Body\n")?,
+            "Body\n"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,469 @@
+//! # Git History
+//!
+//! Reads the git history of content files to fill in their metadata,
+//! so sitemaps, feeds and "last updated" snippets need no hand-kept
+//! dates. Which values are read is set by [`GitConfig`], and each is
+//! set in a page's frontmatter unless the page sets it itself:
+//!
+//! - `lastmod` is the date of the file's latest commit, which
+//!   [`Page::lastmod`](crate::site::Page::lastmod) and sitemaps use,
+//! - `created` is the date of its first commit, and
+//! - `contributors` lists the `name` and number of `commits` of each
+//!   author of its commits, most commits first.
+//!
+//! Dates are RFC 3339 timestamps in the offset they were committed in.
+//! Authors are named as the repository's `.mailmap` maps them. Merge
+//! commits are skipped, and a file's history is followed by its current
+//! path only, so a renamed file starts afresh. Files without commits,
+//! such as new files, are left alone, and a shallow clone only knows
+//! the commits it fetched.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use chrono::DateTime;
+//! use nucleusflow::core::config::GitConfig;
+//! use nucleusflow::processors::git::{
+//!     Contributor, FileHistory, GitHistory,
+//! };
+//! use std::path::{Path, PathBuf};
+//!
+//! let history = FileHistory {
+//!     created: DateTime::parse_from_rfc3339("2024-01-02T09:00:00Z")
+//!         .unwrap(),
+//!     lastmod: DateTime::parse_from_rfc3339("2024-05-01T10:00:00Z")
+//!         .unwrap(),
+//!     contributors: vec![Contributor {
+//!         name: "Ada Lovelace".to_string(),
+//!         commits: 3,
+//!     }],
+//! };
+//! let git = GitHistory::new(
+//!     GitConfig::default(),
+//!     [(PathBuf::from("post.md"), history)].into_iter().collect(),
+//! );
+//!
+//! let mut frontmatter = serde_json::Map::new();
+//! git.apply(Path::new("post.md"), &mut frontmatter);
+//! assert_eq!(frontmatter["lastmod"], "2024-05-01T10:00:00+00:00");
+//! assert_eq!(frontmatter["contributors"][0]["commits"], 3);
+//! ```
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, FixedOffset};
+use git2::{Delta, DiffOptions, Repository, Sort, Time};
+use serde_json::{json, Map, Value as JsonValue};
+
+use crate::core::config::GitConfig;
+use crate::{ProcessingError, Result};
+
+/// An author of a file's commits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Contributor {
+    /// The author's name
+    pub name: String,
+
+    /// The number of the file's commits they authored
+    pub commits: usize,
+}
+
+/// What a file's commits tell about it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileHistory {
+    /// When the file was first committed
+    pub created: DateTime<FixedOffset>,
+
+    /// When the file was last committed
+    pub lastmod: DateTime<FixedOffset>,
+
+    /// The authors of the file's commits, most commits first
+    pub contributors: Vec<Contributor>,
+}
+
+/// The git history of a site's content files.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GitHistory {
+    config: GitConfig,
+    files: HashMap<PathBuf, FileHistory>,
+}
+
+impl GitHistory {
+    /// Creates the history of content files from what is known of
+    /// each, keyed by its path relative to the content directory.
+    pub fn new(
+        config: GitConfig,
+        files: HashMap<PathBuf, FileHistory>,
+    ) -> Self {
+        Self { config, files }
+    }
+
+    /// Reads the history of the content files in the repository
+    /// holding a content directory.
+    ///
+    /// # Arguments
+    /// * `config` - The values set from the history.
+    /// * `content_dir` - The content directory.
+    ///
+    /// # Returns
+    /// * `Result<Self>` - The history of each committed file under the
+    ///   content directory, or a configuration error if it is not in a
+    ///   git repository or the history cannot be read.
+    pub fn read(config: GitConfig, content_dir: &Path) -> Result<Self> {
+        let git_error = |e: git2::Error| {
+            ProcessingError::configuration(
+                format!(
+                    "Failed to read the git history of {}: {}",
+                    content_dir.display(),
+                    e.message()
+                ),
+                Some(content_dir.to_path_buf()),
+                Some(Box::new(e)),
+            )
+        };
+        let repo =
+            Repository::discover(content_dir).map_err(git_error)?;
+        if repo.is_empty().map_err(git_error)? {
+            return Ok(Self::new(config, HashMap::new()));
+        }
+        let prefix = content_prefix(&repo, content_dir)?;
+        let mailmap = repo.mailmap().map_err(git_error)?;
+
+        let mut revwalk = repo.revwalk().map_err(git_error)?;
+        revwalk.push_head().map_err(git_error)?;
+        revwalk.set_sorting(Sort::TIME).map_err(git_error)?;
+
+        let mut commits: HashMap<PathBuf, Vec<(Time, String)>> =
+            HashMap::new();
+        for oid in revwalk {
+            let commit = repo
+                .find_commit(oid.map_err(git_error)?)
+                .map_err(git_error)?;
+            if commit.parent_count() > 1 {
+                continue;
+            }
+            let parent = match commit.parent_count() {
+                0 => None,
+                _ => Some(
+                    commit
+                        .parent(0)
+                        .and_then(|parent| parent.tree())
+                        .map_err(git_error)?,
+                ),
+            };
+            let tree = commit.tree().map_err(git_error)?;
+            let mut options = DiffOptions::new();
+            if !prefix.is_empty() {
+                _ = options.pathspec(&prefix);
+            }
+            let diff = repo
+                .diff_tree_to_tree(
+                    parent.as_ref(),
+                    Some(&tree),
+                    Some(&mut options),
+                )
+                .map_err(git_error)?;
+            let author = commit
+                .author_with_mailmap(&mailmap)
+                .map_err(git_error)?
+                .name()
+                .unwrap_or_default()
+                .to_string();
+
+            for delta in diff.deltas() {
+                if delta.status() == Delta::Deleted {
+                    continue;
+                }
+                if let Some(path) = delta
+                    .new_file()
+                    .path()
+                    .and_then(|path| path.strip_prefix(&prefix).ok())
+                {
+                    commits
+                        .entry(path.to_path_buf())
+                        .or_default()
+                        .push((commit.time(), author.clone()));
+                }
+            }
+        }
+
+        let files = commits
+            .into_iter()
+            .filter_map(|(path, commits)| {
+                Some((path, file_history(&commits)?))
+            })
+            .collect();
+        Ok(Self::new(config, files))
+    }
+
+    /// Returns the history of a content file.
+    ///
+    /// # Arguments
+    /// * `path` - The file's path relative to the content directory.
+    pub fn file(&self, path: &Path) -> Option<&FileHistory> {
+        self.files.get(path)
+    }
+
+    /// Sets the configured values of a content file's history in its
+    /// frontmatter, leaving alone those it sets itself.
+    ///
+    /// # Arguments
+    /// * `path` - The file's path relative to the content directory.
+    /// * `frontmatter` - The file's frontmatter.
+    pub fn apply(
+        &self,
+        path: &Path,
+        frontmatter: &mut Map<String, JsonValue>,
+    ) {
+        let file = match self.file(path) {
+            Some(file) => file,
+            None => return,
+        };
+        let mut set = |key: &str, value: JsonValue| {
+            _ = frontmatter.entry(key).or_insert(value);
+        };
+        if self.config.lastmod {
+            set("lastmod", file.lastmod.to_rfc3339().into());
+        }
+        if self.config.created {
+            set("created", file.created.to_rfc3339().into());
+        }
+        if self.config.contributors {
+            set(
+                "contributors",
+                file.contributors
+                    .iter()
+                    .map(|contributor| {
+                        json!({
+                            "name": contributor.name,
+                            "commits": contributor.commits,
+                        })
+                    })
+                    .collect::<Vec<_>>()
+                    .into(),
+            );
+        }
+    }
+}
+
+/// Returns the content directory's path in the repository's working
+/// tree, with `/` separators and a trailing `/`, or an empty string if
+/// it is the working tree itself.
+fn content_prefix(
+    repo: &Repository,
+    content_dir: &Path,
+) -> Result<String> {
+    let workdir = repo.workdir().ok_or_else(|| {
+        ProcessingError::configuration(
+            "Git history cannot be read from a bare repository",
+            Some(content_dir.to_path_buf()),
+            None,
+        )
+    })?;
+    let canonical = |path: &Path| {
+        path.canonicalize().map_err(|e| {
+            ProcessingError::io_error(path.to_path_buf(), e)
+        })
+    };
+    let relative = canonical(content_dir)?
+        .strip_prefix(canonical(workdir)?)
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let mut prefix = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    if !prefix.is_empty() {
+        prefix.push('/');
+    }
+    Ok(prefix)
+}
+
+/// Returns what a file's commits, newest first, tell about it.
+fn file_history(commits: &[(Time, String)]) -> Option<FileHistory> {
+    let mut contributors: Vec<Contributor> = Vec::new();
+    for (_, name) in commits {
+        match contributors.iter_mut().find(|c| &c.name == name) {
+            Some(contributor) => contributor.commits += 1,
+            None => contributors.push(Contributor {
+                name: name.clone(),
+                commits: 1,
+            }),
+        }
+    }
+    contributors.sort_by(|a, b| {
+        b.commits.cmp(&a.commits).then_with(|| a.name.cmp(&b.name))
+    });
+    Some(FileHistory {
+        created: commit_date(commits.last()?.0)?,
+        lastmod: commit_date(commits.first()?.0)?,
+        contributors,
+    })
+}
+
+/// Returns when a commit was made, in the offset it was made in.
+fn commit_date(time: Time) -> Option<DateTime<FixedOffset>> {
+    let offset = FixedOffset::east_opt(time.offset_minutes() * 60)?;
+    Some(
+        DateTime::from_timestamp(time.seconds(), 0)?
+            .with_timezone(&offset),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::{Commit, Signature};
+    use std::fs;
+    use tempfile::TempDir;
+
+    const DAY: i64 = 86_400;
+    const START: i64 = 1_700_000_000;
+
+    fn commit(
+        repo: &Repository,
+        files: &[&str],
+        author: &str,
+        seconds: i64,
+    ) {
+        let workdir = repo.workdir().unwrap();
+        let mut index = repo.index().unwrap();
+        for file in files {
+            let path = workdir.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, seconds.to_string()).unwrap();
+            index.add_path(Path::new(file)).unwrap();
+        }
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::new(
+            author,
+            &format!("{}@example.com", author.to_lowercase()),
+            &Time::new(seconds, 60),
+        )
+        .unwrap();
+        let parent = repo
+            .head()
+            .ok()
+            .and_then(|head| head.peel_to_commit().ok());
+        let parents: Vec<&Commit<'_>> = parent.iter().collect();
+        _ = repo
+            .commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                "Update",
+                &tree,
+                &parents,
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn test_read() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let content_dir = temp_dir.path().join("content");
+        fs::create_dir(&content_dir)?;
+        assert_eq!(
+            GitHistory::read(GitConfig::default(), &content_dir)?,
+            GitHistory::default()
+        );
+
+        commit(&repo, &["content/post.md", "README.md"], "Ada", START);
+        commit(
+            &repo,
+            &["content/post.md", "content/docs/guide.md"],
+            "Grace",
+            START + DAY,
+        );
+        commit(&repo, &["content/post.md"], "Ada", START + 2 * DAY);
+
+        let history =
+            GitHistory::read(GitConfig::default(), &content_dir)?;
+        let post = history.file(Path::new("post.md")).unwrap();
+        assert_eq!(
+            post.created.to_rfc3339(),
+            "2023-11-14T23:13:20+01:00"
+        );
+        assert_eq!(
+            post.lastmod.to_rfc3339(),
+            "2023-11-16T23:13:20+01:00"
+        );
+        assert_eq!(
+            post.contributors,
+            [
+                Contributor {
+                    name: "Ada".to_string(),
+                    commits: 2,
+                },
+                Contributor {
+                    name: "Grace".to_string(),
+                    commits: 1,
+                },
+            ]
+        );
+        let guide = history.file(Path::new("docs/guide.md")).unwrap();
+        assert_eq!(guide.created, guide.lastmod);
+        assert!(history.file(Path::new("README.md")).is_none());
+        assert!(history.file(Path::new("../README.md")).is_none());
+
+        let outside = TempDir::new().unwrap();
+        assert!(GitHistory::read(GitConfig::default(), outside.path())
+            .unwrap_err()
+            .to_string()
+            .contains("Failed to read the git history"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply() {
+        let date = |d: &str| DateTime::parse_from_rfc3339(d).unwrap();
+        let file = FileHistory {
+            created: date("2024-01-02T09:00:00+02:00"),
+            lastmod: date("2024-05-01T10:00:00+02:00"),
+            contributors: vec![Contributor {
+                name: "Ada".to_string(),
+                commits: 1,
+            }],
+        };
+        let files: HashMap<_, _> =
+            [(PathBuf::from("post.md"), file)].into_iter().collect();
+
+        let history =
+            GitHistory::new(GitConfig::default(), files.clone());
+        let mut frontmatter = json!({ "lastmod": "2024-06-01" })
+            .as_object()
+            .cloned()
+            .unwrap();
+        history.apply(Path::new("post.md"), &mut frontmatter);
+        assert_eq!(
+            JsonValue::Object(frontmatter),
+            json!({
+                "lastmod": "2024-06-01",
+                "created": "2024-01-02T09:00:00+02:00",
+                "contributors": [{ "name": "Ada", "commits": 1 }],
+            })
+        );
+
+        let history = GitHistory::new(
+            GitConfig {
+                created: false,
+                contributors: false,
+                ..GitConfig::default()
+            },
+            files,
+        );
+        let mut frontmatter = Map::new();
+        history.apply(Path::new("post.md"), &mut frontmatter);
+        assert_eq!(
+            JsonValue::Object(frontmatter),
+            json!({ "lastmod": "2024-05-01T10:00:00+02:00" })
+        );
+        let mut frontmatter = Map::new();
+        history.apply(Path::new("other.md"), &mut frontmatter);
+        assert!(frontmatter.is_empty());
+    }
+}
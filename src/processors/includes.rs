@@ -0,0 +1,286 @@
+//! # Includes
+//!
+//! Expands `{{include "snippets/warning.md"}}` directives, so fragments
+//! such as warnings or licence notes can be shared across many pages.
+//!
+//! A directive is replaced by the named file's content, without its
+//! frontmatter, before the page is processed, so fragments are written
+//! in the same format as the pages including them. Fragments may
+//! include other fragments.
+//!
+//! Paths are relative to the content directory and must stay inside
+//! it: absolute paths and paths escaping it through `..` or symlinks
+//! are rejected. Only top-level files of the content directory and
+//! files of sections are pages, so fragments kept in a subdirectory
+//! that is not a section are not built on their own. A fragment that
+//! includes itself, directly or through others, fails the page with
+//! the cycle.
+//!
+//! Directives inside fenced code blocks are left as they are, so the
+//! syntax can be documented.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use nucleusflow::processors::includes::Includes;
+//! use std::path::Path;
+//!
+//! let includes = Includes::new("content");
+//! let page = Path::new("content/install.md");
+//! let expanded = includes
+//!     .expand(page, "# Install\n\n{{include \"snippets/warning.md\"}}\n")
+//!     .unwrap();
+//! ```
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::processors::frontmatter::split_frontmatter;
+use crate::{ProcessingError, Result};
+
+/// Opens an include directive.
+pub const INCLUDE_OPEN: &str = "{{include ";
+
+/// Closes an include directive.
+const INCLUDE_CLOSE: &str = "}}";
+
+/// Expands include directives against a content directory.
+#[derive(Debug, Clone)]
+pub struct Includes {
+    content_dir: PathBuf,
+}
+
+impl Includes {
+    /// Creates an expander reading fragments from `content_dir`.
+    pub fn new<P: AsRef<Path>>(content_dir: P) -> Self {
+        Self {
+            content_dir: content_dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Expands the include directives in a page's content.
+    ///
+    /// # Arguments
+    /// * `source` - The content file the page was read from.
+    /// * `content` - The page's content.
+    ///
+    /// # Returns
+    /// * `Result<String>` - The content with every directive replaced,
+    ///   or an error if a fragment is outside the content directory,
+    ///   cannot be read or includes itself.
+    pub fn expand(
+        &self,
+        source: &Path,
+        content: &str,
+    ) -> Result<String> {
+        if !content.contains(INCLUDE_OPEN) {
+            return Ok(content.to_string());
+        }
+        let mut stack = vec![fs::canonicalize(source)
+            .unwrap_or_else(|_| source.to_path_buf())];
+        self.expand_nested(content, &mut stack)
+    }
+
+    /// Expands the directives of content included through `stack`.
+    fn expand_nested(
+        &self,
+        content: &str,
+        stack: &mut Vec<PathBuf>,
+    ) -> Result<String> {
+        let mut output = String::with_capacity(content.len());
+        let mut fence: Option<(char, usize)> = None;
+        for line in content.split_inclusive('\n') {
+            let trimmed = line.trim_start();
+            let marker = trimmed
+                .chars()
+                .next()
+                .filter(|c| *c == '`' || *c == '~');
+            let length = marker.map_or(0, |marker| {
+                trimmed.chars().take_while(|&c| c == marker).count()
+            });
+            match (fence, marker) {
+                (Some((open, open_length)), Some(marker))
+                    if marker == open
+                        && length >= open_length
+                        && trimmed[length..].trim().is_empty() =>
+                {
+                    fence = None;
+                    output.push_str(line);
+                    continue;
+                }
+                (None, Some(marker)) if length >= 3 => {
+                    fence = Some((marker, length));
+                }
+                _ => {}
+            }
+            if fence.is_some() || !line.contains(INCLUDE_OPEN) {
+                output.push_str(line);
+                continue;
+            }
+            self.expand_line(line, stack, &mut output)?;
+        }
+        Ok(output)
+    }
+
+    /// Expands the directives on one line of content.
+    fn expand_line(
+        &self,
+        line: &str,
+        stack: &mut Vec<PathBuf>,
+        output: &mut String,
+    ) -> Result<()> {
+        let mut rest = line;
+        while let Some(start) = rest.find(INCLUDE_OPEN) {
+            output.push_str(&rest[..start]);
+            let after = &rest[start + INCLUDE_OPEN.len()..];
+            let end = after.find(INCLUDE_CLOSE).ok_or_else(|| {
+                ProcessingError::validation(
+                    "Unclosed include directive",
+                    Some(rest[start..].trim_end()),
+                )
+            })?;
+            let target = after[..end]
+                .trim()
+                .strip_prefix('"')
+                .and_then(|target| target.strip_suffix('"'))
+                .filter(|target| !target.is_empty())
+                .ok_or_else(|| {
+                    ProcessingError::validation(
+                        "Include directives name a quoted path",
+                        Some(
+                            &rest[start
+                                ..start + INCLUDE_OPEN.len() + end + 2],
+                        ),
+                    )
+                })?;
+            rest = &after[end + INCLUDE_CLOSE.len()..];
+
+            let path = self.resolve(target)?;
+            if let Some(index) = stack.iter().position(|p| *p == path) {
+                let cycle: Vec<String> = stack[index..]
+                    .iter()
+                    .chain(Some(&path))
+                    .map(|p| p.display().to_string())
+                    .collect();
+                return Err(ProcessingError::validation(
+                    format!("Include cycle: {}", cycle.join(" -> ")),
+                    None::<String>,
+                ));
+            }
+            let fragment = fs::read_to_string(&path).map_err(|e| {
+                ProcessingError::io_error(path.clone(), e)
+            })?;
+            let body = split_frontmatter(&fragment)
+                .map_or(fragment.as_str(), |(_, body)| body);
+
+            stack.push(path);
+            let expanded = self.expand_nested(body, stack)?;
+            _ = stack.pop();
+            output.push_str(expanded.trim_end_matches('\n'));
+        }
+        output.push_str(rest);
+        Ok(())
+    }
+
+    /// Resolves a fragment path, keeping it inside the content
+    /// directory.
+    fn resolve(&self, target: &str) -> Result<PathBuf> {
+        let outside = || {
+            ProcessingError::validation(
+                format!(
+                    "Included file '{}' is outside the content directory",
+                    target
+                ),
+                None::<String>,
+            )
+        };
+        let relative = Path::new(target);
+        if relative.components().any(|c| {
+            !matches!(c, Component::Normal(_) | Component::CurDir)
+        }) {
+            return Err(outside());
+        }
+        let path = self.content_dir.join(relative);
+        let path = fs::canonicalize(&path)
+            .map_err(|e| ProcessingError::io_error(path, e))?;
+        let root =
+            fs::canonicalize(&self.content_dir).map_err(|e| {
+                ProcessingError::io_error(self.content_dir.clone(), e)
+            })?;
+        if path.starts_with(&root) {
+            Ok(path)
+        } else {
+            Err(outside())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_expand() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content = temp_dir.path();
+        fs::create_dir(content.join("snippets"))?;
+        fs::write(
+            content.join("snippets/warning.md"),
+            "---\ntitle: Warning\n---\n> Careful: {{include \"snippets/term.md\"}}\n",
+        )?;
+        fs::write(content.join("snippets/term.md"), "hot\n")?;
+        let includes = Includes::new(content);
+        let page = content.join("page.md");
+
+        assert_eq!(
+            includes.expand(
+                &page,
+                "# Tea\n\n{{include \"snippets/warning.md\"}}\n\n\
+                 ```\n{{include \"snippets/term.md\"}}\n```\n"
+            )?,
+            "# Tea\n\n> Careful: hot\n\n```\n{{include \"snippets/term.md\"}}\n```\n"
+        );
+        assert_eq!(
+            includes.expand(&page, "No includes")?,
+            "No includes"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_expand_errors() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let content = temp_dir.path().join("content");
+        fs::create_dir(&content)?;
+        fs::write(temp_dir.path().join("secret.md"), "secret")?;
+        fs::write(content.join("a.md"), "{{include \"b.md\"}}")?;
+        fs::write(content.join("b.md"), "{{include \"./a.md\"}}")?;
+        let includes = Includes::new(&content);
+        let page = content.join("page.md");
+        let error = |text: &str| {
+            includes.expand(&page, text).unwrap_err().to_string()
+        };
+
+        assert!(error("{{include \"../secret.md\"}}")
+            .contains("outside the content directory"));
+        assert!(error("{{include \"/etc/passwd\"}}")
+            .contains("outside the content directory"));
+        assert!(error("{{include \"a.md\"}}").contains("Include cycle"));
+        assert!(
+            error("{{include \"missing.md\"}}").contains("missing.md")
+        );
+        assert!(error("{{include \"a.md\"").contains("Unclosed"));
+        assert!(error("{{include a.md}}").contains("quoted path"));
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(
+                temp_dir.path().join("secret.md"),
+                content.join("link.md"),
+            )?;
+            assert!(error("{{include \"link.md\"}}")
+                .contains("outside the content directory"));
+        }
+        Ok(())
+    }
+}
@@ -8,9 +8,35 @@
 //!
 //! - **Safe Processing**: Secure content handling with robust sanitization
 //! - **Metadata Extraction**: YAML frontmatter parsing with type-safe handling
-//! - **Table of Contents**: Automatic generation of nested TOC structures
-//! - **Configurable Options**: Support for tables, footnotes, and strikethrough
+//! - **Table of Contents**: Automatic generation of nested TOC structures,
+//!   placed at a `<!-- toc -->` marker, prepended, or returned apart
+//!   from the content for templates to place
+//! - **Heading Anchors**: Every heading gets a unique `id`, such as
+//!   `intro` and `intro-1`, optionally with a `¶` permalink
+//! - **Heading Offset**: Headings can be demoted by a number of levels
+//!   for pages whose template already supplies the `<h1>`
+//! - **Configurable Options**: Support for tables, footnotes,
+//!   strikethrough, task lists, heading attributes and definition lists
+//! - **Smart Punctuation**: Straight quotes, `--`, `---` and `...` can
+//!   be rendered as curly quotes, dashes and ellipses
+//! - **Footnotes**: Listed at the end of the page with links back to
+//!   each citation, numbered per page or continuously across pages,
+//!   under an optional heading
+//! - **Summaries**: A `<!--more-->` marker on a line of its own ends
+//!   the page's summary; see [`crate::processors::summary`]
+//! - **Reading Time**: The word count and estimated reading time of
+//!   the content are part of its metadata; see [`ReadingStats`]
 //! - **Content Validation**: Protection against XSS and other injection attacks
+//! - **Per-Page Overrides**: Frontmatter can adjust processor options for a
+//!   single page
+//! - **Raw HTML Blocks**: Fenced `{=html}` blocks pass trusted snippets
+//!   through sanitization when the site or page policy allows it
+//! - **Diagrams**: Fenced `mermaid` and `plantuml` blocks are wrapped
+//!   for client-side rendering or rendered to SVG by a configured
+//!   command; see [`crate::processors::diagrams`]
+//! - **Math**: `$...$` and `$$...$$` are rendered to MathML or HTML at
+//!   build time with KaTeX, with the `math` feature, so pages need no
+//!   client-side math script
 //!
 //! ## Example Usage
 //!
@@ -36,11 +62,25 @@
 //! ```
 
 use crate::core::{
+    config::{
+        ContentConfig, FootnoteConfig, FootnoteNumbering,
+        MarkdownExtensions, Profile,
+    },
     error::{ProcessingError, Result},
     traits::Processor,
 };
+use crate::generators::bundles::PageBundles;
+#[cfg(feature = "parallel-markdown")]
+use crate::processors::chunks::ChunkConfig;
+use crate::processors::diagrams::{
+    Diagram, DiagramConfig, DIAGRAM_LANGUAGES,
+};
+use crate::processors::frontmatter::split_frontmatter;
+use crate::processors::summary::{MORE_MARKER, SUMMARY_MARKER};
+use ammonia::Builder;
 use pulldown_cmark::{
-    html, Event, HeadingLevel, Options as MarkdownOptions, Parser, Tag,
+    html, CodeBlockKind, CowStr, Event, HeadingLevel,
+    Options as MarkdownOptions, Parser, Tag, TagEnd,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
@@ -51,37 +91,40 @@
 /// Maximum allowed size for Markdown content in bytes (10MB)
 const MAX_CONTENT_SIZE: usize = 10 * 1024 * 1024;
 
-/// List of allowed HTML tags that won't be stripped during sanitization
-const ALLOWED_HTML_TAGS: &[&str] = &[
-    "p",
-    "br",
-    "h1",
-    "h2",
-    "h3",
-    "h4",
-    "h5",
-    "h6",
-    "strong",
-    "em",
-    "del",
-    "ul",
-    "ol",
-    "li",
-    "code",
-    "pre",
-    "blockquote",
-    "hr",
-    "table",
-    "thead",
-    "tbody",
-    "tr",
-    "th",
-    "td",
-    "img",
-    "a",
-    "nav",
+/// Reading speed reading times are estimated with, unless configured.
+pub const DEFAULT_WORDS_PER_MINUTE: usize = 200;
+
+/// Frontmatter keys that override processor options for a single page.
+const OVERRIDE_KEYS: &[&str] = &[
+    "sanitize",
+    "toc",
+    "toc_min_level",
+    "toc_max_level",
+    "heading_offset",
+    "math",
+    "auto_links",
+    "raw_html",
+    "markdown",
 ];
 
+/// Info string marking a fenced code block as raw, trusted HTML.
+const RAW_HTML_INFO: &str = "{=html}";
+
+/// Largest number of levels headings can be demoted by, which turns
+/// `#` into `<h6>`.
+const MAX_HEADING_OFFSET: u8 = 5;
+
+/// Marker replaced with the Table of Contents.
+pub const TOC_MARKER: &str = "<!-- toc -->";
+
+/// Separates a standalone Table of Contents from the content that
+/// follows it in the processor's output; see [`split_toc`].
+const TOC_SEPARATOR: char = '\u{E001}';
+
+/// Delimiter for raw HTML placeholders. A private-use character is used
+/// so the placeholder survives sanitization untouched.
+const RAW_HTML_MARKER: char = '\u{E000}';
+
 /// Configuration options for markdown processing.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessorConfig {
@@ -93,14 +136,66 @@ pub struct ProcessorConfig {
     #[serde(default)]
     pub toc: bool,
 
+    /// Minimum heading level for TOC (1-6)
+    #[serde(default = "default_toc_min_level")]
+    pub toc_min_level: u8,
+
     /// Maximum heading level for TOC (1-6)
     #[serde(default = "default_toc_level")]
     pub toc_max_level: u8,
 
+    /// Where the TOC is placed
+    #[serde(default)]
+    pub toc_placement: TocPlacement,
+
+    /// Append a `¶` permalink to each heading
+    #[serde(default)]
+    pub heading_anchors: bool,
+
+    /// Levels every heading is demoted by (0-5), so `#` renders as
+    /// `<h2>` with an offset of 1; headings never go below `<h6>`, and
+    /// TOC levels still refer to the Markdown levels
+    #[serde(default)]
+    pub heading_offset: u8,
+
     /// Enable automatic link references
     #[serde(default = "default_true")]
     pub auto_links: bool,
 
+    /// Parse `$...$` and `$$...$$` as math, rendered at build time with
+    /// the `math` feature, and otherwise left as TeX in
+    /// `<span class="math">` elements for client-side rendering
+    #[serde(default)]
+    pub math: bool,
+
+    /// The markup math is rendered to with the `math` feature
+    #[serde(default)]
+    pub math_output: MathOutput,
+
+    /// Render straight quotes as curly quotes, `--` and `---` as en
+    /// and em dashes, and `...` as an ellipsis
+    #[serde(default)]
+    pub smart_punctuation: bool,
+
+    /// How footnotes are numbered and rendered
+    #[serde(default)]
+    pub footnotes: FootnoteConfig,
+
+    /// Reading speed the reading time in [`ContentMetadata`] is
+    /// estimated with
+    #[serde(default = "default_words_per_minute")]
+    pub words_per_minute: usize,
+
+    /// How each diagram language is rendered, keyed by fence language;
+    /// `mermaid` and `plantuml` are rendered client-side unless
+    /// configured
+    #[serde(default)]
+    pub diagrams: HashMap<String, DiagramConfig>,
+
+    /// Allow fenced `{=html}` blocks to bypass sanitization
+    #[serde(default)]
+    pub allow_raw_html: bool,
+
     /// Custom processor options
     #[serde(default)]
     pub options: HashMap<String, JsonValue>,
@@ -111,13 +206,60 @@ fn default() -> Self {
         Self {
             sanitize: true,
             toc: false,
+            toc_min_level: 1,
             toc_max_level: 3,
+            toc_placement: TocPlacement::default(),
+            heading_anchors: false,
+            heading_offset: 0,
             auto_links: true,
+            math: false,
+            math_output: MathOutput::default(),
+            smart_punctuation: false,
+            footnotes: FootnoteConfig::default(),
+            words_per_minute: DEFAULT_WORDS_PER_MINUTE,
+            diagrams: HashMap::new(),
+            allow_raw_html: false,
             options: HashMap::new(),
         }
     }
 }
 
+/// Where the Table of Contents is placed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TocPlacement {
+    /// At the first `<!-- toc -->` marker, or before the content if
+    /// there is none
+    Content,
+    /// Apart from the content, for templates to place as `page.toc`;
+    /// see [`split_toc`]
+    Standalone,
+}
+
+impl Default for TocPlacement {
+    fn default() -> Self {
+        Self::Content
+    }
+}
+
+/// The markup math is rendered to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MathOutput {
+    /// MathML, which browsers display without any stylesheet
+    Mathml,
+    /// KaTeX HTML, which needs the KaTeX stylesheet and fonts
+    Html,
+    /// KaTeX HTML with MathML for assistive technology
+    Both,
+}
+
+impl Default for MathOutput {
+    fn default() -> Self {
+        Self::Mathml
+    }
+}
+
 /// Metadata extracted from Markdown content.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ContentMetadata {
@@ -129,10 +271,323 @@ pub struct ContentMetadata {
     pub date: Option<String>,
     /// Content tags
     pub tags: Vec<String>,
+    /// Per-page CSS/JS bundles
+    #[serde(default)]
+    pub bundles: PageBundles,
+    /// Word count and reading time of the content
+    #[serde(flatten)]
+    pub reading: ReadingStats,
     /// Custom metadata fields
     pub custom: HashMap<String, JsonValue>,
 }
 
+/// The word count and estimated reading time of a page's text.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(default)]
+pub struct ReadingStats {
+    /// The number of words
+    pub word_count: usize,
+    /// The estimated reading time in minutes, rounded up
+    pub reading_time: usize,
+}
+
+impl ReadingStats {
+    /// Counts the words of some text and estimates how long it takes to
+    /// read. Only runs of characters containing a letter or digit count
+    /// as words, so stray punctuation is left out.
+    ///
+    /// # Arguments
+    /// * `text` - Plain text, without markup.
+    /// * `words_per_minute` - The reading speed.
+    pub fn new(text: &str, words_per_minute: usize) -> Self {
+        let word_count = text
+            .split_whitespace()
+            .filter(|word| word.chars().any(char::is_alphanumeric))
+            .count();
+        let words_per_minute = words_per_minute.max(1);
+        Self {
+            word_count,
+            reading_time: (word_count + words_per_minute - 1)
+                / words_per_minute,
+        }
+    }
+}
+
+/// Processor options overridden by a page's frontmatter.
+///
+/// Only the fields present in the frontmatter are applied; everything else
+/// falls back to the global [`ProcessorConfig`].
+///
+/// ```yaml
+/// ---
+/// title: Changelog
+/// toc: true
+/// markdown:
+///   smart_punctuation: false
+/// ---
+/// ```
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(deny_unknown_fields)]
+pub struct PageOverrides {
+    /// Enables or disables sanitization (disabling requires the
+    /// development profile)
+    pub sanitize: Option<bool>,
+
+    /// Enables or disables Table of Contents generation
+    pub toc: Option<bool>,
+
+    /// Minimum heading level for the TOC (1-6)
+    pub toc_min_level: Option<u8>,
+
+    /// Maximum heading level for the TOC (1-6)
+    pub toc_max_level: Option<u8>,
+
+    /// Levels every heading is demoted by (0-5)
+    pub heading_offset: Option<u8>,
+
+    /// Enables or disables automatic link references
+    pub auto_links: Option<bool>,
+
+    /// Enables or disables math
+    pub math: Option<bool>,
+
+    /// Allows or forbids fenced `{=html}` raw blocks on this page
+    /// (allowing them where the site forbids them requires the
+    /// development profile)
+    pub raw_html: Option<bool>,
+
+    /// Markdown syntax extension overrides
+    pub markdown: Option<MarkdownOverrides>,
+}
+
+/// Markdown syntax extensions that can be toggled per page.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(deny_unknown_fields)]
+pub struct MarkdownOverrides {
+    /// Enables or disables tables
+    pub tables: Option<bool>,
+
+    /// Enables or disables strikethrough
+    pub strikethrough: Option<bool>,
+
+    /// Enables or disables footnotes
+    pub footnotes: Option<bool>,
+
+    /// Enables or disables task lists
+    pub tasklists: Option<bool>,
+
+    /// Enables or disables smart punctuation (curly quotes, dashes)
+    pub smart_punctuation: Option<bool>,
+
+    /// Enables or disables `{#id .class}` heading attributes
+    pub heading_attributes: Option<bool>,
+
+    /// Enables or disables `term` / `: definition` lists
+    pub definition_lists: Option<bool>,
+}
+
+impl PageOverrides {
+    /// Removes override keys from parsed frontmatter and validates them.
+    ///
+    /// # Arguments
+    /// * `frontmatter` - The parsed frontmatter; override keys are removed
+    ///   so they are not treated as custom metadata.
+    ///
+    /// # Returns
+    /// * `Result<Self>` - The overrides, or a validation error if a key is
+    ///   unknown or has the wrong type.
+    pub fn take_from(
+        frontmatter: &mut HashMap<String, JsonValue>,
+    ) -> Result<Self> {
+        let overrides: serde_json::Map<String, JsonValue> =
+            OVERRIDE_KEYS
+                .iter()
+                .filter_map(|&key| frontmatter.remove_entry(key))
+                .collect();
+        let overrides: Self =
+            serde_json::from_value(JsonValue::Object(overrides))
+                .map_err(|e| {
+                    ProcessingError::validation(
+                        format!("Invalid processor override: {}", e),
+                        Some("frontmatter"),
+                    )
+                })?;
+
+        for (key, level) in [
+            ("toc_min_level", overrides.toc_min_level),
+            ("toc_max_level", overrides.toc_max_level),
+        ] {
+            match level {
+                Some(level) if !(1..=6).contains(&level) => {
+                    return Err(ProcessingError::validation(
+                        format!(
+                            "{} must be between 1 and 6, got {}",
+                            key, level
+                        ),
+                        Some("frontmatter"),
+                    ));
+                }
+                _ => {}
+            }
+        }
+        if let Some(offset) = overrides.heading_offset {
+            if offset > MAX_HEADING_OFFSET {
+                return Err(ProcessingError::validation(
+                    format!(
+                        "heading_offset must be between 0 and {}, got {}",
+                        MAX_HEADING_OFFSET, offset
+                    ),
+                    Some("frontmatter"),
+                ));
+            }
+        }
+        Ok(overrides)
+    }
+
+    /// Merges the overrides over a global configuration.
+    ///
+    /// # Arguments
+    /// * `config` - The global processor configuration.
+    /// * `options` - The global Markdown parser options.
+    /// * `profile` - The active build profile.
+    ///
+    /// # Returns
+    /// * `Result<(ProcessorConfig, MarkdownOptions)>` - The effective
+    ///   configuration for the page, or an error if an override is not
+    ///   permitted in the active profile or the TOC levels are reversed.
+    pub fn apply(
+        &self,
+        config: &ProcessorConfig,
+        options: MarkdownOptions,
+        profile: Profile,
+    ) -> Result<(ProcessorConfig, MarkdownOptions)> {
+        if self.sanitize == Some(false)
+            && profile != Profile::Development
+        {
+            return Err(ProcessingError::validation(
+                "Sanitization can only be disabled per page in the development profile",
+                Some("frontmatter"),
+            ));
+        }
+        if self.raw_html == Some(true)
+            && !config.allow_raw_html
+            && profile != Profile::Development
+        {
+            return Err(ProcessingError::validation(
+                "Raw HTML can only be allowed per page in the development profile",
+                Some("frontmatter"),
+            ));
+        }
+
+        let mut config = config.clone();
+        if let Some(sanitize) = self.sanitize {
+            config.sanitize = sanitize;
+        }
+        if let Some(toc) = self.toc {
+            config.toc = toc;
+        }
+        if let Some(level) = self.toc_min_level {
+            config.toc_min_level = level;
+        }
+        if let Some(level) = self.toc_max_level {
+            config.toc_max_level = level;
+        }
+        if let Some(offset) = self.heading_offset {
+            config.heading_offset = offset;
+        }
+        if config.toc_min_level > config.toc_max_level {
+            return Err(ProcessingError::validation(
+                format!(
+                    "toc_min_level {} is above toc_max_level {}",
+                    config.toc_min_level, config.toc_max_level
+                ),
+                Some("frontmatter"),
+            ));
+        }
+        if let Some(auto_links) = self.auto_links {
+            config.auto_links = auto_links;
+        }
+        if let Some(math) = self.math {
+            config.math = math;
+        }
+        if let Some(raw_html) = self.raw_html {
+            config.allow_raw_html = raw_html;
+        }
+
+        let mut options = options;
+        options.set(MarkdownOptions::ENABLE_MATH, config.math);
+        options.set(
+            MarkdownOptions::ENABLE_SMART_PUNCTUATION,
+            config.smart_punctuation,
+        );
+        if let Some(markdown) = &self.markdown {
+            for (value, flag) in [
+                (markdown.tables, MarkdownOptions::ENABLE_TABLES),
+                (
+                    markdown.strikethrough,
+                    MarkdownOptions::ENABLE_STRIKETHROUGH,
+                ),
+                (markdown.footnotes, MarkdownOptions::ENABLE_FOOTNOTES),
+                (markdown.tasklists, MarkdownOptions::ENABLE_TASKLISTS),
+                (
+                    markdown.smart_punctuation,
+                    MarkdownOptions::ENABLE_SMART_PUNCTUATION,
+                ),
+                (
+                    markdown.heading_attributes,
+                    MarkdownOptions::ENABLE_HEADING_ATTRIBUTES,
+                ),
+                (
+                    markdown.definition_lists,
+                    MarkdownOptions::ENABLE_DEFINITION_LIST,
+                ),
+            ] {
+                if let Some(enable) = value {
+                    options.set(flag, enable);
+                }
+            }
+        }
+
+        Ok((config, options))
+    }
+}
+
+/// Output set aside while the rest of a page is sanitized.
+#[derive(Debug)]
+enum RawBlock {
+    /// A trusted fenced `{=html}` block
+    Html(String),
+    /// A diagram rendered to SVG at build time
+    Diagram(String),
+    /// Math rendered at build time
+    #[cfg(feature = "math")]
+    Math(String),
+    /// A task list checkbox, checked or not
+    Checkbox(bool),
+}
+
+impl RawBlock {
+    fn html(&self) -> &str {
+        match self {
+            RawBlock::Html(html) | RawBlock::Diagram(html) => html,
+            RawBlock::Checkbox(true) => {
+                r#"<input type="checkbox" disabled="" checked="">"#
+            }
+            RawBlock::Checkbox(false) => {
+                r#"<input type="checkbox" disabled="">"#
+            }
+            #[cfg(feature = "math")]
+            RawBlock::Math(html) => html,
+        }
+    }
+}
+
 /// Represents a heading in the Table of Contents.
 #[derive(Debug)]
 struct TocEntry {
@@ -140,7 +595,8 @@ struct TocEntry {
     text: String,
     /// Heading level (1-6)
     level: u8,
-    /// Generated ID for the heading
+    /// ID the heading is rendered with; empty until one is assigned
+    /// to a heading without an explicit `{#id}`
     id: String,
 }
 
@@ -149,51 +605,140 @@ struct TocEntry {
 pub struct MarkdownProcessor {
     options: MarkdownOptions,
     config: ProcessorConfig,
-    /// Cache of allowed HTML tags for faster sanitization
+    /// Build profile governing which per-page overrides are permitted
+    profile: Profile,
+    /// HTML tags kept by the sanitizer
     allowed_tags: Arc<HashSet<String>>,
+    /// Attributes kept on allowed tags by the sanitizer
+    allowed_attributes: Arc<HashSet<String>>,
+    /// URL schemes permitted in links and sources
+    allowed_protocols: Arc<HashSet<String>>,
+    /// How long documents are split for parallel parsing, if they are
+    #[cfg(feature = "parallel-markdown")]
+    parallel: Option<ChunkConfig>,
 }
 
 impl MarkdownProcessor {
     /// Creates a new MarkdownProcessor with default settings.
     pub fn new() -> Self {
-        let allowed_tags = ALLOWED_HTML_TAGS
-            .iter()
-            .map(|&tag| tag.to_string())
-            .collect();
+        let content = ContentConfig::default();
 
         Self {
             options: MarkdownOptions::empty(),
             config: ProcessorConfig::default(),
-            allowed_tags: Arc::new(allowed_tags),
+            // Unsafe per-page overrides must be opted into explicitly
+            profile: Profile::Production,
+            allowed_tags: to_lowercase_set(content.allowed_html_tags),
+            allowed_attributes: to_lowercase_set(
+                content.allowed_html_attributes,
+            ),
+            allowed_protocols: to_lowercase_set(
+                content.allowed_protocols,
+            ),
+            #[cfg(feature = "parallel-markdown")]
+            parallel: None,
         }
     }
 
     /// Enables table support in Markdown processing.
     pub fn with_tables(mut self, enable: bool) -> Self {
-        if enable {
-            self.options.insert(MarkdownOptions::ENABLE_TABLES);
-        } else {
-            self.options.remove(MarkdownOptions::ENABLE_TABLES);
-        }
+        self.options.set(MarkdownOptions::ENABLE_TABLES, enable);
         self
     }
 
     /// Enables strikethrough support in Markdown processing.
     pub fn with_strikethrough(mut self, enable: bool) -> Self {
-        if enable {
-            self.options.insert(MarkdownOptions::ENABLE_STRIKETHROUGH);
-        } else {
-            self.options.remove(MarkdownOptions::ENABLE_STRIKETHROUGH);
-        }
+        self.options
+            .set(MarkdownOptions::ENABLE_STRIKETHROUGH, enable);
         self
     }
 
     /// Enables footnote support in Markdown processing.
     pub fn with_footnotes(mut self, enable: bool) -> Self {
-        if enable {
-            self.options.insert(MarkdownOptions::ENABLE_FOOTNOTES);
-        } else {
-            self.options.remove(MarkdownOptions::ENABLE_FOOTNOTES);
+        self.options.set(MarkdownOptions::ENABLE_FOOTNOTES, enable);
+        self
+    }
+
+    /// Enables `- [ ]` and `- [x]` task lists, rendered with disabled
+    /// checkboxes.
+    pub fn with_tasklists(mut self, enable: bool) -> Self {
+        self.options.set(MarkdownOptions::ENABLE_TASKLISTS, enable);
+        self
+    }
+
+    /// Enables `{#id .class}` attributes on headings.
+    pub fn with_heading_attributes(mut self, enable: bool) -> Self {
+        self.options
+            .set(MarkdownOptions::ENABLE_HEADING_ATTRIBUTES, enable);
+        self
+    }
+
+    /// Enables definition lists: a term on one line followed by
+    /// `: definition` lines.
+    pub fn with_definition_lists(mut self, enable: bool) -> Self {
+        self.options
+            .set(MarkdownOptions::ENABLE_DEFINITION_LIST, enable);
+        self
+    }
+
+    /// Enables typographic punctuation: curly quotes, en and em dashes
+    /// for `--` and `---`, and ellipses for `...`.
+    pub fn with_smart_punctuation(mut self, enable: bool) -> Self {
+        self.config.smart_punctuation = enable;
+        self
+    }
+
+    /// Sets how footnotes are numbered and rendered.
+    ///
+    /// With [`FootnoteNumbering::Continuous`], each page's footnotes
+    /// are numbered on from the start passed to
+    /// [`MarkdownProcessor::process_page`].
+    pub fn with_footnote_config(
+        mut self,
+        footnotes: FootnoteConfig,
+    ) -> Self {
+        self.config.footnotes = footnotes;
+        self
+    }
+
+    /// Sets the reading speed reading times are estimated with.
+    pub fn with_words_per_minute(
+        mut self,
+        words_per_minute: usize,
+    ) -> Self {
+        self.config.words_per_minute = words_per_minute;
+        self
+    }
+
+    /// Enables or disables the extensions a [`MarkdownExtensions`]
+    /// sets, leaving those it does not set unchanged.
+    pub fn with_extensions(
+        mut self,
+        extensions: &MarkdownExtensions,
+    ) -> Self {
+        if let Some(enable) = extensions.smart_punctuation {
+            self.config.smart_punctuation = enable;
+        }
+        for (value, flag) in [
+            (extensions.tables, MarkdownOptions::ENABLE_TABLES),
+            (
+                extensions.strikethrough,
+                MarkdownOptions::ENABLE_STRIKETHROUGH,
+            ),
+            (extensions.footnotes, MarkdownOptions::ENABLE_FOOTNOTES),
+            (extensions.tasklists, MarkdownOptions::ENABLE_TASKLISTS),
+            (
+                extensions.heading_attributes,
+                MarkdownOptions::ENABLE_HEADING_ATTRIBUTES,
+            ),
+            (
+                extensions.definition_lists,
+                MarkdownOptions::ENABLE_DEFINITION_LIST,
+            ),
+        ] {
+            if let Some(enable) = value {
+                self.options.set(flag, enable);
+            }
         }
         self
     }
@@ -204,17 +749,129 @@ pub fn with_config(mut self, config: ProcessorConfig) -> Self {
         self
     }
 
+    /// Replaces the HTML tags kept by the sanitizer, typically with
+    /// [`ContentConfig::allowed_html_tags`].
+    pub fn with_allowed_tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowed_tags = to_lowercase_set(tags);
+        self
+    }
+
+    /// Replaces the attributes kept on allowed tags, typically with
+    /// [`ContentConfig::allowed_html_attributes`].
+    pub fn with_allowed_attributes<I, S>(
+        mut self,
+        attributes: I,
+    ) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowed_attributes = to_lowercase_set(attributes);
+        self
+    }
+
+    /// Replaces the URL schemes permitted in links and sources, typically
+    /// with [`ContentConfig::allowed_protocols`].
+    pub fn with_allowed_protocols<I, S>(mut self, protocols: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowed_protocols = to_lowercase_set(protocols);
+        self
+    }
+
+    /// Applies the sanitization policy from a [`ContentConfig`]: whether
+    /// to sanitize, and the allowed tags, attributes and protocols, and
+    /// its Markdown extensions.
+    pub fn with_content_config(
+        mut self,
+        content: &ContentConfig,
+    ) -> Self {
+        self.config.sanitize = content.sanitize;
+        self.config.footnotes = content.footnotes.clone();
+        self.config.words_per_minute = content.words_per_minute;
+        self.with_extensions(&content.markdown)
+            .with_allowed_tags(
+                content.allowed_html_tags.iter().cloned(),
+            )
+            .with_allowed_attributes(
+                content.allowed_html_attributes.iter().cloned(),
+            )
+            .with_allowed_protocols(
+                content.allowed_protocols.iter().cloned(),
+            )
+    }
+
+    /// Sets the build profile, which controls whether frontmatter may
+    /// disable sanitization for a page.
+    pub fn with_profile(mut self, profile: Profile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Parses long documents in chunks on a thread pool.
+    ///
+    /// Documents are split only where each chunk renders exactly as it
+    /// would as part of the whole, so the output does not change.
+    ///
+    /// # Arguments
+    /// * `config` - How long a document must be before it is split, and
+    ///   how large its chunks are.
+    #[cfg(feature = "parallel-markdown")]
+    pub fn with_parallel_parsing(
+        mut self,
+        config: ChunkConfig,
+    ) -> Self {
+        self.parallel = Some(config);
+        self
+    }
+
     /// Extracts and validates metadata from Markdown content.
     fn extract_metadata(
         &self,
         content: &str,
     ) -> Result<ContentMetadata> {
+        let mut frontmatter = Self::parse_frontmatter(content);
+        for key in OVERRIDE_KEYS {
+            _ = frontmatter.remove(*key);
+        }
+
         let mut metadata = ContentMetadata::default();
+        Self::process_metadata(&mut metadata, frontmatter)?;
+
+        let body = split_frontmatter(content)
+            .map_or(content, |(_, body)| body);
+        metadata.reading = ReadingStats::new(
+            &markdown_text(body, self.options),
+            self.config.words_per_minute,
+        );
+
+        // Extract title from first H1 if not found in frontmatter
+        if metadata.title.is_none() {
+            for line in content.lines() {
+                if let Some(title) = line.strip_prefix("# ") {
+                    metadata.title = Some(title.trim().to_string());
+                    break;
+                }
+            }
+        }
+
+        Ok(metadata)
+    }
+
+    /// Parses YAML frontmatter, returning an empty map if there is none.
+    pub fn parse_frontmatter(
+        content: &str,
+    ) -> HashMap<String, JsonValue> {
         let mut lines = content.lines();
+        let mut frontmatter = String::with_capacity(1024);
 
-        // Handle YAML frontmatter
         if content.starts_with("---\n") {
-            let mut frontmatter = String::with_capacity(1024);
             let _ = lines.next(); // Skip first "---"
 
             for line in lines.by_ref() {
@@ -224,25 +881,15 @@ fn extract_metadata(
                 frontmatter.push_str(line);
                 frontmatter.push('\n');
             }
-
-            if let Ok(yaml) =
-                from_str::<HashMap<String, JsonValue>>(&frontmatter)
-            {
-                Self::process_metadata(&mut metadata, yaml)?;
-            }
         }
 
-        // Extract title from first H1 if not found in frontmatter
-        if metadata.title.is_none() {
-            for line in content.lines() {
-                if let Some(title) = line.strip_prefix("# ") {
-                    metadata.title = Some(title.trim().to_string());
-                    break;
-                }
-            }
-        }
+        from_str::<HashMap<String, JsonValue>>(&frontmatter)
+            .unwrap_or_default()
+    }
 
-        Ok(metadata)
+    /// Extracts per-page processor overrides from the frontmatter.
+    fn extract_overrides(content: &str) -> Result<PageOverrides> {
+        PageOverrides::take_from(&mut Self::parse_frontmatter(content))
     }
 
     /// Processes and validates metadata from YAML frontmatter.
@@ -282,6 +929,15 @@ fn process_metadata(
                             .collect();
                     }
                 }
+                "bundles" => {
+                    metadata.bundles = serde_json::from_value(value)
+                        .map_err(|e| {
+                            ProcessingError::validation(
+                                format!("Invalid bundles: {}", e),
+                                Some("bundles"),
+                            )
+                        })?;
+                }
                 _ => {
                     let _ = metadata.custom.insert(key, value);
                 }
@@ -291,75 +947,37 @@ fn process_metadata(
         Ok(())
     }
 
-    /// Generates an accessible Table of Contents.
-    fn generate_toc(&self, content: &str) -> Result<String> {
+    /// Generates an accessible Table of Contents of the headings from
+    /// `min_level` to `max_level`.
+    fn generate_toc(
+        &self,
+        headings: &[TocEntry],
+        min_level: u8,
+        max_level: u8,
+    ) -> Result<String> {
         let mut toc = String::from(
         "<nav class=\"toc\" aria-label=\"Table of Contents\">\n<ul>\n",
     );
-        let mut entries = Vec::new();
-        let parser = Parser::new_ext(content, self.options);
-        let mut current_text = String::new();
-        let mut current_level = None;
-
-        for event in parser {
-            match event {
-                Event::Start(Tag::Heading { level, .. }) => {
-                    current_text.clear();
-                    current_level = Some(level);
-                }
-                Event::Text(text) => {
-                    current_text.push_str(&text);
-                }
-                Event::End(_) => {
-                    if let Some(level) = current_level.take() {
-                        let level_num = match level {
-                            HeadingLevel::H1 => 1,
-                            HeadingLevel::H2 => 2,
-                            HeadingLevel::H3 => 3,
-                            HeadingLevel::H4 => 4,
-                            HeadingLevel::H5 => 5,
-                            HeadingLevel::H6 => 6,
-                        };
-
-                        if level_num <= self.config.toc_max_level {
-                            let id =
-                                self.generate_heading_id(&current_text);
-                            entries.push(TocEntry {
-                                text: current_text.clone(),
-                                level: level_num,
-                                id,
-                            });
-                        }
-                    }
-                }
-                _ => {}
-            }
-        }
+        let entries: Vec<&TocEntry> = headings
+            .iter()
+            .filter(|heading| {
+                (min_level..=max_level).contains(&heading.level)
+            })
+            .collect();
 
-        self.build_toc_html(&mut toc, &entries)?;
+        self.build_toc_html(&mut toc, &entries, min_level)?;
         toc.push_str("</ul>\n</nav>");
         Ok(toc)
     }
 
-    /// Generates a unique ID for a heading.
-    fn generate_heading_id(&self, text: &str) -> String {
-        text.to_lowercase()
-            .chars()
-            .filter_map(|c| match c {
-                'a'..='z' | '0'..='9' => Some(c),
-                ' ' | '-' | '_' => Some('-'),
-                _ => None,
-            })
-            .collect()
-    }
-
     /// Builds the HTML structure for the Table of Contents.
     fn build_toc_html(
         &self,
         toc: &mut String,
-        entries: &[TocEntry],
+        entries: &[&TocEntry],
+        min_level: u8,
     ) -> Result<()> {
-        let mut current_level = 1;
+        let mut current_level = min_level;
 
         for entry in entries {
             while entry.level > current_level {
@@ -377,7 +995,7 @@ fn build_toc_html(
             ));
         }
 
-        while current_level > 1 {
+        while current_level > min_level {
             toc.push_str("</ul>\n");
             current_level -= 1;
         }
@@ -386,44 +1004,273 @@ fn build_toc_html(
     }
 
     /// Sanitizes HTML content to prevent XSS and other injection attacks.
+    ///
+    /// Tags outside the allowlist are removed, keeping their text, while
+    /// `script` and `style` elements are dropped with their contents.
+    /// Only allowed attributes survive, and absolute URLs are limited to
+    /// the allowed protocols.
     fn sanitize_html(&self, html: &str) -> Result<String> {
-        let mut output = String::with_capacity(html.len());
-        let mut in_tag = false;
-        let mut current_tag = String::new();
-
-        for c in html.chars() {
-            match c {
-                '<' => {
-                    in_tag = true;
-                    current_tag.clear();
-                }
-                '>' if in_tag => {
-                    in_tag = false;
-                    let tag_name = current_tag
-                        .split_whitespace()
-                        .next()
-                        .unwrap_or("")
-                        .trim_start_matches('/')
-                        .to_lowercase();
-
-                    if self.allowed_tags.contains(&tag_name) {
-                        output.push('<');
-                        output.push_str(&current_tag);
-                        output.push('>');
-                    }
-                }
-                _ if in_tag => {
-                    current_tag.push(c);
+        let tags: HashSet<&str> =
+            self.allowed_tags.iter().map(String::as_str).collect();
+        let clean_content_tags: HashSet<&str> = ["script", "style"]
+            .into_iter()
+            .filter(|tag| !tags.contains(tag))
+            .collect();
+
+        Ok(Builder::default()
+            .tags(tags)
+            .clean_content_tags(clean_content_tags)
+            .tag_attributes(HashMap::new())
+            .generic_attributes(
+                self.allowed_attributes
+                    .iter()
+                    .map(String::as_str)
+                    .collect(),
+            )
+            .url_schemes(
+                self.allowed_protocols
+                    .iter()
+                    .map(String::as_str)
+                    .collect(),
+            )
+            .link_rel(None)
+            .clean(html)
+            .to_string())
+    }
+
+    /// Renders Markdown to HTML, replacing permitted `{=html}` blocks
+    /// with placeholders and giving every heading a unique `id`.
+    ///
+    /// Footnotes are numbered on from `footnote_start`.
+    ///
+    /// # Returns
+    /// * `(String, Vec<RawBlock>, Vec<TocEntry>)` - The rendered HTML,
+    ///   the raw blocks, indexed by placeholder number, and the headings,
+    ///   in document order.
+    fn render_markdown(
+        &self,
+        content: &str,
+        options: MarkdownOptions,
+        config: &ProcessorConfig,
+        footnote_start: usize,
+    ) -> (String, Vec<RawBlock>, Vec<TocEntry>) {
+        let rendered = self.render_chunks(
+            content,
+            options,
+            config,
+            footnote_start,
+        );
+        let raw_html = rendered
+            .1
+            .iter()
+            .filter(|block| matches!(block, RawBlock::Html(_)))
+            .count();
+
+        if raw_html > 0 && self.profile == Profile::Production {
+            tracing::warn!(
+                "Security: {} raw HTML block(s) bypass sanitization in a production build",
+                raw_html
+            );
+        }
+        rendered
+    }
+
+    /// Renders a document in one pass.
+    #[cfg(not(feature = "parallel-markdown"))]
+    fn render_chunks(
+        &self,
+        content: &str,
+        options: MarkdownOptions,
+        config: &ProcessorConfig,
+        footnote_start: usize,
+    ) -> (String, Vec<RawBlock>, Vec<TocEntry>) {
+        render_document(content, options, config, footnote_start)
+    }
+
+    /// Renders a document in parallel chunks if parallel parsing is
+    /// enabled and the document is long enough, renumbering the raw
+    /// HTML placeholders of later chunks.
+    ///
+    /// Headings are collected from every chunk first, so their IDs are
+    /// unique across the whole document.
+    #[cfg(feature = "parallel-markdown")]
+    fn render_chunks(
+        &self,
+        content: &str,
+        options: MarkdownOptions,
+        config: &ProcessorConfig,
+        footnote_start: usize,
+    ) -> (String, Vec<RawBlock>, Vec<TocEntry>) {
+        use rayon::prelude::*;
+
+        let chunks = match &self.parallel {
+            Some(config) => config.split(content),
+            None => vec![content],
+        };
+        if chunks.len() == 1 {
+            return render_document(
+                content,
+                options,
+                config,
+                footnote_start,
+            );
+        }
+
+        let scanned: Vec<Vec<TocEntry>> = chunks
+            .par_iter()
+            .map(|chunk| scan_headings(chunk, options))
+            .collect();
+        let counts: Vec<usize> = scanned.iter().map(Vec::len).collect();
+        let headings =
+            assign_heading_ids(scanned.into_iter().flatten().collect());
+        let mut remaining = headings.as_slice();
+        let chunk_headings: Vec<&[TocEntry]> = counts
+            .iter()
+            .map(|&count| {
+                let (chunk, rest) = remaining.split_at(count);
+                remaining = rest;
+                chunk
+            })
+            .collect();
+
+        let rendered: Vec<(String, Vec<RawBlock>)> = chunks
+            .par_iter()
+            .zip(chunk_headings)
+            .map(|(chunk, headings)| {
+                render_events(
+                    chunk,
+                    options,
+                    config,
+                    headings,
+                    footnote_start,
+                )
+            })
+            .collect();
+        let mut html_output = String::with_capacity(content.len() * 2);
+        let mut raw_blocks = Vec::new();
+        for (html, blocks) in rendered {
+            let offset = raw_blocks.len();
+            html_output
+                .push_str(&renumber_raw_html(html, &blocks, offset));
+            raw_blocks.extend(blocks);
+        }
+        (html_output, raw_blocks, headings)
+    }
+
+    /// Processes a page, numbering its footnotes on from
+    /// `footnote_start` with [`FootnoteNumbering::Continuous`].
+    ///
+    /// `footnote_start` is the number of footnotes on the pages before
+    /// this one, in site order; see [`count_footnotes`]. As it is
+    /// passed in, a page is numbered the same whatever order pages are
+    /// processed in. [`Processor::process`] numbers from 1.
+    ///
+    /// # Arguments
+    /// * `content` - The page's Markdown, with its frontmatter.
+    /// * `context` - Configuration fields overriding the processor's
+    ///   own, such as `{ "toc": true }`.
+    /// * `footnote_start` - The footnotes numbered before the page.
+    ///
+    /// # Returns
+    /// * `Result<String>` - The page's HTML, or an error if processing
+    ///   fails.
+    pub fn process_page(
+        &self,
+        content: String,
+        context: Option<&JsonValue>,
+        footnote_start: usize,
+    ) -> Result<String> {
+        // Validate content
+        self.validate(&content)?;
+
+        // Validate metadata and extract per-page overrides
+        _ = self.extract_metadata(&content)?;
+        let overrides = Self::extract_overrides(&content)?;
+
+        // Merge the context's fields over the processor's own
+        // configuration
+        let config = self.context_config(context);
+        let (config, options) =
+            overrides.apply(&config, self.options, self.profile)?;
+
+        // Number footnotes from 1, or on from the pages before
+        let footnote_start = match config.footnotes.numbering {
+            FootnoteNumbering::Page => 0,
+            FootnoteNumbering::Continuous => footnote_start,
+        };
+
+        // Parse Markdown to HTML, setting trusted raw blocks aside
+        let (mut html_output, raw_blocks, headings) = self
+            .render_markdown(
+                &content,
+                options,
+                &config,
+                footnote_start,
+            );
+
+        // Generate the TOC if enabled, placing it at the marker or
+        // before the content, or setting it aside
+        let mut standalone_toc = None;
+        if config.toc {
+            let toc = self.generate_toc(
+                &headings,
+                config.toc_min_level,
+                config.toc_max_level,
+            )?;
+            match config.toc_placement {
+                TocPlacement::Content
+                    if html_output.contains(TOC_MARKER) =>
+                {
+                    html_output =
+                        html_output.replacen(TOC_MARKER, &toc, 1);
                 }
-                _ => {
-                    if !in_tag {
-                        output.push(c);
-                    }
+                TocPlacement::Content => {
+                    html_output = format!("{}\n{}", toc, html_output);
                 }
+                TocPlacement::Standalone => standalone_toc = Some(toc),
+            }
+        }
+
+        // Sanitize if enabled
+        let sanitize = |html: String| {
+            if config.sanitize {
+                self.sanitize_html(&html)
+            } else {
+                Ok(html)
             }
+        };
+        let processed =
+            restore_raw_html(sanitize(html_output)?, &raw_blocks);
+        match standalone_toc {
+            Some(toc) => Ok(format!(
+                "{}{}{}",
+                sanitize(toc)?,
+                TOC_SEPARATOR,
+                processed
+            )),
+            None => Ok(processed),
         }
+    }
 
-        Ok(output)
+    /// Returns the processor's configuration with the fields a
+    /// context sets merged over it, or unchanged if the context is not
+    /// an object or its fields are invalid.
+    fn context_config(
+        &self,
+        context: Option<&JsonValue>,
+    ) -> ProcessorConfig {
+        let own = serde_json::to_value(&self.config);
+        match (own, context) {
+            (
+                Ok(JsonValue::Object(mut own)),
+                Some(JsonValue::Object(fields)),
+            ) => {
+                own.extend(fields.clone());
+                serde_json::from_value(JsonValue::Object(own))
+                    .unwrap_or_else(|_| self.config.clone())
+            }
+            _ => self.config.clone(),
+        }
     }
 
     /// Validates that the content is safe to process.
@@ -490,51 +1337,547 @@ fn process(
         content: String,
         context: Option<&Self::Context>,
     ) -> Result<Self::Output> {
-        // Validate content
-        self.validate(&content)?;
+        self.process_page(content, context, 0)
+    }
+}
+
+/// Splits a standalone Table of Contents from the content of a
+/// processor's output.
+///
+/// # Arguments
+/// * `output` - The output of [`MarkdownProcessor`], or of any other
+///   content processor.
+///
+/// # Returns
+/// * `(Option<&str>, &str)` - The Table of Contents, if the output
+///   carries one, and the content.
+pub fn split_toc(output: &str) -> (Option<&str>, &str) {
+    match output.split_once(TOC_SEPARATOR) {
+        Some((toc, content)) => (Some(toc), content),
+        None => (None, output),
+    }
+}
 
-        // Extract metadata
-        let metadata = self.extract_metadata(&content)?;
+/// Returns the number of footnotes a page numbers: those it cites and
+/// those it defines without citing.
+///
+/// Summed over the pages before a page, in site order, it gives the
+/// page's `footnote_start`; see [`MarkdownProcessor::process_page`].
+pub fn count_footnotes(content: &str) -> usize {
+    let mut labels = HashSet::new();
+    for event in
+        Parser::new_ext(content, MarkdownOptions::ENABLE_FOOTNOTES)
+    {
+        match event {
+            Event::FootnoteReference(label)
+            | Event::Start(Tag::FootnoteDefinition(label)) => {
+                _ = labels.insert(label.to_lowercase());
+            }
+            _ => {}
+        }
+    }
+    labels.len()
+}
 
-        // Parse configuration from context
-        let config: ProcessorConfig = context
-            .and_then(|ctx| serde_json::from_value(ctx.clone()).ok())
-            .unwrap_or_default();
+/// Returns the ID the table of contents links a heading's text to.
+pub(crate) fn heading_id(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            'a'..='z' | '0'..='9' => Some(c),
+            ' ' | '-' | '_' => Some('-'),
+            _ => None,
+        })
+        .collect()
+}
 
-        // Parse Markdown to HTML
-        let parser = Parser::new_ext(&content, self.options);
-        let mut html_output = String::with_capacity(content.len() * 2);
-        html::push_html(&mut html_output, parser);
+/// Collects allowlist entries into a shared, lowercased set.
+fn to_lowercase_set<I, S>(entries: I) -> Arc<HashSet<String>>
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    Arc::new(
+        entries
+            .into_iter()
+            .map(|entry| entry.into().to_lowercase())
+            .collect(),
+    )
+}
 
-        // Generate and prepend TOC if enabled
-        if config.toc {
-            let toc = self.generate_toc(&content)?;
-            println!("Generated ToC: {}", toc); // Debugging line
-            html_output = format!("{}\n{}", toc, html_output);
+/// Collects a document's headings, with their explicit `{#id}` if
+/// they have one and an empty ID otherwise.
+fn scan_headings(
+    content: &str,
+    options: MarkdownOptions,
+) -> Vec<TocEntry> {
+    let mut headings = Vec::new();
+    let mut current: Option<TocEntry> = None;
+
+    for event in Parser::new_ext(content, options) {
+        match event {
+            Event::Start(Tag::Heading { level, id, .. }) => {
+                current = Some(TocEntry {
+                    text: String::new(),
+                    level: level as u8,
+                    id: id.map(|id| id.to_string()).unwrap_or_default(),
+                });
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some(heading) = current.as_mut() {
+                    heading.text.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                headings.extend(current.take());
+            }
+            _ => {}
         }
+    }
+    headings
+}
 
-        // Sanitize if enabled
-        let processed = if config.sanitize {
-            self.sanitize_html(&html_output)?
-        } else {
-            html_output
-        };
+/// Returns the text of a Markdown document, without its markup.
+fn markdown_text(content: &str, options: MarkdownOptions) -> String {
+    let mut text = String::with_capacity(content.len());
+    for event in Parser::new_ext(content, options) {
+        if let Event::Text(words) | Event::Code(words) = event {
+            text.push_str(&words);
+            text.push(' ');
+        }
+    }
+    text
+}
 
-        // Add metadata as JSON-LD if present
-        if !metadata.custom.is_empty() {
-            let json_ld = serde_json::to_string(&metadata.custom)
-                .map_err(|e| ProcessingError::ContentProcessing {
-                    details: "Failed to serialize metadata".to_string(),
-                    source: Some(Box::new(e)),
-                })?;
-            Ok(format!(
-                "{}\n<script type=\"application/ld+json\">{}</script>",
-                processed, json_ld
-            ))
-        } else {
-            Ok(processed)
+/// Gives each heading without an explicit ID one made from its text,
+/// suffixed with `-1`, `-2` and so on when it is already taken.
+fn assign_heading_ids(mut headings: Vec<TocEntry>) -> Vec<TocEntry> {
+    let mut taken: HashSet<String> = headings
+        .iter()
+        .filter(|heading| !heading.id.is_empty())
+        .map(|heading| heading.id.clone())
+        .collect();
+
+    for heading in
+        headings.iter_mut().filter(|heading| heading.id.is_empty())
+    {
+        let mut base = heading_id(&heading.text);
+        if base.is_empty() {
+            base = "section".to_string();
+        }
+        let id = std::iter::once(base.clone())
+            .chain((1..).map(|n| format!("{}-{}", base, n)))
+            .find(|id| !taken.contains(id))
+            .unwrap_or(base);
+        _ = taken.insert(id.clone());
+        heading.id = id;
+    }
+    headings
+}
+
+/// Renders a whole document in one pass.
+fn render_document(
+    content: &str,
+    options: MarkdownOptions,
+    config: &ProcessorConfig,
+    footnote_start: usize,
+) -> (String, Vec<RawBlock>, Vec<TocEntry>) {
+    let headings = assign_heading_ids(scan_headings(content, options));
+    let (html_output, raw_blocks) = render_events(
+        content,
+        options,
+        config,
+        &headings,
+        footnote_start,
+    );
+    (html_output, raw_blocks, headings)
+}
+
+/// Renders Markdown to HTML, replacing permitted `{=html}` blocks
+/// with placeholders numbered from zero, rendering the headings
+/// with the IDs of `headings`, in order, and numbering footnotes on
+/// from `footnote_start`.
+fn render_events(
+    content: &str,
+    options: MarkdownOptions,
+    config: &ProcessorConfig,
+    headings: &[TocEntry],
+    footnote_start: usize,
+) -> (String, Vec<RawBlock>) {
+    let allow_raw_html = config.allow_raw_html;
+    let mut raw_blocks = Vec::new();
+    let mut current_raw: Option<String> = None;
+    let mut events = Vec::new();
+    let mut current_diagram: Option<(&str, String)> = None;
+    let mut ids = headings.iter().map(|heading| heading.id.as_str());
+    let mut current_id = None;
+
+    for event in Parser::new_ext(content, options) {
+        match event {
+            Event::Start(Tag::Heading {
+                level,
+                id,
+                classes,
+                attrs,
+            }) => {
+                let id = ids
+                    .next()
+                    .map(|id| CowStr::from(id.to_string()))
+                    .or(id);
+                current_id = id.clone();
+                events.push(Event::Start(Tag::Heading {
+                    level: demote(level, config.heading_offset),
+                    id,
+                    classes,
+                    attrs,
+                }));
+            }
+            Event::End(TagEnd::Heading(level)) => {
+                if let Some(id) = current_id.take() {
+                    if config.heading_anchors {
+                        events.push(Event::InlineHtml(CowStr::from(
+                            heading_anchor(&id),
+                        )));
+                    }
+                }
+                events.push(Event::End(TagEnd::Heading(demote(
+                    level,
+                    config.heading_offset,
+                ))));
+            }
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(
+                ref info,
+            ))) if allow_raw_html && info.trim() == RAW_HTML_INFO => {
+                current_raw = Some(String::new());
+            }
+            Event::Text(ref text) if current_raw.is_some() => {
+                if let Some(raw) = current_raw.as_mut() {
+                    raw.push_str(text);
+                }
+            }
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(
+                ref info,
+            ))) if diagram_language(info, config).is_some() => {
+                current_diagram = diagram_language(info, config)
+                    .map(|language| (language, String::new()));
+            }
+            Event::Text(ref text) if current_diagram.is_some() => {
+                if let Some((_, source)) = current_diagram.as_mut() {
+                    source.push_str(text);
+                }
+            }
+            Event::End(TagEnd::CodeBlock)
+                if current_diagram.is_some() =>
+            {
+                if let Some((language, source)) = current_diagram.take()
+                {
+                    let diagram = match config.diagrams.get(language) {
+                        Some(diagram) => {
+                            diagram.render(language, &source)
+                        }
+                        None => DiagramConfig::default()
+                            .render(language, &source),
+                    };
+                    match diagram {
+                        Diagram::Client(html) => {
+                            events
+                                .push(Event::Html(CowStr::from(html)));
+                        }
+                        Diagram::Svg(svg) => {
+                            events.push(Event::Html(CowStr::from(
+                                raw_html_placeholder(raw_blocks.len()),
+                            )));
+                            raw_blocks.push(RawBlock::Diagram(svg));
+                        }
+                    }
+                }
+            }
+            Event::End(TagEnd::CodeBlock) if current_raw.is_some() => {
+                if let Some(raw) = current_raw.take() {
+                    events.push(Event::Html(CowStr::from(
+                        raw_html_placeholder(raw_blocks.len()),
+                    )));
+                    raw_blocks.push(RawBlock::Html(raw));
+                }
+            }
+            #[cfg(feature = "math")]
+            Event::InlineMath(ref tex)
+            | Event::DisplayMath(ref tex) => {
+                let display = matches!(event, Event::DisplayMath(_));
+                match render_math(tex, display, config.math_output) {
+                    Ok(math) => {
+                        events.push(Event::InlineHtml(CowStr::from(
+                            raw_html_placeholder(raw_blocks.len()),
+                        )));
+                        raw_blocks.push(RawBlock::Math(math));
+                    }
+                    Err(e) => {
+                        tracing::warn!(
+                            error = %e,
+                            "Leaving math '{}' unrendered",
+                            tex
+                        );
+                        events.push(event);
+                    }
+                }
+            }
+            Event::Html(ref html) if html.trim() == MORE_MARKER => {
+                events.push(Event::Html(CowStr::from(
+                    SUMMARY_MARKER.to_string(),
+                )));
+            }
+            Event::TaskListMarker(checked) => {
+                events.push(Event::InlineHtml(CowStr::from(
+                    raw_html_placeholder(raw_blocks.len()),
+                )));
+                raw_blocks.push(RawBlock::Checkbox(checked));
+            }
+            event => events.push(event),
         }
     }
+
+    let events =
+        place_footnotes(events, &config.footnotes, footnote_start);
+    let mut html_output = String::with_capacity(content.len() * 2);
+    html::push_html(&mut html_output, events.into_iter());
+    (html_output, raw_blocks)
+}
+
+/// Replaces footnote references with numbered links and moves the
+/// footnote definitions into a section at the end.
+///
+/// Footnotes are numbered in the order they are first cited, followed
+/// by any that are never cited, on from `footnote_start`. Each
+/// reference gets its own `fnref-` ID, so every one can be linked
+/// back to.
+fn place_footnotes<'a>(
+    events: Vec<Event<'a>>,
+    config: &FootnoteConfig,
+    footnote_start: usize,
+) -> Vec<Event<'a>> {
+    let mut body = Vec::with_capacity(events.len());
+    let mut definitions: Vec<(String, Vec<Event<'a>>)> = Vec::new();
+    let mut current: Option<(String, Vec<Event<'a>>)> = None;
+    let mut cited: Vec<String> = Vec::new();
+
+    for event in events {
+        match event {
+            Event::Start(Tag::FootnoteDefinition(label)) => {
+                current = Some((label.to_lowercase(), Vec::new()));
+            }
+            Event::End(TagEnd::FootnoteDefinition) => {
+                definitions.extend(current.take());
+            }
+            event => {
+                if let Event::FootnoteReference(ref label) = event {
+                    let label = label.to_lowercase();
+                    if !cited.contains(&label) {
+                        cited.push(label);
+                    }
+                }
+                match current.as_mut() {
+                    Some((_, definition)) => definition.push(event),
+                    None => body.push(event),
+                }
+            }
+        }
+    }
+    if cited.is_empty() && definitions.is_empty() {
+        return body;
+    }
+
+    // Footnotes never cited are listed after the cited ones
+    let mut order = cited;
+    for (label, _) in &definitions {
+        if !order.contains(label) {
+            order.push(label.clone());
+        }
+    }
+    let first = footnote_start + 1;
+    let number = |label: &str| {
+        order
+            .iter()
+            .position(|l| l == label)
+            .map_or(0, |i| first + i)
+    };
+
+    // Replaces references with links, counting each footnote's
+    // references for their IDs and backlinks
+    let mut references: HashMap<usize, usize> = HashMap::new();
+    let mut cite = |events: Vec<Event<'a>>| -> Vec<Event<'a>> {
+        events
+            .into_iter()
+            .map(|event| match event {
+                Event::FootnoteReference(label) => {
+                    let n = number(&label.to_lowercase());
+                    let count = references.entry(n).or_insert(0);
+                    *count += 1;
+                    Event::InlineHtml(CowStr::from(format!(
+                        "<sup class=\"footnote-ref\"><a href=\"#fn-{n}\" id=\"{id}\">{n}</a></sup>",
+                        n = n,
+                        id = footnote_ref_id(n, *count)
+                    )))
+                }
+                event => event,
+            })
+            .collect()
+    };
+    let mut output = cite(body);
+    let mut definitions: Vec<(usize, Vec<Event<'a>>)> = definitions
+        .into_iter()
+        .map(|(label, events)| (number(&label), cite(events)))
+        .collect();
+    definitions.sort_by_key(|(n, _)| *n);
+
+    let mut section = String::from("<section class=\"footnotes\">\n");
+    if !config.heading.is_empty() {
+        section.push_str(&format!(
+            "<h2>{}</h2>\n",
+            escape_html(&config.heading)
+        ));
+    }
+    if first == 1 {
+        section.push_str("<ol>\n");
+    } else {
+        section.push_str(&format!("<ol start=\"{}\">\n", first));
+    }
+    output.push(Event::Html(CowStr::from(section)));
+    for (n, mut definition) in definitions {
+        output.push(Event::Html(CowStr::from(format!(
+            "<li id=\"fn-{}\">",
+            n
+        ))));
+        let cited = references.get(&n).copied().unwrap_or(0);
+        if config.backlinks && cited > 0 {
+            let backlinks: String = (1..=cited)
+                .map(|count| {
+                    format!(
+                        " <a href=\"#{}\" class=\"footnote-backref\" aria-label=\"Back to reference {}\">\u{21a9}</a>",
+                        footnote_ref_id(n, count),
+                        n
+                    )
+                })
+                .collect();
+            // Inside the last paragraph, so the links follow its text
+            let at = match definition.last() {
+                Some(Event::End(TagEnd::Paragraph)) => {
+                    definition.len() - 1
+                }
+                _ => definition.len(),
+            };
+            definition
+                .insert(at, Event::InlineHtml(CowStr::from(backlinks)));
+        }
+        output.extend(definition);
+        output.push(Event::Html(CowStr::from("</li>\n")));
+    }
+    output.push(Event::Html(CowStr::from("</ol>\n</section>\n")));
+    output
+}
+
+/// Returns the ID of a footnote's `count`th reference.
+fn footnote_ref_id(number: usize, count: usize) -> String {
+    if count == 1 {
+        format!("fnref-{}", number)
+    } else {
+        format!("fnref-{}-{}", number, count)
+    }
+}
+
+/// Renders TeX to MathML or HTML with KaTeX.
+#[cfg(feature = "math")]
+fn render_math(
+    tex: &str,
+    display: bool,
+    output: MathOutput,
+) -> std::result::Result<String, katex::Error> {
+    let mut opts = katex::Opts::default();
+    opts.set_display_mode(display);
+    opts.set_output_type(match output {
+        MathOutput::Mathml => katex::OutputType::Mathml,
+        MathOutput::Html => katex::OutputType::Html,
+        MathOutput::Both => katex::OutputType::HtmlAndMathml,
+    });
+    katex::render_with_opts(tex, &opts)
+}
+
+/// Returns the diagram language of a fenced code block's info string,
+/// if it is a built-in or configured one.
+fn diagram_language<'a>(
+    info: &str,
+    config: &'a ProcessorConfig,
+) -> Option<&'a str> {
+    let language = info.split_whitespace().next()?;
+    DIAGRAM_LANGUAGES
+        .iter()
+        .copied()
+        .find(|builtin| *builtin == language)
+        .or_else(|| {
+            config
+                .diagrams
+                .get_key_value(language)
+                .map(|(key, _)| key.as_str())
+        })
+}
+
+/// Demotes a heading level by `offset` levels, down to `<h6>`.
+fn demote(level: HeadingLevel, offset: u8) -> HeadingLevel {
+    let demoted = (level as usize + usize::from(offset)).min(6);
+    HeadingLevel::try_from(demoted).unwrap_or(HeadingLevel::H6)
+}
+
+/// Returns the `¶` permalink appended to a heading.
+fn heading_anchor(id: &str) -> String {
+    format!(
+        " <a class=\"heading-anchor\" href=\"#{}\" aria-label=\"Permalink\">¶</a>",
+        escape_html(id)
+    )
+}
+
+/// Escapes text for use in HTML content or a quoted attribute.
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Returns the placeholder standing in for a raw HTML block.
+fn raw_html_placeholder(index: usize) -> String {
+    format!("{marker}{}{marker}", index, marker = RAW_HTML_MARKER)
+}
+
+/// Shifts the raw HTML placeholders of a chunk by the number of blocks
+/// in the chunks before it.
+#[cfg(feature = "parallel-markdown")]
+fn renumber_raw_html(
+    html: String,
+    raw_blocks: &[RawBlock],
+    offset: usize,
+) -> String {
+    if offset == 0 {
+        return html;
+    }
+    // Highest first, so a shifted number is never shifted again
+    (0..raw_blocks.len()).rev().fold(html, |html, index| {
+        html.replace(
+            &raw_html_placeholder(index),
+            &raw_html_placeholder(index + offset),
+        )
+    })
+}
+
+/// Substitutes raw HTML placeholders with the original snippets.
+fn restore_raw_html(html: String, raw_blocks: &[RawBlock]) -> String {
+    if raw_blocks.is_empty() {
+        return html;
+    }
+    raw_blocks
+        .iter()
+        .enumerate()
+        .fold(html, |html, (index, raw)| {
+            html.replace(&raw_html_placeholder(index), raw.html())
+        })
 }
 
 // Helper functions for default values
@@ -542,6 +1885,14 @@ fn default_true() -> bool {
     true
 }
 
+fn default_words_per_minute() -> usize {
+    DEFAULT_WORDS_PER_MINUTE
+}
+
+fn default_toc_min_level() -> u8 {
+    1
+}
+
 fn default_toc_level() -> u8 {
     3
 }
@@ -556,7 +1907,7 @@ fn test_markdown_processor_basic() {
         let processor = MarkdownProcessor::new();
         let input = "# Test\n\nThis is a **test**.";
         let result = processor.process(input.to_owned(), None).unwrap();
-        assert!(result.contains("<h1>"));
+        assert!(result.contains("<h1 id=\"test\">"));
         assert!(result.contains("<strong>"));
     }
 
@@ -597,6 +1948,35 @@ fn test_metadata_extraction() {
         assert_eq!(metadata.date, Some("2024-01-01".to_string()));
         assert_eq!(metadata.tags, vec!["test", "example"]);
         assert!(metadata.custom.contains_key("custom_field"));
+        assert_eq!(
+            metadata.reading,
+            ReadingStats {
+                word_count: 1,
+                reading_time: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_reading_stats() {
+        let stats = ReadingStats::new("One, two - `three`!", 2);
+        assert_eq!(stats.word_count, 3);
+        assert_eq!(stats.reading_time, 2);
+        assert_eq!(
+            ReadingStats::new(" - ", 200),
+            ReadingStats::default()
+        );
+
+        let input = format!(
+            "---\ntitle: Long\n---\n# Long\n\n{}",
+            "word ".repeat(250)
+        );
+        let metadata = MarkdownProcessor::new()
+            .with_words_per_minute(100)
+            .extract_metadata(&input)
+            .unwrap();
+        assert_eq!(metadata.reading.word_count, 251);
+        assert_eq!(metadata.reading.reading_time, 3);
     }
 
     #[test]
@@ -616,6 +1996,204 @@ fn test_toc_generation() {
         assert!(result.contains("<ul>"));
     }
 
+    #[test]
+    fn test_context_merges_config() {
+        let processor =
+            MarkdownProcessor::new().with_smart_punctuation(true);
+        let input = "# Title\n\n\"quoted\"";
+        let result = processor
+            .process_page(
+                input.to_owned(),
+                Some(&json!({ "toc": true })),
+                0,
+            )
+            .unwrap();
+        assert!(result.contains(r#"<nav class="toc""#));
+        assert!(result.contains("\u{201c}quoted\u{201d}"));
+
+        let ignored = processor
+            .process(
+                input.to_owned(),
+                Some(&json!({ "footnote_start": 3 })),
+            )
+            .unwrap();
+        assert!(ignored.contains("\u{201c}quoted\u{201d}"));
+    }
+
+    #[test]
+    fn test_toc_levels_and_marker() {
+        let processor = MarkdownProcessor::new();
+        let input = "# Title\n\nIntro\n\n<!-- toc -->\n\n## Setup\n\n\
+                     ### Install\n\n#### Details";
+        let context = json!({
+            "toc": true,
+            "toc_min_level": 2,
+            "toc_max_level": 3
+        });
+
+        let result = processor
+            .process(input.to_owned(), Some(&context))
+            .unwrap();
+        let toc = result.find("<nav class=\"toc\"").unwrap();
+        assert!(result.find("<p>Intro</p>").unwrap() < toc);
+        assert!(toc < result.find("<h2 id=\"setup\">").unwrap());
+        assert!(!result.contains(TOC_MARKER));
+        assert!(result.contains("<ul>\n<li><a href=\"#setup\""));
+        assert!(result.contains("href=\"#install\""));
+        assert!(!result.contains("href=\"#title\""));
+        assert!(!result.contains("href=\"#details\""));
+    }
+
+    #[test]
+    fn test_toc_standalone() {
+        let processor = MarkdownProcessor::new();
+        let context = json!({
+            "toc": true,
+            "toc_placement": "standalone"
+        });
+
+        let result = processor
+            .process("# One\n\n## Two".to_owned(), Some(&context))
+            .unwrap();
+        let (toc, content) = split_toc(&result);
+        let toc = toc.unwrap();
+        assert!(toc.starts_with("<nav class=\"toc\""));
+        assert!(toc.contains("href=\"#two\""));
+        assert!(content.starts_with("<h1 id=\"one\">One</h1>"));
+        assert!(!content.contains("<nav"));
+
+        assert_eq!(split_toc("<p>Plain</p>"), (None, "<p>Plain</p>"));
+    }
+
+    #[test]
+    fn test_math() {
+        let processor = MarkdownProcessor::new();
+        let input = "Euler: $e^{i\\pi} = -1$\n\n$$\\sum_i x_i$$";
+
+        let plain = processor.process(input.to_owned(), None).unwrap();
+        assert!(plain.contains("$e^{i\\pi} = -1$"));
+
+        let context = json!({ "math": true });
+        let result = processor
+            .process(input.to_owned(), Some(&context))
+            .unwrap();
+        assert!(!result.contains('$'));
+        #[cfg(feature = "math")]
+        {
+            assert!(result.contains("<math"));
+            assert!(result.contains("display=\"block\""));
+        }
+        #[cfg(not(feature = "math"))]
+        assert!(result.contains("e^{i\\pi} = -1"));
+    }
+
+    #[test]
+    fn test_diagrams() {
+        let input = "```mermaid\ngraph TD; A-->B\n```\n\n\
+                     ```dot\ndigraph { a -> b }\n```\n\n\
+                     ```rust\nfn main() {}\n```";
+
+        let result = MarkdownProcessor::new()
+            .process(input.to_owned(), None)
+            .unwrap();
+        assert!(result.contains(
+            "<pre class=\"mermaid\">graph TD; A--&gt;B</pre>"
+        ));
+        assert!(result.contains("<code class=\"language-dot\">"));
+        assert!(result.contains("<code class=\"language-rust\">"));
+
+        #[cfg(unix)]
+        {
+            let mut config = ProcessorConfig::default();
+            _ = config.diagrams.insert(
+                "dot".to_string(),
+                DiagramConfig {
+                    command: vec![
+                        "sh".to_string(),
+                        "-c".to_string(),
+                        "cat >/dev/null; echo '<svg><g/></svg>'"
+                            .to_string(),
+                    ],
+                },
+            );
+            let result = MarkdownProcessor::new()
+                .with_config(config)
+                .process(input.to_owned(), None)
+                .unwrap();
+            assert!(result.contains("<svg><g/></svg>"));
+            assert!(result.contains("<pre class=\"mermaid\">"));
+        }
+    }
+
+    #[test]
+    fn test_heading_offset() {
+        let processor =
+            MarkdownProcessor::new().with_config(ProcessorConfig {
+                heading_offset: 1,
+                ..ProcessorConfig::default()
+            });
+        let result = processor
+            .process("# Title\n\n###### Deep".to_owned(), None)
+            .unwrap();
+        assert!(result.contains("<h2 id=\"title\">Title</h2>"));
+        assert!(result.contains("<h6 id=\"deep\">Deep</h6>"));
+
+        let page = "---\nheading_offset: 2\ntoc: true\n---\n# Title";
+        let result = processor.process(page.to_owned(), None).unwrap();
+        assert!(result.contains("<h3 id=\"title\">Title</h3>"));
+        assert!(result.contains("href=\"#title\""));
+
+        let page = "---\nheading_offset: 0\n---\n# Title";
+        let result = processor.process(page.to_owned(), None).unwrap();
+        assert!(result.contains("<h1 id=\"title\">"));
+
+        let invalid = "---\nheading_offset: 6\n---\n# Title";
+        assert!(processor.process(invalid.to_owned(), None).is_err());
+    }
+
+    #[test]
+    fn test_heading_ids() {
+        let processor = MarkdownProcessor::new();
+        let input = "# Intro\n\n## Intro\n\n## `Intro`\n\n## !!!";
+        let context = json!({ "toc": true });
+
+        let result = processor
+            .process(input.to_owned(), Some(&context))
+            .unwrap();
+        for id in ["intro", "intro-1", "intro-2", "section"] {
+            assert!(result.contains(&format!("id=\"{}\"", id)));
+            assert!(result.contains(&format!("href=\"#{}\"", id)));
+        }
+        assert!(!result.contains("heading-anchor"));
+    }
+
+    #[test]
+    fn test_heading_ids_keep_explicit_ids() {
+        let processor = MarkdownProcessor::new();
+        let input = "---\nmarkdown:\n  heading_attributes: true\n---\n\
+                     ## Setup\n\n## Other {#setup}";
+
+        let result = processor.process(input.to_owned(), None).unwrap();
+        assert!(result.contains("<h2 id=\"setup-1\">Setup</h2>"));
+        assert!(result.contains("<h2 id=\"setup\">Other</h2>"));
+    }
+
+    #[test]
+    fn test_heading_anchors() {
+        let processor = MarkdownProcessor::new();
+        let context = json!({ "heading_anchors": true });
+
+        let result = processor
+            .process("## Getting started".to_owned(), Some(&context))
+            .unwrap();
+        assert_eq!(
+            result.trim(),
+            "<h2 id=\"getting-started\">Getting started \
+             <a class=\"heading-anchor\" href=\"#getting-started\" \
+             aria-label=\"Permalink\">¶</a></h2>"
+        );
+    }
+
     #[test]
     fn test_sanitization() {
         let processor = MarkdownProcessor::new();
@@ -651,8 +2229,7 @@ fn test_validation() {
 
     #[test]
     fn test_heading_id_generation() {
-        let processor = MarkdownProcessor::new();
-        let id = processor.generate_heading_id("Hello World! 123");
+        let id = heading_id("Hello World! 123");
         assert_eq!(id, "hello-world-123");
     }
 
@@ -669,6 +2246,26 @@ fn test_custom_metadata() {
 
         let metadata = processor.extract_metadata(input).unwrap();
         assert!(metadata.custom.contains_key("custom"));
+
+        // Custom metadata is not schema.org data, so it is not
+        // emitted as JSON-LD
+        let html = processor.process(input.to_owned(), None).unwrap();
+        assert!(!html.contains("application/ld+json"));
+    }
+
+    #[test]
+    fn test_bundles_metadata() {
+        let processor = MarkdownProcessor::new();
+        let input =
+            "---\nbundles:\n  css: [gallery.css]\n---\n# Gallery";
+
+        let metadata = processor.extract_metadata(input).unwrap();
+        assert_eq!(metadata.bundles.css, vec!["gallery.css"]);
+        assert!(metadata.bundles.js.is_empty());
+        assert!(!metadata.custom.contains_key("bundles"));
+
+        let input = "---\nbundles:\n  images: [a.png]\n---\n# Gallery";
+        assert!(processor.extract_metadata(input).is_err());
     }
 
     #[test]
@@ -687,4 +2284,335 @@ fn test_sanitization_with_allowed_tags() {
         assert!(!result.contains("<script>"));
         assert!(!result.contains("<iframe>"));
     }
+
+    #[test]
+    fn test_sanitization_attributes() {
+        let processor = MarkdownProcessor::new();
+        let input = r#"<a href="/docs" class="btn" onclick="steal()">Docs</a>
+<a href="javascript:alert(1)">Bad</a>
+<img src="a.png" alt="A" onerror="steal()">
+<p style="color:red">Text <b>bold</b></p>
+<script>alert('bad')</script>"#;
+        let result = processor.sanitize_html(input).unwrap();
+
+        assert!(
+            result.contains(r#"<a href="/docs" class="btn">Docs</a>"#)
+        );
+        assert!(result.contains(r#"<a>Bad</a>"#));
+        assert!(result.contains(r#"<img src="a.png" alt="A">"#));
+        assert!(result.contains("<p>Text bold</p>"));
+        assert!(!result.contains("onclick"));
+        assert!(!result.contains("alert"));
+    }
+
+    #[test]
+    fn test_with_allowed_tags() {
+        let processor = MarkdownProcessor::new()
+            .with_allowed_tags(["p", "Details"]);
+        let result = processor
+            .sanitize_html("<details><p>Hi</p></details><h1>T</h1>")
+            .unwrap();
+        assert_eq!(result, "<details><p>Hi</p></details>T");
+    }
+
+    #[test]
+    fn test_with_content_config() {
+        let mut content = ContentConfig::default();
+        content.allowed_html_tags.push("video".to_string());
+        content.allowed_html_attributes.push("controls".to_string());
+        content.allowed_protocols = vec!["https".to_string()];
+        let processor =
+            MarkdownProcessor::new().with_content_config(&content);
+
+        let result = processor
+            .sanitize_html(
+                r#"<video src="clip.mp4" controls></video><a href="http://a.test">A</a><a href="https://b.test">B</a>"#,
+            )
+            .unwrap();
+        assert!(
+            result.contains(r#"<video src="clip.mp4" controls="">"#)
+        );
+        assert!(result.contains("<a>A</a>"));
+        assert!(result.contains(r#"<a href="https://b.test">B</a>"#));
+    }
+
+    #[test]
+    fn test_markdown_extensions() {
+        let input =
+            "# Plan {#plan .wide}\n\n- [x] Done\n- [ ] Todo\n\n\
+                     Term\n: Definition\n";
+        let plain = MarkdownProcessor::new()
+            .process(input.to_owned(), None)
+            .unwrap();
+        assert!(plain.contains("[x] Done"));
+        assert!(!plain.contains("<dl>"));
+
+        let processor = MarkdownProcessor::new()
+            .with_tasklists(true)
+            .with_heading_attributes(true)
+            .with_definition_lists(true);
+        let result = processor.process(input.to_owned(), None).unwrap();
+        assert!(
+            result.contains(r#"<h1 id="plan" class="wide">Plan</h1>"#)
+        );
+        assert!(result.contains(
+            r#"<li><input type="checkbox" disabled="" checked="">"#
+        ));
+        assert!(
+            result.contains(r#"<input type="checkbox" disabled="">"#)
+        );
+        assert!(
+            result.contains("<dl>\n<dt>Term</dt>\n<dd>Definition</dd>")
+        );
+
+        let mut content = ContentConfig::default();
+        content.markdown.tasklists = Some(true);
+        let processor = MarkdownProcessor::new()
+            .with_definition_lists(true)
+            .with_content_config(&content);
+        let result = processor
+            .process(
+                "- [ ] Todo\n\nTerm\n: Definition\n".to_owned(),
+                None,
+            )
+            .unwrap();
+        assert!(result.contains("<input"));
+        assert!(result.contains("<dd>Definition</dd>"));
+
+        let result = processor
+            .process(
+                "---\nmarkdown:\n  definition_lists: false\n---\nTerm\n: Def\n"
+                    .to_owned(),
+                None,
+            )
+            .unwrap();
+        assert!(!result.contains("<dd>"));
+    }
+
+    #[test]
+    fn test_smart_punctuation() {
+        let input = "\"Quoted\" -- it's 1990---2000...";
+        let plain = MarkdownProcessor::new()
+            .process(input.to_owned(), None)
+            .unwrap();
+        assert!(plain.contains("Quoted") && plain.contains("-- it"));
+        assert!(!plain.contains('\u{201c}'));
+
+        let processor =
+            MarkdownProcessor::new().with_smart_punctuation(true);
+        let result = processor.process(input.to_owned(), None).unwrap();
+        assert!(result
+            .contains("\u{201c}Quoted\u{201d} \u{2013} it\u{2019}s 1990\u{2014}2000\u{2026}"));
+
+        let result = processor
+            .process(
+                format!("---\nmarkdown:\n  smart_punctuation: false\n---\n{}", input),
+                None,
+            )
+            .unwrap();
+        assert!(result.contains("--"));
+
+        let mut content = ContentConfig::default();
+        content.markdown.smart_punctuation = Some(true);
+        let result = MarkdownProcessor::new()
+            .with_content_config(&content)
+            .process("a -- b".to_owned(), None)
+            .unwrap();
+        assert!(result.contains("a \u{2013} b"));
+    }
+
+    #[test]
+    fn test_more_marker() {
+        let result = MarkdownProcessor::new()
+            .process(
+                "Intro <!--more--> text\n\n<!--more-->\n\nBody"
+                    .to_owned(),
+                None,
+            )
+            .unwrap();
+        assert_eq!(result.matches(SUMMARY_MARKER).count(), 1);
+        assert!(result.starts_with("<p>Intro  text</p>"));
+    }
+
+    #[test]
+    fn test_footnotes() {
+        let input = "Tea[^tea] and cake[^cake], then tea[^tea].\n\n\
+                     [^cake]: Lemon.\n\n[^tea]: Green.\n";
+        let processor = MarkdownProcessor::new().with_footnotes(true);
+        let result = processor.process(input.to_owned(), None).unwrap();
+        assert!(result.contains(
+            "Tea<sup class=\"footnote-ref\"><a href=\"#fn-1\" id=\"fnref-1\">1</a></sup>"
+        ));
+        assert!(result.contains("id=\"fnref-1-2\">1</a>"));
+        assert!(
+            result.contains("<a href=\"#fn-2\" id=\"fnref-2\">2</a>")
+        );
+        assert!(result.contains("<section class=\"footnotes\">\n<ol>"));
+        assert!(result.contains(
+            "<li id=\"fn-1\">\n<p>Green. <a href=\"#fnref-1\" class=\"footnote-backref\" aria-label=\"Back to reference 1\">\u{21a9}</a> \
+             <a href=\"#fnref-1-2\""
+        ));
+        assert!(
+            result.find("id=\"fn-1\"").unwrap()
+                < result.find("id=\"fn-2\"").unwrap()
+        );
+
+        let processor = MarkdownProcessor::new()
+            .with_footnotes(true)
+            .with_footnote_config(FootnoteConfig {
+                backlinks: false,
+                numbering: FootnoteNumbering::Continuous,
+                heading: "Notes & Sources".to_string(),
+            });
+        let first = processor.process(input.to_owned(), None).unwrap();
+        assert!(first.contains("<h2>Notes &amp; Sources</h2>\n<ol>"));
+        assert!(!first.contains("footnote-backref"));
+        assert_eq!(count_footnotes(input), 2);
+        let again = "Again[^a].\n\n[^a]: Once.\n";
+        let second = processor
+            .process_page(
+                again.to_owned(),
+                None,
+                count_footnotes(input),
+            )
+            .unwrap();
+        assert!(second.contains("<ol start=\"3\">"));
+        assert!(second.contains("<li id=\"fn-3\">"));
+        assert_eq!(
+            processor.process_page(again.to_owned(), None, 2).unwrap(),
+            second
+        );
+        let per_page = MarkdownProcessor::new().with_footnotes(true);
+        assert!(per_page
+            .process_page(again.to_owned(), None, 2)
+            .unwrap()
+            .contains("<li id=\"fn-1\">"));
+    }
+
+    #[test]
+    fn test_page_overrides() {
+        let processor = MarkdownProcessor::new().with_tables(true);
+        let input = r#"---
+title: Page
+toc: true
+markdown:
+  tables: false
+  smart_punctuation: true
+---
+# Heading
+
+"quoted"
+
+| A | B |
+|---|---|
+| 1 | 2 |"#;
+
+        let result = processor.process(input.to_owned(), None).unwrap();
+        assert!(result.contains(r#"<nav class="toc""#));
+        assert!(!result.contains("<table>"));
+        assert!(result.contains("\u{201c}quoted\u{201d}"));
+        // Override keys are not exposed as custom metadata
+        assert!(!result.contains("application/ld+json"));
+    }
+
+    #[test]
+    fn test_page_override_validation() {
+        let processor = MarkdownProcessor::new();
+
+        let unknown = "---\nmarkdown:\n  tabels: true\n---\n# Test";
+        assert!(processor.process(unknown.to_owned(), None).is_err());
+
+        let level = "---\ntoc_max_level: 9\n---\n# Test";
+        assert!(processor.process(level.to_owned(), None).is_err());
+
+        let reversed =
+            "---\ntoc_min_level: 4\ntoc_max_level: 2\n---\n# Test";
+        assert!(processor.process(reversed.to_owned(), None).is_err());
+    }
+
+    #[test]
+    fn test_sanitize_override_requires_development() {
+        let input = "---\nsanitize: false\n---\n# Test\n\n<u>raw</u>";
+
+        let production = MarkdownProcessor::new();
+        assert!(production.process(input.to_owned(), None).is_err());
+
+        let development =
+            MarkdownProcessor::new().with_profile(Profile::Development);
+        let result =
+            development.process(input.to_owned(), None).unwrap();
+        assert!(result.contains("<u>raw</u>"));
+    }
+
+    #[test]
+    fn test_raw_html_blocks() {
+        let input = "# Test\n\n```{=html}\n<iframe src=\"/embed\"></iframe>\n```\n\n<iframe src=\"/other\"></iframe>";
+
+        // Forbidden by default: rendered as an escaped code block
+        let processor = MarkdownProcessor::new();
+        let result = processor.process(input.to_owned(), None).unwrap();
+        assert!(!result.contains("<iframe"));
+
+        // Allowed by site policy: only the fenced block bypasses
+        // sanitization
+        let processor =
+            MarkdownProcessor::new().with_config(ProcessorConfig {
+                allow_raw_html: true,
+                ..ProcessorConfig::default()
+            });
+        let result = processor.process(input.to_owned(), None).unwrap();
+        assert!(result.contains("<iframe src=\"/embed\"></iframe>"));
+        assert!(!result.contains("/other\"></iframe>"));
+    }
+
+    #[test]
+    fn test_raw_html_page_policy() {
+        let input = "---\nraw_html: true\n---\n# Test\n\n```{=html}\n<u>raw</u>\n```";
+        let production = MarkdownProcessor::new();
+        assert!(production.process(input.to_owned(), None).is_err());
+
+        let development =
+            MarkdownProcessor::new().with_profile(Profile::Development);
+        let result =
+            development.process(input.to_owned(), None).unwrap();
+        assert!(result.contains("<u>raw</u>"));
+
+        // Pages may restate what the site already allows
+        let allowed = production.with_config(ProcessorConfig {
+            allow_raw_html: true,
+            ..ProcessorConfig::default()
+        });
+        let result = allowed.process(input.to_owned(), None).unwrap();
+        assert!(result.contains("<u>raw</u>"));
+    }
+
+    #[cfg(feature = "parallel-markdown")]
+    #[test]
+    fn test_parallel_parsing() {
+        let mut input = String::from("---\ntitle: Book\n---\n");
+        for chapter in 0..20 {
+            input.push_str(&format!(
+                "# Chapter {}\n\nText with *emphasis*.\n\n```{{=html}}\n<u>{}</u>\n```\n\n```\n\n# not a heading\n```\n\n",
+                chapter, chapter
+            ));
+        }
+        let config = ProcessorConfig {
+            allow_raw_html: true,
+            ..ProcessorConfig::default()
+        };
+        let sequential = MarkdownProcessor::new()
+            .with_config(config.clone())
+            .process(input.clone(), None)
+            .unwrap();
+        let parallel = MarkdownProcessor::new()
+            .with_config(config)
+            .with_parallel_parsing(ChunkConfig {
+                min_document_bytes: 0,
+                chunk_bytes: 100,
+            })
+            .process(input, None)
+            .unwrap();
+        assert_eq!(parallel, sequential);
+        assert!(parallel.contains("<u>19</u>"));
+    }
 }
@@ -6,7 +6,17 @@
 //!
 //! ## Available Processors
 //!
+//! - [`chunks`]: Splits long Markdown documents for parallel parsing
+//! - [`citations`]: Resolves `[@key]` citations of a BibTeX bibliography
+//! - [`diagrams`]: Renders fenced diagram blocks, such as Mermaid
+//! - [`exec`]: Pipes content through an external program, such as Pandoc
+//! - [`frontmatter`]: Merges configured frontmatter defaults into content files
+//! - `git`: Reads content files' dates and contributors from git history,
+//!   with the `git` feature
+//! - [`includes`]: Expands `{{include "..."}}` directives with shared fragments
 //! - [`markdown`]: Processes Markdown content with support for frontmatter and extensions
+//! - [`summary`]: Extracts page summaries for list pages, feeds and meta descriptions
+//! - [`wikilinks`]: Resolves `[[Page Title]]` links between content files
 //!
 //! ## Usage
 //!
@@ -54,8 +64,27 @@
 use serde_json::Value as JsonValue;
 use std::collections::HashMap;
 
+/// Splitting of long Markdown documents at block boundaries.
+pub mod chunks;
+/// Citations of BibTeX entries and their references sections.
+pub mod citations;
+/// Client-side and build-time rendering of diagram code blocks.
+pub mod diagrams;
+/// Processing of content by external programs.
+pub mod exec;
+/// Frontmatter defaults applied to content files by path.
+pub mod frontmatter;
+/// Metadata read from the git history of content files.
+#[cfg(feature = "git")]
+pub mod git;
+/// Expansion of include directives with shared content fragments.
+pub mod includes;
 /// Markdown processing functionality.
 pub mod markdown;
+/// Extraction of page summaries.
+pub mod summary;
+/// Resolution of wiki-style links between content files.
+pub mod wikilinks;
 
 // Re-export commonly used types
 pub use markdown::MarkdownProcessor;
@@ -0,0 +1,207 @@
+//! # Summaries
+//!
+//! Extracts a summary of each page for list pages, feeds and meta
+//! descriptions, which templates see as `page.summary`. A page's
+//! summary is, in order of precedence:
+//!
+//! 1. its `summary` frontmatter, escaped,
+//! 2. its content up to a `<!--more-->` marker on a line of its own,
+//!    which is removed from the content, or
+//! 3. the first paragraphs or words of its content, as set by
+//!    [`SummaryConfig`].
+//!
+//! Summaries cut at a marker or after whole paragraphs keep the
+//! content's markup. Summaries cut after a number of words are plain
+//! text, ending with `…` when the content is longer.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::core::config::SummaryConfig;
+//! use nucleusflow::processors::summary::extract_summary;
+//! use serde_json::Map;
+//!
+//! let (summary, content) = extract_summary(
+//!     &Map::new(),
+//!     "<p>Intro</p>\n<!--more-->\n<p>Body</p>",
+//!     &SummaryConfig::default(),
+//! );
+//! assert_eq!(summary.as_deref(), Some("<p>Intro</p>"));
+//! assert_eq!(content, "<p>Intro</p>\n\n<p>Body</p>");
+//!
+//! let config = SummaryConfig { words: 2, paragraphs: None };
+//! let (summary, _) =
+//!     extract_summary(&Map::new(), "<p>One <em>two</em> three</p>", &config);
+//! assert_eq!(summary.as_deref(), Some("One two…"));
+//! ```
+
+use serde_json::{Map, Value as JsonValue};
+
+use crate::core::config::SummaryConfig;
+use crate::extract::plain_text;
+use crate::processors::markdown::escape_html;
+
+/// Ends a page's summary.
+pub const MORE_MARKER: &str = "<!--more-->";
+
+/// Stands in for a `<!--more-->` marker in processed Markdown. A
+/// private-use character is used so the marker survives sanitization,
+/// which strips comments.
+pub(crate) const SUMMARY_MARKER: char = '\u{E002}';
+
+/// Closes a paragraph.
+const PARAGRAPH_END: &str = "</p>";
+
+/// Extracts a page's summary.
+///
+/// # Arguments
+/// * `frontmatter` - The page's frontmatter.
+/// * `content` - The page's processed content.
+/// * `config` - How summaries are cut from pages without a marker.
+///
+/// # Returns
+/// * `(Option<String>, String)` - The summary, unless the page has no
+///   text, and the content without its markers.
+pub fn extract_summary(
+    frontmatter: &Map<String, JsonValue>,
+    content: &str,
+    config: &SummaryConfig,
+) -> (Option<String>, String) {
+    let (marked, content) = split_summary(content);
+    let summary = match frontmatter
+        .get("summary")
+        .and_then(JsonValue::as_str)
+        .map(str::trim)
+        .filter(|summary| !summary.is_empty())
+    {
+        Some(summary) => Some(escape_html(summary)),
+        None => marked
+            .map(|summary| summary.trim().to_string())
+            .filter(|summary| !summary.is_empty())
+            .or_else(|| cut_summary(&content, config)),
+    };
+    (summary, content)
+}
+
+/// Splits content at its first summary marker.
+///
+/// # Returns
+/// * `(Option<&str>, String)` - The content before the marker, if it
+///   has one, and the content without any marker.
+pub fn split_summary(content: &str) -> (Option<&str>, String) {
+    let start =
+        [content.find(SUMMARY_MARKER), content.find(MORE_MARKER)]
+            .iter()
+            .flatten()
+            .min()
+            .copied();
+    match start {
+        Some(start) => (
+            Some(&content[..start]),
+            content
+                .replace(SUMMARY_MARKER, "")
+                .replace(MORE_MARKER, ""),
+        ),
+        None => (None, content.to_string()),
+    }
+}
+
+/// Cuts a summary from the first paragraphs or words of content.
+fn cut_summary(
+    content: &str,
+    config: &SummaryConfig,
+) -> Option<String> {
+    if let Some(paragraphs) = config.paragraphs {
+        let mut end = 0;
+        for _ in 0..paragraphs {
+            match content[end..].find(PARAGRAPH_END) {
+                Some(i) => end += i + PARAGRAPH_END.len(),
+                None => {
+                    end = content.len();
+                    break;
+                }
+            }
+        }
+        let summary = content[..end].trim();
+        return if summary.is_empty() {
+            None
+        } else {
+            Some(summary.to_string())
+        };
+    }
+
+    let text = plain_text(content);
+    let words: Vec<&str> = text.split_whitespace().collect();
+    if words.is_empty() {
+        return None;
+    }
+    let mut summary = words[..words.len().min(config.words)].join(" ");
+    if words.len() > config.words {
+        summary.push('…');
+    }
+    Some(escape_html(&summary))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_summary() {
+        let config = SummaryConfig::default();
+        let content = "<p>Intro</p>\u{E002}<p>Body</p><!--more-->";
+        let (summary, rest) =
+            extract_summary(&Map::new(), content, &config);
+        assert_eq!(summary.as_deref(), Some("<p>Intro</p>"));
+        assert_eq!(rest, "<p>Intro</p><p>Body</p>");
+
+        let frontmatter =
+            serde_json::json!({ "summary": "Fish & chips" });
+        let (summary, rest) = extract_summary(
+            frontmatter.as_object().unwrap(),
+            content,
+            &config,
+        );
+        assert_eq!(summary.as_deref(), Some("Fish &amp; chips"));
+        assert_eq!(rest, "<p>Intro</p><p>Body</p>");
+
+        assert_eq!(extract_summary(&Map::new(), "", &config).0, None);
+    }
+
+    #[test]
+    fn test_cut_summary() {
+        let content =
+            "<h1>Title</h1>\n<p>One &lt;two&gt;</p>\n<p>Three</p>";
+        let words = |words| {
+            cut_summary(
+                content,
+                &SummaryConfig {
+                    words,
+                    paragraphs: None,
+                },
+            )
+        };
+        assert_eq!(words(2).as_deref(), Some("Title One…"));
+        assert_eq!(words(3).as_deref(), Some("Title One &lt;two&gt;…"));
+        assert_eq!(
+            words(10).as_deref(),
+            Some("Title One &lt;two&gt; Three")
+        );
+
+        let paragraphs = |paragraphs| {
+            cut_summary(
+                content,
+                &SummaryConfig {
+                    words: 1,
+                    paragraphs: Some(paragraphs),
+                },
+            )
+        };
+        assert_eq!(
+            paragraphs(1).as_deref(),
+            Some("<h1>Title</h1>\n<p>One &lt;two&gt;</p>")
+        );
+        assert_eq!(paragraphs(5).as_deref(), Some(content));
+        assert_eq!(paragraphs(0), None);
+    }
+}
@@ -0,0 +1,276 @@
+//! # Wiki Links
+//!
+//! Resolves Obsidian-style wiki links between content files:
+//!
+//! - `[[Page Title]]` links to the page with that title,
+//! - `[[page-slug]]` links to the content file with that name, such as
+//!   `page-slug.md`, and
+//! - `[[target|Label]]` shows `Label` rather than the target, while
+//!   `[[target#Section]]` links to a heading on the target page.
+//!
+//! Targets match case-insensitively, ignoring punctuation, so
+//! `[[Getting started]]` finds a page titled "Getting Started!". A
+//! target can also be a page's output path without its extension, such
+//! as `[[guides/install]]`, to tell apart pages sharing a title.
+//!
+//! Links are resolved in each page's processed HTML, once every page's
+//! permalink is known. Text inside `<code>` and `<pre>` elements is left
+//! alone. A target that matches no page, or several, fails the page
+//! linking to it.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::processors::wikilinks::WikiLinks;
+//! use nucleusflow::site::Page;
+//!
+//! let page = Page::new(
+//!     "content/getting-started.md",
+//!     "getting-started.html",
+//!     serde_json::json!({ "title": "Getting Started" })
+//!         .as_object()
+//!         .cloned()
+//!         .unwrap(),
+//! );
+//! let links = WikiLinks::new(&[page]);
+//! assert_eq!(
+//!     links.rewrite("<p>See [[getting started|the guide]].</p>").unwrap(),
+//!     "<p>See <a href=\"/getting-started.html\">the guide</a>.</p>"
+//! );
+//! assert!(links.rewrite("<p>[[Missing]]</p>").is_err());
+//! ```
+
+use std::collections::HashMap;
+
+use crate::generators::tombstones::manifest_path;
+use crate::processors::markdown::heading_id;
+use crate::site::Page;
+
+/// The permalinks wiki links resolve to, keyed by target.
+#[derive(Debug, Clone, Default)]
+pub struct WikiLinks {
+    targets: HashMap<String, Vec<String>>,
+}
+
+impl WikiLinks {
+    /// Indexes pages by title, content file name and output path.
+    pub fn new(pages: &[Page]) -> Self {
+        let mut targets: HashMap<String, Vec<String>> = HashMap::new();
+        for page in pages {
+            let mut keys = vec![wiki_key(&manifest_path(
+                &page.path.with_extension(""),
+            ))];
+            keys.extend(page.title().map(wiki_key));
+            keys.extend(
+                page.source
+                    .file_stem()
+                    .map(|stem| wiki_key(&stem.to_string_lossy())),
+            );
+            keys.sort();
+            keys.dedup();
+            for key in keys.into_iter().filter(|key| !key.is_empty()) {
+                targets.entry(key).or_default().push(page.url.clone());
+            }
+        }
+        Self { targets }
+    }
+
+    /// Resolves a wiki link target to a page's URL.
+    ///
+    /// # Returns
+    /// * `Result<&str, String>` - The URL, or why the target does not
+    ///   resolve.
+    pub fn resolve(&self, target: &str) -> Result<&str, String> {
+        match self.targets.get(&wiki_key(target)).map(Vec::as_slice) {
+            Some([url]) => Ok(url),
+            Some(urls) if urls.len() > 1 => Err(format!(
+                "'{}' matches several pages: {}",
+                target,
+                urls.join(", ")
+            )),
+            _ => Err(format!("'{}' matches no page", target)),
+        }
+    }
+
+    /// Replaces the wiki links in some HTML with links to their pages.
+    ///
+    /// # Arguments
+    /// * `html` - A page's processed content.
+    ///
+    /// # Returns
+    /// * `Result<String, Vec<String>>` - The HTML with every wiki link
+    ///   replaced, or why each unresolved link does not resolve.
+    pub fn rewrite(&self, html: &str) -> Result<String, Vec<String>> {
+        let mut output = String::with_capacity(html.len());
+        let mut errors = Vec::new();
+        let mut code_depth = 0usize;
+        let mut rest = html;
+
+        while !rest.is_empty() {
+            let next = match rest.find(['<', '[']) {
+                Some(next) => next,
+                None => break,
+            };
+            output.push_str(&rest[..next]);
+            rest = &rest[next..];
+
+            if rest.starts_with('<') {
+                let end = rest.find('>').map_or(rest.len(), |i| i + 1);
+                let tag = rest[1..end].trim_end_matches('>');
+                let (closing, name) = match tag.strip_prefix('/') {
+                    Some(name) => (true, name),
+                    None => (false, tag),
+                };
+                let name: String = name
+                    .chars()
+                    .take_while(char::is_ascii_alphanumeric)
+                    .collect();
+                if name.eq_ignore_ascii_case("code")
+                    || name.eq_ignore_ascii_case("pre")
+                {
+                    code_depth = if closing {
+                        code_depth.saturating_sub(1)
+                    } else {
+                        code_depth + 1
+                    };
+                }
+                output.push_str(&rest[..end]);
+                rest = &rest[end..];
+                continue;
+            }
+
+            let link = rest
+                .strip_prefix("[[")
+                .filter(|_| code_depth == 0)
+                .and_then(|inner| {
+                    inner.find("]]").map(|end| &inner[..end])
+                })
+                .filter(|link| !link.contains(['<', '[', '\n']));
+            let link = match link {
+                Some(link) => link,
+                None => {
+                    output.push('[');
+                    rest = &rest[1..];
+                    continue;
+                }
+            };
+            rest = &rest[link.len() + 4..];
+
+            let (target, label) = match link.split_once('|') {
+                Some((target, label)) => (target, label.trim()),
+                None => (link, link.trim()),
+            };
+            let (page, fragment) = match target.split_once('#') {
+                Some((page, section)) => {
+                    (page, format!("#{}", heading_id(&decode(section))))
+                }
+                None => (target, String::new()),
+            };
+            match self.resolve(&decode(page)) {
+                Ok(url) => output.push_str(&format!(
+                    "<a href=\"{}{}\">{}</a>",
+                    url, fragment, label
+                )),
+                Err(error) => {
+                    errors.push(error);
+                    output.push_str(&format!("[[{}]]", link));
+                }
+            }
+        }
+        output.push_str(rest);
+
+        if errors.is_empty() {
+            Ok(output)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Returns the key a target is matched by: its letters and digits,
+/// lowercased, with `-` between words.
+fn wiki_key(target: &str) -> String {
+    let mut key = String::with_capacity(target.len());
+    for c in target.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() || c == '/' {
+            key.push(c);
+        } else if !key.is_empty() && !key.ends_with(['-', '/']) {
+            key.push('-');
+        }
+    }
+    key.trim_end_matches('-').replace("-/", "/")
+}
+
+/// Decodes the character references HTML escaping writes into text.
+fn decode(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::site::tests::page;
+    use serde_json::json;
+
+    #[test]
+    fn test_resolve() {
+        let links = WikiLinks::new(&[
+            Page {
+                source: "content/install.md".into(),
+                ..page(
+                    "guides/install.html",
+                    json!({ "title": "Setup" }),
+                )
+            },
+            page("setup.html", json!({})),
+            page("faq.html", json!({ "title": "Fish & Chips!" })),
+        ]);
+        assert_eq!(links.resolve("fish &  chips"), Ok("/faq.html"));
+        assert_eq!(links.resolve("FAQ"), Ok("/faq.html"));
+        assert_eq!(
+            links.resolve("install"),
+            Ok("/guides/install.html")
+        );
+        assert_eq!(
+            links.resolve("guides/install"),
+            Ok("/guides/install.html")
+        );
+        assert!(links
+            .resolve("setup")
+            .unwrap_err()
+            .contains("several pages"));
+        assert!(links
+            .resolve("nowhere")
+            .unwrap_err()
+            .contains("no page"));
+    }
+
+    #[test]
+    fn test_rewrite() {
+        let links = WikiLinks::new(&[page(
+            "about.html",
+            json!({ "title": "About Us" }),
+        )]);
+        assert_eq!(
+            links
+                .rewrite(
+                    "<p>[[About Us]], [[about|us]] and \
+                     [[about#Our Team]] [x]</p>\
+                     <pre><code>[[not a link]]</code></pre>"
+                )
+                .unwrap(),
+            "<p><a href=\"/about.html\">About Us</a>, \
+             <a href=\"/about.html\">us</a> and \
+             <a href=\"/about.html#our-team\">about#Our Team</a> [x]</p>\
+             <pre><code>[[not a link]]</code></pre>"
+        );
+        assert_eq!(
+            links.rewrite("[[a]] [[about]] [[b|c]]").unwrap_err(),
+            ["'a' matches no page", "'b' matches no page"]
+        );
+    }
+}
@@ -0,0 +1,97 @@
+//! # Build Results
+//!
+//! [`NucleusFlow::process`](crate::NucleusFlow::process) returns a
+//! [`BuildResult`] describing what the build did: the pages it wrote,
+//! the pages it left out, the warnings it raised, the manifests it
+//! wrote and how long each phase took. Applications embedding the
+//! pipeline, and the CLI, act on it instead of parsing logs.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::result::{BuildResult, BuiltPage};
+//!
+//! let result = BuildResult {
+//!     pages: vec![BuiltPage {
+//!         source: "content/index.md".into(),
+//!         path: "index.html".into(),
+//!         url: "/".to_string(),
+//!     }],
+//!     warnings: vec!["content/index.md: missing description".into()],
+//!     ..BuildResult::default()
+//! };
+//! assert_eq!(
+//!     result.to_string(),
+//!     "Built 1 page in 0 ms (0 skipped, 1 warning)"
+//! );
+//! ```
+
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// A page a build wrote.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuiltPage {
+    /// The content file the page came from
+    pub source: PathBuf,
+    /// The page's path relative to the output directory
+    pub path: PathBuf,
+    /// The page's site-relative URL
+    pub url: String,
+}
+
+/// A content file a build left out without failing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SkippedPage {
+    /// The content file that was left out
+    pub source: PathBuf,
+    /// Why it was left out
+    pub reason: String,
+}
+
+/// How long each phase of a build took.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BuildTimings {
+    /// Reading, validating and processing the content files
+    pub collect: Duration,
+    /// Rendering the pages and generating the output
+    pub render: Duration,
+    /// Capturing screenshots and pushing to the preview target
+    pub publish: Duration,
+    /// The whole build
+    pub total: Duration,
+}
+
+/// The outcome of a successful build.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuildResult {
+    /// The pages written, ordered by output path
+    pub pages: Vec<BuiltPage>,
+    /// The content files left out, such as pages skipped by the
+    /// collision policy
+    pub skipped: Vec<SkippedPage>,
+    /// The warnings raised, such as validator warnings
+    pub warnings: Vec<String>,
+    /// The sitemaps, redirect exports, page manifest and build report
+    /// written, relative to the output directory
+    pub manifests: Vec<PathBuf>,
+    /// How long the build took
+    pub timings: BuildTimings,
+}
+
+impl fmt::Display for BuildResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plural = |count: usize| if count == 1 { "" } else { "s" };
+        write!(
+            f,
+            "Built {} page{} in {} ms ({} skipped, {} warning{})",
+            self.pages.len(),
+            plural(self.pages.len()),
+            self.timings.total.as_millis(),
+            self.skipped.len(),
+            self.warnings.len(),
+            plural(self.warnings.len())
+        )
+    }
+}
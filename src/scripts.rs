@@ -0,0 +1,462 @@
+//! # Page Scripts
+//!
+//! Rhai scripts customise pages at two points of the build without
+//! compiling a plugin. A script defines either or both of:
+//!
+//! - `on_page(page)`, run once a page's frontmatter is extracted and
+//!   its content processed, which receives the page as a map of its
+//!   `source` path, `frontmatter` and `content`, and
+//! - `on_output(page, html)`, run on a page's rendered HTML before it
+//!   is written, which receives the page as templates see it.
+//!
+//! `on_page` returns the page with its `frontmatter` or `content`
+//! changed, and `on_output` returns the HTML to write; either leaves
+//! the page as it was by returning nothing:
+//!
+//! ```text
+//! fn on_page(page) {
+//!     if page.frontmatter.draft == true {
+//!         page.frontmatter.title += " (draft)";
+//!     }
+//!     page
+//! }
+//!
+//! fn on_output(page, html) {
+//!     html.replace("</body>", "<!-- " + page.url + " --></body>");
+//!     html
+//! }
+//! ```
+//!
+//! Scripts run in the order they are given, each on the page the one
+//! before it returned. A script that fails, or returns anything else,
+//! fails the page. Scripts cannot touch files or run programs, and one
+//! running more than ten million operations is stopped, so a runaway
+//! loop cannot hang the build. What scripts `print` is logged.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::scripts::Scripts;
+//! use serde_json::{json, Map};
+//! use std::path::Path;
+//!
+//! let mut scripts = Scripts::new();
+//! scripts
+//!     .add(
+//!         "shout.rhai",
+//!         "fn on_page(page) { page.content.make_upper(); page }",
+//!     )
+//!     .unwrap();
+//!
+//! let mut frontmatter = Map::new();
+//! let mut content = "<p>Hello</p>".to_string();
+//! scripts
+//!     .on_page(Path::new("hello.md"), &mut frontmatter, &mut content)
+//!     .unwrap();
+//! assert_eq!(content, "<P>HELLO</P>");
+//! ```
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rhai::serde::{from_dynamic, to_dynamic};
+use rhai::{CallFnOptions, Dynamic, Engine, EvalAltResult, Scope, AST};
+use serde_json::{json, Map, Value as JsonValue};
+
+use crate::site::Page;
+use crate::{ProcessingError, Result};
+
+/// The function scripts define to change pages before they render.
+pub const ON_PAGE: &str = "on_page";
+
+/// The function scripts define to change pages' HTML before it is
+/// written.
+pub const ON_OUTPUT: &str = "on_output";
+
+/// The number of operations a script may run per call.
+const MAX_OPERATIONS: u64 = 10_000_000;
+
+/// A compiled script.
+#[derive(Debug)]
+struct Script {
+    /// The script's name, such as its path
+    name: String,
+
+    /// The script's compiled functions
+    ast: AST,
+}
+
+impl Script {
+    /// Returns `true` if the script defines a hook function.
+    fn defines(&self, hook: &str, arity: usize) -> bool {
+        self.ast
+            .iter_functions()
+            .any(|f| f.name == hook && f.params.len() == arity)
+    }
+
+    /// Returns an error about one of the script's hook functions.
+    fn failed(
+        &self,
+        hook: &str,
+        details: impl std::fmt::Display,
+    ) -> ProcessingError {
+        ProcessingError::content_processing(
+            format!(
+                "Script {} failed in {}: {}",
+                self.name, hook, details
+            ),
+            None,
+        )
+    }
+}
+
+/// The scripts run on each page, in order.
+#[derive(Debug)]
+pub struct Scripts {
+    engine: Engine,
+    scripts: Vec<Script>,
+}
+
+impl Default for Scripts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scripts {
+    /// Creates an empty set of scripts.
+    pub fn new() -> Self {
+        let mut engine = Engine::new();
+        _ = engine
+            .set_max_operations(MAX_OPERATIONS)
+            .on_print(|text| tracing::info!("{}", text))
+            .on_debug(|text, source, position| {
+                tracing::debug!(
+                    "{} ({} {})",
+                    text,
+                    source.unwrap_or("script"),
+                    position
+                )
+            });
+        Self {
+            engine,
+            scripts: Vec::new(),
+        }
+    }
+
+    /// Reads and compiles script files, run in the order given.
+    ///
+    /// # Arguments
+    /// * `paths` - The script files, such as `scripts/drafts.rhai`.
+    ///
+    /// # Returns
+    /// * `Result<Self>` - The scripts, or an error if a file cannot be
+    ///   read or compiled.
+    pub fn read(paths: &[PathBuf]) -> Result<Self> {
+        let mut scripts = Self::new();
+        for path in paths {
+            let source = fs::read_to_string(path).map_err(|e| {
+                ProcessingError::io_error(path.clone(), e)
+            })?;
+            scripts.add(path.display().to_string(), &source).map_err(
+                |e| {
+                    ProcessingError::configuration(
+                        e.to_string(),
+                        Some(path.clone()),
+                        None,
+                    )
+                },
+            )?;
+        }
+        Ok(scripts)
+    }
+
+    /// Compiles a script and runs it after those added before it.
+    ///
+    /// # Arguments
+    /// * `name` - The script's name in errors, such as its path.
+    /// * `source` - The script's Rhai source.
+    ///
+    /// # Returns
+    /// * `Result<()>` - Indicates success, or a validation error if
+    ///   the script does not compile.
+    pub fn add(
+        &mut self,
+        name: impl Into<String>,
+        source: &str,
+    ) -> Result<()> {
+        let name = name.into();
+        let ast = self.engine.compile(source).map_err(|e| {
+            ProcessingError::validation(
+                format!("Script {} does not compile: {}", name, e),
+                None::<String>,
+            )
+        })?;
+        self.scripts.push(Script { name, ast });
+        Ok(())
+    }
+
+    /// Runs the scripts' `on_page` functions on a page's frontmatter
+    /// and processed content.
+    ///
+    /// # Arguments
+    /// * `source` - The page's content file.
+    /// * `frontmatter` - The page's frontmatter, changed in place.
+    /// * `content` - The page's processed content, changed in place.
+    ///
+    /// # Returns
+    /// * `Result<()>` - Indicates success, or an error if a script
+    ///   fails or returns something other than the page or nothing.
+    pub fn on_page(
+        &self,
+        source: &Path,
+        frontmatter: &mut Map<String, JsonValue>,
+        content: &mut String,
+    ) -> Result<()> {
+        for script in
+            self.scripts.iter().filter(|s| s.defines(ON_PAGE, 1))
+        {
+            let page = json!({
+                "source": source,
+                "frontmatter": frontmatter,
+                "content": content,
+            });
+            let page = self
+                .call(script, ON_PAGE, (dynamic(&page)?,))
+                .map_err(|e| script.failed(ON_PAGE, e))?;
+            if page.is_unit() {
+                continue;
+            }
+            let mut page: Map<String, JsonValue> = from_dynamic(&page)
+                .map_err(|_| {
+                    script.failed(
+                        ON_PAGE,
+                        "returned something other than the page",
+                    )
+                })?;
+            match (page.remove("frontmatter"), page.remove("content")) {
+                (
+                    Some(JsonValue::Object(changed_frontmatter)),
+                    Some(JsonValue::String(changed_content)),
+                ) => {
+                    *frontmatter = changed_frontmatter;
+                    *content = changed_content;
+                }
+                _ => return Err(script.failed(
+                    ON_PAGE,
+                    "returned a page without frontmatter or content",
+                )),
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs the scripts' `on_output` functions on a page's rendered
+    /// HTML.
+    ///
+    /// # Arguments
+    /// * `page` - The page being written.
+    /// * `html` - The page's HTML.
+    ///
+    /// # Returns
+    /// * `Result<String>` - The HTML to write, or an error if a script
+    ///   fails or returns something other than a string or nothing.
+    pub fn on_output(
+        &self,
+        page: &Page,
+        html: String,
+    ) -> Result<String> {
+        let mut html = html;
+        for script in
+            self.scripts.iter().filter(|s| s.defines(ON_OUTPUT, 2))
+        {
+            let output = self
+                .call(
+                    script,
+                    ON_OUTPUT,
+                    (
+                        dynamic(&page.to_json())?,
+                        Dynamic::from(html.clone()),
+                    ),
+                )
+                .map_err(|e| script.failed(ON_OUTPUT, e))?;
+            if output.is_unit() {
+                continue;
+            }
+            html = output.into_string().map_err(|_| {
+                script.failed(
+                    ON_OUTPUT,
+                    "returned something other than HTML",
+                )
+            })?;
+        }
+        Ok(html)
+    }
+
+    /// Calls a script's hook function without rerunning its top-level
+    /// statements.
+    fn call(
+        &self,
+        script: &Script,
+        hook: &str,
+        args: impl rhai::FuncArgs,
+    ) -> std::result::Result<Dynamic, Box<EvalAltResult>> {
+        self.engine.call_fn_with_options(
+            CallFnOptions::new().eval_ast(false),
+            &mut Scope::new(),
+            &script.ast,
+            hook,
+            args,
+        )
+    }
+}
+
+/// Converts JSON to a script value.
+fn dynamic(value: &JsonValue) -> Result<Dynamic> {
+    to_dynamic(value).map_err(|e| {
+        ProcessingError::serialization(
+            format!("Page cannot be passed to scripts: {}", e),
+            None,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn compile(sources: &[&str]) -> Scripts {
+        let mut scripts = Scripts::new();
+        for (index, source) in sources.iter().enumerate() {
+            scripts.add(format!("{}.rhai", index), source).unwrap();
+        }
+        scripts
+    }
+
+    fn on_page(
+        scripts: &Scripts,
+        frontmatter: JsonValue,
+    ) -> Result<(JsonValue, String)> {
+        let mut frontmatter = match frontmatter {
+            JsonValue::Object(frontmatter) => frontmatter,
+            _ => Map::new(),
+        };
+        let mut content = "<p>Body</p>".to_string();
+        scripts.on_page(
+            Path::new("content/post.md"),
+            &mut frontmatter,
+            &mut content,
+        )?;
+        Ok((JsonValue::Object(frontmatter), content))
+    }
+
+    #[test]
+    fn test_on_page() -> Result<()> {
+        let scripts = compile(&[
+            r#"
+            fn on_page(page) {
+                page.frontmatter.words = page.content.len;
+                page.frontmatter.source = page.source;
+                page
+            }
+            "#,
+            r#"
+            fn on_page(page) {
+                if page.frontmatter.draft == true {
+                    page.frontmatter.title += " (draft)";
+                    page.content += "<p>Unpublished</p>";
+                    return page;
+                }
+            }
+            fn on_output(page, html) { page.url + html }
+            "#,
+        ]);
+        assert_eq!(
+            on_page(
+                &scripts,
+                json!({ "title": "Post", "draft": true })
+            )?,
+            (
+                json!({
+                    "title": "Post (draft)",
+                    "draft": true,
+                    "words": 11,
+                    "source": "content/post.md",
+                }),
+                "<p>Body</p><p>Unpublished</p>".to_string()
+            )
+        );
+        assert_eq!(
+            on_page(&scripts, json!({ "title": "Post" }))?.0["title"],
+            "Post"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_on_output() -> Result<()> {
+        let page =
+            Page::new("content/post.md", "post.html", Map::new());
+        let scripts = compile(&[
+            r#"
+            fn on_output(page, html) {
+                html + "<!-- " + page.url + " -->"
+            }
+            "#,
+            r#"
+            fn on_output(page, html) {
+                if html.is_empty() { "empty" }
+            }
+            "#,
+            "fn on_page(page) { page.content = \"ignored\"; page }",
+        ]);
+        assert_eq!(
+            scripts.on_output(&page, "<html></html>".to_string())?,
+            "<html></html><!-- /post.html -->"
+        );
+        assert!(compile(&["fn on_output(page, html) { 1 }"])
+            .on_output(&page, String::new())
+            .unwrap_err()
+            .to_string()
+            .contains("returned something other than HTML"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_script_errors() {
+        let error = |source: &str| {
+            on_page(&compile(&[source]), json!({}))
+                .unwrap_err()
+                .to_string()
+        };
+        assert!(error("fn on_page(page) { page.missing.field }")
+            .contains("Script 0.rhai failed in on_page"));
+        assert!(error("fn on_page(page) { loop {} }")
+            .contains("Too many operations"));
+        assert!(error("fn on_page(page) { 42 }")
+            .contains("returned something other than the page"));
+        assert!(error("fn on_page(page) { #{ content: \"\" } }")
+            .contains("without frontmatter or content"));
+        assert!(Scripts::new()
+            .add("broken.rhai", "fn on_page(page) {")
+            .unwrap_err()
+            .to_string()
+            .contains("Script broken.rhai does not compile"));
+    }
+
+    #[test]
+    fn test_read() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("title.rhai");
+        fs::write(
+            &path,
+            r#"fn on_page(p) { p.frontmatter.title = "S"; p }"#,
+        )?;
+        let scripts = Scripts::read(&[path])?;
+        assert_eq!(on_page(&scripts, json!({}))?.0["title"], "S");
+        assert!(Scripts::read(&[temp_dir.path().join("missing.rhai")])
+            .is_err());
+        Ok(())
+    }
+}
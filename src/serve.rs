@@ -0,0 +1,387 @@
+//! # Development Server
+//!
+//! Serves a built site over HTTP on the local machine, so it can be
+//! previewed as a static host would serve it:
+//!
+//! - a path naming a directory is served its `index.html`,
+//! - a path without an extension is served the `.html` page of the
+//!   same name, if there is one, and
+//! - a path naming nothing is answered `404 Not Found` with the site's
+//!   `404.html` page, or a plain message if the site has none.
+//!
+//! Only `GET` and `HEAD` requests are answered, and paths escaping the
+//! served directory through `..` are not found. Requests with a line
+//! longer than 8 KiB or more than 100 headers are refused.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::serve::DevServer;
+//! use std::fs;
+//!
+//! let site = tempfile::tempdir().unwrap();
+//! fs::write(site.path().join("index.html"), "<p>Home</p>").unwrap();
+//! fs::write(site.path().join("404.html"), "<p>Lost</p>").unwrap();
+//!
+//! let server = DevServer::new(site.path());
+//! assert_eq!(server.respond("/").status, 200);
+//!
+//! let missing = server.respond("/missing/page");
+//! assert_eq!(missing.status, 404);
+//! assert_eq!(missing.body, b"<p>Lost</p>");
+//! ```
+
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use crate::check::percent_decode;
+use crate::{ProcessingError, Result};
+
+/// The page served, with a 404 status, for paths naming nothing.
+pub const NOT_FOUND_PAGE: &str = "404.html";
+
+/// The longest request line or header line read, in bytes.
+const MAX_LINE_BYTES: u64 = 8 * 1024;
+
+/// The most header lines read from a request.
+const MAX_HEADERS: usize = 100;
+
+/// The most bytes of a refused request read before closing.
+const DRAIN_BYTES: u64 = 64 * 1024;
+
+/// A response to a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    /// The HTTP status code
+    pub status: u16,
+
+    /// The body's media type
+    pub content_type: &'static str,
+
+    /// The body
+    pub body: Vec<u8>,
+}
+
+/// Serves the files of a built site.
+#[derive(Debug, Clone)]
+pub struct DevServer {
+    root: PathBuf,
+}
+
+impl DevServer {
+    /// Creates a server for the site built into `root`.
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Returns the response to a `GET` request for a URL path.
+    ///
+    /// # Arguments
+    /// * `url` - The requested path, such as `/blog/?page=2`.
+    ///
+    /// # Returns
+    /// * `Response` - The file the path names, or the site's
+    ///   `404.html` with a 404 status if it names nothing.
+    pub fn respond(&self, url: &str) -> Response {
+        if let Some(path) = self.resolve(url) {
+            if let Ok(body) = fs::read(&path) {
+                return Response {
+                    status: 200,
+                    content_type: content_type(&path),
+                    body,
+                };
+            }
+        }
+        let not_found = self.root.join(NOT_FOUND_PAGE);
+        Response {
+            status: 404,
+            content_type: content_type(&not_found),
+            body: fs::read(&not_found)
+                .unwrap_or_else(|_| b"<h1>404 Not Found</h1>".to_vec()),
+        }
+    }
+
+    /// Answers requests until the listener fails, each connection on
+    /// its own thread.
+    ///
+    /// # Arguments
+    /// * `listener` - The socket requests arrive on.
+    ///
+    /// # Returns
+    /// * `Result<()>` - An error if the listener fails.
+    pub fn serve(&self, listener: &TcpListener) -> Result<()> {
+        for stream in listener.incoming() {
+            let stream = stream.map_err(|e| {
+                ProcessingError::content_processing(
+                    format!("Development server failed: {}", e),
+                    None,
+                )
+            })?;
+            let server = self.clone();
+            _ = thread::spawn(move || {
+                if let Err(e) = server.handle(stream) {
+                    tracing::debug!(error = %e, "Request failed");
+                }
+            });
+        }
+        Ok(())
+    }
+
+    /// Reads a request from a connection and writes its response.
+    fn handle(&self, stream: TcpStream) -> io::Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let request_line = read_head(&mut reader)?;
+        let mut words = request_line
+            .as_ref()
+            .map_or("", String::as_str)
+            .split_whitespace();
+        let (method, url) = (words.next(), words.next().unwrap_or("/"));
+        let response = match (&request_line, method) {
+            (Err(status), _) => error_response(*status),
+            (Ok(_), Some("GET" | "HEAD")) => self.respond(url),
+            _ => error_response(405),
+        };
+        tracing::info!(
+            "{} {} {}",
+            response.status,
+            method.unwrap_or("-"),
+            url
+        );
+
+        let mut stream = stream;
+        write!(
+            stream,
+            "HTTP/1.1 {} {}\r\nContent-Type: {}\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n",
+            response.status,
+            reason(response.status),
+            response.content_type,
+            response.body.len()
+        )?;
+        if method != Some("HEAD") {
+            stream.write_all(&response.body)?;
+        }
+        stream.flush()?;
+
+        // Read the rest of a refused request, up to a bound, so closing
+        // the connection does not reset it before the client reads the
+        // response.
+        if request_line.is_err() {
+            stream.shutdown(Shutdown::Write)?;
+            stream.set_read_timeout(Some(Duration::from_secs(1)))?;
+            _ = io::copy(
+                &mut reader.take(DRAIN_BYTES),
+                &mut io::sink(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns the file a URL path names, if any.
+    fn resolve(&self, url: &str) -> Option<PathBuf> {
+        let end = url.find(['?', '#']).unwrap_or(url.len());
+        let decoded = percent_decode(&url[..end]);
+        let relative = Path::new(decoded.trim_start_matches('/'));
+        if !relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return None;
+        }
+
+        let path = self.root.join(relative);
+        if path.is_dir() {
+            let index = path.join("index.html");
+            return index.is_file().then(|| index);
+        }
+        if path.is_file() {
+            return Some(path);
+        }
+        let page = path.with_extension("html");
+        (path.extension().is_none() && page.is_file()).then(|| page)
+    }
+}
+
+/// Reads a request's line and headers, keeping the request line.
+///
+/// # Returns
+/// * `io::Result<std::result::Result<String, u16>>` - The request
+///   line, or the status refusing a request whose request line (400)
+///   or headers (431) are too long or too many.
+fn read_head<R: BufRead>(
+    reader: &mut R,
+) -> io::Result<std::result::Result<String, u16>> {
+    let request_line = match read_line(&mut *reader)? {
+        Some(line) => line,
+        None => return Ok(Err(400)),
+    };
+    for _ in 0..=MAX_HEADERS {
+        match read_line(&mut *reader)? {
+            Some(header) if header.trim().is_empty() => {
+                return Ok(Ok(request_line))
+            }
+            Some(_) => {}
+            None => return Ok(Err(431)),
+        }
+    }
+    Ok(Err(431))
+}
+
+/// Reads a line of at most [`MAX_LINE_BYTES`], or `None` if it is
+/// longer. The end of the stream reads as an empty line.
+fn read_line<R: BufRead>(reader: R) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    let read = reader.take(MAX_LINE_BYTES).read_line(&mut line)?;
+    Ok((read < MAX_LINE_BYTES as usize || line.ends_with('\n'))
+        .then(|| line))
+}
+
+/// Returns a plain text response for an error status.
+fn error_response(status: u16) -> Response {
+    Response {
+        status,
+        content_type: "text/plain; charset=utf-8",
+        body: reason(status).as_bytes().to_vec(),
+    }
+}
+
+/// Returns the media type of a file, by its extension.
+fn content_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase());
+    match extension.as_deref() {
+        Some("html" | "htm") => "text/html; charset=utf-8",
+        Some("css") => "text/css; charset=utf-8",
+        Some("js" | "mjs") => "text/javascript; charset=utf-8",
+        Some("json" | "map") => "application/json",
+        Some("xml") => "application/xml",
+        Some("txt") => "text/plain; charset=utf-8",
+        Some("svg") => "image/svg+xml",
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("ico") => "image/x-icon",
+        Some("woff2") => "font/woff2",
+        Some("woff") => "font/woff",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Returns the reason phrase of a status code the server sends.
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        431 => "Request Header Fields Too Large",
+        _ => "Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use tempfile::TempDir;
+
+    fn site() -> io::Result<TempDir> {
+        let temp_dir = TempDir::new()?;
+        fs::create_dir(temp_dir.path().join("blog"))?;
+        fs::write(temp_dir.path().join("index.html"), "home")?;
+        fs::write(temp_dir.path().join("about.html"), "about")?;
+        fs::write(temp_dir.path().join("blog/index.html"), "blog")?;
+        fs::write(temp_dir.path().join("blog/a b.css"), "css")?;
+        Ok(temp_dir)
+    }
+
+    #[test]
+    fn test_respond() -> Result<()> {
+        let site = site()?;
+        let server = DevServer::new(site.path());
+        let body = |url: &str| {
+            let response = server.respond(url);
+            (response.status, String::from_utf8(response.body).unwrap())
+        };
+        assert_eq!(body("/"), (200, "home".to_string()));
+        assert_eq!(body("/blog?page=2"), (200, "blog".to_string()));
+        assert_eq!(body("/about"), (200, "about".to_string()));
+        assert_eq!(body("/blog/a%20b.css"), (200, "css".to_string()));
+        assert_eq!(
+            server.respond("/blog/a%20b.css").content_type,
+            "text/css; charset=utf-8"
+        );
+        assert_eq!(body("/missing").0, 404);
+        assert!(body("/missing").1.contains("404 Not Found"));
+
+        fs::write(site.path().join(NOT_FOUND_PAGE), "lost")?;
+        assert_eq!(body("/missing"), (404, "lost".to_string()));
+        assert_eq!(body("/../index.html"), (404, "lost".to_string()));
+        assert_eq!(
+            server.respond("/missing").content_type,
+            "text/html; charset=utf-8"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_serve() -> Result<()> {
+        let site = site()?;
+        fs::write(site.path().join(NOT_FOUND_PAGE), "lost")?;
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let address = listener.local_addr()?;
+        let server = DevServer::new(site.path());
+        _ = thread::spawn(move || server.serve(&listener));
+
+        let request = |request: &str| -> io::Result<String> {
+            let mut stream = TcpStream::connect(address)?;
+            stream.write_all(request.as_bytes())?;
+            let mut response = String::new();
+            _ = stream.read_to_string(&mut response)?;
+            Ok(response)
+        };
+        let found = request("GET /about HTTP/1.1\r\nHost: x\r\n\r\n")?;
+        assert!(found.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(found.ends_with("\r\n\r\nabout"));
+        let missing = request("GET /nope HTTP/1.1\r\n\r\n")?;
+        assert!(missing.starts_with("HTTP/1.1 404 Not Found\r\n"));
+        assert!(missing.ends_with("\r\n\r\nlost"));
+        let head = request("HEAD /nope HTTP/1.1\r\n\r\n")?;
+        assert!(head.contains("Content-Length: 4\r\n"));
+        assert!(head.ends_with("\r\n\r\n"));
+        assert!(request("POST / HTTP/1.1\r\n\r\n")?
+            .starts_with("HTTP/1.1 405 Method Not Allowed\r\n"));
+
+        let long = "a".repeat(MAX_LINE_BYTES as usize);
+        assert!(request(&format!("GET /{} HTTP/1.1\r\n\r\n", long))?
+            .starts_with("HTTP/1.1 400 Bad Request\r\n"));
+        let too_large =
+            "HTTP/1.1 431 Request Header Fields Too Large\r\n";
+        assert!(request(&format!(
+            "GET / HTTP/1.1\r\nX: {}\r\n\r\n",
+            long
+        ))?
+        .starts_with(too_large));
+        let headers = "X: y\r\n".repeat(MAX_HEADERS + 1);
+        assert!(request(&format!(
+            "GET / HTTP/1.1\r\n{}\r\n",
+            headers
+        ))?
+        .starts_with(too_large));
+        let headers = "X: y\r\n".repeat(MAX_HEADERS);
+        assert!(request(&format!(
+            "GET / HTTP/1.1\r\n{}\r\n",
+            headers
+        ))?
+        .starts_with("HTTP/1.1 200 OK\r\n"));
+        Ok(())
+    }
+}
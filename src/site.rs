@@ -0,0 +1,689 @@
+//! # Site Model
+//!
+//! The collection pass reads every content file into a [`Page`] and
+//! gathers them into a [`Site`] before anything is rendered. Library
+//! users can query and change the model between the two passes, for
+//! example to drop drafts or fill in missing titles, and then hand it
+//! back to `NucleusFlow::process_site` to render it:
+//!
+//! ```rust,no_run
+//! # use nucleusflow::NucleusFlow;
+//! # fn run(nucleus: &NucleusFlow) -> nucleusflow::core::error::Result<()> {
+//! let mut site = nucleus.collect()?;
+//! site.pages.retain(|page| !page.is_draft());
+//! let result = nucleus.process_site(&site)?;
+//! println!("{}", result);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Pages are grouped into [`Section`]s by the directory of their output
+//! path. Both types serialise to JSON, which is how templates see them.
+//!
+//! Utility pages, such as a 404 or search page, are built like any
+//! other page but are left out of the sitemap. Templates that list
+//! pages, such as feeds or search indexes, can skip them with the
+//! page's `utility` flag.
+//!
+//! Within a section, listed pages are linked to the pages before and
+//! after them, which templates see as `page.prev` and `page.next`; see
+//! [`Site::navigation`]. Each page also has a breadcrumb trail, from
+//! the home page through the directories above it, which templates see
+//! as `page.breadcrumbs`; see [`Site::breadcrumbs`].
+//!
+//! A page is written at the path of its content file, unless its
+//! frontmatter sets one of [`OUTPUT_PATH_KEYS`]: `output_path` names
+//! the file written, such as `.well-known/security.txt`, and `url` the
+//! address it is served at, such as `/about/`. Either must stay inside
+//! the output directory.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Component, Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::{Map, Value as JsonValue};
+
+use crate::processors::markdown::ReadingStats;
+use crate::{ProcessingError, Result};
+
+/// Output paths of pages treated as utility pages without any
+/// frontmatter.
+pub const UTILITY_PAGES: [&str; 5] = [
+    "404.html",
+    "410.html",
+    "offline.html",
+    "search.html",
+    "search/index.html",
+];
+
+/// The home page's title in breadcrumb trails, when nothing names it.
+pub const HOME_TITLE: &str = "Home";
+
+/// Frontmatter keys overriding where a page is written, in order of
+/// precedence.
+pub const OUTPUT_PATH_KEYS: [&str; 2] = ["output_path", "url"];
+
+/// A single content file, ready to be rendered.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Page {
+    /// The content file the page was read from
+    pub source: PathBuf,
+
+    /// The page's path relative to the output directory
+    pub path: PathBuf,
+
+    /// The page's site-relative URL
+    pub url: String,
+
+    /// The page's frontmatter
+    pub frontmatter: Map<String, JsonValue>,
+
+    /// The processed page content
+    pub content: String,
+
+    /// The page's table of contents, if its content processor set one
+    /// apart from the content
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub toc: Option<String>,
+
+    /// The page's summary, for list pages, feeds and meta descriptions;
+    /// see [`crate::processors::summary`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+
+    /// The content's word count and estimated reading time, exposed to
+    /// templates as `word_count` and `reading_time`
+    #[serde(flatten)]
+    pub reading: ReadingStats,
+}
+
+impl Page {
+    /// Creates a page without content, served at the URL of its output
+    /// path, and without a table of contents, summary or reading stats.
+    ///
+    /// # Arguments
+    /// * `source` - The content file the page is read from.
+    /// * `path` - The page's path relative to the output directory.
+    /// * `frontmatter` - The page's frontmatter.
+    pub fn new(
+        source: impl Into<PathBuf>,
+        path: impl Into<PathBuf>,
+        frontmatter: Map<String, JsonValue>,
+    ) -> Self {
+        let path = path.into();
+        Self {
+            source: source.into(),
+            url: page_url(&path),
+            path,
+            frontmatter,
+            content: String::new(),
+            toc: None,
+            summary: None,
+            reading: ReadingStats::default(),
+        }
+    }
+
+    /// Returns the page's `title` frontmatter, if it is a string.
+    pub fn title(&self) -> Option<&str> {
+        self.string("title")
+    }
+
+    /// Returns the page's `date` frontmatter, if it is a string.
+    pub fn date(&self) -> Option<&str> {
+        self.string("date")
+    }
+
+    /// Returns when the page last changed: its `lastmod` frontmatter,
+    /// falling back to its `date`.
+    pub fn lastmod(&self) -> Option<&str> {
+        self.string("lastmod").or_else(|| self.date())
+    }
+
+    /// Returns the page's `weight` frontmatter, which orders it among
+    /// its section's pages, lightest first.
+    pub fn weight(&self) -> Option<i64> {
+        self.frontmatter.get("weight").and_then(JsonValue::as_i64)
+    }
+
+    /// Returns `true` if the page's frontmatter sets `draft: true`.
+    pub fn is_draft(&self) -> bool {
+        self.frontmatter
+            .get("draft")
+            .and_then(JsonValue::as_bool)
+            .unwrap_or(false)
+    }
+
+    /// Returns `true` if the page is a utility page, which sitemaps,
+    /// feeds and search indexes leave out: its `utility` frontmatter, or
+    /// whether its output path is one of [`UTILITY_PAGES`].
+    pub fn is_utility(&self) -> bool {
+        self.frontmatter
+            .get("utility")
+            .and_then(JsonValue::as_bool)
+            .unwrap_or_else(|| {
+                UTILITY_PAGES.contains(&url_path(&self.path).as_str())
+            })
+    }
+
+    /// Returns the page's `aliases` frontmatter: the old URLs that
+    /// redirect to it, given as a list or a single string.
+    pub fn aliases(&self) -> Vec<&str> {
+        match self.frontmatter.get("aliases") {
+            Some(JsonValue::String(alias)) => vec![alias.as_str()],
+            Some(JsonValue::Array(aliases)) => {
+                aliases.iter().filter_map(JsonValue::as_str).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns the name of the section the page belongs to: the
+    /// directory of its output path, or an empty string at the root.
+    pub fn section(&self) -> String {
+        url_path(self.path.parent().unwrap_or_else(|| Path::new("")))
+    }
+
+    /// Orders pages as sections and series list them: by `weight`,
+    /// lightest first, then by `date`, oldest first, then by path.
+    /// Pages without a weight or date follow those with one.
+    pub fn listing_order(&self, other: &Page) -> Ordering {
+        let key = |page: &Page| {
+            (
+                page.weight().is_none(),
+                page.weight(),
+                page.date().is_none(),
+                page.date().map(str::to_string),
+            )
+        };
+        key(self)
+            .cmp(&key(other))
+            .then_with(|| self.path.cmp(&other.path))
+    }
+
+    /// Returns the page as JSON for template contexts, with its
+    /// `utility` flag.
+    pub fn to_json(&self) -> JsonValue {
+        let mut json =
+            serde_json::to_value(self).unwrap_or(JsonValue::Null);
+        if let JsonValue::Object(map) = &mut json {
+            _ = map.insert(
+                "utility".to_string(),
+                JsonValue::Bool(self.is_utility()),
+            );
+        }
+        json
+    }
+
+    fn string(&self, key: &str) -> Option<&str> {
+        self.frontmatter.get(key).and_then(JsonValue::as_str)
+    }
+}
+
+/// The pages sharing an output directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Section {
+    /// The directory's output-relative path, or empty for the root
+    pub name: String,
+
+    /// The URLs of the section's pages, in path order
+    pub pages: Vec<String>,
+}
+
+/// A link to another page, for navigation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PageLink {
+    /// The page's site-relative URL
+    pub url: String,
+
+    /// The page's title, if its frontmatter sets one
+    pub title: Option<String>,
+}
+
+impl PageLink {
+    /// Creates a link to a page.
+    pub fn new(page: &Page) -> Self {
+        Self {
+            url: page.url.clone(),
+            title: page.title().map(str::to_string),
+        }
+    }
+}
+
+/// The pages before and after a page in its section.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PageNavigation {
+    /// The page before, if the page is not the first
+    pub prev: Option<PageLink>,
+
+    /// The page after, if the page is not the last
+    pub next: Option<PageLink>,
+}
+
+/// A step of a page's breadcrumb trail.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Breadcrumb {
+    /// The step's site-relative URL
+    pub url: String,
+
+    /// The step's title
+    pub title: String,
+}
+
+impl Breadcrumb {
+    /// Returns the breadcrumb trail from the home page to a page.
+    ///
+    /// # Arguments
+    /// * `page` - The page the trail leads to.
+    /// * `home` - The home page's title, unless `title` gives one.
+    /// * `title` - The title of the page at a directory's URL, such as
+    ///   `/docs/`, if there is one; other directories are named after
+    ///   their path segment.
+    ///
+    /// # Returns
+    /// * `Vec<Breadcrumb>` - The home page, each directory above the
+    ///   page, then the page itself, unless it is the home page.
+    pub fn trail<F>(page: &Page, home: &str, title: F) -> Vec<Self>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let mut trail = vec![Self {
+            title: title("/").unwrap_or_else(|| home.to_string()),
+            url: "/".to_string(),
+        }];
+        let segments: Vec<&str> = page
+            .url
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect();
+        let (last, directories) = match segments.split_last() {
+            Some(split) => split,
+            None => return trail,
+        };
+        let mut url = String::from("/");
+        for segment in directories {
+            url.push_str(segment);
+            url.push('/');
+            trail.push(Self {
+                title: title(&url)
+                    .unwrap_or_else(|| (*segment).to_string()),
+                url: url.clone(),
+            });
+        }
+        trail.push(Self {
+            url: page.url.clone(),
+            title: page
+                .title()
+                .unwrap_or_else(|| last.trim_end_matches(".html"))
+                .to_string(),
+        });
+        trail
+    }
+}
+
+/// Every page of a site.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Site {
+    /// The pages, ordered by output path
+    pub pages: Vec<Page>,
+}
+
+impl Site {
+    /// Creates a site from pages, ordering them by output path.
+    pub fn new(mut pages: Vec<Page>) -> Self {
+        pages.sort_by(|a, b| a.path.cmp(&b.path));
+        Self { pages }
+    }
+
+    /// Returns the page at a site-relative URL.
+    pub fn page(&self, url: &str) -> Option<&Page> {
+        self.pages.iter().find(|page| page.url == url)
+    }
+
+    /// Returns the pages that belong in sitemaps, feeds and search
+    /// indexes: those that are neither drafts nor utility pages.
+    pub fn listed_pages(&self) -> impl Iterator<Item = &Page> {
+        self.pages
+            .iter()
+            .filter(|page| !page.is_draft() && !page.is_utility())
+    }
+
+    /// Groups the pages into sections, ordered by name.
+    pub fn sections(&self) -> Vec<Section> {
+        let mut sections: BTreeMap<String, Vec<String>> =
+            BTreeMap::new();
+        for page in &self.pages {
+            sections
+                .entry(page.section())
+                .or_default()
+                .push(page.url.clone());
+        }
+        sections
+            .into_iter()
+            .map(|(name, pages)| Section { name, pages })
+            .collect()
+    }
+
+    /// Links each listed page to the pages before and after it in its
+    /// section.
+    ///
+    /// A section's pages are ordered by [`Page::listing_order`].
+    /// Section index pages, whose file is `index.html`, list the others
+    /// and are not linked.
+    ///
+    /// # Returns
+    /// * `HashMap<String, PageNavigation>` - The links of each linked
+    ///   page, keyed by its URL.
+    pub fn navigation(&self) -> HashMap<String, PageNavigation> {
+        let mut sections: BTreeMap<String, Vec<&Page>> =
+            BTreeMap::new();
+        for page in self.listed_pages().filter(|page| {
+            page.path
+                .file_name()
+                .map_or(true, |name| name != "index.html")
+        }) {
+            sections.entry(page.section()).or_default().push(page);
+        }
+
+        let mut navigation = HashMap::new();
+        for pages in sections.values_mut() {
+            pages.sort_by(|a, b| a.listing_order(b));
+            for (index, page) in pages.iter().enumerate() {
+                let prev = index
+                    .checked_sub(1)
+                    .map(|prev| PageLink::new(pages[prev]));
+                let next = pages
+                    .get(index + 1)
+                    .map(|next| PageLink::new(next));
+                _ = navigation.insert(
+                    page.url.clone(),
+                    PageNavigation { prev, next },
+                );
+            }
+        }
+        navigation
+    }
+
+    /// Returns the breadcrumb trail of each page, from the home page
+    /// through the directories above it; see [`Breadcrumb::trail`].
+    ///
+    /// Directories are titled by their index pages' titles, such as
+    /// the `title` of `docs/index.html` for `/docs/`.
+    ///
+    /// # Arguments
+    /// * `home` - The home page's title, unless its index page has one.
+    ///
+    /// # Returns
+    /// * `HashMap<String, Vec<Breadcrumb>>` - The trail of each page,
+    ///   keyed by its URL.
+    pub fn breadcrumbs(
+        &self,
+        home: &str,
+    ) -> HashMap<String, Vec<Breadcrumb>> {
+        let titles: HashMap<&str, &str> = self
+            .pages
+            .iter()
+            .filter_map(|page| Some((page.url.as_str(), page.title()?)))
+            .collect();
+        let title = |url: &str| titles.get(url).map(|t| t.to_string());
+        self.pages
+            .iter()
+            .map(|page| {
+                (page.url.clone(), Breadcrumb::trail(page, home, title))
+            })
+            .collect()
+    }
+
+    /// Returns the site as JSON, with its pages and sections.
+    pub fn to_json(&self) -> JsonValue {
+        serde_json::json!({
+            "pages": self
+                .pages
+                .iter()
+                .map(Page::to_json)
+                .collect::<Vec<_>>(),
+            "sections": self.sections(),
+        })
+    }
+}
+
+/// Returns the site-relative URL of an output-relative page path, with
+/// `index.html` pages addressed by their directory.
+pub(crate) fn page_url(relative_path: &Path) -> String {
+    let mut segments: Vec<_> = relative_path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect();
+    if segments.last().map_or(false, |name| name == "index.html") {
+        _ = segments.pop();
+        segments.push("".into());
+    }
+    format!("/{}", segments.join("/"))
+}
+
+/// Returns the output path of a generated page, such as an archive,
+/// from its configured path with its placeholders replaced.
+///
+/// # Arguments
+/// * `kind` - The kind of page, for the error message.
+/// * `pattern` - The configured path, for the error message.
+/// * `path` - The path to write, where a path ending with `/` is
+///   written as the directory's `index.html`.
+///
+/// # Returns
+/// * `Result<PathBuf>` - The output-relative path, or an error if it
+///   is outside the output directory.
+pub(crate) fn generated_path(
+    kind: &str,
+    pattern: &str,
+    path: &str,
+) -> Result<PathBuf> {
+    let mut relative = PathBuf::from(path.trim_start_matches('/'));
+    if path.ends_with('/') {
+        relative.push("index.html");
+    }
+    if relative.as_os_str().is_empty()
+        || relative
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_)))
+    {
+        return Err(ProcessingError::validation(
+            format!(
+                "{} path '{}' is outside the output directory",
+                kind, pattern
+            ),
+            None::<String>,
+        ));
+    }
+    Ok(relative)
+}
+
+/// Returns a relative path joined with `/` on every platform.
+fn url_path(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Returns a page read from `content/<path>`, for tests.
+    pub(crate) fn page(path: &str, frontmatter: JsonValue) -> Page {
+        Page::new(
+            PathBuf::from("content").join(path),
+            path,
+            frontmatter.as_object().cloned().unwrap_or_default(),
+        )
+    }
+
+    #[test]
+    fn test_page_accessors() {
+        let page = page(
+            "blog/post.html",
+            serde_json::json!({
+                "title": "Post",
+                "date": "2024-03-01",
+                "draft": true,
+            }),
+        );
+        assert_eq!(page.title(), Some("Post"));
+        assert_eq!(page.lastmod(), Some("2024-03-01"));
+        assert!(page.is_draft());
+        assert_eq!(page.section(), "blog");
+        assert_eq!(page.to_json()["frontmatter"]["title"], "Post");
+    }
+
+    #[test]
+    fn test_site_sections() {
+        let site = Site::new(vec![
+            page("blog/b.html", serde_json::json!({})),
+            page("index.html", serde_json::json!({})),
+            page("blog/a.html", serde_json::json!({})),
+        ]);
+
+        assert_eq!(site.pages[0].url, "/blog/a.html");
+        assert!(site.page("/").is_some());
+        assert_eq!(
+            site.sections(),
+            [
+                Section {
+                    name: String::new(),
+                    pages: vec!["/".to_string()],
+                },
+                Section {
+                    name: "blog".to_string(),
+                    pages: vec![
+                        "/blog/a.html".to_string(),
+                        "/blog/b.html".to_string()
+                    ],
+                },
+            ]
+        );
+        assert_eq!(site.to_json()["sections"][1]["name"], "blog");
+    }
+
+    #[test]
+    fn test_site_navigation() {
+        let site = Site::new(vec![
+            page("blog/index.html", serde_json::json!({})),
+            page("blog/c.html", serde_json::json!({ "title": "C" })),
+            page(
+                "blog/b.html",
+                serde_json::json!({ "date": "2024-02-01" }),
+            ),
+            page(
+                "blog/a.html",
+                serde_json::json!({ "date": "2024-03-01" }),
+            ),
+            page("blog/first.html", serde_json::json!({ "weight": 1 })),
+            page(
+                "blog/draft.html",
+                serde_json::json!({ "draft": true }),
+            ),
+            page("about.html", serde_json::json!({})),
+        ]);
+        let navigation = site.navigation();
+        let order = |url: &str| {
+            let links = &navigation[url];
+            (
+                links.prev.as_ref().map(|link| link.url.as_str()),
+                links.next.as_ref().map(|link| link.url.as_str()),
+            )
+        };
+
+        assert_eq!(
+            order("/blog/first.html"),
+            (None, Some("/blog/b.html"))
+        );
+        assert_eq!(
+            order("/blog/b.html"),
+            (Some("/blog/first.html"), Some("/blog/a.html"))
+        );
+        assert_eq!(
+            navigation["/blog/a.html"].next,
+            Some(PageLink {
+                url: "/blog/c.html".to_string(),
+                title: Some("C".to_string()),
+            })
+        );
+        assert_eq!(order("/blog/c.html"), (Some("/blog/a.html"), None));
+        assert_eq!(order("/about.html"), (None, None));
+        assert!(!navigation.contains_key("/blog/index.html"));
+        assert!(!navigation.contains_key("/blog/draft.html"));
+    }
+
+    #[test]
+    fn test_site_breadcrumbs() {
+        let mut docs = page("docs/index.html", serde_json::json!({}));
+        docs.url = "/docs/".to_string();
+        docs.frontmatter = serde_json::json!({ "title": "Docs" })
+            .as_object()
+            .cloned()
+            .unwrap();
+        let mut home = page("index.html", serde_json::json!({}));
+        home.url = "/".to_string();
+        let site = Site::new(vec![
+            home,
+            docs,
+            page(
+                "docs/guides/install.html",
+                serde_json::json!({ "title": "Install" }),
+            ),
+            page("docs/faq.html", serde_json::json!({})),
+        ]);
+        let breadcrumbs = site.breadcrumbs("Home");
+        let trail = |url: &str| -> Vec<(&str, &str)> {
+            breadcrumbs[url]
+                .iter()
+                .map(|crumb| (crumb.url.as_str(), crumb.title.as_str()))
+                .collect()
+        };
+
+        assert_eq!(trail("/"), [("/", "Home")]);
+        assert_eq!(
+            trail("/docs/"),
+            [("/", "Home"), ("/docs/", "Docs")]
+        );
+        assert_eq!(
+            trail("/docs/guides/install.html"),
+            [
+                ("/", "Home"),
+                ("/docs/", "Docs"),
+                ("/docs/guides/", "guides"),
+                ("/docs/guides/install.html", "Install"),
+            ]
+        );
+        assert_eq!(
+            trail("/docs/faq.html")[2],
+            ("/docs/faq.html", "faq")
+        );
+    }
+
+    #[test]
+    fn test_utility_pages() {
+        let site = Site::new(vec![
+            page("404.html", serde_json::json!({})),
+            page("search/index.html", serde_json::json!({})),
+            page(
+                "search.html",
+                serde_json::json!({ "utility": false }),
+            ),
+            page("offline/index.html", serde_json::json!({})),
+            page("thanks.html", serde_json::json!({ "utility": true })),
+            page("draft.html", serde_json::json!({ "draft": true })),
+        ]);
+
+        let listed: Vec<_> =
+            site.listed_pages().map(|page| page.url.as_str()).collect();
+        assert_eq!(listed, ["/offline/", "/search.html"]);
+        assert!(site.pages[0].is_utility());
+        assert_eq!(site.to_json()["pages"][0]["utility"], true);
+        assert_eq!(
+            site.page("/search.html").unwrap().to_json()["utility"],
+            false
+        );
+    }
+}
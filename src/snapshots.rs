@@ -0,0 +1,379 @@
+//! # Template Snapshot Tests
+//!
+//! Renders templates against sample contexts and compares the output
+//! with stored snapshots, so a theme can be refactored and verified
+//! without building a whole site.
+//!
+//! Each sample context is a JSON file in the fixtures directory, usually
+//! `tests/templates/`. The part of its name before the first `.` names
+//! the template it renders, so several contexts can exercise the same
+//! template, and its snapshot is stored beside it with an `.html`
+//! extension:
+//!
+//! ```text
+//! tests/templates/
+//! ├── post.json          renders `post`
+//! ├── post.html          snapshot of post.json
+//! ├── post.draft.json    renders `post` with a draft context
+//! └── post.draft.html    snapshot of post.draft.json
+//! ```
+//!
+//! A case without a snapshot, or whose output differs from it, fails
+//! until the snapshots are updated.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use nucleusflow::snapshots::TemplateSnapshots;
+//! use nucleusflow::template::HandlebarsRenderer;
+//! use std::path::Path;
+//!
+//! let renderer = HandlebarsRenderer::new(Path::new("templates")).unwrap();
+//! let report = TemplateSnapshots::new("tests/templates")
+//!     .run(&renderer, false)
+//!     .unwrap();
+//! print!("{}", report);
+//! assert!(report.passed());
+//! ```
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value as JsonValue;
+
+use crate::{ProcessingError, Result, TemplateRenderer};
+
+/// The extension of sample context files.
+const CONTEXT_EXTENSION: &str = "json";
+
+/// The extension of snapshot files.
+const SNAPSHOT_EXTENSION: &str = "html";
+
+/// The outcome of a single snapshot case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotOutcome {
+    /// The output matches the snapshot
+    Passed,
+    /// The snapshot was written or rewritten with the output
+    Updated,
+    /// There is no snapshot for the case
+    Missing,
+    /// The output differs from the snapshot
+    Changed {
+        /// The first differing line, counting from 1
+        line: usize,
+        /// The snapshot's version of the line
+        expected: String,
+        /// The output's version of the line
+        actual: String,
+    },
+    /// The context could not be read or the template failed to render
+    Failed(String),
+}
+
+impl SnapshotOutcome {
+    /// Returns `true` unless the case failed.
+    pub fn is_ok(&self) -> bool {
+        matches!(
+            self,
+            SnapshotOutcome::Passed | SnapshotOutcome::Updated
+        )
+    }
+}
+
+/// A snapshot case and its outcome.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotCase {
+    /// The sample context file
+    pub context: PathBuf,
+    /// The template the context was rendered with
+    pub template: String,
+    /// What happened
+    pub outcome: SnapshotOutcome,
+}
+
+/// The outcomes of a snapshot run, ordered by context file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SnapshotReport {
+    /// Every case that was run
+    pub cases: Vec<SnapshotCase>,
+}
+
+impl SnapshotReport {
+    /// Returns `true` if no case failed.
+    pub fn passed(&self) -> bool {
+        self.cases.iter().all(|case| case.outcome.is_ok())
+    }
+
+    /// Returns the number of failed cases.
+    pub fn failures(&self) -> usize {
+        self.cases
+            .iter()
+            .filter(|case| !case.outcome.is_ok())
+            .count()
+    }
+}
+
+impl fmt::Display for SnapshotReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for case in &self.cases {
+            let name = case.context.display();
+            match &case.outcome {
+                SnapshotOutcome::Passed => {
+                    writeln!(f, "ok       {}", name)?
+                }
+                SnapshotOutcome::Updated => {
+                    writeln!(f, "updated  {}", name)?
+                }
+                SnapshotOutcome::Missing => {
+                    writeln!(f, "missing  {} (no snapshot)", name)?
+                }
+                SnapshotOutcome::Changed {
+                    line,
+                    expected,
+                    actual,
+                } => {
+                    writeln!(f, "changed  {} (line {})", name, line)?;
+                    writeln!(f, "  - {}", expected)?;
+                    writeln!(f, "  + {}", actual)?;
+                }
+                SnapshotOutcome::Failed(error) => {
+                    writeln!(f, "failed   {}: {}", name, error)?
+                }
+            }
+        }
+        writeln!(
+            f,
+            "{} cases, {} failed",
+            self.cases.len(),
+            self.failures()
+        )
+    }
+}
+
+/// Snapshot tests for the templates of a renderer.
+#[derive(Debug, Clone)]
+pub struct TemplateSnapshots {
+    fixtures_dir: PathBuf,
+}
+
+impl TemplateSnapshots {
+    /// Creates snapshot tests reading sample contexts and snapshots
+    /// from a directory.
+    pub fn new<P: AsRef<Path>>(fixtures_dir: P) -> Self {
+        Self {
+            fixtures_dir: fixtures_dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Renders every sample context and compares or updates its
+    /// snapshot.
+    ///
+    /// # Arguments
+    /// * `renderer` - The renderer holding the templates under test.
+    /// * `update` - Writes the output as the snapshot of every case
+    ///   that renders, instead of comparing.
+    ///
+    /// # Returns
+    /// * `Result<SnapshotReport>` - The outcome of each case, or an
+    ///   error if the fixtures directory or a snapshot cannot be read
+    ///   or written.
+    pub fn run(
+        &self,
+        renderer: &dyn TemplateRenderer,
+        update: bool,
+    ) -> Result<SnapshotReport> {
+        let mut report = SnapshotReport::default();
+        for context in self.contexts()? {
+            let template = template_name(&context);
+            let outcome =
+                match render_context(renderer, &template, &context) {
+                    Ok(output) => {
+                        check_snapshot(&context, &output, update)?
+                    }
+                    Err(e) => SnapshotOutcome::Failed(e.to_string()),
+                };
+            report.cases.push(SnapshotCase {
+                context,
+                template,
+                outcome,
+            });
+        }
+        Ok(report)
+    }
+
+    /// Returns the sample context files, sorted by name.
+    fn contexts(&self) -> Result<Vec<PathBuf>> {
+        let entries =
+            fs::read_dir(&self.fixtures_dir).map_err(|e| {
+                ProcessingError::io_error(self.fixtures_dir.clone(), e)
+            })?;
+        let mut contexts = Vec::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|e| {
+                    ProcessingError::io_error(
+                        self.fixtures_dir.clone(),
+                        e,
+                    )
+                })?
+                .path();
+            if path.is_file()
+                && path
+                    .extension()
+                    .map_or(false, |ext| ext == CONTEXT_EXTENSION)
+            {
+                contexts.push(path);
+            }
+        }
+        contexts.sort();
+        Ok(contexts)
+    }
+}
+
+/// Returns the template a context file renders: its name up to the
+/// first `.`.
+fn template_name(context: &Path) -> String {
+    let name = context
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    name.split('.').next().unwrap_or_default().to_string()
+}
+
+/// Reads a context file and renders the template with it.
+fn render_context(
+    renderer: &dyn TemplateRenderer,
+    template: &str,
+    context: &Path,
+) -> Result<String> {
+    let json = fs::read_to_string(context).map_err(|e| {
+        ProcessingError::io_error(context.to_path_buf(), e)
+    })?;
+    let context: JsonValue = serde_json::from_str(&json)?;
+    renderer.validate(template, &context)?;
+    renderer.render(template, &context)
+}
+
+/// Compares output with the snapshot of a context, or writes it.
+fn check_snapshot(
+    context: &Path,
+    output: &str,
+    update: bool,
+) -> Result<SnapshotOutcome> {
+    let path = context.with_extension(SNAPSHOT_EXTENSION);
+    if update {
+        fs::write(&path, output)
+            .map_err(|e| ProcessingError::io_error(path.clone(), e))?;
+        return Ok(SnapshotOutcome::Updated);
+    }
+    if !path.exists() {
+        return Ok(SnapshotOutcome::Missing);
+    }
+
+    let snapshot = fs::read_to_string(&path)
+        .map_err(|e| ProcessingError::io_error(path.clone(), e))?;
+    Ok(compare(&snapshot, output))
+}
+
+/// Compares output with a snapshot line by line.
+pub(crate) fn compare(snapshot: &str, output: &str) -> SnapshotOutcome {
+    let mut expected = snapshot.lines();
+    let mut actual = output.lines();
+    let mut line = 1;
+    loop {
+        match (expected.next(), actual.next()) {
+            (None, None) => return SnapshotOutcome::Passed,
+            (Some(a), Some(b)) if a == b => line += 1,
+            (a, b) => {
+                return SnapshotOutcome::Changed {
+                    line,
+                    expected: a.unwrap_or_default().to_string(),
+                    actual: b.unwrap_or_default().to_string(),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::template::HandlebarsRenderer;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_template_name() {
+        assert_eq!(template_name(Path::new("t/post.json")), "post");
+        assert_eq!(
+            template_name(Path::new("t/post.draft.json")),
+            "post"
+        );
+    }
+
+    #[test]
+    fn test_compare() {
+        assert_eq!(compare("a\nb\n", "a\nb"), SnapshotOutcome::Passed);
+        assert_eq!(
+            compare("a\nb\n", "a\nc\n"),
+            SnapshotOutcome::Changed {
+                line: 2,
+                expected: "b".to_string(),
+                actual: "c".to_string(),
+            }
+        );
+        assert!(matches!(
+            compare("a\n", "a\nb\n"),
+            SnapshotOutcome::Changed { line: 2, .. }
+        ));
+    }
+
+    #[test]
+    fn test_snapshot_run() -> Result<()> {
+        let temp_dir = TempDir::new().unwrap();
+        let templates = temp_dir.path().join("templates");
+        let fixtures = temp_dir.path().join("fixtures");
+        fs::create_dir(&templates)?;
+        fs::create_dir(&fixtures)?;
+        fs::write(templates.join("post.hbs"), "<h1>{{title}}</h1>")?;
+        fs::write(fixtures.join("post.json"), r#"{"title": "One"}"#)?;
+        fs::write(
+            fixtures.join("post.two.json"),
+            r#"{"title": "Two"}"#,
+        )?;
+        fs::write(fixtures.join("missing.json"), "{}")?;
+
+        let renderer = HandlebarsRenderer::new(&templates)?;
+        let snapshots = TemplateSnapshots::new(&fixtures);
+
+        let report = snapshots.run(&renderer, false)?;
+        assert_eq!(report.failures(), 3);
+        assert!(matches!(
+            report.cases[0].outcome,
+            SnapshotOutcome::Failed(_)
+        ));
+        assert_eq!(report.cases[1].outcome, SnapshotOutcome::Missing);
+
+        let report = snapshots.run(&renderer, true)?;
+        assert_eq!(report.failures(), 1);
+        assert_eq!(
+            fs::read_to_string(fixtures.join("post.two.html"))?,
+            "<h1>Two</h1>"
+        );
+
+        fs::write(fixtures.join("post.json"), r#"{"title": "New"}"#)?;
+        let report = snapshots.run(&renderer, false)?;
+        assert_eq!(
+            report.cases[1].outcome,
+            SnapshotOutcome::Changed {
+                line: 1,
+                expected: "<h1>One</h1>".to_string(),
+                actual: "<h1>New</h1>".to_string(),
+            }
+        );
+        assert_eq!(report.cases[2].outcome, SnapshotOutcome::Passed);
+        assert!(report.to_string().ends_with("3 cases, 2 failed\n"));
+        Ok(())
+    }
+}
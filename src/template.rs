@@ -12,6 +12,9 @@
 //! - Partial template support
 //! - Custom helper registration
 
+use crate::core::cache::{CacheCounter, CacheStats};
+use crate::core::error::{line_span, SourceSnippet};
+use crate::plugin::PluginRegistry;
 use crate::{ProcessingError, Result, TemplateRenderer};
 use handlebars::{
     Context, Handlebars, Helper, Output, RenderContext, RenderError,
@@ -21,12 +24,17 @@
 use serde_json::Value as JsonValue;
 use std::collections::HashMap;
 use std::convert::From;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 /// Represents a custom template helper with helper name and execution.
 pub trait TemplateHelper: Send + Sync {
     /// Executes the helper with the given parameters and context.
+    ///
+    /// Templates write a string result as it is, so `{{uppercase
+    /// title}}` writes `HELLO` rather than `"HELLO"`, and any other
+    /// result as JSON.
     fn execute(
         &self,
         params: &[JsonValue],
@@ -37,6 +45,20 @@ fn execute(
     fn name(&self) -> &str;
 }
 
+impl<H: TemplateHelper + ?Sized> TemplateHelper for Arc<H> {
+    fn execute(
+        &self,
+        params: &[JsonValue],
+        context: &JsonValue,
+    ) -> Result<JsonValue> {
+        (**self).execute(params, context)
+    }
+
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+}
+
 /// Provides details for template validation errors.
 #[derive(Debug, Clone)]
 pub struct ValidationError {
@@ -48,14 +70,49 @@ pub struct ValidationError {
     pub column: Option<usize>,
     /// Template source snippet where error occurred
     pub source: Option<String>,
+    /// Byte range of the offending expression in `source`, if known
+    pub span: Option<Range<usize>>,
+}
+
+impl ValidationError {
+    /// Returns the span of the template the error points at, falling
+    /// back to the expression at its line and column.
+    ///
+    /// # Arguments
+    /// * `name` - The name shown for the template, usually its path.
+    ///
+    /// # Returns
+    /// * `Option<SourceSnippet>` - The snippet, or `None` if the source
+    ///   or position of the error is unknown.
+    pub fn snippet(&self, name: &str) -> Option<SourceSnippet> {
+        let source = self.source.as_ref()?;
+        let span = match (&self.span, self.line) {
+            (Some(span), _) => span.clone(),
+            (None, Some(line)) => {
+                expression_span(source, line, self.column.unwrap_or(1))
+            }
+            (None, None) => return None,
+        };
+        Some(SourceSnippet::new(
+            name,
+            source.as_str(),
+            span,
+            self.details.as_str(),
+        ))
+    }
 }
 
 impl From<ValidationError> for ProcessingError {
     fn from(error: ValidationError) -> Self {
-        ProcessingError::TemplateProcessing {
+        let snippet = error.snippet("template");
+        let error = ProcessingError::TemplateProcessing {
             details: error.details,
             template_name: String::new(),
             source: None,
+        };
+        match snippet {
+            Some(snippet) => error.with_snippet(snippet),
+            None => error,
         }
     }
 }
@@ -66,6 +123,7 @@ pub struct HandlebarsRenderer {
     engine: Arc<RwLock<Handlebars<'static>>>, // Handlebars engine
     template_dir: PathBuf,                    // Directory for templates
     template_cache: Arc<RwLock<HashMap<String, String>>>, // Cache for loaded templates
+    cache_counter: Arc<CacheCounter>, // Template cache hits and misses
     helpers: Arc<RwLock<HashMap<String, Box<dyn TemplateHelper>>>>, // Custom registered helpers
     strict_mode: bool, // Flag for strict mode
 }
@@ -82,6 +140,18 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 impl HandlebarsRenderer {
     /// Creates a new instance of `HandlebarsRenderer`.
     pub fn new(template_dir: &Path) -> Result<Self> {
+        Self::with_plugins(template_dir, &PluginRegistry::new())
+    }
+
+    /// Creates a renderer with the helpers of every enabled plugin
+    /// registered under their namespaced `plugin:helper` names.
+    ///
+    /// The helpers are registered before the templates are loaded, so
+    /// templates that call them pass validation.
+    pub fn with_plugins(
+        template_dir: &Path,
+        plugins: &PluginRegistry,
+    ) -> Result<Self> {
         let mut handlebars = Handlebars::new();
         handlebars.set_dev_mode(cfg!(debug_assertions));
         handlebars.register_escape_fn(handlebars::html_escape);
@@ -90,12 +160,18 @@ pub fn new(template_dir: &Path) -> Result<Self> {
             engine: Arc::new(RwLock::new(handlebars)),
             template_dir: template_dir.to_path_buf(),
             template_cache: Arc::new(RwLock::new(HashMap::new())),
+            cache_counter: Arc::new(CacheCounter::default()),
             helpers: Arc::new(RwLock::new(HashMap::new())),
             strict_mode: false,
         };
 
         renderer =
             renderer.with_helper("uppercase", helpers::UppercaseHelper);
+        renderer = renderer
+            .with_helper("placeholder", helpers::PlaceholderHelper);
+        for (name, helper) in plugins.helpers() {
+            renderer = renderer.with_helper(name, Arc::clone(helper));
+        }
         renderer.load_templates()?;
         Ok(renderer)
     }
@@ -116,7 +192,7 @@ pub fn with_helper<H>(self, name: &str, helper: H) -> Self
             .helpers
             .write()
             .insert(name.to_string(), Box::new(helper.clone()));
-        self.register_helper(name, helper);
+        register_helper(&mut self.engine.write(), name, helper);
         self
     }
 
@@ -142,7 +218,6 @@ pub fn with_partial(
 
     /// Loads templates from the directory, caching and validating them.
     fn load_templates(&self) -> Result<()> {
-        let mut engine = self.engine.write();
         let mut cache = self.template_cache.write();
 
         for entry in
@@ -198,17 +273,30 @@ fn load_templates(&self) -> Result<()> {
                 })?;
 
                 self.validate_template(&template_content).map_err(
-                    |e| ProcessingError::TemplateProcessing {
-                        details: format!(
-                            "Template validation failed: {}",
-                            e
-                        ),
-                        template_name: template_name.to_string(),
-                        source: None,
+                    |e| {
+                        let error =
+                            ProcessingError::TemplateProcessing {
+                                details: format!(
+                                    "Template validation failed: {}",
+                                    e.details
+                                ),
+                                template_name: template_name
+                                    .to_string(),
+                                source: None,
+                            };
+                        match e.snippet(&path.display().to_string()) {
+                            Some(snippet) => {
+                                error.with_snippet(snippet)
+                            }
+                            None => error,
+                        }
                     },
                 )?;
 
-                engine
+                // Validation takes its own read lock, so the engine is
+                // only locked for writing once the template has passed.
+                self.engine
+                    .write()
                     .register_template_string(
                         template_name,
                         &template_content,
@@ -233,78 +321,63 @@ fn load_templates(&self) -> Result<()> {
         Ok(())
     }
 
-    /// Registers a helper function with the Handlebars engine.
-    fn register_helper<H>(&self, name: &str, helper: H)
-    where
-        H: TemplateHelper + 'static,
-    {
-        let helper_fn = move |h: &Helper,
-                              _: &Handlebars,
-                              ctx: &Context,
-                              _: &mut RenderContext,
-                              out: &mut dyn Output|
-              -> std::result::Result<
-            (),
-            RenderError,
-        > {
-            let params: Vec<JsonValue> =
-                h.params().iter().map(|p| p.value().clone()).collect();
-
-            let result =
-                helper.execute(&params, ctx.data()).map_err(|e| {
-                    RenderError::from(RenderErrorReason::Other(
-                        e.to_string(),
-                    ))
-                })?;
-            out.write(&result.to_string())?;
-            Ok(())
-        };
-
-        self.engine
-            .write()
-            .register_helper(name, Box::new(helper_fn));
-    }
-
     /// Validates the template syntax to catch errors early.
-    fn validate_template(&self, template: &str) -> Result<()> {
+    fn validate_template(
+        &self,
+        template: &str,
+    ) -> std::result::Result<(), ValidationError> {
         let engine = self.engine.read();
-        _ = engine
-            .render_template(template, &JsonValue::Null)
-            .map_err(|e| ValidationError {
-                details: e.to_string(),
-                line: e.line_no,
-                column: e.column_no,
+        if let Err(e) =
+            engine.render_template(template, &JsonValue::Null)
+        {
+            let (details, position) = match e.reason() {
+                RenderErrorReason::TemplateError(error) => {
+                    (error.reason().to_string(), error.pos())
+                }
+                reason => {
+                    (reason.to_string(), e.line_no.zip(e.column_no))
+                }
+            };
+            return Err(ValidationError {
+                details,
+                line: position.map(|(line, _)| line),
+                column: position.map(|(_, column)| column),
                 source: Some(template.to_string()),
-            })?;
+                span: None,
+            });
+        }
 
         let mut brackets = Vec::new();
-        let mut in_tag = false;
+        let unmatched = |details: &str, offset: usize| {
+            let line = template[..offset].matches('\n').count() + 1;
+            let line_start =
+                template[..offset].rfind('\n').map_or(0, |i| i + 1);
+            ValidationError {
+                details: details.to_string(),
+                line: Some(line),
+                column: Some(
+                    template[line_start..offset].chars().count() + 1,
+                ),
+                source: Some(template.to_string()),
+                span: Some(offset..offset + 1),
+            }
+        };
 
-        for (i, c) in template.chars().enumerate() {
+        for (i, c) in template.char_indices() {
             match c {
-                '{' if in_tag => brackets.push(('{', i)),
+                '{' => brackets.push(i),
                 '}' if brackets.pop().is_none() => {
-                    return Err(ValidationError {
-                        details: "Unmatched closing brace".to_string(),
-                        line: None,
-                        column: Some(i),
-                        source: Some(template.to_string()),
-                    }
-                    .into());
+                    return Err(unmatched(
+                        "Unmatched closing brace",
+                        i,
+                    ));
                 }
-                '{' => in_tag = true,
                 _ => {}
             }
         }
 
-        if !brackets.is_empty() {
-            return Err(ValidationError {
-                details: "Unmatched opening brace".to_string(),
-                line: None,
-                column: Some(brackets[0].1),
-                source: Some(template.to_string()),
-            }
-            .into());
+        if let Some(&offset) = brackets.first() {
+            return Err(unmatched("Unmatched opening brace", offset));
         }
 
         Ok(())
@@ -372,6 +445,8 @@ fn render(
         template: &str,
         context: &JsonValue,
     ) -> Result<String> {
+        self.cache_counter
+            .record(self.template_cache.read().contains_key(template));
         if self.strict_mode {
             self.validate_context(template, context)?;
         }
@@ -404,6 +479,131 @@ fn validate(
 
         Ok(())
     }
+
+    fn cache_stats(&self) -> Option<CacheStats> {
+        Some(self.cache_counter.stats())
+    }
+}
+
+/// Renders an inline error page for a template that failed to render.
+///
+/// Used by error recovery mode so a single broken template does not
+/// block previewing the rest of the site. The page names the template,
+/// the source file and the error, all HTML-escaped. It is intended for
+/// development builds only and must never be deployed.
+///
+/// # Arguments
+/// * `template` - The name of the template that failed.
+/// * `page` - The path of the page being rendered.
+/// * `error` - The rendering error.
+///
+/// # Returns
+/// * `String` - A standalone HTML document describing the failure.
+pub fn render_error_placeholder(
+    template: &str,
+    page: &Path,
+    error: &ProcessingError,
+) -> String {
+    format!(
+        concat!(
+            "<!DOCTYPE html>\n",
+            "<html lang=\"en\">\n",
+            "<head><meta charset=\"utf-8\">",
+            "<meta name=\"robots\" content=\"noindex\">",
+            "<title>Template error</title></head>\n",
+            "<body>\n",
+            "<div class=\"nucleusflow-template-error\" ",
+            "style=\"font-family:monospace;border:2px solid #c00;",
+            "padding:1em;margin:1em;background:#fff0f0\">\n",
+            "<h1>Template error</h1>\n",
+            "<p><strong>Template:</strong> {}</p>\n",
+            "<p><strong>Page:</strong> {}</p>\n",
+            "<pre>{}</pre>\n",
+            "</div>\n",
+            "</body>\n",
+            "</html>\n"
+        ),
+        handlebars::html_escape(template),
+        handlebars::html_escape(&page.display().to_string()),
+        handlebars::html_escape(&error.to_string()),
+    )
+}
+
+/// Returns the byte range of the `{{ ... }}` expression at a line and
+/// column of `source`, both numbered from 1. The range runs to the end
+/// of the line when the expression is not closed on it, and is empty at
+/// the end of `source` for positions past its last line.
+fn expression_span(
+    source: &str,
+    line: usize,
+    column: usize,
+) -> Range<usize> {
+    let text = match line_span(source, line) {
+        Some(text) => text,
+        None => return source.len()..source.len(),
+    };
+    let line_start =
+        source[..text.start].rfind('\n').map_or(0, |i| i + 1);
+    let offset = source[line_start..text.end]
+        .char_indices()
+        .nth(column.saturating_sub(1))
+        .map_or(text.end, |(i, _)| line_start + i)
+        .max(text.start);
+
+    let start = if source[offset..].starts_with("{{") {
+        offset
+    } else {
+        source[text.start..offset]
+            .rfind("{{")
+            .map_or(offset, |i| text.start + i)
+    };
+    let end = source[offset..text.end]
+        .find("}}")
+        .map_or(text.end, |i| offset + i + 2);
+    if start < end {
+        start..end
+    } else {
+        text
+    }
+}
+
+/// Registers a helper function with a Handlebars engine.
+fn register_helper<H>(
+    engine: &mut Handlebars<'static>,
+    name: &str,
+    helper: H,
+) where
+    H: TemplateHelper + 'static,
+{
+    let helper_fn = move |h: &Helper,
+                          _: &Handlebars,
+                          ctx: &Context,
+                          _: &mut RenderContext,
+                          out: &mut dyn Output|
+          -> std::result::Result<(), RenderError> {
+        let params: Vec<JsonValue> =
+            h.params().iter().map(|p| p.value().clone()).collect();
+
+        let result =
+            helper.execute(&params, ctx.data()).map_err(|e| {
+                RenderError::from(RenderErrorReason::Other(
+                    e.to_string(),
+                ))
+            })?;
+        out.write(&helper_output(result))?;
+        Ok(())
+    };
+
+    engine.register_helper(name, Box::new(helper_fn));
+}
+
+/// Returns the text a template writes for a helper's result: strings
+/// as they are, and any other value as JSON.
+fn helper_output(value: JsonValue) -> String {
+    match value {
+        JsonValue::String(text) => text,
+        other => other.to_string(),
+    }
 }
 
 /// Built-in helpers for template processing.
@@ -437,4 +637,288 @@ fn name(&self) -> &str {
             "uppercase"
         }
     }
+
+    /// Helper to look up a generated image placeholder.
+    ///
+    /// Reads from the `placeholders` object of the rendering context, as
+    /// produced by `HtmlGenerator::placeholders_context`. Usage:
+    /// `{{placeholder "img/hero.jpg"}}` returns the data URI, while
+    /// `{{placeholder "img/hero.jpg" "blurhash"}}` returns another field.
+    #[derive(Debug, Clone, Copy)]
+    pub struct PlaceholderHelper;
+
+    impl TemplateHelper for PlaceholderHelper {
+        fn execute(
+            &self,
+            params: &[JsonValue],
+            context: &JsonValue,
+        ) -> Result<JsonValue> {
+            let path = params
+                .first()
+                .and_then(|p| p.as_str())
+                .ok_or_else(|| ProcessingError::TemplateProcessing {
+                    details:
+                        "Placeholder helper requires an image path"
+                            .to_string(),
+                    template_name: String::new(),
+                    source: None,
+                })?;
+            let field = params
+                .get(1)
+                .and_then(|p| p.as_str())
+                .unwrap_or("data_uri");
+
+            Ok(context
+                .get("placeholders")
+                .and_then(|p| p.get(path.trim_start_matches('/')))
+                .and_then(|p| p.get(field))
+                .cloned()
+                .unwrap_or_else(|| JsonValue::String(String::new())))
+        }
+
+        fn name(&self) -> &str {
+            "placeholder"
+        }
+    }
+}
+
+/// A harness for unit testing [`TemplateHelper`] implementations.
+///
+/// [`HelperHarness`] runs a helper the way templates call it, without
+/// a template directory or a [`HandlebarsRenderer`]: directly with a
+/// list of parameters, or through a template string rendered by a bare
+/// Handlebars engine, so parameter parsing and output formatting match
+/// real templates.
+///
+/// # Examples
+///
+/// ```rust
+/// use nucleusflow::template::helpers::UppercaseHelper;
+/// use nucleusflow::template::testing::HelperHarness;
+/// use serde_json::json;
+///
+/// let harness = HelperHarness::new(UppercaseHelper)
+///     .with_context(json!({ "title": "hello" }));
+/// assert_eq!(harness.call(&[json!("hi")]).unwrap(), json!("HI"));
+/// harness.assert_output(&[json!("hi")], "HI");
+/// assert_eq!(
+///     harness.render("<h1>{{uppercase title}}</h1>").unwrap(),
+///     "<h1>HELLO</h1>"
+/// );
+/// ```
+pub mod testing {
+    use super::*;
+
+    /// Runs a single [`TemplateHelper`] against a rendering context.
+    #[derive(Debug, Clone)]
+    pub struct HelperHarness<H> {
+        helper: H,
+        context: JsonValue,
+    }
+
+    impl<H: TemplateHelper + Clone + 'static> HelperHarness<H> {
+        /// Creates a harness for `helper` with a `null` context.
+        pub fn new(helper: H) -> Self {
+            Self {
+                helper,
+                context: JsonValue::Null,
+            }
+        }
+
+        /// Sets the rendering context the helper receives.
+        pub fn with_context(mut self, context: JsonValue) -> Self {
+            self.context = context;
+            self
+        }
+
+        /// Returns the helper under test.
+        pub fn helper(&self) -> &H {
+            &self.helper
+        }
+
+        /// Executes the helper with `params`.
+        ///
+        /// # Arguments
+        /// * `params` - The parameters, as a template would pass them.
+        ///
+        /// # Returns
+        /// * `Result<JsonValue>` - The helper's result, or its error.
+        pub fn call(&self, params: &[JsonValue]) -> Result<JsonValue> {
+            self.helper.execute(params, &self.context)
+        }
+
+        /// Executes the helper with `params` and returns the text a
+        /// template would write for its result.
+        ///
+        /// # Arguments
+        /// * `params` - The parameters, as a template would pass them.
+        ///
+        /// # Returns
+        /// * `Result<String>` - The helper's output: strings as they
+        ///   are and any other value as JSON, or the helper's error.
+        pub fn output(&self, params: &[JsonValue]) -> Result<String> {
+            self.call(params).map(helper_output)
+        }
+
+        /// Renders a template calling the helper by its name.
+        ///
+        /// # Arguments
+        /// * `template` - The template, such as `{{uppercase title}}`.
+        ///
+        /// # Returns
+        /// * `Result<String>` - The rendered text, or an error if the
+        ///   template is invalid or the helper fails.
+        pub fn render(&self, template: &str) -> Result<String> {
+            let mut engine = Handlebars::new();
+            engine.set_strict_mode(true);
+            engine.register_escape_fn(handlebars::html_escape);
+            register_helper(
+                &mut engine,
+                self.helper.name(),
+                self.helper.clone(),
+            );
+            engine.render_template(template, &self.context).map_err(
+                |e| ProcessingError::TemplateProcessing {
+                    details: format!(
+                        "Failed to render with helper '{}': {}",
+                        self.helper.name(),
+                        e
+                    ),
+                    template_name: String::new(),
+                    source: Some(Box::new(e)),
+                },
+            )
+        }
+
+        /// Asserts that the helper writes `expected` for `params`.
+        ///
+        /// # Panics
+        /// If the helper fails or writes anything else.
+        #[track_caller]
+        pub fn assert_output(
+            &self,
+            params: &[JsonValue],
+            expected: &str,
+        ) {
+            match self.output(params) {
+                Ok(output) => assert_eq!(
+                    output,
+                    expected,
+                    "helper '{}' wrote unexpected output for {:?}",
+                    self.helper.name(),
+                    params
+                ),
+                Err(e) => panic!(
+                    "helper '{}' failed for {:?}: {}",
+                    self.helper.name(),
+                    params,
+                    e
+                ),
+            }
+        }
+
+        /// Asserts that the helper fails for `params`, returning its
+        /// error for further checks.
+        ///
+        /// # Panics
+        /// If the helper succeeds.
+        #[track_caller]
+        pub fn assert_error(
+            &self,
+            params: &[JsonValue],
+        ) -> ProcessingError {
+            match self.call(params) {
+                Ok(value) => panic!(
+                    "helper '{}' returned {} for {:?}, expected an error",
+                    self.helper.name(),
+                    value,
+                    params
+                ),
+                Err(e) => e,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn load_error(template: &str) -> ProcessingError {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("page.hbs"), template)
+            .unwrap();
+        HandlebarsRenderer::new(temp_dir.path()).unwrap_err()
+    }
+
+    #[test]
+    fn test_template_error_snippets() {
+        let error = load_error("<p>\n  {{foo bar=}}\n</p>\n");
+        let snippet = error.snippet().unwrap();
+        assert!(snippet.name().ends_with("page.hbs"));
+        assert_eq!(snippet.text(), "{{foo bar=}}");
+        assert!(snippet.label().contains("expected helper_parameter"));
+
+        let error = load_error("<p>\n  {{title}}}\n</p>\n");
+        assert!(matches!(error, ProcessingError::Located { .. }));
+        assert_eq!(error.snippet().unwrap().span(), 15..16);
+        assert!(error
+            .render_diagnostic()
+            .contains("Unmatched closing brace"));
+
+        let error = ValidationError {
+            details: "Unexpected end of template".to_string(),
+            line: Some(4),
+            column: Some(1),
+            source: Some("{{#if a}}\n".to_string()),
+            span: None,
+        };
+        assert_eq!(error.snippet("page.hbs").unwrap().span(), 10..10);
+    }
+
+    #[test]
+    fn test_helper_output() {
+        use serde_json::json;
+
+        assert_eq!(helper_output(json!("a \"b\"")), "a \"b\"");
+        assert_eq!(helper_output(json!(8)), "8");
+        assert_eq!(helper_output(json!(null)), "null");
+        assert_eq!(
+            helper_output(json!({ "width": 8 })),
+            "{\"width\":8}"
+        );
+    }
+
+    #[test]
+    fn test_helper_harness() {
+        use serde_json::json;
+        use testing::HelperHarness;
+
+        let harness = HelperHarness::new(helpers::PlaceholderHelper)
+            .with_context(json!({
+                "placeholders": {
+                    "img/hero.jpg": { "data_uri": "data:a", "width": 8 }
+                }
+            }));
+        harness.assert_output(&[json!("/img/hero.jpg")], "data:a");
+        harness.assert_output(
+            &[json!("img/hero.jpg"), json!("width")],
+            "8",
+        );
+        harness.assert_output(&[json!("img/missing.jpg")], "");
+        assert!(harness
+            .assert_error(&[json!(1)])
+            .to_string()
+            .contains("requires an image path"));
+        assert_eq!(
+            harness
+                .render(
+                    "<img src=\"{{placeholder \"img/hero.jpg\"}}\">"
+                )
+                .unwrap(),
+            "<img src=\"data:a\">"
+        );
+        assert!(harness.render("{{placeholder}}").is_err());
+    }
 }
@@ -0,0 +1,216 @@
+//! # Source Watching
+//!
+//! Detects changes to a site's sources, so `nucleusflow serve --watch`
+//! can rebuild the site, and push it to a preview deploy target, as
+//! soon as a file is saved.
+//!
+//! Changes are found by polling: each poll walks the watched
+//! directories and compares every file's modification time and size
+//! with the previous poll's. Polling needs no platform support and
+//! catches edits on network shares, at the cost of noticing a change
+//! up to one poll interval late.
+//!
+//! Files excluded by the project's `.nucleusignore`, such as editor
+//! swap files, are not watched, so saving them triggers no rebuild.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use nucleusflow::watch::SourceWatcher;
+//! use std::fs;
+//!
+//! let content = tempfile::tempdir().unwrap();
+//! let mut watcher = SourceWatcher::new(&[content.path()]);
+//! assert!(watcher.poll().is_empty());
+//!
+//! fs::write(content.path().join("index.md"), "# Home").unwrap();
+//! assert_eq!(
+//!     watcher.poll(),
+//!     vec![content.path().join("index.md")]
+//! );
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use walkdir::{DirEntry, WalkDir};
+
+use crate::core::ignore::IgnoreRules;
+
+/// How often `nucleusflow serve --watch` polls for changes.
+pub const WATCH_INTERVAL_MS: u64 = 300;
+
+/// What a poll records of a file to tell whether it changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Stamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+/// Watches directories of sources for created, changed and deleted
+/// files.
+#[derive(Debug, Clone)]
+pub struct SourceWatcher {
+    roots: Vec<PathBuf>,
+    stamps: BTreeMap<PathBuf, Stamp>,
+    ignore_rules: Option<IgnoreRules>,
+}
+
+impl SourceWatcher {
+    /// Creates a watcher for directories, recording their files as
+    /// they are now.
+    ///
+    /// Directories that do not exist are watched for being created.
+    ///
+    /// # Arguments
+    /// * `roots` - The directories to watch, including their
+    ///   subdirectories.
+    pub fn new<P: AsRef<Path>>(roots: &[P]) -> Self {
+        let mut watcher = Self {
+            roots: roots
+                .iter()
+                .map(|root| root.as_ref().to_path_buf())
+                .collect(),
+            stamps: BTreeMap::new(),
+            ignore_rules: None,
+        };
+        watcher.stamps = watcher.scan();
+        watcher
+    }
+
+    /// Stops watching files matched by project ignore rules, such as
+    /// those loaded from `.nucleusignore`.
+    pub fn with_ignore_rules(mut self, rules: IgnoreRules) -> Self {
+        self.ignore_rules = Some(rules);
+        self.stamps = self.scan();
+        self
+    }
+
+    /// Returns `true` if a walked entry is excluded by the ignore rules.
+    fn is_ignored_entry(&self, entry: &DirEntry) -> bool {
+        self.ignore_rules.as_ref().map_or(false, |rules| {
+            rules.is_ignored(entry.path(), entry.file_type().is_dir())
+        })
+    }
+
+    /// Returns the files created, changed or deleted since the watcher
+    /// was created or last polled.
+    ///
+    /// # Returns
+    /// * `Vec<PathBuf>` - The changed files, ordered by path, or
+    ///   nothing if no file changed.
+    pub fn poll(&mut self) -> Vec<PathBuf> {
+        let stamps = self.scan();
+        let mut changed: Vec<PathBuf> = stamps
+            .iter()
+            .filter(|(path, stamp)| {
+                self.stamps.get(*path) != Some(*stamp)
+            })
+            .map(|(path, _)| path.clone())
+            .collect();
+        changed.extend(
+            self.stamps
+                .keys()
+                .filter(|path| !stamps.contains_key(*path))
+                .cloned(),
+        );
+        changed.sort();
+        self.stamps = stamps;
+        changed
+    }
+
+    /// Records every file under the watched directories.
+    fn scan(&self) -> BTreeMap<PathBuf, Stamp> {
+        let mut stamps = BTreeMap::new();
+        for root in &self.roots {
+            let files = WalkDir::new(root)
+                .follow_links(true)
+                .into_iter()
+                .filter_entry(|entry| {
+                    entry.depth() == 0 || !self.is_ignored_entry(entry)
+                })
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.file_type().is_file());
+            for entry in files {
+                if let Ok(metadata) = entry.metadata() {
+                    let stamp = Stamp {
+                        modified: metadata.modified().ok(),
+                        len: metadata.len(),
+                    };
+                    _ = stamps.insert(entry.into_path(), stamp);
+                }
+            }
+        }
+        stamps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, File};
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_poll_reports_changes() -> std::io::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir(root.join("blog"))?;
+        fs::write(root.join("index.md"), "# Home")?;
+        fs::write(root.join("blog/post.md"), "# Post")?;
+        let mut watcher = SourceWatcher::new(&[root]);
+        assert!(watcher.poll().is_empty());
+
+        // Move the modification time rather than waiting for the
+        // clock, so the edit is seen even on coarse filesystems.
+        let post = File::options()
+            .write(true)
+            .open(root.join("blog/post.md"))?;
+        post.set_modified(SystemTime::now() + Duration::from_secs(10))?;
+        fs::write(root.join("about.md"), "# About")?;
+        fs::remove_file(root.join("index.md"))?;
+        assert_eq!(
+            watcher.poll(),
+            vec![
+                root.join("about.md"),
+                root.join("blog/post.md"),
+                root.join("index.md"),
+            ]
+        );
+        assert!(watcher.poll().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ignored_files_are_not_watched() -> crate::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        fs::create_dir(root.join("_drafts"))?;
+        let rules =
+            IgnoreRules::from_patterns(root, &["*.swp", "_drafts/"])?;
+        let mut watcher =
+            SourceWatcher::new(&[root]).with_ignore_rules(rules);
+
+        fs::write(root.join(".index.md.swp"), "swap")?;
+        fs::write(root.join("_drafts/idea.md"), "# Idea")?;
+        assert!(watcher.poll().is_empty());
+        fs::write(root.join("index.md"), "# Home")?;
+        assert_eq!(watcher.poll(), vec![root.join("index.md")]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_root_is_watched() -> std::io::Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path().join("static");
+        let mut watcher = SourceWatcher::new(&[&root]);
+        assert!(watcher.poll().is_empty());
+
+        fs::create_dir(&root)?;
+        fs::write(root.join("app.css"), "body {}")?;
+        assert_eq!(watcher.poll(), vec![root.join("app.css")]);
+        Ok(())
+    }
+}